@@ -21,17 +21,22 @@ export default {
     // 是否为预定义
     switch (funcsStr) {
       case 'initialized': {
-        // Steamworks 初始化
-        try {
-          LocalStore.steamworks = Steamworks.init(480)
+        // Steamworks 初始化，插件日志写入 logs/steamworks.log
+        Steamworks.enableLogging()
+        const { diagnostics, api } = Steamworks.initWithDiagnostics(480)
+        LocalStore.steamworks_diagnostics = diagnostics
+        if (api) {
+          LocalStore.steamworks = api
           // Steamworks.electronEnableSteamOverlay()
           LocalStore.steamworks_error = ''
-        } catch (e) {
-          console.log('[Steamworks] 服务异常:', (<Error>e).message)
-          LocalStore.steamworks_error = (<Error>e).message
+        } else {
+          console.log('[Steamworks] 服务异常:', diagnostics)
+          LocalStore.steamworks_error = diagnostics.error ?? ''
         }
         return LocalStore.steamworks_error
       }
+      case 'diagnostics':
+        return LocalStore.steamworks_diagnostics
       case 'setAppid':
         return (LocalStore.steamworks = Steamworks.init(args[0])) != undefined
       case 'simpleQueryServer':
@@ -1,56 +1,27 @@
 import client = require('./client')
 
-export const enum ChatMemberStateChange {
-  /** This user has joined or is joining the lobby. */
-  Entered,
-  /** This user has left or is leaving the lobby. */
-  Left,
-  /** User disconnected without leaving the lobby first. */
-  Disconnected,
-  /** The user has been kicked. */
-  Kicked,
-  /** The user has been kicked and banned. */
-  Banned
-}
-
 export interface CallbackReturns {
-  [client.SteamApiCallback.PersonaStateChange]: {
-    steam_id: bigint
-    flags: { bits: number }
-  }
-  [client.SteamApiCallback.SteamServersConnected]: object
-  [client.SteamApiCallback.SteamServersDisconnected]: {
-    reason: number
-  }
-  [client.SteamApiCallback.SteamServerConnectFailure]: {
-    reason: number
-    still_retrying: boolean
-  }
-  [client.SteamApiCallback.LobbyDataUpdate]: {
-    lobby: bigint
-    member: bigint
-    success: boolean
-  }
-  [client.SteamApiCallback.LobbyChatUpdate]: {
-    lobby: bigint
-    user_changed: bigint
-    making_change: bigint
-    member_state_change: ChatMemberStateChange
-  }
-  [client.SteamApiCallback.P2PSessionRequest]: {
-    remote: bigint
-  }
-  [client.SteamApiCallback.P2PSessionConnectFail]: {
-    remote: bigint
-    error: number
-  }
-  [client.SteamApiCallback.GameLobbyJoinRequested]: {
-    lobby_steam_id: bigint
-    friend_steam_id: bigint
-  }
-  [client.SteamApiCallback.MicroTxnAuthorizationResponse]: {
-    app_id: number
-    order_id: number | bigint
-    authorized: boolean
-  }
+  [client.SteamApiCallback.PersonaStateChange]: client.PersonaStateChangePayload
+  [client.SteamApiCallback.SteamServersConnected]: client.SteamServersConnectedPayload
+  [client.SteamApiCallback.SteamServersDisconnected]: client.SteamServersDisconnectedPayload
+  [client.SteamApiCallback.SteamServerConnectFailure]: client.SteamServerConnectFailurePayload
+  [client.SteamApiCallback.LobbyDataUpdate]: client.LobbyDataUpdatePayload
+  [client.SteamApiCallback.LobbyChatUpdate]: client.LobbyChatUpdatePayload
+  [client.SteamApiCallback.GameLobbyJoinRequested]: client.GameLobbyJoinRequestedPayload
+  [client.SteamApiCallback.P2PSessionRequest]: client.P2PSessionRequestPayload
+  [client.SteamApiCallback.P2PSessionConnectFail]: client.P2PSessionConnectFailPayload
+  [client.SteamApiCallback.MicroTxnAuthorizationResponse]: client.MicroTxnAuthorizationResponsePayload
+  [client.SteamApiCallback.UserStatsReceived]: client.UserStatsReceivedPayload
+  [client.SteamApiCallback.UserStatsStored]: client.UserStatsStoredPayload
+  [client.SteamApiCallback.DownloadItemResult]: client.DownloadItemResultPayload
+  [client.SteamApiCallback.ItemInstalled]: client.ItemInstalledPayload
+  [client.SteamApiCallback.GameOverlayActivated]: client.GameOverlayActivatedPayload
+  [client.SteamApiCallback.GameRichPresenceJoinRequested]: client.GameRichPresenceJoinRequestedPayload
+  [client.SteamApiCallback.ValidateAuthTicketResponse]: client.ValidateAuthTicketResponsePayload
+  [client.SteamApiCallback.LobbyEnter]: client.LobbyEnterPayload
+  [client.SteamApiCallback.LobbyChatMsg]: client.LobbyChatMsgPayload
+  [client.SteamApiCallback.AvatarImageLoaded]: client.AvatarImageLoadedPayload
+  [client.SteamApiCallback.FloatingGamepadTextInputDismissed]: client.FloatingGamepadTextInputDismissedPayload
+  [client.SteamApiCallback.SteamShutdown]: client.SteamShutdownPayload
+  [client.SteamApiCallback.SessionChanged]: client.SessionChangedPayload
 }
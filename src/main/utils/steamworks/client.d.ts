@@ -1,5 +1,44 @@
+/** Steam 初始化诊断报告 */
+export interface InitDiagnostics {
+  /** 是否初始化成功 */
+  initialized: boolean
+  /** 初始化失败时 Steam 返回的错误信息 */
+  error?: string
+  /** Steam 客户端进程是否正在运行 */
+  steamRunning: boolean
+  /** Steam 动态库是否已加载，插件与动态库一同加载，能生成报告时总是为 true */
+  libraryLoaded: boolean
+  /** 在程序目录或工作目录中找到的 Steam 动态库路径 */
+  libraryPath?: string
+  /** 实际使用的 App ID，初始化失败且未传入时为空 */
+  appId?: number
+  /** steam_appid.txt 的路径，位于当前工作目录 */
+  appIdFilePath: string
+  /** steam_appid.txt 是否存在 */
+  appIdFileExists: boolean
+  /** steam_appid.txt 中记录的 App ID */
+  appIdFileAppId?: number
+  /** steam_appid.txt 是否可以写入（不存在时能否创建） */
+  appIdFileWritable: boolean
+  /** 当前用户是否拥有该 App，仅在初始化成功后可知 */
+  ownsApp?: boolean
+  /** 调用 restartAppIfNecessary 是否会通过 Steam 重新启动程序 */
+  restartWouldRelaunch: boolean
+}
+/**
+ * 初始化 Steam 客户端，并返回诊断报告说明初始化失败的原因
+ *
+ * 与 init 不同，初始化失败时不会抛出错误，错误信息记录在报告的 error 中
+ *
+ * @param app_id 要加载的游戏的 App ID，为空时读取 steam_appid.txt
+ * @returns 诊断报告
+ */
+export function initWithDiagnostics(appId?: number | undefined | null): InitDiagnostics
 export function init(appId?: number | undefined | null): void
 export function restartAppIfNecessary(appId: number): boolean
+/** 停止后台回调分发并释放 Steam 客户端 */
+export function shutdown(): void
+/** 手动分发一次回调，后台回调分发运行时可以不调用 */
 export function runCallbacks(): void
 /** ISteamUser 接口回调 */
 export const enum SteamApiCallback {
@@ -34,7 +73,42 @@ export const enum SteamApiCallback {
    */
   P2PSessionConnectFail = 8,
   /** 在用户响应了小额交易授权请求时调用 */
-  MicroTxnAuthorizationResponse = 9
+  MicroTxnAuthorizationResponse = 9,
+  /** 当前用户的统计与成就数据已从服务器取得 */
+  UserStatsReceived = 10,
+  /** 统计与成就已上传到服务器 */
+  UserStatsStored = 11,
+  /** 创意工坊物品下载完成（成功或失败） */
+  DownloadItemResult = 12,
+  /** 创意工坊物品已安装或更新 */
+  ItemInstalled = 13,
+  /** Steam 界面打开或关闭 */
+  GameOverlayActivated = 14,
+  /** 用户通过好友列表接受了富状态邀请或加入了好友的游戏 */
+  GameRichPresenceJoinRequested = 15,
+  /** BeginAuthSession 验证票证的结果 */
+  ValidateAuthTicketResponse = 16,
+  /** 进入大厅，CreateLobby、JoinLobby 之后触发 */
+  LobbyEnter = 17,
+  /** 大厅收到聊天消息 */
+  LobbyChatMsg = 18,
+  /** 用户头像加载完成，此时可以获取头像图片 */
+  AvatarImageLoaded = 19,
+  /** 浮动游戏手柄文本输入框已关闭 */
+  FloatingGamepadTextInputDismissed = 20,
+  /** Steam 客户端即将关闭，应尽快保存数据并退出 */
+  SteamShutdown = 21,
+  /** 插件事件：Steam 会话已切换（重新初始化或切换了 AppId），已注册的回调会自动迁移到新的会话 */
+  SessionChanged = 22
+}
+/** 事件流缓冲区已满时的处理方式 */
+export const enum OverflowPolicy {
+  /** 丢弃最早的事件 */
+  DropOldest = 0,
+  /** 丢弃新到达的事件 */
+  DropNewest = 1,
+  /** 阻塞回调分发线程，直到事件被取走或事件流关闭 */
+  Block = 2
 }
 /** 用来枚举好友列表或快速检查用户之间关系的标识 */
 export const enum EFriendFlags {
@@ -172,6 +246,40 @@ export const enum ELobbyType {
    */
   Invisible = 3
 }
+/** 大厅成员状态的变化，通过 LobbyChatUpdate 回调发送 */
+export const enum EChatMemberStateChange {
+  /** 该用户已经或正在加入大厅 */
+  Entered = 0,
+  /** 该用户已经或正在离开大厅 */
+  Left = 1,
+  /** 用户未先离开大厅就断开了连接 */
+  Disconnected = 2,
+  /** 用户已被踢出 */
+  Kicked = 3,
+  /** 用户已被踢出并封禁 */
+  Banned = 4
+}
+/** BeginAuthSession 验证票证失败的原因，通过 ValidateAuthTicketResponse 回调发送 */
+export const enum EAuthSessionResponse {
+  /** 用户未连接到 Steam */
+  UserNotConnectedToSteam = 0,
+  /** 用户没有许可证或许可证已过期 */
+  NoLicenseOrExpired = 1,
+  /** 用户被此游戏的 VAC 封禁 */
+  VACBanned = 2,
+  /** 用户帐户已在其他位置登录，且会话连接已关闭 */
+  LoggedInElseWhere = 3,
+  /** VAC 无法对该用户进行反作弊检查 */
+  VACCheckTimedOut = 4,
+  /** 票证已被发行者取消 */
+  TicketCancelled = 5,
+  /** 票证已被使用，无效 */
+  TicketAlreadyUsed = 6,
+  /** 该票证并非来自连接至 Steam 的用户实例 */
+  TicketInvalid = 7,
+  /** 用户被此游戏封禁，由发行者设置封禁而不是 VAC */
+  PublisherIssuedBan = 8
+}
 /** 需要打开的对话框类型 */
 export const enum PSteamDialog {
   /** 好友对话框 */
@@ -198,6 +306,388 @@ export interface CSteamId {
   /** 账户ID */
   accountId: number
 }
+/** Steam 错误，与 `[STEAM_ERROR:<eresult>:<name>]` 错误信息中的内容一致 */
+export interface SteamErrorInfo {
+  /** EResult 数值 */
+  eresult: number
+  /** EResult 名称 */
+  name: string
+}
+/** 好友的状态改变 */
+export interface PersonaStateChangePayload {
+  /** 状态改变的用户 */
+  steamId: CSteamId
+  /** 改变的内容，EPersonaChange 标识的组合 */
+  flags: number
+  /** 用户当前的状态 */
+  state: EPersonaState
+}
+/** 已连接到 Steam 服务器 */
+export interface SteamServersConnectedPayload {}
+/** 与 Steam 服务器的连接已断开 */
+export interface SteamServersDisconnectedPayload {
+  /** 断开的原因 */
+  reason: SteamErrorInfo
+}
+/** 连接 Steam 服务器失败 */
+export interface SteamServerConnectFailurePayload {
+  /** 失败的原因 */
+  reason: SteamErrorInfo
+  /** Steam 客户端是否仍在尝试连接 */
+  stillRetrying: boolean
+}
+/** 大厅或大厅成员的元数据已变更 */
+export interface LobbyDataUpdatePayload {
+  /** 大厅的 Steam ID */
+  lobby: bigint
+  /** 元数据变更的成员，大厅自身的元数据变更时与大厅相同 */
+  member: bigint
+  /** 是否成功 */
+  success: boolean
+}
+/** 大厅成员发生变化 */
+export interface LobbyChatUpdatePayload {
+  /** 大厅的 Steam ID */
+  lobby: bigint
+  /** 状态发生变化的用户 */
+  userChanged: CSteamId
+  /** 造成变化的用户，例如踢出成员的管理员 */
+  makingChange: CSteamId
+  /** 成员状态的变化 */
+  memberStateChange: EChatMemberStateChange
+}
+/** 用户请求加入大厅 */
+export interface GameLobbyJoinRequestedPayload {
+  /** 要加入的大厅的 Steam ID */
+  lobbySteamId: bigint
+  /** 发出邀请或正在该大厅中的好友 */
+  friendSteamId: CSteamId
+}
+/** 用户请求建立 P2P 会话 */
+export interface P2PSessionRequestPayload {
+  /** 请求建立会话的用户 */
+  remote: CSteamId
+}
+/** P2P 会话连接失败 */
+export interface P2PSessionConnectFailPayload {
+  /** 连接失败的用户 */
+  remote: CSteamId
+  /** EP2PSessionError */
+  error: number
+}
+/** 用户响应了小额交易授权请求 */
+export interface MicroTxnAuthorizationResponsePayload {
+  /** App ID */
+  appId: number
+  /** 订单 ID */
+  orderId: bigint
+  /** 用户是否授权了交易 */
+  authorized: boolean
+}
+/** 用户的统计与成就数据已取得 */
+export interface UserStatsReceivedPayload {
+  /** 数据所属的用户 */
+  steamId: CSteamId
+  /** 数据所属的游戏 */
+  gameId: bigint
+  /** 获取失败时的错误，成功时为空 */
+  error?: SteamErrorInfo
+}
+/** 统计与成就已上传 */
+export interface UserStatsStoredPayload {
+  /** 数据所属的游戏 */
+  gameId: bigint
+  /** 上传失败时的错误，成功时为空 */
+  error?: SteamErrorInfo
+}
+/** 创意工坊物品下载完成 */
+export interface DownloadItemResultPayload {
+  /** 物品所属的 App ID */
+  appId: number
+  /** 物品ID */
+  publishedFileId: bigint
+  /** 下载失败时的错误，成功时为空 */
+  error?: SteamErrorInfo
+}
+/** 创意工坊物品已安装或更新 */
+export interface ItemInstalledPayload {
+  /** 物品所属的 App ID */
+  appId: number
+  /** 物品ID */
+  publishedFileId: bigint
+}
+/** Steam 界面打开或关闭 */
+export interface GameOverlayActivatedPayload {
+  /** 界面是否打开 */
+  active: boolean
+}
+/** 用户接受了富状态邀请 */
+export interface GameRichPresenceJoinRequestedPayload {
+  /** 发出邀请的好友 */
+  friendSteamId: CSteamId
+  /** 好友富状态中的 connect 字符串 */
+  connect: string
+}
+/** 验证票证的结果 */
+export interface ValidateAuthTicketResponsePayload {
+  /** 票证所属的用户 */
+  steamId: CSteamId
+  /** 验证失败的原因，成功时为空 */
+  error?: EAuthSessionResponse
+  /** 游戏的所有者，通过家庭共享游玩时与票证所属的用户不同 */
+  ownerSteamId: CSteamId
+}
+/** 进入大厅 */
+export interface LobbyEnterPayload {
+  /** 大厅的 Steam ID */
+  lobby: bigint
+  /** 聊天权限，目前未使用 */
+  chatPermissions: number
+  /** 大厅是否锁定，只有受邀者可以加入 */
+  locked: boolean
+  /** EChatRoomEnterResponse，1 表示成功 */
+  chatRoomEnterResponse: number
+}
+/** 大厅收到聊天消息 */
+export interface LobbyChatMsgPayload {
+  /** 大厅的 Steam ID */
+  lobby: bigint
+  /** 发送消息的用户 */
+  user: CSteamId
+  /** EChatEntryType，1 表示普通聊天消息 */
+  chatEntryType: number
+  /** 消息编号 */
+  chatId: number
+  /** 消息内容 */
+  message: string
+}
+/** 用户头像加载完成 */
+export interface AvatarImageLoadedPayload {
+  /** 头像所属的用户 */
+  steamId: CSteamId
+  /** 头像图片句柄 */
+  image: number
+  /** 图片宽度 */
+  width: number
+  /** 图片高度 */
+  height: number
+}
+/** 浮动游戏手柄文本输入框已关闭 */
+export interface FloatingGamepadTextInputDismissedPayload {}
+/** Steam 客户端即将关闭 */
+export interface SteamShutdownPayload {}
+/** Steam 会话已切换 */
+export interface SessionChangedPayload {
+  /** 新会话编号 */
+  session: number
+  /** 新会话的 App ID */
+  appId: number
+  /** 切换前的 App ID，首次初始化时为空 */
+  previousAppId?: number
+}
+/** 服务器类型 */
+export const enum EServerType {
+  /** 专用服务器 */
+  Dedicated = 0,
+  /** 非专用服务器 */
+  NonDedicated = 1,
+  /** SourceTV 中继 */
+  SourceTv = 2,
+  Unknown = 3
+}
+/** 服务器操作系统 */
+export const enum EServerEnvironment {
+  Linux = 0,
+  Windows = 1,
+  Mac = 2,
+  Unknown = 3
+}
+/** Source 服务器查询（A2S），直接通过 UDP 查询，不需要初始化 Steam */
+export namespace a2s {
+  /** 查询选项 */
+  export interface QueryOptions {
+    /** 等待回复的超时时间（毫秒），包括 challenge 握手，默认 3000 */
+    timeoutMs?: number
+  }
+  /** The Ship 服务器信息 */
+  export interface A2sShipInfo {
+    /** 游戏模式：0 Hunt、1 Elimination、2 Duel、3 Deathmatch、4 VIP Team、5 Team Elimination */
+    mode: number
+    /** 需要的目击者数量 */
+    witnesses: number
+    /** 被目击后的时长（秒） */
+    duration: number
+  }
+  /** GoldSrc 模组信息 */
+  export interface A2sGoldSrcMod {
+    /** 模组网站 */
+    link: string
+    /** 模组下载地址 */
+    downloadLink: string
+    version: number
+    /** 模组大小（字节） */
+    size: number
+    /** 是否只支持多人游戏 */
+    multiplayerOnly: boolean
+    /** 是否使用自己的 DLL 而不是 Half-Life 的 DLL */
+    customDll: boolean
+  }
+  /** A2S_INFO 服务器信息 */
+  export interface A2sServerInfo {
+    /** 查询的地址 */
+    address: string
+    /** 延迟（毫秒），从发出最后一次请求到收到完整回复 */
+    latency: number
+    /** 协议版本 */
+    protocol: number
+    /** 服务器名称 */
+    name: string
+    /** 当前地图 */
+    map: string
+    /** 游戏目录，如 `cstrike` */
+    folder: string
+    /** 游戏名称 */
+    game: string
+    /** AppId，超过 16 位的 AppId 请使用 gameId */
+    appId: number
+    /** 玩家数量（包括机器人） */
+    players: number
+    /** 最大玩家数量 */
+    maxPlayers: number
+    /** 机器人数量 */
+    bots: number
+    serverType: EServerType
+    environment: EServerEnvironment
+    /** 是否需要密码 */
+    password: boolean
+    /** 是否启用 VAC */
+    vac: boolean
+    /** 游戏版本 */
+    version: string
+    /** 游戏端口 */
+    port?: number
+    /** 服务器 Steam ID */
+    steamId?: bigint
+    /** SourceTV 端口 */
+    tvPort?: number
+    /** SourceTV 名称 */
+    tvName?: string
+    /** 服务器标签 */
+    keywords?: string
+    /** 64 位游戏 ID，低 24 位为完整的 AppId */
+    gameId?: bigint
+    /** The Ship 的额外信息 */
+    ship?: A2sShipInfo
+    /** 是否为 GoldSrc 旧格式回复，旧格式没有版本号与 Steam ID，AppId 根据游戏目录推断，未知时为 0 */
+    goldsrc: boolean
+    /** GoldSrc 模组信息，仅旧格式回复 */
+    goldsrcMod?: A2sGoldSrcMod
+  }
+  /** A2S_PLAYER 玩家信息 */
+  export interface A2sPlayer {
+    /** 玩家序号，大多数游戏总是 0 */
+    index: number
+    name: string
+    score: number
+    /** 在服务器中的时长（秒） */
+    duration: number
+    /** 死亡数，仅 The Ship */
+    deaths?: number
+    /** 金钱，仅 The Ship */
+    money?: number
+  }
+  /** A2S_RULES 服务器规则 */
+  export interface A2sRule {
+    /** 控制台变量名 */
+    name: string
+    value: string
+  }
+  /**
+   * 查询服务器信息（A2S_INFO）
+   *
+   * @param address 服务器地址，`ip:port` 或 `域名:port`，未指定端口时使用 27015
+   * @param options 查询选项
+   * @returns 服务器信息
+   */
+  export function queryInfo(address: string, options?: QueryOptions | undefined | null): Promise<A2sServerInfo>
+  /**
+   * 查询服务器中的玩家（A2S_PLAYER）
+   *
+   * @param address 服务器地址
+   * @param options 查询选项
+   * @returns 玩家列表
+   */
+  export function queryPlayers(address: string, options?: QueryOptions | undefined | null): Promise<Array<A2sPlayer>>
+  /**
+   * 查询服务器规则（A2S_RULES）
+   *
+   * @param address 服务器地址
+   * @param options 查询选项
+   * @returns 规则列表
+   */
+  export function queryRules(address: string, options?: QueryOptions | undefined | null): Promise<Array<A2sRule>>
+  /** 批量查询选项 */
+  export interface BatchQueryOptions {
+    /** 同时进行的查询数量，默认 32 */
+    concurrency?: number
+    /** 单次查询的超时时间（毫秒），默认 3000 */
+    timeoutMs?: number
+    /** 超时或网络错误后的重试次数，默认 1 */
+    retries?: number
+  }
+  /** 批量查询中一个服务器的结果 */
+  export interface PingResult {
+    /** 传入的地址 */
+    address: string
+    /** 服务器信息，查询失败时为空 */
+    info?: A2sServerInfo
+    /** 查询失败的原因，格式与其他接口的错误信息相同，如 `[TIMEOUT] a2s_info 1.2.3.4:27015: no response` */
+    error?: string
+    /** 实际发出的查询次数 */
+    attempts: number
+  }
+  /** 批量查询汇总 */
+  export interface PingSummary {
+    /** 查询的服务器数量 */
+    total: number
+    /** 有回复的服务器数量 */
+    answered: number
+    /** 查询失败的服务器数量 */
+    failed: number
+    /** 总耗时（毫秒） */
+    elapsedMs: number
+  }
+  /**
+   * 并发查询多个服务器的信息（A2S_INFO）
+   *
+   * 每个服务器回复或最终失败时立即调用 onResult，调用顺序为完成顺序。
+   * 所有服务器完成后返回的 Promise 才会完成。
+   *
+   * @param addresses 服务器地址列表
+   * @param options 批量查询选项
+   * @param on_result 每个服务器的查询结果
+   * @returns 查询汇总
+   */
+  export function pingServers(addresses: Array<string>, options: BatchQueryOptions | undefined | null, onResult: (result: PingResult) => void): Promise<PingSummary>
+  /** 局域网服务器发现选项 */
+  export interface LanDiscoveryOptions {
+    /** 广播地址，默认 `255.255.255.255`，也可以是子网广播地址或单个主机 */
+    broadcastAddress?: string
+    /** 广播的起始端口，默认 27015 */
+    portStart?: number
+    /** 广播的结束端口（包含），默认 27020，不能小于起始端口 */
+    portEnd?: number
+    /** 等待回复的时间（毫秒），默认 1500 */
+    durationMs?: number
+  }
+  /**
+   * 在局域网中发现服务器：向子网广播 A2S_INFO，等待一段时间后返回收到的所有回复
+   *
+   * @param options 发现选项
+   * @returns 去重后的服务器，按延迟排序，广播地址或端口范围无效时抛出 INVALID_ARGUMENT
+   */
+  export function discoverLan(options?: LanDiscoveryOptions | undefined | null): Promise<Array<A2sServerInfo>>
+}
 export namespace callback {
   /**
    * 注册该用户操作回调
@@ -212,6 +702,45 @@ export namespace callback {
   export class Handle {
     disconnect(): void
   }
+  /** 事件流选项 */
+  export interface EventStreamOptions {
+    /** 缓冲区最多保存的事件数量，默认 256 */
+    capacity?: number
+    /** 缓冲区已满时的处理方式，默认丢弃最早的事件 */
+    overflow?: OverflowPolicy
+  }
+  /** 事件流迭代结果，与 JS 异步迭代器协议一致 */
+  export interface EventStreamResult<T = any> {
+    done: boolean
+    value?: T
+  }
+  /** 回调事件流，可以用 for await 逐个读取事件 */
+  export class EventStream<T = any> implements AsyncIterableIterator<T> {
+    /** 读取下一个事件，没有事件时等待；事件流关闭后 done 为 true */
+    next(): Promise<IteratorResult<T, undefined>>
+    /** 关闭事件流，for await 循环提前退出时自动调用 */
+    return(): IteratorResult<T, undefined>
+    /** 关闭事件流，注销回调并丢弃缓冲区中的事件，等待中的 next 立即结束 */
+    close(): void
+    /** 因缓冲区已满被丢弃的事件数量 */
+    get dropped(): number
+    /** 缓冲区中尚未读取的事件数量 */
+    get pending(): number
+    /** 事件流是否已关闭 */
+    get closed(): boolean
+    [Symbol.asyncIterator](): EventStream<T>
+  }
+  /**
+   * 以事件流的方式订阅回调，事件写入有界缓冲区，不会因 JS 处理缓慢而阻塞回调分发线程
+   *
+   * @param steam_callback Steam用户操作回调
+   * @param options 缓冲区大小与溢出处理方式
+   * @returns 事件流
+   */
+  export function streamCallback<C extends keyof import('./callbacks').CallbackReturns>(
+    steamCallback: C,
+    options?: EventStreamOptions | undefined | null
+  ): EventStream<import('./callbacks').CallbackReturns[C]>
 }
 export namespace steamapps {
   /**
@@ -357,7 +886,7 @@ export namespace steamfriends {
    * @param steam_id64 另一位用户的 Steam ID
    * @returns true 表示用户为好友且在游戏中, 否则返回 false
    */
-  export function getFriendGamePlayed(steamId64: bigint): FriendGameInfo | null
+  export function getFriendGamePlayed(steamId64: bigint | string): FriendGameInfo | null
   /**
    * 获取好友所有信息
    *
@@ -391,14 +920,14 @@ export namespace steamfriends {
    * @param steam_id64 要将此对话框打开至的上下文的 Steam ID。
    * @noreturns
    */
-  export function activateGameOverlayToUser(dialog: PSteamDialog, steamId64: bigint): void
+  export function activateGameOverlayToUser(dialog: PSteamDialog, steamId64: bigint | string): void
   /**
    * 激活 Steam 界面，打开邀请对话框。 进入该大厅的邀请将从此窗口发出。
    *
    * @param lobby_id 选定玩家将受邀进入的大厅的 Steam ID
    * @noreturns
    */
-  export function activateGameOverlayInviteDialog(lobbyId: bigint): void
+  export function activateGameOverlayInviteDialog(lobbyId: bigint | string): void
   /**
    * 激活 Steam 界面网页浏览器，直接前往指定的 URL。
    *
@@ -464,22 +993,31 @@ export namespace steaminput {
      * @param action_set_handle 您要激活的操作集的句柄
      * @noreturns
      */
-    activateActionSet(actionSetHandle: bigint): void
+    activateActionSet(actionSetHandle: bigint | string): void
     /**
      * 返回所提供的数字游戏操作是否当前被按下
      *
      * @returns 此操作的当前状态；若此操作当前被按下，为 true，反之则为 false。
      */
-    getDigitalActionData(actionHandle: bigint): boolean
+    getDigitalActionData(actionHandle: bigint | string): boolean
     /**
      * 返回所提供的模拟游戏操作的当前状态
      *
      * @param action_handle
      * @return 返回当前模拟游戏所在坐标
      */
-    getAnalogActionVector(actionHandle: bigint): AnalogActionVector
+    getAnalogActionVector(actionHandle: bigint | string): AnalogActionVector
   }
 }
+/** 收藏服务器的列表标识，对应 k_unFavoriteFlag* */
+export const enum EFavoriteFlags {
+  /** 无 */
+  None = 0,
+  /** 收藏夹 */
+  Favorite = 1,
+  /** 历史记录 */
+  History = 2
+}
 export namespace steammatchmaking {
   /**
    * 创建一个新的匹配大厅。
@@ -501,7 +1039,7 @@ export namespace steammatchmaking {
    * @returns SteamAPICall_t，与LobbyEnter_t 调用结果一起使用。
    * - 触发一个 LobbyDataUpdate_t 回调。
    */
-  export function joinLobby(lobbyId: bigint): Promise<Lobby>
+  export function joinLobby(lobbyId: bigint | string): Promise<Lobby>
   /**
    * 获得经筛选的相关大厅列表。
    * 每次只能有一个活跃的大厅搜索。 如果新请求被启动，旧请求将被取消。 视用户与 Steam 后端的连接状况而定，此调用可能需要 300 毫秒至 5 秒完成，20 秒则超时。
@@ -613,6 +1151,172 @@ export namespace steammatchmaking {
      */
     mergeFullData(data: Record<string, string>): boolean
   }
+  /** Steam 收藏夹或历史记录中的服务器，同一服务器在收藏夹与历史记录中是两条记录 */
+  export interface FavoriteGame {
+    /** 服务器所属游戏的 AppId */
+    appId: number
+    /** 服务器的 IPv4 地址 */
+    ip: string
+    /** 游戏端口 */
+    connectionPort: number
+    /** 查询端口，通常与游戏端口相同 */
+    queryPort: number
+    /** EFavoriteFlags 的组合，收藏夹为 1，历史记录为 2 */
+    flags: number
+    /** 最后一次在该服务器上游戏的 Unix 时间（秒），添加时为空则使用 Steam 服务器时间 */
+    lastPlayed?: number
+  }
+  /**
+   * 获取用户收藏夹与历史记录中的所有服务器，与 Steam 服务器浏览器中的列表一致
+   *
+   * @returns 服务器列表，按 GetFavoriteGame 的序号排列
+   */
+  export function getFavoriteGames(): Array<FavoriteGame>
+  /**
+   * 将服务器添加到用户的收藏夹或历史记录，已存在时只更新最后游戏时间
+   *
+   * @param game 要添加的服务器，flags 决定添加到哪个列表
+   * @returns 服务器在列表中的序号，Steam 拒绝添加时抛出 STEAM_ERROR
+   */
+  export function addFavoriteGame(game: FavoriteGame): number
+  /**
+   * 将服务器从用户的收藏夹或历史记录中删除
+   *
+   * @param game 要删除的服务器，AppId、地址、端口与 flags 都需要匹配，忽略 lastPlayed
+   * @returns 是否找到并删除了服务器
+   */
+  export function removeFavoriteGame(game: FavoriteGame): boolean
+}
+/** 服务器列表类型 */
+export const enum EServerListType {
+  /** 主服务器上的互联网服务器 */
+  Internet = 0,
+  /** 局域网服务器，不支持筛选 */
+  Lan = 1,
+  /** 收藏的服务器 */
+  Favorites = 2,
+  /** 最近玩过的服务器 */
+  History = 3,
+  /** 好友正在玩的服务器 */
+  Friends = 4,
+  /** 可观战的服务器 */
+  Spectator = 5
+}
+/** 服务器列表刷新结束的原因 */
+export const enum EMatchMakingServerResponse {
+  /** 至少有一个服务器回复 */
+  ServerResponded = 0,
+  /** 没有服务器回复 */
+  ServerFailedToRespond = 1,
+  /** 主服务器上没有符合条件的服务器 */
+  NoServersListedOnMasterServer = 2
+}
+/**
+ * 通过 Steam 服务器浏览器获取服务器列表与服务器详情。
+ * https://partner.steamgames.com/doc/api/ISteamMatchmakingServers
+ */
+export namespace steammatchmakingservers {
+  /** 服务器列表筛选条件，如 `gamedir`、`map`、`secure`、`notfull`、`gametagsand` */
+  export interface ServerFilter {
+    key: string
+    value: string
+  }
+  /** 服务器列表请求选项 */
+  export interface ServerListOptions {
+    /** 要查询的游戏 App ID，默认为当前 App ID */
+    appId?: number
+    /** 筛选条件，局域网列表会忽略 */
+    filters?: Array<ServerFilter>
+  }
+  /** Steam 服务器浏览器中的服务器 */
+  export interface GameServer {
+    /** 查询地址，`ip:查询端口` */
+    address: string
+    ip: string
+    queryPort: number
+    /** 游戏连接端口 */
+    connectionPort: number
+    /** 延迟（毫秒） */
+    ping: number
+    /** 服务器是否回复过查询 */
+    hadSuccessfulResponse: boolean
+    name: string
+    map: string
+    gameDir: string
+    gameDescription: string
+    appId: number
+    players: number
+    maxPlayers: number
+    botPlayers: number
+    /** 是否需要密码 */
+    password: boolean
+    /** 是否启用 VAC */
+    secure: boolean
+    /** 上次在该服务器游戏的时间（Unix 时间戳），仅收藏与历史列表 */
+    timeLastPlayed: number
+    serverVersion: number
+    /** 服务器标签 */
+    tags: Array<string>
+    /** 服务器 Steam ID，未知时为空 */
+    steamId?: bigint
+  }
+  /** 服务器列表刷新结果 */
+  export interface ServerListSummary {
+    response: EMatchMakingServerResponse
+    /** 回复了查询的服务器数 */
+    responded: number
+    /** 没有回复的服务器数 */
+    failed: number
+  }
+  /** 服务器中的玩家 */
+  export interface ServerPlayerInfo {
+    name: string
+    score: number
+    /** 在服务器中的时长（秒） */
+    timePlayed: number
+  }
+  /** 服务器规则（控制台变量） */
+  export interface ServerRule {
+    name: string
+    value: string
+  }
+  /**
+   * 请求服务器列表，服务器陆续回复时逐个返回。
+   * 同一时间可以进行多个请求，请求在取消或句柄被回收时释放。
+   *
+   * @param list_type 服务器列表类型
+   * @param options 请求选项
+   * @param on_server 每个回复了查询的服务器
+   * @param on_complete 列表刷新结束时调用
+   * @returns 请求句柄，可以随时取消
+   */
+  export function requestServerList(listType: EServerListType, options: ServerListOptions | undefined | null, onServer: (server: GameServer) => void, onComplete?: ((summary: ServerListSummary) => void) | undefined | null): ServerListRequest
+  /**
+   * 通过 Steam 查询单个服务器的信息
+   *
+   * @param address 服务器地址，`ip:查询端口`，未指定端口时使用 27015，IPv6 地址抛出 INVALID_ARGUMENT
+   * @returns 服务器信息，服务器没有回复时返回超时错误
+   */
+  export function pingServer(address: string): Promise<GameServer>
+  /**
+   * 通过 Steam 查询服务器中的玩家
+   *
+   * @param address 服务器地址，`ip:查询端口`，未指定端口时使用 27015，IPv6 地址抛出 INVALID_ARGUMENT
+   * @returns 玩家列表
+   */
+  export function getServerPlayers(address: string): Promise<Array<ServerPlayerInfo>>
+  /**
+   * 通过 Steam 查询服务器规则（控制台变量）
+   *
+   * @param address 服务器地址，`ip:查询端口`，未指定端口时使用 27015，IPv6 地址抛出 INVALID_ARGUMENT
+   * @returns 规则列表，按服务器返回的顺序排列
+   */
+  export function getServerRules(address: string): Promise<Array<ServerRule>>
+  /** 进行中的服务器列表请求 */
+  export class ServerListRequest {
+    /** 取消请求，之后不会再收到任何服务器 */
+    cancel(): void
+  }
 }
 export namespace steamnetworking {
   /** P2P数据包信息 */
@@ -634,7 +1338,7 @@ export namespace steamnetworking {
    * @param stema_id64 要向其发送数据包的目标用户
    * @param send_type 指定您希望数据传输的方式，如可靠、不可靠、缓冲，等等。
    */
-  export function sendP2PPacket(steamId64: bigint, sendType: EP2PSend, data: Buffer): boolean
+  export function sendP2PPacket(steamId64: bigint | string, sendType: EP2PSend, data: Buffer): boolean
   /**
    * 检查是否有 P2P 数据包可读，如有，获取消息大小。
    * 应在您使用的每个通道中循环调用。 如有数据包可用，您应调用 ReadP2PPacket 获取数据包数据。
@@ -664,7 +1368,790 @@ export namespace steamnetworking {
    * @param steam_id64 向我们发送了初始数据包的用户的 Steam ID
    * @returns true， 表示成功；只有在 steamIDRemote 无效时为 false。
    */
-  export function acceptP2PsessionWithUser(steamId64: bigint): void
+  export function acceptP2PsessionWithUser(steamId64: bigint | string): void
+}
+/** 控制台事件类型 */
+export const enum EConsoleEvent {
+  /** 连接到服务器 */
+  Connected = 0,
+  /** 连接后服务器信息中的地图 */
+  Map = 1,
+  /** 击杀信息 */
+  Kill = 2,
+  /** 自杀 */
+  Suicide = 3,
+  /** 聊天 */
+  Chat = 4,
+  /** 本回合对其他玩家造成的伤害，CS:GO 在死亡或回合结束时打印 */
+  DamageGiven = 5,
+  /** 本回合受到其他玩家的伤害 */
+  DamageTaken = 6,
+  /** 无法识别的行 */
+  Other = 7
+}
+/** 跟踪以 `-condebug` 启动的游戏输出的 `console.log`，不需要初始化 Steam */
+export namespace condebug {
+  /** 控制台的一行，只有与事件类型相关的字段有值 */
+  export interface ConsoleEvent {
+    kind: EConsoleEvent
+    /** 本地时间，格式为 `2026-10-18T12:34:56`，只有 TF2 的行有 */
+    time?: string
+    /** 去掉时间前缀后的完整内容 */
+    message: string
+    /** 服务器地址，仅 Connected */
+    server?: string
+    /** 地图，仅 Map */
+    map?: string
+    /** 击杀者，仅 Kill */
+    killer?: string
+    /** 被击杀的玩家，仅 Kill */
+    victim?: string
+    /** 武器，如 `scattergun`，仅 Kill */
+    weapon?: string
+    /** 是否暴击，仅 TF2 的 Kill */
+    critical?: boolean
+    /** 自杀、发言或伤害统计中的另一方玩家 */
+    player?: string
+    /** 聊天内容，仅 Chat */
+    text?: string
+    /** 是否为队伍聊天，仅 Chat */
+    teamOnly?: boolean
+    /** 发言的玩家是否已经死亡或在观战，仅 Chat */
+    dead?: boolean
+    /** CS:GO 队伍聊天中发言玩家的位置，仅 Chat */
+    location?: string
+    /** 伤害，DamageGiven 与 DamageTaken */
+    damage?: number
+    /** 命中次数，DamageGiven 与 DamageTaken */
+    hits?: number
+  }
+  /** 跟踪选项 */
+  export interface ConsoleTailOptions {
+    /** 从文件开头读取，默认只读取开始跟踪后新增的行 */
+    fromStart?: boolean
+    /** 检查文件的间隔（毫秒），默认 250 */
+    pollIntervalMs?: number
+  }
+  /**
+   * 跟踪 `console.log`，文件被清空或删除后重新创建时从头读取
+   *
+   * @param path 文件路径，如 `steamapps/common/Team Fortress 2/tf/console.log`，文件可以还不存在
+   * @param options 跟踪选项
+   * @param on_event 每读到一行时调用
+   * @returns 跟踪器
+   */
+  export function tail(path: string, options: ConsoleTailOptions | undefined | null, onEvent: (event: ConsoleEvent) => void): ConsoleTailer
+  /**
+   * 解析一行控制台输出
+   *
+   * @param line 控制台输出的一行，如 `alice killed bob with scattergun.`
+   * @returns 解析后的事件，无法识别时类型为 Other
+   */
+  export function parseLine(line: string): ConsoleEvent
+  /** 控制台日志跟踪器，停止或被回收时停止读取 */
+  export class ConsoleTailer {
+    /** 跟踪的文件 */
+    path: string
+    /**
+     * 停止跟踪
+     *
+     * @noreturns
+     */
+    stop(): void
+  }
+}
+/** 录像事件类型 */
+export const enum EDemoEvent {
+  /** 玩家连接 */
+  PlayerConnected = 0,
+  /** 玩家断开连接 */
+  PlayerDisconnected = 1,
+  /** 击杀 */
+  Kill = 2,
+  /** 回合结束 */
+  RoundEnded = 3
+}
+/** 读取 Source 1 录像（.dem），不需要初始化 Steam */
+export namespace demo {
+  /** 录像文件头 */
+  export interface DemoHeader {
+    /** 录像格式版本，CS:GO、L4D 为 4，其他游戏为 3 */
+    demoProtocol: number
+    /** 录制时的网络协议版本 */
+    networkProtocol: number
+    /** 服务器名称，SourceTV 录像为服务器的地址 */
+    serverName: string
+    /** 录制者的名称，SourceTV 录像为 `tv_name` */
+    clientName: string
+    mapName: string
+    /** 游戏目录，如 `csgo`、`cstrike`、`tf` */
+    gameDirectory: string
+    /** 时长（秒），录制中断时为 0 */
+    playbackTime: number
+    /** tick 数，录制中断时为 0 */
+    ticks: number
+    /** 帧数，录制中断时为 0 */
+    frames: number
+  }
+  /** 录像中的玩家 */
+  export interface DemoPlayer {
+    /** 服务器分配的用户 ID */
+    userId: number
+    name: string
+    /** `STEAM_1:0:123` 或 `BOT`，只从事件中得知用户 ID 的玩家为空字符串 */
+    steamId: string
+    /** 机器人与未知的玩家为空 */
+    steamId64?: bigint
+    bot: boolean
+  }
+  /** 录像中的事件，只有与事件类型相关的字段有值 */
+  export interface DemoEvent {
+    kind: EDemoEvent
+    tick: number
+    /** 连接或断开连接的玩家 */
+    player?: DemoPlayer
+    /** 击杀者，被世界击杀或自杀时为空，仅 Kill */
+    attacker?: DemoPlayer
+    /** 被击杀的玩家，仅 Kill */
+    victim?: DemoPlayer
+    /** 助攻的玩家，仅 Kill */
+    assister?: DemoPlayer
+    /** 武器，如 `ak47`，仅 Kill */
+    weapon?: string
+    headshot?: boolean
+    /** 是否穿墙击杀，仅 Kill */
+    penetrated?: boolean
+    /** 断开连接的原因，仅 PlayerDisconnected */
+    reason?: string
+    /** 获胜的队伍，2 为 T，3 为 CT，仅 RoundEnded */
+    winner?: number
+    /** 回合结束原因，见游戏的 `CSRoundEndReason`，仅 RoundEnded */
+    endReason?: number
+    /** 回合结束消息，如 `#SFUI_Notice_CTs_Win`，仅 RoundEnded */
+    message?: string
+  }
+  /** 一回合的结果 */
+  export interface DemoRound {
+    /** 回合结束的 tick */
+    tick: number
+    /** 获胜的队伍，2 为 T，3 为 CT */
+    winner: number
+    endReason: number
+    message: string
+  }
+  /** 录像汇总 */
+  export interface DemoSummary {
+    header: DemoHeader
+    /** 最后一帧的 tick */
+    lastTick: number
+    /** 时长（秒），文件头中没有时按 tick 估算，无法估算时为空 */
+    duration?: number
+    /** 每个 tick 的时长（秒），只有 CS:GO 录像有 */
+    tickInterval?: number
+    /** 文件是否不完整，录制中断的录像没有结束标记 */
+    truncated: boolean
+    /** 是否解析了游戏事件，只有 CS:GO 录像会解析，其他游戏的玩家、击杀与回合为空 */
+    eventsParsed: boolean
+    /** 录像中出现过的玩家，按第一次出现的顺序排列 */
+    players: Array<DemoPlayer>
+    kills: number
+    rounds: Array<DemoRound>
+  }
+  /**
+   * 只读取录像的文件头
+   *
+   * @param path 录像路径
+   * @returns 文件头，不是 Source 1 录像时抛出 PARSE_ERROR
+   */
+  export function readDemoHeader(path: string): Promise<DemoHeader>
+  /** 目录中的一个录像 */
+  export interface DemoFile {
+    path: string
+    /** 文件大小（字节） */
+    size: number
+    /** 修改时间，Unix 时间（秒） */
+    modified?: number
+    header: DemoHeader
+  }
+  /**
+   * 列出目录中的所有录像并读取文件头，不会进入子目录，无法读取的文件会被跳过并记录日志
+   *
+   * @param dir 目录，如服务器的 `csgo` 目录或 `tv_autorecord` 的保存位置
+   * @returns 按文件名排序的录像
+   */
+  export function listDemos(dir: string): Promise<Array<DemoFile>>
+  /**
+   * 读取整个录像，按 tick 顺序报告玩家连接、断开连接、击杀与回合结束
+   *
+   * 游戏事件只能从 CS:GO 录像中解析，其他游戏只读取文件头与帧，L4D 系列与 Portal 2 的帧格式未知，只读取文件头，汇总中的 eventsParsed 为 false。
+   * 录制中断的录像会读到文件结束为止，汇总中的 truncated 为 true。
+   *
+   * @param path 录像路径
+   * @param on_event 每个事件时调用，不需要事件时可以省略
+   * @returns 录像汇总，不是 Source 1 录像或数据损坏时抛出 PARSE_ERROR
+   */
+  export function parseDemo(path: string, onEvent?: ((event: DemoEvent) => void) | undefined | null): Promise<DemoSummary>
+}
+/** Game State Integration 事件类型 */
+export const enum EGsiEvent {
+  /** 地图改变，包括第一次收到地图 */
+  MapChanged = 0,
+  /** 比赛阶段改变，如热身结束进入 `live` */
+  MapPhaseChanged = 1,
+  /** 回合阶段改变，如冻结时间结束进入 `live` */
+  RoundPhaseChanged = 2,
+  /** 回合结束并决出胜方 */
+  RoundWon = 3,
+  /** 任意一方比分改变 */
+  ScoreChanged = 4,
+  /** 炸弹状态改变，如安放、拆除、爆炸 */
+  BombChanged = 5,
+  /** 本地玩家或正在观察的玩家改变 */
+  PlayerSwitched = 6,
+  /** 玩家的击杀、助攻、死亡、MVP 或得分改变 */
+  PlayerStatsChanged = 7,
+  /** 玩家的生命值降为 0 */
+  PlayerDied = 8
+}
+/** 接收 CS:GO 与 CS2 的 Game State Integration 状态，不需要初始化 Steam */
+export namespace gsi {
+  /** 上报状态的游戏客户端 */
+  export interface GsiProvider {
+    name: string
+    appId: number
+    version: number
+    /** 客户端登录的 Steam ID */
+    steamId: string
+    /** Unix 时间（秒） */
+    timestamp: number
+  }
+  /** 一方队伍 */
+  export interface GsiTeam {
+    score: number
+    consecutiveRoundLosses: number
+    timeoutsRemaining: number
+    matchesWonThisSeries: number
+    /** 比赛中设置的队伍名称 */
+    name?: string
+  }
+  /** 地图与比赛 */
+  export interface GsiMap {
+    /** 游戏模式，如 `competitive` */
+    mode: string
+    name: string
+    /** `warmup`、`live`、`intermission` 或 `gameover` */
+    phase: string
+    /** 已经结束的回合数 */
+    round: number
+    teamCt: GsiTeam
+    teamT: GsiTeam
+    numMatchesToWinSeries: number
+  }
+  /** 当前回合 */
+  export interface GsiRound {
+    /** `freezetime`、`live` 或 `over` */
+    phase: string
+    /** `planted`、`exploded` 或 `defused`，未安放时为空 */
+    bomb?: string
+    /** 回合结束后获胜的队伍，`CT` 或 `T` */
+    winTeam?: string
+  }
+  /** 玩家的实时状态 */
+  export interface GsiPlayerVitals {
+    health: number
+    armor: number
+    helmet: boolean
+    /** 是否有拆弹器，只有 CT 有值 */
+    defuseKit?: boolean
+    /** 闪光程度，0 到 255 */
+    flashed: number
+    smoked: number
+    burning: number
+    money: number
+    roundKills: number
+    /** 本回合爆头击杀数 */
+    roundKillHs: number
+    roundTotalDamage?: number
+    equipValue: number
+  }
+  /** 玩家本场比赛的数据 */
+  export interface GsiMatchStats {
+    kills: number
+    assists: number
+    deaths: number
+    mvps: number
+    score: number
+  }
+  /** 玩家 */
+  export interface GsiPlayer {
+    steamId: string
+    name: string
+    clan?: string
+    /** 观战时的按键编号 */
+    observerSlot?: number
+    /** `CT` 或 `T` */
+    team?: string
+    /** `playing`、`menu` 或 `textinput`，只有本地玩家有值 */
+    activity?: string
+    state?: GsiPlayerVitals
+    matchStats?: GsiMatchStats
+    /** 坐标 `[x, y, z]` */
+    position?: Array<number>
+    /** 朝向的单位向量 `[x, y, z]` */
+    forward?: Array<number>
+  }
+  /** 炸弹，只在观战时上报 */
+  export interface GsiBomb {
+    /** `carried`、`dropped`、`planting`、`planted`、`defusing`、`defused` 或 `exploded` */
+    state: string
+    /** 坐标 `[x, y, z]` */
+    position?: Array<number>
+    /** 携带、安放或拆除炸弹的玩家 Steam ID */
+    player?: string
+    /** 爆炸或拆除完成前的秒数 */
+    countdown?: number
+  }
+  /** 当前阶段的倒计时，只在观战时上报 */
+  export interface GsiPhaseCountdowns {
+    /** `freezetime`、`live`、`bomb`、`defuse`、`over`、`warmup` 等 */
+    phase: string
+    /** 阶段结束前的秒数 */
+    phaseEndsIn?: number
+  }
+  /** 一次上报的游戏状态，只包含 cfg 中启用的部分 */
+  export interface GsiGameState {
+    provider?: GsiProvider
+    map?: GsiMap
+    round?: GsiRound
+    /** 本地玩家，观战时为正在观察的玩家 */
+    player?: GsiPlayer
+    /** 所有玩家，只在观战或 GOTV 中上报 */
+    allPlayers?: Array<GsiPlayer>
+    bomb?: GsiBomb
+    phaseCountdowns?: GsiPhaseCountdowns
+  }
+  /** 与上一次状态相比的变化，只有与事件类型相关的字段有值 */
+  export interface GsiEvent {
+    kind: EGsiEvent
+    /** 变化前的值：地图、阶段、炸弹状态或玩家 Steam ID，第一次上报时为空 */
+    previous?: string
+    /** 相关的玩家，PlayerStatsChanged 与 PlayerDied */
+    steamId?: string
+    /** 获胜的队伍，仅 RoundWon */
+    team?: string
+    /** 变化前的比赛数据，仅 PlayerStatsChanged */
+    previousStats?: GsiMatchStats
+  }
+  /** 一次上报 */
+  export interface GsiUpdate {
+    /** 发送状态的地址 */
+    source: string
+    state: GsiGameState
+    /** 与同一客户端上一次状态相比的变化，状态没有变化时为空数组 */
+    events: Array<GsiEvent>
+  }
+  /** 状态监听选项 */
+  export interface GsiListenOptions {
+    /** 监听的本机地址，默认 `127.0.0.1` */
+    host?: string
+    /** cfg 中的 auth token，设置后拒绝 token 不匹配的状态 */
+    token?: string
+  }
+  /**
+   * 监听 HTTP 端口接收游戏状态，游戏需要先加载 writeConfig 写入的 cfg
+   *
+   * @param port 监听的端口，与 cfg 中 uri 的端口一致
+   * @param options 监听选项
+   * @param on_update 每收到一次状态时调用，游戏在状态变化时与每个心跳间隔发送
+   * @returns 服务端，端口被占用时抛出 NETWORK_ERROR
+   */
+  export function listen(port: number, options: GsiListenOptions | undefined | null, onUpdate: (update: GsiUpdate) => void): GsiServer
+  /** cfg 选项 */
+  export interface GsiConfigOptions {
+    /** 接收状态的地址，如 `http://127.0.0.1:3000` */
+    uri: string
+    /** 文件名中的名称，默认 `sourcebox`，生成 `gamestate_integration_sourcebox.cfg` */
+    name?: string
+    /** 随状态发送的 auth token，可以使用 generateToken 生成 */
+    token?: string
+    /** 请求超时（秒），默认 5 */
+    timeout?: number
+    /** 收集变化的时间（秒），默认 0.1 */
+    buffer?: number
+    /** 两次发送之间的最短间隔（秒），默认 0.1 */
+    throttle?: number
+    /** 状态没有变化时的发送间隔（秒），默认 10 */
+    heartbeat?: number
+  }
+  /**
+   * 生成 cfg 内容，启用地图、回合、玩家、所有玩家与炸弹状态
+   *
+   * @param options cfg 选项
+   * @returns cfg 文件内容
+   */
+  export function generateConfig(options: GsiConfigOptions): string
+  /**
+   * 把 cfg 写入游戏的 cfg 目录，已存在时覆盖，游戏重启后生效
+   *
+   * @param dir 游戏安装目录，如 `steamapps/common/Counter-Strike Global Offensive`，
+   * 会自动找到 CS2 的 `game/csgo/cfg` 或 CS:GO 的 `csgo/cfg`，也可以直接传入 cfg 目录
+   * @param options cfg 选项
+   * @returns 写入的文件路径
+   */
+  export function writeConfig(dir: string, options: GsiConfigOptions): Promise<string>
+  /**
+   * 生成随机的 auth token
+   *
+   * @returns 32 位十六进制字符串
+   */
+  export function generateToken(): string
+  /** Game State Integration 服务端，停止或被回收时关闭端口 */
+  export class GsiServer {
+    /** 实际监听的地址 */
+    address: string
+    /**
+     * 停止监听并关闭端口
+     *
+     * @noreturns
+     */
+    stop(): void
+    /**
+     * 最后收到的状态
+     *
+     * @returns 还没有收到状态时为空
+     */
+    state(): GsiGameState | null
+  }
+}
+/** HL 日志事件类型 */
+export const enum ELogEvent {
+  /** 日志文件开始 */
+  LogStarted = 0,
+  /** 日志文件关闭 */
+  LogClosed = 1,
+  /** 开始加载地图 */
+  MapLoading = 2,
+  /** 地图加载完成 */
+  MapStarted = 3,
+  /** 玩家连接 */
+  Connected = 4,
+  /** 玩家通过 Steam 验证 */
+  Validated = 5,
+  /** 玩家进入游戏 */
+  Entered = 6,
+  /** 玩家断开连接 */
+  Disconnected = 7,
+  /** 玩家加入队伍 */
+  JoinedTeam = 8,
+  /** 玩家改名 */
+  ChangedName = 9,
+  /** 击杀 */
+  Kill = 10,
+  /** 造成伤害 */
+  Attack = 11,
+  /** 自杀 */
+  Suicide = 12,
+  /** 聊天 */
+  Say = 13,
+  /** 玩家触发的事件，如下包、拆包 */
+  PlayerTriggered = 14,
+  /** 队伍触发的事件，如回合胜利 */
+  TeamTriggered = 15,
+  /** 队伍得分 */
+  TeamScored = 16,
+  /** 服务器触发的事件，如回合开始、回合结束 */
+  WorldTriggered = 17,
+  /** 服务器变量改变 */
+  Cvar = 18,
+  /** 执行了 RCON 命令 */
+  Rcon = 19,
+  /** 无法识别的日志行 */
+  Other = 20
+}
+/** 接收服务器通过 `logaddress_add` 发送的 HL 日志，不需要初始化 Steam */
+export namespace hllog {
+  /** 日志中的玩家 */
+  export interface LogPlayer {
+    name: string
+    /** 服务器分配的用户 ID，控制台为 0 */
+    userId: number
+    /** `STEAM_1:0:123`、`[U:1:123]`、`BOT` 或 `Console` */
+    steamId: string
+    /** 队伍，未加入队伍时可能为空 */
+    team: string
+  }
+  /** 一行日志，只有与事件类型相关的字段有值 */
+  export interface HlLogEvent {
+    kind: ELogEvent
+    /** 发送日志的服务器地址，parseLine 解析的日志为空 */
+    source?: string
+    /** 日志行的时间，服务器的本地时间，格式为 `2026-10-18T12:34:56`，不含时区 */
+    time?: string
+    /** 去掉时间前缀后的完整内容 */
+    message: string
+    /** 事件的主体：击杀者、造成伤害的玩家、发言的玩家等 */
+    player?: LogPlayer
+    /** 被击杀或受到伤害的玩家，PlayerTriggered 的 against 目标 */
+    target?: LogPlayer
+    /** 武器，Kill、Attack 与 Suicide */
+    weapon?: string
+    /** 是否爆头，仅 Kill */
+    headshot?: boolean
+    /** 队伍，JoinedTeam、TeamTriggered 与 TeamScored */
+    team?: string
+    /** 聊天内容、新名字或 RCON 命令 */
+    text?: string
+    /** 是否为队伍聊天，仅 Say */
+    teamOnly?: boolean
+    /** 地图，MapLoading 与 MapStarted */
+    map?: string
+    /** 触发的事件名，如 `Round_Start`、`Planted_The_Bomb` */
+    action?: string
+    /** 玩家的 IP 地址或执行 RCON 命令的地址，Connected 与 Rcon */
+    address?: string
+    /** 变量名，仅 Cvar */
+    cvar?: string
+    /** 变量值，仅 Cvar */
+    value?: string
+    /** 队伍得分与人数，仅 TeamScored */
+    score?: number
+    players?: number
+    /** 行尾的 `(键 "值")` 属性，`(headshot)` 之类的标记值为空字符串 */
+    properties: Record<string, string>
+  }
+  /** 日志监听选项 */
+  export interface HlLogListenOptions {
+    /** 监听的本机地址，默认 `0.0.0.0` */
+    host?: string
+    /** 服务器的 `sv_logsecret`，设置后只接受密码匹配的日志，为空时接受所有日志 */
+    secret?: string
+  }
+  /**
+   * 监听 UDP 端口接收日志，在服务器上执行 `logaddress_add <本机 IP>:<端口>` 后开始发送
+   *
+   * @param port 监听的端口，为 0 时由系统分配，实际端口见返回值的 address
+   * @param options 监听选项
+   * @param on_event 每收到一行日志时调用
+   * @returns 监听器，端口被占用时抛出 NETWORK_ERROR
+   */
+  export function listen(port: number, options: HlLogListenOptions | undefined | null, onEvent: (event: HlLogEvent) => void): HlLogListener
+  /**
+   * 解析一行日志，可以用于读取服务器保存的日志文件
+   *
+   * @param line 日志行，如 `L 10/18/2026 - 12:34:56: "Player<2><[U:1:1]><CT>" say "gg"`
+   * @returns 解析后的事件，无法识别时类型为 Other
+   */
+  export function parseLine(line: string): HlLogEvent
+  /** 日志回放选项 */
+  export interface HlLogReplayOptions {
+    /** 按 `sv_logsecret` 格式发送 */
+    secret?: string
+    /** 每行之间的间隔（毫秒），默认 0，本机回放大量日志时建议设置以免丢包 */
+    intervalMs?: number
+  }
+  /**
+   * 把日志行按服务器的格式发送到监听地址，用于在没有服务器时调试
+   *
+   * @param address 监听地址，`ip:port`
+   * @param lines 日志行，可以直接使用服务器 `logs` 目录中的日志文件内容
+   * @param options 回放选项
+   * @returns 发送的行数，空行会被跳过
+   */
+  export function replay(address: string, lines: Array<string>, options?: HlLogReplayOptions | undefined | null): Promise<number>
+  /** HL 日志监听器，停止或被回收时关闭端口 */
+  export class HlLogListener {
+    /** 实际监听的地址 */
+    address: string
+    /**
+     * 停止监听并关闭端口
+     *
+     * @noreturns
+     */
+    stop(): void
+  }
+}
+/** 日志级别 */
+export const enum LogLevel {
+  /** 每一次 Steam API 调用 */
+  Trace = 0,
+  /** 回调分发、异步调用完成与会话切换 */
+  Debug = 1,
+  /** Steam 初始化、关闭等状态变化 */
+  Info = 2,
+  /** 超时、调用失败与 Steam 的警告信息 */
+  Warn = 3,
+  /** 插件内部错误 */
+  Error = 4,
+  /** 不输出日志 */
+  Off = 5
+}
+/** 一条日志 */
+export interface LogRecord {
+  level: LogLevel
+  /** 日志来源，如 "api"、"callback"、"steam" */
+  target: string
+  message: string
+  /** 记录时间，Unix 毫秒时间戳 */
+  timestamp: number
+}
+/** 插件日志，注册日志函数后才会输出 */
+export namespace logging {
+  /**
+   * 注册日志函数，替换之前注册的日志函数
+   *
+   * 日志函数不会阻止进程退出
+   *
+   * @param handler 接收日志的函数
+   */
+  export function setSink(handler: (record: LogRecord) => void): void
+  /** 移除日志函数 */
+  export function clearSink(): void
+  /**
+   * 设置日志级别，低于该级别的日志不会输出，默认 Info
+   *
+   * @param level 日志级别
+   */
+  export function setLevel(level: LogLevel): void
+  /** 获取当前日志级别 */
+  export function getLevel(): LogLevel
+}
+/** 后台回调分发，`init` 成功后自动以默认间隔启动 */
+export namespace pump {
+  /** 后台回调分发状态 */
+  export interface PumpStatus {
+    /** 是否正在运行 */
+    running: boolean
+    /** 分发间隔毫秒数，未运行时为 0 */
+    intervalMs: number
+    /** 已执行的分发次数 */
+    ticks: number
+  }
+  /**
+   * 启动后台回调分发，启动后无需再手动调用 runCallbacks
+   *
+   * @param interval_ms 分发间隔毫秒数，默认 33
+   */
+  export function start(intervalMs?: number | undefined | null): void
+  /** 停止后台回调分发，之后需要手动调用 runCallbacks */
+  export function stop(): void
+  /** 获取后台回调分发状态 */
+  export function status(): PumpStatus
+}
+export namespace rcon {
+  /** RCON 连接选项 */
+  export interface RconOptions {
+    /** 连接、验证与每条命令各自的超时时间（毫秒），默认 5000 */
+    timeoutMs?: number
+  }
+  /**
+   * 连接 RCON 并验证密码
+   *
+   * @param address 服务器地址，`ip:port` 或 `域名:port`，未指定端口时使用 27015
+   * @param password RCON 密码，即服务器的 `rcon_password`
+   * @param options 连接选项
+   * @returns 验证成功的连接，密码错误时错误码为 AUTH_FAILED
+   */
+  export function connect(address: string, password: string, options?: RconOptions | undefined | null): Promise<RconConnection>
+  /** RCON 连接，同一连接上的命令按调用顺序依次执行 */
+  export class RconConnection {
+    /** 连接的地址 */
+    address: string
+    /**
+     * 执行控制台命令
+     *
+     * 连接断开时会自动重新连接并验证，命令开始回复之前断开时会重试一次。
+     *
+     * @param command 控制台命令，如 `status`
+     * @returns 命令的完整回复，多个包的回复会被合并
+     */
+    exec(command: string): Promise<string>
+    /**
+     * 关闭连接，等待正在执行的命令结束，之后执行命令会失败
+     *
+     * @noreturns
+     */
+    close(): Promise<void>
+  }
+}
+/** 导入的服务器来自哪个列表 */
+export const enum EServerEntrySource {
+  /** serverbrowser_hist.vdf 中的收藏夹 */
+  Favorites = 0,
+  /** serverbrowser_hist.vdf 中的历史记录 */
+  History = 1,
+  /** 纯文本列表 */
+  List = 2
+}
+/** 从 Steam 配置文件与纯文本导入服务器列表和黑名单，不需要初始化 Steam */
+export namespace serverlist {
+  /** 导入的服务器 */
+  export interface ImportedServer {
+    /** `ip:port` 或 `域名:port`，未指定端口时为 27015 */
+    address: string
+    /** 服务器名称，纯文本列表中没有 */
+    name?: string
+    /** 服务器所属游戏的 AppId，纯文本列表中没有 */
+    appId?: number
+    /** 最后一次游戏的 Unix 时间（秒），纯文本列表中没有 */
+    lastPlayed?: number
+    source: EServerEntrySource
+  }
+  /** 一条黑名单规则 */
+  export interface BlacklistRule {
+    /** 规则，单个地址为 `ip` 或 `ip:port`，区间为 `首地址-尾地址`，可以直接传给 createBlacklist */
+    rule: string
+    /** Steam 黑名单中记录的服务器名称 */
+    name?: string
+  }
+  /**
+   * 导入 Steam 服务器浏览器的收藏夹与历史记录
+   *
+   * @param path `Steam/userdata/<账号 ID>/7/remote/serverbrowser_hist.vdf`
+   * 或旧版本的 `Steam/config/serverbrowser_hist.vdf`
+   * @returns 收藏夹中的服务器在前，历史记录在后，同一服务器可能在两个列表中各出现一次
+   */
+  export function importServerHistory(path: string): Promise<Array<ImportedServer>>
+  /**
+   * 导入每行一个地址的纯文本服务器列表，支持 `//`、`#`、`;` 注释与 `connect` 前缀，
+   * 无法解析的行会被跳过并记录日志
+   *
+   * @param path 文件路径
+   * @returns 去重后的服务器，保持文件中的顺序
+   */
+  export function importServerList(path: string): Promise<Array<ImportedServer>>
+  /**
+   * 读取服务器黑名单，支持 Steam 服务器浏览器导出的黑名单与每行一个规则的纯文本，
+   * 无法解析的规则会被跳过并记录日志
+   *
+   * @param path 文件路径，如游戏目录中的 `cfg/server_blacklist.txt`
+   * @returns 黑名单
+   */
+  export function loadBlacklist(path: string): Promise<ServerBlacklist>
+  /**
+   * 根据规则创建黑名单
+   *
+   * @param rules 规则列表，如 `1.2.3.4:27015`、`1.2.3.4`（所有端口）、`1.2.3.*`、`1.2.3.0/24`、`1.2.3.4-1.2.3.20`
+   * @returns 黑名单，有无法解析的规则时抛出 PARSE_ERROR
+   */
+  export function createBlacklist(rules: Array<string>): ServerBlacklist
+  /** 服务器黑名单 */
+  export class ServerBlacklist {
+    /** 黑名单中的所有规则 */
+    rules(): Array<BlacklistRule>
+    /**
+     * 服务器是否在黑名单中
+     *
+     * @param address `ip:port`，未指定端口时使用 27015，域名无法判断，总是返回 false
+     */
+    isBlocked(address: string): boolean
+    /**
+     * 移除黑名单中的服务器，可以用于 A2S 查询、服务器浏览器与导入的服务器列表
+     *
+     * @param servers 含 address 属性的对象，没有 address 属性的对象会被保留
+     * @returns 不在黑名单中的服务器，保持原来的顺序
+     */
+    filter<T extends { address: string }>(servers: Array<T>): Array<T>
+  }
 }
 export namespace steamremotestorage {
   /**
@@ -687,7 +2174,7 @@ export namespace steamremotestorage {
    * 注意： 这是一个同步调用，因此将阻止您调用磁盘 IO 的线程，也将阻止 SteamAPI，这可能导致您应用程序中的其他线程受阻。
    * 要避免客户端电脑由于磁盘繁忙出现“卡顿”，我们建议使用此API 的异步版本，即 FileReadAsync。
    *
-   * @returns 返回读取文件信息
+   * @returns 返回读取文件信息，文件不存在、读取失败或不是 UTF-8 文本时抛出 IO_ERROR
    */
   export function fileRead(name: string): string
   /**
@@ -820,7 +2307,7 @@ export namespace steamugc {
    *
    * @returns 返回此物品状态。 应与 EItemState 标记一起使用，以确定创意工坊物品的状态。
    */
-  export function getItemState(itemId: bigint): number
+  export function getItemState(itemId: bigint | string): number
   /**
    * 创建一个尚无附加内容的新创意工坊物品
    *
@@ -836,21 +2323,21 @@ export namespace steamugc {
    * @param app_id 创意工坊所属应用ID
    * @returns 返回更新结果及错误信息
    */
-  export function updateItem(itemId: bigint, updateDetails: UgcUpdate, appId?: number | undefined | null): Promise<UgcResult>
+  export function updateItem(itemId: bigint | string, updateDetails: UgcUpdate, appId?: number | undefined | null): Promise<UgcResult>
   /**
    * 订阅创意工坊物品。 会尽快下载并安装该物品。
    *
    * @param item_id 要订阅的创意工坊物品
    * @returns 返回订阅结果
    */
-  export function subscribeItem(itemId: bigint): Promise<void>
+  export function subscribeItem(itemId: bigint | string): Promise<void>
   /**
    * 取消来自创意工坊物品的订阅。 退出游戏后会移除该物品。
    *
    * @param item_id 要取消订阅的创意工坊物品项目
    * @returns 返回订阅结果
    */
-  export function unsubscribeItem(itemId: bigint): Promise<void>
+  export function unsubscribeItem(itemId: bigint | string): Promise<void>
   /**
    * 下载或更新创意工坊物品。
    * 如果返回值为 true，在调用 GetItemInstallInfo 或访问磁盘上创意工坊物品前，注册并等待 DownloadItemResult_t 回调。
@@ -864,7 +2351,7 @@ export namespace steamugc {
    * @param high_priority 以高优先级模式开始下载，暂停所有其他正在进行的 Steam 下载并立即开始下载此创意工坊物品
    * @returns true 表明下载成功开始；否则，如果 nPublishedFileID 无效或用户尚未登录，返回 false。
    */
-  export function downloadItem(itemId: bigint, highPriority: boolean): boolean
+  export function downloadItem(itemId: bigint | string, highPriority: boolean): boolean
   /**
    * 获得当前游戏中，当前用户所订阅的所有物品的清单。
    *
@@ -878,7 +2365,7 @@ export namespace steamugc {
    * @param query 要查询创意工坊指定选项
    * @returns 返回创意工坊查询结果
    */
-  export function getItem(item: bigint, query?: WorkshopItemQueryOptions | undefined | null): Promise<WorkshopItem | null>
+  export function getItem(item: bigint | string, query?: WorkshopItemQueryOptions | undefined | null): Promise<WorkshopItem | null>
   /**
    * 查询特定 UGC 物品的详细信息。 目前您可以请求的项目数量限制为 1,000，但将来此限制可能会取消。
    *
@@ -886,20 +2373,20 @@ export namespace steamugc {
    * @param query 要查询创意工坊指定选项
    * @returns 返回创意工坊查询结果
    */
-  export function getItems(items: Array<bigint>, query?: WorkshopItemQueryOptions | undefined | null): Promise<Array<WorkshopItem | undefined | null>>
+  export function getItems(items: Array<bigint | string>, query?: WorkshopItemQueryOptions | undefined | null): Promise<Array<WorkshopItem | undefined | null>>
   /**
    * 获取当前已安装在光盘上的创意工坊物品相关信息。
    *
    * @param item_id 	要获取安装信息的创意工坊物品
    * @returns 返回创意工坊安装信息
    */
-  export function getItemInstallInfo(itemId: bigint): InstallInfo | null
+  export function getItemInstallInfo(itemId: bigint | string): InstallInfo | null
   /**
    * 获取设置了 k_EItemStateNeedsUpdate 的创意工坊物品的待下载状态信息
    *
    * @param item_id 要获取下载信息的创意工坊物品
    */
-  export function getItemDownloadInfo(itemId: bigint): DownloadInfo | null
+  export function getItemDownloadInfo(itemId: bigint | string): DownloadInfo | null
   /**
    * 获取创意工坊物品更新的进度
    *
@@ -912,7 +2399,7 @@ export namespace steamugc {
    * @param progress_callback_interval_ms 创意工坊回调时间
    */
   export function updateItemWithCallback(
-    itemId: bigint,
+    itemId: bigint | string,
     updateDetails: UgcUpdate,
     appId: number | undefined | null,
     successCallback: (data: UgcResult) => void,
@@ -945,7 +2432,7 @@ export namespace steamuser {
    * @param timeoutSeconds - 等待票证验证的秒数。 默认值为 10 秒。 如果是游戏服务器，且是从可信的第三方获得的，则可以使用游戏服务器的 steam ID
    * @returns 返回验证票证
    */
-  export function getSessionTicketWithSteamId(steamId64: bigint, timeoutSeconds?: number | undefined | null): Promise<Ticket>
+  export function getSessionTicketWithSteamId(steamId64: bigint | string, timeoutSeconds?: number | undefined | null): Promise<Ticket>
   /**
    * 通过SteamID获取身份验证票证，以便发送给希望对您进行身份验证的实体。
    * 调用此函数之后，您可以发送票证至实体，该实体然后可以调用 BeginAuthSession/ISteamGameServer::BeginAuthSession，验证此实体的完整性。
@@ -1088,3 +2575,111 @@ export namespace steamutils {
    */
   export function isSteamRunningOnSteamDeck(): boolean
 }
+/** 服务器监视事件类型 */
+export const enum EWatchEvent {
+  /** 服务器开始回复，包括第一次查询成功 */
+  Online = 0,
+  /** 服务器连续多次没有回复 */
+  Offline = 1,
+  /** 地图改变 */
+  MapChanged = 2,
+  /** 玩家加入 */
+  PlayerJoined = 3,
+  /** 玩家离开 */
+  PlayerLeft = 4,
+  /** 服务器已满 */
+  Full = 5,
+  /** 服务器从满员变为有空位 */
+  HasSlots = 6,
+  /** 延迟突增 */
+  LatencySpike = 7
+}
+/** 服务器监视，在后台按间隔查询服务器并报告变化，不需要初始化 Steam */
+export namespace watch {
+  /** 监视选项 */
+  export interface ServerWatchOptions {
+    /** 默认轮询间隔（毫秒），默认 30000，最小 1000 */
+    intervalMs?: number
+    /** 单次查询的超时时间（毫秒），默认 3000 */
+    timeoutMs?: number
+    /** 是否查询玩家列表以报告玩家进出，默认 true */
+    trackPlayers?: boolean
+    /** 连续失败多少次后认为离线，默认 2 */
+    offlineAfter?: number
+    /** 延迟超过基准多少倍算作突增，默认 2 */
+    latencySpikeFactor?: number
+    /** 延迟至少增加多少毫秒才算突增，默认 100 */
+    latencySpikeMinMs?: number
+    /** 保存人数历史的目录，为空时不保存 */
+    historyDir?: string
+    /** 每个服务器保留的历史采样数量，默认 2880 */
+    historySize?: number
+  }
+  /** 服务器监视事件 */
+  export interface ServerWatchEvent {
+    kind: EWatchEvent
+    /** 添加服务器时传入的地址 */
+    address: string
+    /** 本次查询到的服务器信息，离线时为空 */
+    info?: a2s.A2sServerInfo
+    /** 换图前的地图，仅 MapChanged */
+    previousMap?: string
+    /** 加入或离开的玩家名，仅 PlayerJoined 与 PlayerLeft */
+    player?: string
+    /** 突增前的平均延迟（毫秒），仅 LatencySpike，当前延迟见 info.latency */
+    baselineMs?: number
+    /** 最后一次查询失败的原因，仅 Offline */
+    error?: string
+  }
+  /** 人数历史中的一次采样 */
+  export interface PopulationSample {
+    /** 采样时间（Unix 毫秒），可以直接传给 `new Date()` */
+    time: number
+    /** 玩家数量（包括机器人），离线时为空 */
+    players?: number
+    /** 最大玩家数量，离线时为空 */
+    maxPlayers?: number
+    /** 机器人数量，离线时为空 */
+    bots?: number
+  }
+  /**
+   * 创建服务器监视器
+   *
+   * @param options 监视选项
+   * @param on_event 服务器状态变化时调用
+   * @returns 监视器，使用 add 添加服务器
+   */
+  export function createWatcher(options: ServerWatchOptions | undefined | null, onEvent: (event: ServerWatchEvent) => void): ServerWatcher
+  /** 服务器监视器，停止或被回收时结束所有轮询 */
+  export class ServerWatcher {
+    /**
+     * 开始监视服务器，第一次查询立即进行；已在监视的服务器会以新的间隔重新开始
+     *
+     * @param address 服务器地址，`ip:port` 或 `域名:port`，未指定端口时使用 27015
+     * @param interval_ms 该服务器的轮询间隔（毫秒），为空时使用默认间隔
+     * @returns 是否为新添加的服务器
+     */
+    add(address: string, intervalMs?: number | undefined | null): boolean
+    /**
+     * 停止监视服务器，已保存的历史不会被删除
+     *
+     * @returns 服务器是否在监视中
+     */
+    remove(address: string): boolean
+    /** 正在监视的服务器地址 */
+    servers(): Array<string>
+    /**
+     * 停止监视所有服务器
+     *
+     * @noreturns
+     */
+    stop(): void
+    /**
+     * 读取服务器的人数历史，服务器不需要正在监视
+     *
+     * @param address 添加服务器时传入的地址
+     * @returns 按时间排序的采样，未指定历史目录时为空
+     */
+    history(address: string): Promise<Array<PopulationSample>>
+  }
+}
@@ -1,6 +1,9 @@
 import Electron from 'electron'
+import log4js from 'log4js'
+import path from 'path'
 import { Client } from './types'
-import { SteamApiCallback } from './client'
+import { InitDiagnostics, SteamApiCallback } from './client'
+import type { LogLevel, LogRecord } from './client'
 import SteamworksNode from '../../../../resources/win64/steamworks.win32-x64-msvc.node'
 
 /// 目前只考虑Windows x64使用 steamworks 跳过该步骤
@@ -22,7 +25,35 @@ import SteamworksNode from '../../../../resources/win64/steamworks.win32-x64-msv
   }
 })() */
 
-let runCallbacksInterval: NodeJS.Timeout
+// 原生类无法声明 Symbol 方法，这里补上使事件流可以直接用于 for await
+SteamworksNode.callback.EventStream.prototype[Symbol.asyncIterator] = function () {
+  return this
+}
+
+/** log4js level names indexed by `LogLevel` */
+const LOG_LEVELS = ['trace', 'debug', 'info', 'warn', 'error', 'off']
+
+/**
+ * Write native steamworks logs to `steamworks.log` in the Electron logs directory
+ * @param {LogLevel} [level] - Lowest level to record, defaults to `LogLevel.Info`
+ * @returns {string} - Path of the log file
+ */
+const enableLogging = (level: LogLevel = SteamworksNode.LogLevel.Info): string => {
+  const filename = path.join(Electron.app.getPath('logs'), 'steamworks.log')
+  log4js.configure({
+    appenders: {
+      steamworks: { type: 'file', filename, maxLogSize: 10 * 1024 * 1024, backups: 3 }
+    },
+    categories: { default: { appenders: ['steamworks'], level: LOG_LEVELS[level] } }
+  })
+
+  const logger = log4js.getLogger('steamworks')
+  SteamworksNode.logging.setSink((record: LogRecord) => {
+    logger.log(LOG_LEVELS[record.level], `[${record.target}] ${record.message}`)
+  })
+  SteamworksNode.logging.setLevel(level)
+  return filename
+}
 
 /**
  * Initialize the steam client or throw an error if it fails
@@ -30,20 +61,33 @@ let runCallbacksInterval: NodeJS.Timeout
  * @returns {Omit<Client, 'init' | 'runCallbacks'>}
  */
 const init = (appId: number): Omit<Client, 'init' | 'runCallbacks'> => {
+  // 回调由插件的后台线程分发，runCallbacks 只在 pump.stop() 后需要手动调用
+  // eslint-disable-next-line @typescript-eslint/no-unused-vars
   const { init: internalInit, runCallbacks, ...api } = SteamworksNode
 
   internalInit(appId)
 
-  clearInterval(runCallbacksInterval)
-  runCallbacksInterval = setInterval(runCallbacks, 1000 / 30)
-
   return api
 }
 
+/**
+ * Initialize the steam client and report why it failed instead of throwing
+ * @param {number} [appId] - App ID of the game to load, if undefined, will search for a steam_appid.txt file
+ * @returns {{ diagnostics: InitDiagnostics, api?: Omit<Client, 'init' | 'runCallbacks'> }} - `api` is only set when initialization succeeded
+ */
+const initWithDiagnostics = (
+  appId?: number
+): { diagnostics: InitDiagnostics; api?: Omit<Client, 'init' | 'runCallbacks'> } => {
+  // eslint-disable-next-line @typescript-eslint/no-unused-vars
+  const { init, runCallbacks, ...api } = SteamworksNode
+
+  const diagnostics = SteamworksNode.initWithDiagnostics(appId)
+  return { diagnostics, api: diagnostics.initialized ? api : undefined }
+}
+
 const shutdown = () => {
   const { shutdown } = SteamworksNode
 
-  clearInterval(runCallbacksInterval)
   shutdown()
 }
 
@@ -56,6 +100,51 @@ const restartAppIfNecessary = (appId: number): boolean => {
   return SteamworksNode.restartAppIfNecessary(appId)
 }
 
+/**
+ * Read the stable error code from an error thrown by the native module
+ * @param {unknown} error - Error thrown by a steamworks call, e.g. `[STEAM_NOT_INITIALIZED] ...`
+ * @returns {string | undefined} - Error code such as `STEAM_NOT_INITIALIZED`
+ */
+const getSteamErrorCode = (error: unknown): string | undefined => {
+  return parseSteamError(error)?.code
+}
+
+export interface SteamErrorInfo {
+  /** Stable error code, e.g. `STEAM_ERROR`, `STEAM_NOT_INITIALIZED` or `PANIC` */
+  code: string
+  /** Numeric EResult, only present for `STEAM_ERROR` */
+  eresult?: number
+  /** Symbolic EResult name such as `AccessDenied`, `LimitExceeded` or `Timeout` */
+  name?: string
+  /** Steam API call that failed, e.g. `join_lobby` */
+  call?: string
+  /** Human readable description */
+  message: string
+}
+
+/**
+ * Parse an error thrown by the native module into its structured parts
+ * @param {unknown} error - Error thrown by a steamworks call, e.g. `[STEAM_ERROR:15:AccessDenied] join_lobby: access denied`
+ * @returns {SteamErrorInfo | undefined} - Structured error, or undefined if it did not come from the native module
+ */
+const parseSteamError = (error: unknown): SteamErrorInfo | undefined => {
+  const message = error instanceof Error ? error.message : String(error)
+  const match = /^\[([A-Z0-9_]+)(?::(\d+):(\w+))?\]\s*(.*)$/s.exec(message)
+  if (!match) return undefined
+
+  const [, code, eresult, name, rest] = match
+  if (eresult === undefined) return { code, message: rest }
+
+  const call = /^(\w+): (.*)$/s.exec(rest)
+  return {
+    code,
+    eresult: Number(eresult),
+    name,
+    call: call?.[1],
+    message: call ? call[2] : rest
+  }
+}
+
 /**
  * Enable the steam overlay on electron
  * @param {boolean} [disableEachFrameInvalidation] - Should attach a single pixel to be rendered each frame
@@ -83,8 +172,12 @@ const electronEnableSteamOverlay = (disableEachFrameInvalidation?: boolean) => {
 
 export default {
   init,
+  enableLogging,
+  initWithDiagnostics,
   shutdown,
   restartAppIfNecessary,
+  getSteamErrorCode,
+  parseSteamError,
   electronEnableSteamOverlay,
   SteamCallback: SteamworksNode.callback.SteamCallback as SteamApiCallback
 }
@@ -0,0 +1,548 @@
+use crate::query::message::{
+    Environment, GoldSrcMod, Player, Rule, ServerInfo, ServerType, ShipInfo,
+};
+use napi_derive::napi;
+
+#[napi]
+/// 服务器类型
+pub enum EServerType {
+    /// 专用服务器
+    Dedicated,
+    /// 非专用服务器
+    NonDedicated,
+    /// SourceTV 中继
+    SourceTv,
+    Unknown,
+}
+
+impl From<ServerType> for EServerType {
+    fn from(value: ServerType) -> Self {
+        match value {
+            ServerType::Dedicated => EServerType::Dedicated,
+            ServerType::NonDedicated => EServerType::NonDedicated,
+            ServerType::SourceTv => EServerType::SourceTv,
+            ServerType::Unknown => EServerType::Unknown,
+        }
+    }
+}
+
+#[napi]
+/// 服务器操作系统
+pub enum EServerEnvironment {
+    Linux,
+    Windows,
+    Mac,
+    Unknown,
+}
+
+impl From<Environment> for EServerEnvironment {
+    fn from(value: Environment) -> Self {
+        match value {
+            Environment::Linux => EServerEnvironment::Linux,
+            Environment::Windows => EServerEnvironment::Windows,
+            Environment::Mac => EServerEnvironment::Mac,
+            Environment::Unknown => EServerEnvironment::Unknown,
+        }
+    }
+}
+
+#[napi]
+/// Source 服务器查询（A2S），直接通过 UDP 查询，不需要初始化 Steam
+pub mod a2s {
+    use super::{EServerEnvironment, EServerType, GoldSrcMod, Player, Rule, ServerInfo, ShipInfo};
+    use crate::api::logging::steam_log;
+    use crate::error::SteamworksError;
+    use crate::query::batch::{self, BatchResult};
+    use crate::query::lan::{self, DiscoveryOptions};
+    use crate::query::{self, Query};
+    use napi::bindgen_prelude::{BigInt, Error};
+    use napi::threadsafe_function::{
+        ErrorStrategy, ThreadsafeFunction, ThreadsafeFunctionCallMode,
+    };
+    use napi::{Env, JsFunction, JsObject};
+    use std::net::Ipv4Addr;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Arc;
+    use std::time::{Duration, Instant};
+
+    #[napi(object)]
+    /// 查询选项
+    pub struct QueryOptions {
+        /// 等待回复的超时时间（毫秒），包括 challenge 握手，默认 3000
+        pub timeout_ms: Option<u32>,
+    }
+
+    impl QueryOptions {
+        pub(crate) fn timeout(options: &Option<QueryOptions>) -> Duration {
+            options
+                .as_ref()
+                .and_then(|options| options.timeout_ms)
+                .map_or(query::DEFAULT_TIMEOUT, |ms| {
+                    Duration::from_millis(u64::from(ms))
+                })
+        }
+    }
+
+    #[napi(object)]
+    /// The Ship 服务器信息
+    pub struct A2sShipInfo {
+        /// 游戏模式：0 Hunt、1 Elimination、2 Duel、3 Deathmatch、4 VIP Team、5 Team Elimination
+        pub mode: u32,
+        /// 需要的目击者数量
+        pub witnesses: u32,
+        /// 被目击后的时长（秒）
+        pub duration: u32,
+    }
+
+    impl From<ShipInfo> for A2sShipInfo {
+        fn from(ship: ShipInfo) -> Self {
+            Self {
+                mode: u32::from(ship.mode),
+                witnesses: u32::from(ship.witnesses),
+                duration: u32::from(ship.duration),
+            }
+        }
+    }
+
+    #[napi(object)]
+    /// GoldSrc 模组信息
+    pub struct A2sGoldSrcMod {
+        /// 模组网站
+        pub link: String,
+        /// 模组下载地址
+        pub download_link: String,
+        pub version: u32,
+        /// 模组大小（字节）
+        pub size: u32,
+        /// 是否只支持多人游戏
+        pub multiplayer_only: bool,
+        /// 是否使用自己的 DLL 而不是 Half-Life 的 DLL
+        pub custom_dll: bool,
+    }
+
+    impl From<GoldSrcMod> for A2sGoldSrcMod {
+        fn from(info: GoldSrcMod) -> Self {
+            Self {
+                link: info.link,
+                download_link: info.download_link,
+                version: info.version,
+                size: info.size,
+                multiplayer_only: info.multiplayer_only,
+                custom_dll: info.custom_dll,
+            }
+        }
+    }
+
+    #[napi(object)]
+    /// A2S_INFO 服务器信息
+    pub struct A2sServerInfo {
+        /// 查询的地址
+        pub address: String,
+        /// 延迟（毫秒），从发出最后一次请求到收到完整回复
+        pub latency: f64,
+        /// 协议版本
+        pub protocol: u32,
+        /// 服务器名称
+        pub name: String,
+        /// 当前地图
+        pub map: String,
+        /// 游戏目录，如 `cstrike`
+        pub folder: String,
+        /// 游戏名称
+        pub game: String,
+        /// AppId，超过 16 位的 AppId 请使用 gameId
+        pub app_id: u32,
+        /// 玩家数量（包括机器人）
+        pub players: u32,
+        /// 最大玩家数量
+        pub max_players: u32,
+        /// 机器人数量
+        pub bots: u32,
+        pub server_type: EServerType,
+        pub environment: EServerEnvironment,
+        /// 是否需要密码
+        pub password: bool,
+        /// 是否启用 VAC
+        pub vac: bool,
+        /// 游戏版本
+        pub version: String,
+        /// 游戏端口
+        pub port: Option<u32>,
+        /// 服务器 Steam ID
+        pub steam_id: Option<BigInt>,
+        /// SourceTV 端口
+        pub tv_port: Option<u32>,
+        /// SourceTV 名称
+        pub tv_name: Option<String>,
+        /// 服务器标签
+        pub keywords: Option<String>,
+        /// 64 位游戏 ID，低 24 位为完整的 AppId
+        pub game_id: Option<BigInt>,
+        /// The Ship 的额外信息
+        pub ship: Option<A2sShipInfo>,
+        /// 是否为 GoldSrc 旧格式回复，旧格式没有版本号与 Steam ID，AppId 根据游戏目录推断，未知时为 0
+        pub goldsrc: bool,
+        /// GoldSrc 模组信息，仅旧格式回复
+        pub goldsrc_mod: Option<A2sGoldSrcMod>,
+    }
+
+    impl A2sServerInfo {
+        pub(crate) fn from_info(address: String, info: ServerInfo) -> Self {
+            Self {
+                address,
+                latency: info.latency.as_secs_f64() * 1000.0,
+                protocol: u32::from(info.protocol),
+                name: info.name,
+                map: info.map,
+                folder: info.folder,
+                game: info.game,
+                app_id: u32::from(info.app_id),
+                players: u32::from(info.players),
+                max_players: u32::from(info.max_players),
+                bots: u32::from(info.bots),
+                server_type: info.server_type.into(),
+                environment: info.environment.into(),
+                password: info.password,
+                vac: info.vac,
+                version: info.version,
+                port: info.port.map(u32::from),
+                steam_id: info.steam_id.map(BigInt::from),
+                tv_port: info.source_tv.as_ref().map(|tv| u32::from(tv.port)),
+                tv_name: info.source_tv.map(|tv| tv.name),
+                keywords: info.keywords,
+                game_id: info.game_id.map(BigInt::from),
+                ship: info.ship.map(A2sShipInfo::from),
+                goldsrc: info.goldsrc,
+                goldsrc_mod: info.goldsrc_mod.map(A2sGoldSrcMod::from),
+            }
+        }
+    }
+
+    #[napi(object)]
+    /// A2S_PLAYER 玩家信息
+    pub struct A2sPlayer {
+        /// 玩家序号，大多数游戏总是 0
+        pub index: u32,
+        pub name: String,
+        pub score: i32,
+        /// 在服务器中的时长（秒）
+        pub duration: f64,
+        /// 死亡数，仅 The Ship
+        pub deaths: Option<i32>,
+        /// 金钱，仅 The Ship
+        pub money: Option<i32>,
+    }
+
+    impl From<Player> for A2sPlayer {
+        fn from(player: Player) -> Self {
+            Self {
+                index: u32::from(player.index),
+                name: player.name,
+                score: player.score,
+                duration: f64::from(player.duration),
+                deaths: player.ship.map(|ship| ship.deaths),
+                money: player.ship.map(|ship| ship.money),
+            }
+        }
+    }
+
+    #[napi(object)]
+    /// A2S_RULES 服务器规则
+    pub struct A2sRule {
+        /// 控制台变量名
+        pub name: String,
+        pub value: String,
+    }
+
+    impl From<Rule> for A2sRule {
+        fn from(rule: Rule) -> Self {
+            Self {
+                name: rule.name,
+                value: rule.value,
+            }
+        }
+    }
+
+    /// 解析地址并建立查询连接
+    async fn connect(
+        call: &'static str,
+        address: &str,
+        options: &Option<QueryOptions>,
+    ) -> Result<Query, Error> {
+        let target = query::resolve(address)
+            .await
+            .map_err(|e| e.context(call, address))?;
+        Query::connect(target, QueryOptions::timeout(options))
+            .await
+            .map_err(|e| e.context(call, address).into())
+    }
+
+    /// 记录失败的查询并转换为 JS 错误
+    fn query_error(call: &'static str, address: &str, error: query::Error) -> Error {
+        steam_log!(Debug, "a2s", "{} {} failed: {}", call, address, error);
+        error.context(call, address).into()
+    }
+
+    #[napi]
+    /// 查询服务器信息（A2S_INFO）
+    ///
+    /// @param address 服务器地址，`ip:port` 或 `域名:port`，未指定端口时使用 27015
+    /// @param options 查询选项
+    /// @returns 服务器信息
+    pub async fn query_info(
+        address: String,
+        options: Option<QueryOptions>,
+    ) -> Result<A2sServerInfo, Error> {
+        crate::error::catch_panic_async("a2s_info", async move {
+            let query = connect("a2s_info", &address, &options).await?;
+            let info = query
+                .info()
+                .await
+                .map_err(|e| query_error("a2s_info", &address, e))?;
+            Ok(A2sServerInfo::from_info(address, info))
+        })
+        .await?
+    }
+
+    #[napi]
+    /// 查询服务器中的玩家（A2S_PLAYER）
+    ///
+    /// @param address 服务器地址
+    /// @param options 查询选项
+    /// @returns 玩家列表
+    pub async fn query_players(
+        address: String,
+        options: Option<QueryOptions>,
+    ) -> Result<Vec<A2sPlayer>, Error> {
+        crate::error::catch_panic_async("a2s_player", async move {
+            let query = connect("a2s_player", &address, &options).await?;
+            let players = query
+                .players()
+                .await
+                .map_err(|e| query_error("a2s_player", &address, e))?;
+            Ok(players.into_iter().map(A2sPlayer::from).collect())
+        })
+        .await?
+    }
+
+    #[napi]
+    /// 查询服务器规则（A2S_RULES）
+    ///
+    /// @param address 服务器地址
+    /// @param options 查询选项
+    /// @returns 规则列表
+    pub async fn query_rules(
+        address: String,
+        options: Option<QueryOptions>,
+    ) -> Result<Vec<A2sRule>, Error> {
+        crate::error::catch_panic_async("a2s_rules", async move {
+            let query = connect("a2s_rules", &address, &options).await?;
+            let rules = query
+                .rules()
+                .await
+                .map_err(|e| query_error("a2s_rules", &address, e))?;
+            Ok(rules.into_iter().map(A2sRule::from).collect())
+        })
+        .await?
+    }
+
+    #[napi(object)]
+    /// 批量查询选项
+    pub struct BatchQueryOptions {
+        /// 同时进行的查询数量，默认 32
+        pub concurrency: Option<u32>,
+        /// 单次查询的超时时间（毫秒），默认 3000
+        pub timeout_ms: Option<u32>,
+        /// 超时或网络错误后的重试次数，默认 1
+        pub retries: Option<u32>,
+    }
+
+    impl From<Option<BatchQueryOptions>> for batch::BatchOptions {
+        fn from(options: Option<BatchQueryOptions>) -> Self {
+            let mut batch_options = batch::BatchOptions::default();
+            if let Some(options) = options {
+                if let Some(concurrency) = options.concurrency {
+                    batch_options.concurrency = concurrency as usize;
+                }
+                if let Some(timeout_ms) = options.timeout_ms {
+                    batch_options.timeout = Duration::from_millis(u64::from(timeout_ms));
+                }
+                if let Some(retries) = options.retries {
+                    batch_options.retries = retries;
+                }
+            }
+            batch_options
+        }
+    }
+
+    #[napi(object)]
+    /// 批量查询中一个服务器的结果
+    pub struct PingResult {
+        /// 传入的地址
+        pub address: String,
+        /// 服务器信息，查询失败时为空
+        pub info: Option<A2sServerInfo>,
+        /// 查询失败的原因，格式与其他接口的错误信息相同，如 `[TIMEOUT] a2s_info 1.2.3.4:27015: no response`
+        pub error: Option<String>,
+        /// 实际发出的查询次数
+        pub attempts: u32,
+    }
+
+    impl From<BatchResult> for PingResult {
+        fn from(result: BatchResult) -> Self {
+            let (info, error) = match result.result {
+                Ok(info) => (
+                    Some(A2sServerInfo::from_info(result.address.clone(), info)),
+                    None,
+                ),
+                Err(error) => (
+                    None,
+                    Some(error.context("a2s_info", &result.address).to_string()),
+                ),
+            };
+            Self {
+                address: result.address,
+                info,
+                error,
+                attempts: result.attempts,
+            }
+        }
+    }
+
+    #[napi(object)]
+    /// 批量查询汇总
+    pub struct PingSummary {
+        /// 查询的服务器数量
+        pub total: u32,
+        /// 有回复的服务器数量
+        pub answered: u32,
+        /// 查询失败的服务器数量
+        pub failed: u32,
+        /// 总耗时（毫秒）
+        pub elapsed_ms: f64,
+    }
+
+    #[napi(ts_return_type = "Promise<PingSummary>", catch_unwind)]
+    /// 并发查询多个服务器的信息（A2S_INFO）
+    ///
+    /// 每个服务器回复或最终失败时立即调用 onResult，调用顺序为完成顺序。
+    /// 所有服务器完成后返回的 Promise 才会完成。
+    ///
+    /// @param addresses 服务器地址列表
+    /// @param options 批量查询选项
+    /// @param on_result 每个服务器的查询结果
+    /// @returns 查询汇总
+    pub fn ping_servers(
+        env: Env,
+        addresses: Vec<String>,
+        options: Option<BatchQueryOptions>,
+        #[napi(ts_arg_type = "(result: PingResult) => void")] on_result: JsFunction,
+    ) -> Result<JsObject, Error> {
+        let on_result: ThreadsafeFunction<PingResult, ErrorStrategy::Fatal> =
+            on_result.create_threadsafe_function(0, |ctx| Ok(vec![ctx.value]))?;
+        let options = batch::BatchOptions::from(options);
+        let total = addresses.len() as u32;
+
+        env.spawn_future(async move {
+            crate::error::catch_panic_async("ping_servers", async move {
+                let start = Instant::now();
+                let answered = Arc::new(AtomicU32::new(0));
+                let counter = answered.clone();
+                batch::query_all(addresses, options, move |result| {
+                    if result.result.is_ok() {
+                        counter.fetch_add(1, Ordering::Relaxed);
+                    }
+                    on_result.call(result.into(), ThreadsafeFunctionCallMode::NonBlocking);
+                })
+                .await;
+
+                let answered = answered.load(Ordering::Relaxed);
+                Ok(PingSummary {
+                    total,
+                    answered,
+                    failed: total - answered,
+                    elapsed_ms: start.elapsed().as_secs_f64() * 1000.0,
+                })
+            })
+            .await?
+        })
+    }
+
+    #[napi(object)]
+    /// 局域网服务器发现选项
+    pub struct LanDiscoveryOptions {
+        /// 广播地址，默认 `255.255.255.255`，也可以是子网广播地址或单个主机
+        pub broadcast_address: Option<String>,
+        /// 广播的起始端口，默认 27015
+        pub port_start: Option<u16>,
+        /// 广播的结束端口（包含），默认 27020，不能小于起始端口
+        pub port_end: Option<u16>,
+        /// 等待回复的时间（毫秒），默认 1500
+        pub duration_ms: Option<u32>,
+    }
+
+    impl TryFrom<Option<LanDiscoveryOptions>> for DiscoveryOptions {
+        type Error = Error;
+
+        fn try_from(options: Option<LanDiscoveryOptions>) -> Result<Self, Self::Error> {
+            let mut discovery = DiscoveryOptions::default();
+            let Some(options) = options else {
+                return Ok(discovery);
+            };
+
+            if let Some(address) = options.broadcast_address {
+                discovery.target = address.trim().parse::<Ipv4Addr>().map_err(|_| {
+                    SteamworksError::InvalidArgument {
+                        name: "broadcastAddress",
+                        reason: format!("{:?} is not an IPv4 address", address),
+                    }
+                })?;
+            }
+            let start = options.port_start.unwrap_or(*discovery.ports.start());
+            let end = options
+                .port_end
+                .unwrap_or(start.max(*discovery.ports.end()));
+            if end < start {
+                return Err(SteamworksError::InvalidArgument {
+                    name: "portEnd",
+                    reason: format!("{} is less than portStart {}", end, start),
+                }
+                .into());
+            }
+            discovery.ports = start..=end;
+            if let Some(duration_ms) = options.duration_ms {
+                discovery.duration = Duration::from_millis(u64::from(duration_ms));
+            }
+            Ok(discovery)
+        }
+    }
+
+    #[napi]
+    /// 在局域网中发现服务器：向子网广播 A2S_INFO，等待一段时间后返回收到的所有回复
+    ///
+    /// @param options 发现选项
+    /// @returns 去重后的服务器，按延迟排序，广播地址或端口范围无效时抛出 INVALID_ARGUMENT
+    pub async fn discover_lan(
+        options: Option<LanDiscoveryOptions>,
+    ) -> Result<Vec<A2sServerInfo>, Error> {
+        crate::error::catch_panic_async("a2s_discover", async move {
+            let options = DiscoveryOptions::try_from(options)?;
+            let servers = lan::discover(&options)
+                .await
+                .map_err(|e| query_error("a2s_discover", &options.target.to_string(), e))?;
+            steam_log!(
+                Debug,
+                "a2s",
+                "a2s_discover {} ports {:?}: {} servers",
+                options.target,
+                options.ports,
+                servers.len()
+            );
+            Ok(servers
+                .into_iter()
+                .map(|server| A2sServerInfo::from_info(server.address.to_string(), server.info))
+                .collect())
+        })
+        .await?
+    }
+}
@@ -1,8 +1,12 @@
+use super::payloads::CallbackPayload;
 use napi::bindgen_prelude::{FromNapiValue, ToNapiValue};
-use napi::threadsafe_function::{ErrorStrategy, ThreadsafeFunction, ThreadsafeFunctionCallMode};
 use napi_derive::napi;
+use std::collections::VecDeque;
+use std::sync::{Condvar, Mutex, PoisonError};
+use tokio::sync::Notify;
 
 #[napi]
+#[derive(Debug, PartialEq, Eq, Hash)]
 /// ISteamUser 接口回调
 pub enum SteamApiCallback {
     /// 好友的状态改变时调用
@@ -29,85 +33,161 @@ pub enum SteamApiCallback {
     P2PSessionConnectFail,
     /// 在用户响应了小额交易授权请求时调用
     MicroTxnAuthorizationResponse,
+    /// 当前用户的统计与成就数据已从服务器取得
+    UserStatsReceived,
+    /// 统计与成就已上传到服务器
+    UserStatsStored,
+    /// 创意工坊物品下载完成（成功或失败）
+    DownloadItemResult,
+    /// 创意工坊物品已安装或更新
+    ItemInstalled,
+    /// Steam 界面打开或关闭
+    GameOverlayActivated,
+    /// 用户通过好友列表接受了富状态邀请或加入了好友的游戏
+    GameRichPresenceJoinRequested,
+    /// BeginAuthSession 验证票证的结果
+    ValidateAuthTicketResponse,
+    /// 进入大厅，CreateLobby、JoinLobby 之后触发
+    LobbyEnter,
+    /// 大厅收到聊天消息
+    LobbyChatMsg,
+    /// 用户头像加载完成，此时可以获取头像图片
+    AvatarImageLoaded,
+    /// 浮动游戏手柄文本输入框已关闭
+    FloatingGamepadTextInputDismissed,
+    /// Steam 客户端即将关闭，应尽快保存数据并退出
+    SteamShutdown,
+    /// 插件事件：Steam 会话已切换（重新初始化或切换了 AppId），已注册的回调会自动迁移到新的会话
+    SessionChanged,
 }
 
-impl SteamApiCallback {
-    pub(crate) fn set(
-        cb: SteamApiCallback,
-        handler: ThreadsafeFunction<serde_json::Value, ErrorStrategy::Fatal>,
-    ) -> steamworks::CallbackHandle {
-        match cb {
-            SteamApiCallback::PersonaStateChange => {
-                Self::register_callback::<steamworks::PersonaStateChange>(handler)
-            }
-            SteamApiCallback::SteamServersConnected => {
-                Self::register_callback::<steamworks::SteamServersConnected>(handler)
-            }
-            SteamApiCallback::SteamServersDisconnected => {
-                Self::register_callback::<steamworks::SteamServersDisconnected>(handler)
-            }
-            SteamApiCallback::SteamServerConnectFailure => {
-                Self::register_callback::<steamworks::SteamServerConnectFailure>(handler)
-            }
-            SteamApiCallback::LobbyDataUpdate => {
-                Self::register_callback::<steamworks::LobbyDataUpdate>(handler)
-            }
-            SteamApiCallback::LobbyChatUpdate => {
-                Self::register_callback::<steamworks::LobbyChatUpdate>(handler)
-            }
-            SteamApiCallback::P2PSessionRequest => {
-                Self::register_callback::<steamworks::P2PSessionRequest>(handler)
-            }
-            SteamApiCallback::P2PSessionConnectFail => {
-                Self::register_callback::<steamworks::P2PSessionConnectFail>(handler)
-            }
-            SteamApiCallback::GameLobbyJoinRequested => {
-                Self::register_callback::<steamworks::GameLobbyJoinRequested>(handler)
+#[napi]
+/// 事件流缓冲区已满时的处理方式
+pub enum OverflowPolicy {
+    /// 丢弃最早的事件
+    DropOldest,
+    /// 丢弃新到达的事件
+    DropNewest,
+    /// 阻塞回调分发线程，直到事件被取走或事件流关闭
+    Block,
+}
+
+#[derive(Default)]
+struct QueueState {
+    events: VecDeque<CallbackPayload>,
+    closed: bool,
+    dropped: u64,
+}
+
+/// 事件流的有界缓冲区，回调分发线程写入，JS 异步读取
+struct EventQueue {
+    state: Mutex<QueueState>,
+    capacity: usize,
+    policy: OverflowPolicy,
+    /// 缓冲区有空位或事件流关闭，唤醒阻塞的分发线程
+    space: Condvar,
+    /// 有新事件或事件流关闭，唤醒等待中的 next
+    ready: Notify,
+}
+
+impl EventQueue {
+    fn new(capacity: usize, policy: OverflowPolicy) -> Self {
+        Self {
+            state: Mutex::new(QueueState::default()),
+            capacity: capacity.max(1),
+            policy,
+            space: Condvar::new(),
+            ready: Notify::new(),
+        }
+    }
+
+    fn push(&self, value: CallbackPayload) {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        while !state.closed && state.events.len() >= self.capacity {
+            match self.policy {
+                OverflowPolicy::DropOldest => {
+                    state.events.pop_front();
+                    state.dropped += 1;
+                }
+                OverflowPolicy::DropNewest => {
+                    state.dropped += 1;
+                    return;
+                }
+                OverflowPolicy::Block => {
+                    state = self
+                        .space
+                        .wait(state)
+                        .unwrap_or_else(PoisonError::into_inner)
+                }
             }
-            SteamApiCallback::MicroTxnAuthorizationResponse => {
-                Self::register_callback::<steamworks::MicroTxnAuthorizationResponse>(handler)
+        }
+
+        if state.closed {
+            return;
+        }
+        state.events.push_back(value);
+        drop(state);
+        self.ready.notify_one();
+    }
+
+    /// 取出下一个事件，事件流关闭后返回 `None`
+    async fn pop(&self) -> Option<CallbackPayload> {
+        loop {
+            let ready = self.ready.notified();
+            {
+                let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+                if state.closed {
+                    return None;
+                }
+                if let Some(value) = state.events.pop_front() {
+                    self.space.notify_one();
+                    return Some(value);
+                }
             }
+            ready.await;
         }
     }
 
-    fn register_callback<C>(
-        threadsafe_handler: ThreadsafeFunction<serde_json::Value, ErrorStrategy::Fatal>,
-    ) -> steamworks::CallbackHandle
-    where
-        C: steamworks::Callback + serde::Serialize,
-    {
-        let client = crate::client::get_client();
-        client.register_callback(move |value: C| {
-            let value = serde_json::to_value(&value).unwrap();
-            threadsafe_handler.call(value, ThreadsafeFunctionCallMode::Blocking);
-        })
+    fn close(&self) {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        state.closed = true;
+        state.events.clear();
+        drop(state);
+        self.space.notify_all();
+        self.ready.notify_waiters();
     }
 }
 
 #[napi]
 pub mod callback {
-    use super::SteamApiCallback;
+    use super::{CallbackPayload, EventQueue, OverflowPolicy, SteamApiCallback};
+    use crate::client::Subscription;
     use napi::{
-        threadsafe_function::{ErrorStrategy, ThreadsafeFunction},
-        JsFunction,
+        threadsafe_function::{ErrorStrategy, ThreadsafeFunction, ThreadsafeFunctionCallMode},
+        Error, JsFunction,
     };
+    use std::sync::{Arc, PoisonError};
+
+    /// 事件流默认缓冲区大小
+    const DEFAULT_CAPACITY: u32 = 256;
 
     #[napi]
     pub struct Handle {
-        handle: Option<steamworks::CallbackHandle>,
+        handle: Option<Subscription>,
     }
 
     #[napi]
     impl Handle {
-        #[napi]
+        #[napi(catch_unwind)]
         pub fn disconnect(&mut self) {
-            if let Some(handle) = self.handle.take() {
-                handle.disconnect();
-            }
+            self.handle.take();
         }
     }
 
-    #[napi(ts_generic_types = "C extends keyof import('./callbacks').CallbackReturns")]
+    #[napi(
+        ts_generic_types = "C extends keyof import('./callbacks').CallbackReturns",
+        catch_unwind
+    )]
     /// 注册该用户操作回调
     ///
     /// @param steam_callback Steam用户操作回调
@@ -115,16 +195,141 @@ pub mod callback {
     pub fn register_callback(
         #[napi(ts_arg_type = "C")] steam_callback: SteamApiCallback,
         #[napi(ts_arg_type = "(value: import('./callbacks').CallbackReturns[C]) => void")] handler: JsFunction,
-    ) -> Handle {
-        let threadsafe_handler: ThreadsafeFunction<serde_json::Value, ErrorStrategy::Fatal> =
-            handler
-                .create_threadsafe_function(0, |ctx| Ok(vec![ctx.value]))
-                .unwrap();
+    ) -> Result<Handle, Error> {
+        let threadsafe_handler: ThreadsafeFunction<CallbackPayload, ErrorStrategy::Fatal> =
+            handler.create_threadsafe_function(0, |ctx| Ok(vec![ctx.value]))?;
 
-        let handle = SteamApiCallback::set(steam_callback, threadsafe_handler);
+        // 注册在切换 AppId 后会自动迁移到新的会话
+        let handle = crate::client::subscribe(steam_callback, move |value| {
+            threadsafe_handler.call(value, ThreadsafeFunctionCallMode::Blocking);
+        })?;
 
-        Handle {
+        Ok(Handle {
             handle: Some(handle),
+        })
+    }
+
+    #[napi(object)]
+    /// 事件流选项
+    pub struct EventStreamOptions {
+        /// 缓冲区最多保存的事件数量，默认 256
+        pub capacity: Option<u32>,
+        /// 缓冲区已满时的处理方式，默认丢弃最早的事件
+        pub overflow: Option<OverflowPolicy>,
+    }
+
+    #[napi(object, object_from_js = false)]
+    /// 事件流迭代结果，与 JS 异步迭代器协议一致
+    pub struct EventStreamResult {
+        pub done: bool,
+        #[napi(ts_type = "unknown")]
+        pub value: Option<CallbackPayload>,
+    }
+
+    #[napi]
+    /// 回调事件流，可以用 for await 逐个读取事件
+    pub struct EventStream {
+        queue: Arc<EventQueue>,
+        handle: Option<Subscription>,
+    }
+
+    #[napi]
+    impl EventStream {
+        #[napi]
+        /// 读取下一个事件，没有事件时等待；事件流关闭后 done 为 true
+        pub async fn next(&self) -> EventStreamResult {
+            let value = self.queue.pop().await;
+            EventStreamResult {
+                done: value.is_none(),
+                value,
+            }
+        }
+
+        #[napi(js_name = "return", catch_unwind)]
+        /// 关闭事件流，for await 循环提前退出时自动调用
+        pub fn finish(&mut self) -> EventStreamResult {
+            self.close();
+            EventStreamResult {
+                done: true,
+                value: None,
+            }
+        }
+
+        #[napi(catch_unwind)]
+        /// 关闭事件流，注销回调并丢弃缓冲区中的事件，等待中的 next 立即结束
+        pub fn close(&mut self) {
+            self.handle.take();
+            self.queue.close();
+        }
+
+        #[napi(getter, catch_unwind)]
+        /// 因缓冲区已满被丢弃的事件数量
+        pub fn dropped(&self) -> i64 {
+            self.queue
+                .state
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .dropped as i64
         }
+
+        #[napi(getter, catch_unwind)]
+        /// 缓冲区中尚未读取的事件数量
+        pub fn pending(&self) -> u32 {
+            self.queue
+                .state
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .events
+                .len() as u32
+        }
+
+        #[napi(getter, catch_unwind)]
+        /// 事件流是否已关闭
+        pub fn closed(&self) -> bool {
+            self.queue
+                .state
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .closed
+        }
+    }
+
+    impl Drop for EventStream {
+        fn drop(&mut self) {
+            // 被回收时唤醒可能因 Block 策略阻塞的分发线程
+            self.close();
+        }
+    }
+
+    #[napi(
+        ts_generic_types = "C extends keyof import('./callbacks').CallbackReturns",
+        catch_unwind
+    )]
+    /// 以事件流的方式订阅回调，事件写入有界缓冲区，不会因 JS 处理缓慢而阻塞回调分发线程
+    ///
+    /// @param steam_callback Steam用户操作回调
+    /// @param options 缓冲区大小与溢出处理方式
+    /// @returns 事件流
+    pub fn stream_callback(
+        #[napi(ts_arg_type = "C")] steam_callback: SteamApiCallback,
+        options: Option<EventStreamOptions>,
+    ) -> Result<EventStream, Error> {
+        let (capacity, overflow) = options
+            .map(|options| (options.capacity, options.overflow))
+            .unwrap_or_default();
+        let queue = Arc::new(EventQueue::new(
+            capacity.unwrap_or(DEFAULT_CAPACITY) as usize,
+            overflow.unwrap_or(OverflowPolicy::DropOldest),
+        ));
+
+        let handle = {
+            let queue = queue.clone();
+            crate::client::subscribe(steam_callback, move |value| queue.push(value))?
+        };
+
+        Ok(EventStream {
+            queue,
+            handle: Some(handle),
+        })
     }
 }
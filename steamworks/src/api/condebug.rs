@@ -0,0 +1,230 @@
+use crate::condebug::line::ConsoleEventKind;
+use napi_derive::napi;
+
+#[napi]
+/// 控制台事件类型
+pub enum EConsoleEvent {
+    /// 连接到服务器
+    Connected,
+    /// 连接后服务器信息中的地图
+    Map,
+    /// 击杀信息
+    Kill,
+    /// 自杀
+    Suicide,
+    /// 聊天
+    Chat,
+    /// 本回合对其他玩家造成的伤害，CS:GO 在死亡或回合结束时打印
+    DamageGiven,
+    /// 本回合受到其他玩家的伤害
+    DamageTaken,
+    /// 无法识别的行
+    Other,
+}
+
+impl From<&ConsoleEventKind> for EConsoleEvent {
+    fn from(value: &ConsoleEventKind) -> Self {
+        match value {
+            ConsoleEventKind::Connected { .. } => EConsoleEvent::Connected,
+            ConsoleEventKind::Map { .. } => EConsoleEvent::Map,
+            ConsoleEventKind::Kill { .. } => EConsoleEvent::Kill,
+            ConsoleEventKind::Suicide { .. } => EConsoleEvent::Suicide,
+            ConsoleEventKind::Chat { .. } => EConsoleEvent::Chat,
+            ConsoleEventKind::DamageGiven { .. } => EConsoleEvent::DamageGiven,
+            ConsoleEventKind::DamageTaken { .. } => EConsoleEvent::DamageTaken,
+            ConsoleEventKind::Other => EConsoleEvent::Other,
+        }
+    }
+}
+
+#[napi]
+/// 跟踪以 `-condebug` 启动的游戏输出的 `console.log`，不需要初始化 Steam
+pub mod condebug {
+    use super::EConsoleEvent;
+    use crate::condebug::line::{ConsoleEventKind, ConsoleLine};
+    use crate::condebug::{self, TailOptions, Tailer};
+    use crate::hllog::line::LogTime;
+    use napi::bindgen_prelude::Error;
+    use napi::threadsafe_function::{
+        ErrorStrategy, ThreadsafeFunction, ThreadsafeFunctionCallMode,
+    };
+    use napi::JsFunction;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[napi(object)]
+    /// 控制台的一行，只有与事件类型相关的字段有值
+    pub struct ConsoleEvent {
+        pub kind: EConsoleEvent,
+        /// 本地时间，格式为 `2026-10-18T12:34:56`，只有 TF2 的行有
+        pub time: Option<String>,
+        /// 去掉时间前缀后的完整内容
+        pub message: String,
+        /// 服务器地址，仅 Connected
+        pub server: Option<String>,
+        /// 地图，仅 Map
+        pub map: Option<String>,
+        /// 击杀者，仅 Kill
+        pub killer: Option<String>,
+        /// 被击杀的玩家，仅 Kill
+        pub victim: Option<String>,
+        /// 武器，如 `scattergun`，仅 Kill
+        pub weapon: Option<String>,
+        /// 是否暴击，仅 TF2 的 Kill
+        pub critical: Option<bool>,
+        /// 自杀、发言或伤害统计中的另一方玩家
+        pub player: Option<String>,
+        /// 聊天内容，仅 Chat
+        pub text: Option<String>,
+        /// 是否为队伍聊天，仅 Chat
+        pub team_only: Option<bool>,
+        /// 发言的玩家是否已经死亡或在观战，仅 Chat
+        pub dead: Option<bool>,
+        /// CS:GO 队伍聊天中发言玩家的位置，仅 Chat
+        pub location: Option<String>,
+        /// 伤害，DamageGiven 与 DamageTaken
+        pub damage: Option<u32>,
+        /// 命中次数，DamageGiven 与 DamageTaken
+        pub hits: Option<u32>,
+    }
+
+    impl From<ConsoleLine> for ConsoleEvent {
+        fn from(line: ConsoleLine) -> Self {
+            let mut event = Self {
+                kind: EConsoleEvent::from(&line.kind),
+                time: line.time.as_ref().map(LogTime::to_iso_string),
+                message: line.message,
+                server: None,
+                map: None,
+                killer: None,
+                victim: None,
+                weapon: None,
+                critical: None,
+                player: None,
+                text: None,
+                team_only: None,
+                dead: None,
+                location: None,
+                damage: None,
+                hits: None,
+            };
+            match line.kind {
+                ConsoleEventKind::Connected { server } => event.server = Some(server),
+                ConsoleEventKind::Map { map } => event.map = Some(map),
+                ConsoleEventKind::Kill {
+                    killer,
+                    victim,
+                    weapon,
+                    critical,
+                } => {
+                    event.killer = Some(killer);
+                    event.victim = Some(victim);
+                    event.weapon = Some(weapon);
+                    event.critical = Some(critical);
+                }
+                ConsoleEventKind::Suicide { player } => event.player = Some(player),
+                ConsoleEventKind::Chat {
+                    player,
+                    text,
+                    team_only,
+                    dead,
+                    location,
+                } => {
+                    event.player = Some(player);
+                    event.text = Some(text);
+                    event.team_only = Some(team_only);
+                    event.dead = Some(dead);
+                    event.location = location;
+                }
+                ConsoleEventKind::DamageGiven {
+                    player,
+                    damage,
+                    hits,
+                }
+                | ConsoleEventKind::DamageTaken {
+                    player,
+                    damage,
+                    hits,
+                } => {
+                    event.player = Some(player);
+                    event.damage = Some(damage);
+                    event.hits = Some(hits);
+                }
+                ConsoleEventKind::Other => {}
+            }
+            event
+        }
+    }
+
+    #[napi(object)]
+    /// 跟踪选项
+    pub struct ConsoleTailOptions {
+        /// 从文件开头读取，默认只读取开始跟踪后新增的行
+        pub from_start: Option<bool>,
+        /// 检查文件的间隔（毫秒），默认 250
+        pub poll_interval_ms: Option<u32>,
+    }
+
+    #[napi]
+    /// 控制台日志跟踪器，停止或被回收时停止读取
+    pub struct ConsoleTailer {
+        /// 跟踪的文件
+        pub path: String,
+        tailer: Arc<Tailer>,
+    }
+
+    #[napi]
+    impl ConsoleTailer {
+        #[napi(catch_unwind)]
+        /// 停止跟踪
+        ///
+        /// @noreturns
+        pub fn stop(&self) {
+            self.tailer.stop();
+        }
+    }
+
+    #[napi(catch_unwind)]
+    /// 跟踪 `console.log`，文件被清空或删除后重新创建时从头读取
+    ///
+    /// @param path 文件路径，如 `steamapps/common/Team Fortress 2/tf/console.log`，文件可以还不存在
+    /// @param options 跟踪选项
+    /// @param on_event 每读到一行时调用
+    /// @returns 跟踪器
+    pub fn tail(
+        path: String,
+        options: Option<ConsoleTailOptions>,
+        #[napi(ts_arg_type = "(event: ConsoleEvent) => void")] on_event: JsFunction,
+    ) -> Result<ConsoleTailer, Error> {
+        let mut tail_options = TailOptions::default();
+        if let Some(options) = options {
+            tail_options.from_start = options.from_start.unwrap_or_default();
+            if let Some(interval) = options.poll_interval_ms {
+                tail_options.poll_interval = Duration::from_millis(u64::from(interval.max(10)));
+            }
+        }
+
+        let on_event: ThreadsafeFunction<ConsoleEvent, ErrorStrategy::Fatal> =
+            on_event.create_threadsafe_function(0, |ctx| Ok(vec![ctx.value]))?;
+        let handler: condebug::LineHandler = Arc::new(move |line: ConsoleLine| {
+            on_event.call(line.into(), ThreadsafeFunctionCallMode::NonBlocking);
+        });
+
+        let tailer = napi::bindgen_prelude::within_runtime_if_available(|| {
+            Tailer::start(path.clone(), tail_options, handler)
+        });
+        Ok(ConsoleTailer {
+            path,
+            tailer: Arc::new(tailer),
+        })
+    }
+
+    #[napi(catch_unwind)]
+    /// 解析一行控制台输出
+    ///
+    /// @param line 控制台输出的一行，如 `alice killed bob with scattergun.`
+    /// @returns 解析后的事件，无法识别时类型为 Other
+    pub fn parse_line(line: String) -> ConsoleEvent {
+        ConsoleLine::parse(&line).into()
+    }
+}
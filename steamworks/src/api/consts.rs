@@ -3,8 +3,8 @@ use std::fmt;
 use napi::bindgen_prelude::{BigInt, FromNapiValue, ToNapiValue};
 use napi_derive::napi;
 use steamworks::{
-    FriendFlags, FriendState, LobbyType, OverlayToStoreFlag, PublishedFileVisibility, SendType,
-    SteamId, UpdateStatus,
+    AuthSessionError, ChatMemberStateChange, FriendFlags, FriendState, LobbyType,
+    OverlayToStoreFlag, PublishedFileVisibility, SendType, SteamId, UpdateStatus,
 };
 
 #[napi]
@@ -88,6 +88,20 @@ impl From<FriendState> for EPersonaState {
     }
 }
 
+impl From<EPersonaState> for FriendState {
+    fn from(state: EPersonaState) -> Self {
+        match state {
+            EPersonaState::Offline => Self::Offline,
+            EPersonaState::Online => Self::Online,
+            EPersonaState::Busy => Self::Busy,
+            EPersonaState::Away => Self::Away,
+            EPersonaState::Snooze => Self::Snooze,
+            EPersonaState::LookingToPlay => Self::LookingToPlay,
+            EPersonaState::LookingToTrade => Self::LookingToTrade,
+        }
+    }
+}
+
 #[napi]
 /// 指定物品状态。 这些标记可以组合使用。 通过 GetItemState 返回。
 pub enum EItemState {
@@ -237,6 +251,72 @@ impl From<ELobbyType> for LobbyType {
     }
 }
 
+#[napi]
+/// 大厅成员状态的变化，通过 LobbyChatUpdate 回调发送
+pub enum EChatMemberStateChange {
+    /// 该用户已经或正在加入大厅
+    Entered,
+    /// 该用户已经或正在离开大厅
+    Left,
+    /// 用户未先离开大厅就断开了连接
+    Disconnected,
+    /// 用户已被踢出
+    Kicked,
+    /// 用户已被踢出并封禁
+    Banned,
+}
+
+impl From<&ChatMemberStateChange> for EChatMemberStateChange {
+    fn from(change: &ChatMemberStateChange) -> Self {
+        match change {
+            ChatMemberStateChange::Entered => Self::Entered,
+            ChatMemberStateChange::Left => Self::Left,
+            ChatMemberStateChange::Disconnected => Self::Disconnected,
+            ChatMemberStateChange::Kicked => Self::Kicked,
+            ChatMemberStateChange::Banned => Self::Banned,
+        }
+    }
+}
+
+#[napi]
+/// BeginAuthSession 验证票证失败的原因，通过 ValidateAuthTicketResponse 回调发送
+pub enum EAuthSessionResponse {
+    /// 用户未连接到 Steam
+    UserNotConnectedToSteam,
+    /// 用户没有许可证或许可证已过期
+    NoLicenseOrExpired,
+    /// 用户被此游戏的 VAC 封禁
+    VACBanned,
+    /// 用户帐户已在其他位置登录，且会话连接已关闭
+    LoggedInElseWhere,
+    /// VAC 无法对该用户进行反作弊检查
+    VACCheckTimedOut,
+    /// 票证已被发行者取消
+    TicketCancelled,
+    /// 票证已被使用，无效
+    TicketAlreadyUsed,
+    /// 该票证并非来自连接至 Steam 的用户实例
+    TicketInvalid,
+    /// 用户被此游戏封禁，由发行者设置封禁而不是 VAC
+    PublisherIssuedBan,
+}
+
+impl From<&AuthSessionError> for EAuthSessionResponse {
+    fn from(error: &AuthSessionError) -> Self {
+        match error {
+            AuthSessionError::UserNotConnectedToSteam => Self::UserNotConnectedToSteam,
+            AuthSessionError::NoLicenseOrExpired => Self::NoLicenseOrExpired,
+            AuthSessionError::VACBanned => Self::VACBanned,
+            AuthSessionError::LoggedInElseWhere => Self::LoggedInElseWhere,
+            AuthSessionError::VACCheckTimedOut => Self::VACCheckTimedOut,
+            AuthSessionError::TicketCancelled => Self::TicketCancelled,
+            AuthSessionError::TicketAlreadyUsed => Self::TicketAlreadyUsed,
+            AuthSessionError::TicketInvalid => Self::TicketInvalid,
+            AuthSessionError::PublisherIssuedBan => Self::PublisherIssuedBan,
+        }
+    }
+}
+
 #[napi]
 /// 需要打开的对话框类型
 pub enum PSteamDialog {
@@ -270,7 +350,7 @@ impl fmt::Display for PSteamDialog {
     }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 #[napi(object)]
 /// Steam 游戏在全局中的唯一标识符
 pub struct CSteamID {
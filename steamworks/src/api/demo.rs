@@ -0,0 +1,401 @@
+use crate::demo::packet::DemoEventKind;
+use napi_derive::napi;
+
+#[napi]
+/// 录像事件类型
+pub enum EDemoEvent {
+    /// 玩家连接
+    PlayerConnected,
+    /// 玩家断开连接
+    PlayerDisconnected,
+    /// 击杀
+    Kill,
+    /// 回合结束
+    RoundEnded,
+}
+
+impl From<&DemoEventKind> for EDemoEvent {
+    fn from(value: &DemoEventKind) -> Self {
+        match value {
+            DemoEventKind::PlayerConnected { .. } => EDemoEvent::PlayerConnected,
+            DemoEventKind::PlayerDisconnected { .. } => EDemoEvent::PlayerDisconnected,
+            DemoEventKind::Kill { .. } => EDemoEvent::Kill,
+            DemoEventKind::RoundEnded { .. } => EDemoEvent::RoundEnded,
+        }
+    }
+}
+
+#[napi]
+/// 读取 Source 1 录像（.dem），不需要初始化 Steam
+pub mod demo {
+    use super::EDemoEvent;
+    use crate::api::logging::steam_log;
+    use crate::demo::packet::{DemoEvent as Event, DemoEventKind, Player};
+    use crate::demo::{self, DemoError, DemoHeader as Header, RoundResult, Summary};
+    use crate::error::SteamworksError;
+    use napi::bindgen_prelude::{BigInt, Error};
+    use napi::threadsafe_function::{
+        ErrorStrategy, ThreadsafeFunction, ThreadsafeFunctionCallMode,
+    };
+    use napi::{Env, JsFunction, JsObject};
+    use std::fs::File;
+    use std::io::{BufReader, Read};
+    use std::path::Path;
+
+    #[napi(object)]
+    /// 录像文件头
+    pub struct DemoHeader {
+        /// 录像格式版本，CS:GO、L4D 为 4，其他游戏为 3
+        pub demo_protocol: i32,
+        /// 录制时的网络协议版本
+        pub network_protocol: i32,
+        /// 服务器名称，SourceTV 录像为服务器的地址
+        pub server_name: String,
+        /// 录制者的名称，SourceTV 录像为 `tv_name`
+        pub client_name: String,
+        pub map_name: String,
+        /// 游戏目录，如 `csgo`、`cstrike`、`tf`
+        pub game_directory: String,
+        /// 时长（秒），录制中断时为 0
+        pub playback_time: f64,
+        /// tick 数，录制中断时为 0
+        pub ticks: i32,
+        /// 帧数，录制中断时为 0
+        pub frames: i32,
+    }
+
+    impl From<Header> for DemoHeader {
+        fn from(header: Header) -> Self {
+            Self {
+                demo_protocol: header.demo_protocol,
+                network_protocol: header.network_protocol,
+                server_name: header.server_name,
+                client_name: header.client_name,
+                map_name: header.map_name,
+                game_directory: header.game_directory,
+                playback_time: f64::from(header.playback_time),
+                ticks: header.ticks,
+                frames: header.frames,
+            }
+        }
+    }
+
+    #[napi(object)]
+    /// 录像中的玩家
+    pub struct DemoPlayer {
+        /// 服务器分配的用户 ID
+        pub user_id: i32,
+        pub name: String,
+        /// `STEAM_1:0:123` 或 `BOT`，只从事件中得知用户 ID 的玩家为空字符串
+        pub steam_id: String,
+        /// 机器人与未知的玩家为空
+        pub steam_id64: Option<BigInt>,
+        pub bot: bool,
+    }
+
+    impl From<Player> for DemoPlayer {
+        fn from(player: Player) -> Self {
+            Self {
+                user_id: player.user_id,
+                name: player.name,
+                steam_id: player.steam_id,
+                steam_id64: (player.steam_id64 != 0).then(|| BigInt::from(player.steam_id64)),
+                bot: player.bot,
+            }
+        }
+    }
+
+    #[napi(object)]
+    /// 录像中的事件，只有与事件类型相关的字段有值
+    pub struct DemoEvent {
+        pub kind: EDemoEvent,
+        pub tick: i32,
+        /// 连接或断开连接的玩家
+        pub player: Option<DemoPlayer>,
+        /// 击杀者，被世界击杀或自杀时为空，仅 Kill
+        pub attacker: Option<DemoPlayer>,
+        /// 被击杀的玩家，仅 Kill
+        pub victim: Option<DemoPlayer>,
+        /// 助攻的玩家，仅 Kill
+        pub assister: Option<DemoPlayer>,
+        /// 武器，如 `ak47`，仅 Kill
+        pub weapon: Option<String>,
+        pub headshot: Option<bool>,
+        /// 是否穿墙击杀，仅 Kill
+        pub penetrated: Option<bool>,
+        /// 断开连接的原因，仅 PlayerDisconnected
+        pub reason: Option<String>,
+        /// 获胜的队伍，2 为 T，3 为 CT，仅 RoundEnded
+        pub winner: Option<i32>,
+        /// 回合结束原因，见游戏的 `CSRoundEndReason`，仅 RoundEnded
+        pub end_reason: Option<i32>,
+        /// 回合结束消息，如 `#SFUI_Notice_CTs_Win`，仅 RoundEnded
+        pub message: Option<String>,
+    }
+
+    impl From<Event> for DemoEvent {
+        fn from(event: Event) -> Self {
+            let mut result = Self {
+                kind: EDemoEvent::from(&event.kind),
+                tick: event.tick,
+                player: None,
+                attacker: None,
+                victim: None,
+                assister: None,
+                weapon: None,
+                headshot: None,
+                penetrated: None,
+                reason: None,
+                winner: None,
+                end_reason: None,
+                message: None,
+            };
+            match event.kind {
+                DemoEventKind::PlayerConnected { player } => result.player = Some(player.into()),
+                DemoEventKind::PlayerDisconnected { player, reason } => {
+                    result.player = Some(player.into());
+                    result.reason = Some(reason);
+                }
+                DemoEventKind::Kill {
+                    attacker,
+                    victim,
+                    assister,
+                    weapon,
+                    headshot,
+                    penetrated,
+                } => {
+                    result.attacker = attacker.map(DemoPlayer::from);
+                    result.victim = Some(victim.into());
+                    result.assister = assister.map(DemoPlayer::from);
+                    result.weapon = Some(weapon);
+                    result.headshot = Some(headshot);
+                    result.penetrated = Some(penetrated);
+                }
+                DemoEventKind::RoundEnded {
+                    winner,
+                    reason,
+                    message,
+                } => {
+                    result.winner = Some(winner);
+                    result.end_reason = Some(reason);
+                    result.message = Some(message);
+                }
+            }
+            result
+        }
+    }
+
+    #[napi(object)]
+    /// 一回合的结果
+    pub struct DemoRound {
+        /// 回合结束的 tick
+        pub tick: i32,
+        /// 获胜的队伍，2 为 T，3 为 CT
+        pub winner: i32,
+        pub end_reason: i32,
+        pub message: String,
+    }
+
+    impl From<RoundResult> for DemoRound {
+        fn from(round: RoundResult) -> Self {
+            Self {
+                tick: round.tick,
+                winner: round.winner,
+                end_reason: round.reason,
+                message: round.message,
+            }
+        }
+    }
+
+    #[napi(object)]
+    /// 录像汇总
+    pub struct DemoSummary {
+        pub header: DemoHeader,
+        /// 最后一帧的 tick
+        pub last_tick: i32,
+        /// 时长（秒），文件头中没有时按 tick 估算，无法估算时为空
+        pub duration: Option<f64>,
+        /// 每个 tick 的时长（秒），只有 CS:GO 录像有
+        pub tick_interval: Option<f64>,
+        /// 文件是否不完整，录制中断的录像没有结束标记
+        pub truncated: bool,
+        /// 是否解析了游戏事件，只有 CS:GO 录像会解析，其他游戏的玩家、击杀与回合为空
+        pub events_parsed: bool,
+        /// 录像中出现过的玩家，按第一次出现的顺序排列
+        pub players: Vec<DemoPlayer>,
+        pub kills: u32,
+        pub rounds: Vec<DemoRound>,
+    }
+
+    impl From<Summary> for DemoSummary {
+        fn from(summary: Summary) -> Self {
+            Self {
+                duration: summary.duration().map(f64::from),
+                header: summary.header.into(),
+                last_tick: summary.last_tick,
+                tick_interval: summary.tick_interval.map(f64::from),
+                truncated: summary.truncated,
+                events_parsed: summary.events_parsed,
+                players: summary.players.into_iter().map(DemoPlayer::from).collect(),
+                kills: summary.kills,
+                rounds: summary.rounds.into_iter().map(DemoRound::from).collect(),
+            }
+        }
+    }
+
+    fn demo_error(call: &'static str, path: &str, error: DemoError) -> SteamworksError {
+        match error {
+            DemoError::Io(error) => SteamworksError::Io {
+                call,
+                path: path.to_string(),
+                error,
+            },
+            DemoError::Invalid(message) => SteamworksError::Parse {
+                call,
+                path: path.to_string(),
+                message,
+            },
+        }
+    }
+
+    fn read_header(path: &Path) -> Result<Header, DemoError> {
+        let mut data = Vec::with_capacity(demo::HEADER_SIZE);
+        File::open(path)?
+            .take(demo::HEADER_SIZE as u64)
+            .read_to_end(&mut data)?;
+        Header::parse(&data)
+    }
+
+    /// 在阻塞线程中执行，录像通常有几十到几百 MB
+    async fn blocking<T: Send + 'static>(task: impl FnOnce() -> T + Send + 'static) -> T {
+        match tokio::task::spawn_blocking(task).await {
+            Ok(value) => value,
+            // 交给外层的 catch_panic_async 转换为 PANIC 错误
+            Err(error) => std::panic::resume_unwind(error.into_panic()),
+        }
+    }
+
+    #[napi]
+    /// 只读取录像的文件头
+    ///
+    /// @param path 录像路径
+    /// @returns 文件头，不是 Source 1 录像时抛出 PARSE_ERROR
+    pub async fn read_demo_header(path: String) -> Result<DemoHeader, Error> {
+        crate::error::catch_panic_async("read_demo_header", async move {
+            let header = blocking({
+                let path = path.clone();
+                move || read_header(Path::new(&path))
+            })
+            .await
+            .map_err(|error| demo_error("read_demo_header", &path, error))?;
+            Ok(header.into())
+        })
+        .await?
+    }
+
+    #[napi(object)]
+    /// 目录中的一个录像
+    pub struct DemoFile {
+        pub path: String,
+        /// 文件大小（字节）
+        pub size: f64,
+        /// 修改时间，Unix 时间（秒）
+        pub modified: Option<f64>,
+        pub header: DemoHeader,
+    }
+
+    #[napi]
+    /// 列出目录中的所有录像并读取文件头，不会进入子目录，无法读取的文件会被跳过并记录日志
+    ///
+    /// @param dir 目录，如服务器的 `csgo` 目录或 `tv_autorecord` 的保存位置
+    /// @returns 按文件名排序的录像
+    pub async fn list_demos(dir: String) -> Result<Vec<DemoFile>, Error> {
+        crate::error::catch_panic_async("list_demos", async move {
+            let result = blocking({
+                let dir = dir.clone();
+                move || -> std::io::Result<Vec<DemoFile>> {
+                    let mut paths: Vec<_> = std::fs::read_dir(&dir)?
+                        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                        .filter(|path| {
+                            path.extension()
+                                .is_some_and(|extension| extension.eq_ignore_ascii_case("dem"))
+                        })
+                        .collect();
+                    paths.sort();
+
+                    let mut demos = Vec::with_capacity(paths.len());
+                    for path in paths {
+                        let header = match read_header(&path) {
+                            Ok(header) => header,
+                            Err(error) => {
+                                steam_log!(Debug, "demo", "skipping {}: {}", path.display(), error);
+                                continue;
+                            }
+                        };
+                        let metadata = std::fs::metadata(&path).ok();
+                        demos.push(DemoFile {
+                            path: path.to_string_lossy().into_owned(),
+                            size: metadata
+                                .as_ref()
+                                .map_or(0.0, |metadata| metadata.len() as f64),
+                            modified: metadata
+                                .and_then(|metadata| metadata.modified().ok())
+                                .and_then(|time| time.duration_since(std::time::UNIX_EPOCH).ok())
+                                .map(|duration| duration.as_secs_f64()),
+                            header: header.into(),
+                        });
+                    }
+                    Ok(demos)
+                }
+            })
+            .await;
+            let demos = result.map_err(|error| SteamworksError::Io {
+                call: "list_demos",
+                path: dir.clone(),
+                error,
+            })?;
+            Ok(demos)
+        })
+        .await?
+    }
+
+    #[napi(ts_return_type = "Promise<DemoSummary>", catch_unwind)]
+    /// 读取整个录像，按 tick 顺序报告玩家连接、断开连接、击杀与回合结束
+    ///
+    /// 游戏事件只能从 CS:GO 录像中解析，其他游戏只读取文件头与帧，L4D 系列与 Portal 2 的帧格式未知，只读取文件头，汇总中的 eventsParsed 为 false。
+    /// 录制中断的录像会读到文件结束为止，汇总中的 truncated 为 true。
+    ///
+    /// @param path 录像路径
+    /// @param on_event 每个事件时调用，不需要事件时可以省略
+    /// @returns 录像汇总，不是 Source 1 录像或数据损坏时抛出 PARSE_ERROR
+    pub fn parse_demo(
+        env: Env,
+        path: String,
+        #[napi(ts_arg_type = "((event: DemoEvent) => void)")] on_event: Option<JsFunction>,
+    ) -> Result<JsObject, Error> {
+        let on_event: Option<ThreadsafeFunction<DemoEvent, ErrorStrategy::Fatal>> = on_event
+            .map(|on_event| on_event.create_threadsafe_function(0, |ctx| Ok(vec![ctx.value])))
+            .transpose()?;
+
+        env.spawn_future(async move {
+            crate::error::catch_panic_async("parse_demo", async move {
+                let result = blocking({
+                    let path = path.clone();
+                    move || {
+                        let file = File::open(&path)?;
+                        demo::parse(BufReader::new(file), |event| {
+                            if let Some(on_event) = &on_event {
+                                on_event
+                                    .call(event.into(), ThreadsafeFunctionCallMode::NonBlocking);
+                            }
+                        })
+                    }
+                })
+                .await;
+                let summary = result.map_err(|error| demo_error("parse_demo", &path, error))?;
+                Ok(DemoSummary::from(summary))
+            })
+            .await?
+        })
+    }
+}
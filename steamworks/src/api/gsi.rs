@@ -0,0 +1,519 @@
+use crate::gsi::diff::GsiEventKind;
+use napi_derive::napi;
+
+#[napi]
+/// Game State Integration 事件类型
+pub enum EGsiEvent {
+    /// 地图改变，包括第一次收到地图
+    MapChanged,
+    /// 比赛阶段改变，如热身结束进入 `live`
+    MapPhaseChanged,
+    /// 回合阶段改变，如冻结时间结束进入 `live`
+    RoundPhaseChanged,
+    /// 回合结束并决出胜方
+    RoundWon,
+    /// 任意一方比分改变
+    ScoreChanged,
+    /// 炸弹状态改变，如安放、拆除、爆炸
+    BombChanged,
+    /// 本地玩家或正在观察的玩家改变
+    PlayerSwitched,
+    /// 玩家的击杀、助攻、死亡、MVP 或得分改变
+    PlayerStatsChanged,
+    /// 玩家的生命值降为 0
+    PlayerDied,
+}
+
+impl From<&GsiEventKind> for EGsiEvent {
+    fn from(value: &GsiEventKind) -> Self {
+        match value {
+            GsiEventKind::MapChanged { .. } => EGsiEvent::MapChanged,
+            GsiEventKind::MapPhaseChanged { .. } => EGsiEvent::MapPhaseChanged,
+            GsiEventKind::RoundPhaseChanged { .. } => EGsiEvent::RoundPhaseChanged,
+            GsiEventKind::RoundWon { .. } => EGsiEvent::RoundWon,
+            GsiEventKind::ScoreChanged => EGsiEvent::ScoreChanged,
+            GsiEventKind::BombChanged { .. } => EGsiEvent::BombChanged,
+            GsiEventKind::PlayerSwitched { .. } => EGsiEvent::PlayerSwitched,
+            GsiEventKind::PlayerStatsChanged { .. } => EGsiEvent::PlayerStatsChanged,
+            GsiEventKind::PlayerDied { .. } => EGsiEvent::PlayerDied,
+        }
+    }
+}
+
+#[napi]
+/// 接收 CS:GO 与 CS2 的 Game State Integration 状态，不需要初始化 Steam
+pub mod gsi {
+    use super::EGsiEvent;
+    use crate::error::SteamworksError;
+    use crate::gsi::config::{self, ConfigOptions};
+    use crate::gsi::diff::GsiEventKind;
+    use crate::gsi::state::{self, GameState, MatchStats, PlayerState, TeamState};
+    use crate::gsi::{self, ListenOptions, Server};
+    use napi::bindgen_prelude::Error;
+    use napi::threadsafe_function::{
+        ErrorStrategy, ThreadsafeFunction, ThreadsafeFunctionCallMode,
+    };
+    use napi::JsFunction;
+    use std::path::Path;
+    use std::sync::Arc;
+
+    #[napi(object)]
+    /// 上报状态的游戏客户端
+    pub struct GsiProvider {
+        pub name: String,
+        pub app_id: u32,
+        pub version: u32,
+        /// 客户端登录的 Steam ID
+        pub steam_id: String,
+        /// Unix 时间（秒）
+        pub timestamp: f64,
+    }
+
+    #[napi(object)]
+    /// 一方队伍
+    pub struct GsiTeam {
+        pub score: u32,
+        pub consecutive_round_losses: u32,
+        pub timeouts_remaining: u32,
+        pub matches_won_this_series: u32,
+        /// 比赛中设置的队伍名称
+        pub name: Option<String>,
+    }
+
+    impl From<TeamState> for GsiTeam {
+        fn from(team: TeamState) -> Self {
+            Self {
+                score: team.score,
+                consecutive_round_losses: team.consecutive_round_losses,
+                timeouts_remaining: team.timeouts_remaining,
+                matches_won_this_series: team.matches_won_this_series,
+                name: team.name,
+            }
+        }
+    }
+
+    #[napi(object)]
+    /// 地图与比赛
+    pub struct GsiMap {
+        /// 游戏模式，如 `competitive`
+        pub mode: String,
+        pub name: String,
+        /// `warmup`、`live`、`intermission` 或 `gameover`
+        pub phase: String,
+        /// 已经结束的回合数
+        pub round: u32,
+        pub team_ct: GsiTeam,
+        pub team_t: GsiTeam,
+        pub num_matches_to_win_series: u32,
+    }
+
+    #[napi(object)]
+    /// 当前回合
+    pub struct GsiRound {
+        /// `freezetime`、`live` 或 `over`
+        pub phase: String,
+        /// `planted`、`exploded` 或 `defused`，未安放时为空
+        pub bomb: Option<String>,
+        /// 回合结束后获胜的队伍，`CT` 或 `T`
+        pub win_team: Option<String>,
+    }
+
+    #[napi(object)]
+    /// 玩家的实时状态
+    pub struct GsiPlayerVitals {
+        pub health: u32,
+        pub armor: u32,
+        pub helmet: bool,
+        /// 是否有拆弹器，只有 CT 有值
+        pub defuse_kit: Option<bool>,
+        /// 闪光程度，0 到 255
+        pub flashed: u32,
+        pub smoked: u32,
+        pub burning: u32,
+        pub money: u32,
+        pub round_kills: u32,
+        /// 本回合爆头击杀数
+        pub round_kill_hs: u32,
+        pub round_total_damage: Option<u32>,
+        pub equip_value: u32,
+    }
+
+    #[napi(object)]
+    /// 玩家本场比赛的数据
+    pub struct GsiMatchStats {
+        pub kills: i32,
+        pub assists: i32,
+        pub deaths: i32,
+        pub mvps: i32,
+        pub score: i32,
+    }
+
+    impl From<MatchStats> for GsiMatchStats {
+        fn from(stats: MatchStats) -> Self {
+            Self {
+                kills: stats.kills,
+                assists: stats.assists,
+                deaths: stats.deaths,
+                mvps: stats.mvps,
+                score: stats.score,
+            }
+        }
+    }
+
+    #[napi(object)]
+    /// 玩家
+    pub struct GsiPlayer {
+        pub steam_id: String,
+        pub name: String,
+        pub clan: Option<String>,
+        /// 观战时的按键编号
+        pub observer_slot: Option<u32>,
+        /// `CT` 或 `T`
+        pub team: Option<String>,
+        /// `playing`、`menu` 或 `textinput`，只有本地玩家有值
+        pub activity: Option<String>,
+        pub state: Option<GsiPlayerVitals>,
+        pub match_stats: Option<GsiMatchStats>,
+        /// 坐标 `[x, y, z]`
+        pub position: Option<Vec<f64>>,
+        /// 朝向的单位向量 `[x, y, z]`
+        pub forward: Option<Vec<f64>>,
+    }
+
+    fn vector(text: Option<&str>) -> Option<Vec<f64>> {
+        text.and_then(state::parse_vector).map(Vec::from)
+    }
+
+    fn seconds(text: Option<&str>) -> Option<f64> {
+        text.and_then(|text| text.trim().parse().ok())
+    }
+
+    impl GsiPlayer {
+        fn from_state(steam_id: &str, player: &PlayerState) -> Self {
+            Self {
+                steam_id: steam_id.to_string(),
+                name: player.name.clone(),
+                clan: player.clan.clone(),
+                observer_slot: player.observer_slot,
+                team: player.team.clone(),
+                activity: player.activity.clone(),
+                state: player.state.as_ref().map(|vitals| GsiPlayerVitals {
+                    health: vitals.health,
+                    armor: vitals.armor,
+                    helmet: vitals.helmet,
+                    defuse_kit: vitals.defusekit,
+                    flashed: vitals.flashed,
+                    smoked: vitals.smoked,
+                    burning: vitals.burning,
+                    money: vitals.money,
+                    round_kills: vitals.round_kills,
+                    round_kill_hs: vitals.round_killhs,
+                    round_total_damage: vitals.round_totaldmg,
+                    equip_value: vitals.equip_value,
+                }),
+                match_stats: player.match_stats.map(GsiMatchStats::from),
+                position: vector(player.position.as_deref()),
+                forward: vector(player.forward.as_deref()),
+            }
+        }
+    }
+
+    #[napi(object)]
+    /// 炸弹，只在观战时上报
+    pub struct GsiBomb {
+        /// `carried`、`dropped`、`planting`、`planted`、`defusing`、`defused` 或 `exploded`
+        pub state: String,
+        /// 坐标 `[x, y, z]`
+        pub position: Option<Vec<f64>>,
+        /// 携带、安放或拆除炸弹的玩家 Steam ID
+        pub player: Option<String>,
+        /// 爆炸或拆除完成前的秒数
+        pub countdown: Option<f64>,
+    }
+
+    #[napi(object)]
+    /// 当前阶段的倒计时，只在观战时上报
+    pub struct GsiPhaseCountdowns {
+        /// `freezetime`、`live`、`bomb`、`defuse`、`over`、`warmup` 等
+        pub phase: String,
+        /// 阶段结束前的秒数
+        pub phase_ends_in: Option<f64>,
+    }
+
+    #[napi(object)]
+    /// 一次上报的游戏状态，只包含 cfg 中启用的部分
+    pub struct GsiGameState {
+        pub provider: Option<GsiProvider>,
+        pub map: Option<GsiMap>,
+        pub round: Option<GsiRound>,
+        /// 本地玩家，观战时为正在观察的玩家
+        pub player: Option<GsiPlayer>,
+        /// 所有玩家，只在观战或 GOTV 中上报
+        pub all_players: Option<Vec<GsiPlayer>>,
+        pub bomb: Option<GsiBomb>,
+        pub phase_countdowns: Option<GsiPhaseCountdowns>,
+    }
+
+    impl From<GameState> for GsiGameState {
+        fn from(state: GameState) -> Self {
+            Self {
+                provider: state.provider.map(|provider| GsiProvider {
+                    name: provider.name,
+                    app_id: provider.appid,
+                    version: provider.version,
+                    steam_id: provider.steamid,
+                    timestamp: provider.timestamp as f64,
+                }),
+                map: state.map.map(|map| GsiMap {
+                    mode: map.mode,
+                    name: map.name,
+                    phase: map.phase,
+                    round: map.round,
+                    team_ct: map.team_ct.into(),
+                    team_t: map.team_t.into(),
+                    num_matches_to_win_series: map.num_matches_to_win_series,
+                }),
+                round: state.round.map(|round| GsiRound {
+                    phase: round.phase,
+                    bomb: round.bomb,
+                    win_team: round.win_team,
+                }),
+                player: state
+                    .player
+                    .as_ref()
+                    .map(|player| GsiPlayer::from_state(&player.steamid, player)),
+                all_players: state.allplayers.as_ref().map(|players| {
+                    players
+                        .iter()
+                        .map(|(steam_id, player)| GsiPlayer::from_state(steam_id, player))
+                        .collect()
+                }),
+                bomb: state.bomb.map(|bomb| GsiBomb {
+                    position: vector(bomb.position.as_deref()),
+                    countdown: seconds(bomb.countdown.as_deref()),
+                    state: bomb.state,
+                    player: bomb.player,
+                }),
+                phase_countdowns: state.phase_countdowns.map(|countdowns| GsiPhaseCountdowns {
+                    phase_ends_in: seconds(countdowns.phase_ends_in.as_deref()),
+                    phase: countdowns.phase,
+                }),
+            }
+        }
+    }
+
+    #[napi(object)]
+    /// 与上一次状态相比的变化，只有与事件类型相关的字段有值
+    pub struct GsiEvent {
+        pub kind: EGsiEvent,
+        /// 变化前的值：地图、阶段、炸弹状态或玩家 Steam ID，第一次上报时为空
+        pub previous: Option<String>,
+        /// 相关的玩家，PlayerStatsChanged 与 PlayerDied
+        pub steam_id: Option<String>,
+        /// 获胜的队伍，仅 RoundWon
+        pub team: Option<String>,
+        /// 变化前的比赛数据，仅 PlayerStatsChanged
+        pub previous_stats: Option<GsiMatchStats>,
+    }
+
+    impl From<GsiEventKind> for GsiEvent {
+        fn from(kind: GsiEventKind) -> Self {
+            let mut event = Self {
+                kind: EGsiEvent::from(&kind),
+                previous: None,
+                steam_id: None,
+                team: None,
+                previous_stats: None,
+            };
+            match kind {
+                GsiEventKind::MapChanged { previous }
+                | GsiEventKind::MapPhaseChanged { previous }
+                | GsiEventKind::RoundPhaseChanged { previous }
+                | GsiEventKind::BombChanged { previous }
+                | GsiEventKind::PlayerSwitched { previous } => event.previous = previous,
+                GsiEventKind::RoundWon { team } => event.team = Some(team),
+                GsiEventKind::ScoreChanged => {}
+                GsiEventKind::PlayerStatsChanged { steam_id, previous } => {
+                    event.steam_id = Some(steam_id);
+                    event.previous_stats = Some(previous.into());
+                }
+                GsiEventKind::PlayerDied { steam_id } => event.steam_id = Some(steam_id),
+            }
+            event
+        }
+    }
+
+    #[napi(object)]
+    /// 一次上报
+    pub struct GsiUpdate {
+        /// 发送状态的地址
+        pub source: String,
+        pub state: GsiGameState,
+        /// 与同一客户端上一次状态相比的变化，状态没有变化时为空数组
+        pub events: Vec<GsiEvent>,
+    }
+
+    impl From<gsi::GsiUpdate> for GsiUpdate {
+        fn from(update: gsi::GsiUpdate) -> Self {
+            Self {
+                source: update.source.to_string(),
+                state: update.state.into(),
+                events: update.events.into_iter().map(GsiEvent::from).collect(),
+            }
+        }
+    }
+
+    #[napi(object)]
+    /// 状态监听选项
+    pub struct GsiListenOptions {
+        /// 监听的本机地址，默认 `127.0.0.1`
+        pub host: Option<String>,
+        /// cfg 中的 auth token，设置后拒绝 token 不匹配的状态
+        pub token: Option<String>,
+    }
+
+    #[napi]
+    /// Game State Integration 服务端，停止或被回收时关闭端口
+    pub struct GsiServer {
+        /// 实际监听的地址
+        pub address: String,
+        server: Arc<Server>,
+    }
+
+    #[napi]
+    impl GsiServer {
+        #[napi(catch_unwind)]
+        /// 停止监听并关闭端口
+        ///
+        /// @noreturns
+        pub fn stop(&self) {
+            self.server.stop();
+        }
+
+        #[napi(catch_unwind)]
+        /// 最后收到的状态
+        ///
+        /// @returns 还没有收到状态时为空
+        pub fn state(&self) -> Option<GsiGameState> {
+            self.server.latest().map(GsiGameState::from)
+        }
+    }
+
+    #[napi(catch_unwind)]
+    /// 监听 HTTP 端口接收游戏状态，游戏需要先加载 writeConfig 写入的 cfg
+    ///
+    /// @param port 监听的端口，与 cfg 中 uri 的端口一致
+    /// @param options 监听选项
+    /// @param on_update 每收到一次状态时调用，游戏在状态变化时与每个心跳间隔发送
+    /// @returns 服务端，端口被占用时抛出 NETWORK_ERROR
+    pub fn listen(
+        port: u16,
+        options: Option<GsiListenOptions>,
+        #[napi(ts_arg_type = "(update: GsiUpdate) => void")] on_update: JsFunction,
+    ) -> Result<GsiServer, Error> {
+        let (host, token) = match options {
+            Some(options) => (options.host, options.token),
+            None => (None, None),
+        };
+        let address = match host {
+            // IPv6 地址需要加方括号
+            Some(host) if host.contains(':') && !host.starts_with('[') => {
+                format!("[{}]:{}", host, port)
+            }
+            Some(host) => format!("{}:{}", host, port),
+            None => format!("127.0.0.1:{}", port),
+        };
+
+        let on_update: ThreadsafeFunction<GsiUpdate, ErrorStrategy::Fatal> =
+            on_update.create_threadsafe_function(0, |ctx| Ok(vec![ctx.value]))?;
+        let handler: gsi::UpdateHandler = Arc::new(move |update: gsi::GsiUpdate| {
+            on_update.call(update.into(), ThreadsafeFunctionCallMode::NonBlocking);
+        });
+
+        let server = napi::bindgen_prelude::within_runtime_if_available(|| {
+            Server::bind(&address, ListenOptions { token }, handler)
+        })
+        .map_err(|error| SteamworksError::Network {
+            call: "gsi_listen",
+            target: address.clone(),
+            error,
+        })?;
+
+        Ok(GsiServer {
+            address: server.local_addr().to_string(),
+            server: Arc::new(server),
+        })
+    }
+
+    #[napi(object)]
+    /// cfg 选项
+    pub struct GsiConfigOptions {
+        /// 接收状态的地址，如 `http://127.0.0.1:3000`
+        pub uri: String,
+        /// 文件名中的名称，默认 `sourcebox`，生成 `gamestate_integration_sourcebox.cfg`
+        pub name: Option<String>,
+        /// 随状态发送的 auth token，可以使用 generateToken 生成
+        pub token: Option<String>,
+        /// 请求超时（秒），默认 5
+        pub timeout: Option<f64>,
+        /// 收集变化的时间（秒），默认 0.1
+        pub buffer: Option<f64>,
+        /// 两次发送之间的最短间隔（秒），默认 0.1
+        pub throttle: Option<f64>,
+        /// 状态没有变化时的发送间隔（秒），默认 10
+        pub heartbeat: Option<f64>,
+    }
+
+    impl From<GsiConfigOptions> for ConfigOptions {
+        fn from(options: GsiConfigOptions) -> Self {
+            let mut config = ConfigOptions::new(options.uri);
+            if let Some(name) = options.name {
+                config.name = name;
+            }
+            config.token = options.token;
+            config.timeout = options.timeout.unwrap_or(config.timeout);
+            config.buffer = options.buffer.unwrap_or(config.buffer);
+            config.throttle = options.throttle.unwrap_or(config.throttle);
+            config.heartbeat = options.heartbeat.unwrap_or(config.heartbeat);
+            config
+        }
+    }
+
+    #[napi(catch_unwind)]
+    /// 生成 cfg 内容，启用地图、回合、玩家、所有玩家与炸弹状态
+    ///
+    /// @param options cfg 选项
+    /// @returns cfg 文件内容
+    pub fn generate_config(options: GsiConfigOptions) -> String {
+        ConfigOptions::from(options).render()
+    }
+
+    #[napi]
+    /// 把 cfg 写入游戏的 cfg 目录，已存在时覆盖，游戏重启后生效
+    ///
+    /// @param dir 游戏安装目录，如 `steamapps/common/Counter-Strike Global Offensive`，
+    /// 会自动找到 CS2 的 `game/csgo/cfg` 或 CS:GO 的 `csgo/cfg`，也可以直接传入 cfg 目录
+    /// @param options cfg 选项
+    /// @returns 写入的文件路径
+    pub async fn write_config(dir: String, options: GsiConfigOptions) -> Result<String, Error> {
+        crate::error::catch_panic_async("gsi_write_config", async move {
+            let options = ConfigOptions::from(options);
+            let path =
+                config::write(Path::new(&dir), &options).map_err(|error| SteamworksError::Io {
+                    call: "gsi_write_config",
+                    path: dir.clone(),
+                    error,
+                })?;
+            Ok(path.to_string_lossy().into_owned())
+        })
+        .await?
+    }
+
+    #[napi(catch_unwind)]
+    /// 生成随机的 auth token
+    ///
+    /// @returns 32 位十六进制字符串
+    pub fn generate_token() -> String {
+        config::generate_token()
+    }
+}
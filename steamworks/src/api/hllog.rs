@@ -0,0 +1,408 @@
+use napi_derive::napi;
+
+#[napi]
+/// HL 日志事件类型
+pub enum ELogEvent {
+    /// 日志文件开始
+    LogStarted,
+    /// 日志文件关闭
+    LogClosed,
+    /// 开始加载地图
+    MapLoading,
+    /// 地图加载完成
+    MapStarted,
+    /// 玩家连接
+    Connected,
+    /// 玩家通过 Steam 验证
+    Validated,
+    /// 玩家进入游戏
+    Entered,
+    /// 玩家断开连接
+    Disconnected,
+    /// 玩家加入队伍
+    JoinedTeam,
+    /// 玩家改名
+    ChangedName,
+    /// 击杀
+    Kill,
+    /// 造成伤害
+    Attack,
+    /// 自杀
+    Suicide,
+    /// 聊天
+    Say,
+    /// 玩家触发的事件，如下包、拆包
+    PlayerTriggered,
+    /// 队伍触发的事件，如回合胜利
+    TeamTriggered,
+    /// 队伍得分
+    TeamScored,
+    /// 服务器触发的事件，如回合开始、回合结束
+    WorldTriggered,
+    /// 服务器变量改变
+    Cvar,
+    /// 执行了 RCON 命令
+    Rcon,
+    /// 无法识别的日志行
+    Other,
+}
+
+#[napi]
+/// 接收服务器通过 `logaddress_add` 发送的 HL 日志，不需要初始化 Steam
+pub mod hllog {
+    use super::ELogEvent;
+    use crate::error::SteamworksError;
+    use crate::hllog::line::{LogEventKind, LogLine, LogTime, Player};
+    use crate::hllog::{self, ListenOptions, Listener, LogEvent};
+    use crate::query;
+    use napi::bindgen_prelude::Error;
+    use napi::threadsafe_function::{
+        ErrorStrategy, ThreadsafeFunction, ThreadsafeFunctionCallMode,
+    };
+    use napi::JsFunction;
+    use std::collections::HashMap;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[napi(object)]
+    /// 日志中的玩家
+    pub struct LogPlayer {
+        pub name: String,
+        /// 服务器分配的用户 ID，控制台为 0
+        pub user_id: i32,
+        /// `STEAM_1:0:123`、`[U:1:123]`、`BOT` 或 `Console`
+        pub steam_id: String,
+        /// 队伍，未加入队伍时可能为空
+        pub team: String,
+    }
+
+    impl From<Player> for LogPlayer {
+        fn from(player: Player) -> Self {
+            Self {
+                name: player.name,
+                user_id: player.user_id,
+                steam_id: player.steam_id,
+                team: player.team,
+            }
+        }
+    }
+
+    #[napi(object)]
+    /// 一行日志，只有与事件类型相关的字段有值
+    pub struct HlLogEvent {
+        pub kind: ELogEvent,
+        /// 发送日志的服务器地址，parseLine 解析的日志为空
+        pub source: Option<String>,
+        /// 日志行的时间，服务器的本地时间，格式为 `2026-10-18T12:34:56`，不含时区
+        pub time: Option<String>,
+        /// 去掉时间前缀后的完整内容
+        pub message: String,
+        /// 事件的主体：击杀者、造成伤害的玩家、发言的玩家等
+        pub player: Option<LogPlayer>,
+        /// 被击杀或受到伤害的玩家，PlayerTriggered 的 against 目标
+        pub target: Option<LogPlayer>,
+        /// 武器，Kill、Attack 与 Suicide
+        pub weapon: Option<String>,
+        /// 是否爆头，仅 Kill
+        pub headshot: Option<bool>,
+        /// 队伍，JoinedTeam、TeamTriggered 与 TeamScored
+        pub team: Option<String>,
+        /// 聊天内容、新名字或 RCON 命令
+        pub text: Option<String>,
+        /// 是否为队伍聊天，仅 Say
+        pub team_only: Option<bool>,
+        /// 地图，MapLoading 与 MapStarted
+        pub map: Option<String>,
+        /// 触发的事件名，如 `Round_Start`、`Planted_The_Bomb`
+        pub action: Option<String>,
+        /// 玩家的 IP 地址或执行 RCON 命令的地址，Connected 与 Rcon
+        pub address: Option<String>,
+        /// 变量名，仅 Cvar
+        pub cvar: Option<String>,
+        /// 变量值，仅 Cvar
+        pub value: Option<String>,
+        /// 队伍得分与人数，仅 TeamScored
+        pub score: Option<i32>,
+        pub players: Option<u32>,
+        /// 行尾的 `(键 "值")` 属性，`(headshot)` 之类的标记值为空字符串
+        pub properties: HashMap<String, String>,
+    }
+
+    impl HlLogEvent {
+        fn from_line(source: Option<String>, line: LogLine) -> Self {
+            let properties = line
+                .properties
+                .iter()
+                .map(|property| {
+                    let value = property.value.clone().unwrap_or_default();
+                    (property.key.clone(), value)
+                })
+                .collect();
+            let mut event = Self {
+                kind: ELogEvent::Other,
+                source,
+                time: line.time.as_ref().map(LogTime::to_iso_string),
+                headshot: None,
+                message: line.message,
+                player: None,
+                target: None,
+                weapon: None,
+                team: None,
+                text: None,
+                team_only: None,
+                map: None,
+                action: None,
+                address: None,
+                cvar: None,
+                value: None,
+                score: None,
+                players: None,
+                properties,
+            };
+
+            event.kind = match line.kind {
+                LogEventKind::LogStarted => ELogEvent::LogStarted,
+                LogEventKind::LogClosed => ELogEvent::LogClosed,
+                LogEventKind::MapLoading { map } => {
+                    event.map = Some(map);
+                    ELogEvent::MapLoading
+                }
+                LogEventKind::MapStarted { map } => {
+                    event.map = Some(map);
+                    ELogEvent::MapStarted
+                }
+                LogEventKind::Connected { player, address } => {
+                    event.player = Some(player.into());
+                    event.address = Some(address);
+                    ELogEvent::Connected
+                }
+                LogEventKind::Validated { player } => {
+                    event.player = Some(player.into());
+                    ELogEvent::Validated
+                }
+                LogEventKind::Entered { player } => {
+                    event.player = Some(player.into());
+                    ELogEvent::Entered
+                }
+                LogEventKind::Disconnected { player } => {
+                    event.player = Some(player.into());
+                    ELogEvent::Disconnected
+                }
+                LogEventKind::JoinedTeam { player, team } => {
+                    event.player = Some(player.into());
+                    event.team = Some(team);
+                    ELogEvent::JoinedTeam
+                }
+                LogEventKind::ChangedName { player, name } => {
+                    event.player = Some(player.into());
+                    event.text = Some(name);
+                    ELogEvent::ChangedName
+                }
+                LogEventKind::Kill {
+                    killer,
+                    victim,
+                    weapon,
+                } => {
+                    event.player = Some(killer.into());
+                    event.target = Some(victim.into());
+                    event.weapon = Some(weapon);
+                    event.headshot = Some(event.properties.contains_key("headshot"));
+                    ELogEvent::Kill
+                }
+                LogEventKind::Attack {
+                    attacker,
+                    victim,
+                    weapon,
+                } => {
+                    event.player = Some(attacker.into());
+                    event.target = Some(victim.into());
+                    event.weapon = Some(weapon);
+                    ELogEvent::Attack
+                }
+                LogEventKind::Suicide { player, weapon } => {
+                    event.player = Some(player.into());
+                    event.weapon = Some(weapon);
+                    ELogEvent::Suicide
+                }
+                LogEventKind::Say {
+                    player,
+                    message,
+                    team_only,
+                } => {
+                    event.player = Some(player.into());
+                    event.text = Some(message);
+                    event.team_only = Some(team_only);
+                    ELogEvent::Say
+                }
+                LogEventKind::PlayerTriggered {
+                    player,
+                    action,
+                    target,
+                } => {
+                    event.player = Some(player.into());
+                    event.action = Some(action);
+                    event.target = target.map(LogPlayer::from);
+                    ELogEvent::PlayerTriggered
+                }
+                LogEventKind::TeamTriggered { team, action } => {
+                    event.team = Some(team);
+                    event.action = Some(action);
+                    ELogEvent::TeamTriggered
+                }
+                LogEventKind::TeamScored {
+                    team,
+                    score,
+                    players,
+                } => {
+                    event.team = Some(team);
+                    event.score = Some(score);
+                    event.players = Some(players);
+                    ELogEvent::TeamScored
+                }
+                LogEventKind::WorldTriggered { action } => {
+                    event.action = Some(action);
+                    ELogEvent::WorldTriggered
+                }
+                LogEventKind::Cvar { name, value } => {
+                    event.cvar = Some(name);
+                    event.value = Some(value);
+                    ELogEvent::Cvar
+                }
+                LogEventKind::Rcon { address, command } => {
+                    event.address = Some(address);
+                    event.text = Some(command);
+                    ELogEvent::Rcon
+                }
+                LogEventKind::Other => ELogEvent::Other,
+            };
+            event
+        }
+    }
+
+    impl From<LogEvent> for HlLogEvent {
+        fn from(event: LogEvent) -> Self {
+            HlLogEvent::from_line(Some(event.source.to_string()), event.line)
+        }
+    }
+
+    #[napi(object)]
+    /// 日志监听选项
+    pub struct HlLogListenOptions {
+        /// 监听的本机地址，默认 `0.0.0.0`
+        pub host: Option<String>,
+        /// 服务器的 `sv_logsecret`，设置后只接受密码匹配的日志，为空时接受所有日志
+        pub secret: Option<String>,
+    }
+
+    #[napi]
+    /// HL 日志监听器，停止或被回收时关闭端口
+    pub struct HlLogListener {
+        /// 实际监听的地址
+        pub address: String,
+        listener: Arc<Listener>,
+    }
+
+    #[napi]
+    impl HlLogListener {
+        #[napi(catch_unwind)]
+        /// 停止监听并关闭端口
+        ///
+        /// @noreturns
+        pub fn stop(&self) {
+            self.listener.stop();
+        }
+    }
+
+    #[napi(catch_unwind)]
+    /// 监听 UDP 端口接收日志，在服务器上执行 `logaddress_add <本机 IP>:<端口>` 后开始发送
+    ///
+    /// @param port 监听的端口，为 0 时由系统分配，实际端口见返回值的 address
+    /// @param options 监听选项
+    /// @param on_event 每收到一行日志时调用
+    /// @returns 监听器，端口被占用时抛出 NETWORK_ERROR
+    pub fn listen(
+        port: u16,
+        options: Option<HlLogListenOptions>,
+        #[napi(ts_arg_type = "(event: HlLogEvent) => void")] on_event: JsFunction,
+    ) -> Result<HlLogListener, Error> {
+        let (host, secret) = match options {
+            Some(options) => (options.host, options.secret),
+            None => (None, None),
+        };
+        let address = match host {
+            // IPv6 地址需要加方括号
+            Some(host) if host.contains(':') && !host.starts_with('[') => {
+                format!("[{}]:{}", host, port)
+            }
+            Some(host) => format!("{}:{}", host, port),
+            None => format!("0.0.0.0:{}", port),
+        };
+
+        let on_event: ThreadsafeFunction<HlLogEvent, ErrorStrategy::Fatal> =
+            on_event.create_threadsafe_function(0, |ctx| Ok(vec![ctx.value]))?;
+        let handler: hllog::EventHandler = Arc::new(move |event: LogEvent| {
+            on_event.call(event.into(), ThreadsafeFunctionCallMode::NonBlocking);
+        });
+
+        let listener = napi::bindgen_prelude::within_runtime_if_available(|| {
+            Listener::bind(&address, ListenOptions { secret }, handler)
+        })
+        .map_err(|error| SteamworksError::Network {
+            call: "hllog_listen",
+            target: address.clone(),
+            error,
+        })?;
+
+        Ok(HlLogListener {
+            address: listener.local_addr().to_string(),
+            listener: Arc::new(listener),
+        })
+    }
+
+    #[napi(catch_unwind)]
+    /// 解析一行日志，可以用于读取服务器保存的日志文件
+    ///
+    /// @param line 日志行，如 `L 10/18/2026 - 12:34:56: "Player<2><[U:1:1]><CT>" say "gg"`
+    /// @returns 解析后的事件，无法识别时类型为 Other
+    pub fn parse_line(line: String) -> HlLogEvent {
+        HlLogEvent::from_line(None, LogLine::parse(&line))
+    }
+
+    #[napi(object)]
+    /// 日志回放选项
+    pub struct HlLogReplayOptions {
+        /// 按 `sv_logsecret` 格式发送
+        pub secret: Option<String>,
+        /// 每行之间的间隔（毫秒），默认 0，本机回放大量日志时建议设置以免丢包
+        pub interval_ms: Option<u32>,
+    }
+
+    #[napi]
+    /// 把日志行按服务器的格式发送到监听地址，用于在没有服务器时调试
+    ///
+    /// @param address 监听地址，`ip:port`
+    /// @param lines 日志行，可以直接使用服务器 `logs` 目录中的日志文件内容
+    /// @param options 回放选项
+    /// @returns 发送的行数，空行会被跳过
+    pub async fn replay(
+        address: String,
+        lines: Vec<String>,
+        options: Option<HlLogReplayOptions>,
+    ) -> Result<u32, Error> {
+        crate::error::catch_panic_async("hllog_replay", async move {
+            let (secret, interval_ms) = match options {
+                Some(options) => (options.secret, options.interval_ms.unwrap_or(0)),
+                None => (None, 0),
+            };
+            let target = query::resolve(&address)
+                .await
+                .map_err(|error| error.context("hllog_replay", &address))?;
+            let interval = Duration::from_millis(u64::from(interval_ms));
+            let sent = hllog::send(target, &lines, secret.as_deref(), interval)
+                .await
+                .map_err(|error| query::Error::Io(error).context("hllog_replay", &address))?;
+            Ok(sent)
+        })
+        .await?
+    }
+}
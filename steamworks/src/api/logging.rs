@@ -0,0 +1,162 @@
+//! 插件日志
+//!
+//! 记录 Steam API 调用、回调分发、超时与 Steam 自身的警告信息，
+//! 日志通过 JS 注册的日志函数输出，没有注册时不会产生任何开销。
+
+use napi::bindgen_prelude::{FromNapiValue, ToNapiValue};
+use napi::threadsafe_function::{ErrorStrategy, ThreadsafeFunction, ThreadsafeFunctionCallMode};
+use napi_derive::napi;
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use std::sync::{PoisonError, RwLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// 日志函数最多积压的日志数量，超出后新日志会被丢弃，避免拖慢回调分发线程
+const SINK_QUEUE_SIZE: usize = 1024;
+
+#[napi]
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+/// 日志级别
+pub enum LogLevel {
+    /// 每一次 Steam API 调用
+    Trace,
+    /// 回调分发、异步调用完成与会话切换
+    Debug,
+    /// Steam 初始化、关闭等状态变化
+    Info,
+    /// 超时、调用失败与 Steam 的警告信息
+    Warn,
+    /// 插件内部错误
+    Error,
+    /// 不输出日志
+    Off,
+}
+
+#[napi(object)]
+/// 一条日志
+pub struct LogRecord {
+    pub level: LogLevel,
+    /// 日志来源，如 "api"、"callback"、"steam"
+    pub target: String,
+    pub message: String,
+    /// 记录时间，Unix 毫秒时间戳
+    pub timestamp: f64,
+}
+
+lazy_static! {
+    static ref SINK: RwLock<Option<ThreadsafeFunction<LogRecord, ErrorStrategy::Fatal>>> =
+        RwLock::new(None);
+}
+
+static LEVEL: AtomicU8 = AtomicU8::new(LogLevel::Info as u8);
+static HAS_SINK: AtomicBool = AtomicBool::new(false);
+
+fn level_from_u8(level: u8) -> LogLevel {
+    match level {
+        0 => LogLevel::Trace,
+        1 => LogLevel::Debug,
+        2 => LogLevel::Info,
+        3 => LogLevel::Warn,
+        4 => LogLevel::Error,
+        _ => LogLevel::Off,
+    }
+}
+
+/// 该级别的日志是否会被输出，用于在格式化日志之前提前跳过
+pub fn enabled(level: LogLevel) -> bool {
+    level != LogLevel::Off
+        && HAS_SINK.load(Ordering::Relaxed)
+        && level >= level_from_u8(LEVEL.load(Ordering::Relaxed))
+}
+
+/// 输出一条日志，不会阻塞调用线程
+pub fn write(level: LogLevel, target: &str, message: String) {
+    if !enabled(level) {
+        return;
+    }
+
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0.0, |d| d.as_secs_f64() * 1000.0);
+
+    if let Some(sink) = SINK.read().unwrap_or_else(PoisonError::into_inner).as_ref() {
+        sink.call(
+            LogRecord {
+                level,
+                target: target.to_string(),
+                message,
+                timestamp,
+            },
+            ThreadsafeFunctionCallMode::NonBlocking,
+        );
+    }
+}
+
+/// 按级别输出日志，日志不会被输出时不会格式化参数
+///
+/// ```ignore
+/// steam_log!(Warn, "api", "{} timed out after {}s", call, seconds);
+/// ```
+macro_rules! steam_log {
+    ($level:ident, $target:expr, $($arg:tt)+) => {
+        if $crate::api::logging::enabled($crate::api::logging::LogLevel::$level) {
+            $crate::api::logging::write(
+                $crate::api::logging::LogLevel::$level,
+                $target,
+                format!($($arg)+),
+            );
+        }
+    };
+}
+
+pub(crate) use steam_log;
+
+#[napi]
+/// 插件日志，注册日志函数后才会输出
+pub mod logging {
+    use super::{level_from_u8, LogLevel, HAS_SINK, LEVEL, SINK, SINK_QUEUE_SIZE};
+    use napi::bindgen_prelude::Error;
+    use napi::{Env, JsFunction};
+    use std::sync::atomic::Ordering;
+    use std::sync::PoisonError;
+
+    #[napi(catch_unwind)]
+    /// 注册日志函数，替换之前注册的日志函数
+    ///
+    /// 日志函数不会阻止进程退出
+    ///
+    /// @param handler 接收日志的函数
+    pub fn set_sink(
+        env: Env,
+        #[napi(ts_arg_type = "(record: LogRecord) => void")] handler: JsFunction,
+    ) -> Result<(), Error> {
+        let mut sink =
+            handler.create_threadsafe_function(SINK_QUEUE_SIZE, |ctx| Ok(vec![ctx.value]))?;
+        sink.unref(&env)?;
+
+        *SINK.write().unwrap_or_else(PoisonError::into_inner) = Some(sink);
+        HAS_SINK.store(true, Ordering::Relaxed);
+        crate::error::install_panic_hook();
+        Ok(())
+    }
+
+    #[napi(catch_unwind)]
+    /// 移除日志函数
+    pub fn clear_sink() {
+        HAS_SINK.store(false, Ordering::Relaxed);
+        SINK.write().unwrap_or_else(PoisonError::into_inner).take();
+    }
+
+    #[napi(catch_unwind)]
+    /// 设置日志级别，低于该级别的日志不会输出，默认 Info
+    ///
+    /// @param level 日志级别
+    pub fn set_level(level: LogLevel) {
+        LEVEL.store(level as u8, Ordering::Relaxed);
+    }
+
+    #[napi(catch_unwind)]
+    /// 获取当前日志级别
+    pub fn get_level() -> LogLevel {
+        level_from_u8(LEVEL.load(Ordering::Relaxed))
+    }
+}
@@ -0,0 +1,442 @@
+use super::callback::SteamApiCallback;
+use super::consts::{ELobbyType, EPersonaState};
+use super::payloads::CallbackPayload;
+use super::steammatchmakingservers::EServerListType;
+use crate::backend::mock::MockBackend;
+use crate::error::SteamworksError;
+use napi_derive::napi;
+use std::sync::{Arc, Mutex, PoisonError};
+
+lazy_static! {
+    static ref MOCK_BACKEND: Mutex<Option<Arc<MockBackend>>> = Mutex::new(None);
+}
+
+fn get_mock() -> Result<Arc<MockBackend>, SteamworksError> {
+    MOCK_BACKEND
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone()
+        .ok_or(SteamworksError::NotInitialized)
+}
+
+#[napi]
+/// 内存模拟 Steam，用于在没有 Steam 客户端的环境下测试
+pub mod mock {
+    use super::{
+        get_mock, CallbackPayload, ELobbyType, EPersonaState, EServerListType, SteamApiCallback,
+        MOCK_BACKEND,
+    };
+    use crate::backend::mock::{MockBackend, MockFriend, MockLobby, MockServer, MockState};
+    use crate::backend::{GameServerItem, ItemInstallInfo, ServerListKind, ServerPlayer};
+    use crate::error::steam_error_from_code;
+    use napi::bindgen_prelude::{BigInt, Buffer, Error};
+    use napi::{Env, JsUnknown, NapiRaw};
+    use std::collections::{BTreeMap, HashMap, HashSet};
+    use std::net::{Ipv4Addr, SocketAddrV4};
+    use std::sync::{Arc, PoisonError};
+    use std::time::Duration;
+    use steamworks::{
+        AppId, FriendFlags, FriendGame, GameId, ItemState, LobbyId, LobbyType, PublishedFileId,
+        SteamId,
+    };
+
+    #[napi(object)]
+    /// 模拟用户信息
+    pub struct MockUserOptions {
+        /// 当前用户 64 位 SteamID
+        pub steam_id64: Option<BigInt>,
+        /// 当前用户昵称
+        pub name: Option<String>,
+        /// 当前用户 Steam 等级
+        pub level: Option<u32>,
+        /// 当前进程的 App ID
+        pub app_id: Option<u32>,
+        /// 当前用户所在国家代码
+        pub ip_country: Option<String>,
+        /// Steam 云是否启用
+        pub cloud_enabled: Option<bool>,
+    }
+
+    #[napi(object)]
+    /// 模拟好友信息
+    pub struct MockFriendOptions {
+        /// 好友 64 位 SteamID
+        pub steam_id64: BigInt,
+        /// 好友昵称
+        pub name: String,
+        /// 好友状态，默认在线
+        pub state: Option<EPersonaState>,
+        /// 好友正在玩的游戏的 App ID
+        pub game_app_id: Option<u32>,
+        /// 好友正在玩的服务器的 IP
+        pub game_ip: Option<String>,
+        /// 好友正在玩的服务器端口
+        pub game_port: Option<u16>,
+        /// 好友正在玩的服务器的查询端口
+        pub query_port: Option<u16>,
+        /// 好友所在的大厅的 Steam ID
+        pub lobby_id: Option<BigInt>,
+    }
+
+    #[napi(object)]
+    /// 模拟大厅信息
+    pub struct MockLobbyOptions {
+        /// 大厅所有者 64 位 SteamID
+        pub owner: BigInt,
+        /// 大厅其他成员 64 位 SteamID
+        pub members: Option<Vec<BigInt>>,
+        /// 可加入大厅的玩家最大数量
+        pub member_limit: Option<u32>,
+        /// 大厅类型，默认公开
+        pub lobby_type: Option<ELobbyType>,
+        /// 是否可加入，默认可加入
+        pub joinable: Option<bool>,
+        /// 大厅元数据
+        pub data: Option<HashMap<String, String>>,
+    }
+
+    #[napi(object)]
+    /// 模拟创意工坊物品
+    pub struct MockWorkshopItemOptions {
+        /// 物品ID，为空时自动分配
+        pub published_file_id: Option<BigInt>,
+        /// 物品标题
+        pub title: String,
+        /// 物品描述
+        pub description: Option<String>,
+        /// 物品作者 64 位 SteamID，默认为当前用户
+        pub owner: Option<BigInt>,
+        /// 物品标签
+        pub tags: Option<Vec<String>>,
+        /// 预览文件图片链接
+        pub preview_url: Option<String>,
+        /// 当前用户是否已订阅
+        pub subscribed: Option<bool>,
+        /// 安装目录，存在时物品视为已下载
+        pub install_folder: Option<String>,
+        /// 物品的字节大小
+        pub size_on_disk: Option<BigInt>,
+    }
+
+    #[napi(object)]
+    /// 模拟服务器中的玩家
+    pub struct MockServerPlayerOptions {
+        pub name: String,
+        pub score: Option<i32>,
+        /// 在服务器中的时长（秒）
+        pub time_played: Option<f64>,
+    }
+
+    #[napi(object)]
+    /// 模拟服务器浏览器中的服务器
+    pub struct MockServerOptions {
+        /// 查询地址，`ip:查询端口`
+        pub address: String,
+        /// 游戏连接端口，默认与查询端口相同
+        pub connection_port: Option<u16>,
+        pub name: String,
+        pub map: Option<String>,
+        pub game_dir: Option<String>,
+        pub game_description: Option<String>,
+        /// 默认为当前 App ID
+        pub app_id: Option<u32>,
+        /// 玩家数，默认为玩家列表的长度
+        pub players: Option<i32>,
+        pub max_players: Option<i32>,
+        pub bot_players: Option<i32>,
+        pub password: Option<bool>,
+        pub secure: Option<bool>,
+        /// 延迟（毫秒）
+        pub ping: Option<i32>,
+        pub tags: Option<Vec<String>>,
+        /// 出现在哪些服务器列表中，默认只出现在互联网列表
+        pub lists: Option<Vec<EServerListType>>,
+        /// 玩家列表
+        pub player_list: Option<Vec<MockServerPlayerOptions>>,
+        /// 服务器规则
+        pub rules: Option<HashMap<String, String>>,
+        /// 为 false 时服务器出现在列表中但不回复查询，默认回复
+        pub responds: Option<bool>,
+    }
+
+    #[napi(catch_unwind)]
+    /// 使用内存模拟 Steam 代替真实客户端，之后所有 Steam 接口都将访问模拟数据
+    ///
+    /// @param options 模拟用户信息
+    pub fn init(options: Option<MockUserOptions>) -> Result<(), Error> {
+        let mut state = MockState::default();
+
+        if let Some(options) = options {
+            if let Some(steam_id64) = options.steam_id64 {
+                state.steam_id =
+                    SteamId::from_raw(crate::id::bigint_u64("steam_id64", &steam_id64)?);
+            }
+
+            if let Some(name) = options.name {
+                state.name = name;
+            }
+
+            if let Some(level) = options.level {
+                state.level = level;
+            }
+
+            if let Some(app_id) = options.app_id {
+                state.app_id = AppId(app_id);
+                state.subscribed_apps.insert(state.app_id);
+                state.installed_apps.insert(state.app_id);
+            }
+
+            if let Some(ip_country) = options.ip_country {
+                state.ip_country = ip_country;
+            }
+
+            if let Some(cloud_enabled) = options.cloud_enabled {
+                state.cloud_enabled_for_account = cloud_enabled;
+                state.cloud_enabled_for_app = cloud_enabled;
+            }
+        }
+
+        let backend = Arc::new(MockBackend::new(state));
+        crate::client::set_client(backend.clone());
+        *MOCK_BACKEND.lock().unwrap_or_else(PoisonError::into_inner) = Some(backend);
+        crate::api::pump::start_pump(crate::api::pump::DEFAULT_INTERVAL_MS)
+    }
+
+    #[napi(catch_unwind)]
+    /// 添加一位模拟好友
+    pub fn add_friend(friend: MockFriendOptions) -> Result<(), Error> {
+        let steam_id = crate::id::bigint_u64("steam_id64", &friend.steam_id64)?;
+        let lobby_id = match &friend.lobby_id {
+            Some(lobby_id) => crate::id::bigint_u64("lobby_id", lobby_id)?,
+            None => 0,
+        };
+        let game = friend.game_app_id.map(|app_id| FriendGame {
+            game: GameId::from_raw(app_id as u64),
+            game_address: friend
+                .game_ip
+                .as_deref()
+                .and_then(|ip| ip.parse::<Ipv4Addr>().ok())
+                .unwrap_or(Ipv4Addr::UNSPECIFIED),
+            game_port: friend.game_port.unwrap_or_default(),
+            query_port: friend.query_port.unwrap_or_default(),
+            lobby: LobbyId::from_raw(lobby_id),
+        });
+
+        get_mock()?.state().friends.push(MockFriend {
+            steam_id: SteamId::from_raw(steam_id),
+            name: friend.name,
+            state: friend.state.unwrap_or(EPersonaState::Online).into(),
+            flags: FriendFlags::IMMEDIATE,
+            game,
+        });
+        Ok(())
+    }
+
+    #[napi(catch_unwind)]
+    /// 添加一个模拟大厅
+    ///
+    /// @returns 大厅的 Steam ID
+    pub fn add_lobby(lobby: MockLobbyOptions) -> Result<BigInt, Error> {
+        let owner = SteamId::from_raw(crate::id::bigint_u64("owner", &lobby.owner)?);
+        let mut members = vec![owner];
+        for member in lobby.members.as_deref().unwrap_or_default() {
+            let member = SteamId::from_raw(crate::id::bigint_u64("members", member)?);
+            if member != owner {
+                members.push(member);
+            }
+        }
+
+        let lobby_type = LobbyType::from(lobby.lobby_type.unwrap_or(ELobbyType::Public));
+        let lobby_id = get_mock()?.state().add_lobby(MockLobby {
+            owner,
+            members,
+            member_limit: lobby.member_limit.map(|limit| limit as usize),
+            listed: matches!(lobby_type, LobbyType::Public | LobbyType::Invisible),
+            joinable: lobby.joinable.unwrap_or(true),
+            data: lobby
+                .data
+                .unwrap_or_default()
+                .into_iter()
+                .collect::<BTreeMap<_, _>>(),
+        });
+
+        Ok(BigInt::from(lobby_id.raw()))
+    }
+
+    #[napi(catch_unwind)]
+    /// 添加一个模拟服务器，可以通过服务器浏览器接口查询
+    pub fn add_server(server: MockServerOptions) -> Result<(), Error> {
+        let address: SocketAddrV4 = server.address.parse().map_err(|_| {
+            Error::from_reason(format!("Invalid server address: {}", server.address))
+        })?;
+        let players: Vec<ServerPlayer> = server
+            .player_list
+            .unwrap_or_default()
+            .into_iter()
+            .map(|player| ServerPlayer {
+                name: player.name,
+                score: player.score.unwrap_or_default(),
+                time_played: player.time_played.unwrap_or_default() as f32,
+            })
+            .collect();
+        let lists: HashSet<ServerListKind> = match server.lists {
+            Some(lists) => lists.into_iter().map(ServerListKind::from).collect(),
+            None => HashSet::from([ServerListKind::Internet]),
+        };
+
+        let backend = get_mock()?;
+        let mut state = backend.state();
+        let app_id = server.app_id.map_or(state.app_id, AppId);
+        let responds = server.responds.unwrap_or(true);
+        state.servers.push(MockServer {
+            item: GameServerItem {
+                address,
+                connection_port: server.connection_port.unwrap_or(address.port()),
+                ping: server.ping.unwrap_or_default(),
+                had_successful_response: responds,
+                do_not_refresh: false,
+                game_dir: server.game_dir.unwrap_or_default(),
+                map: server.map.unwrap_or_default(),
+                game_description: server.game_description.unwrap_or_default(),
+                app_id,
+                players: server.players.unwrap_or(players.len() as i32),
+                max_players: server.max_players.unwrap_or_default(),
+                bot_players: server.bot_players.unwrap_or_default(),
+                password: server.password.unwrap_or_default(),
+                secure: server.secure.unwrap_or_default(),
+                time_last_played: 0,
+                server_version: 0,
+                server_name: server.name,
+                game_tags: server.tags.unwrap_or_default().join(","),
+                steam_id: SteamId::from_raw(0),
+            },
+            lists,
+            players,
+            rules: server.rules.unwrap_or_default().into_iter().collect(),
+            responds,
+        });
+        Ok(())
+    }
+
+    #[napi(catch_unwind)]
+    /// 添加一个模拟创意工坊物品
+    ///
+    /// @returns 物品ID
+    pub fn add_workshop_item(item: MockWorkshopItemOptions) -> Result<BigInt, Error> {
+        let backend = get_mock()?;
+        let mut state = backend.state();
+
+        let item_id = match &item.published_file_id {
+            Some(item_id) => crate::id::bigint_u64("published_file_id", item_id)?,
+            None => 0,
+        };
+        let mut mock_item = state.blank_item(PublishedFileId(item_id));
+
+        mock_item.details.title = item.title;
+        mock_item.details.description = item.description.unwrap_or_default();
+        mock_item.details.tags = item.tags.unwrap_or_default();
+        mock_item.details.preview_url = item.preview_url;
+        if let Some(owner) = item.owner {
+            mock_item.details.owner = SteamId::from_raw(crate::id::bigint_u64("owner", &owner)?);
+        }
+
+        if item.subscribed.unwrap_or(false) {
+            mock_item.state |= ItemState::SUBSCRIBED;
+        }
+
+        if let Some(folder) = item.install_folder {
+            mock_item.state |= ItemState::INSTALLED;
+            mock_item.install = Some(ItemInstallInfo {
+                folder,
+                size_on_disk: match &item.size_on_disk {
+                    Some(size) => crate::id::bigint_u64("size_on_disk", size)?,
+                    None => 0,
+                },
+                timestamp: mock_item.details.time_updated,
+            });
+        }
+
+        Ok(BigInt::from(state.add_item(mock_item).0))
+    }
+
+    #[napi(catch_unwind)]
+    /// 设置 Steam 云文件内容
+    pub fn set_cloud_file(name: String, content: Buffer) -> Result<(), Error> {
+        get_mock()?.state().files.insert(name, content.to_vec());
+        Ok(())
+    }
+
+    #[napi(catch_unwind)]
+    /// 设置统计值，未设置的统计视为不存在
+    pub fn set_stat(name: String, value: i32) -> Result<(), Error> {
+        get_mock()?.state().stats.insert(name, value);
+        Ok(())
+    }
+
+    #[napi(catch_unwind)]
+    /// 设置成就解锁状态，未设置的成就视为不存在
+    pub fn set_achievement(name: String, achieved: bool) -> Result<(), Error> {
+        get_mock()?.state().achievements.insert(name, achieved);
+        Ok(())
+    }
+
+    #[napi(catch_unwind)]
+    /// 模拟收到一个 P2P 数据包
+    pub fn push_p2p_packet(steam_id64: BigInt, data: Buffer) -> Result<(), Error> {
+        let steam_id = SteamId::from_raw(crate::id::bigint_u64("steam_id64", &steam_id64)?);
+        get_mock()?
+            .state()
+            .incoming_packets
+            .push_back((steam_id, data.to_vec()));
+        Ok(())
+    }
+
+    #[napi(
+        ts_generic_types = "C extends keyof import('./callbacks').CallbackReturns",
+        catch_unwind
+    )]
+    /// 在下一次 runCallbacks 时分发一个回调事件
+    ///
+    /// @param steam_callback Steam用户操作回调
+    /// @param payload 回调内容
+    pub fn emit_callback(
+        env: Env,
+        #[napi(ts_arg_type = "C")] steam_callback: SteamApiCallback,
+        #[napi(ts_arg_type = "import('./callbacks').CallbackReturns[C]")] payload: JsUnknown,
+    ) -> Result<(), Error> {
+        // 事件内容的结构取决于回调类型，需要按类型转换
+        let payload =
+            unsafe { CallbackPayload::from_napi_value(steam_callback, env.raw(), payload.raw())? };
+        get_mock()?.emit(payload);
+        Ok(())
+    }
+
+    #[napi(catch_unwind)]
+    /// 为指定操作注入错误
+    ///
+    /// @param operation 后端操作名，如 "create_lobby"、"join_lobby"、"file_write"
+    /// @param eresult 返回的 EResult 错误码
+    /// @param times 触发次数，为空时一直生效
+    pub fn inject_fault(operation: String, eresult: u32, times: Option<u32>) -> Result<(), Error> {
+        get_mock()?
+            .state()
+            .inject_fault(&operation, steam_error_from_code(eresult), times);
+        Ok(())
+    }
+
+    #[napi(catch_unwind)]
+    /// 清除所有注入的错误
+    pub fn clear_faults() -> Result<(), Error> {
+        get_mock()?.state().clear_faults();
+        Ok(())
+    }
+
+    #[napi(catch_unwind)]
+    /// 设置异步调用结果与回调的分发延迟
+    ///
+    /// @param latency_ms 延迟毫秒数
+    pub fn set_latency(latency_ms: u32) -> Result<(), Error> {
+        get_mock()?.state().latency = Duration::from_millis(latency_ms as u64);
+        Ok(())
+    }
+}
@@ -1,12 +1,25 @@
+pub mod a2s;
 pub mod callback;
+pub mod condebug;
 pub mod consts;
+pub mod demo;
+pub mod gsi;
+pub mod hllog;
+pub mod logging;
+pub mod mock;
+pub mod payloads;
+pub mod pump;
+pub mod rcon;
+pub mod serverlist;
 pub mod steamapps;
 pub mod steamfriends;
 pub mod steaminput;
 pub mod steammatchmaking;
+pub mod steammatchmakingservers;
 pub mod steamnetworking;
 pub mod steamremotestorage;
 pub mod steamugc;
 pub mod steamuser;
 pub mod steamuserstats;
 pub mod steamutils;
+pub mod watch;
@@ -0,0 +1,548 @@
+//! 回调事件内容
+//!
+//! 每种回调都有对应的 `#[napi(object)]` 结构，64 位 ID 使用 `BigInt` 或 [`CSteamID`]，
+//! 枚举使用插件自己的枚举类型，生成的 TypeScript 定义与实际传给 JS 的值一致。
+
+use super::callback::SteamApiCallback;
+use super::consts::{CSteamID, EAuthSessionResponse, EChatMemberStateChange, EPersonaState};
+use crate::backend::callbacks::{
+    AvatarImageLoaded, FloatingGamepadTextInputDismissed, ItemInstalled, LobbyChatMsg, LobbyEnter,
+    SteamShutdown,
+};
+use napi::bindgen_prelude::{BigInt, FromNapiValue, Result, ToNapiValue};
+use napi::sys;
+use napi_derive::napi;
+use steamworks::SteamError;
+
+#[derive(Clone)]
+#[napi(object)]
+/// Steam 错误，与 `[STEAM_ERROR:<eresult>:<name>]` 错误信息中的内容一致
+pub struct SteamErrorInfo {
+    /// EResult 数值
+    pub eresult: u32,
+    /// EResult 名称
+    pub name: String,
+}
+
+impl SteamErrorInfo {
+    pub(crate) fn from_error(error: &SteamError) -> Self {
+        let (eresult, name) = crate::error::eresult(error);
+        Self { eresult, name }
+    }
+
+    fn from_result(result: &std::result::Result<(), SteamError>) -> Option<Self> {
+        result.as_ref().err().map(Self::from_error)
+    }
+}
+
+#[derive(Clone)]
+#[napi(object)]
+/// 好友的状态改变
+pub struct PersonaStateChangePayload {
+    /// 状态改变的用户
+    pub steam_id: CSteamID,
+    /// 改变的内容，EPersonaChange 标识的组合
+    pub flags: u32,
+    /// 用户当前的状态
+    pub state: EPersonaState,
+}
+
+#[derive(Clone)]
+#[napi(object)]
+/// 已连接到 Steam 服务器
+pub struct SteamServersConnectedPayload {}
+
+#[derive(Clone)]
+#[napi(object)]
+/// 与 Steam 服务器的连接已断开
+pub struct SteamServersDisconnectedPayload {
+    /// 断开的原因
+    pub reason: SteamErrorInfo,
+}
+
+#[derive(Clone)]
+#[napi(object)]
+/// 连接 Steam 服务器失败
+pub struct SteamServerConnectFailurePayload {
+    /// 失败的原因
+    pub reason: SteamErrorInfo,
+    /// Steam 客户端是否仍在尝试连接
+    pub still_retrying: bool,
+}
+
+#[derive(Clone)]
+#[napi(object)]
+/// 大厅或大厅成员的元数据已变更
+pub struct LobbyDataUpdatePayload {
+    /// 大厅的 Steam ID
+    pub lobby: BigInt,
+    /// 元数据变更的成员，大厅自身的元数据变更时与大厅相同
+    pub member: BigInt,
+    /// 是否成功
+    pub success: bool,
+}
+
+#[derive(Clone)]
+#[napi(object)]
+/// 大厅成员发生变化
+pub struct LobbyChatUpdatePayload {
+    /// 大厅的 Steam ID
+    pub lobby: BigInt,
+    /// 状态发生变化的用户
+    pub user_changed: CSteamID,
+    /// 造成变化的用户，例如踢出成员的管理员
+    pub making_change: CSteamID,
+    /// 成员状态的变化
+    pub member_state_change: EChatMemberStateChange,
+}
+
+#[derive(Clone)]
+#[napi(object)]
+/// 用户请求加入大厅
+pub struct GameLobbyJoinRequestedPayload {
+    /// 要加入的大厅的 Steam ID
+    pub lobby_steam_id: BigInt,
+    /// 发出邀请或正在该大厅中的好友
+    pub friend_steam_id: CSteamID,
+}
+
+#[derive(Clone)]
+#[napi(object)]
+/// 用户请求建立 P2P 会话
+pub struct P2PSessionRequestPayload {
+    /// 请求建立会话的用户
+    pub remote: CSteamID,
+}
+
+#[derive(Clone)]
+#[napi(object)]
+/// P2P 会话连接失败
+pub struct P2PSessionConnectFailPayload {
+    /// 连接失败的用户
+    pub remote: CSteamID,
+    /// EP2PSessionError
+    pub error: u32,
+}
+
+#[derive(Clone)]
+#[napi(object)]
+/// 用户响应了小额交易授权请求
+pub struct MicroTxnAuthorizationResponsePayload {
+    /// App ID
+    pub app_id: u32,
+    /// 订单 ID
+    pub order_id: BigInt,
+    /// 用户是否授权了交易
+    pub authorized: bool,
+}
+
+#[derive(Clone)]
+#[napi(object)]
+/// 用户的统计与成就数据已取得
+pub struct UserStatsReceivedPayload {
+    /// 数据所属的用户
+    pub steam_id: CSteamID,
+    /// 数据所属的游戏
+    pub game_id: BigInt,
+    /// 获取失败时的错误，成功时为空
+    pub error: Option<SteamErrorInfo>,
+}
+
+#[derive(Clone)]
+#[napi(object)]
+/// 统计与成就已上传
+pub struct UserStatsStoredPayload {
+    /// 数据所属的游戏
+    pub game_id: BigInt,
+    /// 上传失败时的错误，成功时为空
+    pub error: Option<SteamErrorInfo>,
+}
+
+#[derive(Clone)]
+#[napi(object)]
+/// 创意工坊物品下载完成
+pub struct DownloadItemResultPayload {
+    /// 物品所属的 App ID
+    pub app_id: u32,
+    /// 物品ID
+    pub published_file_id: BigInt,
+    /// 下载失败时的错误，成功时为空
+    pub error: Option<SteamErrorInfo>,
+}
+
+#[derive(Clone)]
+#[napi(object)]
+/// 创意工坊物品已安装或更新
+pub struct ItemInstalledPayload {
+    /// 物品所属的 App ID
+    pub app_id: u32,
+    /// 物品ID
+    pub published_file_id: BigInt,
+}
+
+#[derive(Clone)]
+#[napi(object)]
+/// Steam 界面打开或关闭
+pub struct GameOverlayActivatedPayload {
+    /// 界面是否打开
+    pub active: bool,
+}
+
+#[derive(Clone)]
+#[napi(object)]
+/// 用户接受了富状态邀请
+pub struct GameRichPresenceJoinRequestedPayload {
+    /// 发出邀请的好友
+    pub friend_steam_id: CSteamID,
+    /// 好友富状态中的 connect 字符串
+    pub connect: String,
+}
+
+#[derive(Clone)]
+#[napi(object)]
+/// 验证票证的结果
+pub struct ValidateAuthTicketResponsePayload {
+    /// 票证所属的用户
+    pub steam_id: CSteamID,
+    /// 验证失败的原因，成功时为空
+    pub error: Option<EAuthSessionResponse>,
+    /// 游戏的所有者，通过家庭共享游玩时与票证所属的用户不同
+    pub owner_steam_id: CSteamID,
+}
+
+#[derive(Clone)]
+#[napi(object)]
+/// 进入大厅
+pub struct LobbyEnterPayload {
+    /// 大厅的 Steam ID
+    pub lobby: BigInt,
+    /// 聊天权限，目前未使用
+    pub chat_permissions: u32,
+    /// 大厅是否锁定，只有受邀者可以加入
+    pub locked: bool,
+    /// EChatRoomEnterResponse，1 表示成功
+    pub chat_room_enter_response: u32,
+}
+
+#[derive(Clone)]
+#[napi(object)]
+/// 大厅收到聊天消息
+pub struct LobbyChatMsgPayload {
+    /// 大厅的 Steam ID
+    pub lobby: BigInt,
+    /// 发送消息的用户
+    pub user: CSteamID,
+    /// EChatEntryType，1 表示普通聊天消息
+    pub chat_entry_type: u32,
+    /// 消息编号
+    pub chat_id: u32,
+    /// 消息内容
+    pub message: String,
+}
+
+#[derive(Clone)]
+#[napi(object)]
+/// 用户头像加载完成
+pub struct AvatarImageLoadedPayload {
+    /// 头像所属的用户
+    pub steam_id: CSteamID,
+    /// 头像图片句柄
+    pub image: i32,
+    /// 图片宽度
+    pub width: i32,
+    /// 图片高度
+    pub height: i32,
+}
+
+#[derive(Clone)]
+#[napi(object)]
+/// 浮动游戏手柄文本输入框已关闭
+pub struct FloatingGamepadTextInputDismissedPayload {}
+
+#[derive(Clone)]
+#[napi(object)]
+/// Steam 客户端即将关闭
+pub struct SteamShutdownPayload {}
+
+#[derive(Clone)]
+#[napi(object)]
+/// Steam 会话已切换
+pub struct SessionChangedPayload {
+    /// 新会话编号
+    pub session: u32,
+    /// 新会话的 App ID
+    pub app_id: u32,
+    /// 切换前的 App ID，首次初始化时为空
+    pub previous_app_id: Option<u32>,
+}
+
+macro_rules! callback_payloads {
+    ($($kind:ident($payload:ident),)*) => {
+        /// 回调事件，每种回调对应一个事件内容结构
+        #[derive(Clone)]
+        pub enum CallbackPayload {
+            $($kind($payload),)*
+        }
+
+        impl CallbackPayload {
+            /// 事件所属的回调
+            pub fn kind(&self) -> SteamApiCallback {
+                match self {
+                    $(Self::$kind(_) => SteamApiCallback::$kind,)*
+                }
+            }
+
+            /// 按回调类型把 JS 值转换为事件
+            ///
+            /// # Safety
+            ///
+            /// `env` 与 `value` 必须是当前 JS 调用中有效的句柄
+            pub unsafe fn from_napi_value(
+                kind: SteamApiCallback,
+                env: sys::napi_env,
+                value: sys::napi_value,
+            ) -> Result<Self> {
+                match kind {
+                    $(SteamApiCallback::$kind => $payload::from_napi_value(env, value).map(Self::$kind),)*
+                }
+            }
+        }
+
+        impl ToNapiValue for CallbackPayload {
+            unsafe fn to_napi_value(env: sys::napi_env, val: Self) -> Result<sys::napi_value> {
+                match val {
+                    $(Self::$kind(payload) => $payload::to_napi_value(env, payload),)*
+                }
+            }
+        }
+
+        $(
+            impl From<$payload> for CallbackPayload {
+                fn from(payload: $payload) -> Self {
+                    Self::$kind(payload)
+                }
+            }
+        )*
+    };
+}
+
+callback_payloads! {
+    PersonaStateChange(PersonaStateChangePayload),
+    SteamServersConnected(SteamServersConnectedPayload),
+    SteamServersDisconnected(SteamServersDisconnectedPayload),
+    SteamServerConnectFailure(SteamServerConnectFailurePayload),
+    LobbyDataUpdate(LobbyDataUpdatePayload),
+    LobbyChatUpdate(LobbyChatUpdatePayload),
+    GameLobbyJoinRequested(GameLobbyJoinRequestedPayload),
+    P2PSessionRequest(P2PSessionRequestPayload),
+    P2PSessionConnectFail(P2PSessionConnectFailPayload),
+    MicroTxnAuthorizationResponse(MicroTxnAuthorizationResponsePayload),
+    UserStatsReceived(UserStatsReceivedPayload),
+    UserStatsStored(UserStatsStoredPayload),
+    DownloadItemResult(DownloadItemResultPayload),
+    ItemInstalled(ItemInstalledPayload),
+    GameOverlayActivated(GameOverlayActivatedPayload),
+    GameRichPresenceJoinRequested(GameRichPresenceJoinRequestedPayload),
+    ValidateAuthTicketResponse(ValidateAuthTicketResponsePayload),
+    LobbyEnter(LobbyEnterPayload),
+    LobbyChatMsg(LobbyChatMsgPayload),
+    AvatarImageLoaded(AvatarImageLoadedPayload),
+    FloatingGamepadTextInputDismissed(FloatingGamepadTextInputDismissedPayload),
+    SteamShutdown(SteamShutdownPayload),
+    SessionChanged(SessionChangedPayload),
+}
+
+impl From<&steamworks::SteamServersConnected> for SteamServersConnectedPayload {
+    fn from(_: &steamworks::SteamServersConnected) -> Self {
+        Self {}
+    }
+}
+
+impl From<&steamworks::SteamServersDisconnected> for SteamServersDisconnectedPayload {
+    fn from(value: &steamworks::SteamServersDisconnected) -> Self {
+        Self {
+            reason: SteamErrorInfo::from_error(&value.reason),
+        }
+    }
+}
+
+impl From<&steamworks::SteamServerConnectFailure> for SteamServerConnectFailurePayload {
+    fn from(value: &steamworks::SteamServerConnectFailure) -> Self {
+        Self {
+            reason: SteamErrorInfo::from_error(&value.reason),
+            still_retrying: value.still_retrying,
+        }
+    }
+}
+
+impl From<&steamworks::LobbyDataUpdate> for LobbyDataUpdatePayload {
+    fn from(value: &steamworks::LobbyDataUpdate) -> Self {
+        Self {
+            lobby: value.lobby.raw().into(),
+            member: value.member.raw().into(),
+            success: value.success,
+        }
+    }
+}
+
+impl From<&steamworks::LobbyChatUpdate> for LobbyChatUpdatePayload {
+    fn from(value: &steamworks::LobbyChatUpdate) -> Self {
+        Self {
+            lobby: value.lobby.raw().into(),
+            user_changed: CSteamID::from_steamid(value.user_changed),
+            making_change: CSteamID::from_steamid(value.making_change),
+            member_state_change: (&value.member_state_change).into(),
+        }
+    }
+}
+
+impl From<&steamworks::GameLobbyJoinRequested> for GameLobbyJoinRequestedPayload {
+    fn from(value: &steamworks::GameLobbyJoinRequested) -> Self {
+        Self {
+            lobby_steam_id: value.lobby_steam_id.raw().into(),
+            friend_steam_id: CSteamID::from_steamid(value.friend_steam_id),
+        }
+    }
+}
+
+impl From<&steamworks::P2PSessionRequest> for P2PSessionRequestPayload {
+    fn from(value: &steamworks::P2PSessionRequest) -> Self {
+        Self {
+            remote: CSteamID::from_steamid(value.remote),
+        }
+    }
+}
+
+impl From<&steamworks::P2PSessionConnectFail> for P2PSessionConnectFailPayload {
+    fn from(value: &steamworks::P2PSessionConnectFail) -> Self {
+        Self {
+            remote: CSteamID::from_steamid(value.remote),
+            error: value.error.into(),
+        }
+    }
+}
+
+impl From<&steamworks::MicroTxnAuthorizationResponse> for MicroTxnAuthorizationResponsePayload {
+    fn from(value: &steamworks::MicroTxnAuthorizationResponse) -> Self {
+        Self {
+            app_id: value.app_id.0,
+            order_id: value.order_id.into(),
+            authorized: value.authorized,
+        }
+    }
+}
+
+impl From<&steamworks::UserStatsReceived> for UserStatsReceivedPayload {
+    fn from(value: &steamworks::UserStatsReceived) -> Self {
+        Self {
+            steam_id: CSteamID::from_steamid(value.steam_id),
+            game_id: value.game_id.raw().into(),
+            error: SteamErrorInfo::from_result(&value.result),
+        }
+    }
+}
+
+impl From<&steamworks::UserStatsStored> for UserStatsStoredPayload {
+    fn from(value: &steamworks::UserStatsStored) -> Self {
+        Self {
+            game_id: value.game_id.raw().into(),
+            error: SteamErrorInfo::from_result(&value.result),
+        }
+    }
+}
+
+impl From<&steamworks::DownloadItemResult> for DownloadItemResultPayload {
+    fn from(value: &steamworks::DownloadItemResult) -> Self {
+        Self {
+            app_id: value.app_id.0,
+            published_file_id: value.published_file_id.0.into(),
+            error: value.error.as_ref().map(SteamErrorInfo::from_error),
+        }
+    }
+}
+
+impl From<&ItemInstalled> for ItemInstalledPayload {
+    fn from(value: &ItemInstalled) -> Self {
+        Self {
+            app_id: value.app_id.0,
+            published_file_id: value.published_file_id.0.into(),
+        }
+    }
+}
+
+impl From<&steamworks::GameOverlayActivated> for GameOverlayActivatedPayload {
+    fn from(value: &steamworks::GameOverlayActivated) -> Self {
+        Self {
+            active: value.active,
+        }
+    }
+}
+
+impl From<&steamworks::GameRichPresenceJoinRequested> for GameRichPresenceJoinRequestedPayload {
+    fn from(value: &steamworks::GameRichPresenceJoinRequested) -> Self {
+        Self {
+            friend_steam_id: CSteamID::from_steamid(value.friend_steam_id),
+            connect: value.connect.clone(),
+        }
+    }
+}
+
+impl From<&steamworks::ValidateAuthTicketResponse> for ValidateAuthTicketResponsePayload {
+    fn from(value: &steamworks::ValidateAuthTicketResponse) -> Self {
+        Self {
+            steam_id: CSteamID::from_steamid(value.steam_id),
+            error: value
+                .response
+                .as_ref()
+                .err()
+                .map(EAuthSessionResponse::from),
+            owner_steam_id: CSteamID::from_steamid(value.owner_steam_id),
+        }
+    }
+}
+
+impl From<&LobbyEnter> for LobbyEnterPayload {
+    fn from(value: &LobbyEnter) -> Self {
+        Self {
+            lobby: value.lobby.raw().into(),
+            chat_permissions: value.chat_permissions,
+            locked: value.locked,
+            chat_room_enter_response: value.chat_room_enter_response,
+        }
+    }
+}
+
+impl From<&LobbyChatMsg> for LobbyChatMsgPayload {
+    fn from(value: &LobbyChatMsg) -> Self {
+        Self {
+            lobby: value.lobby.raw().into(),
+            user: CSteamID::from_steamid(value.user),
+            chat_entry_type: value.chat_entry_type.into(),
+            chat_id: value.chat_id,
+            message: value.message.clone(),
+        }
+    }
+}
+
+impl From<&AvatarImageLoaded> for AvatarImageLoadedPayload {
+    fn from(value: &AvatarImageLoaded) -> Self {
+        Self {
+            steam_id: CSteamID::from_steamid(value.steam_id),
+            image: value.image,
+            width: value.width,
+            height: value.height,
+        }
+    }
+}
+
+impl From<&FloatingGamepadTextInputDismissed> for FloatingGamepadTextInputDismissedPayload {
+    fn from(_: &FloatingGamepadTextInputDismissed) -> Self {
+        Self {}
+    }
+}
+
+impl From<&SteamShutdown> for SteamShutdownPayload {
+    fn from(_: &SteamShutdown) -> Self {
+        Self {}
+    }
+}
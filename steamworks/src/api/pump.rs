@@ -0,0 +1,124 @@
+use napi::bindgen_prelude::Error;
+use napi_derive::napi;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::sync::{Mutex, PoisonError};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+/// 默认回调分发间隔，约每秒 30 次
+pub const DEFAULT_INTERVAL_MS: u32 = 33;
+
+/// 后台回调分发线程
+struct PumpThread {
+    interval_ms: u32,
+    stop: Sender<()>,
+    thread: JoinHandle<()>,
+}
+
+impl PumpThread {
+    fn shutdown(self) {
+        let _ = self.stop.send(());
+        let _ = self.thread.join();
+    }
+}
+
+lazy_static! {
+    static ref PUMP: Mutex<Option<PumpThread>> = Mutex::new(None);
+}
+
+/// 后台线程已执行的分发次数
+static TICKS: AtomicU64 = AtomicU64::new(0);
+
+/// 启动后台回调分发，已在运行时按新的间隔重新启动
+///
+/// 所有分发都在同一个线程中串行进行，并与 JS 调用的 `runCallbacks` 互斥，
+/// 回调按 Steam 产生的顺序送达 JS。
+pub fn start_pump(interval_ms: u32) -> Result<(), Error> {
+    let mut pump = PUMP.lock().unwrap_or_else(PoisonError::into_inner);
+    if let Some(pump) = pump.take() {
+        pump.shutdown();
+    }
+
+    let interval_ms = interval_ms.max(1);
+    let interval = Duration::from_millis(u64::from(interval_ms));
+    let (stop, stopped) = mpsc::channel();
+
+    let thread = std::thread::Builder::new()
+        .name("steam-callbacks".to_string())
+        .spawn(move || loop {
+            // 客户端未初始化或已关闭时跳过本次分发
+            // 分发中 panic 时跳过本次分发，线程继续运行
+            if let Ok(client) = crate::client::get_client() {
+                if crate::error::catch_panic("run_callbacks", || client.run_callbacks()).is_ok() {
+                    TICKS.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+
+            match stopped.recv_timeout(interval) {
+                Err(RecvTimeoutError::Timeout) => continue,
+                _ => break,
+            }
+        })
+        .map_err(|e| Error::from_reason(e.to_string()))?;
+
+    *pump = Some(PumpThread {
+        interval_ms,
+        stop,
+        thread,
+    });
+    Ok(())
+}
+
+/// 停止后台回调分发，等待正在进行的分发结束后返回
+pub fn stop_pump() {
+    let pump = PUMP.lock().unwrap_or_else(PoisonError::into_inner).take();
+    if let Some(pump) = pump {
+        pump.shutdown();
+    }
+}
+
+#[napi]
+/// 后台回调分发，`init` 成功后自动以默认间隔启动
+pub mod pump {
+    use super::{start_pump, stop_pump, DEFAULT_INTERVAL_MS, PUMP, TICKS};
+    use napi::bindgen_prelude::Error;
+    use std::sync::atomic::Ordering;
+    use std::sync::PoisonError;
+
+    #[napi(object)]
+    /// 后台回调分发状态
+    pub struct PumpStatus {
+        /// 是否正在运行
+        pub running: bool,
+        /// 分发间隔毫秒数，未运行时为 0
+        pub interval_ms: u32,
+        /// 已执行的分发次数
+        pub ticks: i64,
+    }
+
+    #[napi(catch_unwind)]
+    /// 启动后台回调分发，启动后无需再手动调用 runCallbacks
+    ///
+    /// @param interval_ms 分发间隔毫秒数，默认 33
+    pub fn start(interval_ms: Option<u32>) -> Result<(), Error> {
+        start_pump(interval_ms.unwrap_or(DEFAULT_INTERVAL_MS))
+    }
+
+    #[napi(catch_unwind)]
+    /// 停止后台回调分发，之后需要手动调用 runCallbacks
+    pub fn stop() {
+        stop_pump()
+    }
+
+    #[napi(catch_unwind)]
+    /// 获取后台回调分发状态
+    pub fn status() -> PumpStatus {
+        let pump = PUMP.lock().unwrap_or_else(PoisonError::into_inner);
+        PumpStatus {
+            running: pump.is_some(),
+            interval_ms: pump.as_ref().map_or(0, |pump| pump.interval_ms),
+            ticks: TICKS.load(Ordering::Relaxed) as i64,
+        }
+    }
+}
@@ -0,0 +1,99 @@
+use napi_derive::napi;
+
+#[napi]
+/// Source RCON，通过 TCP 执行服务器控制台命令，不需要初始化 Steam
+pub mod rcon {
+    use crate::api::logging::steam_log;
+    use crate::rcon::{self as client, Connection};
+    use napi::bindgen_prelude::Error;
+    use std::sync::Arc;
+    use std::time::Duration;
+    use tokio::sync::Mutex;
+
+    #[napi(object)]
+    /// RCON 连接选项
+    pub struct RconOptions {
+        /// 连接、验证与每条命令各自的超时时间（毫秒），默认 5000
+        pub timeout_ms: Option<u32>,
+    }
+
+    #[napi]
+    /// RCON 连接，同一连接上的命令按调用顺序依次执行
+    pub struct RconConnection {
+        /// 连接的地址
+        pub address: String,
+        connection: Arc<Mutex<Connection>>,
+    }
+
+    #[napi]
+    impl RconConnection {
+        #[napi]
+        /// 执行控制台命令
+        ///
+        /// 连接断开时会自动重新连接并验证，命令开始回复之前断开时会重试一次。
+        ///
+        /// @param command 控制台命令，如 `status`
+        /// @returns 命令的完整回复，多个包的回复会被合并
+        pub async fn exec(&self, command: String) -> Result<String, Error> {
+            crate::error::catch_panic_async("rcon_exec", async move {
+                let mut connection = self.connection.lock().await;
+                connection.exec(&command).await.map_err(|error| {
+                    steam_log!(
+                        Debug,
+                        "rcon",
+                        "rcon_exec {} failed: {}",
+                        self.address,
+                        error
+                    );
+                    error.context("rcon_exec", &self.address).into()
+                })
+            })
+            .await?
+        }
+
+        #[napi]
+        /// 关闭连接，等待正在执行的命令结束，之后执行命令会失败
+        ///
+        /// @noreturns
+        pub async fn close(&self) -> Result<(), Error> {
+            crate::error::catch_panic_async("rcon_close", async move {
+                self.connection.lock().await.close();
+            })
+            .await?;
+            Ok(())
+        }
+    }
+
+    #[napi]
+    /// 连接 RCON 并验证密码
+    ///
+    /// @param address 服务器地址，`ip:port` 或 `域名:port`，未指定端口时使用 27015
+    /// @param password RCON 密码，即服务器的 `rcon_password`
+    /// @param options 连接选项
+    /// @returns 验证成功的连接，密码错误时错误码为 AUTH_FAILED
+    pub async fn connect(
+        address: String,
+        password: String,
+        options: Option<RconOptions>,
+    ) -> Result<RconConnection, Error> {
+        crate::error::catch_panic_async("rcon_connect", async move {
+            let timeout = options
+                .and_then(|options| options.timeout_ms)
+                .map_or(client::DEFAULT_TIMEOUT, |ms| {
+                    Duration::from_millis(u64::from(ms))
+                });
+            let connection = Connection::connect(&address, &password, timeout)
+                .await
+                .map_err(|error| {
+                    steam_log!(Debug, "rcon", "rcon_connect {} failed: {}", address, error);
+                    Error::from(error.context("rcon_connect", &address))
+                })?;
+
+            Ok(RconConnection {
+                address,
+                connection: Arc::new(Mutex::new(connection)),
+            })
+        })
+        .await?
+    }
+}
@@ -0,0 +1,233 @@
+use crate::serverlist::EntrySource;
+use napi_derive::napi;
+
+#[napi]
+/// 导入的服务器来自哪个列表
+pub enum EServerEntrySource {
+    /// serverbrowser_hist.vdf 中的收藏夹
+    Favorites,
+    /// serverbrowser_hist.vdf 中的历史记录
+    History,
+    /// 纯文本列表
+    List,
+}
+
+impl From<EntrySource> for EServerEntrySource {
+    fn from(value: EntrySource) -> Self {
+        match value {
+            EntrySource::Favorites => EServerEntrySource::Favorites,
+            EntrySource::History => EServerEntrySource::History,
+            EntrySource::List => EServerEntrySource::List,
+        }
+    }
+}
+
+#[napi]
+/// 从 Steam 配置文件与纯文本导入服务器列表和黑名单，不需要初始化 Steam
+pub mod serverlist {
+    use super::EServerEntrySource;
+    use crate::api::logging::steam_log;
+    use crate::error::SteamworksError;
+    use crate::serverlist::blacklist::{Blacklist, BlacklistRange};
+    use crate::serverlist::{self, ServerEntry};
+    use napi::bindgen_prelude::Error;
+    use napi::JsObject;
+    use std::sync::Arc;
+
+    #[napi(object)]
+    /// 导入的服务器
+    pub struct ImportedServer {
+        /// `ip:port` 或 `域名:port`，未指定端口时为 27015
+        pub address: String,
+        /// 服务器名称，纯文本列表中没有
+        pub name: Option<String>,
+        /// 服务器所属游戏的 AppId，纯文本列表中没有
+        pub app_id: Option<u32>,
+        /// 最后一次游戏的 Unix 时间（秒），纯文本列表中没有
+        pub last_played: Option<u32>,
+        pub source: EServerEntrySource,
+    }
+
+    impl From<ServerEntry> for ImportedServer {
+        fn from(entry: ServerEntry) -> Self {
+            Self {
+                address: entry.address,
+                name: entry.name,
+                app_id: entry.app_id,
+                last_played: entry.last_played,
+                source: entry.source.into(),
+            }
+        }
+    }
+
+    #[napi(object)]
+    /// 一条黑名单规则
+    pub struct BlacklistRule {
+        /// 规则，单个地址为 `ip` 或 `ip:port`，区间为 `首地址-尾地址`，可以直接传给 createBlacklist
+        pub rule: String,
+        /// Steam 黑名单中记录的服务器名称
+        pub name: Option<String>,
+    }
+
+    impl From<&BlacklistRange> for BlacklistRule {
+        fn from(range: &BlacklistRange) -> Self {
+            Self {
+                rule: range.to_string(),
+                name: range.name.clone(),
+            }
+        }
+    }
+
+    /// 读取文本文件，无效的 UTF-8 按替换字符处理
+    fn read_text(call: &'static str, path: &str) -> Result<String, SteamworksError> {
+        std::fs::read(path)
+            .map(|data| String::from_utf8_lossy(&data).into_owned())
+            .map_err(|error| SteamworksError::Io {
+                call,
+                path: path.to_string(),
+                error,
+            })
+    }
+
+    fn warn_invalid(call: &'static str, path: &str, invalid: &[String]) {
+        if let Some(first) = invalid.first() {
+            steam_log!(
+                Warn,
+                "serverlist",
+                "{} {}: skipped {} invalid line(s), first: {:?}",
+                call,
+                path,
+                invalid.len(),
+                first
+            );
+        }
+    }
+
+    #[napi]
+    /// 导入 Steam 服务器浏览器的收藏夹与历史记录
+    ///
+    /// @param path `Steam/userdata/<账号 ID>/7/remote/serverbrowser_hist.vdf`
+    /// 或旧版本的 `Steam/config/serverbrowser_hist.vdf`
+    /// @returns 收藏夹中的服务器在前，历史记录在后，同一服务器可能在两个列表中各出现一次
+    pub async fn import_server_history(path: String) -> Result<Vec<ImportedServer>, Error> {
+        crate::error::catch_panic_async("import_server_history", async move {
+            let text = read_text("import_server_history", &path)?;
+            let entries =
+                serverlist::parse_history(&text).map_err(|error| SteamworksError::Parse {
+                    call: "import_server_history",
+                    path: path.clone(),
+                    message: error.to_string(),
+                })?;
+            Ok(entries.into_iter().map(ImportedServer::from).collect())
+        })
+        .await?
+    }
+
+    #[napi]
+    /// 导入每行一个地址的纯文本服务器列表，支持 `//`、`#`、`;` 注释与 `connect` 前缀，
+    /// 无法解析的行会被跳过并记录日志
+    ///
+    /// @param path 文件路径
+    /// @returns 去重后的服务器，保持文件中的顺序
+    pub async fn import_server_list(path: String) -> Result<Vec<ImportedServer>, Error> {
+        crate::error::catch_panic_async("import_server_list", async move {
+            let text = read_text("import_server_list", &path)?;
+            let (entries, invalid) = serverlist::parse_list(&text);
+            warn_invalid("import_server_list", &path, &invalid);
+            Ok(entries.into_iter().map(ImportedServer::from).collect())
+        })
+        .await?
+    }
+
+    #[napi]
+    /// 服务器黑名单
+    pub struct ServerBlacklist {
+        blacklist: Arc<Blacklist>,
+    }
+
+    #[napi]
+    impl ServerBlacklist {
+        #[napi(catch_unwind)]
+        /// 黑名单中的所有规则
+        pub fn rules(&self) -> Vec<BlacklistRule> {
+            self.blacklist
+                .ranges()
+                .iter()
+                .map(BlacklistRule::from)
+                .collect()
+        }
+
+        #[napi(catch_unwind)]
+        /// 服务器是否在黑名单中
+        ///
+        /// @param address `ip:port`，未指定端口时使用 27015，域名无法判断，总是返回 false
+        pub fn is_blocked(&self, address: String) -> bool {
+            self.blacklist.contains_address(&address)
+        }
+
+        #[napi(
+            catch_unwind,
+            ts_generic_types = "T extends { address: string }",
+            ts_args_type = "servers: Array<T>",
+            ts_return_type = "Array<T>"
+        )]
+        /// 移除黑名单中的服务器，可以用于 A2S 查询、服务器浏览器与导入的服务器列表
+        ///
+        /// @param servers 含 address 属性的对象，没有 address 属性的对象会被保留
+        /// @returns 不在黑名单中的服务器，保持原来的顺序
+        pub fn filter(&self, servers: Vec<JsObject>) -> Result<Vec<JsObject>, Error> {
+            let mut kept = Vec::with_capacity(servers.len());
+            for server in servers {
+                let address: Option<String> = server.get("address")?;
+                if !address.is_some_and(|address| self.blacklist.contains_address(&address)) {
+                    kept.push(server);
+                }
+            }
+            Ok(kept)
+        }
+    }
+
+    #[napi]
+    /// 读取服务器黑名单，支持 Steam 服务器浏览器导出的黑名单与每行一个规则的纯文本，
+    /// 无法解析的规则会被跳过并记录日志
+    ///
+    /// @param path 文件路径，如游戏目录中的 `cfg/server_blacklist.txt`
+    /// @returns 黑名单
+    pub async fn load_blacklist(path: String) -> Result<ServerBlacklist, Error> {
+        crate::error::catch_panic_async("load_blacklist", async move {
+            let text = read_text("load_blacklist", &path)?;
+            let (blacklist, invalid) =
+                Blacklist::parse(&text).map_err(|error| SteamworksError::Parse {
+                    call: "load_blacklist",
+                    path: path.clone(),
+                    message: error.to_string(),
+                })?;
+            warn_invalid("load_blacklist", &path, &invalid);
+            Ok(ServerBlacklist {
+                blacklist: Arc::new(blacklist),
+            })
+        })
+        .await?
+    }
+
+    #[napi(catch_unwind)]
+    /// 根据规则创建黑名单
+    ///
+    /// @param rules 规则列表，如 `1.2.3.4:27015`、`1.2.3.4`（所有端口）、`1.2.3.*`、`1.2.3.0/24`、`1.2.3.4-1.2.3.20`
+    /// @returns 黑名单，有无法解析的规则时抛出 PARSE_ERROR
+    pub fn create_blacklist(rules: Vec<String>) -> Result<ServerBlacklist, Error> {
+        let ranges = rules
+            .iter()
+            .map(|rule| {
+                BlacklistRange::parse(rule).ok_or_else(|| SteamworksError::Parse {
+                    call: "create_blacklist",
+                    path: format!("{:?}", rule),
+                    message: "invalid blacklist rule".to_string(),
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(ServerBlacklist {
+            blacklist: Arc::new(Blacklist::new(ranges)),
+        })
+    }
+}
@@ -4,8 +4,8 @@ use napi_derive::napi;
 #[napi]
 pub mod steamfriends {
     use super::{EFriendFlags, EPersonaState, PSteamDialog, EOverlayToStoreFlag};
-    use napi::bindgen_prelude::BigInt;
-    use steamworks::{FriendGame, SteamId, OverlayToStoreFlag, FriendFlags};
+    use napi::bindgen_prelude::{BigInt, Either, Error};
+    use steamworks::{FriendGame, OverlayToStoreFlag, FriendFlags};
 
     #[napi(object)]
     /// 好友游玩信息
@@ -52,78 +52,79 @@ pub mod steamfriends {
         pub game: Option<FriendGameInfo>,
     }
 
-    #[napi]
+    #[napi(catch_unwind)]
     /// 获取当前用户的昵称（显示名称）
     /// 此名称与用户的社区个人资料页面显示的名称相同
     /// 要获取其他用户的昵称，请使用 GetFriendPersonaName
     ///
     /// @returns UTF-8 格式的当前用户的昵称. 保证不为 NULL
-    pub fn get_persona_name() -> String {
-        let client = crate::client::get_client();
-        client.friends().name()
+    pub fn get_persona_name() -> Result<String, Error> {
+        let client = crate::client::get_client()?;
+        Ok(client.persona_name())
     }
 
-    #[napi]
+    #[napi(catch_unwind)]
     /// 设置当前用户的丰富状态键/值, 该键/值会自动分享给玩同一游戏的所有好友
     /// 详情 https://partner.steamgames.com/doc/api/ISteamFriends#SetRichPresence
     ///
     /// @param pchKey 要设置的丰富状态"键"
     /// @param pchValue 要与 pchKey 关联的丰富状态"值". 若设为空字符串（""）或 NULL, 便会移除已设置的键
-    pub fn set_rich_presence(key: String, value: Option<String>) {
-        let client = crate::client::get_client();
-        client.friends().set_rich_presence(&key, value.as_deref());
+    pub fn set_rich_presence(key: String, value: Option<String>) -> Result<(), Error> {
+        let client = crate::client::get_client()?;
+        client.set_rich_presence(&key, value.as_deref());
+        Ok(())
     }
 
-    #[napi]
+    #[napi(catch_unwind)]
     /// 检查指定的好友是否在游戏中，若是则获取游戏的相关信息。
     ///
     /// @param steam_id64 另一位用户的 Steam ID
     /// @returns true 表示用户为好友且在游戏中, 否则返回 false
-    pub fn get_friend_game_played(steam_id64: BigInt) -> Option<FriendGameInfo> {
-        let client = crate::client::get_client();
-        let result = client
-            .friends()
-            .get_friend(SteamId::from_raw(steam_id64.get_u64().1))
-            .game_played();
+    pub fn get_friend_game_played(
+        steam_id64: Either<BigInt, String>,
+    ) -> Result<Option<FriendGameInfo>, Error> {
+        let steam_id = crate::id::steam_id("steam_id64", &steam_id64)?;
+        let client = crate::client::get_client()?;
+        let result = client.friend_game_played(steam_id);
 
-        FriendGameInfo::from_friend_game(result)
+        Ok(FriendGameInfo::from_friend_game(result))
     }
 
-    #[napi]
+    #[napi(catch_unwind)]
     /// 获取好友所有信息
     ///
     /// @param flags 一个或多个 EFriendFlags 的并集（二进制）
     /// @returns 返回指定标识所有好友信息
-    pub fn get_friends(flag: EFriendFlags) -> Vec<FrinedInfo> {
-        let client = crate::client::get_client();
+    pub fn get_friends(flag: EFriendFlags) -> Result<Vec<FrinedInfo>, Error> {
+        let client = crate::client::get_client()?;
         let eflag = FriendFlags::from(flag);
-        client
-            .friends()
-            .get_friends(eflag)
+        Ok(client
+            .friends(eflag)
             .into_iter()
             .map(|friend| FrinedInfo {
-                steamid: BigInt::from(friend.id().raw()),
-                name: friend.name(),
-                state: EPersonaState::from(friend.state()),
-                has_friend: friend.has_friend(eflag),
-                game: FriendGameInfo::from_friend_game(friend.game_played()),
+                steamid: BigInt::from(friend.steam_id.raw()),
+                name: friend.name,
+                state: EPersonaState::from(friend.state),
+                has_friend: friend.has_friend,
+                game: FriendGameInfo::from_friend_game(friend.game),
             })
-            .collect()
+            .collect())
     }
 
-    #[napi]
+    #[napi(catch_unwind)]
     /// 激活 Steam 界面，打开指定的对话框。
     /// 等同于调用 ActivateGameOverlayToUser，其中steamID 设为 ISteamUser::GetSteamID。
     ///
     /// @param dialog 要打开的对话框。
     /// - 有效选项包括“好友”、“社区”、“玩家”、“设置”、“官方游戏组”、“统计”和“成就”。
     /// @noreturns
-    pub fn activate_game_overlay(dialog: PSteamDialog) {
-        let client = crate::client::get_client();
-        client.friends().activate_game_overlay(&dialog.to_string())
+    pub fn activate_game_overlay(dialog: PSteamDialog) -> Result<(), Error> {
+        let client = crate::client::get_client()?;
+        client.activate_game_overlay(&dialog.to_string());
+        Ok(())
     }
 
-    #[napi]
+    #[napi(catch_unwind)]
     /// 激活 Steam 界面，打开指定的对话框。
     /// 有效 pchDialog 选项包括：
     /// - "steamid": 打开界面网页浏览器，前往指定的用户或组资料。
@@ -139,42 +140,48 @@ pub mod steamfriends {
     /// @param dialog 要打开的对话框。
     /// @param steam_id64 要将此对话框打开至的上下文的 Steam ID。
     /// @noreturns
-    pub fn activate_game_overlay_to_user(dialog: PSteamDialog, steam_id64: BigInt) {
-        let client = crate::client::get_client();
-        client.friends().activate_game_overlay_to_user(
-            &dialog.to_string(),
-            steamworks::SteamId::from_raw(steam_id64.get_u64().1),
-        )
+    pub fn activate_game_overlay_to_user(
+        dialog: PSteamDialog,
+        steam_id64: Either<BigInt, String>,
+    ) -> Result<(), Error> {
+        let steam_id = crate::id::steam_id("steam_id64", &steam_id64)?;
+        let client = crate::client::get_client()?;
+        client.activate_game_overlay_to_user(&dialog.to_string(), steam_id);
+        Ok(())
     }
 
-    #[napi]
+    #[napi(catch_unwind)]
     /// 激活 Steam 界面，打开邀请对话框。 进入该大厅的邀请将从此窗口发出。
     ///
     /// @param lobby_id 选定玩家将受邀进入的大厅的 Steam ID
     /// @noreturns
-    pub fn activate_game_overlay_invite_dialog(lobby_id: BigInt) {
-        let client = crate::client::get_client();
-        client
-            .friends()
-            .activate_invite_dialog(steamworks::LobbyId::from_raw(lobby_id.get_u64().1))
+    pub fn activate_game_overlay_invite_dialog(
+        lobby_id: Either<BigInt, String>,
+    ) -> Result<(), Error> {
+        let lobby_id = crate::id::lobby_id("lobby_id", &lobby_id)?;
+        let client = crate::client::get_client()?;
+        client.activate_invite_dialog(lobby_id);
+        Ok(())
     }
 
-    #[napi]
+    #[napi(catch_unwind)]
     /// 激活 Steam 界面网页浏览器，直接前往指定的 URL。
     ///
     /// @param url 要打开的网页。 （须有完整且符合协议的网址，例如“http://www.steampowered.com”）
     /// @noreturns
-    pub fn activate_game_overlay_to_web_page(url: String) {
-        let client = crate::client::get_client();
-        client.friends().activate_game_overlay_to_web_page(&url)
+    pub fn activate_game_overlay_to_web_page(url: String) -> Result<(), Error> {
+        let client = crate::client::get_client()?;
+        client.activate_game_overlay_to_web_page(&url);
+        Ok(())
     }
 
-    #[napi]
-    pub fn activate_to_store(app_id: u32, flag: EOverlayToStoreFlag) {
-        let client = crate::client::get_client();
-        client.friends().activate_game_overlay_to_store(
+    #[napi(catch_unwind)]
+    pub fn activate_to_store(app_id: u32, flag: EOverlayToStoreFlag) -> Result<(), Error> {
+        let client = crate::client::get_client()?;
+        client.activate_game_overlay_to_store(
             steamworks::AppId(app_id),
             OverlayToStoreFlag::from(flag),
-        )
+        );
+        Ok(())
     }
 }
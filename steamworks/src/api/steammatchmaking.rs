@@ -1,14 +1,28 @@
 use super::consts::{CSteamID, ELobbyType};
 use napi_derive::napi;
 
+#[napi]
+/// 收藏服务器的列表标识，对应 k_unFavoriteFlag*
+pub enum EFavoriteFlags {
+    /// 无
+    None = 0x00,
+    /// 收藏夹
+    Favorite = 0x01,
+    /// 历史记录
+    History = 0x02,
+}
+
 #[napi]
 /// 为客户端提供匹配服务、访问收藏夹以及在游戏大厅中进行操作的函数。
 /// https://partner.steamgames.com/doc/api/ISteamMatchmaking
 pub mod steammatchmaking {
     use super::{CSteamID, ELobbyType};
-    use napi::bindgen_prelude::{BigInt, Error};
+    use crate::backend;
+    use crate::error::{steam_error, SteamworksError};
+    use napi::bindgen_prelude::{BigInt, Either, Error};
     use std::collections::HashMap;
-    use steamworks::{LobbyId, LobbyType};
+    use std::net::Ipv4Addr;
+    use steamworks::{AppId, LobbyId, LobbyType, SteamError};
     use tokio::sync::oneshot;
 
     #[napi]
@@ -26,102 +40,101 @@ pub mod steammatchmaking {
         /// @returns SteamAPICall_t，与LobbyEnter_t 调用结果一起使用。
         /// 触发一个 LobbyDataUpdate_t 回调。
         pub async fn join(&self) -> Result<Lobby, Error> {
-            join_lobby(self.id.clone()).await
+            crate::error::catch_panic_async("Lobby::join", async move {
+                join_lobby(Either::A(self.id.clone())).await
+            })
+            .await?
         }
 
-        #[napi]
+        #[napi(catch_unwind)]
         /// 离开用户当前所在的大厅，这将立即在客户端生效，大厅的其他用户将得到 LobbyChatUpdate_t 回调的通知。
         ///
         /// @noreturns
-        pub fn leave(&self) {
-            let client = crate::client::get_client();
-            client.matchmaking().leave_lobby(self.lobby_id);
+        pub fn leave(&self) -> Result<(), Error> {
+            let client = crate::client::get_client()?;
+            client.leave_lobby(self.lobby_id);
+            Ok(())
         }
 
-        #[napi]
+        #[napi(catch_unwind)]
         /// 激活 Steam 界面，打开邀请对话框。 进入该大厅的邀请将从此窗口发出。
-        pub fn open_invite_dialog(&self) {
-            let client = crate::client::get_client();
-            client.friends().activate_invite_dialog(self.lobby_id);
+        pub fn open_invite_dialog(&self) -> Result<(), Error> {
+            let client = crate::client::get_client()?;
+            client.activate_invite_dialog(self.lobby_id);
+            Ok(())
         }
 
-        #[napi]
+        #[napi(catch_unwind)]
         /// 获得一个大厅中的用户数。
         /// 注意： 当前用户必须在大厅中才能获取该大厅中其他用户的 Steam ID。
         /// 用于循环访问，调用此函数后，可使用 GetLobbyMemberByIndex 来获得大厅中每个成员的 Steam ID。
         /// 通过 ISteamFriends 接口可自动收到大厅其他成员的个人信息（姓名、头像等）。
         ///
         /// @returns 大厅中成员的数量，如果当前用户没有来自大厅的数据，则为 0。
-        pub fn get_member_count(&self) -> usize {
-            let client = crate::client::get_client();
-            client.matchmaking().lobby_member_count(self.lobby_id)
+        pub fn get_member_count(&self) -> Result<usize, Error> {
+            let client = crate::client::get_client()?;
+            Ok(client.lobby_member_count(self.lobby_id))
         }
 
-        #[napi]
+        #[napi(catch_unwind)]
         /// 当前对于可以加入的玩家数量的限制。
         /// 如果没有定义限制，返回 0。
         ///
         /// @returns 如果指定大厅没有可用元数据，则返回 0。
-        pub fn get_member_limit(&self) -> Option<usize> {
-            let client = crate::client::get_client();
-            client.matchmaking().lobby_member_limit(self.lobby_id)
+        pub fn get_member_limit(&self) -> Result<Option<usize>, Error> {
+            let client = crate::client::get_client()?;
+            Ok(client.lobby_member_limit(self.lobby_id))
         }
 
-        #[napi]
+        #[napi(catch_unwind)]
         /// 获取当前大厅所有盛有
         ///
         /// @returns 当前大厅所有成员
-        pub fn get_members(&self) -> Vec<CSteamID> {
-            let client = crate::client::get_client();
-            client
-                .matchmaking()
+        pub fn get_members(&self) -> Result<Vec<CSteamID>, Error> {
+            let client = crate::client::get_client()?;
+            Ok(client
                 .lobby_members(self.lobby_id)
                 .into_iter()
                 .map(CSteamID::from_steamid)
-                .collect()
+                .collect())
         }
 
-        #[napi]
+        #[napi(catch_unwind)]
         /// 返回当前大厅所有者。
         /// 注意： 您必须是大厅成员才能访问
         /// 始终只有一位大厅所有者，如果当前所有者离开，该大厅中的另一名玩家将自动成为所有者。
         /// 在一个大厅的所有者刚离开时，其他玩家便有机会（但较少见）加入该大厅，进入大厅后，其自身便成为该大厅的所有者。
         ///
         /// @returns 如果您不在大厅中，则返回 k_steamIDNil。
-        pub fn get_owner(&self) -> CSteamID {
-            let client = crate::client::get_client();
-            CSteamID::from_steamid(client.matchmaking().lobby_owner(self.lobby_id))
+        pub fn get_owner(&self) -> Result<CSteamID, Error> {
+            let client = crate::client::get_client()?;
+            Ok(CSteamID::from_steamid(client.lobby_owner(self.lobby_id)))
         }
 
-        #[napi]
+        #[napi(catch_unwind)]
         /// 设置一个大厅是否对其他玩家开放。 始终默认为启用新的大厅。
         /// 如果禁止加入，那么没有玩家可以加入，即便他们是好友或已受邀请。
         /// 禁止加入的大厅将不会从大厅搜索中返回。
         ///
         /// @returns true， 表示成功；否则，如果您不是大厅所有者，则返回 false。
-        pub fn set_joinable(&self, joinable: bool) -> bool {
-            let client = crate::client::get_client();
-            client
-                .matchmaking()
-                .set_lobby_joinable(self.lobby_id, joinable)
+        pub fn set_joinable(&self, joinable: bool) -> Result<bool, Error> {
+            let client = crate::client::get_client()?;
+            Ok(client.set_lobby_joinable(self.lobby_id, joinable))
         }
 
-        #[napi]
+        #[napi(catch_unwind)]
         /// 获取与指定大厅中的指定键相关的元数据。
         /// 注意： 仅能从客户端知晓的大厅获取元数据，客户端或是从 LobbyMatchList_t 收到一个大厅列表并使用
         /// RequestLobbyData 获取数据，或是在加入一个大厅后而知晓大厅的。
         /// 
         /// @params key 要获取值的键
         /// @returns 如果没有为此键设置值，或 steamIDLobby 无效，则返回一个空白字符串（""）。
-        pub fn get_data(&self, key: String) -> Option<String> {
-            let client = crate::client::get_client();
-            client
-                .matchmaking()
-                .lobby_data(self.lobby_id, &key)
-                .map(|s| s.to_string())
+        pub fn get_data(&self, key: String) -> Result<Option<String>, Error> {
+            let client = crate::client::get_client()?;
+            Ok(client.lobby_data(self.lobby_id, &key))
         }
 
-        #[napi]
+        #[napi(catch_unwind)]
         /// 在大厅元数据中设置键/值对。 此函数可用于设置大厅名称、当前地图、游戏模式等。
         /// 这只能由大厅所有者设置。 大厅成员则应使用 SetLobbyMemberData。
         /// 通过 LobbyDataUpdate_t 回调，大厅中的每位用户都将能收到大厅数据变更的通知，并且任何加入的新用户都将收到所有现有数据。
@@ -130,55 +143,54 @@ pub mod steammatchmaking {
         /// @param key 要设置数据的键。 不得长于 k_nMaxLobbyKeyLength。
         /// @param value 要设置的值。 不得长于 k_cubChatMetadataMax。
         /// @return true， 表示数据设置成功。 如果 steamIDLobby 为无效索引或键/值太长，则为 false。
-        pub fn set_data(&self, key: String, value: String) -> bool {
-            let client = crate::client::get_client();
-            client
-                .matchmaking()
-                .set_lobby_data(self.lobby_id, &key, &value)
+        pub fn set_data(&self, key: String, value: String) -> Result<bool, Error> {
+            let client = crate::client::get_client()?;
+            Ok(client.set_lobby_data(self.lobby_id, &key, &value))
         }
 
-        #[napi]
+        #[napi(catch_unwind)]
         /// 从大厅移除元数据键。
         /// 此操作只能由大厅所有者完成。
         /// 只有当键存在时，才会发送数据。 在发送数据之前会稍有延迟，因此您可以重复调用来设置您需要的所有数据，并在最后一次顺序调用之后，数据将被自动批处理和发送。
         /// 
         /// @returns true， 表示成功删除了键/值；否则，如果 steamIDLobby 或 pchKey 无效，则为 false。
-        pub fn delete_data(&self, key: String) -> bool {
-            let client = crate::client::get_client();
-            client.matchmaking().delete_lobby_data(self.lobby_id, &key)
+        pub fn delete_data(&self, key: String) -> Result<bool, Error> {
+            let client = crate::client::get_client()?;
+            Ok(client.delete_lobby_data(self.lobby_id, &key))
         }
 
-        #[napi]
+        #[napi(catch_unwind)]
         /// 获取当前大厅所有数据键
         /// 
         /// @returns 返回当前大厅数据键
-        pub fn get_full_data(&self) -> HashMap<String, String> {
-            let client = crate::client::get_client();
+        pub fn get_full_data(&self) -> Result<HashMap<String, String>, Error> {
+            let client = crate::client::get_client()?;
 
             let mut data = HashMap::new();
 
-            let count = client.matchmaking().lobby_data_count(self.lobby_id);
+            let count = client.lobby_data_count(self.lobby_id);
             for i in 0..count {
-                let maybe_lobby_data = client.matchmaking().lobby_data_by_index(self.lobby_id, i);
+                let maybe_lobby_data = client.lobby_data_by_index(self.lobby_id, i);
 
                 if let Some((key, value)) = maybe_lobby_data {
                     data.insert(key, value);
                 }
             }
 
-            data
+            Ok(data)
         }
 
-        #[napi]
+        #[napi(catch_unwind)]
         /// 合并完整数据键数据
         /// 
         /// @params data 大厅所有数据键
         /// @returns 是否合并成功
-        pub fn merge_full_data(&self, data: HashMap<String, String>) -> bool {
-            let matchmaking = crate::client::get_client().matchmaking();
-            data.iter()
-                .map(|(key, value)| matchmaking.set_lobby_data(self.lobby_id, key, value))
-                .all(|x| x)
+        pub fn merge_full_data(&self, data: HashMap<String, String>) -> Result<bool, Error> {
+            let client = crate::client::get_client()?;
+            Ok(data
+                .iter()
+                .map(|(key, value)| client.set_lobby_data(self.lobby_id, key, value))
+                .all(|x| x))
         }
     }
 
@@ -193,23 +205,28 @@ pub mod steammatchmaking {
     /// - 如果通过 LobbyCreated_t 调用结果返回的结果表明是成功的，那么大厅此时已有人加入，可随时使用。
     /// - 本地用户已加入他们自己的大厅，因此也收到了 LobbyEnter_t 回调。
     pub async fn create_lobby(lobby_type: ELobbyType, max_members: u32) -> Result<Lobby, Error> {
-        let client = crate::client::get_client();
+        crate::error::catch_panic_async("create_lobby", async move {
+            let (session, client) = crate::client::get_session()?;
 
-        let (tx, rx) = oneshot::channel();
+            let (tx, rx) = oneshot::channel();
 
-        client
-            .matchmaking()
-            .create_lobby(LobbyType::from(lobby_type), max_members, |result| {
-                tx.send(result).unwrap();
-            });
-
-        rx.await
-            .unwrap()
-            .map(|lobby_id| Lobby {
-                id: BigInt::from(lobby_id.raw()),
-                lobby_id,
-            })
-            .map_err(|e| Error::from_reason(e.to_string()))
+            client.create_lobby(
+                LobbyType::from(lobby_type),
+                max_members,
+                Box::new(|result| {
+                    let _ = tx.send(result);
+                }),
+            );
+
+            crate::client::session_result(session, rx)
+                .await?
+                .map(|lobby_id| Lobby {
+                    id: BigInt::from(lobby_id.raw()),
+                    lobby_id,
+                })
+                .map_err(steam_error("create_lobby"))
+        })
+        .await?
     }
 
     #[napi]
@@ -219,25 +236,29 @@ pub mod steammatchmaking {
     /// @param lobby_id 要加入的大厅的 Steam ID
     /// @returns SteamAPICall_t，与LobbyEnter_t 调用结果一起使用。
     /// - 触发一个 LobbyDataUpdate_t 回调。
-    pub async fn join_lobby(lobby_id: BigInt) -> Result<Lobby, Error> {
-        let client = crate::client::get_client();
-
-        let (tx, rx) = oneshot::channel();
-
-        client.matchmaking().join_lobby(
-            steamworks::LobbyId::from_raw(lobby_id.get_u64().1),
-            |result| {
-                tx.send(result).unwrap();
-            },
-        );
-
-        rx.await
-            .unwrap()
-            .map(|lobby_id| Lobby {
-                id: BigInt::from(lobby_id.raw()),
+    pub async fn join_lobby(lobby_id: Either<BigInt, String>) -> Result<Lobby, Error> {
+        crate::error::catch_panic_async("join_lobby", async move {
+            let lobby_id = crate::id::lobby_id("lobby_id", &lobby_id)?;
+            let (session, client) = crate::client::get_session()?;
+
+            let (tx, rx) = oneshot::channel();
+
+            client.join_lobby(
                 lobby_id,
-            })
-            .map_err(|_| Error::from_reason("Failed to join lobby".to_string()))
+                Box::new(|result| {
+                    let _ = tx.send(result);
+                }),
+            );
+
+            crate::client::session_result(session, rx)
+                .await?
+                .map(|lobby_id| Lobby {
+                    id: BigInt::from(lobby_id.raw()),
+                    lobby_id,
+                })
+                .map_err(steam_error("join_lobby"))
+        })
+        .await?
     }
 
     #[napi]
@@ -251,25 +272,115 @@ pub mod steammatchmaking {
     /// - SteamAPICall_t，与 LobbyMatchList_t 调用结果一起使用。
     /// 注意：这也会出于对旧应用程序的兼容而作为回调返回，但如果可能，您应使用调用结果。
     pub async fn get_lobbies() -> Result<Vec<Lobby>, Error> {
-        let client = crate::client::get_client();
-
-        let (tx, rx) = oneshot::channel();
-
-        client.matchmaking().request_lobby_list(|lobbies| {
-            tx.send(lobbies).unwrap();
-        });
-
-        rx.await
-            .unwrap()
-            .map(|lobbies| {
-                lobbies
-                    .iter()
-                    .map(|lobby_id| Lobby {
-                        id: BigInt::from(lobby_id.raw()),
-                        lobby_id: *lobby_id,
-                    })
-                    .collect()
+        crate::error::catch_panic_async("get_lobbies", async move {
+            let (session, client) = crate::client::get_session()?;
+
+            let (tx, rx) = oneshot::channel();
+
+            client.request_lobby_list(Box::new(|lobbies| {
+                let _ = tx.send(lobbies);
+            }));
+
+            crate::client::session_result(session, rx)
+                .await?
+                .map(|lobbies| {
+                    lobbies
+                        .iter()
+                        .map(|lobby_id| Lobby {
+                            id: BigInt::from(lobby_id.raw()),
+                            lobby_id: *lobby_id,
+                        })
+                        .collect()
+                })
+                .map_err(steam_error("request_lobby_list"))
+        })
+        .await?
+    }
+
+    #[napi(object)]
+    /// Steam 收藏夹或历史记录中的服务器，同一服务器在收藏夹与历史记录中是两条记录
+    pub struct FavoriteGame {
+        /// 服务器所属游戏的 AppId
+        pub app_id: u32,
+        /// 服务器的 IPv4 地址
+        pub ip: String,
+        /// 游戏端口
+        pub connection_port: u16,
+        /// 查询端口，通常与游戏端口相同
+        pub query_port: u16,
+        /// EFavoriteFlags 的组合，收藏夹为 1，历史记录为 2
+        pub flags: u32,
+        /// 最后一次在该服务器上游戏的 Unix 时间（秒），添加时为空则使用 Steam 服务器时间
+        pub last_played: Option<u32>,
+    }
+
+    impl From<backend::FavoriteGame> for FavoriteGame {
+        fn from(game: backend::FavoriteGame) -> Self {
+            Self {
+                app_id: game.app_id.0,
+                ip: game.ip.to_string(),
+                connection_port: game.connection_port,
+                query_port: game.query_port,
+                flags: game.flags,
+                last_played: Some(game.last_played),
+            }
+        }
+    }
+
+    impl FavoriteGame {
+        fn to_backend(&self, last_played: u32) -> Result<backend::FavoriteGame, Error> {
+            let ip = self.ip.trim().parse::<Ipv4Addr>().map_err(|_| {
+                SteamworksError::InvalidArgument {
+                    name: "ip",
+                    reason: format!("{:?} is not an IPv4 address", self.ip),
+                }
+            })?;
+            Ok(backend::FavoriteGame {
+                app_id: AppId(self.app_id),
+                ip,
+                connection_port: self.connection_port,
+                query_port: self.query_port,
+                flags: self.flags,
+                last_played: self.last_played.unwrap_or(last_played),
             })
-            .map_err(|e| Error::from_reason(e.to_string()))
+        }
+    }
+
+    #[napi(catch_unwind)]
+    /// 获取用户收藏夹与历史记录中的所有服务器，与 Steam 服务器浏览器中的列表一致
+    ///
+    /// @returns 服务器列表，按 GetFavoriteGame 的序号排列
+    pub fn get_favorite_games() -> Result<Vec<FavoriteGame>, Error> {
+        let client = crate::client::get_client()?;
+        Ok(client
+            .favorite_games()
+            .into_iter()
+            .map(FavoriteGame::from)
+            .collect())
+    }
+
+    #[napi(catch_unwind)]
+    /// 将服务器添加到用户的收藏夹或历史记录，已存在时只更新最后游戏时间
+    ///
+    /// @param game 要添加的服务器，flags 决定添加到哪个列表
+    /// @returns 服务器在列表中的序号，Steam 拒绝添加时抛出 STEAM_ERROR
+    pub fn add_favorite_game(game: FavoriteGame) -> Result<u32, Error> {
+        let client = crate::client::get_client()?;
+        let game = game.to_backend(client.server_real_time())?;
+        client
+            .add_favorite_game(&game)
+            .ok_or(SteamError::Generic)
+            .map_err(steam_error("add_favorite_game"))
+    }
+
+    #[napi(catch_unwind)]
+    /// 将服务器从用户的收藏夹或历史记录中删除
+    ///
+    /// @param game 要删除的服务器，AppId、地址、端口与 flags 都需要匹配，忽略 lastPlayed
+    /// @returns 是否找到并删除了服务器
+    pub fn remove_favorite_game(game: FavoriteGame) -> Result<bool, Error> {
+        let client = crate::client::get_client()?;
+        let game = game.to_backend(0)?;
+        Ok(client.remove_favorite_game(&game))
     }
 }
@@ -0,0 +1,411 @@
+use crate::backend::{GameServerItem, ServerListKind, ServerListResponse, ServerPlayer};
+use napi_derive::napi;
+
+#[napi]
+/// 服务器列表类型
+pub enum EServerListType {
+    /// 主服务器上的互联网服务器
+    Internet,
+    /// 局域网服务器，不支持筛选
+    Lan,
+    /// 收藏的服务器
+    Favorites,
+    /// 最近玩过的服务器
+    History,
+    /// 好友正在玩的服务器
+    Friends,
+    /// 可观战的服务器
+    Spectator,
+}
+
+impl From<EServerListType> for ServerListKind {
+    fn from(value: EServerListType) -> Self {
+        match value {
+            EServerListType::Internet => ServerListKind::Internet,
+            EServerListType::Lan => ServerListKind::Lan,
+            EServerListType::Favorites => ServerListKind::Favorites,
+            EServerListType::History => ServerListKind::History,
+            EServerListType::Friends => ServerListKind::Friends,
+            EServerListType::Spectator => ServerListKind::Spectator,
+        }
+    }
+}
+
+#[napi]
+/// 服务器列表刷新结束的原因
+pub enum EMatchMakingServerResponse {
+    /// 至少有一个服务器回复
+    ServerResponded,
+    /// 没有服务器回复
+    ServerFailedToRespond,
+    /// 主服务器上没有符合条件的服务器
+    NoServersListedOnMasterServer,
+}
+
+impl From<ServerListResponse> for EMatchMakingServerResponse {
+    fn from(value: ServerListResponse) -> Self {
+        match value {
+            ServerListResponse::ServerResponded => EMatchMakingServerResponse::ServerResponded,
+            ServerListResponse::ServerFailedToRespond => {
+                EMatchMakingServerResponse::ServerFailedToRespond
+            }
+            ServerListResponse::NoServersListedOnMasterServer => {
+                EMatchMakingServerResponse::NoServersListedOnMasterServer
+            }
+        }
+    }
+}
+
+#[napi]
+/// 通过 Steam 服务器浏览器获取服务器列表与服务器详情。
+/// https://partner.steamgames.com/doc/api/ISteamMatchmakingServers
+pub mod steammatchmakingservers {
+    use super::{EMatchMakingServerResponse, EServerListType, GameServerItem, ServerPlayer};
+    use crate::backend::{CallbackGuard, ServerListEvent, ServerListKind};
+    use crate::error::{steam_error, SteamworksError};
+    use crate::query;
+    use napi::bindgen_prelude::{BigInt, Error};
+    use napi::threadsafe_function::{
+        ErrorStrategy, ThreadsafeFunction, ThreadsafeFunctionCallMode,
+    };
+    use napi::JsFunction;
+    use std::net::{SocketAddr, SocketAddrV4};
+    use steamworks::{AppId, SteamError};
+    use tokio::sync::oneshot;
+
+    #[napi(object)]
+    /// 服务器列表筛选条件，如 `gamedir`、`map`、`secure`、`notfull`、`gametagsand`
+    pub struct ServerFilter {
+        pub key: String,
+        pub value: String,
+    }
+
+    #[napi(object)]
+    /// 服务器列表请求选项
+    pub struct ServerListOptions {
+        /// 要查询的游戏 App ID，默认为当前 App ID
+        pub app_id: Option<u32>,
+        /// 筛选条件，局域网列表会忽略
+        pub filters: Option<Vec<ServerFilter>>,
+    }
+
+    #[napi(object)]
+    /// Steam 服务器浏览器中的服务器
+    pub struct GameServer {
+        /// 查询地址，`ip:查询端口`
+        pub address: String,
+        pub ip: String,
+        pub query_port: u16,
+        /// 游戏连接端口
+        pub connection_port: u16,
+        /// 延迟（毫秒）
+        pub ping: i32,
+        /// 服务器是否回复过查询
+        pub had_successful_response: bool,
+        pub name: String,
+        pub map: String,
+        pub game_dir: String,
+        pub game_description: String,
+        pub app_id: u32,
+        pub players: i32,
+        pub max_players: i32,
+        pub bot_players: i32,
+        /// 是否需要密码
+        pub password: bool,
+        /// 是否启用 VAC
+        pub secure: bool,
+        /// 上次在该服务器游戏的时间（Unix 时间戳），仅收藏与历史列表
+        pub time_last_played: u32,
+        pub server_version: i32,
+        /// 服务器标签
+        pub tags: Vec<String>,
+        /// 服务器 Steam ID，未知时为空
+        pub steam_id: Option<BigInt>,
+    }
+
+    impl From<GameServerItem> for GameServer {
+        fn from(item: GameServerItem) -> Self {
+            let steam_id = item.steam_id.raw();
+            Self {
+                address: item.address.to_string(),
+                ip: item.address.ip().to_string(),
+                query_port: item.address.port(),
+                connection_port: item.connection_port,
+                ping: item.ping,
+                had_successful_response: item.had_successful_response,
+                name: item.server_name,
+                map: item.map,
+                game_dir: item.game_dir,
+                game_description: item.game_description,
+                app_id: item.app_id.0,
+                players: item.players,
+                max_players: item.max_players,
+                bot_players: item.bot_players,
+                password: item.password,
+                secure: item.secure,
+                time_last_played: item.time_last_played,
+                server_version: item.server_version,
+                tags: item
+                    .game_tags
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|tag| !tag.is_empty())
+                    .map(str::to_string)
+                    .collect(),
+                steam_id: (steam_id != 0).then(|| BigInt::from(steam_id)),
+            }
+        }
+    }
+
+    #[napi(object)]
+    /// 服务器列表刷新结果
+    pub struct ServerListSummary {
+        pub response: EMatchMakingServerResponse,
+        /// 回复了查询的服务器数
+        pub responded: u32,
+        /// 没有回复的服务器数
+        pub failed: u32,
+    }
+
+    #[napi(object)]
+    /// 服务器中的玩家
+    pub struct ServerPlayerInfo {
+        pub name: String,
+        pub score: i32,
+        /// 在服务器中的时长（秒）
+        pub time_played: f64,
+    }
+
+    impl From<ServerPlayer> for ServerPlayerInfo {
+        fn from(player: ServerPlayer) -> Self {
+            Self {
+                name: player.name,
+                score: player.score,
+                time_played: f64::from(player.time_played),
+            }
+        }
+    }
+
+    #[napi(object)]
+    /// 服务器规则（控制台变量）
+    pub struct ServerRule {
+        pub name: String,
+        pub value: String,
+    }
+
+    #[napi]
+    /// 进行中的服务器列表请求
+    pub struct ServerListRequest {
+        handle: Option<CallbackGuard>,
+    }
+
+    #[napi]
+    impl ServerListRequest {
+        #[napi(catch_unwind)]
+        /// 取消请求，之后不会再收到任何服务器
+        pub fn cancel(&mut self) {
+            self.handle.take();
+        }
+    }
+
+    /// 解析服务器地址，Steam 服务器浏览器只支持 IPv4
+    async fn resolve(call: &'static str, address: &str) -> Result<SocketAddrV4, Error> {
+        match query::resolve(address).await {
+            Ok(SocketAddr::V4(address)) => Ok(address),
+            Ok(SocketAddr::V6(resolved)) => Err(SteamworksError::InvalidArgument {
+                name: "address",
+                reason: format!(
+                    "{:?} resolves to IPv6 address {}, only IPv4 is supported",
+                    address,
+                    resolved.ip()
+                ),
+            }
+            .into()),
+            Err(error) => Err(error.context(call, address).into()),
+        }
+    }
+
+    /// 服务器没有回复时返回与 A2S 查询一致的超时错误
+    fn server_error(call: &'static str, address: SocketAddrV4) -> impl Fn(SteamError) -> Error {
+        move |error| match error {
+            SteamError::Timeout => SteamworksError::Timeout {
+                call,
+                target: address.to_string(),
+            }
+            .into(),
+            error => steam_error(call)(error),
+        }
+    }
+
+    #[napi(catch_unwind)]
+    /// 请求服务器列表，服务器陆续回复时逐个返回。
+    /// 同一时间可以进行多个请求，请求在取消或句柄被回收时释放。
+    ///
+    /// @param list_type 服务器列表类型
+    /// @param options 请求选项
+    /// @param on_server 每个回复了查询的服务器
+    /// @param on_complete 列表刷新结束时调用
+    /// @returns 请求句柄，可以随时取消
+    pub fn request_server_list(
+        list_type: EServerListType,
+        options: Option<ServerListOptions>,
+        #[napi(ts_arg_type = "(server: GameServer) => void")] on_server: JsFunction,
+        #[napi(ts_arg_type = "((summary: ServerListSummary) => void)")] on_complete: Option<
+            JsFunction,
+        >,
+    ) -> Result<ServerListRequest, Error> {
+        let on_server: ThreadsafeFunction<GameServer, ErrorStrategy::Fatal> =
+            on_server.create_threadsafe_function(0, |ctx| Ok(vec![ctx.value]))?;
+        let on_complete: Option<ThreadsafeFunction<ServerListSummary, ErrorStrategy::Fatal>> =
+            on_complete
+                .map(|on_complete| {
+                    on_complete.create_threadsafe_function(0, |ctx| Ok(vec![ctx.value]))
+                })
+                .transpose()?;
+
+        start_server_list(
+            list_type,
+            options,
+            move |server| {
+                on_server.call(server, ThreadsafeFunctionCallMode::NonBlocking);
+            },
+            move |summary| {
+                if let Some(on_complete) = &on_complete {
+                    on_complete.call(summary, ThreadsafeFunctionCallMode::NonBlocking);
+                }
+            },
+        )
+    }
+
+    /// 发起服务器列表请求，统计回复与没有回复的服务器数量
+    ///
+    /// @param on_server 每个回复了查询的服务器
+    /// @param on_complete 列表刷新结束时调用
+    pub(crate) fn start_server_list(
+        list_type: EServerListType,
+        options: Option<ServerListOptions>,
+        on_server: impl Fn(GameServer) + Send + 'static,
+        on_complete: impl Fn(ServerListSummary) + Send + 'static,
+    ) -> Result<ServerListRequest, Error> {
+        let client = crate::client::get_client()?;
+        let (app_id, filters) = match options {
+            Some(options) => (
+                options.app_id.map(AppId),
+                options
+                    .filters
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|filter| (filter.key, filter.value))
+                    .collect(),
+            ),
+            None => (None, Vec::new()),
+        };
+        let app_id = app_id.unwrap_or_else(|| client.app_id());
+
+        let (mut responded, mut failed) = (0, 0);
+        let guard = client.request_server_list(
+            ServerListKind::from(list_type),
+            app_id,
+            filters,
+            Box::new(move |event| match event {
+                ServerListEvent::Responded(item) => {
+                    responded += 1;
+                    on_server(item.into());
+                }
+                ServerListEvent::Failed(_) => failed += 1,
+                ServerListEvent::Complete(response) => on_complete(ServerListSummary {
+                    response: response.into(),
+                    responded,
+                    failed,
+                }),
+            }),
+        );
+
+        Ok(ServerListRequest {
+            handle: Some(guard),
+        })
+    }
+
+    #[napi]
+    /// 通过 Steam 查询单个服务器的信息
+    ///
+    /// @param address 服务器地址，`ip:查询端口`，未指定端口时使用 27015，IPv6 地址抛出 INVALID_ARGUMENT
+    /// @returns 服务器信息，服务器没有回复时返回超时错误
+    pub async fn ping_server(address: String) -> Result<GameServer, Error> {
+        crate::error::catch_panic_async("ping_server", async move {
+            let address = resolve("ping_server", &address).await?;
+            let (session, client) = crate::client::get_session()?;
+
+            let (tx, rx) = oneshot::channel();
+            let _guard = client.ping_server(
+                address,
+                Box::new(|result| {
+                    let _ = tx.send(result);
+                }),
+            );
+
+            crate::client::session_result(session, rx)
+                .await?
+                .map(GameServer::from)
+                .map_err(server_error("ping_server", address))
+        })
+        .await?
+    }
+
+    #[napi]
+    /// 通过 Steam 查询服务器中的玩家
+    ///
+    /// @param address 服务器地址，`ip:查询端口`，未指定端口时使用 27015，IPv6 地址抛出 INVALID_ARGUMENT
+    /// @returns 玩家列表
+    pub async fn get_server_players(address: String) -> Result<Vec<ServerPlayerInfo>, Error> {
+        crate::error::catch_panic_async("get_server_players", async move {
+            let address = resolve("server_players", &address).await?;
+            let (session, client) = crate::client::get_session()?;
+
+            let (tx, rx) = oneshot::channel();
+            let _guard = client.server_players(
+                address,
+                Box::new(|result| {
+                    let _ = tx.send(result);
+                }),
+            );
+
+            crate::client::session_result(session, rx)
+                .await?
+                .map(|players| players.into_iter().map(ServerPlayerInfo::from).collect())
+                .map_err(server_error("server_players", address))
+        })
+        .await?
+    }
+
+    #[napi]
+    /// 通过 Steam 查询服务器规则（控制台变量）
+    ///
+    /// @param address 服务器地址，`ip:查询端口`，未指定端口时使用 27015，IPv6 地址抛出 INVALID_ARGUMENT
+    /// @returns 规则列表，按服务器返回的顺序排列
+    pub async fn get_server_rules(address: String) -> Result<Vec<ServerRule>, Error> {
+        crate::error::catch_panic_async("get_server_rules", async move {
+            let address = resolve("server_rules", &address).await?;
+            let (session, client) = crate::client::get_session()?;
+
+            let (tx, rx) = oneshot::channel();
+            let _guard = client.server_rules(
+                address,
+                Box::new(|result| {
+                    let _ = tx.send(result);
+                }),
+            );
+
+            crate::client::session_result(session, rx)
+                .await?
+                .map(|rules| {
+                    rules
+                        .into_iter()
+                        .map(|(name, value)| ServerRule { name, value })
+                        .collect()
+                })
+                .map_err(server_error("server_rules", address))
+        })
+        .await?
+    }
+}
@@ -6,12 +6,12 @@ use napi_derive::napi;
 #[napi]
 pub mod steamugc {
     use super::{CSteamID, EItemUpdateStatus, ERemoteStoragePublishedFileVisibility};
-    use napi::bindgen_prelude::{BigInt, Error};
+    use crate::backend::{ItemUpdate, WorkshopItemDetails, WorkshopQuery};
+    use crate::error::steam_error;
+    use napi::bindgen_prelude::{BigInt, Either, Error};
     use napi::threadsafe_function::ErrorStrategy;
     use napi::threadsafe_function::ThreadsafeFunction;
     use napi::threadsafe_function::ThreadsafeFunctionCallMode;
-    use std::path::Path;
-    use steamworks::{ClientManager, FileType, PublishedFileId, UpdateHandle};
     use tokio::sync::oneshot;
 
     #[napi(object)]
@@ -42,40 +42,17 @@ pub mod steamugc {
         pub visibility: Option<ERemoteStoragePublishedFileVisibility>,
     }
 
-    impl UgcUpdate {
-        pub fn submit(
-            self,
-            mut update: UpdateHandle<ClientManager>,
-            callback: impl FnOnce(Result<(PublishedFileId, bool), steamworks::SteamError>)
-                + Send
-                + 'static,
-        ) -> steamworks::UpdateWatchHandle<ClientManager> {
-            if let Some(title) = self.title {
-                update = update.title(title.as_str());
-            }
-
-            if let Some(description) = self.description {
-                update = update.description(description.as_str());
-            }
-
-            if let Some(preview_path) = self.preview_path {
-                update = update.preview_path(Path::new(&preview_path));
-            }
-
-            if let Some(tags) = self.tags {
-                update = update.tags(tags);
-            }
-
-            if let Some(content_path) = self.content_path {
-                update = update.content_path(Path::new(&content_path));
-            }
-
-            if let Some(visibility) = self.visibility {
-                update = update.visibility(visibility.into());
+    impl From<UgcUpdate> for ItemUpdate {
+        fn from(update: UgcUpdate) -> Self {
+            Self {
+                title: update.title,
+                description: update.description,
+                change_note: update.change_note,
+                preview_path: update.preview_path,
+                content_path: update.content_path,
+                tags: update.tags,
+                visibility: update.visibility.map(Into::into),
             }
-
-            let change_note = self.change_note.as_deref();
-            update.submit(change_note, callback)
         }
     }
 
@@ -160,26 +137,37 @@ pub mod steamugc {
         pub preview_url: Option<String>,
     }
 
+    impl From<WorkshopItemQueryOptions> for WorkshopQuery {
+        fn from(query: WorkshopItemQueryOptions) -> Self {
+            Self {
+                cached_response_max_age: query.cached_response_max_age,
+                include_metadata: query.include_metadata,
+                include_long_description: query.include_long_description,
+                language: query.language,
+            }
+        }
+    }
+
     impl WorkshopItem {
-        fn from_query(result: steamworks::QueryResult, preview_url: Option<String>) -> Self {
+        fn from_details(details: WorkshopItemDetails) -> Self {
             Self {
-                published_file_id: BigInt::from(result.published_file_id.0),
-                creator_app_id: result.creator_app_id.map(|id| id.0),
-                consumer_app_id: result.consumer_app_id.map(|id| id.0),
-                title: result.title,
-                description: result.description,
-                owner: CSteamID::from_steamid(result.owner),
-                time_created: result.time_created,
-                time_updated: result.time_updated,
-                banned: result.banned,
-                accepted_for_use: result.accepted_for_use,
-                tags: result.tags,
-                tags_truncated: result.tags_truncated,
-                url: result.url,
-                num_upvotes: result.num_upvotes,
-                num_downvotes: result.num_downvotes,
-                num_children: result.num_children,
-                preview_url,
+                published_file_id: BigInt::from(details.published_file_id.0),
+                creator_app_id: details.creator_app_id.map(|id| id.0),
+                consumer_app_id: details.consumer_app_id.map(|id| id.0),
+                title: details.title,
+                description: details.description,
+                owner: CSteamID::from_steamid(details.owner),
+                time_created: details.time_created,
+                time_updated: details.time_updated,
+                banned: details.banned,
+                accepted_for_use: details.accepted_for_use,
+                tags: details.tags,
+                tags_truncated: details.tags_truncated,
+                url: details.url,
+                num_upvotes: details.num_upvotes,
+                num_downvotes: details.num_downvotes,
+                num_children: details.num_children,
+                preview_url: details.preview_url,
             }
         }
     }
@@ -187,13 +175,12 @@ pub mod steamugc {
     /// 获取此客户端上创意工坊物品的当前状态
     ///
     /// @returns 返回此物品状态。 应与 EItemState 标记一起使用，以确定创意工坊物品的状态。
-    #[napi]
-    pub fn get_item_state(item_id: BigInt) -> u32 {
-        let client = crate::client::get_client();
-        let result = client
-            .ugc()
-            .item_state(PublishedFileId(item_id.get_u64().1));
-        result.bits()
+    #[napi(catch_unwind)]
+    pub fn get_item_state(item_id: Either<BigInt, String>) -> Result<u32, Error> {
+        let item_id = crate::id::published_file_id("item_id", &item_id)?;
+        let client = crate::client::get_client()?;
+        let result = client.item_state(item_id);
+        Ok(result.bits())
     }
 
     #[napi]
@@ -201,27 +188,31 @@ pub mod steamugc {
     ///
     /// @return 创意工坊信息
     pub async fn create_item(app_id: Option<u32>) -> Result<UgcResult, Error> {
-        let client = crate::client::get_client();
-        let app_id = app_id
-            .map(steamworks::AppId)
-            .unwrap_or_else(|| client.utils().app_id());
-
-        let (tx, rx) = oneshot::channel();
-
-        client
-            .ugc()
-            .create_item(app_id, FileType::Community, |result| {
-                tx.send(result).unwrap();
-            });
-
-        let result = rx.await.unwrap();
-        match result {
-            Ok((item_id, needs_to_accept_agreement)) => Ok(UgcResult {
-                item_id: BigInt::from(item_id.0),
-                needs_to_accept_agreement,
-            }),
-            Err(e) => Err(Error::from_reason(e.to_string())),
-        }
+        crate::error::catch_panic_async("create_item", async move {
+            let (session, client) = crate::client::get_session()?;
+            let app_id = app_id
+                .map(steamworks::AppId)
+                .unwrap_or_else(|| client.app_id());
+
+            let (tx, rx) = oneshot::channel();
+
+            client.create_item(
+                app_id,
+                Box::new(|result| {
+                    let _ = tx.send(result);
+                }),
+            );
+
+            let result = crate::client::session_result(session, rx).await?;
+            match result {
+                Ok((item_id, needs_to_accept_agreement)) => Ok(UgcResult {
+                    item_id: BigInt::from(item_id.0),
+                    needs_to_accept_agreement,
+                }),
+                Err(e) => Err(steam_error("create_item")(e)),
+            }
+        })
+        .await?
     }
 
     #[napi]
@@ -233,36 +224,39 @@ pub mod steamugc {
     /// @param app_id 创意工坊所属应用ID
     /// @returns 返回更新结果及错误信息
     pub async fn update_item(
-        item_id: BigInt,
+        item_id: Either<BigInt, String>,
         update_details: UgcUpdate,
         app_id: Option<u32>,
     ) -> Result<UgcResult, Error> {
-        let client = crate::client::get_client();
-
-        let app_id = app_id
-            .map(steamworks::AppId)
-            .unwrap_or_else(|| client.utils().app_id());
-
-        let (tx, rx) = oneshot::channel();
-
-        {
-            let update_handle = client
-                .ugc()
-                .start_item_update(app_id, PublishedFileId(item_id.get_u64().1));
-
-            update_details.submit(update_handle, |result| {
-                tx.send(result).unwrap();
-            });
-        };
-
-        let result = rx.await.unwrap();
-        match result {
-            Ok((item_id, needs_to_accept_agreement)) => Ok(UgcResult {
-                item_id: BigInt::from(item_id.0),
-                needs_to_accept_agreement,
-            }),
-            Err(e) => Err(Error::from_reason(e.to_string())),
-        }
+        crate::error::catch_panic_async("update_item", async move {
+            let item_id = crate::id::published_file_id("item_id", &item_id)?;
+            let (session, client) = crate::client::get_session()?;
+
+            let app_id = app_id
+                .map(steamworks::AppId)
+                .unwrap_or_else(|| client.app_id());
+
+            let (tx, rx) = oneshot::channel();
+
+            client.submit_item_update(
+                app_id,
+                item_id,
+                update_details.into(),
+                Box::new(|result| {
+                    let _ = tx.send(result);
+                }),
+            );
+
+            let result = crate::client::session_result(session, rx).await?;
+            match result {
+                Ok((item_id, needs_to_accept_agreement)) => Ok(UgcResult {
+                    item_id: BigInt::from(item_id.0),
+                    needs_to_accept_agreement,
+                }),
+                Err(e) => Err(steam_error("submit_item_update")(e)),
+            }
+        })
+        .await?
     }
 
     #[napi]
@@ -270,21 +264,26 @@ pub mod steamugc {
     ///
     /// @param item_id 要订阅的创意工坊物品
     /// @returns 返回订阅结果
-    pub async fn subscribe_item(item_id: BigInt) -> Result<(), Error> {
-        let client = crate::client::get_client();
-        let (tx, rx) = oneshot::channel();
-
-        client
-            .ugc()
-            .subscribe_item(PublishedFileId(item_id.get_u64().1), |result| {
-                tx.send(result).unwrap();
-            });
-
-        let result = rx.await.unwrap();
-        match result {
-            Ok(()) => Ok(()),
-            Err(e) => Err(Error::from_reason(e.to_string())),
-        }
+    pub async fn subscribe_item(item_id: Either<BigInt, String>) -> Result<(), Error> {
+        crate::error::catch_panic_async("subscribe_item", async move {
+            let item_id = crate::id::published_file_id("item_id", &item_id)?;
+            let (session, client) = crate::client::get_session()?;
+            let (tx, rx) = oneshot::channel();
+
+            client.subscribe_item(
+                item_id,
+                Box::new(|result| {
+                    let _ = tx.send(result);
+                }),
+            );
+
+            let result = crate::client::session_result(session, rx).await?;
+            match result {
+                Ok(()) => Ok(()),
+                Err(e) => Err(steam_error("subscribe_item")(e)),
+            }
+        })
+        .await?
     }
 
     /// 取消来自创意工坊物品的订阅。 退出游戏后会移除该物品。
@@ -292,21 +291,26 @@ pub mod steamugc {
     /// @param item_id 要取消订阅的创意工坊物品项目
     /// @returns 返回订阅结果
     #[napi]
-    pub async fn unsubscribe_item(item_id: BigInt) -> Result<(), Error> {
-        let client = crate::client::get_client();
-        let (tx, rx) = oneshot::channel();
-
-        client
-            .ugc()
-            .unsubscribe_item(PublishedFileId(item_id.get_u64().1), |result| {
-                tx.send(result).unwrap();
-            });
-
-        let result = rx.await.unwrap();
-        match result {
-            Ok(()) => Ok(()),
-            Err(e) => Err(Error::from_reason(e.to_string())),
-        }
+    pub async fn unsubscribe_item(item_id: Either<BigInt, String>) -> Result<(), Error> {
+        crate::error::catch_panic_async("unsubscribe_item", async move {
+            let item_id = crate::id::published_file_id("item_id", &item_id)?;
+            let (session, client) = crate::client::get_session()?;
+            let (tx, rx) = oneshot::channel();
+
+            client.unsubscribe_item(
+                item_id,
+                Box::new(|result| {
+                    let _ = tx.send(result);
+                }),
+            );
+
+            let result = crate::client::session_result(session, rx).await?;
+            match result {
+                Ok(()) => Ok(()),
+                Err(e) => Err(steam_error("unsubscribe_item")(e)),
+            }
+        })
+        .await?
     }
 
     /// 下载或更新创意工坊物品。
@@ -320,26 +324,28 @@ pub mod steamugc {
     /// @param item_id 要下载的创意工坊物品
     /// @param high_priority 以高优先级模式开始下载，暂停所有其他正在进行的 Steam 下载并立即开始下载此创意工坊物品
     /// @returns true 表明下载成功开始；否则，如果 nPublishedFileID 无效或用户尚未登录，返回 false。
-    #[napi]
-    pub fn download_item(item_id: BigInt, high_priority: bool) -> bool {
-        let client = crate::client::get_client();
-        client
-            .ugc()
-            .download_item(PublishedFileId(item_id.get_u64().1), high_priority)
+    #[napi(catch_unwind)]
+    pub fn download_item(
+        item_id: Either<BigInt, String>,
+        high_priority: bool,
+    ) -> Result<bool, Error> {
+        let item_id = crate::id::published_file_id("item_id", &item_id)?;
+        let client = crate::client::get_client()?;
+        Ok(client.download_item(item_id, high_priority))
     }
 
     /// 获得当前游戏中，当前用户所订阅的所有物品的清单。
     ///
     /// @returns 已订阅的创意工坊物品ID
-    #[napi]
-    pub fn get_subscribed_items() -> Vec<BigInt> {
-        let client = crate::client::get_client();
-        let result = client.ugc().subscribed_items();
+    #[napi(catch_unwind)]
+    pub fn get_subscribed_items() -> Result<Vec<BigInt>, Error> {
+        let client = crate::client::get_client()?;
+        let result = client.subscribed_items();
 
-        result
+        Ok(result
             .iter()
             .map(|item| BigInt::from(item.0))
-            .collect::<Vec<_>>()
+            .collect::<Vec<_>>())
     }
 
     #[napi]
@@ -349,49 +355,35 @@ pub mod steamugc {
     /// @param query 要查询创意工坊指定选项
     /// @returns 返回创意工坊查询结果
     pub async fn get_item(
-        item: BigInt,
+        item: Either<BigInt, String>,
         query: Option<WorkshopItemQueryOptions>,
     ) -> Result<Option<WorkshopItem>, Error> {
-        let client = crate::client::get_client();
-        let (tx, rx) = oneshot::channel();
+        crate::error::catch_panic_async("get_item", async move {
+            let item = crate::id::published_file_id("item", &item)?;
+            let (session, client) = crate::client::get_session()?;
+            let (tx, rx) = oneshot::channel();
 
-        {
-            let mut item_query = client
-                .ugc()
-                .query_item(PublishedFileId(item.get_u64().1))
-                .map_err(|e| Error::from_reason(e.to_string()))?;
-
-            if let Some(query) = query {
-                if let Some(cached_response_max_age) = query.cached_response_max_age {
-                    item_query = item_query.allow_cached_response(cached_response_max_age);
-                }
-
-                if let Some(include_metadata) = query.include_metadata {
-                    item_query = item_query.include_metadata(include_metadata);
-                }
-
-                if let Some(include_long_description) = query.include_long_description {
-                    item_query = item_query.include_long_desc(include_long_description);
-                }
-
-                if let Some(language) = query.language {
-                    item_query = item_query.language(&language);
-                }
-            }
-
-            item_query.fetch(|result| {
-                tx.send(result.map(|result| {
-                    result
-                        .get(0)
-                        .map(|item| WorkshopItem::from_query(item, result.preview_url(0)))
-                }))
-                .unwrap();
-            });
-        }
+            client
+                .query_items(
+                    vec![item],
+                    query.map(Into::into).unwrap_or_default(),
+                    Box::new(|result| {
+                        let _ = tx.send(result.map(|items| {
+                            items
+                                .into_iter()
+                                .next()
+                                .flatten()
+                                .map(WorkshopItem::from_details)
+                        }));
+                    }),
+                )
+                .map_err(steam_error("create_query_ugc_details_request"))?;
 
-        rx.await
-            .unwrap()
-            .map_err(|e| Error::from_reason(e.to_string()))
+            crate::client::session_result(session, rx)
+                .await?
+                .map_err(steam_error("send_query_ugc_request"))
+        })
+        .await?
     }
 
     #[napi]
@@ -401,99 +393,79 @@ pub mod steamugc {
     /// @param query 要查询创意工坊指定选项
     /// @returns 返回创意工坊查询结果
     pub async fn get_items(
-        items: Vec<BigInt>,
+        items: Vec<Either<BigInt, String>>,
         query: Option<WorkshopItemQueryOptions>,
     ) -> Result<Vec<Option<WorkshopItem>>, Error> {
-        let client = crate::client::get_client();
-        let (tx, rx) = oneshot::channel();
-        {
-            let mut item_query = client
-                .ugc()
+        crate::error::catch_panic_async("get_items", async move {
+            let items = items
+                .iter()
+                .map(|item| crate::id::published_file_id("items", item))
+                .collect::<Result<Vec<_>, _>>()?;
+            let (session, client) = crate::client::get_session()?;
+            let (tx, rx) = oneshot::channel();
+
+            client
                 .query_items(
-                    items
-                        .iter()
-                        .map(|id| PublishedFileId(id.get_u64().1))
-                        .collect(),
+                    items,
+                    query.map(Into::into).unwrap_or_default(),
+                    Box::new(|result| {
+                        let _ = tx.send(result.map(|items| {
+                            items
+                                .into_iter()
+                                .map(|item| item.map(WorkshopItem::from_details))
+                                .collect()
+                        }));
+                    }),
                 )
-                .map_err(|e| Error::from_reason(e.to_string()))?;
+                .map_err(steam_error("create_query_ugc_details_request"))?;
 
-            if let Some(query) = query {
-                if let Some(cached_response_max_age) = query.cached_response_max_age {
-                    item_query = item_query.allow_cached_response(cached_response_max_age);
-                }
-
-                if let Some(include_metadata) = query.include_metadata {
-                    item_query = item_query.include_metadata(include_metadata);
-                }
-
-                if let Some(include_long_description) = query.include_long_description {
-                    item_query = item_query.include_long_desc(include_long_description);
-                }
-
-                if let Some(language) = query.language {
-                    item_query = item_query.language(&language);
-                }
-            }
-
-            item_query.fetch(|result| {
-                tx.send(result.map(|result| {
-                    result
-                        .iter()
-                        .enumerate()
-                        .map(|(i, item)| {
-                            item.map(|item| {
-                                WorkshopItem::from_query(item, result.preview_url(i as u32))
-                            })
-                        })
-                        .collect()
-                }))
-                .unwrap();
-            });
-        }
-
-        rx.await
-            .unwrap()
-            .map_err(|e| Error::from_reason(e.to_string()))
+            crate::client::session_result(session, rx)
+                .await?
+                .map_err(steam_error("send_query_ugc_request"))
+        })
+        .await?
     }
 
     /// 获取当前已安装在光盘上的创意工坊物品相关信息。
     ///
     /// @param item_id 	要获取安装信息的创意工坊物品
     /// @returns 返回创意工坊安装信息
-    #[napi]
-    pub fn get_item_install_info(item_id: BigInt) -> Option<InstallInfo> {
-        let client = crate::client::get_client();
-        let result = client
-            .ugc()
-            .item_install_info(PublishedFileId(item_id.get_u64().1));
-
-        match result {
+    #[napi(catch_unwind)]
+    pub fn get_item_install_info(
+        item_id: Either<BigInt, String>,
+    ) -> Result<Option<InstallInfo>, Error> {
+        let item_id = crate::id::published_file_id("item_id", &item_id)?;
+        let client = crate::client::get_client()?;
+        let result = client.item_install_info(item_id);
+
+        Ok(match result {
             Some(install_info) => Some(InstallInfo {
                 folder: install_info.folder,
                 size_on_disk: BigInt::from(install_info.size_on_disk),
                 timestamp: install_info.timestamp,
             }),
             None => None,
-        }
+        })
     }
 
     /// 获取设置了 k_EItemStateNeedsUpdate 的创意工坊物品的待下载状态信息
     ///
     /// @param item_id 要获取下载信息的创意工坊物品
-    #[napi]
-    pub fn get_item_download_info(item_id: BigInt) -> Option<DownloadInfo> {
-        let client = crate::client::get_client();
-        let result = client
-            .ugc()
-            .item_download_info(PublishedFileId(item_id.get_u64().1));
-
-        result.map(|download_info| DownloadInfo {
+    #[napi(catch_unwind)]
+    pub fn get_item_download_info(
+        item_id: Either<BigInt, String>,
+    ) -> Result<Option<DownloadInfo>, Error> {
+        let item_id = crate::id::published_file_id("item_id", &item_id)?;
+        let client = crate::client::get_client()?;
+        let result = client.item_download_info(item_id);
+
+        Ok(result.map(|download_info| DownloadInfo {
             current: BigInt::from(download_info.0),
             total: BigInt::from(download_info.1),
-        })
+        }))
     }
 
-    #[napi]
+    #[napi(catch_unwind)]
     /// 获取创意工坊物品更新的进度
     ///
     /// @param item_id 创意工坊物品ID
@@ -504,7 +476,7 @@ pub mod steamugc {
     /// @param progress_callback 创意工坊上传失败时回调
     /// @param progress_callback_interval_ms 创意工坊回调时间
     pub fn update_item_with_callback(
-        item_id: BigInt,
+        item_id: Either<BigInt, String>,
         update_details: UgcUpdate,
         app_id: Option<u32>,
         #[napi(ts_arg_type = "(data: UgcResult) => void")] success_callback: napi::JsFunction,
@@ -513,47 +485,59 @@ pub mod steamugc {
             napi::JsFunction,
         >,
         progress_callback_interval_ms: Option<u32>,
-    ) {
+    ) -> Result<(), Error> {
+        let item_id = crate::id::published_file_id("item_id", &item_id)?;
         let success_callback: ThreadsafeFunction<UgcResult, ErrorStrategy::Fatal> =
-            success_callback
-                .create_threadsafe_function(0, |ctx| Ok(vec![ctx.value]))
-                .unwrap();
-        let error_callback: ThreadsafeFunction<Error, ErrorStrategy::Fatal> = error_callback
-            .create_threadsafe_function(0, |ctx| Ok(vec![ctx.value]))
-            .unwrap();
+            success_callback.create_threadsafe_function(0, |ctx| Ok(vec![ctx.value]))?;
+        let error_callback: ThreadsafeFunction<Error, ErrorStrategy::Fatal> =
+            error_callback.create_threadsafe_function(0, |ctx| Ok(vec![ctx.value]))?;
 
-        let client = crate::client::get_client();
+        let (session, client) = crate::client::get_session()?;
 
         let app_id = app_id
             .map(steamworks::AppId)
-            .unwrap_or_else(|| client.utils().app_id());
+            .unwrap_or_else(|| client.app_id());
 
         {
-            let update_handle = client
-                .ugc()
-                .start_item_update(app_id, PublishedFileId(item_id.get_u64().1));
-
-            let update_watch_handle = update_details.submit(update_handle, move |result| {
+            let (tx, rx) = oneshot::channel();
+
+            let update_watch_handle = client.submit_item_update(
+                app_id,
+                item_id,
+                update_details.into(),
+                Box::new(move |result| {
+                    let _ = tx.send(result);
+                }),
+            );
+
+            // 会话切换时同样通过 error_callback 通知，保证两个回调之一一定会被调用
+            napi::bindgen_prelude::spawn(async move {
+                let result = crate::error::catch_panic_async(
+                    "update_item_with_callback",
+                    crate::client::session_result(session, rx),
+                )
+                .await;
                 match result {
-                    Ok((item_id, needs_to_accept_agreement)) => success_callback.call(
+                    Ok(Ok(Ok((item_id, needs_to_accept_agreement)))) => success_callback.call(
                         UgcResult {
                             item_id: BigInt::from(item_id.0),
                             needs_to_accept_agreement,
                         },
                         ThreadsafeFunctionCallMode::Blocking,
                     ),
-                    Err(e) => error_callback.call(
-                        Error::from_reason(e.to_string()),
+                    Ok(Ok(Err(e))) => error_callback.call(
+                        steam_error("submit_item_update")(e),
                         ThreadsafeFunctionCallMode::Blocking,
                     ),
+                    Ok(Err(e)) | Err(e) => {
+                        error_callback.call(e.into(), ThreadsafeFunctionCallMode::Blocking)
+                    }
                 };
             });
 
             if let Some(progress_callback) = progress_callback {
                 let progress_callback: ThreadsafeFunction<UpdateProgress, ErrorStrategy::Fatal> =
-                    progress_callback
-                        .create_threadsafe_function(0, |ctx| Ok(vec![ctx.value]))
-                        .unwrap();
+                    progress_callback.create_threadsafe_function(0, |ctx| Ok(vec![ctx.value]))?;
 
                 std::thread::spawn(move || loop {
                     let (status, progress, total) = update_watch_handle.progress();
@@ -574,5 +558,6 @@ pub mod steamugc {
                 });
             }
         };
+        Ok(())
     }
 }
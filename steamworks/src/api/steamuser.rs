@@ -4,10 +4,12 @@ use napi_derive::napi;
 #[napi]
 pub mod steamuser {
     use super::CSteamID;
-    use napi::bindgen_prelude::{BigInt, Buffer, Error};
+    use crate::api::logging::steam_log;
+    use crate::backend::{TicketHandle, TicketIdentity};
+    use crate::error::steam_error;
+    use napi::bindgen_prelude::{BigInt, Buffer, Either, Error};
     use std::net::SocketAddr;
-    use steamworks::networking_types::NetworkingIdentity;
-    use steamworks::{AuthSessionTicketResponse, AuthTicket, SteamId, TicketForWebApiResponse};
+    use steamworks::SteamError;
     use tokio::sync::oneshot;
 
     #[napi]
@@ -16,40 +18,46 @@ pub mod steamuser {
         /// 票证数据
         pub(crate) data: Vec<u8>,
         /// 票证句柄
-        pub(crate) handle: AuthTicket,
+        pub(crate) handle: TicketHandle,
+        /// 创建票证时的会话，会话切换后旧客户端的票证已随之失效
+        pub(crate) session: u32,
     }
 
     #[napi]
     impl Ticket {
-        #[napi]
-        pub fn cancel(&mut self) {
-            let client = crate::client::get_client();
-            client.user().cancel_authentication_ticket(self.handle);
+        #[napi(catch_unwind)]
+        pub fn cancel(&mut self) -> Result<(), Error> {
+            let (session, client) = crate::client::get_session()?;
+            // 句柄只在创建它的会话中有效，避免误取消新会话中编号相同的票证
+            if session == self.session {
+                client.cancel_auth_ticket(self.handle);
+            }
+            Ok(())
         }
 
-        #[napi]
+        #[napi(catch_unwind)]
         pub fn get_bytes(&self) -> Buffer {
             self.data.clone().into()
         }
     }
 
-    #[napi]
+    #[napi(catch_unwind)]
     /// 获取当前登录至 Steam 客户端帐户的 Steam ID。 通常被称为“当前用户”，或“本地用户”。
     /// Steam ID 是 Steam 帐户、Steam 组、大厅和聊天室的唯一标识符，用于在 Steamworks API 各部分中区分用户。
     ///
     /// @returns CSteamID
-    pub fn get_steam_id() -> CSteamID {
-        let client = crate::client::get_client();
-        CSteamID::from_steamid(client.user().steam_id())
+    pub fn get_steam_id() -> Result<CSteamID, Error> {
+        let client = crate::client::get_client()?;
+        Ok(CSteamID::from_steamid(client.steam_id()))
     }
 
-    #[napi]
+    #[napi(catch_unwind)]
     /// 获得用户的 Steam 社区个人资料中显示的 Steam 等级
     ///
     /// @returns 当前用户的等级
-    pub fn get_player_steam_level() -> u32 {
-        let client = crate::client::get_client();
-        client.user().level()
+    pub fn get_player_steam_level() -> Result<u32, Error> {
+        let client = crate::client::get_client()?;
+        Ok(client.level())
     }
 
     /// 获取身份验证票证，以便发送给希望对您进行身份验证的实体。
@@ -59,38 +67,31 @@ pub mod steamuser {
     /// @param timeoutSeconds - 等待票证验证的秒数。 默认值为 10 秒。
     /// @returns 返回验证票证
     async fn get_auth_session_ticket(
-        network_identity: NetworkingIdentity,
+        network_identity: TicketIdentity,
         timeout_seconds: Option<u32>,
     ) -> Result<Ticket, Error> {
-        let client = crate::client::get_client();
+        let (session, client) = crate::client::get_session()?;
         let (tx, rx) = oneshot::channel();
-        let mut tx = Some(tx);
-
-        let (ticket_handle, ticket) = client
-            .user()
-            .authentication_session_ticket(network_identity);
-
-        let callback =
-            client.register_callback(move |session_ticket_response: AuthSessionTicketResponse| {
-                if session_ticket_response.ticket == ticket_handle {
-                    if let Some(tx) = tx.take() {
-                        tx.send(match session_ticket_response.result {
-                            Ok(()) => Ok(()),
-                            Err(e) => Err(Error::from_reason(e.to_string())),
-                        })
-                        .unwrap();
-                    }
-                }
-            });
 
-        let mut ticket = Ticket {
+        let (ticket_handle, ticket, callback) = client.auth_session_ticket(
+            network_identity,
+            Box::new(|result| {
+                let _ = tx.send(result.map_err(steam_error("get_auth_session_ticket")));
+            }),
+        );
+
+        let ticket = Ticket {
             data: ticket,
             handle: ticket_handle,
+            session,
         };
 
         let timeout_seconds = u64::from(timeout_seconds.unwrap_or(10));
-        let result =
-            tokio::time::timeout(std::time::Duration::from_secs(timeout_seconds), rx).await;
+        let result = tokio::time::timeout(
+            std::time::Duration::from_secs(timeout_seconds),
+            crate::client::session_result(session, rx),
+        )
+        .await;
 
         drop(callback);
 
@@ -98,19 +99,21 @@ pub mod steamuser {
             Ok(result) => match result {
                 Ok(Ok(())) => Ok(ticket),
                 Ok(Err(e)) => {
-                    ticket.cancel();
+                    client.cancel_auth_ticket(ticket_handle);
                     Err(e)
                 }
-                Err(e) => {
-                    ticket.cancel();
-                    Err(Error::from_reason(e.to_string()))
-                }
+                // 会话已切换，票证随旧客户端一起失效
+                Err(e) => Err(e.into()),
             },
             Err(_) => {
-                ticket.cancel();
-                Err(Error::from_reason(
-                    "Steam didn't validated the ticket in time.",
-                ))
+                steam_log!(
+                    Warn,
+                    "api",
+                    "get_auth_session_ticket timed out after {}s",
+                    timeout_seconds
+                );
+                client.cancel_auth_ticket(ticket_handle);
+                Err(steam_error("get_auth_session_ticket")(SteamError::Timeout))
             }
         }
     }
@@ -125,14 +128,14 @@ pub mod steamuser {
     /// @param timeoutSeconds - 等待票证验证的秒数。 默认值为 10 秒。 如果是游戏服务器，且是从可信的第三方获得的，则可以使用游戏服务器的 steam ID
     /// @returns 返回验证票证
     pub async fn get_session_ticket_with_steam_id(
-        steam_id64: BigInt,
+        steam_id64: Either<BigInt, String>,
         timeout_seconds: Option<u32>,
     ) -> Result<Ticket, Error> {
-        get_auth_session_ticket(
-            NetworkingIdentity::new_steam_id(SteamId::from_raw(steam_id64.get_u64().1)),
-            timeout_seconds,
-        )
-        .await
+        crate::error::catch_panic_async("get_session_ticket_with_steam_id", async move {
+            let steam_id = crate::id::steam_id("steam_id64", &steam_id64)?;
+            get_auth_session_ticket(TicketIdentity::SteamId(steam_id), timeout_seconds).await
+        })
+        .await?
     }
 
     /// 通过SteamID获取身份验证票证，以便发送给希望对您进行身份验证的实体。
@@ -146,12 +149,17 @@ pub mod steamuser {
         ip: String,
         timeout_seconds: Option<u32>,
     ) -> Result<Ticket, Error> {
-        match ip.parse::<SocketAddr>() {
-            Ok(addr) => {
-                get_auth_session_ticket(NetworkingIdentity::new_ip(addr), timeout_seconds).await
+        crate::error::catch_panic_async("get_session_ticket_with_ip", async move {
+            match ip.parse::<SocketAddr>() {
+                Ok(addr) => {
+                    get_auth_session_ticket(TicketIdentity::Ip(addr), timeout_seconds).await
+                }
+                Err(_) => Err(steam_error("get_auth_session_ticket")(
+                    SteamError::InvalidParameter,
+                )),
             }
-            Err(e) => Err(Error::from_reason(e.to_string())),
-        }
+        })
+        .await?
     }
 
     #[napi]
@@ -166,57 +174,54 @@ pub mod steamuser {
         identity: String,
         timeout_seconds: Option<u32>,
     ) -> Result<Ticket, Error> {
-        let client = crate::client::get_client();
-        let (tx, rx) = oneshot::channel();
-        let mut tx = Some(tx);
-
-        let ticket_handle = client
-            .user()
-            .authentication_session_ticket_for_webapi(&identity);
-
-        let callback =
-            client.register_callback(move |ticket_for_webapi_response: TicketForWebApiResponse| {
-                if ticket_for_webapi_response.ticket_handle == ticket_handle {
-                    let mut ticket = ticket_for_webapi_response.ticket;
-                    ticket.truncate(ticket_for_webapi_response.ticket_len as usize);
-
-                    if let Some(tx) = tx.take() {
-                        tx.send(match ticket_for_webapi_response.result {
-                            Ok(()) => Ok(ticket),
-                            Err(e) => Err(Error::from_reason(e.to_string())),
-                        })
-                        .unwrap();
-                    }
-                }
-            });
-
-        let timeout_seconds = u64::from(timeout_seconds.unwrap_or(10));
-        let result =
-            tokio::time::timeout(std::time::Duration::from_secs(timeout_seconds), rx).await;
-
-        drop(callback);
-
-        match result {
-            Ok(result) => match result {
-                Ok(Ok(data)) => Ok(Ticket {
-                    handle: ticket_handle,
-                    data,
+        crate::error::catch_panic_async("get_auth_ticket_for_web_api", async move {
+            let (session, client) = crate::client::get_session()?;
+            let (tx, rx) = oneshot::channel();
+
+            let (ticket_handle, callback) = client.auth_ticket_for_web_api(
+                &identity,
+                Box::new(|result| {
+                    let _ = tx.send(result.map_err(steam_error("get_auth_ticket_for_web_api")));
                 }),
-                Ok(Err(e)) => {
-                    client.user().cancel_authentication_ticket(ticket_handle);
-                    Err(e)
-                }
-                Err(e) => {
-                    client.user().cancel_authentication_ticket(ticket_handle);
-                    Err(Error::from_reason(e.to_string()))
+            );
+
+            let timeout_seconds = u64::from(timeout_seconds.unwrap_or(10));
+            let result = tokio::time::timeout(
+                std::time::Duration::from_secs(timeout_seconds),
+                crate::client::session_result(session, rx),
+            )
+            .await;
+
+            drop(callback);
+
+            match result {
+                Ok(result) => match result {
+                    Ok(Ok(data)) => Ok(Ticket {
+                        handle: ticket_handle,
+                        data,
+                        session,
+                    }),
+                    Ok(Err(e)) => {
+                        client.cancel_auth_ticket(ticket_handle);
+                        Err(e)
+                    }
+                    // 会话已切换，票证随旧客户端一起失效
+                    Err(e) => Err(e.into()),
+                },
+                Err(_) => {
+                    steam_log!(
+                        Warn,
+                        "api",
+                        "get_auth_ticket_for_web_api timed out after {}s",
+                        timeout_seconds
+                    );
+                    client.cancel_auth_ticket(ticket_handle);
+                    Err(steam_error("get_auth_ticket_for_web_api")(
+                        SteamError::Timeout,
+                    ))
                 }
-            },
-            Err(_) => {
-                client.user().cancel_authentication_ticket(ticket_handle);
-                Err(Error::from_reason(
-                    "Steam didn't validated the ticket in time.",
-                ))
             }
-        }
+        })
+        .await?
     }
 }
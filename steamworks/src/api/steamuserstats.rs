@@ -2,7 +2,9 @@ use napi_derive::napi;
 
 #[napi]
 pub mod steamuserstats {
-    #[napi]
+    use napi::bindgen_prelude::Error;
+
+    #[napi(catch_unwind)]
     /// 获取当前用户的当前统计值。
     /// 您必须先调用 RequestCurrentStats，且须通过其回调返回成功之后，才能调用此函数。
     /// 使用 GetUserStat 为其他用户获取统计。
@@ -12,12 +14,12 @@ pub mod steamuserstats {
     /// - 在 Steamworks 网站的"应用管理员"中存在指定统计，且更改已发布。
     /// - RequestCurrentStats 已完成且回调返回成功。
     /// - 传入此函数的类型必须与 Steamworks 网站的"应用管理员"中列出的类型一致。
-    pub fn get_stat(name: String) -> Option<i32> {
-        let client = crate::client::get_client();
-        client.user_stats().get_stat_i32(&name).ok()
+    pub fn get_stat(name: String) -> Result<Option<i32>, Error> {
+        let client = crate::client::get_client()?;
+        Ok(client.stat_i32(&name))
     }
 
-    #[napi]
+    #[napi(catch_unwind)]
     /// 为当前用户设置/更新给定统计的值。
     /// 您必须先调用 RequestCurrentStats，且须通过其回调返回成功之后，才能调用此函数。
     /// 此调用只修改 Steam 的内存状态，因此开销较小。 这样做使 Steam 在游戏崩溃或意外关闭时能存留更改。
@@ -30,12 +32,12 @@ pub mod steamuserstats {
     /// - 在 Steamworks 网站的"应用管理员"中存在指定统计，且更改已发布。
     /// - RequestCurrentStats 已完成且回调返回成功。
     /// - 传入此函数的类型必须与 Steamworks 网站的"应用管理员"中列出的类型一致。
-    pub fn set_stat(name: String, value: i32) -> bool {
-        let client = crate::client::get_client();
-        client.user_stats().set_stat_i32(&name, value).is_ok()
+    pub fn set_stat(name: String, value: i32) -> Result<bool, Error> {
+        let client = crate::client::get_client()?;
+        Ok(client.set_stat_i32(&name, value))
     }
 
-    #[napi]
+    #[napi(catch_unwind)]
     /// 解锁一项成就。
     /// 您必须先调用 RequestCurrentStats，且须通过其回调返回成功之后，才能调用此函数。
     /// 您可以多次解锁一项成就，而无需担心只设置尚未设置的成就。 此调用只修改 Steam 的内存状态，因此开销较小。
@@ -45,17 +47,12 @@ pub mod steamuserstats {
     /// @returns 此函数若达成所有下列条件，返回 true；否则返回 false。
     /// - 在 Steamworks 网站的"应用管理员"中存在指定成就的"API 名称"，且更改已发布。
     /// - RequestCurrentStats 已完成且回调返回成功。
-    pub fn set_achievement(achievement: String) -> bool {
-        let client = crate::client::get_client();
-        client
-            .user_stats()
-            .achievement(&achievement)
-            .set()
-            .and_then(|_| client.user_stats().store_stats())
-            .is_ok()
+    pub fn set_achievement(achievement: String) -> Result<bool, Error> {
+        let client = crate::client::get_client()?;
+        Ok(client.set_achievement(&achievement) && client.store_stats())
     }
 
-    #[napi]
+    #[napi(catch_unwind)]
     /// 获取成就的解锁状态。
     /// 针对其他用户的等效函数为：GetUserAchievement。
     /// 
@@ -63,16 +60,12 @@ pub mod steamuserstats {
     /// @returns 此函数若达成所有下列条件，返回 true；否则返回 false。
     /// - RequestCurrentStats 已完成且回调返回成功。
     /// - 在 Steamworks 网站的"应用管理员"中存在指定成就的"API 名称"，且更改已发布。
-    pub fn get_achievement(achievement: String) -> bool {
-        let client = crate::client::get_client();
-        client
-            .user_stats()
-            .achievement(&achievement)
-            .get()
-            .unwrap_or(false)
+    pub fn get_achievement(achievement: String) -> Result<bool, Error> {
+        let client = crate::client::get_client()?;
+        Ok(client.achievement(&achievement).unwrap_or(false))
     }
 
-    #[napi]
+    #[napi(catch_unwind)]
     /// 重置一项成就的解锁状态。
     /// 此函数主要仅用于测试目的。
     /// 您必须先调用 RequestCurrentStats，且须通过其回调返回成功之后，才能调用此函数。
@@ -82,17 +75,12 @@ pub mod steamuserstats {
     /// @returns 此函数若达成所有下列条件，返回 true；否则返回 false。
     /// - 在 Steamworks 网站的"应用管理员"中存在指定成就的"API 名称"，且更改已发布。
     /// - RequestCurrentStats 已完成且回调返回成功。
-    pub fn clear_achievement(achievement: String) -> bool {
-        let client = crate::client::get_client();
-        client
-            .user_stats()
-            .achievement(&achievement)
-            .clear()
-            .and_then(|_| client.user_stats().store_stats())
-            .is_ok()
+    pub fn clear_achievement(achievement: String) -> Result<bool, Error> {
+        let client = crate::client::get_client()?;
+        Ok(client.clear_achievement(&achievement) && client.store_stats())
     }
 
-    #[napi]
+    #[napi(catch_unwind)]
     /// 将变动的统计与成就数据发送至服务器进行持久保存。
     /// 若失败，则不会发送任何数据至服务器。 建议不断重试，直至调用成功。
     /// 此调用会受到速率限制。 调用频率应该以分钟计，而非秒计。
@@ -108,23 +96,20 @@ pub mod steamuserstats {
     /// 如果 m_eResult 得到 k_EResultInvalidParam 的结果，那么有一个或多个统计因破坏约束或过期而遭到拒绝。
     /// 这种情况下，服务器发回更新值，统计应在本地更新以保持同步。 此时您无需再次调用 RequestCurrentStats。
     /// 如果已有一个或更多成就解锁，则这也将触发一个 UserAchievementStored_t 回调。
-    pub fn store_stats() -> bool {
-        let client = crate::client::get_client();
-        client.user_stats().store_stats().is_ok()
+    pub fn store_stats() -> Result<bool, Error> {
+        let client = crate::client::get_client()?;
+        Ok(client.store_stats())
     }
 
-    #[napi]
+    #[napi(catch_unwind)]
     /// 重置当前用户的统计，且可选择是否重置成就。
     /// 此函数自动调用 StoreStats，以持久保留对服务器的更改。 这通常应只用于开发时的测试目的。
     /// 在调用此函数之后，调用 RequestCurrentStats，确认您与 Steam 提供的新默认值同步了您的统计。
     ///
     /// @param achievements_too	是否也重置用户的成就
     /// @returns true 表示 RequestCurrentStats 已调用且成功地返回了其回调，也即成功；否则返回 false。
-    pub fn reset_all_stats(achievements_too: bool) -> bool {
-        let client = crate::client::get_client();
-        client
-            .user_stats()
-            .reset_all_stats(achievements_too)
-            .is_ok()
+    pub fn reset_all_stats(achievements_too: bool) -> Result<bool, Error> {
+        let client = crate::client::get_client()?;
+        Ok(client.reset_all_stats(achievements_too))
     }
 }
@@ -0,0 +1,275 @@
+use napi_derive::napi;
+
+#[napi]
+/// 服务器监视事件类型
+pub enum EWatchEvent {
+    /// 服务器开始回复，包括第一次查询成功
+    Online,
+    /// 服务器连续多次没有回复
+    Offline,
+    /// 地图改变
+    MapChanged,
+    /// 玩家加入
+    PlayerJoined,
+    /// 玩家离开
+    PlayerLeft,
+    /// 服务器已满
+    Full,
+    /// 服务器从满员变为有空位
+    HasSlots,
+    /// 延迟突增
+    LatencySpike,
+}
+
+#[napi]
+/// 服务器监视，在后台按间隔查询服务器并报告变化，不需要初始化 Steam
+pub mod watch {
+    use super::EWatchEvent;
+    use crate::api::a2s::a2s::A2sServerInfo;
+    use crate::api::logging::steam_log;
+    use crate::error::SteamworksError;
+    use crate::watch::history::Sample;
+    use crate::watch::{self, WatchEvent, WatchEventKind, WatchOptions, Watcher};
+    use napi::bindgen_prelude::Error;
+    use napi::threadsafe_function::{
+        ErrorStrategy, ThreadsafeFunction, ThreadsafeFunctionCallMode,
+    };
+    use napi::JsFunction;
+    use std::path::PathBuf;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[napi(object)]
+    /// 监视选项
+    pub struct ServerWatchOptions {
+        /// 默认轮询间隔（毫秒），默认 30000，最小 1000
+        pub interval_ms: Option<u32>,
+        /// 单次查询的超时时间（毫秒），默认 3000
+        pub timeout_ms: Option<u32>,
+        /// 是否查询玩家列表以报告玩家进出，默认 true
+        pub track_players: Option<bool>,
+        /// 连续失败多少次后认为离线，默认 2
+        pub offline_after: Option<u32>,
+        /// 延迟超过基准多少倍算作突增，默认 2
+        pub latency_spike_factor: Option<f64>,
+        /// 延迟至少增加多少毫秒才算突增，默认 100
+        pub latency_spike_min_ms: Option<u32>,
+        /// 保存人数历史的目录，为空时不保存
+        pub history_dir: Option<String>,
+        /// 每个服务器保留的历史采样数量，默认 2880
+        pub history_size: Option<u32>,
+    }
+
+    impl From<Option<ServerWatchOptions>> for WatchOptions {
+        fn from(options: Option<ServerWatchOptions>) -> Self {
+            let mut watch_options = WatchOptions::default();
+            let Some(options) = options else {
+                return watch_options;
+            };
+
+            if let Some(interval_ms) = options.interval_ms {
+                watch_options.interval = Duration::from_millis(u64::from(interval_ms));
+            }
+            if let Some(timeout_ms) = options.timeout_ms {
+                watch_options.timeout = Duration::from_millis(u64::from(timeout_ms));
+            }
+            if let Some(track_players) = options.track_players {
+                watch_options.players = track_players;
+            }
+            if let Some(offline_after) = options.offline_after {
+                watch_options.offline_after = offline_after.max(1);
+            }
+            if let Some(factor) = options.latency_spike_factor {
+                watch_options.latency_spike_factor = factor;
+            }
+            if let Some(min_ms) = options.latency_spike_min_ms {
+                watch_options.latency_spike_min = Duration::from_millis(u64::from(min_ms));
+            }
+            watch_options.history_dir = options.history_dir.map(PathBuf::from);
+            if let Some(history_size) = options.history_size {
+                watch_options.history_capacity = history_size as usize;
+            }
+            watch_options
+        }
+    }
+
+    #[napi(object)]
+    /// 服务器监视事件
+    pub struct ServerWatchEvent {
+        pub kind: EWatchEvent,
+        /// 添加服务器时传入的地址
+        pub address: String,
+        /// 本次查询到的服务器信息，离线时为空
+        pub info: Option<A2sServerInfo>,
+        /// 换图前的地图，仅 MapChanged
+        pub previous_map: Option<String>,
+        /// 加入或离开的玩家名，仅 PlayerJoined 与 PlayerLeft
+        pub player: Option<String>,
+        /// 突增前的平均延迟（毫秒），仅 LatencySpike，当前延迟见 info.latency
+        pub baseline_ms: Option<f64>,
+        /// 最后一次查询失败的原因，仅 Offline
+        pub error: Option<String>,
+    }
+
+    impl From<WatchEvent> for ServerWatchEvent {
+        fn from(event: WatchEvent) -> Self {
+            let mut value = Self {
+                kind: EWatchEvent::Online,
+                info: event
+                    .info
+                    .map(|info| A2sServerInfo::from_info(event.address.clone(), info)),
+                address: event.address,
+                previous_map: None,
+                player: None,
+                baseline_ms: None,
+                error: None,
+            };
+            value.kind = match event.kind {
+                WatchEventKind::Online => EWatchEvent::Online,
+                WatchEventKind::Offline(error) => {
+                    value.error = Some(error.context("a2s_info", &value.address).to_string());
+                    EWatchEvent::Offline
+                }
+                WatchEventKind::MapChanged { previous } => {
+                    value.previous_map = Some(previous);
+                    EWatchEvent::MapChanged
+                }
+                WatchEventKind::PlayerJoined(name) => {
+                    value.player = Some(name);
+                    EWatchEvent::PlayerJoined
+                }
+                WatchEventKind::PlayerLeft(name) => {
+                    value.player = Some(name);
+                    EWatchEvent::PlayerLeft
+                }
+                WatchEventKind::Full => EWatchEvent::Full,
+                WatchEventKind::HasSlots => EWatchEvent::HasSlots,
+                WatchEventKind::LatencySpike { baseline } => {
+                    value.baseline_ms = Some(baseline.as_secs_f64() * 1000.0);
+                    EWatchEvent::LatencySpike
+                }
+            };
+            value
+        }
+    }
+
+    #[napi(object)]
+    /// 人数历史中的一次采样
+    pub struct PopulationSample {
+        /// 采样时间（Unix 毫秒），可以直接传给 `new Date()`
+        pub time: f64,
+        /// 玩家数量（包括机器人），离线时为空
+        pub players: Option<u32>,
+        /// 最大玩家数量，离线时为空
+        pub max_players: Option<u32>,
+        /// 机器人数量，离线时为空
+        pub bots: Option<u32>,
+    }
+
+    impl From<Sample> for PopulationSample {
+        fn from(sample: Sample) -> Self {
+            let online = sample.players.is_some();
+            Self {
+                time: sample.time as f64 * 1000.0,
+                players: sample.players.map(u32::from),
+                max_players: online.then_some(u32::from(sample.max_players)),
+                bots: online.then_some(u32::from(sample.bots)),
+            }
+        }
+    }
+
+    #[napi]
+    /// 服务器监视器，停止或被回收时结束所有轮询
+    pub struct ServerWatcher {
+        watcher: Arc<Watcher>,
+    }
+
+    #[napi]
+    impl ServerWatcher {
+        #[napi(catch_unwind)]
+        /// 开始监视服务器，第一次查询立即进行；已在监视的服务器会以新的间隔重新开始
+        ///
+        /// @param address 服务器地址，`ip:port` 或 `域名:port`，未指定端口时使用 27015
+        /// @param interval_ms 该服务器的轮询间隔（毫秒），为空时使用默认间隔
+        /// @returns 是否为新添加的服务器
+        pub fn add(&self, address: String, interval_ms: Option<u32>) -> bool {
+            let interval = interval_ms.map(|ms| Duration::from_millis(u64::from(ms)));
+            napi::bindgen_prelude::within_runtime_if_available(|| {
+                self.watcher.add(&address, interval)
+            })
+        }
+
+        #[napi(catch_unwind)]
+        /// 停止监视服务器，已保存的历史不会被删除
+        ///
+        /// @returns 服务器是否在监视中
+        pub fn remove(&self, address: String) -> bool {
+            self.watcher.remove(&address)
+        }
+
+        #[napi(catch_unwind)]
+        /// 正在监视的服务器地址
+        pub fn servers(&self) -> Vec<String> {
+            self.watcher.addresses()
+        }
+
+        #[napi(catch_unwind)]
+        /// 停止监视所有服务器
+        ///
+        /// @noreturns
+        pub fn stop(&self) {
+            self.watcher.stop();
+        }
+
+        #[napi]
+        /// 读取服务器的人数历史，服务器不需要正在监视
+        ///
+        /// @param address 添加服务器时传入的地址
+        /// @returns 按时间排序的采样，未指定历史目录时为空
+        pub async fn history(&self, address: String) -> Result<Vec<PopulationSample>, Error> {
+            let watcher = self.watcher.clone();
+            crate::error::catch_panic_async("watch_history", async move {
+                let Some(history) = watcher.history() else {
+                    return Ok(Vec::new());
+                };
+                let samples = history.load(address.trim()).map_err(|error| {
+                    steam_log!(
+                        Warn,
+                        "watch",
+                        "failed to read history for {}: {}",
+                        address,
+                        error
+                    );
+                    SteamworksError::Io {
+                        call: "watch_history",
+                        path: history.path(address.trim()).display().to_string(),
+                        error,
+                    }
+                })?;
+                Ok(samples.into_iter().map(PopulationSample::from).collect())
+            })
+            .await?
+        }
+    }
+
+    #[napi(catch_unwind)]
+    /// 创建服务器监视器
+    ///
+    /// @param options 监视选项
+    /// @param on_event 服务器状态变化时调用
+    /// @returns 监视器，使用 add 添加服务器
+    pub fn create_watcher(
+        options: Option<ServerWatchOptions>,
+        #[napi(ts_arg_type = "(event: ServerWatchEvent) => void")] on_event: JsFunction,
+    ) -> Result<ServerWatcher, Error> {
+        let on_event: ThreadsafeFunction<ServerWatchEvent, ErrorStrategy::Fatal> =
+            on_event.create_threadsafe_function(0, |ctx| Ok(vec![ctx.value]))?;
+        let handler: watch::EventHandler = Arc::new(move |event: WatchEvent| {
+            on_event.call(event.into(), ThreadsafeFunctionCallMode::NonBlocking);
+        });
+
+        Ok(ServerWatcher {
+            watcher: Arc::new(Watcher::new(WatchOptions::from(options), handler)),
+        })
+    }
+}
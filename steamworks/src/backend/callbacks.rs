@@ -0,0 +1,171 @@
+//! steamworks-rs 未封装的 Steam 回调
+
+use std::ffi::c_void;
+use std::ptr;
+use steamworks::{sys, AppId, Callback, LobbyId, PublishedFileId, SteamError, SteamId};
+
+/// 大厅聊天消息的最大字节数
+const MAX_LOBBY_CHAT_MESSAGE: usize = 4096;
+
+/// 进入大厅的结果，既作为 `JoinLobby` 的调用结果，也作为回调广播
+#[derive(Clone, Debug)]
+pub struct LobbyEnter {
+    pub lobby: LobbyId,
+    pub chat_permissions: u32,
+    pub locked: bool,
+    /// EChatRoomEnterResponse
+    pub chat_room_enter_response: u32,
+}
+
+unsafe impl Callback for LobbyEnter {
+    const ID: i32 = 504;
+    const SIZE: i32 = std::mem::size_of::<sys::LobbyEnter_t>() as i32;
+
+    unsafe fn from_raw(raw: *mut c_void) -> Self {
+        let val = &*(raw as *const sys::LobbyEnter_t);
+        LobbyEnter {
+            lobby: LobbyId::from_raw(val.m_ulSteamIDLobby),
+            chat_permissions: val.m_rgfChatPermissions,
+            locked: val.m_bLocked,
+            chat_room_enter_response: val.m_EChatRoomEnterResponse,
+        }
+    }
+}
+
+impl LobbyEnter {
+    /// 将 EChatRoomEnterResponse 转换为对应的 Steam 错误
+    pub fn result(&self) -> Result<LobbyId, SteamError> {
+        match self.chat_room_enter_response {
+            // k_EChatRoomEnterResponseSuccess
+            1 => Ok(self.lobby),
+            // k_EChatRoomEnterResponseDoesntExist
+            2 => Err(SteamError::NoMatch),
+            // k_EChatRoomEnterResponseNotAllowed / Limited / ClanDisabled
+            3 | 7 | 8 => Err(SteamError::AccessDenied),
+            // k_EChatRoomEnterResponseFull
+            4 => Err(SteamError::LimitExceeded),
+            // k_EChatRoomEnterResponseBanned / CommunityBan
+            6 | 9 => Err(SteamError::Banned),
+            // k_EChatRoomEnterResponseMemberBlockedYou / YouBlockedMember
+            10 | 11 => Err(SteamError::Blocked),
+            // k_EChatRoomEnterResponseRatelimitExceeded
+            15 => Err(SteamError::RateLimitExceeded),
+            _ => Err(SteamError::Generic),
+        }
+    }
+}
+
+/// 大厅收到聊天消息
+#[derive(Clone, Debug)]
+pub struct LobbyChatMsg {
+    pub lobby: LobbyId,
+    pub user: SteamId,
+    /// EChatEntryType
+    pub chat_entry_type: u8,
+    pub chat_id: u32,
+    /// 消息内容，非 UTF-8 字节会被替换
+    pub message: String,
+}
+
+unsafe impl Callback for LobbyChatMsg {
+    const ID: i32 = 507;
+    const SIZE: i32 = std::mem::size_of::<sys::LobbyChatMsg_t>() as i32;
+
+    unsafe fn from_raw(raw: *mut c_void) -> Self {
+        let val = &*(raw as *const sys::LobbyChatMsg_t);
+
+        // 回调只携带消息编号，内容需要在回调中立即读取
+        let mut data = vec![0u8; MAX_LOBBY_CHAT_MESSAGE];
+        let len = sys::SteamAPI_ISteamMatchmaking_GetLobbyChatEntry(
+            sys::SteamAPI_SteamMatchmaking_v009(),
+            val.m_ulSteamIDLobby,
+            val.m_iChatID as i32,
+            ptr::null_mut(),
+            data.as_mut_ptr() as *mut c_void,
+            data.len() as i32,
+            ptr::null_mut(),
+        );
+        data.truncate(len.max(0) as usize);
+
+        LobbyChatMsg {
+            lobby: LobbyId::from_raw(val.m_ulSteamIDLobby),
+            user: SteamId::from_raw(val.m_ulSteamIDUser),
+            chat_entry_type: val.m_eChatEntryType,
+            chat_id: val.m_iChatID,
+            message: String::from_utf8_lossy(&data)
+                .trim_end_matches('\0')
+                .to_string(),
+        }
+    }
+}
+
+/// 创意工坊物品安装或更新完成
+#[derive(Clone, Debug)]
+pub struct ItemInstalled {
+    pub app_id: AppId,
+    pub published_file_id: PublishedFileId,
+}
+
+unsafe impl Callback for ItemInstalled {
+    const ID: i32 = 3405;
+    const SIZE: i32 = std::mem::size_of::<sys::ItemInstalled_t>() as i32;
+
+    unsafe fn from_raw(raw: *mut c_void) -> Self {
+        let val = &*(raw as *const sys::ItemInstalled_t);
+        ItemInstalled {
+            app_id: AppId(val.m_unAppID),
+            published_file_id: PublishedFileId(val.m_nPublishedFileId),
+        }
+    }
+}
+
+/// 用户头像加载完成
+#[derive(Clone, Debug)]
+pub struct AvatarImageLoaded {
+    pub steam_id: SteamId,
+    /// 头像图片句柄
+    pub image: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
+unsafe impl Callback for AvatarImageLoaded {
+    const ID: i32 = 334;
+    const SIZE: i32 = std::mem::size_of::<sys::AvatarImageLoaded_t>() as i32;
+
+    unsafe fn from_raw(raw: *mut c_void) -> Self {
+        let val = &*(raw as *const sys::AvatarImageLoaded_t);
+        AvatarImageLoaded {
+            steam_id: SteamId::from_raw(val.m_steamID.m_steamid.m_unAll64Bits),
+            image: val.m_iImage,
+            width: val.m_iWide,
+            height: val.m_iTall,
+        }
+    }
+}
+
+/// 浮动游戏手柄文本输入框已关闭
+#[derive(Clone, Debug)]
+pub struct FloatingGamepadTextInputDismissed {}
+
+unsafe impl Callback for FloatingGamepadTextInputDismissed {
+    const ID: i32 = 738;
+    const SIZE: i32 = std::mem::size_of::<sys::FloatingGamepadTextInputDismissed_t>() as i32;
+
+    unsafe fn from_raw(_: *mut c_void) -> Self {
+        FloatingGamepadTextInputDismissed {}
+    }
+}
+
+/// Steam 客户端即将关闭
+#[derive(Clone, Debug)]
+pub struct SteamShutdown {}
+
+unsafe impl Callback for SteamShutdown {
+    const ID: i32 = 704;
+    const SIZE: i32 = std::mem::size_of::<sys::SteamShutdown_t>() as i32;
+
+    unsafe fn from_raw(_: *mut c_void) -> Self {
+        SteamShutdown {}
+    }
+}
@@ -0,0 +1,76 @@
+//! steamworks-rs 未封装的 ISteamMatchmaking 收藏服务器函数
+//!
+//! 收藏夹与历史记录由 Steam 客户端保存，与游戏内服务器浏览器中看到的列表一致。
+
+use super::FavoriteGame;
+use std::net::Ipv4Addr;
+use steamworks::{sys, AppId};
+
+fn matchmaking() -> *mut sys::ISteamMatchmaking {
+    unsafe { sys::SteamAPI_SteamMatchmaking_v009() }
+}
+
+/// 读取收藏夹与历史记录中的所有服务器
+pub fn favorite_games() -> Vec<FavoriteGame> {
+    unsafe {
+        let matchmaking = matchmaking();
+        let count = sys::SteamAPI_ISteamMatchmaking_GetFavoriteGameCount(matchmaking);
+        (0..count)
+            .filter_map(|index| {
+                let mut app_id = 0;
+                let mut ip = 0;
+                let mut connection_port = 0;
+                let mut query_port = 0;
+                let mut flags = 0;
+                let mut last_played = 0;
+                sys::SteamAPI_ISteamMatchmaking_GetFavoriteGame(
+                    matchmaking,
+                    index,
+                    &mut app_id,
+                    &mut ip,
+                    &mut connection_port,
+                    &mut query_port,
+                    &mut flags,
+                    &mut last_played,
+                )
+                .then(|| FavoriteGame {
+                    app_id: AppId(app_id),
+                    ip: Ipv4Addr::from(ip),
+                    connection_port,
+                    query_port,
+                    flags,
+                    last_played,
+                })
+            })
+            .collect()
+    }
+}
+
+pub fn add_favorite_game(game: &FavoriteGame) -> Option<u32> {
+    let index = unsafe {
+        sys::SteamAPI_ISteamMatchmaking_AddFavoriteGame(
+            matchmaking(),
+            game.app_id.0,
+            u32::from(game.ip),
+            game.connection_port,
+            game.query_port,
+            game.flags,
+            game.last_played,
+        )
+    };
+    // 添加失败时返回 -1
+    u32::try_from(index).ok()
+}
+
+pub fn remove_favorite_game(game: &FavoriteGame) -> bool {
+    unsafe {
+        sys::SteamAPI_ISteamMatchmaking_RemoveFavoriteGame(
+            matchmaking(),
+            game.app_id.0,
+            u32::from(game.ip),
+            game.connection_port,
+            game.query_port,
+            game.flags,
+        )
+    }
+}
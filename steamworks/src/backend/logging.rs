@@ -0,0 +1,373 @@
+//! 记录日志的 Steam 后端
+//!
+//! 包装任意 [`SteamBackend`]，在转发前记录每一次调用（Trace），
+//! 异步调用完成时记录耗时与结果，回调分发时记录回调类型（Debug）。
+
+use super::{
+    ApiCallback, CallbackGuard, CallbackHandler, FavoriteGame, FriendInfo, GameServerItem,
+    ItemInstallInfo, ItemUpdate, ServerListEvent, ServerListHandler, ServerListKind, ServerPlayer,
+    SteamBackend, TicketHandle, TicketIdentity, UpdateWatch, WorkshopItemDetails, WorkshopQuery,
+};
+use crate::api::callback::SteamApiCallback;
+use crate::api::logging::steam_log;
+use std::fmt::Debug;
+use std::net::SocketAddrV4;
+use std::sync::Arc;
+use std::time::Instant;
+use steamworks::{
+    AppId, FriendFlags, FriendGame, ItemState, LobbyId, LobbyType, OverlayToStoreFlag,
+    PublishedFileId, SendType, SteamError, SteamId,
+};
+
+pub struct LoggingBackend {
+    inner: Arc<dyn SteamBackend>,
+}
+
+impl LoggingBackend {
+    pub fn new(inner: Arc<dyn SteamBackend>) -> Self {
+        Self { inner }
+    }
+}
+
+/// 格式化调用参数
+fn debug_args(args: &[&dyn Debug]) -> String {
+    args.iter()
+        .map(|arg| format!("{:?}", arg))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// 包装异步调用的结果回调，完成时记录耗时与结果
+fn completion<T: Send + 'static>(call: &'static str, callback: ApiCallback<T>) -> ApiCallback<T> {
+    let start = Instant::now();
+    Box::new(move |result| {
+        match &result {
+            Ok(_) => steam_log!(Debug, "api", "{} completed in {:?}", call, start.elapsed()),
+            Err(error) => steam_log!(
+                Warn,
+                "api",
+                "{} failed after {:?}: {}",
+                call,
+                start.elapsed(),
+                error
+            ),
+        }
+        callback(result);
+    })
+}
+
+/// 转发参数都实现了 `Debug` 的同步调用
+macro_rules! forward {
+    ($(fn $name:ident(&self $(, $arg:ident: $ty:ty)*) $(-> $ret:ty)?;)*) => {$(
+        fn $name(&self $(, $arg: $ty)*) $(-> $ret)? {
+            steam_log!(Trace, "api", "{}({})", stringify!($name), debug_args(&[$(&$arg),*]));
+            self.inner.$name($($arg),*)
+        }
+    )*};
+}
+
+impl SteamBackend for LoggingBackend {
+    fn run_callbacks(&self) {
+        // 由后台线程周期性调用，不记录
+        self.inner.run_callbacks();
+    }
+
+    fn register_callback(&self, kind: SteamApiCallback, handler: CallbackHandler) -> CallbackGuard {
+        steam_log!(Debug, "callback", "register {:?}", kind);
+        self.inner.register_callback(
+            kind,
+            Box::new(move |payload| {
+                steam_log!(Debug, "callback", "dispatch {:?}", kind);
+                handler(payload);
+            }),
+        )
+    }
+
+    forward! {
+        fn is_subscribed_app(&self, app_id: AppId) -> bool;
+        fn is_dlc_installed(&self, app_id: AppId) -> bool;
+        fn is_app_installed(&self, app_id: AppId) -> bool;
+        fn is_subscribed_from_free_weekend(&self) -> bool;
+        fn is_vac_banned(&self) -> bool;
+        fn is_cybercafe(&self) -> bool;
+        fn is_low_violence(&self) -> bool;
+        fn is_subscribed(&self) -> bool;
+        fn app_owner(&self) -> SteamId;
+        fn available_game_languages(&self) -> Vec<String>;
+        fn current_beta_name(&self) -> Option<String>;
+        fn app_install_dir(&self, app_id: AppId) -> String;
+        fn current_game_language(&self) -> String;
+
+        fn persona_name(&self) -> String;
+        fn set_rich_presence(&self, key: &str, value: Option<&str>) -> bool;
+        fn friend_game_played(&self, steam_id: SteamId) -> Option<FriendGame>;
+        fn friends(&self, flags: FriendFlags) -> Vec<FriendInfo>;
+        fn activate_game_overlay(&self, dialog: &str);
+        fn activate_game_overlay_to_user(&self, dialog: &str, steam_id: SteamId);
+        fn activate_invite_dialog(&self, lobby_id: LobbyId);
+        fn activate_game_overlay_to_web_page(&self, url: &str);
+
+        fn input_init(&self);
+        fn input_shutdown(&self);
+        fn connected_controllers(&self) -> Vec<u64>;
+        fn action_set_handle(&self, action_set_name: &str) -> u64;
+        fn digital_action_handle(&self, action_name: &str) -> u64;
+        fn analog_action_handle(&self, action_name: &str) -> u64;
+        fn activate_action_set(&self, controller: u64, action_set_handle: u64);
+        fn digital_action_state(&self, controller: u64, action_handle: u64) -> bool;
+        fn analog_action_vector(&self, controller: u64, action_handle: u64) -> (f32, f32);
+
+        fn leave_lobby(&self, lobby_id: LobbyId);
+        fn lobby_member_count(&self, lobby_id: LobbyId) -> usize;
+        fn lobby_member_limit(&self, lobby_id: LobbyId) -> Option<usize>;
+        fn lobby_members(&self, lobby_id: LobbyId) -> Vec<SteamId>;
+        fn lobby_owner(&self, lobby_id: LobbyId) -> SteamId;
+        fn set_lobby_joinable(&self, lobby_id: LobbyId, joinable: bool) -> bool;
+        fn lobby_data(&self, lobby_id: LobbyId, key: &str) -> Option<String>;
+        fn lobby_data_count(&self, lobby_id: LobbyId) -> u32;
+        fn lobby_data_by_index(&self, lobby_id: LobbyId, index: u32) -> Option<(String, String)>;
+        fn set_lobby_data(&self, lobby_id: LobbyId, key: &str, value: &str) -> bool;
+        fn delete_lobby_data(&self, lobby_id: LobbyId, key: &str) -> bool;
+        fn favorite_games(&self) -> Vec<FavoriteGame>;
+        fn add_favorite_game(&self, game: &FavoriteGame) -> Option<u32>;
+        fn remove_favorite_game(&self, game: &FavoriteGame) -> bool;
+
+        fn is_p2p_packet_available(&self) -> Option<usize>;
+        fn accept_p2p_session(&self, steam_id: SteamId);
+
+        fn is_cloud_enabled_for_account(&self) -> bool;
+        fn is_cloud_enabled_for_app(&self) -> bool;
+        fn file_read(&self, name: &str) -> std::io::Result<Vec<u8>>;
+        fn file_delete(&self, name: &str) -> bool;
+        fn file_exists(&self, name: &str) -> bool;
+
+        fn item_state(&self, item_id: PublishedFileId) -> ItemState;
+        fn download_item(&self, item_id: PublishedFileId, high_priority: bool) -> bool;
+        fn subscribed_items(&self) -> Vec<PublishedFileId>;
+        fn item_install_info(&self, item_id: PublishedFileId) -> Option<ItemInstallInfo>;
+        fn item_download_info(&self, item_id: PublishedFileId) -> Option<(u64, u64)>;
+
+        fn steam_id(&self) -> SteamId;
+        fn level(&self) -> u32;
+        fn cancel_auth_ticket(&self, ticket: TicketHandle);
+
+        fn request_current_stats(&self);
+        fn stat_i32(&self, name: &str) -> Option<i32>;
+        fn set_stat_i32(&self, name: &str, value: i32) -> bool;
+        fn achievement(&self, name: &str) -> Option<bool>;
+        fn set_achievement(&self, name: &str) -> bool;
+        fn clear_achievement(&self, name: &str) -> bool;
+        fn store_stats(&self) -> bool;
+        fn reset_all_stats(&self, achievements_too: bool) -> bool;
+
+        fn app_id(&self) -> AppId;
+        fn ip_country(&self) -> String;
+        fn server_real_time(&self) -> u32;
+        fn is_steam_running_on_steam_deck(&self) -> bool;
+    }
+
+    fn activate_game_overlay_to_store(&self, app_id: AppId, flag: OverlayToStoreFlag) {
+        steam_log!(Trace, "api", "activate_game_overlay_to_store({:?})", app_id);
+        self.inner.activate_game_overlay_to_store(app_id, flag);
+    }
+
+    fn create_lobby(
+        &self,
+        lobby_type: LobbyType,
+        max_members: u32,
+        callback: ApiCallback<LobbyId>,
+    ) {
+        steam_log!(Trace, "api", "create_lobby({})", max_members);
+        self.inner.create_lobby(
+            lobby_type,
+            max_members,
+            completion("create_lobby", callback),
+        );
+    }
+
+    fn join_lobby(&self, lobby_id: LobbyId, callback: ApiCallback<LobbyId>) {
+        steam_log!(Trace, "api", "join_lobby({:?})", lobby_id);
+        self.inner
+            .join_lobby(lobby_id, completion("join_lobby", callback));
+    }
+
+    fn request_lobby_list(&self, callback: ApiCallback<Vec<LobbyId>>) {
+        steam_log!(Trace, "api", "request_lobby_list()");
+        self.inner
+            .request_lobby_list(completion("request_lobby_list", callback));
+    }
+
+    fn request_server_list(
+        &self,
+        kind: ServerListKind,
+        app_id: AppId,
+        filters: Vec<(String, String)>,
+        mut handler: ServerListHandler,
+    ) -> CallbackGuard {
+        steam_log!(
+            Trace,
+            "api",
+            "request_server_list({:?}, {:?}, {:?})",
+            kind,
+            app_id,
+            filters
+        );
+        let start = Instant::now();
+        let (mut responded, mut failed) = (0u32, 0u32);
+        self.inner.request_server_list(
+            kind,
+            app_id,
+            filters,
+            Box::new(move |event| {
+                match &event {
+                    ServerListEvent::Responded(_) => responded += 1,
+                    ServerListEvent::Failed(_) => failed += 1,
+                    ServerListEvent::Complete(response) => steam_log!(
+                        Debug,
+                        "api",
+                        "request_server_list({:?}) completed in {:?}: {:?}, {} responded, {} failed",
+                        kind,
+                        start.elapsed(),
+                        response,
+                        responded,
+                        failed
+                    ),
+                }
+                handler(event);
+            }),
+        )
+    }
+
+    fn ping_server(
+        &self,
+        address: SocketAddrV4,
+        callback: ApiCallback<GameServerItem>,
+    ) -> CallbackGuard {
+        steam_log!(Trace, "api", "ping_server({})", address);
+        self.inner
+            .ping_server(address, completion("ping_server", callback))
+    }
+
+    fn server_players(
+        &self,
+        address: SocketAddrV4,
+        callback: ApiCallback<Vec<ServerPlayer>>,
+    ) -> CallbackGuard {
+        steam_log!(Trace, "api", "server_players({})", address);
+        self.inner
+            .server_players(address, completion("server_players", callback))
+    }
+
+    fn server_rules(
+        &self,
+        address: SocketAddrV4,
+        callback: ApiCallback<Vec<(String, String)>>,
+    ) -> CallbackGuard {
+        steam_log!(Trace, "api", "server_rules({})", address);
+        self.inner
+            .server_rules(address, completion("server_rules", callback))
+    }
+
+    fn send_p2p_packet(&self, steam_id: SteamId, send_type: SendType, data: &[u8]) -> bool {
+        steam_log!(
+            Trace,
+            "api",
+            "send_p2p_packet({:?}, {} bytes)",
+            steam_id,
+            data.len()
+        );
+        self.inner.send_p2p_packet(steam_id, send_type, data)
+    }
+
+    fn read_p2p_packet(&self, buffer: &mut [u8]) -> Option<(SteamId, usize)> {
+        steam_log!(Trace, "api", "read_p2p_packet({} bytes)", buffer.len());
+        self.inner.read_p2p_packet(buffer)
+    }
+
+    fn file_write(&self, name: &str, content: &[u8]) -> bool {
+        steam_log!(
+            Trace,
+            "api",
+            "file_write({:?}, {} bytes)",
+            name,
+            content.len()
+        );
+        self.inner.file_write(name, content)
+    }
+
+    fn create_item(&self, app_id: AppId, callback: ApiCallback<(PublishedFileId, bool)>) {
+        steam_log!(Trace, "api", "create_item({:?})", app_id);
+        self.inner
+            .create_item(app_id, completion("create_item", callback));
+    }
+
+    fn submit_item_update(
+        &self,
+        app_id: AppId,
+        item_id: PublishedFileId,
+        update: ItemUpdate,
+        callback: ApiCallback<(PublishedFileId, bool)>,
+    ) -> Box<dyn UpdateWatch> {
+        steam_log!(
+            Trace,
+            "api",
+            "submit_item_update({:?}, {:?})",
+            app_id,
+            item_id
+        );
+        self.inner.submit_item_update(
+            app_id,
+            item_id,
+            update,
+            completion("submit_item_update", callback),
+        )
+    }
+
+    fn subscribe_item(&self, item_id: PublishedFileId, callback: ApiCallback<()>) {
+        steam_log!(Trace, "api", "subscribe_item({:?})", item_id);
+        self.inner
+            .subscribe_item(item_id, completion("subscribe_item", callback));
+    }
+
+    fn unsubscribe_item(&self, item_id: PublishedFileId, callback: ApiCallback<()>) {
+        steam_log!(Trace, "api", "unsubscribe_item({:?})", item_id);
+        self.inner
+            .unsubscribe_item(item_id, completion("unsubscribe_item", callback));
+    }
+
+    fn query_items(
+        &self,
+        items: Vec<PublishedFileId>,
+        query: WorkshopQuery,
+        callback: ApiCallback<Vec<Option<WorkshopItemDetails>>>,
+    ) -> Result<(), SteamError> {
+        steam_log!(Trace, "api", "query_items({:?})", items);
+        let result = self
+            .inner
+            .query_items(items, query, completion("query_items", callback));
+        if let Err(error) = &result {
+            steam_log!(Warn, "api", "query_items failed: {}", error);
+        }
+        result
+    }
+
+    fn auth_session_ticket(
+        &self,
+        identity: TicketIdentity,
+        callback: ApiCallback<()>,
+    ) -> (TicketHandle, Vec<u8>, CallbackGuard) {
+        steam_log!(Trace, "api", "auth_session_ticket({:?})", identity);
+        self.inner
+            .auth_session_ticket(identity, completion("auth_session_ticket", callback))
+    }
+
+    fn auth_ticket_for_web_api(
+        &self,
+        identity: &str,
+        callback: ApiCallback<Vec<u8>>,
+    ) -> (TicketHandle, CallbackGuard) {
+        steam_log!(Trace, "api", "auth_ticket_for_web_api({:?})", identity);
+        self.inner
+            .auth_ticket_for_web_api(identity, completion("auth_ticket_for_web_api", callback))
+    }
+}
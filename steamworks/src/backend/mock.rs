@@ -0,0 +1,2256 @@
+//! 内存模拟 Steam 后端
+//!
+//! 用户、好友、大厅、创意工坊物品、云文件与统计全部保存在内存中，可以通过脚本预置，
+//! 并支持为指定操作注入错误与延迟。异步调用结果与回调同真实 Steam 一样，只在 `run_callbacks` 时分发。
+
+use super::{
+    ApiCallback, CallbackGuard, CallbackHandler, FavoriteGame, FriendInfo, GameServerItem,
+    ItemInstallInfo, ItemUpdate, ServerListEvent, ServerListHandler, ServerListKind,
+    ServerListResponse, ServerPlayer, SteamBackend, TicketHandle, TicketIdentity, UpdateWatch,
+    WorkshopItemDetails, WorkshopQuery,
+};
+use crate::api::callback::SteamApiCallback;
+use crate::api::consts::{CSteamID, EChatMemberStateChange};
+use crate::api::payloads::{
+    CallbackPayload, DownloadItemResultPayload, ItemInstalledPayload, LobbyChatUpdatePayload,
+    LobbyDataUpdatePayload, LobbyEnterPayload, UserStatsReceivedPayload, UserStatsStoredPayload,
+};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::net::SocketAddrV4;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use steamworks::{
+    AppId, FriendFlags, FriendGame, FriendState, ItemState, LobbyId, LobbyType, OverlayToStoreFlag,
+    PublishedFileId, SendType, SteamError, SteamId, UpdateStatus,
+};
+
+/// 模拟大厅 ID 起始值（聊天室类型的 Steam ID）
+const LOBBY_ID_BASE: u64 = 0x0186_0000_0000_0000;
+
+/// 模拟好友
+pub struct MockFriend {
+    pub steam_id: SteamId,
+    pub name: String,
+    pub state: FriendState,
+    pub flags: FriendFlags,
+    pub game: Option<FriendGame>,
+}
+
+/// 模拟大厅
+pub struct MockLobby {
+    pub owner: SteamId,
+    pub members: Vec<SteamId>,
+    pub member_limit: Option<usize>,
+    /// 是否会出现在大厅搜索结果中
+    pub listed: bool,
+    pub joinable: bool,
+    pub data: BTreeMap<String, String>,
+}
+
+impl MockLobby {
+    /// 成员数是否已达上限
+    pub fn is_full(&self) -> bool {
+        self.member_limit
+            .is_some_and(|limit| self.members.len() >= limit)
+    }
+}
+
+/// 模拟创意工坊物品
+pub struct MockItem {
+    pub details: WorkshopItemDetails,
+    pub state: ItemState,
+    pub install: Option<ItemInstallInfo>,
+}
+
+/// 服务器列表请求的类型、App ID 与筛选条件
+pub type ServerListRequest = (ServerListKind, AppId, Vec<(String, String)>);
+
+/// 模拟服务器浏览器中的服务器
+pub struct MockServer {
+    pub item: GameServerItem,
+    /// 出现在哪些服务器列表中
+    pub lists: HashSet<ServerListKind>,
+    pub players: Vec<ServerPlayer>,
+    pub rules: Vec<(String, String)>,
+    /// 为 false 时服务器出现在列表中但不回复查询
+    pub responds: bool,
+}
+
+impl MockServer {
+    /// 是否满足服务器列表的筛选条件，只支持常用的筛选键，其他键被忽略
+    fn matches(&self, app_id: AppId, filters: &[(String, String)]) -> bool {
+        let item = &self.item;
+        let has_tags = |value: &str| {
+            let tags: HashSet<&str> = item.game_tags.split(',').map(str::trim).collect();
+            value
+                .split(',')
+                .map(str::trim)
+                .all(|tag| tags.contains(tag))
+        };
+
+        item.app_id == app_id
+            && filters.iter().all(|(key, value)| match key.as_str() {
+                "gamedir" => item.game_dir.eq_ignore_ascii_case(value),
+                "map" => item.map.eq_ignore_ascii_case(value),
+                "secure" => item.secure,
+                "notfull" => item.players < item.max_players,
+                "hasplayers" | "empty" => item.players > 0,
+                "noplayers" => item.players == 0,
+                "gametagsand" => has_tags(value),
+                "gameaddr" => {
+                    item.address.to_string() == *value || item.address.ip().to_string() == *value
+                }
+                _ => true,
+            })
+    }
+}
+
+/// 注入的错误
+struct MockFault {
+    error: SteamError,
+    /// 剩余触发次数，为空时一直生效
+    remaining: Option<u32>,
+}
+
+/// 模拟 Steam 的全部可脚本化状态
+pub struct MockState {
+    pub steam_id: SteamId,
+    pub name: String,
+    pub level: u32,
+    pub app_id: AppId,
+    pub ip_country: String,
+    /// 固定的服务器时间，为空时使用系统时间
+    pub server_real_time: Option<u32>,
+    pub steam_deck: bool,
+
+    pub subscribed_apps: HashSet<AppId>,
+    pub installed_apps: HashSet<AppId>,
+    pub installed_dlcs: HashSet<AppId>,
+    pub free_weekend: bool,
+    pub vac_banned: bool,
+    pub low_violence: bool,
+    pub available_languages: Vec<String>,
+    pub current_language: String,
+    pub beta_name: Option<String>,
+
+    pub rich_presence: HashMap<String, String>,
+    pub friends: Vec<MockFriend>,
+
+    pub lobbies: BTreeMap<u64, MockLobby>,
+    next_lobby: u64,
+
+    pub items: BTreeMap<u64, MockItem>,
+    next_item: u64,
+
+    pub cloud_enabled_for_account: bool,
+    pub cloud_enabled_for_app: bool,
+    pub files: HashMap<String, Vec<u8>>,
+
+    pub stats: HashMap<String, i32>,
+    pub achievements: HashMap<String, bool>,
+
+    /// 等待读取的 P2P 数据包
+    pub incoming_packets: VecDeque<(SteamId, Vec<u8>)>,
+    /// 已发送的 P2P 数据包
+    pub sent_packets: Vec<(SteamId, Vec<u8>)>,
+    pub accepted_sessions: HashSet<SteamId>,
+
+    pub controllers: Vec<u64>,
+    pub servers: Vec<MockServer>,
+    /// 已发起的服务器列表请求
+    pub server_list_requests: Vec<ServerListRequest>,
+    /// Steam 收藏夹与历史记录
+    pub favorites: Vec<FavoriteGame>,
+    next_ticket: TicketHandle,
+
+    faults: HashMap<String, MockFault>,
+    pub latency: Duration,
+}
+
+impl Default for MockState {
+    fn default() -> Self {
+        let app_id = AppId(480);
+        Self {
+            steam_id: SteamId::from_raw(76561197960287930),
+            name: "Mock User".to_string(),
+            level: 1,
+            app_id,
+            ip_country: "US".to_string(),
+            server_real_time: None,
+            steam_deck: false,
+            subscribed_apps: HashSet::from([app_id]),
+            installed_apps: HashSet::from([app_id]),
+            installed_dlcs: HashSet::new(),
+            free_weekend: false,
+            vac_banned: false,
+            low_violence: false,
+            available_languages: vec!["english".to_string()],
+            current_language: "english".to_string(),
+            beta_name: None,
+            rich_presence: HashMap::new(),
+            friends: Vec::new(),
+            lobbies: BTreeMap::new(),
+            next_lobby: 1,
+            items: BTreeMap::new(),
+            next_item: 1,
+            cloud_enabled_for_account: true,
+            cloud_enabled_for_app: true,
+            files: HashMap::new(),
+            stats: HashMap::new(),
+            achievements: HashMap::new(),
+            incoming_packets: VecDeque::new(),
+            sent_packets: Vec::new(),
+            accepted_sessions: HashSet::new(),
+            controllers: Vec::new(),
+            servers: Vec::new(),
+            server_list_requests: Vec::new(),
+            favorites: Vec::new(),
+            next_ticket: 1,
+            faults: HashMap::new(),
+            latency: Duration::ZERO,
+        }
+    }
+}
+
+impl MockState {
+    /// 为指定操作注入错误
+    ///
+    /// @param operation 后端方法名，如 `create_lobby`、`file_write`
+    /// @param times 触发次数，为空时一直生效
+    pub fn inject_fault(&mut self, operation: &str, error: SteamError, times: Option<u32>) {
+        self.faults.insert(
+            operation.to_string(),
+            MockFault {
+                error,
+                remaining: times,
+            },
+        );
+    }
+
+    pub fn clear_faults(&mut self) {
+        self.faults.clear();
+    }
+
+    fn take_fault(&mut self, operation: &str) -> Option<SteamError> {
+        let fault = self.faults.get_mut(operation)?;
+        let error = fault.error;
+
+        if let Some(remaining) = fault.remaining.as_mut() {
+            *remaining = remaining.saturating_sub(1);
+            if *remaining == 0 {
+                self.faults.remove(operation);
+            }
+        }
+
+        Some(error)
+    }
+
+    /// 添加大厅并返回大厅 ID
+    pub fn add_lobby(&mut self, lobby: MockLobby) -> LobbyId {
+        let lobby_id = LOBBY_ID_BASE + self.next_lobby;
+        self.next_lobby += 1;
+        self.lobbies.insert(lobby_id, lobby);
+        LobbyId::from_raw(lobby_id)
+    }
+
+    /// 当前用户加入大厅，已在大厅中时直接成功
+    pub fn join_lobby(&mut self, lobby_id: LobbyId) -> Result<LobbyId, SteamError> {
+        let steam_id = self.steam_id;
+        let lobby = self
+            .lobbies
+            .get_mut(&lobby_id.raw())
+            .ok_or(SteamError::NoMatch)?;
+
+        if !lobby.members.contains(&steam_id) {
+            if !lobby.joinable {
+                return Err(SteamError::AccessDenied);
+            }
+            if lobby.is_full() {
+                return Err(SteamError::LimitExceeded);
+            }
+            lobby.members.push(steam_id);
+        }
+
+        Ok(lobby_id)
+    }
+
+    /// 添加创意工坊物品，物品 ID 为 0 时自动分配
+    pub fn add_item(&mut self, mut item: MockItem) -> PublishedFileId {
+        if item.details.published_file_id.0 == 0 {
+            item.details.published_file_id = PublishedFileId(self.next_item);
+        }
+        let item_id = item.details.published_file_id;
+        self.next_item = self.next_item.max(item_id.0 + 1);
+        self.items.insert(item_id.0, item);
+        item_id
+    }
+
+    /// 生成一个属于当前用户的空白创意工坊物品
+    pub fn blank_item(&self, item_id: PublishedFileId) -> MockItem {
+        MockItem {
+            details: WorkshopItemDetails {
+                published_file_id: item_id,
+                creator_app_id: Some(self.app_id),
+                consumer_app_id: Some(self.app_id),
+                title: String::new(),
+                description: String::new(),
+                owner: self.steam_id,
+                time_created: self.now(),
+                time_updated: self.now(),
+                banned: false,
+                accepted_for_use: false,
+                tags: Vec::new(),
+                tags_truncated: false,
+                url: String::new(),
+                num_upvotes: 0,
+                num_downvotes: 0,
+                num_children: 0,
+                preview_url: None,
+            },
+            state: ItemState::NONE,
+            install: None,
+        }
+    }
+
+    fn now(&self) -> u32 {
+        self.server_real_time.unwrap_or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs() as u32)
+                .unwrap_or_default()
+        })
+    }
+}
+
+/// 等待分发的任务
+struct Pending {
+    due: Instant,
+    task: Box<dyn FnOnce() + Send>,
+}
+
+fn push(queue: &Mutex<Vec<Pending>>, due: Instant, task: impl FnOnce() + Send + 'static) {
+    queue
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .push(Pending {
+            due,
+            task: Box::new(task),
+        });
+}
+
+type Handlers = Vec<(u64, SteamApiCallback, Arc<CallbackHandler>)>;
+
+/// 内存模拟 Steam 后端
+#[derive(Default)]
+pub struct MockBackend {
+    state: Mutex<MockState>,
+    queue: Arc<Mutex<Vec<Pending>>>,
+    handlers: Arc<Mutex<Handlers>>,
+    next_handler: Mutex<u64>,
+    /// 保证同一时间只有一个线程在分发回调
+    dispatch: Mutex<()>,
+}
+
+impl MockBackend {
+    pub fn new(state: MockState) -> Self {
+        Self {
+            state: Mutex::new(state),
+            ..Default::default()
+        }
+    }
+
+    /// 访问模拟状态
+    pub fn state(&self) -> MutexGuard<'_, MockState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// 在下一次 `run_callbacks` 时分发一个回调事件
+    pub fn emit(&self, payload: impl Into<CallbackPayload>) {
+        let payload = payload.into();
+        let kind = payload.kind();
+        let handlers = self.handlers.clone();
+        self.schedule(move || {
+            let handlers: Vec<_> = handlers
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .iter()
+                .filter(|(_, handler_kind, _)| *handler_kind == kind)
+                .map(|(_, _, handler)| handler.clone())
+                .collect();
+
+            for handler in handlers {
+                handler(payload.clone());
+            }
+        });
+    }
+
+    fn schedule(&self, task: impl FnOnce() + Send + 'static) {
+        let due = Instant::now() + self.state().latency;
+        push(&self.queue, due, task);
+    }
+
+    /// 之后每次 `run_callbacks` 分发一个事件，与真实 Steam 陆续收到回复一样
+    fn schedule_each<T: Send + 'static>(
+        &self,
+        events: VecDeque<T>,
+        handler: impl FnMut(T) + Send + 'static,
+    ) {
+        fn next<T: Send + 'static>(
+            queue: Arc<Mutex<Vec<Pending>>>,
+            due: Instant,
+            mut events: VecDeque<T>,
+            mut handler: impl FnMut(T) + Send + 'static,
+        ) {
+            let target = queue.clone();
+            push(&target, due, move || {
+                if let Some(event) = events.pop_front() {
+                    handler(event);
+                    next(queue, Instant::now(), events, handler);
+                }
+            });
+        }
+
+        let due = Instant::now() + self.state().latency;
+        next(self.queue.clone(), due, events, handler);
+    }
+
+    /// 模拟调用结果，若该操作被注入了错误则返回错误
+    fn complete<T: Send + 'static>(
+        &self,
+        operation: &str,
+        callback: ApiCallback<T>,
+        result: impl FnOnce(&mut MockState) -> Result<T, SteamError>,
+    ) {
+        let result = {
+            let mut state = self.state();
+            match state.take_fault(operation) {
+                Some(error) => Err(error),
+                None => result(&mut state),
+            }
+        };
+        self.schedule(move || callback(result));
+    }
+
+    /// 分发 LobbyEnter 回调，失败原因转换为对应的 EChatRoomEnterResponse
+    fn emit_lobby_enter(&self, lobby_id: LobbyId, result: Result<LobbyId, SteamError>) {
+        let response = match result {
+            Ok(_) => 1,
+            Err(SteamError::NoMatch) => 2,
+            Err(SteamError::AccessDenied) => 3,
+            Err(SteamError::LimitExceeded) => 4,
+            Err(SteamError::Banned) => 6,
+            Err(SteamError::Blocked) => 10,
+            Err(SteamError::RateLimitExceeded) => 15,
+            Err(_) => 5,
+        };
+
+        self.emit(LobbyEnterPayload {
+            lobby: lobby_id.raw().into(),
+            chat_permissions: 0,
+            locked: false,
+            chat_room_enter_response: response,
+        });
+    }
+
+    /// 查询指定地址的模拟服务器，服务器不存在或不回复时返回超时
+    fn query_server<T: Send + 'static>(
+        &self,
+        operation: &str,
+        address: SocketAddrV4,
+        callback: ApiCallback<T>,
+        result: impl FnOnce(&MockServer) -> T,
+    ) -> CallbackGuard {
+        let (callback, guard) = cancellable(callback);
+        self.complete(operation, callback, |state| {
+            state
+                .servers
+                .iter()
+                .find(|server| server.item.address == address && server.responds)
+                .map(result)
+                .ok_or(SteamError::Timeout)
+        });
+        guard
+    }
+
+    /// 同步操作是否被注入了错误
+    fn fails(&self, operation: &str) -> bool {
+        self.state().take_fault(operation).is_some()
+    }
+}
+
+/// 模拟的创意工坊更新进度，提交后立即完成
+struct MockUpdateWatch;
+
+impl UpdateWatch for MockUpdateWatch {
+    fn progress(&self) -> (UpdateStatus, u64, u64) {
+        (UpdateStatus::Invalid, 0, 0)
+    }
+}
+
+impl SteamBackend for MockBackend {
+    fn run_callbacks(&self) {
+        let _dispatch = self.dispatch.lock().unwrap_or_else(PoisonError::into_inner);
+        let now = Instant::now();
+        let ready: Vec<Pending> = {
+            let mut queue = self.queue.lock().unwrap_or_else(PoisonError::into_inner);
+            let (ready, pending) = queue.drain(..).partition(|pending| pending.due <= now);
+            *queue = pending;
+            ready
+        };
+
+        for pending in ready {
+            (pending.task)();
+        }
+    }
+
+    fn register_callback(&self, kind: SteamApiCallback, handler: CallbackHandler) -> CallbackGuard {
+        let id = {
+            let mut next_handler = self
+                .next_handler
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner);
+            *next_handler += 1;
+            *next_handler
+        };
+        self.handlers
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push((id, kind, Arc::new(handler)));
+
+        let handlers = Arc::downgrade(&self.handlers);
+        CallbackGuard::new(move || {
+            if let Some(handlers) = handlers.upgrade() {
+                handlers
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .retain(|(handler_id, _, _)| *handler_id != id);
+            }
+        })
+    }
+
+    fn is_subscribed_app(&self, app_id: AppId) -> bool {
+        self.state().subscribed_apps.contains(&app_id)
+    }
+
+    fn is_dlc_installed(&self, app_id: AppId) -> bool {
+        self.state().installed_dlcs.contains(&app_id)
+    }
+
+    fn is_app_installed(&self, app_id: AppId) -> bool {
+        self.state().installed_apps.contains(&app_id)
+    }
+
+    fn is_subscribed_from_free_weekend(&self) -> bool {
+        self.state().free_weekend
+    }
+
+    fn is_vac_banned(&self) -> bool {
+        self.state().vac_banned
+    }
+
+    fn is_cybercafe(&self) -> bool {
+        false
+    }
+
+    fn is_low_violence(&self) -> bool {
+        self.state().low_violence
+    }
+
+    fn is_subscribed(&self) -> bool {
+        let state = self.state();
+        state.subscribed_apps.contains(&state.app_id)
+    }
+
+    fn app_owner(&self) -> SteamId {
+        self.state().steam_id
+    }
+
+    fn available_game_languages(&self) -> Vec<String> {
+        self.state().available_languages.clone()
+    }
+
+    fn current_beta_name(&self) -> Option<String> {
+        self.state().beta_name.clone()
+    }
+
+    fn app_install_dir(&self, app_id: AppId) -> String {
+        format!("steamapps/common/{}", app_id.0)
+    }
+
+    fn current_game_language(&self) -> String {
+        self.state().current_language.clone()
+    }
+
+    fn persona_name(&self) -> String {
+        self.state().name.clone()
+    }
+
+    fn set_rich_presence(&self, key: &str, value: Option<&str>) -> bool {
+        if self.fails("set_rich_presence") {
+            return false;
+        }
+
+        let mut state = self.state();
+        match value.filter(|value| !value.is_empty()) {
+            Some(value) => state
+                .rich_presence
+                .insert(key.to_string(), value.to_string()),
+            None => state.rich_presence.remove(key),
+        };
+        true
+    }
+
+    fn friend_game_played(&self, steam_id: SteamId) -> Option<FriendGame> {
+        self.state()
+            .friends
+            .iter()
+            .find(|friend| friend.steam_id == steam_id)
+            .and_then(|friend| friend.game.clone())
+    }
+
+    fn friends(&self, flags: FriendFlags) -> Vec<FriendInfo> {
+        self.state()
+            .friends
+            .iter()
+            .filter(|friend| friend.flags.intersects(flags))
+            .map(|friend| FriendInfo {
+                steam_id: friend.steam_id,
+                name: friend.name.clone(),
+                state: friend.state,
+                has_friend: true,
+                game: friend.game.clone(),
+            })
+            .collect()
+    }
+
+    fn activate_game_overlay(&self, _dialog: &str) {}
+
+    fn activate_game_overlay_to_user(&self, _dialog: &str, _steam_id: SteamId) {}
+
+    fn activate_invite_dialog(&self, _lobby_id: LobbyId) {}
+
+    fn activate_game_overlay_to_web_page(&self, _url: &str) {}
+
+    fn activate_game_overlay_to_store(&self, _app_id: AppId, _flag: OverlayToStoreFlag) {}
+
+    fn input_init(&self) {}
+
+    fn input_shutdown(&self) {}
+
+    fn connected_controllers(&self) -> Vec<u64> {
+        self.state().controllers.clone()
+    }
+
+    fn action_set_handle(&self, action_set_name: &str) -> u64 {
+        action_handle(action_set_name)
+    }
+
+    fn digital_action_handle(&self, action_name: &str) -> u64 {
+        action_handle(action_name)
+    }
+
+    fn analog_action_handle(&self, action_name: &str) -> u64 {
+        action_handle(action_name)
+    }
+
+    fn activate_action_set(&self, _controller: u64, _action_set_handle: u64) {}
+
+    fn digital_action_state(&self, _controller: u64, _action_handle: u64) -> bool {
+        false
+    }
+
+    fn analog_action_vector(&self, _controller: u64, _action_handle: u64) -> (f32, f32) {
+        (0.0, 0.0)
+    }
+
+    fn create_lobby(
+        &self,
+        lobby_type: LobbyType,
+        max_members: u32,
+        callback: ApiCallback<LobbyId>,
+    ) {
+        let result = {
+            let mut state = self.state();
+            match state.take_fault("create_lobby") {
+                Some(error) => Err(error),
+                None => {
+                    let owner = state.steam_id;
+                    Ok(state.add_lobby(MockLobby {
+                        owner,
+                        members: vec![owner],
+                        member_limit: Some(max_members as usize),
+                        listed: matches!(lobby_type, LobbyType::Public | LobbyType::Invisible),
+                        joinable: true,
+                        data: BTreeMap::new(),
+                    }))
+                }
+            }
+        };
+
+        if let Ok(lobby_id) = result {
+            self.emit_lobby_enter(lobby_id, Ok(lobby_id));
+        }
+        self.schedule(move || callback(result));
+    }
+
+    fn join_lobby(&self, lobby_id: LobbyId, callback: ApiCallback<LobbyId>) {
+        let (steam_id, result) = {
+            let mut state = self.state();
+            let steam_id = state.steam_id;
+            let result = match state.take_fault("join_lobby") {
+                Some(error) => Err(error),
+                None => state.join_lobby(lobby_id),
+            };
+            (steam_id, result)
+        };
+
+        self.emit_lobby_enter(lobby_id, result);
+        if result.is_ok() {
+            self.emit(LobbyChatUpdatePayload {
+                lobby: lobby_id.raw().into(),
+                user_changed: CSteamID::from_steamid(steam_id),
+                making_change: CSteamID::from_steamid(steam_id),
+                member_state_change: EChatMemberStateChange::Entered,
+            });
+        }
+        self.schedule(move || callback(result));
+    }
+
+    fn request_lobby_list(&self, callback: ApiCallback<Vec<LobbyId>>) {
+        self.complete("request_lobby_list", callback, |state| {
+            Ok(state
+                .lobbies
+                .iter()
+                .filter(|(_, lobby)| lobby.listed && lobby.joinable && !lobby.is_full())
+                .map(|(lobby_id, _)| LobbyId::from_raw(*lobby_id))
+                .collect())
+        });
+    }
+
+    fn leave_lobby(&self, lobby_id: LobbyId) {
+        let steam_id = {
+            let mut state = self.state();
+            let steam_id = state.steam_id;
+            let Some(lobby) = state.lobbies.get_mut(&lobby_id.raw()) else {
+                return;
+            };
+
+            lobby.members.retain(|member| *member != steam_id);
+            match lobby.members.first() {
+                Some(next_owner) if lobby.owner == steam_id => lobby.owner = *next_owner,
+                Some(_) => {}
+                None => {
+                    state.lobbies.remove(&lobby_id.raw());
+                }
+            }
+            steam_id
+        };
+
+        self.emit(LobbyChatUpdatePayload {
+            lobby: lobby_id.raw().into(),
+            user_changed: CSteamID::from_steamid(steam_id),
+            making_change: CSteamID::from_steamid(steam_id),
+            member_state_change: EChatMemberStateChange::Left,
+        });
+    }
+
+    fn lobby_member_count(&self, lobby_id: LobbyId) -> usize {
+        self.state()
+            .lobbies
+            .get(&lobby_id.raw())
+            .map_or(0, |lobby| lobby.members.len())
+    }
+
+    fn lobby_member_limit(&self, lobby_id: LobbyId) -> Option<usize> {
+        self.state()
+            .lobbies
+            .get(&lobby_id.raw())
+            .and_then(|lobby| lobby.member_limit)
+    }
+
+    fn lobby_members(&self, lobby_id: LobbyId) -> Vec<SteamId> {
+        self.state()
+            .lobbies
+            .get(&lobby_id.raw())
+            .map(|lobby| lobby.members.clone())
+            .unwrap_or_default()
+    }
+
+    fn lobby_owner(&self, lobby_id: LobbyId) -> SteamId {
+        self.state()
+            .lobbies
+            .get(&lobby_id.raw())
+            .map_or(SteamId::from_raw(0), |lobby| lobby.owner)
+    }
+
+    fn set_lobby_joinable(&self, lobby_id: LobbyId, joinable: bool) -> bool {
+        let mut state = self.state();
+        let steam_id = state.steam_id;
+        match state.lobbies.get_mut(&lobby_id.raw()) {
+            Some(lobby) if lobby.owner == steam_id => {
+                lobby.joinable = joinable;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn lobby_data(&self, lobby_id: LobbyId, key: &str) -> Option<String> {
+        self.state()
+            .lobbies
+            .get(&lobby_id.raw())
+            .and_then(|lobby| lobby.data.get(key).cloned())
+    }
+
+    fn lobby_data_count(&self, lobby_id: LobbyId) -> u32 {
+        self.state()
+            .lobbies
+            .get(&lobby_id.raw())
+            .map_or(0, |lobby| lobby.data.len() as u32)
+    }
+
+    fn lobby_data_by_index(&self, lobby_id: LobbyId, index: u32) -> Option<(String, String)> {
+        self.state()
+            .lobbies
+            .get(&lobby_id.raw())
+            .and_then(|lobby| lobby.data.iter().nth(index as usize))
+            .map(|(key, value)| (key.clone(), value.clone()))
+    }
+
+    fn set_lobby_data(&self, lobby_id: LobbyId, key: &str, value: &str) -> bool {
+        if self.fails("set_lobby_data") {
+            return false;
+        }
+
+        let steam_id = {
+            let mut state = self.state();
+            let steam_id = state.steam_id;
+            match state.lobbies.get_mut(&lobby_id.raw()) {
+                Some(lobby) if lobby.owner == steam_id => {
+                    lobby.data.insert(key.to_string(), value.to_string());
+                }
+                _ => return false,
+            }
+            steam_id
+        };
+
+        self.emit(LobbyDataUpdatePayload {
+            lobby: lobby_id.raw().into(),
+            member: steam_id.raw().into(),
+            success: true,
+        });
+        true
+    }
+
+    fn delete_lobby_data(&self, lobby_id: LobbyId, key: &str) -> bool {
+        let mut state = self.state();
+        let steam_id = state.steam_id;
+        match state.lobbies.get_mut(&lobby_id.raw()) {
+            Some(lobby) if lobby.owner == steam_id => lobby.data.remove(key).is_some(),
+            _ => false,
+        }
+    }
+
+    fn favorite_games(&self) -> Vec<FavoriteGame> {
+        self.state().favorites.clone()
+    }
+
+    fn add_favorite_game(&self, game: &FavoriteGame) -> Option<u32> {
+        if self.fails("add_favorite_game") {
+            return None;
+        }
+
+        let mut state = self.state();
+        let index = match state
+            .favorites
+            .iter()
+            .position(|favorite| favorite.same_entry(game))
+        {
+            Some(index) => {
+                state.favorites[index].last_played = game.last_played;
+                index
+            }
+            None => {
+                state.favorites.push(game.clone());
+                state.favorites.len() - 1
+            }
+        };
+        Some(index as u32)
+    }
+
+    fn remove_favorite_game(&self, game: &FavoriteGame) -> bool {
+        let mut state = self.state();
+        let count = state.favorites.len();
+        state
+            .favorites
+            .retain(|favorite| !favorite.same_entry(game));
+        state.favorites.len() != count
+    }
+
+    fn request_server_list(
+        &self,
+        kind: ServerListKind,
+        app_id: AppId,
+        filters: Vec<(String, String)>,
+        handler: ServerListHandler,
+    ) -> CallbackGuard {
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let guard = {
+            let cancelled = cancelled.clone();
+            CallbackGuard::new(move || cancelled.store(true, Ordering::SeqCst))
+        };
+
+        let mut state = self.state();
+        state
+            .server_list_requests
+            .push((kind, app_id, filters.clone()));
+        // 局域网列表不支持筛选
+        let filters = if kind == ServerListKind::Lan {
+            Vec::new()
+        } else {
+            filters
+        };
+
+        let events = if state.take_fault("request_server_list").is_some() {
+            VecDeque::from([ServerListEvent::Complete(
+                ServerListResponse::ServerFailedToRespond,
+            )])
+        } else {
+            let mut events: VecDeque<ServerListEvent> = state
+                .servers
+                .iter()
+                .filter(|server| server.lists.contains(&kind) && server.matches(app_id, &filters))
+                .map(|server| {
+                    if server.responds {
+                        ServerListEvent::Responded(server.item.clone())
+                    } else {
+                        ServerListEvent::Failed(GameServerItem::unanswered(server.item.address))
+                    }
+                })
+                .collect();
+
+            let response = if events.is_empty() {
+                ServerListResponse::NoServersListedOnMasterServer
+            } else if events
+                .iter()
+                .any(|event| matches!(event, ServerListEvent::Responded(_)))
+            {
+                ServerListResponse::ServerResponded
+            } else {
+                ServerListResponse::ServerFailedToRespond
+            };
+            events.push_back(ServerListEvent::Complete(response));
+            events
+        };
+        drop(state);
+
+        let mut handler = handler;
+        self.schedule_each(events, move |event| {
+            if !cancelled.load(Ordering::SeqCst) {
+                handler(event);
+            }
+        });
+        guard
+    }
+
+    fn ping_server(
+        &self,
+        address: SocketAddrV4,
+        callback: ApiCallback<GameServerItem>,
+    ) -> CallbackGuard {
+        self.query_server("ping_server", address, callback, |server| {
+            server.item.clone()
+        })
+    }
+
+    fn server_players(
+        &self,
+        address: SocketAddrV4,
+        callback: ApiCallback<Vec<ServerPlayer>>,
+    ) -> CallbackGuard {
+        self.query_server("server_players", address, callback, |server| {
+            server.players.clone()
+        })
+    }
+
+    fn server_rules(
+        &self,
+        address: SocketAddrV4,
+        callback: ApiCallback<Vec<(String, String)>>,
+    ) -> CallbackGuard {
+        self.query_server("server_rules", address, callback, |server| {
+            server.rules.clone()
+        })
+    }
+
+    fn send_p2p_packet(&self, steam_id: SteamId, _send_type: SendType, data: &[u8]) -> bool {
+        if self.fails("send_p2p_packet") {
+            return false;
+        }
+
+        self.state().sent_packets.push((steam_id, data.to_vec()));
+        true
+    }
+
+    fn is_p2p_packet_available(&self) -> Option<usize> {
+        self.state()
+            .incoming_packets
+            .front()
+            .map(|(_, data)| data.len())
+    }
+
+    fn read_p2p_packet(&self, buffer: &mut [u8]) -> Option<(SteamId, usize)> {
+        let (steam_id, data) = self.state().incoming_packets.pop_front()?;
+        let size = data.len().min(buffer.len());
+        buffer[..size].copy_from_slice(&data[..size]);
+        Some((steam_id, size))
+    }
+
+    fn accept_p2p_session(&self, steam_id: SteamId) {
+        self.state().accepted_sessions.insert(steam_id);
+    }
+
+    fn is_cloud_enabled_for_account(&self) -> bool {
+        self.state().cloud_enabled_for_account
+    }
+
+    fn is_cloud_enabled_for_app(&self) -> bool {
+        self.state().cloud_enabled_for_app
+    }
+
+    fn file_read(&self, name: &str) -> std::io::Result<Vec<u8>> {
+        let mut state = self.state();
+        if let Some(error) = state.take_fault("file_read") {
+            return Err(std::io::Error::other(error.to_string()));
+        }
+
+        state
+            .files
+            .get(name)
+            .cloned()
+            .ok_or_else(|| std::io::ErrorKind::NotFound.into())
+    }
+
+    fn file_write(&self, name: &str, content: &[u8]) -> bool {
+        if self.fails("file_write") {
+            return false;
+        }
+
+        self.state()
+            .files
+            .insert(name.to_string(), content.to_vec());
+        true
+    }
+
+    fn file_delete(&self, name: &str) -> bool {
+        if self.fails("file_delete") {
+            return false;
+        }
+
+        self.state().files.remove(name).is_some()
+    }
+
+    fn file_exists(&self, name: &str) -> bool {
+        self.state().files.contains_key(name)
+    }
+
+    fn item_state(&self, item_id: PublishedFileId) -> ItemState {
+        self.state()
+            .items
+            .get(&item_id.0)
+            .map_or(ItemState::NONE, |item| item.state)
+    }
+
+    fn create_item(&self, app_id: AppId, callback: ApiCallback<(PublishedFileId, bool)>) {
+        self.complete("create_item", callback, |state| {
+            let mut item = state.blank_item(PublishedFileId(0));
+            item.details.consumer_app_id = Some(app_id);
+            Ok((state.add_item(item), false))
+        });
+    }
+
+    fn submit_item_update(
+        &self,
+        _app_id: AppId,
+        item_id: PublishedFileId,
+        update: ItemUpdate,
+        callback: ApiCallback<(PublishedFileId, bool)>,
+    ) -> Box<dyn UpdateWatch> {
+        self.complete("submit_item_update", callback, |state| {
+            let now = state.now();
+            let item = state
+                .items
+                .get_mut(&item_id.0)
+                .ok_or(SteamError::FileNotFound)?;
+
+            if let Some(title) = update.title {
+                item.details.title = title;
+            }
+
+            if let Some(description) = update.description {
+                item.details.description = description;
+            }
+
+            if let Some(tags) = update.tags {
+                item.details.tags = tags;
+            }
+
+            if let Some(preview_path) = update.preview_path {
+                item.details.preview_url = Some(preview_path);
+            }
+
+            item.details.time_updated = now;
+            Ok((item_id, false))
+        });
+
+        Box::new(MockUpdateWatch)
+    }
+
+    fn subscribe_item(&self, item_id: PublishedFileId, callback: ApiCallback<()>) {
+        self.complete("subscribe_item", callback, |state| {
+            let item = state
+                .items
+                .get_mut(&item_id.0)
+                .ok_or(SteamError::FileNotFound)?;
+
+            item.state |= ItemState::SUBSCRIBED;
+            if item.install.is_some() {
+                item.state |= ItemState::INSTALLED;
+            }
+            Ok(())
+        });
+    }
+
+    fn unsubscribe_item(&self, item_id: PublishedFileId, callback: ApiCallback<()>) {
+        self.complete("unsubscribe_item", callback, |state| {
+            let item = state
+                .items
+                .get_mut(&item_id.0)
+                .ok_or(SteamError::FileNotFound)?;
+
+            item.state.remove(ItemState::SUBSCRIBED);
+            Ok(())
+        });
+    }
+
+    fn download_item(&self, item_id: PublishedFileId, _high_priority: bool) -> bool {
+        if self.fails("download_item") {
+            return false;
+        }
+
+        let (app_id, exists) = {
+            let state = self.state();
+            (state.app_id, state.items.contains_key(&item_id.0))
+        };
+        if exists {
+            self.emit(DownloadItemResultPayload {
+                app_id: app_id.0,
+                published_file_id: item_id.0.into(),
+                error: None,
+            });
+            self.emit(ItemInstalledPayload {
+                app_id: app_id.0,
+                published_file_id: item_id.0.into(),
+            });
+        }
+        exists
+    }
+
+    fn subscribed_items(&self) -> Vec<PublishedFileId> {
+        self.state()
+            .items
+            .values()
+            .filter(|item| item.state.contains(ItemState::SUBSCRIBED))
+            .map(|item| item.details.published_file_id)
+            .collect()
+    }
+
+    fn query_items(
+        &self,
+        items: Vec<PublishedFileId>,
+        _query: WorkshopQuery,
+        callback: ApiCallback<Vec<Option<WorkshopItemDetails>>>,
+    ) -> Result<(), SteamError> {
+        if items.is_empty() || items.len() > 1000 {
+            return Err(SteamError::InvalidParameter);
+        }
+
+        self.complete("query_items", callback, |state| {
+            Ok(items
+                .iter()
+                .map(|item_id| state.items.get(&item_id.0).map(|item| item.details.clone()))
+                .collect())
+        });
+        Ok(())
+    }
+
+    fn item_install_info(&self, item_id: PublishedFileId) -> Option<ItemInstallInfo> {
+        self.state()
+            .items
+            .get(&item_id.0)
+            .filter(|item| item.state.contains(ItemState::INSTALLED))
+            .and_then(|item| item.install.clone())
+    }
+
+    fn item_download_info(&self, item_id: PublishedFileId) -> Option<(u64, u64)> {
+        self.state()
+            .items
+            .get(&item_id.0)
+            .and_then(|item| item.install.as_ref())
+            .map(|install| (install.size_on_disk, install.size_on_disk))
+    }
+
+    fn steam_id(&self) -> SteamId {
+        self.state().steam_id
+    }
+
+    fn level(&self) -> u32 {
+        self.state().level
+    }
+
+    fn auth_session_ticket(
+        &self,
+        _identity: TicketIdentity,
+        callback: ApiCallback<()>,
+    ) -> (TicketHandle, Vec<u8>, CallbackGuard) {
+        let (ticket, data) = {
+            let mut state = self.state();
+            let ticket = state.next_ticket;
+            state.next_ticket += 1;
+            (ticket, mock_ticket(state.steam_id, ticket))
+        };
+
+        let (callback, guard) = cancellable(callback);
+        self.complete("auth_session_ticket", callback, |_| Ok(()));
+        (ticket, data, guard)
+    }
+
+    fn auth_ticket_for_web_api(
+        &self,
+        _identity: &str,
+        callback: ApiCallback<Vec<u8>>,
+    ) -> (TicketHandle, CallbackGuard) {
+        let ticket = {
+            let mut state = self.state();
+            let ticket = state.next_ticket;
+            state.next_ticket += 1;
+            ticket
+        };
+
+        let (callback, guard) = cancellable(callback);
+        self.complete("auth_ticket_for_web_api", callback, move |state| {
+            Ok(mock_ticket(state.steam_id, ticket))
+        });
+        (ticket, guard)
+    }
+
+    fn cancel_auth_ticket(&self, _ticket: TicketHandle) {}
+
+    fn request_current_stats(&self) {
+        let (steam_id, app_id) = {
+            let state = self.state();
+            (state.steam_id, state.app_id)
+        };
+        self.emit(UserStatsReceivedPayload {
+            steam_id: CSteamID::from_steamid(steam_id),
+            game_id: u64::from(app_id.0).into(),
+            error: None,
+        });
+    }
+
+    fn stat_i32(&self, name: &str) -> Option<i32> {
+        self.state().stats.get(name).copied()
+    }
+
+    fn set_stat_i32(&self, name: &str, value: i32) -> bool {
+        if self.fails("set_stat_i32") {
+            return false;
+        }
+
+        match self.state().stats.get_mut(name) {
+            Some(stat) => {
+                *stat = value;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn achievement(&self, name: &str) -> Option<bool> {
+        self.state().achievements.get(name).copied()
+    }
+
+    fn set_achievement(&self, name: &str) -> bool {
+        match self.state().achievements.get_mut(name) {
+            Some(achieved) => {
+                *achieved = true;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn clear_achievement(&self, name: &str) -> bool {
+        match self.state().achievements.get_mut(name) {
+            Some(achieved) => {
+                *achieved = false;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn store_stats(&self) -> bool {
+        if self.fails("store_stats") {
+            return false;
+        }
+
+        let app_id = self.state().app_id;
+        self.emit(UserStatsStoredPayload {
+            game_id: u64::from(app_id.0).into(),
+            error: None,
+        });
+        true
+    }
+
+    fn reset_all_stats(&self, achievements_too: bool) -> bool {
+        if self.fails("reset_all_stats") {
+            return false;
+        }
+
+        let mut state = self.state();
+        state.stats.values_mut().for_each(|stat| *stat = 0);
+        if achievements_too {
+            state
+                .achievements
+                .values_mut()
+                .for_each(|achieved| *achieved = false);
+        }
+        true
+    }
+
+    fn app_id(&self) -> AppId {
+        self.state().app_id
+    }
+
+    fn ip_country(&self) -> String {
+        self.state().ip_country.clone()
+    }
+
+    fn server_real_time(&self) -> u32 {
+        self.state().now()
+    }
+
+    fn is_steam_running_on_steam_deck(&self) -> bool {
+        self.state().steam_deck
+    }
+}
+
+/// 由名称生成稳定的输入句柄
+fn action_handle(name: &str) -> u64 {
+    name.bytes()
+        .fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+        })
+        .max(1)
+}
+
+fn mock_ticket(steam_id: SteamId, ticket: TicketHandle) -> Vec<u8> {
+    [
+        steam_id.raw().to_le_bytes(),
+        u64::from(ticket).to_le_bytes(),
+    ]
+    .concat()
+}
+
+/// 包装调用结果回调，句柄注销后不再分发结果
+fn cancellable<T: Send + 'static>(callback: ApiCallback<T>) -> (ApiCallback<T>, CallbackGuard) {
+    let cancelled = Arc::new(AtomicBool::new(false));
+    let guard = {
+        let cancelled = cancelled.clone();
+        CallbackGuard::new(move || cancelled.store(true, Ordering::SeqCst))
+    };
+
+    let callback: ApiCallback<T> = Box::new(move |result| {
+        if !cancelled.load(Ordering::SeqCst) {
+            callback(result)
+        }
+    });
+    (callback, guard)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::consts::ELobbyType;
+    use crate::api::steammatchmaking::steammatchmaking;
+    use crate::api::steamremotestorage::steamremotestorage;
+    use crate::api::steamugc::steamugc;
+    use napi::bindgen_prelude::{BigInt, Either};
+    use std::future::Future;
+    use std::sync::PoisonError;
+
+    lazy_static! {
+        /// 测试共用全局的 Steam 客户端，需要依次执行
+        static ref CLIENT_LOCK: Mutex<()> = Mutex::new(());
+    }
+
+    fn lock() -> MutexGuard<'static, ()> {
+        CLIENT_LOCK.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// 把模拟后端设为当前客户端，返回的后端用于预置状态
+    fn install(state: MockState) -> Arc<MockBackend> {
+        let backend = Arc::new(MockBackend::new(state));
+        crate::client::drop_client();
+        crate::client::set_client(backend.clone());
+        backend
+    }
+
+    /// 执行异步调用，等待期间不断分发回调
+    fn block_on<T>(backend: &MockBackend, future: impl Future<Output = T>) -> T {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .build()
+            .unwrap();
+        runtime.block_on(async {
+            tokio::pin!(future);
+            loop {
+                let wait = tokio::time::timeout(Duration::from_millis(1), &mut future);
+                match wait.await {
+                    Ok(output) => return output,
+                    Err(_) => backend.run_callbacks(),
+                }
+            }
+        })
+    }
+
+    fn big(value: &BigInt) -> u64 {
+        value.get_u64().1
+    }
+
+    #[test]
+    fn create_and_join_lobby() {
+        let _lock = lock();
+        let backend = install(MockState::default());
+        let me = backend.state().steam_id;
+
+        let lobby = block_on(
+            &backend,
+            steammatchmaking::create_lobby(ELobbyType::Public, 4),
+        )
+        .unwrap();
+        assert_eq!(lobby.get_member_count().unwrap(), 1);
+        assert_eq!(big(&lobby.get_owner().unwrap().steam_id64), me.raw());
+        assert!(lobby
+            .set_data("map".to_string(), "cp_badlands".to_string())
+            .unwrap());
+        assert_eq!(
+            lobby.get_data("map".to_string()).unwrap().as_deref(),
+            Some("cp_badlands")
+        );
+
+        let other = SteamId::from_raw(76561197960287931);
+        let open = backend.state().add_lobby(MockLobby {
+            owner: other,
+            members: vec![other],
+            member_limit: Some(2),
+            listed: true,
+            joinable: true,
+            data: BTreeMap::new(),
+        });
+        let joined = block_on(
+            &backend,
+            steammatchmaking::join_lobby(Either::B(open.raw().to_string())),
+        )
+        .unwrap();
+        assert_eq!(big(&joined.id), open.raw());
+        assert_eq!(backend.lobby_members(open), vec![other, me]);
+
+        // 满员的大厅不出现在搜索结果中，加入时返回 LimitExceeded
+        let lobbies = block_on(&backend, steammatchmaking::get_lobbies()).unwrap();
+        assert_eq!(lobbies.len(), 1);
+        let full = backend.state().add_lobby(MockLobby {
+            owner: other,
+            members: vec![other],
+            member_limit: Some(1),
+            listed: true,
+            joinable: true,
+            data: BTreeMap::new(),
+        });
+        let error = block_on(
+            &backend,
+            steammatchmaking::join_lobby(Either::A(BigInt::from(full.raw()))),
+        )
+        .err()
+        .unwrap();
+        assert!(
+            error
+                .reason
+                .starts_with("[STEAM_ERROR:25:LimitExceeded] join_lobby:"),
+            "{}",
+            error.reason
+        );
+    }
+
+    #[test]
+    fn workshop_items_and_cloud_files() {
+        let _lock = lock();
+        let mut state = MockState::default();
+        state
+            .files
+            .insert("save.txt".to_string(), b"level 3".to_vec());
+        let mut scripted = state.blank_item(PublishedFileId(0));
+        scripted.details.title = "Scripted".to_string();
+        scripted.install = Some(ItemInstallInfo {
+            folder: "workshop/content/480/1".to_string(),
+            size_on_disk: 1024,
+            timestamp: 0,
+        });
+        let scripted = state.add_item(scripted);
+        let backend = install(state);
+
+        let created = block_on(&backend, steamugc::create_item(None)).unwrap();
+        assert_ne!(big(&created.item_id), scripted.0);
+        let update = steamugc::UgcUpdate {
+            title: Some("Map pack".to_string()),
+            description: None,
+            change_note: None,
+            preview_path: None,
+            content_path: None,
+            tags: Some(vec!["maps".to_string()]),
+            visibility: None,
+        };
+        block_on(
+            &backend,
+            steamugc::update_item(Either::A(created.item_id.clone()), update, None),
+        )
+        .unwrap();
+        let item = block_on(
+            &backend,
+            steamugc::get_item(Either::A(created.item_id.clone()), None),
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(item.title, "Map pack");
+        assert_eq!(item.tags, vec!["maps".to_string()]);
+
+        let scripted_id = Either::B(scripted.0.to_string());
+        block_on(&backend, steamugc::subscribe_item(scripted_id)).unwrap();
+        let subscribed = steamugc::get_subscribed_items().unwrap();
+        assert_eq!(
+            subscribed.iter().map(big).collect::<Vec<_>>(),
+            vec![scripted.0]
+        );
+        let info = steamugc::get_item_install_info(Either::A(BigInt::from(scripted.0)))
+            .unwrap()
+            .unwrap();
+        assert_eq!(info.folder, "workshop/content/480/1");
+
+        let missing = block_on(
+            &backend,
+            steamugc::subscribe_item(Either::B("999".to_string())),
+        )
+        .err()
+        .unwrap();
+        assert!(missing
+            .reason
+            .starts_with("[STEAM_ERROR:9:FileNotFound] subscribe_item:"));
+
+        assert_eq!(
+            steamremotestorage::file_read("save.txt".to_string()).unwrap(),
+            "level 3"
+        );
+        assert!(steamremotestorage::file_write("new.txt".to_string(), "data".to_string()).unwrap());
+        assert!(steamremotestorage::file_exists("new.txt".to_string()).unwrap());
+        assert_eq!(backend.state().files["new.txt"], b"data");
+        assert!(steamremotestorage::file_delete("new.txt".to_string()).unwrap());
+        assert!(!steamremotestorage::file_exists("new.txt".to_string()).unwrap());
+    }
+
+    #[test]
+    fn injected_faults_report_eresult() {
+        let _lock = lock();
+        let backend = install(MockState::default());
+        backend
+            .state()
+            .inject_fault("create_lobby", SteamError::AccessDenied, Some(1));
+        backend
+            .state()
+            .inject_fault("file_write", SteamError::IOFailure, None);
+
+        let error = block_on(
+            &backend,
+            steammatchmaking::create_lobby(ELobbyType::Private, 2),
+        )
+        .err()
+        .unwrap();
+        assert!(
+            error
+                .reason
+                .starts_with("[STEAM_ERROR:15:AccessDenied] create_lobby:"),
+            "{}",
+            error.reason
+        );
+        // 只注入了一次，之后的调用成功
+        block_on(
+            &backend,
+            steammatchmaking::create_lobby(ELobbyType::Private, 2),
+        )
+        .unwrap();
+
+        for _ in 0..2 {
+            assert!(!steamremotestorage::file_write("a".to_string(), "b".to_string()).unwrap());
+        }
+        backend.state().clear_faults();
+        assert!(steamremotestorage::file_write("a".to_string(), "b".to_string()).unwrap());
+
+        let missing = steamremotestorage::file_read("missing.txt".to_string())
+            .err()
+            .unwrap();
+        assert!(missing
+            .reason
+            .starts_with("[IO_ERROR] file_read missing.txt:"));
+        backend
+            .state()
+            .inject_fault("file_read", SteamError::IOFailure, Some(1));
+        let failed = steamremotestorage::file_read("a".to_string())
+            .err()
+            .unwrap();
+        assert!(failed.reason.starts_with("[IO_ERROR] file_read a:"));
+    }
+
+    #[test]
+    fn injected_latency_delays_results() {
+        let _lock = lock();
+        let backend = install(MockState {
+            latency: Duration::from_millis(50),
+            ..Default::default()
+        });
+
+        // 延迟未到时分发不会送达结果
+        let (tx, rx) = std::sync::mpsc::channel();
+        backend.request_lobby_list(Box::new(move |result| {
+            let _ = tx.send(result);
+        }));
+        backend.run_callbacks();
+        assert!(rx.try_recv().is_err());
+
+        let start = Instant::now();
+        block_on(&backend, steammatchmaking::get_lobbies()).unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(50));
+        assert!(rx.try_recv().unwrap().unwrap().is_empty());
+    }
+
+    #[test]
+    fn subscriptions_migrate_across_sessions() {
+        let _lock = lock();
+        let backend = install(MockState::default());
+        let me = backend.state().steam_id;
+        let lobby = backend.state().add_lobby(MockLobby {
+            owner: me,
+            members: Vec::new(),
+            member_limit: None,
+            listed: true,
+            joinable: true,
+            data: BTreeMap::new(),
+        });
+
+        let updates = Arc::new(Mutex::new(Vec::new()));
+        let sessions = Arc::new(Mutex::new(Vec::new()));
+        let _updates = {
+            let updates = updates.clone();
+            crate::client::subscribe(SteamApiCallback::LobbyDataUpdate, move |payload| {
+                if let CallbackPayload::LobbyDataUpdate(update) = payload {
+                    updates.lock().unwrap().push(big(&update.lobby));
+                }
+            })
+            .unwrap()
+        };
+        let _sessions = {
+            let sessions = sessions.clone();
+            crate::client::subscribe(SteamApiCallback::SessionChanged, move |payload| {
+                if let CallbackPayload::SessionChanged(changed) = payload {
+                    sessions
+                        .lock()
+                        .unwrap()
+                        .push((changed.app_id, changed.previous_app_id));
+                }
+            })
+            .unwrap()
+        };
+
+        // 回调只在分发时送达
+        assert!(backend.set_lobby_data(lobby, "key", "value"));
+        assert!(updates.lock().unwrap().is_empty());
+        backend.run_callbacks();
+        assert_eq!(*updates.lock().unwrap(), vec![lobby.raw()]);
+
+        // 切换 AppId 后回调迁移到新的客户端，旧客户端的事件不再送达
+        let next = install(MockState {
+            app_id: AppId(440),
+            ..Default::default()
+        });
+        assert_eq!(*sessions.lock().unwrap(), vec![(440, Some(480))]);
+        backend.emit(LobbyDataUpdatePayload {
+            lobby: BigInt::from(1u64),
+            member: BigInt::from(1u64),
+            success: true,
+        });
+        backend.run_callbacks();
+        next.emit(LobbyDataUpdatePayload {
+            lobby: BigInt::from(2u64),
+            member: BigInt::from(2u64),
+            success: true,
+        });
+        next.run_callbacks();
+        assert_eq!(*updates.lock().unwrap(), vec![lobby.raw(), 2]);
+    }
+
+    #[test]
+    fn session_change_rejects_pending_calls() {
+        let _lock = lock();
+        let backend = install(MockState {
+            latency: Duration::from_secs(60),
+            ..Default::default()
+        });
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .build()
+            .unwrap();
+        let error = runtime.block_on(async {
+            let pending = tokio::spawn(steammatchmaking::get_lobbies());
+            tokio::task::yield_now().await;
+            // 与 init 切换 AppId 相同，不等待旧客户端释放
+            crate::client::set_client(Arc::new(MockBackend::default()));
+            pending.await.unwrap().err().unwrap()
+        });
+        assert!(error.reason.starts_with("[STEAM_SESSION_CHANGED]"));
+        drop(backend);
+    }
+
+    #[test]
+    fn favorite_games() {
+        let _lock = lock();
+        let backend = install(MockState {
+            server_real_time: Some(1_700_000_000),
+            ..MockState::default()
+        });
+        let game = |ip: &str, flags| steammatchmaking::FavoriteGame {
+            app_id: 730,
+            ip: ip.to_string(),
+            connection_port: 27015,
+            query_port: 27015,
+            flags,
+            last_played: None,
+        };
+
+        assert_eq!(
+            steammatchmaking::add_favorite_game(game("10.0.0.1", 1)).unwrap(),
+            0
+        );
+        assert_eq!(
+            steammatchmaking::add_favorite_game(game("10.0.0.1", 2)).unwrap(),
+            1
+        );
+        // 已存在的记录只更新游戏时间
+        assert_eq!(
+            steammatchmaking::add_favorite_game(game(" 10.0.0.1 ", 1)).unwrap(),
+            0
+        );
+        let favorites = steammatchmaking::get_favorite_games().unwrap();
+        assert_eq!(favorites.len(), 2);
+        assert_eq!(favorites[0].last_played, Some(1_700_000_000));
+
+        let error = steammatchmaking::add_favorite_game(game("10.0.0", 1))
+            .err()
+            .unwrap();
+        assert_eq!(
+            error.reason,
+            "[INVALID_ARGUMENT] ip: \"10.0.0\" is not an IPv4 address"
+        );
+
+        backend
+            .state()
+            .inject_fault("add_favorite_game", SteamError::Generic, Some(1));
+        let error = steammatchmaking::add_favorite_game(game("10.0.0.2", 1))
+            .err()
+            .unwrap();
+        assert!(error
+            .reason
+            .starts_with("[STEAM_ERROR:2:Generic] add_favorite_game:"));
+        assert_eq!(backend.state().favorites.len(), 2);
+
+        assert!(steammatchmaking::remove_favorite_game(game("10.0.0.1", 2)).unwrap());
+        assert!(!steammatchmaking::remove_favorite_game(game("10.0.0.1", 2)).unwrap());
+        assert_eq!(steammatchmaking::get_favorite_games().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn favorite_games_round_trip() {
+        let _lock = lock();
+        let backend = install(MockState::default());
+        let game = |app_id, ip: &str, ports: (u16, u16), flags, last_played| {
+            steammatchmaking::FavoriteGame {
+                app_id,
+                ip: ip.to_string(),
+                connection_port: ports.0,
+                query_port: ports.1,
+                flags,
+                last_played,
+            }
+        };
+        let first = || game(730, "10.0.0.1", (27015, 27016), 1, Some(1_600_000_000));
+        let second = || game(440, "192.168.1.20", (27017, 27018), 2, Some(1_650_000_000));
+
+        assert_eq!(steammatchmaking::add_favorite_game(first()).unwrap(), 0);
+        assert_eq!(steammatchmaking::add_favorite_game(second()).unwrap(), 1);
+        let favorites = steammatchmaking::get_favorite_games().unwrap();
+        assert_eq!(favorites.len(), 2);
+        for (favorite, expected) in favorites.iter().zip([first(), second()]) {
+            assert_eq!(favorite.app_id, expected.app_id);
+            assert_eq!(favorite.ip, expected.ip);
+            assert_eq!(favorite.connection_port, expected.connection_port);
+            assert_eq!(favorite.query_port, expected.query_port);
+            assert_eq!(favorite.flags, expected.flags);
+            assert_eq!(favorite.last_played, expected.last_played);
+        }
+
+        // 删除时忽略 lastPlayed，其余字段都需要匹配
+        let other_port = game(730, "10.0.0.1", (27015, 27015), 1, None);
+        assert!(!steammatchmaking::remove_favorite_game(other_port).unwrap());
+        let other_flags = game(730, "10.0.0.1", (27015, 27016), 2, None);
+        assert!(!steammatchmaking::remove_favorite_game(other_flags).unwrap());
+        let other_time = game(730, "10.0.0.1", (27015, 27016), 1, None);
+        assert!(steammatchmaking::remove_favorite_game(other_time).unwrap());
+
+        let favorites = steammatchmaking::get_favorite_games().unwrap();
+        assert_eq!(favorites.len(), 1);
+        assert_eq!(favorites[0].app_id, 440);
+        assert_eq!(favorites[0].ip, "192.168.1.20");
+        assert_eq!(backend.state().favorites.len(), 1);
+    }
+
+    #[test]
+    fn callbacks_wait_for_run_callbacks() {
+        let _lock = lock();
+        let backend = install(MockState::default());
+        let me = backend.state().steam_id;
+        let lobby = backend.state().add_lobby(MockLobby {
+            owner: me,
+            members: Vec::new(),
+            member_limit: None,
+            listed: true,
+            joinable: true,
+            data: BTreeMap::new(),
+        });
+
+        let updates = Arc::new(Mutex::new(Vec::new()));
+        let guard = {
+            let updates = updates.clone();
+            backend.register_callback(
+                SteamApiCallback::LobbyDataUpdate,
+                Box::new(move |payload| {
+                    if let CallbackPayload::LobbyDataUpdate(update) = payload {
+                        updates.lock().unwrap().push(big(&update.lobby));
+                    }
+                }),
+            )
+        };
+
+        assert!(backend.set_lobby_data(lobby, "key", "value"));
+        assert!(updates.lock().unwrap().is_empty());
+        backend.run_callbacks();
+        assert_eq!(*updates.lock().unwrap(), vec![lobby.raw()]);
+
+        // 注销后不再送达
+        drop(guard);
+        assert!(backend.set_lobby_data(lobby, "key", "other"));
+        backend.run_callbacks();
+        assert_eq!(updates.lock().unwrap().len(), 1);
+    }
+
+    /// 服务器列表回调收到的内容
+    #[derive(Debug, PartialEq)]
+    enum Listed {
+        Server(String),
+        Summary(&'static str, u32, u32),
+    }
+
+    fn mock_server(port: u16, map: &str, lists: &[ServerListKind], responds: bool) -> MockServer {
+        let mut item = GameServerItem::unanswered(SocketAddrV4::new([10, 0, 0, 1].into(), port));
+        item.app_id = AppId(480);
+        item.map = map.to_string();
+        item.max_players = 16;
+        item.had_successful_response = responds;
+        MockServer {
+            item,
+            lists: lists.iter().copied().collect(),
+            players: vec![ServerPlayer {
+                name: "alice".to_string(),
+                score: 12,
+                time_played: 90.0,
+            }],
+            rules: vec![("mp_timelimit".to_string(), "30".to_string())],
+            responds,
+        }
+    }
+
+    fn start_list(
+        list_type: crate::api::steammatchmakingservers::EServerListType,
+        options: Option<
+            crate::api::steammatchmakingservers::steammatchmakingservers::ServerListOptions,
+        >,
+    ) -> (
+        crate::api::steammatchmakingservers::steammatchmakingservers::ServerListRequest,
+        std::sync::mpsc::Receiver<Listed>,
+    ) {
+        use crate::api::steammatchmakingservers::{
+            steammatchmakingservers, EMatchMakingServerResponse,
+        };
+
+        let (tx, rx) = std::sync::mpsc::channel();
+        let on_complete = tx.clone();
+        let request = steammatchmakingservers::start_server_list(
+            list_type,
+            options,
+            move |server| tx.send(Listed::Server(server.address)).unwrap(),
+            move |summary| {
+                let response = match summary.response {
+                    EMatchMakingServerResponse::ServerResponded => "responded",
+                    EMatchMakingServerResponse::ServerFailedToRespond => "failed",
+                    EMatchMakingServerResponse::NoServersListedOnMasterServer => "none listed",
+                };
+                on_complete
+                    .send(Listed::Summary(response, summary.responded, summary.failed))
+                    .unwrap();
+            },
+        )
+        .unwrap();
+        (request, rx)
+    }
+
+    /// 分发回调直到列表刷新结束
+    fn drain(backend: &MockBackend, received: &std::sync::mpsc::Receiver<Listed>) -> Vec<Listed> {
+        let mut listed = Vec::new();
+        for _ in 0..20 {
+            backend.run_callbacks();
+            listed.extend(received.try_iter());
+            if matches!(listed.last(), Some(Listed::Summary(..))) {
+                break;
+            }
+        }
+        listed
+    }
+
+    fn server(port: u16) -> Listed {
+        Listed::Server(format!("10.0.0.1:{}", port))
+    }
+
+    #[test]
+    fn server_list_streams_servers_and_summary() {
+        use crate::api::steammatchmakingservers::EServerListType;
+        use ServerListKind::{Internet, Lan};
+
+        let _lock = lock();
+        let backend = install(MockState::default());
+        backend.state().servers = vec![
+            mock_server(27015, "de_dust2", &[Internet], true),
+            mock_server(27016, "de_dust2", &[Internet], false),
+            mock_server(27017, "de_nuke", &[Internet, Lan], true),
+            mock_server(27018, "de_dust2", &[Lan], true),
+        ];
+
+        let (_request, received) = start_list(EServerListType::Internet, None);
+        assert!(received.try_recv().is_err());
+        // 每次分发回调收到一个服务器
+        backend.run_callbacks();
+        assert_eq!(received.try_iter().collect::<Vec<_>>(), [server(27015)]);
+        backend.run_callbacks();
+        assert!(received.try_recv().is_err());
+        backend.run_callbacks();
+        assert_eq!(received.try_iter().collect::<Vec<_>>(), [server(27017)]);
+        backend.run_callbacks();
+        assert_eq!(
+            received.try_iter().collect::<Vec<_>>(),
+            [Listed::Summary("responded", 2, 1)]
+        );
+        backend.run_callbacks();
+        assert!(received.try_recv().is_err());
+
+        let (_request, received) = start_list(EServerListType::Favorites, None);
+        assert_eq!(
+            drain(&backend, &received),
+            [Listed::Summary("none listed", 0, 0)]
+        );
+
+        backend.state().servers[0].responds = false;
+        backend.state().servers[2].responds = false;
+        let (_request, received) = start_list(EServerListType::Internet, None);
+        assert_eq!(
+            drain(&backend, &received),
+            [Listed::Summary("failed", 0, 3)]
+        );
+
+        backend
+            .state()
+            .inject_fault("request_server_list", SteamError::Generic, Some(1));
+        let (_request, received) = start_list(EServerListType::Lan, None);
+        assert_eq!(
+            drain(&backend, &received),
+            [Listed::Summary("failed", 0, 0)]
+        );
+    }
+
+    #[test]
+    fn server_list_passes_filters_through() {
+        use crate::api::steammatchmakingservers::steammatchmakingservers::{
+            ServerFilter, ServerListOptions,
+        };
+        use crate::api::steammatchmakingservers::EServerListType;
+        use ServerListKind::{Internet, Lan};
+
+        let _lock = lock();
+        let backend = install(MockState::default());
+        let mut other_game = mock_server(27018, "de_dust2", &[Internet], true);
+        other_game.item.app_id = AppId(730);
+        let mut secure = mock_server(27015, "de_dust2", &[Internet, Lan], true);
+        secure.item.secure = true;
+        backend.state().servers = vec![
+            secure,
+            mock_server(27016, "de_dust2", &[Internet, Lan], true),
+            mock_server(27017, "de_nuke", &[Internet, Lan], true),
+            other_game,
+        ];
+        let options = |app_id, filters: &[(&str, &str)]| {
+            Some(ServerListOptions {
+                app_id,
+                filters: Some(
+                    filters
+                        .iter()
+                        .map(|(key, value)| ServerFilter {
+                            key: key.to_string(),
+                            value: value.to_string(),
+                        })
+                        .collect(),
+                ),
+            })
+        };
+        let filters = [("map", "de_dust2"), ("secure", "1")];
+
+        let (_request, received) = start_list(EServerListType::Internet, options(None, &filters));
+        assert_eq!(
+            drain(&backend, &received),
+            [server(27015), Listed::Summary("responded", 1, 0)]
+        );
+        // 局域网列表忽略筛选条件
+        let (_request, received) = start_list(EServerListType::Lan, options(None, &filters));
+        assert_eq!(drain(&backend, &received).len(), 4);
+        let (_request, received) = start_list(EServerListType::Internet, options(Some(730), &[]));
+        assert_eq!(
+            drain(&backend, &received),
+            [server(27018), Listed::Summary("responded", 1, 0)]
+        );
+
+        let filters: Vec<(String, String)> = filters
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect();
+        assert_eq!(
+            backend.state().server_list_requests,
+            [
+                (Internet, AppId(480), filters.clone()),
+                (Lan, AppId(480), filters),
+                (Internet, AppId(730), Vec::new()),
+            ]
+        );
+    }
+
+    #[test]
+    fn cancelled_server_list_stops_reporting() {
+        use crate::api::steammatchmakingservers::EServerListType;
+
+        let _lock = lock();
+        let backend = install(MockState::default());
+        backend.state().servers = (27015..27018)
+            .map(|port| mock_server(port, "de_dust2", &[ServerListKind::Internet], true))
+            .collect();
+
+        let (mut request, received) = start_list(EServerListType::Internet, None);
+        backend.run_callbacks();
+        assert_eq!(received.try_iter().collect::<Vec<_>>(), [server(27015)]);
+        request.cancel();
+        // 重复取消没有影响
+        request.cancel();
+        for _ in 0..5 {
+            backend.run_callbacks();
+        }
+        assert!(received.try_recv().is_err());
+
+        // 句柄被回收时同样取消
+        let (request, received) = start_list(EServerListType::Internet, None);
+        drop(request);
+        for _ in 0..5 {
+            backend.run_callbacks();
+        }
+        assert!(received.try_recv().is_err());
+    }
+
+    #[test]
+    fn server_queries_time_out_for_silent_servers() {
+        use crate::api::steammatchmakingservers::steammatchmakingservers;
+
+        let _lock = lock();
+        let backend = install(MockState::default());
+        backend.state().servers = vec![
+            mock_server(27015, "de_dust2", &[ServerListKind::Internet], true),
+            mock_server(27016, "de_dust2", &[ServerListKind::Internet], false),
+        ];
+
+        let info = block_on(
+            &backend,
+            steammatchmakingservers::ping_server("10.0.0.1:27015".to_string()),
+        )
+        .unwrap();
+        assert_eq!((info.map.as_str(), info.max_players), ("de_dust2", 16));
+        let players = block_on(
+            &backend,
+            steammatchmakingservers::get_server_players("10.0.0.1:27015".to_string()),
+        )
+        .unwrap();
+        assert_eq!(players[0].name, "alice");
+        assert_eq!(players[0].time_played, 90.0);
+        let rules = block_on(
+            &backend,
+            steammatchmakingservers::get_server_rules("10.0.0.1:27015".to_string()),
+        )
+        .unwrap();
+        assert_eq!(
+            (rules[0].name.as_str(), rules[0].value.as_str()),
+            ("mp_timelimit", "30")
+        );
+
+        let address = || "10.0.0.1:27016".to_string();
+        let errors = [
+            block_on(&backend, steammatchmakingservers::ping_server(address()))
+                .err()
+                .unwrap(),
+            block_on(
+                &backend,
+                steammatchmakingservers::get_server_players(address()),
+            )
+            .err()
+            .unwrap(),
+            block_on(
+                &backend,
+                steammatchmakingservers::get_server_rules(address()),
+            )
+            .err()
+            .unwrap(),
+        ];
+        let reasons: Vec<&str> = errors.iter().map(|error| error.reason.as_str()).collect();
+        assert_eq!(
+            reasons,
+            [
+                "[TIMEOUT] ping_server 10.0.0.1:27016: no response",
+                "[TIMEOUT] server_players 10.0.0.1:27016: no response",
+                "[TIMEOUT] server_rules 10.0.0.1:27016: no response",
+            ]
+        );
+
+        backend
+            .state()
+            .inject_fault("ping_server", SteamError::Generic, Some(1));
+        let error = block_on(
+            &backend,
+            steammatchmakingservers::ping_server("10.0.0.1:27015".to_string()),
+        )
+        .err()
+        .unwrap();
+        assert!(error
+            .reason
+            .starts_with("[STEAM_ERROR:2:Generic] ping_server:"));
+
+        let error = block_on(
+            &backend,
+            steammatchmakingservers::ping_server("[::1]:27015".to_string()),
+        )
+        .err()
+        .unwrap();
+        assert_eq!(
+            error.reason,
+            "[INVALID_ARGUMENT] address: \"[::1]:27015\" resolves to IPv6 address ::1, \
+             only IPv4 is supported"
+        );
+    }
+
+    #[test]
+    fn calls_without_client_report_not_initialized() {
+        let _lock = lock();
+        crate::client::drop_client();
+
+        let error = steamremotestorage::file_exists("save.txt".to_string())
+            .err()
+            .unwrap();
+        assert_eq!(
+            error.reason,
+            "[STEAM_NOT_INITIALIZED] Steam client is not initialized, call init() first"
+        );
+        let error = block_on(&MockBackend::default(), steammatchmaking::get_lobbies())
+            .err()
+            .unwrap();
+        assert!(error.reason.starts_with("[STEAM_NOT_INITIALIZED]"));
+    }
+
+    #[test]
+    fn create_blacklist_reports_invalid_rule() {
+        use crate::api::serverlist::serverlist;
+
+        let blacklist = serverlist::create_blacklist(vec!["1.2.3.0/24".to_string()]).unwrap();
+        assert!(blacklist.is_blocked("1.2.3.4:27015".to_string()));
+        let error = serverlist::create_blacklist(vec!["1.2.3.4".to_string(), "1.2.3".to_string()])
+            .err()
+            .unwrap();
+        assert_eq!(
+            error.reason,
+            "[PARSE_ERROR] create_blacklist \"1.2.3\": invalid blacklist rule"
+        );
+    }
+
+    #[test]
+    fn lan_discovery_rejects_reversed_port_range() {
+        use crate::api::a2s::a2s::LanDiscoveryOptions;
+        use crate::query::lan::DiscoveryOptions;
+
+        let options = |port_start, port_end| {
+            DiscoveryOptions::try_from(Some(LanDiscoveryOptions {
+                broadcast_address: None,
+                port_start,
+                port_end,
+                duration_ms: None,
+            }))
+        };
+        let error = options(Some(27020), Some(27015)).err().unwrap();
+        assert_eq!(
+            error.reason,
+            "[INVALID_ARGUMENT] portEnd: 27015 is less than portStart 27020"
+        );
+        let error = DiscoveryOptions::try_from(Some(LanDiscoveryOptions {
+            broadcast_address: Some("::1".to_string()),
+            port_start: None,
+            port_end: None,
+            duration_ms: None,
+        }))
+        .err()
+        .unwrap();
+        assert_eq!(
+            error.reason,
+            "[INVALID_ARGUMENT] broadcastAddress: \"::1\" is not an IPv4 address"
+        );
+        // 只指定起始端口时结束端口不小于起始端口
+        assert_eq!(options(Some(27030), None).unwrap().ports, 27030..=27030);
+        assert_eq!(options(None, Some(27015)).unwrap().ports, 27015..=27015);
+    }
+}
@@ -0,0 +1,446 @@
+//! Steam 后端抽象
+//!
+//! `api` 下的所有模块都只通过 [`SteamBackend`] 访问 Steam，
+//! 真实的 steamworks-rs 客户端（[`steam::SteamworksBackend`]）与内存模拟实现（[`mock::MockBackend`]）可以互相替换。
+
+use crate::api::callback::SteamApiCallback;
+use crate::api::payloads::CallbackPayload;
+use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4};
+use steamworks::{
+    AppId, FriendFlags, FriendGame, FriendState, ItemState, LobbyId, LobbyType, OverlayToStoreFlag,
+    PublishedFileId, PublishedFileVisibility, SendType, SteamError, SteamId, UpdateStatus,
+};
+
+pub mod callbacks;
+pub mod favorites;
+pub mod logging;
+pub mod mock;
+pub mod servers;
+pub mod steam;
+
+/// 异步调用结果回调，与 steamworks-rs 的调用结果回调形式一致
+pub type ApiCallback<T> = Box<dyn FnOnce(Result<T, SteamError>) + Send + 'static>;
+
+/// Steam 回调事件处理函数
+pub type CallbackHandler = Box<dyn Fn(CallbackPayload) + Send + Sync + 'static>;
+
+/// 回调注册句柄，销毁时自动注销
+pub struct CallbackGuard {
+    disconnect: Option<Box<dyn FnOnce() + Send + 'static>>,
+}
+
+impl CallbackGuard {
+    pub fn new(disconnect: impl FnOnce() + Send + 'static) -> Self {
+        Self {
+            disconnect: Some(Box::new(disconnect)),
+        }
+    }
+
+    /// 立即注销回调
+    pub fn disconnect(mut self) {
+        if let Some(disconnect) = self.disconnect.take() {
+            disconnect();
+        }
+    }
+}
+
+impl Drop for CallbackGuard {
+    fn drop(&mut self) {
+        if let Some(disconnect) = self.disconnect.take() {
+            disconnect();
+        }
+    }
+}
+
+/// 好友信息
+pub struct FriendInfo {
+    pub steam_id: SteamId,
+    pub name: String,
+    pub state: FriendState,
+    /// 是否满足查询时指定的好友标识
+    pub has_friend: bool,
+    pub game: Option<FriendGame>,
+}
+
+/// 创意工坊物品详情
+#[derive(Clone)]
+pub struct WorkshopItemDetails {
+    pub published_file_id: PublishedFileId,
+    pub creator_app_id: Option<AppId>,
+    pub consumer_app_id: Option<AppId>,
+    pub title: String,
+    pub description: String,
+    pub owner: SteamId,
+    pub time_created: u32,
+    pub time_updated: u32,
+    pub banned: bool,
+    pub accepted_for_use: bool,
+    pub tags: Vec<String>,
+    pub tags_truncated: bool,
+    pub url: String,
+    pub num_upvotes: u32,
+    pub num_downvotes: u32,
+    pub num_children: u32,
+    pub preview_url: Option<String>,
+}
+
+/// 创意工坊查询选项
+#[derive(Default)]
+pub struct WorkshopQuery {
+    pub cached_response_max_age: Option<u32>,
+    pub include_metadata: Option<bool>,
+    pub include_long_description: Option<bool>,
+    pub language: Option<String>,
+}
+
+/// 创意工坊物品更新内容
+#[derive(Default)]
+pub struct ItemUpdate {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub change_note: Option<String>,
+    pub preview_path: Option<String>,
+    pub content_path: Option<String>,
+    pub tags: Option<Vec<String>>,
+    pub visibility: Option<PublishedFileVisibility>,
+}
+
+/// 创意工坊物品安装信息
+#[derive(Clone)]
+pub struct ItemInstallInfo {
+    pub folder: String,
+    pub size_on_disk: u64,
+    pub timestamp: u32,
+}
+
+/// 创意工坊物品更新进度查询
+pub trait UpdateWatch: Send {
+    /// 返回更新状态、已处理字节与总字节
+    fn progress(&self) -> (UpdateStatus, u64, u64);
+}
+
+/// 身份验证票证的接收方
+#[derive(Debug)]
+pub enum TicketIdentity {
+    SteamId(SteamId),
+    Ip(SocketAddr),
+}
+
+/// 身份验证票证句柄
+pub type TicketHandle = u32;
+
+/// 服务器列表类型，对应 ISteamMatchmakingServers 的各个 Request*ServerList
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ServerListKind {
+    Internet,
+    Lan,
+    Favorites,
+    History,
+    Friends,
+    Spectator,
+}
+
+/// 服务器浏览器返回的服务器信息，对应 gameserveritem_t
+#[derive(Clone, Debug)]
+pub struct GameServerItem {
+    /// 服务器 IP 与查询端口
+    pub address: SocketAddrV4,
+    /// 游戏连接端口
+    pub connection_port: u16,
+    /// 延迟（毫秒）
+    pub ping: i32,
+    /// 服务器是否回复过查询
+    pub had_successful_response: bool,
+    /// 刷新列表时是否跳过该服务器
+    pub do_not_refresh: bool,
+    pub game_dir: String,
+    pub map: String,
+    pub game_description: String,
+    pub app_id: AppId,
+    pub players: i32,
+    pub max_players: i32,
+    pub bot_players: i32,
+    pub password: bool,
+    /// 是否启用 VAC
+    pub secure: bool,
+    /// 上次在该服务器游戏的时间，仅收藏与历史列表
+    pub time_last_played: u32,
+    pub server_version: i32,
+    pub server_name: String,
+    /// 逗号分隔的服务器标签
+    pub game_tags: String,
+    pub steam_id: SteamId,
+}
+
+impl GameServerItem {
+    /// 只有地址的服务器信息，用于还没有回复的服务器
+    pub fn unanswered(address: SocketAddrV4) -> Self {
+        Self {
+            address,
+            connection_port: address.port(),
+            ping: 0,
+            had_successful_response: false,
+            do_not_refresh: false,
+            game_dir: String::new(),
+            map: String::new(),
+            game_description: String::new(),
+            app_id: AppId(0),
+            players: 0,
+            max_players: 0,
+            bot_players: 0,
+            password: false,
+            secure: false,
+            time_last_played: 0,
+            server_version: 0,
+            server_name: address.to_string(),
+            game_tags: String::new(),
+            steam_id: SteamId::from_raw(0),
+        }
+    }
+}
+
+/// 服务器列表刷新结束的原因，对应 EMatchMakingServerResponse
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ServerListResponse {
+    /// 至少有一个服务器回复
+    ServerResponded,
+    /// 没有服务器回复
+    ServerFailedToRespond,
+    /// 主服务器上没有符合条件的服务器
+    NoServersListedOnMasterServer,
+}
+
+/// 服务器列表请求的进度
+#[derive(Clone, Debug)]
+pub enum ServerListEvent {
+    /// 一个服务器回复了查询
+    Responded(GameServerItem),
+    /// 一个服务器没有回复
+    Failed(GameServerItem),
+    /// 列表刷新结束，之后不会再有事件
+    Complete(ServerListResponse),
+}
+
+/// 收藏的服务器
+pub const FAVORITE_FLAG_FAVORITE: u32 = 0x01;
+/// 历史记录中的服务器
+pub const FAVORITE_FLAG_HISTORY: u32 = 0x02;
+
+/// Steam 收藏夹或历史记录中的服务器，同一服务器在收藏夹与历史记录中是两条记录
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FavoriteGame {
+    pub app_id: AppId,
+    pub ip: Ipv4Addr,
+    pub connection_port: u16,
+    pub query_port: u16,
+    /// `FAVORITE_FLAG_*` 的组合
+    pub flags: u32,
+    /// 最后一次在该服务器上游戏的 Unix 时间（秒）
+    pub last_played: u32,
+}
+
+impl FavoriteGame {
+    /// 是否与另一条记录指向同一个服务器与列表，不比较游戏时间
+    pub fn same_entry(&self, other: &FavoriteGame) -> bool {
+        self.app_id == other.app_id
+            && self.ip == other.ip
+            && self.connection_port == other.connection_port
+            && self.query_port == other.query_port
+            && self.flags == other.flags
+    }
+}
+
+/// 服务器列表事件处理函数
+pub type ServerListHandler = Box<dyn FnMut(ServerListEvent) + Send + 'static>;
+
+/// 通过服务器浏览器查询到的玩家
+#[derive(Clone, Debug)]
+pub struct ServerPlayer {
+    pub name: String,
+    pub score: i32,
+    /// 在服务器中的时长（秒）
+    pub time_played: f32,
+}
+
+/// Steam 后端
+///
+/// 方法按 ISteam 接口分组，命名与 steamworks-rs 保持一致。
+pub trait SteamBackend: Send + Sync {
+    /// 分发已完成的回调与调用结果
+    fn run_callbacks(&self);
+
+    /// 注册 Steam 回调，返回的句柄销毁时注销
+    fn register_callback(&self, kind: SteamApiCallback, handler: CallbackHandler) -> CallbackGuard;
+
+    // ISteamApps
+
+    fn is_subscribed_app(&self, app_id: AppId) -> bool;
+    fn is_dlc_installed(&self, app_id: AppId) -> bool;
+    fn is_app_installed(&self, app_id: AppId) -> bool;
+    fn is_subscribed_from_free_weekend(&self) -> bool;
+    fn is_vac_banned(&self) -> bool;
+    fn is_cybercafe(&self) -> bool;
+    fn is_low_violence(&self) -> bool;
+    fn is_subscribed(&self) -> bool;
+    fn app_owner(&self) -> SteamId;
+    fn available_game_languages(&self) -> Vec<String>;
+    fn current_beta_name(&self) -> Option<String>;
+    fn app_install_dir(&self, app_id: AppId) -> String;
+    fn current_game_language(&self) -> String;
+
+    // ISteamFriends
+
+    fn persona_name(&self) -> String;
+    fn set_rich_presence(&self, key: &str, value: Option<&str>) -> bool;
+    fn friend_game_played(&self, steam_id: SteamId) -> Option<FriendGame>;
+    fn friends(&self, flags: FriendFlags) -> Vec<FriendInfo>;
+    fn activate_game_overlay(&self, dialog: &str);
+    fn activate_game_overlay_to_user(&self, dialog: &str, steam_id: SteamId);
+    fn activate_invite_dialog(&self, lobby_id: LobbyId);
+    fn activate_game_overlay_to_web_page(&self, url: &str);
+    fn activate_game_overlay_to_store(&self, app_id: AppId, flag: OverlayToStoreFlag);
+
+    // ISteamInput
+
+    fn input_init(&self);
+    fn input_shutdown(&self);
+    fn connected_controllers(&self) -> Vec<u64>;
+    fn action_set_handle(&self, action_set_name: &str) -> u64;
+    fn digital_action_handle(&self, action_name: &str) -> u64;
+    fn analog_action_handle(&self, action_name: &str) -> u64;
+    fn activate_action_set(&self, controller: u64, action_set_handle: u64);
+    fn digital_action_state(&self, controller: u64, action_handle: u64) -> bool;
+    fn analog_action_vector(&self, controller: u64, action_handle: u64) -> (f32, f32);
+
+    // ISteamMatchmaking
+
+    fn create_lobby(&self, lobby_type: LobbyType, max_members: u32, callback: ApiCallback<LobbyId>);
+    fn join_lobby(&self, lobby_id: LobbyId, callback: ApiCallback<LobbyId>);
+    fn request_lobby_list(&self, callback: ApiCallback<Vec<LobbyId>>);
+    fn leave_lobby(&self, lobby_id: LobbyId);
+    fn lobby_member_count(&self, lobby_id: LobbyId) -> usize;
+    fn lobby_member_limit(&self, lobby_id: LobbyId) -> Option<usize>;
+    fn lobby_members(&self, lobby_id: LobbyId) -> Vec<SteamId>;
+    fn lobby_owner(&self, lobby_id: LobbyId) -> SteamId;
+    fn set_lobby_joinable(&self, lobby_id: LobbyId, joinable: bool) -> bool;
+    fn lobby_data(&self, lobby_id: LobbyId, key: &str) -> Option<String>;
+    fn lobby_data_count(&self, lobby_id: LobbyId) -> u32;
+    fn lobby_data_by_index(&self, lobby_id: LobbyId, index: u32) -> Option<(String, String)>;
+    fn set_lobby_data(&self, lobby_id: LobbyId, key: &str, value: &str) -> bool;
+    fn delete_lobby_data(&self, lobby_id: LobbyId, key: &str) -> bool;
+    fn favorite_games(&self) -> Vec<FavoriteGame>;
+    /// 添加或更新一条记录，已存在时只更新游戏时间
+    ///
+    /// @returns 记录在列表中的序号，添加失败时返回 None
+    fn add_favorite_game(&self, game: &FavoriteGame) -> Option<u32>;
+    /// 删除一条记录，不比较游戏时间
+    ///
+    /// @returns 是否找到并删除了记录
+    fn remove_favorite_game(&self, game: &FavoriteGame) -> bool;
+
+    // ISteamMatchmakingServers
+
+    /// 请求服务器列表，每个服务器回复或超时时调用 `handler`，返回的句柄销毁时取消并释放请求
+    ///
+    /// @param filters 筛选键值对，如 `gamedir`、`map`，局域网列表不支持筛选
+    fn request_server_list(
+        &self,
+        kind: ServerListKind,
+        app_id: AppId,
+        filters: Vec<(String, String)>,
+        handler: ServerListHandler,
+    ) -> CallbackGuard;
+    /// 查询单个服务器的信息，返回的句柄在查询完成前销毁时取消查询
+    fn ping_server(
+        &self,
+        address: SocketAddrV4,
+        callback: ApiCallback<GameServerItem>,
+    ) -> CallbackGuard;
+    fn server_players(
+        &self,
+        address: SocketAddrV4,
+        callback: ApiCallback<Vec<ServerPlayer>>,
+    ) -> CallbackGuard;
+    fn server_rules(
+        &self,
+        address: SocketAddrV4,
+        callback: ApiCallback<Vec<(String, String)>>,
+    ) -> CallbackGuard;
+
+    // ISteamNetworking
+
+    fn send_p2p_packet(&self, steam_id: SteamId, send_type: SendType, data: &[u8]) -> bool;
+    fn is_p2p_packet_available(&self) -> Option<usize>;
+    fn read_p2p_packet(&self, buffer: &mut [u8]) -> Option<(SteamId, usize)>;
+    fn accept_p2p_session(&self, steam_id: SteamId);
+
+    // ISteamRemoteStorage
+
+    fn is_cloud_enabled_for_account(&self) -> bool;
+    fn is_cloud_enabled_for_app(&self) -> bool;
+    fn file_read(&self, name: &str) -> std::io::Result<Vec<u8>>;
+    fn file_write(&self, name: &str, content: &[u8]) -> bool;
+    fn file_delete(&self, name: &str) -> bool;
+    fn file_exists(&self, name: &str) -> bool;
+
+    // ISteamUGC
+
+    fn item_state(&self, item_id: PublishedFileId) -> ItemState;
+    fn create_item(&self, app_id: AppId, callback: ApiCallback<(PublishedFileId, bool)>);
+    fn submit_item_update(
+        &self,
+        app_id: AppId,
+        item_id: PublishedFileId,
+        update: ItemUpdate,
+        callback: ApiCallback<(PublishedFileId, bool)>,
+    ) -> Box<dyn UpdateWatch>;
+    fn subscribe_item(&self, item_id: PublishedFileId, callback: ApiCallback<()>);
+    fn unsubscribe_item(&self, item_id: PublishedFileId, callback: ApiCallback<()>);
+    fn download_item(&self, item_id: PublishedFileId, high_priority: bool) -> bool;
+    fn subscribed_items(&self) -> Vec<PublishedFileId>;
+    /// 查询创意工坊物品详情，查询无法创建时直接返回错误
+    fn query_items(
+        &self,
+        items: Vec<PublishedFileId>,
+        query: WorkshopQuery,
+        callback: ApiCallback<Vec<Option<WorkshopItemDetails>>>,
+    ) -> Result<(), SteamError>;
+    fn item_install_info(&self, item_id: PublishedFileId) -> Option<ItemInstallInfo>;
+    fn item_download_info(&self, item_id: PublishedFileId) -> Option<(u64, u64)>;
+
+    // ISteamUser
+
+    fn steam_id(&self) -> SteamId;
+    fn level(&self) -> u32;
+    /// 获取会话票证，票证通过验证后调用 `callback`，返回的句柄在等待结束后应当销毁
+    fn auth_session_ticket(
+        &self,
+        identity: TicketIdentity,
+        callback: ApiCallback<()>,
+    ) -> (TicketHandle, Vec<u8>, CallbackGuard);
+    /// 获取 Web API 票证，票证生成后通过 `callback` 返回票证数据
+    fn auth_ticket_for_web_api(
+        &self,
+        identity: &str,
+        callback: ApiCallback<Vec<u8>>,
+    ) -> (TicketHandle, CallbackGuard);
+    fn cancel_auth_ticket(&self, ticket: TicketHandle);
+
+    // ISteamUserStats
+
+    fn request_current_stats(&self);
+    fn stat_i32(&self, name: &str) -> Option<i32>;
+    fn set_stat_i32(&self, name: &str, value: i32) -> bool;
+    fn achievement(&self, name: &str) -> Option<bool>;
+    fn set_achievement(&self, name: &str) -> bool;
+    fn clear_achievement(&self, name: &str) -> bool;
+    fn store_stats(&self) -> bool;
+    fn reset_all_stats(&self, achievements_too: bool) -> bool;
+
+    // ISteamUtils
+
+    fn app_id(&self) -> AppId;
+    fn ip_country(&self) -> String;
+    fn server_real_time(&self) -> u32;
+    fn is_steam_running_on_steam_deck(&self) -> bool;
+}
@@ -0,0 +1,475 @@
+//! steamworks-rs 未封装的 ISteamMatchmakingServers
+//!
+//! 服务器浏览器通过 C++ 回调对象返回结果，这里用 `#[repr(C)]` 结构体模拟对象布局：
+//! 第一个字段为虚函数表指针，虚函数按 isteammatchmaking.h 中的声明顺序排列。
+//! 插件只发布 64 位版本，64 位平台上成员函数与 C 函数的调用约定一致。
+//!
+//! 回调对象由 Steam 在 `SteamAPI_RunCallbacks` 中调用，在请求释放或查询结束之前必须保持有效，
+//! 因此创建与取消请求都要在持有 `SingleClient` 锁时进行。
+
+use super::{
+    ApiCallback, GameServerItem, ServerListEvent, ServerListHandler, ServerListKind,
+    ServerListResponse, ServerPlayer,
+};
+use crate::error::catch_panic;
+use std::ffi::{c_char, c_int, CStr};
+use std::mem;
+use std::net::{Ipv4Addr, SocketAddrV4};
+use steamworks::{sys, AppId, SteamError, SteamId};
+
+/// 筛选键值的最大字节数（含结尾的 0）
+const MAX_FILTER_LENGTH: usize = 256;
+
+fn servers() -> *mut sys::ISteamMatchmakingServers {
+    unsafe { sys::SteamAPI_SteamMatchmakingServers_v002() }
+}
+
+/// 读取定长的 C 字符串
+fn fixed_string(chars: &[c_char]) -> String {
+    let bytes: Vec<u8> = chars
+        .iter()
+        .take_while(|c| **c != 0)
+        .map(|c| *c as u8)
+        .collect();
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+unsafe fn c_string(chars: *const c_char) -> String {
+    if chars.is_null() {
+        String::new()
+    } else {
+        CStr::from_ptr(chars).to_string_lossy().into_owned()
+    }
+}
+
+/// 写入定长的 C 字符串，超出部分截断
+fn write_fixed(target: &mut [c_char; MAX_FILTER_LENGTH], value: &str) {
+    for (target, byte) in target
+        .iter_mut()
+        .zip(value.bytes().take(MAX_FILTER_LENGTH - 1))
+    {
+        *target = byte as c_char;
+    }
+}
+
+impl GameServerItem {
+    fn from_raw(raw: &sys::gameserveritem_t) -> Self {
+        Self {
+            address: SocketAddrV4::new(
+                Ipv4Addr::from(raw.m_NetAdr.m_unIP),
+                raw.m_NetAdr.m_usQueryPort,
+            ),
+            connection_port: raw.m_NetAdr.m_usConnectionPort,
+            ping: raw.m_nPing,
+            had_successful_response: raw.m_bHadSuccessfulResponse,
+            do_not_refresh: raw.m_bDoNotRefresh,
+            game_dir: fixed_string(&raw.m_szGameDir),
+            map: fixed_string(&raw.m_szMap),
+            game_description: fixed_string(&raw.m_szGameDescription),
+            app_id: AppId(raw.m_nAppID),
+            players: raw.m_nPlayers,
+            max_players: raw.m_nMaxPlayers,
+            bot_players: raw.m_nBotPlayers,
+            password: raw.m_bPassword,
+            secure: raw.m_bSecure,
+            time_last_played: raw.m_ulTimeLastPlayed,
+            server_version: raw.m_nServerVersion,
+            server_name: fixed_string(&raw.m_szServerName),
+            game_tags: fixed_string(&raw.m_szGameTags),
+            steam_id: SteamId::from_raw(unsafe { raw.m_steamID.m_steamid.m_unAll64Bits }),
+        }
+    }
+}
+
+/// 进行中的请求，取消时同时释放回调对象
+pub trait PendingRequest: Send {
+    /// 取消请求，调用方必须持有 `SingleClient` 锁
+    fn cancel(self: Box<Self>);
+}
+
+/// ISteamMatchmakingServerListResponse 的虚函数表
+#[repr(C)]
+struct ServerListVtable {
+    server_responded: unsafe extern "C" fn(*mut ServerList, sys::HServerListRequest, c_int),
+    server_failed_to_respond: unsafe extern "C" fn(*mut ServerList, sys::HServerListRequest, c_int),
+    refresh_complete: unsafe extern "C" fn(*mut ServerList, sys::HServerListRequest, c_int),
+}
+
+static SERVER_LIST_VTABLE: ServerListVtable = ServerListVtable {
+    server_responded: ServerList::server_responded,
+    server_failed_to_respond: ServerList::server_failed_to_respond,
+    refresh_complete: ServerList::refresh_complete,
+};
+
+/// 服务器列表请求的回调对象
+#[repr(C)]
+struct ServerList {
+    vtable: &'static ServerListVtable,
+    request: sys::HServerListRequest,
+    /// 列表刷新结束后为空
+    handler: Option<ServerListHandler>,
+}
+
+unsafe impl Send for ServerList {}
+
+/// 发起服务器列表请求
+pub fn request_server_list(
+    kind: ServerListKind,
+    app_id: AppId,
+    filters: &[(String, String)],
+    handler: ServerListHandler,
+) -> Box<dyn PendingRequest> {
+    let mut list = Box::new(ServerList {
+        vtable: &SERVER_LIST_VTABLE,
+        request: std::ptr::null_mut(),
+        handler: Some(handler),
+    });
+
+    let mut pairs: Vec<sys::MatchMakingKeyValuePair_t> = filters
+        .iter()
+        .map(|(key, value)| {
+            let mut pair = sys::MatchMakingKeyValuePair_t {
+                m_szKey: [0; MAX_FILTER_LENGTH],
+                m_szValue: [0; MAX_FILTER_LENGTH],
+            };
+            write_fixed(&mut pair.m_szKey, key);
+            write_fixed(&mut pair.m_szValue, value);
+            pair
+        })
+        .collect();
+    // Steam 在调用期间复制筛选条件
+    let mut pairs_ptr = pairs.as_mut_ptr();
+    let filters_ptr = &mut pairs_ptr as *mut *mut sys::MatchMakingKeyValuePair_t;
+    let count = pairs.len() as u32;
+    let response = &mut *list as *mut ServerList as *mut sys::ISteamMatchmakingServerListResponse;
+
+    list.request = unsafe {
+        match kind {
+            ServerListKind::Internet => {
+                sys::SteamAPI_ISteamMatchmakingServers_RequestInternetServerList(
+                    servers(),
+                    app_id.0,
+                    filters_ptr,
+                    count,
+                    response,
+                )
+            }
+            ServerListKind::Lan => sys::SteamAPI_ISteamMatchmakingServers_RequestLANServerList(
+                servers(),
+                app_id.0,
+                response,
+            ),
+            ServerListKind::Favorites => {
+                sys::SteamAPI_ISteamMatchmakingServers_RequestFavoritesServerList(
+                    servers(),
+                    app_id.0,
+                    filters_ptr,
+                    count,
+                    response,
+                )
+            }
+            ServerListKind::History => {
+                sys::SteamAPI_ISteamMatchmakingServers_RequestHistoryServerList(
+                    servers(),
+                    app_id.0,
+                    filters_ptr,
+                    count,
+                    response,
+                )
+            }
+            ServerListKind::Friends => {
+                sys::SteamAPI_ISteamMatchmakingServers_RequestFriendsServerList(
+                    servers(),
+                    app_id.0,
+                    filters_ptr,
+                    count,
+                    response,
+                )
+            }
+            ServerListKind::Spectator => {
+                sys::SteamAPI_ISteamMatchmakingServers_RequestSpectatorServerList(
+                    servers(),
+                    app_id.0,
+                    filters_ptr,
+                    count,
+                    response,
+                )
+            }
+        }
+    };
+    list
+}
+
+impl ServerList {
+    fn details(request: sys::HServerListRequest, server: c_int) -> Option<GameServerItem> {
+        let raw = unsafe {
+            sys::SteamAPI_ISteamMatchmakingServers_GetServerDetails(servers(), request, server)
+        };
+        unsafe { raw.as_ref() }.map(GameServerItem::from_raw)
+    }
+
+    fn emit(&mut self, event: ServerListEvent) {
+        if let Some(handler) = self.handler.as_mut() {
+            handler(event);
+        }
+    }
+
+    unsafe extern "C" fn server_responded(
+        this: *mut ServerList,
+        request: sys::HServerListRequest,
+        server: c_int,
+    ) {
+        let _ = catch_panic("ServerResponded", || {
+            if let Some(item) = Self::details(request, server) {
+                (*this).emit(ServerListEvent::Responded(item));
+            }
+        });
+    }
+
+    unsafe extern "C" fn server_failed_to_respond(
+        this: *mut ServerList,
+        request: sys::HServerListRequest,
+        server: c_int,
+    ) {
+        let _ = catch_panic("ServerFailedToRespond", || {
+            if let Some(item) = Self::details(request, server) {
+                (*this).emit(ServerListEvent::Failed(item));
+            }
+        });
+    }
+
+    unsafe extern "C" fn refresh_complete(
+        this: *mut ServerList,
+        _request: sys::HServerListRequest,
+        response: c_int,
+    ) {
+        let _ = catch_panic("RefreshComplete", || {
+            let response = match response {
+                0 => ServerListResponse::ServerResponded,
+                2 => ServerListResponse::NoServersListedOnMasterServer,
+                _ => ServerListResponse::ServerFailedToRespond,
+            };
+            // 结束后立即释放处理函数，请求本身保留到取消时释放
+            if let Some(mut handler) = (*this).handler.take() {
+                handler(ServerListEvent::Complete(response));
+            }
+        });
+    }
+}
+
+impl PendingRequest for ServerList {
+    fn cancel(self: Box<Self>) {
+        if !self.request.is_null() {
+            // 释放请求时会取消仍在进行的刷新
+            unsafe {
+                sys::SteamAPI_ISteamMatchmakingServers_ReleaseRequest(servers(), self.request)
+            };
+        }
+    }
+}
+
+/// 单个服务器查询的回调对象，`V` 为对应接口的虚函数表
+#[repr(C)]
+struct ServerQuery<V: 'static, T: 'static> {
+    vtable: &'static V,
+    query: sys::HServerQuery,
+    /// 查询结束后为空
+    callback: Option<ApiCallback<T>>,
+    /// 逐条返回的结果
+    collected: T,
+}
+
+unsafe impl<V, T: Send> Send for ServerQuery<V, T> {}
+
+impl<V, T: Default> ServerQuery<V, T> {
+    fn new(vtable: &'static V, callback: ApiCallback<T>) -> Box<Self> {
+        Box::new(ServerQuery {
+            vtable,
+            query: 0,
+            callback: Some(callback),
+            collected: T::default(),
+        })
+    }
+
+    fn finish(&mut self, result: Result<T, SteamError>) {
+        if let Some(callback) = self.callback.take() {
+            callback(result);
+        }
+    }
+
+    /// 返回逐条收集的结果
+    fn finish_collected(&mut self) {
+        let collected = mem::take(&mut self.collected);
+        self.finish(Ok(collected));
+    }
+}
+
+impl<V, T: Send> PendingRequest for ServerQuery<V, T> {
+    fn cancel(self: Box<Self>) {
+        // 查询结束后 Steam 不再持有回调对象，无需取消
+        if self.callback.is_some() {
+            unsafe {
+                sys::SteamAPI_ISteamMatchmakingServers_CancelServerQuery(servers(), self.query)
+            };
+        }
+    }
+}
+
+/// ISteamMatchmakingPingResponse 的虚函数表
+#[repr(C)]
+struct PingVtable {
+    server_responded: unsafe extern "C" fn(*mut PingQuery, *mut sys::gameserveritem_t),
+    server_failed_to_respond: unsafe extern "C" fn(*mut PingQuery),
+}
+
+type PingQuery = ServerQuery<PingVtable, Option<GameServerItem>>;
+
+static PING_VTABLE: PingVtable = PingVtable {
+    server_responded: ping_responded,
+    server_failed_to_respond: ping_failed,
+};
+
+unsafe extern "C" fn ping_responded(this: *mut PingQuery, server: *mut sys::gameserveritem_t) {
+    let _ = catch_panic("ServerResponded", || {
+        let item = server.as_ref().map(GameServerItem::from_raw);
+        (*this).finish(item.ok_or(SteamError::Generic).map(Some));
+    });
+}
+
+unsafe extern "C" fn ping_failed(this: *mut PingQuery) {
+    let _ = catch_panic("ServerFailedToRespond", || {
+        (*this).finish(Err(SteamError::Timeout))
+    });
+}
+
+/// 查询服务器信息
+pub fn ping_server(
+    address: SocketAddrV4,
+    callback: ApiCallback<GameServerItem>,
+) -> Box<dyn PendingRequest> {
+    let mut query = PingQuery::new(
+        &PING_VTABLE,
+        Box::new(move |result: Result<Option<GameServerItem>, SteamError>| {
+            callback(result.and_then(|item| item.ok_or(SteamError::Generic)))
+        }),
+    );
+    let response = &mut *query as *mut PingQuery as *mut sys::ISteamMatchmakingPingResponse;
+    query.query = unsafe {
+        sys::SteamAPI_ISteamMatchmakingServers_PingServer(
+            servers(),
+            u32::from(*address.ip()),
+            address.port(),
+            response,
+        )
+    };
+    query
+}
+
+/// ISteamMatchmakingPlayersResponse 的虚函数表
+#[repr(C)]
+struct PlayersVtable {
+    add_player_to_list: unsafe extern "C" fn(*mut PlayersQuery, *const c_char, c_int, f32),
+    players_failed_to_respond: unsafe extern "C" fn(*mut PlayersQuery),
+    players_refresh_complete: unsafe extern "C" fn(*mut PlayersQuery),
+}
+
+type PlayersQuery = ServerQuery<PlayersVtable, Vec<ServerPlayer>>;
+
+static PLAYERS_VTABLE: PlayersVtable = PlayersVtable {
+    add_player_to_list: add_player,
+    players_failed_to_respond: players_failed,
+    players_refresh_complete: players_complete,
+};
+
+unsafe extern "C" fn add_player(
+    this: *mut PlayersQuery,
+    name: *const c_char,
+    score: c_int,
+    time_played: f32,
+) {
+    let _ = catch_panic("AddPlayerToList", || {
+        (*this).collected.push(ServerPlayer {
+            name: c_string(name),
+            score,
+            time_played,
+        });
+    });
+}
+
+unsafe extern "C" fn players_failed(this: *mut PlayersQuery) {
+    let _ = catch_panic("PlayersFailedToRespond", || {
+        (*this).finish(Err(SteamError::Timeout))
+    });
+}
+
+unsafe extern "C" fn players_complete(this: *mut PlayersQuery) {
+    let _ = catch_panic("PlayersRefreshComplete", || (*this).finish_collected());
+}
+
+/// 查询服务器中的玩家
+pub fn server_players(
+    address: SocketAddrV4,
+    callback: ApiCallback<Vec<ServerPlayer>>,
+) -> Box<dyn PendingRequest> {
+    let mut query = PlayersQuery::new(&PLAYERS_VTABLE, callback);
+    let response = &mut *query as *mut PlayersQuery as *mut sys::ISteamMatchmakingPlayersResponse;
+    query.query = unsafe {
+        sys::SteamAPI_ISteamMatchmakingServers_PlayerDetails(
+            servers(),
+            u32::from(*address.ip()),
+            address.port(),
+            response,
+        )
+    };
+    query
+}
+
+/// ISteamMatchmakingRulesResponse 的虚函数表
+#[repr(C)]
+struct RulesVtable {
+    rules_responded: unsafe extern "C" fn(*mut RulesQuery, *const c_char, *const c_char),
+    rules_failed_to_respond: unsafe extern "C" fn(*mut RulesQuery),
+    rules_refresh_complete: unsafe extern "C" fn(*mut RulesQuery),
+}
+
+type RulesQuery = ServerQuery<RulesVtable, Vec<(String, String)>>;
+
+static RULES_VTABLE: RulesVtable = RulesVtable {
+    rules_responded: add_rule,
+    rules_failed_to_respond: rules_failed,
+    rules_refresh_complete: rules_complete,
+};
+
+unsafe extern "C" fn add_rule(this: *mut RulesQuery, rule: *const c_char, value: *const c_char) {
+    let _ = catch_panic("RulesResponded", || {
+        (*this).collected.push((c_string(rule), c_string(value)));
+    });
+}
+
+unsafe extern "C" fn rules_failed(this: *mut RulesQuery) {
+    let _ = catch_panic("RulesFailedToRespond", || {
+        (*this).finish(Err(SteamError::Timeout))
+    });
+}
+
+unsafe extern "C" fn rules_complete(this: *mut RulesQuery) {
+    let _ = catch_panic("RulesRefreshComplete", || (*this).finish_collected());
+}
+
+/// 查询服务器规则
+pub fn server_rules(
+    address: SocketAddrV4,
+    callback: ApiCallback<Vec<(String, String)>>,
+) -> Box<dyn PendingRequest> {
+    let mut query = RulesQuery::new(&RULES_VTABLE, callback);
+    let response = &mut *query as *mut RulesQuery as *mut sys::ISteamMatchmakingRulesResponse;
+    query.query = unsafe {
+        sys::SteamAPI_ISteamMatchmakingServers_ServerRules(
+            servers(),
+            u32::from(*address.ip()),
+            address.port(),
+            response,
+        )
+    };
+    query
+}
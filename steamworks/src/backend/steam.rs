@@ -0,0 +1,919 @@
+//! 基于 steamworks-rs 的真实 Steam 后端
+
+use super::callbacks::{
+    AvatarImageLoaded, FloatingGamepadTextInputDismissed, ItemInstalled, LobbyChatMsg, LobbyEnter,
+    SteamShutdown,
+};
+use super::servers::{self, PendingRequest};
+use super::{
+    favorites, ApiCallback, CallbackGuard, CallbackHandler, FavoriteGame, FriendInfo,
+    GameServerItem, ItemInstallInfo, ItemUpdate, ServerListHandler, ServerListKind, ServerPlayer,
+    SteamBackend, TicketHandle, TicketIdentity, UpdateWatch, WorkshopItemDetails, WorkshopQuery,
+};
+use crate::api::callback::SteamApiCallback;
+use crate::api::consts::CSteamID;
+use crate::api::logging::steam_log;
+use crate::api::payloads::{self, CallbackPayload};
+use crate::error::catch_panic;
+use std::any::Any;
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::net::SocketAddrV4;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+use steamworks::networking_types::NetworkingIdentity;
+use steamworks::{
+    AppId, AuthSessionTicketResponse, AuthTicket, Callback, CallbackHandle, Client, ClientManager,
+    FileType, FriendFlags, FriendGame, ItemState, LobbyId, LobbyType, OverlayToStoreFlag,
+    PublishedFileId, SendType, SingleClient, SteamError, SteamId, TicketForWebApiResponse,
+    UpdateStatus, UpdateWatchHandle,
+};
+
+/// `SingleClient` 没有实现 `Send`，只能被单个线程驱动。
+/// 这里所有访问都经过互斥锁，保证同一时间只有一个线程调用 `run_callbacks`。
+struct SingleClientCell(Mutex<SingleClient>);
+
+unsafe impl Send for SingleClientCell {}
+unsafe impl Sync for SingleClientCell {}
+
+type TypedHandler<C> = Arc<dyn Fn(&C) + Send + Sync + 'static>;
+/// 类型擦除的分发器与其在 Steam 上的注册句柄
+type DispatcherEntry = (Arc<dyn Any + Send + Sync>, CallbackHandle);
+
+/// 同一种回调的所有处理函数
+///
+/// steamworks-rs 每种回调只能注册一个处理函数，后注册的会覆盖之前的。
+/// 这里每种回调只向 Steam 注册一次，再分发给所有处理函数。
+struct Dispatcher<C> {
+    handlers: Mutex<Vec<(u64, TypedHandler<C>)>>,
+}
+
+impl<C> Dispatcher<C> {
+    fn dispatch(&self, value: &C) {
+        // 复制一份再调用，处理函数中可以安全地注销自己
+        let handlers: Vec<_> = self
+            .handlers
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .map(|(_, handler)| handler.clone())
+            .collect();
+
+        for handler in handlers {
+            handler(value);
+        }
+    }
+}
+
+/// steamworks-rs 客户端后端
+pub struct SteamworksBackend {
+    client: Client,
+    /// 服务器浏览器请求的取消句柄持有弱引用，取消时与回调分发互斥
+    single: Arc<SingleClientCell>,
+    /// 对外暴露的票证句柄与 Steam 票证的映射
+    tickets: Mutex<HashMap<TicketHandle, AuthTicket>>,
+    next_ticket: Mutex<TicketHandle>,
+    /// 按回调 ID 保存的分发器与其在 Steam 上的注册句柄
+    dispatchers: Mutex<HashMap<i32, DispatcherEntry>>,
+    next_handler: AtomicU64,
+}
+
+impl SteamworksBackend {
+    pub fn new(client: Client, single: SingleClient) -> Self {
+        // Steam 只在以 -debug_steamapi 启动时输出警告信息，严重程度 0 为消息，1 为警告
+        // 该回调由 Steam 通过 C ABI 调用，panic 不能越过这里
+        client.utils().set_warning_callback(|severity, message| {
+            let _ = catch_panic("warning callback", || {
+                let message = message.to_string_lossy();
+                if severity >= 1 {
+                    steam_log!(Warn, "steam", "{}", message.trim_end());
+                } else {
+                    steam_log!(Info, "steam", "{}", message.trim_end());
+                }
+            });
+        });
+
+        Self {
+            client,
+            single: Arc::new(SingleClientCell(Mutex::new(single))),
+            tickets: Mutex::new(HashMap::new()),
+            next_ticket: Mutex::new(1),
+            dispatchers: Mutex::new(HashMap::new()),
+            next_handler: AtomicU64::new(1),
+        }
+    }
+
+    fn track_ticket(&self, ticket: AuthTicket) -> TicketHandle {
+        let mut next_ticket = self
+            .next_ticket
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let handle = *next_ticket;
+        *next_ticket = next_ticket.wrapping_add(1).max(1);
+        self.tickets
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(handle, ticket);
+        handle
+    }
+
+    /// 注册回调处理函数，同一种回调可以注册多个
+    fn subscribe<C>(&self, handler: impl Fn(&C) + Send + Sync + 'static) -> CallbackGuard
+    where
+        C: Callback + 'static,
+    {
+        let dispatcher = {
+            let mut dispatchers = self
+                .dispatchers
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner);
+            let (dispatcher, _) = dispatchers.entry(C::ID).or_insert_with(|| {
+                let dispatcher = Arc::new(Dispatcher::<C> {
+                    handlers: Mutex::new(Vec::new()),
+                });
+                let weak = Arc::downgrade(&dispatcher);
+                let handle = self.client.register_callback(move |value: C| {
+                    if let Some(dispatcher) = weak.upgrade() {
+                        dispatcher.dispatch(&value);
+                    }
+                });
+                (dispatcher, handle)
+            });
+
+            dispatcher
+                .clone()
+                .downcast::<Dispatcher<C>>()
+                .expect("callback id registered with a different type")
+        };
+
+        let id = self.next_handler.fetch_add(1, Ordering::Relaxed);
+        dispatcher
+            .handlers
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push((id, Arc::new(handler)));
+
+        let dispatcher = Arc::downgrade(&dispatcher);
+        CallbackGuard::new(move || {
+            if let Some(dispatcher) = dispatcher.upgrade() {
+                dispatcher
+                    .handlers
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .retain(|(handler_id, _)| *handler_id != id);
+            }
+        })
+    }
+
+    /// 在回调分发的间隙发起服务器浏览器请求，返回的句柄销毁时取消请求并释放回调对象
+    fn server_request(&self, start: impl FnOnce() -> Box<dyn PendingRequest>) -> CallbackGuard {
+        let pending = {
+            let _single = self.single.0.lock().unwrap_or_else(PoisonError::into_inner);
+            start()
+        };
+
+        let single = Arc::downgrade(&self.single);
+        CallbackGuard::new(move || match single.upgrade() {
+            Some(single) => {
+                let _single = single.0.lock().unwrap_or_else(PoisonError::into_inner);
+                pending.cancel();
+            }
+            // 后端已销毁时无法确认 Steam 是否仍持有回调对象，只能泄漏
+            None => std::mem::forget(pending),
+        })
+    }
+
+    fn register<C, P>(&self, handler: CallbackHandler) -> CallbackGuard
+    where
+        C: Callback + 'static,
+        P: for<'a> From<&'a C> + Into<CallbackPayload>,
+    {
+        self.subscribe(move |value: &C| handler(P::from(value).into()))
+    }
+}
+
+struct SteamUpdateWatch(UpdateWatchHandle<ClientManager>);
+
+impl UpdateWatch for SteamUpdateWatch {
+    fn progress(&self) -> (UpdateStatus, u64, u64) {
+        self.0.progress()
+    }
+}
+
+impl WorkshopItemDetails {
+    fn from_query(result: steamworks::QueryResult, preview_url: Option<String>) -> Self {
+        Self {
+            published_file_id: result.published_file_id,
+            creator_app_id: result.creator_app_id,
+            consumer_app_id: result.consumer_app_id,
+            title: result.title,
+            description: result.description,
+            owner: result.owner,
+            time_created: result.time_created,
+            time_updated: result.time_updated,
+            banned: result.banned,
+            accepted_for_use: result.accepted_for_use,
+            tags: result.tags,
+            tags_truncated: result.tags_truncated,
+            url: result.url,
+            num_upvotes: result.num_upvotes,
+            num_downvotes: result.num_downvotes,
+            num_children: result.num_children,
+            preview_url,
+        }
+    }
+}
+
+impl SteamBackend for SteamworksBackend {
+    fn run_callbacks(&self) {
+        self.single
+            .0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .run_callbacks();
+    }
+
+    fn register_callback(&self, kind: SteamApiCallback, handler: CallbackHandler) -> CallbackGuard {
+        match kind {
+            SteamApiCallback::PersonaStateChange => {
+                // 回调只携带变化的内容，当前状态需要在分发时查询
+                let client = self.client.clone();
+                self.subscribe(move |change: &steamworks::PersonaStateChange| {
+                    let state = client.friends().get_friend(change.steam_id).state();
+                    handler(CallbackPayload::PersonaStateChange(payloads::PersonaStateChangePayload {
+                        steam_id: CSteamID::from_steamid(change.steam_id),
+                        flags: change.flags.bits() as u32,
+                        state: state.into(),
+                    }))
+                })
+            }
+            SteamApiCallback::SteamServersConnected => {
+                self.register::<steamworks::SteamServersConnected, payloads::SteamServersConnectedPayload>(handler)
+            }
+            SteamApiCallback::SteamServersDisconnected => {
+                self.register::<steamworks::SteamServersDisconnected, payloads::SteamServersDisconnectedPayload>(handler)
+            }
+            SteamApiCallback::SteamServerConnectFailure => {
+                self.register::<steamworks::SteamServerConnectFailure, payloads::SteamServerConnectFailurePayload>(handler)
+            }
+            SteamApiCallback::LobbyDataUpdate => {
+                self.register::<steamworks::LobbyDataUpdate, payloads::LobbyDataUpdatePayload>(handler)
+            }
+            SteamApiCallback::LobbyChatUpdate => {
+                self.register::<steamworks::LobbyChatUpdate, payloads::LobbyChatUpdatePayload>(handler)
+            }
+            SteamApiCallback::P2PSessionRequest => {
+                self.register::<steamworks::P2PSessionRequest, payloads::P2PSessionRequestPayload>(handler)
+            }
+            SteamApiCallback::P2PSessionConnectFail => {
+                self.register::<steamworks::P2PSessionConnectFail, payloads::P2PSessionConnectFailPayload>(handler)
+            }
+            SteamApiCallback::GameLobbyJoinRequested => {
+                self.register::<steamworks::GameLobbyJoinRequested, payloads::GameLobbyJoinRequestedPayload>(handler)
+            }
+            SteamApiCallback::MicroTxnAuthorizationResponse => {
+                self.register::<steamworks::MicroTxnAuthorizationResponse, payloads::MicroTxnAuthorizationResponsePayload>(handler)
+            }
+            SteamApiCallback::UserStatsReceived => {
+                self.register::<steamworks::UserStatsReceived, payloads::UserStatsReceivedPayload>(handler)
+            }
+            SteamApiCallback::UserStatsStored => {
+                self.register::<steamworks::UserStatsStored, payloads::UserStatsStoredPayload>(handler)
+            }
+            SteamApiCallback::DownloadItemResult => {
+                self.register::<steamworks::DownloadItemResult, payloads::DownloadItemResultPayload>(handler)
+            }
+            SteamApiCallback::ItemInstalled => self.register::<ItemInstalled, payloads::ItemInstalledPayload>(handler),
+            SteamApiCallback::GameOverlayActivated => {
+                self.register::<steamworks::GameOverlayActivated, payloads::GameOverlayActivatedPayload>(handler)
+            }
+            SteamApiCallback::GameRichPresenceJoinRequested => {
+                self.register::<steamworks::GameRichPresenceJoinRequested, payloads::GameRichPresenceJoinRequestedPayload>(handler)
+            }
+            SteamApiCallback::ValidateAuthTicketResponse => {
+                self.register::<steamworks::ValidateAuthTicketResponse, payloads::ValidateAuthTicketResponsePayload>(handler)
+            }
+            SteamApiCallback::LobbyEnter => self.register::<LobbyEnter, payloads::LobbyEnterPayload>(handler),
+            SteamApiCallback::LobbyChatMsg => self.register::<LobbyChatMsg, payloads::LobbyChatMsgPayload>(handler),
+            SteamApiCallback::AvatarImageLoaded => self.register::<AvatarImageLoaded, payloads::AvatarImageLoadedPayload>(handler),
+            SteamApiCallback::FloatingGamepadTextInputDismissed => {
+                self.register::<FloatingGamepadTextInputDismissed, payloads::FloatingGamepadTextInputDismissedPayload>(handler)
+            }
+            SteamApiCallback::SteamShutdown => self.register::<SteamShutdown, payloads::SteamShutdownPayload>(handler),
+            // 会话事件由 client 模块直接分发
+            SteamApiCallback::SessionChanged => CallbackGuard::new(|| {}),
+        }
+    }
+
+    fn is_subscribed_app(&self, app_id: AppId) -> bool {
+        self.client.apps().is_subscribed_app(app_id)
+    }
+
+    fn is_dlc_installed(&self, app_id: AppId) -> bool {
+        self.client.apps().is_dlc_installed(app_id)
+    }
+
+    fn is_app_installed(&self, app_id: AppId) -> bool {
+        self.client.apps().is_app_installed(app_id)
+    }
+
+    fn is_subscribed_from_free_weekend(&self) -> bool {
+        self.client.apps().is_subscribed_from_free_weekend()
+    }
+
+    fn is_vac_banned(&self) -> bool {
+        self.client.apps().is_vac_banned()
+    }
+
+    fn is_cybercafe(&self) -> bool {
+        self.client.apps().is_cybercafe()
+    }
+
+    fn is_low_violence(&self) -> bool {
+        self.client.apps().is_low_violence()
+    }
+
+    fn is_subscribed(&self) -> bool {
+        self.client.apps().is_subscribed()
+    }
+
+    fn app_owner(&self) -> SteamId {
+        self.client.apps().app_owner()
+    }
+
+    fn available_game_languages(&self) -> Vec<String> {
+        self.client.apps().available_game_languages()
+    }
+
+    fn current_beta_name(&self) -> Option<String> {
+        self.client.apps().current_beta_name()
+    }
+
+    fn app_install_dir(&self, app_id: AppId) -> String {
+        self.client.apps().app_install_dir(app_id)
+    }
+
+    fn current_game_language(&self) -> String {
+        self.client.apps().current_game_language()
+    }
+
+    fn persona_name(&self) -> String {
+        self.client.friends().name()
+    }
+
+    fn set_rich_presence(&self, key: &str, value: Option<&str>) -> bool {
+        self.client.friends().set_rich_presence(key, value)
+    }
+
+    fn friend_game_played(&self, steam_id: SteamId) -> Option<FriendGame> {
+        self.client.friends().get_friend(steam_id).game_played()
+    }
+
+    fn friends(&self, flags: FriendFlags) -> Vec<FriendInfo> {
+        self.client
+            .friends()
+            .get_friends(flags)
+            .into_iter()
+            .map(|friend| FriendInfo {
+                steam_id: friend.id(),
+                name: friend.name(),
+                state: friend.state(),
+                has_friend: friend.has_friend(flags),
+                game: friend.game_played(),
+            })
+            .collect()
+    }
+
+    fn activate_game_overlay(&self, dialog: &str) {
+        self.client.friends().activate_game_overlay(dialog)
+    }
+
+    fn activate_game_overlay_to_user(&self, dialog: &str, steam_id: SteamId) {
+        self.client
+            .friends()
+            .activate_game_overlay_to_user(dialog, steam_id)
+    }
+
+    fn activate_invite_dialog(&self, lobby_id: LobbyId) {
+        self.client.friends().activate_invite_dialog(lobby_id)
+    }
+
+    fn activate_game_overlay_to_web_page(&self, url: &str) {
+        self.client.friends().activate_game_overlay_to_web_page(url)
+    }
+
+    fn activate_game_overlay_to_store(&self, app_id: AppId, flag: OverlayToStoreFlag) {
+        self.client
+            .friends()
+            .activate_game_overlay_to_store(app_id, flag)
+    }
+
+    fn input_init(&self) {
+        self.client.input().init(false)
+    }
+
+    fn input_shutdown(&self) {
+        self.client.input().shutdown()
+    }
+
+    fn connected_controllers(&self) -> Vec<u64> {
+        self.client.input().get_connected_controllers()
+    }
+
+    fn action_set_handle(&self, action_set_name: &str) -> u64 {
+        self.client.input().get_action_set_handle(action_set_name)
+    }
+
+    fn digital_action_handle(&self, action_name: &str) -> u64 {
+        self.client.input().get_digital_action_handle(action_name)
+    }
+
+    fn analog_action_handle(&self, action_name: &str) -> u64 {
+        self.client.input().get_analog_action_handle(action_name)
+    }
+
+    fn activate_action_set(&self, controller: u64, action_set_handle: u64) {
+        self.client
+            .input()
+            .activate_action_set_handle(controller, action_set_handle)
+    }
+
+    fn digital_action_state(&self, controller: u64, action_handle: u64) -> bool {
+        self.client
+            .input()
+            .get_digital_action_data(controller, action_handle)
+            .bState
+    }
+
+    fn analog_action_vector(&self, controller: u64, action_handle: u64) -> (f32, f32) {
+        let data = self
+            .client
+            .input()
+            .get_analog_action_data(controller, action_handle);
+        (data.x, data.y)
+    }
+
+    fn create_lobby(
+        &self,
+        lobby_type: LobbyType,
+        max_members: u32,
+        callback: ApiCallback<LobbyId>,
+    ) {
+        self.client
+            .matchmaking()
+            .create_lobby(lobby_type, max_members, callback);
+    }
+
+    fn join_lobby(&self, lobby_id: LobbyId, callback: ApiCallback<LobbyId>) {
+        // steamworks-rs 的调用结果不包含加入失败的原因，原因从 LobbyEnter 回调中读取，
+        // 两者中先到达的一方返回结果
+        let callback = Arc::new(Mutex::new(Some(callback)));
+        let enter_guard = Arc::new(Mutex::new(None::<CallbackGuard>));
+
+        let guard = {
+            let callback = callback.clone();
+            let enter_guard = enter_guard.clone();
+            self.subscribe(move |enter: &LobbyEnter| {
+                if enter.lobby != lobby_id {
+                    return;
+                }
+
+                if let Some(callback) = callback
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .take()
+                {
+                    callback(enter.result());
+                }
+                enter_guard
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .take();
+            })
+        };
+        *enter_guard.lock().unwrap_or_else(PoisonError::into_inner) = Some(guard);
+
+        self.client
+            .matchmaking()
+            .join_lobby(lobby_id, move |result| {
+                if let Some(callback) = callback
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .take()
+                {
+                    callback(result.map_err(|_| SteamError::Generic));
+                }
+                enter_guard
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .take();
+            });
+    }
+
+    fn request_lobby_list(&self, callback: ApiCallback<Vec<LobbyId>>) {
+        self.client.matchmaking().request_lobby_list(callback);
+    }
+
+    fn leave_lobby(&self, lobby_id: LobbyId) {
+        self.client.matchmaking().leave_lobby(lobby_id)
+    }
+
+    fn lobby_member_count(&self, lobby_id: LobbyId) -> usize {
+        self.client.matchmaking().lobby_member_count(lobby_id)
+    }
+
+    fn lobby_member_limit(&self, lobby_id: LobbyId) -> Option<usize> {
+        self.client.matchmaking().lobby_member_limit(lobby_id)
+    }
+
+    fn lobby_members(&self, lobby_id: LobbyId) -> Vec<SteamId> {
+        self.client.matchmaking().lobby_members(lobby_id)
+    }
+
+    fn lobby_owner(&self, lobby_id: LobbyId) -> SteamId {
+        self.client.matchmaking().lobby_owner(lobby_id)
+    }
+
+    fn set_lobby_joinable(&self, lobby_id: LobbyId, joinable: bool) -> bool {
+        self.client
+            .matchmaking()
+            .set_lobby_joinable(lobby_id, joinable)
+    }
+
+    fn lobby_data(&self, lobby_id: LobbyId, key: &str) -> Option<String> {
+        self.client
+            .matchmaking()
+            .lobby_data(lobby_id, key)
+            .map(|s| s.to_string())
+    }
+
+    fn lobby_data_count(&self, lobby_id: LobbyId) -> u32 {
+        self.client.matchmaking().lobby_data_count(lobby_id)
+    }
+
+    fn lobby_data_by_index(&self, lobby_id: LobbyId, index: u32) -> Option<(String, String)> {
+        self.client
+            .matchmaking()
+            .lobby_data_by_index(lobby_id, index)
+    }
+
+    fn set_lobby_data(&self, lobby_id: LobbyId, key: &str, value: &str) -> bool {
+        self.client
+            .matchmaking()
+            .set_lobby_data(lobby_id, key, value)
+    }
+
+    fn delete_lobby_data(&self, lobby_id: LobbyId, key: &str) -> bool {
+        self.client.matchmaking().delete_lobby_data(lobby_id, key)
+    }
+
+    fn favorite_games(&self) -> Vec<FavoriteGame> {
+        favorites::favorite_games()
+    }
+
+    fn add_favorite_game(&self, game: &FavoriteGame) -> Option<u32> {
+        favorites::add_favorite_game(game)
+    }
+
+    fn remove_favorite_game(&self, game: &FavoriteGame) -> bool {
+        favorites::remove_favorite_game(game)
+    }
+
+    fn request_server_list(
+        &self,
+        kind: ServerListKind,
+        app_id: AppId,
+        filters: Vec<(String, String)>,
+        handler: ServerListHandler,
+    ) -> CallbackGuard {
+        self.server_request(|| servers::request_server_list(kind, app_id, &filters, handler))
+    }
+
+    fn ping_server(
+        &self,
+        address: SocketAddrV4,
+        callback: ApiCallback<GameServerItem>,
+    ) -> CallbackGuard {
+        self.server_request(|| servers::ping_server(address, callback))
+    }
+
+    fn server_players(
+        &self,
+        address: SocketAddrV4,
+        callback: ApiCallback<Vec<ServerPlayer>>,
+    ) -> CallbackGuard {
+        self.server_request(|| servers::server_players(address, callback))
+    }
+
+    fn server_rules(
+        &self,
+        address: SocketAddrV4,
+        callback: ApiCallback<Vec<(String, String)>>,
+    ) -> CallbackGuard {
+        self.server_request(|| servers::server_rules(address, callback))
+    }
+
+    fn send_p2p_packet(&self, steam_id: SteamId, send_type: SendType, data: &[u8]) -> bool {
+        self.client
+            .networking()
+            .send_p2p_packet(steam_id, send_type, data)
+    }
+
+    fn is_p2p_packet_available(&self) -> Option<usize> {
+        self.client.networking().is_p2p_packet_available()
+    }
+
+    fn read_p2p_packet(&self, buffer: &mut [u8]) -> Option<(SteamId, usize)> {
+        self.client.networking().read_p2p_packet(buffer)
+    }
+
+    fn accept_p2p_session(&self, steam_id: SteamId) {
+        self.client.networking().accept_p2p_session(steam_id)
+    }
+
+    fn is_cloud_enabled_for_account(&self) -> bool {
+        self.client.remote_storage().is_cloud_enabled_for_account()
+    }
+
+    fn is_cloud_enabled_for_app(&self) -> bool {
+        self.client.remote_storage().is_cloud_enabled_for_app()
+    }
+
+    fn file_read(&self, name: &str) -> std::io::Result<Vec<u8>> {
+        let mut buf = Vec::new();
+        self.client
+            .remote_storage()
+            .file(name)
+            .read()
+            .read_to_end(&mut buf)?;
+        Ok(buf)
+    }
+
+    fn file_write(&self, name: &str, content: &[u8]) -> bool {
+        self.client
+            .remote_storage()
+            .file(name)
+            .write()
+            .write_all(content)
+            .is_ok()
+    }
+
+    fn file_delete(&self, name: &str) -> bool {
+        self.client.remote_storage().file(name).delete()
+    }
+
+    fn file_exists(&self, name: &str) -> bool {
+        self.client.remote_storage().file(name).exists()
+    }
+
+    fn item_state(&self, item_id: PublishedFileId) -> ItemState {
+        self.client.ugc().item_state(item_id)
+    }
+
+    fn create_item(&self, app_id: AppId, callback: ApiCallback<(PublishedFileId, bool)>) {
+        self.client
+            .ugc()
+            .create_item(app_id, FileType::Community, callback);
+    }
+
+    fn submit_item_update(
+        &self,
+        app_id: AppId,
+        item_id: PublishedFileId,
+        update: ItemUpdate,
+        callback: ApiCallback<(PublishedFileId, bool)>,
+    ) -> Box<dyn UpdateWatch> {
+        let mut handle = self.client.ugc().start_item_update(app_id, item_id);
+
+        if let Some(title) = update.title {
+            handle = handle.title(title.as_str());
+        }
+
+        if let Some(description) = update.description {
+            handle = handle.description(description.as_str());
+        }
+
+        if let Some(preview_path) = update.preview_path {
+            handle = handle.preview_path(Path::new(&preview_path));
+        }
+
+        if let Some(tags) = update.tags {
+            handle = handle.tags(tags);
+        }
+
+        if let Some(content_path) = update.content_path {
+            handle = handle.content_path(Path::new(&content_path));
+        }
+
+        if let Some(visibility) = update.visibility {
+            handle = handle.visibility(visibility);
+        }
+
+        let watch = handle.submit(update.change_note.as_deref(), callback);
+        Box::new(SteamUpdateWatch(watch))
+    }
+
+    fn subscribe_item(&self, item_id: PublishedFileId, callback: ApiCallback<()>) {
+        self.client.ugc().subscribe_item(item_id, callback);
+    }
+
+    fn unsubscribe_item(&self, item_id: PublishedFileId, callback: ApiCallback<()>) {
+        self.client.ugc().unsubscribe_item(item_id, callback);
+    }
+
+    fn download_item(&self, item_id: PublishedFileId, high_priority: bool) -> bool {
+        self.client.ugc().download_item(item_id, high_priority)
+    }
+
+    fn subscribed_items(&self) -> Vec<PublishedFileId> {
+        self.client.ugc().subscribed_items()
+    }
+
+    fn query_items(
+        &self,
+        items: Vec<PublishedFileId>,
+        query: WorkshopQuery,
+        callback: ApiCallback<Vec<Option<WorkshopItemDetails>>>,
+    ) -> Result<(), SteamError> {
+        let mut item_query = self
+            .client
+            .ugc()
+            .query_items(items)
+            .map_err(|_| SteamError::InvalidParameter)?;
+
+        if let Some(cached_response_max_age) = query.cached_response_max_age {
+            item_query = item_query.allow_cached_response(cached_response_max_age);
+        }
+
+        if let Some(include_metadata) = query.include_metadata {
+            item_query = item_query.include_metadata(include_metadata);
+        }
+
+        if let Some(include_long_description) = query.include_long_description {
+            item_query = item_query.include_long_desc(include_long_description);
+        }
+
+        if let Some(language) = query.language {
+            item_query = item_query.language(&language);
+        }
+
+        item_query.fetch(move |result| {
+            callback(result.map(|result| {
+                result
+                    .iter()
+                    .enumerate()
+                    .map(|(i, item)| {
+                        item.map(|item| {
+                            WorkshopItemDetails::from_query(item, result.preview_url(i as u32))
+                        })
+                    })
+                    .collect()
+            }))
+        });
+
+        Ok(())
+    }
+
+    fn item_install_info(&self, item_id: PublishedFileId) -> Option<ItemInstallInfo> {
+        self.client
+            .ugc()
+            .item_install_info(item_id)
+            .map(|install_info| ItemInstallInfo {
+                folder: install_info.folder,
+                size_on_disk: install_info.size_on_disk,
+                timestamp: install_info.timestamp,
+            })
+    }
+
+    fn item_download_info(&self, item_id: PublishedFileId) -> Option<(u64, u64)> {
+        self.client.ugc().item_download_info(item_id)
+    }
+
+    fn steam_id(&self) -> SteamId {
+        self.client.user().steam_id()
+    }
+
+    fn level(&self) -> u32 {
+        self.client.user().level()
+    }
+
+    fn auth_session_ticket(
+        &self,
+        identity: TicketIdentity,
+        callback: ApiCallback<()>,
+    ) -> (TicketHandle, Vec<u8>, CallbackGuard) {
+        let identity = match identity {
+            TicketIdentity::SteamId(steam_id) => NetworkingIdentity::new_steam_id(steam_id),
+            TicketIdentity::Ip(addr) => NetworkingIdentity::new_ip(addr),
+        };
+
+        let (ticket, data) = self.client.user().authentication_session_ticket(identity);
+        let callback = Mutex::new(Some(callback));
+
+        let guard = self.subscribe(move |response: &AuthSessionTicketResponse| {
+            if response.ticket == ticket {
+                if let Some(callback) = callback
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .take()
+                {
+                    callback(response.result);
+                }
+            }
+        });
+
+        (self.track_ticket(ticket), data, guard)
+    }
+
+    fn auth_ticket_for_web_api(
+        &self,
+        identity: &str,
+        callback: ApiCallback<Vec<u8>>,
+    ) -> (TicketHandle, CallbackGuard) {
+        let ticket = self
+            .client
+            .user()
+            .authentication_session_ticket_for_webapi(identity);
+        let callback = Mutex::new(Some(callback));
+
+        let guard = self.subscribe(move |response: &TicketForWebApiResponse| {
+            if response.ticket_handle == ticket {
+                let mut data = response.ticket.clone();
+                data.truncate(response.ticket_len as usize);
+
+                if let Some(callback) = callback
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .take()
+                {
+                    callback(response.result.map(|()| data));
+                }
+            }
+        });
+
+        (self.track_ticket(ticket), guard)
+    }
+
+    fn cancel_auth_ticket(&self, ticket: TicketHandle) {
+        if let Some(ticket) = self
+            .tickets
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(&ticket)
+        {
+            self.client.user().cancel_authentication_ticket(ticket);
+        }
+    }
+
+    fn request_current_stats(&self) {
+        self.client.user_stats().request_current_stats()
+    }
+
+    fn stat_i32(&self, name: &str) -> Option<i32> {
+        self.client.user_stats().get_stat_i32(name).ok()
+    }
+
+    fn set_stat_i32(&self, name: &str, value: i32) -> bool {
+        self.client.user_stats().set_stat_i32(name, value).is_ok()
+    }
+
+    fn achievement(&self, name: &str) -> Option<bool> {
+        self.client.user_stats().achievement(name).get().ok()
+    }
+
+    fn set_achievement(&self, name: &str) -> bool {
+        self.client.user_stats().achievement(name).set().is_ok()
+    }
+
+    fn clear_achievement(&self, name: &str) -> bool {
+        self.client.user_stats().achievement(name).clear().is_ok()
+    }
+
+    fn store_stats(&self) -> bool {
+        self.client.user_stats().store_stats().is_ok()
+    }
+
+    fn reset_all_stats(&self, achievements_too: bool) -> bool {
+        self.client
+            .user_stats()
+            .reset_all_stats(achievements_too)
+            .is_ok()
+    }
+
+    fn app_id(&self) -> AppId {
+        self.client.utils().app_id()
+    }
+
+    fn ip_country(&self) -> String {
+        self.client.utils().ip_country()
+    }
+
+    fn server_real_time(&self) -> u32 {
+        self.client.utils().get_server_real_time()
+    }
+
+    fn is_steam_running_on_steam_deck(&self) -> bool {
+        self.client.utils().is_steam_running_on_steam_deck()
+    }
+}
@@ -1,49 +1,239 @@
-use std::sync::Mutex;
-use steamworks::Client;
-use steamworks::SingleClient;
+//! Steam 客户端会话管理
+//!
+//! 每次 `init` 都会开始一个新的会话，切换 AppId 时：
+//! - 旧会话中等待结果的异步调用以 `STEAM_SESSION_CHANGED` 错误结束
+//! - 通过 `subscribe` 注册的回调会迁移到新的客户端
+//! - 注册了 `SessionChanged` 的回调收到会话切换事件
 
-lazy_static! {
-    static ref STEAM_CLIENT: Mutex<Option<Client>> = Mutex::new(None);
+use crate::api::callback::SteamApiCallback;
+use crate::api::logging::steam_log;
+use crate::api::payloads::{CallbackPayload, SessionChangedPayload};
+use crate::backend::logging::LoggingBackend;
+use crate::backend::{CallbackGuard, SteamBackend};
+use crate::error::SteamworksError;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, PoisonError, RwLock};
+use std::time::{Duration, Instant};
+use steamworks::AppId;
+use tokio::sync::{oneshot, watch};
+
+/// 等待旧客户端释放的最长时间
+const RELEASE_TIMEOUT: Duration = Duration::from_secs(2);
+
+type SharedHandler = Arc<dyn Fn(CallbackPayload) + Send + Sync + 'static>;
+
+/// 跨会话保留的回调注册
+struct Registration {
+    kind: SteamApiCallback,
+    handler: SharedHandler,
+    /// 在当前客户端上的注册句柄，没有客户端时为空
+    guard: Option<CallbackGuard>,
+}
+
+#[derive(Default)]
+struct Registrations {
+    next_id: u64,
+    entries: HashMap<u64, Registration>,
 }
 
-static mut STEAM_SINGLE: Option<SingleClient> = None;
+lazy_static! {
+    static ref STEAM_CLIENT: RwLock<Option<Arc<dyn SteamBackend>>> = RwLock::new(None);
+    static ref SESSION: watch::Sender<u32> = watch::channel(0).0;
+    static ref LAST_APP_ID: Mutex<Option<AppId>> = Mutex::new(None);
+    static ref REGISTRATIONS: Mutex<Registrations> = Mutex::new(Registrations::default());
+}
 
 pub fn has_client() -> bool {
-    STEAM_CLIENT.lock().unwrap().is_some()
+    STEAM_CLIENT
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .is_some()
 }
 
-pub fn get_client() -> Client {
-    let option = STEAM_CLIENT.lock().unwrap().to_owned();
-    option.unwrap()
+/// 获取当前 Steam 客户端，未初始化时返回 `SteamworksError::NotInitialized`
+pub fn get_client() -> Result<Arc<dyn SteamBackend>, SteamworksError> {
+    STEAM_CLIENT
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone()
+        .ok_or(SteamworksError::NotInitialized)
 }
 
-pub fn set_client(client: Client) {
-    let mut client_ref = STEAM_CLIENT.lock().unwrap();
-    *client_ref = Some(client);
+/// 获取当前会话编号与 Steam 客户端，用于需要等待结果的异步调用
+pub fn get_session() -> Result<(u32, Arc<dyn SteamBackend>), SteamworksError> {
+    let client = STEAM_CLIENT.read().unwrap_or_else(PoisonError::into_inner);
+    let session = session_id();
+    client
+        .clone()
+        .map(|client| (session, client))
+        .ok_or(SteamworksError::NotInitialized)
 }
 
-pub fn drop_client() {
-    let mut client_ref = STEAM_CLIENT.lock().unwrap();
-    *client_ref = None;
+/// 当前会话编号，每次切换客户端时递增
+pub fn session_id() -> u32 {
+    *SESSION.borrow()
 }
 
-pub fn get_single() -> &'static SingleClient {
-    unsafe {
-        match &STEAM_SINGLE {
-            Some(single) => single,
-            None => panic!("Steam single not initialized"),
+/// 等待异步调用的结果，期间会话发生切换时返回 `SteamworksError::SessionChanged`
+pub async fn session_result<T>(
+    session: u32,
+    result: oneshot::Receiver<T>,
+) -> Result<T, SteamworksError> {
+    let mut changed = SESSION.subscribe();
+    if *changed.borrow_and_update() != session {
+        return Err(SteamworksError::SessionChanged);
+    }
+
+    let result = tokio::select! {
+        result = result => result.map_err(|_| SteamworksError::SessionChanged),
+        _ = changed.changed() => Err(SteamworksError::SessionChanged),
+    };
+    if result.is_err() {
+        steam_log!(
+            Debug,
+            "session",
+            "pending call of session {} rejected",
+            session
+        );
+    }
+    result
+}
+
+/// 开始新的会话，迁移所有回调注册并发出会话切换事件
+pub fn set_client(client: Arc<dyn SteamBackend>) {
+    // 旧客户端由调用方负责先通过 drop_client 释放，这里只结束会话
+    drop(end_session());
+
+    // 先递增会话编号再发布客户端，保证 get_session 不会拿到新客户端与旧编号
+    SESSION.send_modify(|session| *session = session.wrapping_add(1));
+
+    // 所有调用与回调都经过日志后端记录
+    let client: Arc<dyn SteamBackend> = Arc::new(LoggingBackend::new(client));
+    let app_id = client.app_id();
+    *STEAM_CLIENT.write().unwrap_or_else(PoisonError::into_inner) = Some(client.clone());
+    let previous_app_id = LAST_APP_ID
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .replace(app_id);
+
+    steam_log!(
+        Info,
+        "session",
+        "session {} started for app {} (previous app {:?})",
+        session_id(),
+        app_id.0,
+        previous_app_id.map(|app_id| app_id.0)
+    );
+
+    let mut registrations = REGISTRATIONS.lock().unwrap_or_else(PoisonError::into_inner);
+    let event = CallbackPayload::from(SessionChangedPayload {
+        session: session_id(),
+        app_id: app_id.0,
+        previous_app_id: previous_app_id.map(|app_id| app_id.0),
+    });
+
+    for registration in registrations.entries.values_mut() {
+        if registration.kind == SteamApiCallback::SessionChanged {
+            (registration.handler)(event.clone());
+        } else {
+            registration.guard = Some(attach(&client, registration));
         }
     }
 }
 
-pub fn set_single(single: SingleClient) {
-    unsafe {
-        STEAM_SINGLE = Some(single);
+/// 结束当前会话并等待旧客户端释放
+pub fn drop_client() {
+    let previous = end_session();
+    if let Some(previous) = previous {
+        wait_released(previous);
+    }
+}
+
+/// 移除当前客户端，让等待中的异步调用结束并注销旧客户端上的回调
+fn end_session() -> Option<Arc<dyn SteamBackend>> {
+    let previous = STEAM_CLIENT
+        .write()
+        .unwrap_or_else(PoisonError::into_inner)
+        .take();
+
+    if previous.is_some() {
+        steam_log!(Info, "session", "session {} ended", session_id());
+        SESSION.send_modify(|session| *session = session.wrapping_add(1));
+
+        let guards: Vec<_> = REGISTRATIONS
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .entries
+            .values_mut()
+            .filter_map(|registration| registration.guard.take())
+            .collect();
+        drop(guards);
     }
+
+    previous
 }
 
-pub fn drop_single() {
-    unsafe {
-        STEAM_SINGLE = None;
+/// Steam 在客户端完全释放时才会关闭，在此之前不能初始化新的客户端。
+/// 旧会话中的异步调用与回调分发线程会在会话切换后很快释放客户端。
+fn wait_released(client: Arc<dyn SteamBackend>) {
+    let start = Instant::now();
+    while Arc::strong_count(&client) > 1 && start.elapsed() < RELEASE_TIMEOUT {
+        std::thread::sleep(Duration::from_millis(1));
+    }
+    if Arc::strong_count(&client) > 1 {
+        steam_log!(
+            Warn,
+            "session",
+            "previous client still in use after {:?}",
+            RELEASE_TIMEOUT
+        );
     }
 }
+
+fn attach(client: &Arc<dyn SteamBackend>, registration: &Registration) -> CallbackGuard {
+    let handler = registration.handler.clone();
+    client.register_callback(
+        registration.kind,
+        Box::new(move |value| {
+            // 处理函数 panic 时丢弃本次事件，不影响后续分发
+            let _ = crate::error::catch_panic("callback dispatch", || handler(value));
+        }),
+    )
+}
+
+/// 跨会话保留的回调注册句柄，销毁时自动注销
+pub struct Subscription(u64);
+
+impl Drop for Subscription {
+    fn drop(&mut self) {
+        let registration = REGISTRATIONS
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .entries
+            .remove(&self.0);
+        drop(registration);
+    }
+}
+
+/// 注册回调，切换会话后自动迁移到新的客户端
+pub fn subscribe(
+    kind: SteamApiCallback,
+    handler: impl Fn(CallbackPayload) + Send + Sync + 'static,
+) -> Result<Subscription, SteamworksError> {
+    let client = get_client()?;
+
+    let mut registration = Registration {
+        kind,
+        handler: Arc::new(handler),
+        guard: None,
+    };
+    if kind != SteamApiCallback::SessionChanged {
+        registration.guard = Some(attach(&client, &registration));
+    }
+
+    let mut registrations = REGISTRATIONS.lock().unwrap_or_else(PoisonError::into_inner);
+    registrations.next_id += 1;
+    let id = registrations.next_id;
+    registrations.entries.insert(id, registration);
+    Ok(Subscription(id))
+}
@@ -0,0 +1,374 @@
+//! 解析客户端控制台输出的一行
+//!
+//! 控制台输出没有固定格式，这里只识别连接服务器、地图、击杀信息、聊天与 CS:GO 的伤害统计，
+//! 其他行为 `Other`。TF2 会在每行前加上 `10/18/2026 - 12:34:56: ` 时间前缀，其他游戏没有。
+
+use crate::hllog::line::LogTime;
+
+/// CS:GO 在聊天中的玩家名称后插入的从左到右标记
+const NAME_MARK: char = '\u{200e}';
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConsoleEventKind {
+    /// `Connected to 1.2.3.4:27015`
+    Connected {
+        server: String,
+    },
+    /// 连接后打印的服务器信息中的 `Map: de_dust2`
+    Map {
+        map: String,
+    },
+    /// `alice killed bob with scattergun.`，TF2 的暴击在行尾有 `(crit)`
+    Kill {
+        killer: String,
+        victim: String,
+        weapon: String,
+        critical: bool,
+    },
+    /// `alice suicided.`
+    Suicide {
+        player: String,
+    },
+    /// `*DEAD*(TEAM) alice :  hello`
+    Chat {
+        player: String,
+        text: String,
+        team_only: bool,
+        dead: bool,
+        /// CS:GO 队伍聊天中的位置，`alice @ Bombsite A : hello`
+        location: Option<String>,
+    },
+    /// `Damage Given to "bob" - 27 in 1 hit`
+    DamageGiven {
+        player: String,
+        damage: u32,
+        hits: u32,
+    },
+    /// `Damage Taken from "bob" - 100 in 4 hits`
+    DamageTaken {
+        player: String,
+        damage: u32,
+        hits: u32,
+    },
+    Other,
+}
+
+/// 一行控制台输出
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConsoleLine {
+    /// 本地时间，只有 TF2 的行有
+    pub time: Option<LogTime>,
+    /// 去掉时间前缀后的内容
+    pub message: String,
+    pub kind: ConsoleEventKind,
+}
+
+impl ConsoleLine {
+    pub fn parse(line: &str) -> Self {
+        let line = line.trim_end_matches(['\r', '\n']);
+        let (time, message) = split_time(line);
+        Self {
+            time,
+            message: message.to_string(),
+            kind: parse_kind(message),
+        }
+    }
+}
+
+/// 拆出 `10/18/2026 - 12:34:56: ` 时间前缀
+fn split_time(line: &str) -> (Option<LogTime>, &str) {
+    let parsed = line
+        .starts_with(|c: char| c.is_ascii_digit())
+        .then(|| {
+            let (time, message) = line.split_once(": ")?;
+            Some((LogTime::parse(time)?, message))
+        })
+        .flatten();
+    match parsed {
+        Some((time, message)) => (Some(time), message),
+        None => (None, line),
+    }
+}
+
+fn parse_kind(message: &str) -> ConsoleEventKind {
+    if let Some(server) = message.strip_prefix("Connected to ") {
+        return ConsoleEventKind::Connected {
+            server: server.trim().to_string(),
+        };
+    }
+    if let Some(map) = message.strip_prefix("Map: ") {
+        return ConsoleEventKind::Map {
+            map: map.trim().to_string(),
+        };
+    }
+    if let Some(rest) = message.strip_prefix("Damage Given to ") {
+        if let Some((player, damage, hits)) = parse_damage(rest) {
+            return ConsoleEventKind::DamageGiven {
+                player,
+                damage,
+                hits,
+            };
+        }
+    }
+    if let Some(rest) = message.strip_prefix("Damage Taken from ") {
+        if let Some((player, damage, hits)) = parse_damage(rest) {
+            return ConsoleEventKind::DamageTaken {
+                player,
+                damage,
+                hits,
+            };
+        }
+    }
+    parse_kill(message)
+        .or_else(|| parse_chat(message))
+        .unwrap_or(ConsoleEventKind::Other)
+}
+
+/// 解析 `"bob" - 27 in 1 hit`
+fn parse_damage(text: &str) -> Option<(String, u32, u32)> {
+    let text = text.strip_prefix('"')?;
+    // 名称中可能有引号，从后往前找
+    let (player, rest) = text.rsplit_once("\" - ")?;
+    let (damage, hits) = rest.split_once(" in ")?;
+    let hits = hits
+        .strip_suffix(" hits")
+        .or_else(|| hits.strip_suffix(" hit"))?;
+    Some((
+        player.to_string(),
+        damage.trim().parse().ok()?,
+        hits.trim().parse().ok()?,
+    ))
+}
+
+fn parse_kill(message: &str) -> Option<ConsoleEventKind> {
+    let (message, critical) = match message.strip_suffix(" (crit)") {
+        Some(message) => (message, true),
+        None => (message, false),
+    };
+    let message = message.strip_suffix('.')?;
+    if let Some(player) = message.strip_suffix(" suicided") {
+        return Some(ConsoleEventKind::Suicide {
+            player: player.to_string(),
+        });
+    }
+    // 武器是不含空格的实体名，名称中可能有 " killed " 与 " with "
+    let (players, weapon) = message.rsplit_once(" with ")?;
+    if weapon.is_empty() || weapon.contains(char::is_whitespace) {
+        return None;
+    }
+    let (killer, victim) = players.split_once(" killed ")?;
+    if killer.is_empty() || victim.is_empty() {
+        return None;
+    }
+    Some(ConsoleEventKind::Kill {
+        killer: killer.to_string(),
+        victim: victim.to_string(),
+        weapon: weapon.to_string(),
+        critical,
+    })
+}
+
+fn parse_chat(message: &str) -> Option<ConsoleEventKind> {
+    if message.starts_with(char::is_whitespace) {
+        return None;
+    }
+    let mut rest = message;
+    let (mut team_only, mut dead) = (false, false);
+    loop {
+        rest = rest.trim_start();
+        if let Some(after) = rest
+            .strip_prefix("*DEAD*")
+            .or_else(|| rest.strip_prefix("*SPEC*"))
+        {
+            dead = true;
+            rest = after;
+        } else if let Some(after) = [
+            "(TEAM)",
+            "(Counter-Terrorist)",
+            "(Terrorist)",
+            "(Spectator)",
+        ]
+        .iter()
+        .find_map(|prefix| rest.strip_prefix(prefix))
+        {
+            team_only = true;
+            rest = after;
+        } else {
+            break;
+        }
+    }
+
+    let (player, location, text) = match rest.split_once(NAME_MARK) {
+        // CS:GO 用标记分隔名称，名称中的 " : " 不会造成歧义
+        Some((player, after)) => {
+            let (location, text) = after.split_once(" : ")?;
+            let location = location.trim_start().strip_prefix("@ ").map(str::trim);
+            (player, location, text)
+        }
+        // 没有标记也没有前缀时只接受 Source 聊天格式的 ` :  `，
+        // `status` 输出中的 `map     : de_dust2`、`version : 1.38` 不是聊天
+        None => {
+            let separator = if team_only || dead { " : " } else { " :  " };
+            let (player, text) = rest.split_once(separator)?;
+            (player, None, text)
+        }
+    };
+    // 聊天中的名称前后没有空白，`status` 的字段名用空格对齐
+    if player.is_empty() || player.trim() != player {
+        return None;
+    }
+    Some(ConsoleEventKind::Chat {
+        player: player.to_string(),
+        // TF2 在冒号后有两个空格
+        text: text.trim_start().to_string(),
+        team_only,
+        dead,
+        location: location.map(str::to_string),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kind(line: &str) -> ConsoleEventKind {
+        ConsoleLine::parse(line).kind
+    }
+
+    fn chat(
+        player: &str,
+        text: &str,
+        team_only: bool,
+        dead: bool,
+        location: Option<&str>,
+    ) -> ConsoleEventKind {
+        ConsoleEventKind::Chat {
+            player: player.to_string(),
+            text: text.to_string(),
+            team_only,
+            dead,
+            location: location.map(String::from),
+        }
+    }
+
+    #[test]
+    fn parses_chat() {
+        // TF2 与其他 Source 游戏
+        assert_eq!(
+            kind("alice :  hello : world"),
+            chat("alice", "hello : world", false, false, None)
+        );
+        // CS:GO 在名称后插入标记
+        assert_eq!(
+            kind("a : b\u{200e} : gg wp"),
+            chat("a : b", "gg wp", false, false, None)
+        );
+        let line = ConsoleLine::parse("10/18/2026 - 20:15:42: alice :  hi\r\n");
+        assert_eq!(line.message, "alice :  hi");
+        assert_eq!(line.time.unwrap().to_iso_string(), "2026-10-18T20:15:42");
+        assert_eq!(line.kind, chat("alice", "hi", false, false, None));
+    }
+
+    #[test]
+    fn parses_team_chat() {
+        assert_eq!(
+            kind("(TEAM) alice :  push mid"),
+            chat("alice", "push mid", true, false, None)
+        );
+        assert_eq!(
+            kind("(Counter-Terrorist) alice\u{200e} @ Bombsite A : planted"),
+            chat("alice", "planted", true, false, Some("Bombsite A"))
+        );
+        assert_eq!(
+            kind("(Terrorist) bob\u{200e} : rush b"),
+            chat("bob", "rush b", true, false, None)
+        );
+    }
+
+    #[test]
+    fn parses_dead_chat() {
+        assert_eq!(
+            kind("*DEAD* alice :  unlucky"),
+            chat("alice", "unlucky", false, true, None)
+        );
+        assert_eq!(
+            kind("*DEAD*(TEAM) alice :  he is one hp"),
+            chat("alice", "he is one hp", true, true, None)
+        );
+        assert_eq!(
+            kind("*DEAD*(Terrorist) bob\u{200e} @ T Spawn : ct long"),
+            chat("bob", "ct long", true, true, Some("T Spawn"))
+        );
+        assert_eq!(
+            kind("*SPEC* carol :  nice"),
+            chat("carol", "nice", false, true, None)
+        );
+    }
+
+    #[test]
+    fn ignores_status_output() {
+        for line in [
+            "hostname: Valve CS:GO EU West Server",
+            "version : 1.38.7.9/13879 1380/8012 secure  [G:1:2345678]",
+            "udp/ip  : 155.133.248.34:27015  (public ip: 155.133.248.34)",
+            "os      :  Linux",
+            "type    :  official dedicated",
+            "map     : de_dust2",
+            "players : 10 humans, 0 bots (10/0 max) (not hibernating)",
+            "steamid : [G:1:2345678] (85568392922345678)",
+            "edicts  : 1024 used of 2048 max",
+            "# userid name uniqueid connected ping loss state rate",
+            " : ",
+            "alice : hello",
+        ] {
+            assert_eq!(kind(line), ConsoleEventKind::Other, "{}", line);
+        }
+    }
+
+    #[test]
+    fn parses_kills_and_damage() {
+        assert_eq!(
+            kind("alice killed bob with scattergun. (crit)"),
+            ConsoleEventKind::Kill {
+                killer: "alice".to_string(),
+                victim: "bob".to_string(),
+                weapon: "scattergun".to_string(),
+                critical: true,
+            }
+        );
+        assert_eq!(
+            kind("bob suicided."),
+            ConsoleEventKind::Suicide {
+                player: "bob".to_string()
+            }
+        );
+        assert_eq!(
+            kind(r#"Damage Given to "b"ob" - 27 in 1 hit"#),
+            ConsoleEventKind::DamageGiven {
+                player: "b\"ob".to_string(),
+                damage: 27,
+                hits: 1,
+            }
+        );
+        assert_eq!(
+            kind(r#"Damage Taken from "World" - 100 in 4 hits"#),
+            ConsoleEventKind::DamageTaken {
+                player: "World".to_string(),
+                damage: 100,
+                hits: 4,
+            }
+        );
+        assert_eq!(
+            kind("Connected to 10.0.0.1:27015"),
+            ConsoleEventKind::Connected {
+                server: "10.0.0.1:27015".to_string()
+            }
+        );
+        assert_eq!(
+            kind("Map: cp_badlands"),
+            ConsoleEventKind::Map {
+                map: "cp_badlands".to_string()
+            }
+        );
+    }
+}
@@ -0,0 +1,351 @@
+//! 跟踪 `-condebug` 输出的 `console.log`
+//!
+//! 以 `-condebug` 启动的游戏把控制台输出追加到游戏目录中的 `console.log`。这里定时检查文件，
+//! 读取新增的行并解析为事件。文件被清空或被删除后重新创建时从头读取；
+//! 没有换行的最后一行会等到写完整后再报告。
+//!
+//! 重新创建的文件按创建时间、Unix 上的 inode 与文件开头的内容识别。
+//! 三者都相同且新文件不短于已读取的位置时（如不支持创建时间的文件系统上 inode 被复用，
+//! 且新文件开头与旧文件相同），无法发现文件被替换，会从旧的位置继续读取。
+
+pub mod line;
+
+use crate::api::logging::steam_log;
+use line::ConsoleLine;
+use std::fs::{File, Metadata};
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tokio::task::JoinHandle;
+
+/// 每次检查最多读取的字节数，从头读取很大的文件时分多次报告
+const MAX_READ: u64 = 4 * 1024 * 1024;
+/// 用于识别文件的开头字节数
+const HEAD_SIZE: u64 = 256;
+
+#[derive(Debug, Clone)]
+pub struct TailOptions {
+    /// 从文件开头读取，默认只读取开始跟踪后新增的行
+    pub from_start: bool,
+    /// 检查文件的间隔
+    pub poll_interval: Duration,
+}
+
+impl Default for TailOptions {
+    fn default() -> Self {
+        Self {
+            from_start: false,
+            poll_interval: Duration::from_millis(250),
+        }
+    }
+}
+
+pub type LineHandler = Arc<dyn Fn(ConsoleLine) + Send + Sync>;
+
+/// 已经读取到的位置
+struct Position {
+    offset: u64,
+    /// 文件的创建时间，变化说明文件被删除后重新创建，文件系统不支持时为空
+    created: Option<SystemTime>,
+    /// Unix 上的 inode，其他平台为空
+    inode: Option<u64>,
+    /// 文件开头最多 [`HEAD_SIZE`] 字节，内容变化说明文件被替换
+    head: Vec<u8>,
+    /// 还没有换行的部分
+    pending: Vec<u8>,
+}
+
+/// 按文件的变化读取新增的行
+pub struct Follower {
+    path: PathBuf,
+    position: Option<Position>,
+    /// 第一次检查时跳过已有的内容
+    skip_existing: bool,
+}
+
+impl Follower {
+    pub fn new(path: impl Into<PathBuf>, from_start: bool) -> Self {
+        Self {
+            path: path.into(),
+            position: None,
+            skip_existing: !from_start,
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 读取上次检查后新增的完整行
+    ///
+    /// @returns 文件不存在时返回 NotFound，之后出现的文件会从头读取
+    pub fn poll(&mut self) -> io::Result<Vec<String>> {
+        let skip_existing = std::mem::take(&mut self.skip_existing);
+        let mut file = File::open(&self.path)?;
+        let metadata = file.metadata()?;
+        let created = metadata.created().ok();
+        let inode = inode(&metadata);
+        let length = metadata.len();
+
+        let unchanged = match &self.position {
+            Some(position) => {
+                position.created == created
+                    && position.inode == inode
+                    && position.offset <= length
+                    && read_head(&mut file, position.head.len() as u64)? == position.head
+            }
+            None => false,
+        };
+        let position = match self.position.take() {
+            Some(position) if unchanged => position,
+            previous => {
+                if previous.is_some() {
+                    steam_log!(
+                        Debug,
+                        "condebug",
+                        "{} was truncated or replaced, reading from start",
+                        self.path.display()
+                    );
+                }
+                Position {
+                    offset: if skip_existing { length } else { 0 },
+                    created,
+                    inode,
+                    head: Vec::new(),
+                    pending: Vec::new(),
+                }
+            }
+        };
+        let position = self.position.insert(position);
+        // 文件只会被追加，开头的内容不变，读到足够的长度后不再更新
+        if (position.head.len() as u64) < HEAD_SIZE.min(length) {
+            position.head = read_head(&mut file, HEAD_SIZE)?;
+        }
+
+        file.seek(SeekFrom::Start(position.offset))?;
+        let mut data = std::mem::take(&mut position.pending);
+        let read = file.take(MAX_READ).read_to_end(&mut data)?;
+        position.offset += read as u64;
+
+        let complete = data
+            .iter()
+            .rposition(|&byte| byte == b'\n')
+            .map_or(0, |end| end + 1);
+        position.pending = data.split_off(complete);
+        Ok(data
+            .split(|&byte| byte == b'\n')
+            .map(|line| {
+                String::from_utf8_lossy(line)
+                    .trim_end_matches('\r')
+                    .to_string()
+            })
+            .filter(|line| !line.is_empty())
+            .collect())
+    }
+}
+
+/// 读取文件开头最多 `size` 字节
+fn read_head(file: &mut File, size: u64) -> io::Result<Vec<u8>> {
+    file.seek(SeekFrom::Start(0))?;
+    let mut head = Vec::new();
+    file.take(size).read_to_end(&mut head)?;
+    Ok(head)
+}
+
+#[cfg(unix)]
+fn inode(metadata: &Metadata) -> Option<u64> {
+    use std::os::unix::fs::MetadataExt;
+    Some(metadata.ino())
+}
+
+/// Windows 的文件编号需要不稳定的 API，只使用创建时间与文件开头
+#[cfg(not(unix))]
+fn inode(_metadata: &Metadata) -> Option<u64> {
+    None
+}
+
+/// 跟踪文件，被丢弃时停止
+pub struct Tailer {
+    path: PathBuf,
+    task: JoinHandle<()>,
+}
+
+impl Tailer {
+    /// 开始跟踪，需要在 tokio 运行时中调用，文件可以还不存在
+    pub fn start(path: impl Into<PathBuf>, options: TailOptions, handler: LineHandler) -> Self {
+        let path = path.into();
+        let follower = Follower::new(path.clone(), options.from_start);
+        let task = tokio::spawn(follow(follower, options.poll_interval, handler));
+        Self { path, task }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 停止跟踪
+    pub fn stop(&self) {
+        self.task.abort();
+    }
+
+    pub fn is_running(&self) -> bool {
+        !self.task.is_finished()
+    }
+}
+
+impl Drop for Tailer {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+async fn follow(mut follower: Follower, poll_interval: Duration, handler: LineHandler) {
+    let mut interval = tokio::time::interval(poll_interval);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    // 每种错误只记录一次，游戏没有运行时文件通常不存在
+    let mut last_error = None;
+    loop {
+        interval.tick().await;
+        match follower.poll() {
+            Ok(lines) => {
+                last_error = None;
+                for line in lines {
+                    handler(ConsoleLine::parse(&line));
+                }
+            }
+            Err(error) => {
+                if last_error != Some(error.kind()) {
+                    steam_log!(
+                        Debug,
+                        "condebug",
+                        "cannot read {}: {}",
+                        follower.path().display(),
+                        error
+                    );
+                    last_error = Some(error.kind());
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::{self, OpenOptions};
+    use std::io::Write;
+
+    /// 每个测试独立的临时文件，测试开始时不存在
+    fn temp_file(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "steamworks-condebug-{}-{}.log",
+            std::process::id(),
+            name
+        ));
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    fn append(path: &Path, data: &str) {
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .unwrap()
+            .write_all(data.as_bytes())
+            .unwrap();
+    }
+
+    #[test]
+    fn reads_appended_lines_and_waits_for_partial_line() {
+        let path = temp_file("append");
+        append(&path, "first\r\n\nsecond\npart");
+        let mut follower = Follower::new(&path, true);
+        assert_eq!(follower.poll().unwrap(), ["first", "second"]);
+        assert!(follower.poll().unwrap().is_empty());
+
+        append(&path, "ial");
+        assert!(follower.poll().unwrap().is_empty());
+        append(&path, " line\nthird\n");
+        assert_eq!(follower.poll().unwrap(), ["partial line", "third"]);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn skips_existing_lines() {
+        let path = temp_file("skip");
+        append(&path, "old\npartial old");
+        let mut follower = Follower::new(&path, false);
+        assert!(follower.poll().unwrap().is_empty());
+        append(&path, "\nnew\n");
+        // 开始跟踪时没有写完的行只报告之后的部分
+        assert_eq!(follower.poll().unwrap(), ["new"]);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn reads_file_created_after_start_from_beginning() {
+        let path = temp_file("missing");
+        let mut follower = Follower::new(&path, false);
+        let error = follower.poll().unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::NotFound);
+
+        append(&path, "hello\n");
+        assert_eq!(follower.poll().unwrap(), ["hello"]);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn rereads_truncated_file() {
+        let path = temp_file("truncate");
+        append(&path, "one\ntwo\n");
+        let mut follower = Follower::new(&path, true);
+        assert_eq!(follower.poll().unwrap(), ["one", "two"]);
+
+        fs::write(&path, "x\n").unwrap();
+        assert_eq!(follower.poll().unwrap(), ["x"]);
+        append(&path, "y\n");
+        assert_eq!(follower.poll().unwrap(), ["y"]);
+
+        // 清空后写入的内容比已读取的位置更长，按开头的内容发现
+        fs::write(&path, "rewritten\nin place\n").unwrap();
+        assert_eq!(follower.poll().unwrap(), ["rewritten", "in place"]);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn rereads_recreated_file() {
+        let path = temp_file("recreate");
+        append(&path, "before\npending");
+        let mut follower = Follower::new(&path, true);
+        assert_eq!(follower.poll().unwrap(), ["before"]);
+
+        fs::remove_file(&path).unwrap();
+        assert_eq!(follower.poll().unwrap_err().kind(), io::ErrorKind::NotFound);
+        // 新文件比旧文件长，没有写完的行被丢弃
+        append(&path, "after the game restarted\nnext\n");
+        assert_eq!(
+            follower.poll().unwrap(),
+            ["after the game restarted", "next"]
+        );
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn reads_large_file_in_chunks() {
+        let path = temp_file("large");
+        let line = format!("{}\n", "x".repeat(99));
+        let lines = MAX_READ as usize / line.len() + 57;
+        fs::write(&path, line.repeat(lines)).unwrap();
+
+        let mut follower = Follower::new(&path, true);
+        let first = follower.poll().unwrap();
+        assert_eq!(first.len(), MAX_READ as usize / line.len());
+        let second = follower.poll().unwrap();
+        assert_eq!(first.len() + second.len(), lines);
+        assert!(first.iter().chain(&second).all(|read| read.len() == 99));
+        assert!(follower.poll().unwrap().is_empty());
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,200 @@
+//! 按位读取，与引擎的 `bf_read` 相同：字节按小端排列，每个字节从最低位开始读
+
+pub struct BitReader<'a> {
+    data: &'a [u8],
+    /// 已经读取的位数
+    position: usize,
+}
+
+impl<'a> BitReader<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data, position: 0 }
+    }
+
+    /// 剩余的位数
+    pub fn remaining(&self) -> usize {
+        self.data.len() * 8 - self.position
+    }
+
+    pub fn read_bit(&mut self) -> Option<bool> {
+        let byte = *self.data.get(self.position / 8)?;
+        let bit = byte >> (self.position % 8) & 1;
+        self.position += 1;
+        Some(bit == 1)
+    }
+
+    /// 读取最多 32 位无符号整数
+    pub fn read_bits(&mut self, count: u32) -> Option<u32> {
+        debug_assert!(count <= 32);
+        if self.remaining() < count as usize {
+            return None;
+        }
+        let mut value = 0u32;
+        for index in 0..count {
+            if self.read_bit()? {
+                value |= 1 << index;
+            }
+        }
+        Some(value)
+    }
+
+    pub fn read_byte(&mut self) -> Option<u8> {
+        self.read_bits(8).map(|value| value as u8)
+    }
+
+    pub fn read_word(&mut self) -> Option<u16> {
+        self.read_bits(16).map(|value| value as u16)
+    }
+
+    pub fn read_bytes(&mut self, count: usize) -> Option<Vec<u8>> {
+        if self.remaining() < count * 8 {
+            return None;
+        }
+        if self.position.is_multiple_of(8) {
+            let start = self.position / 8;
+            self.position += count * 8;
+            return Some(self.data[start..start + count].to_vec());
+        }
+        (0..count).map(|_| self.read_byte()).collect()
+    }
+
+    /// 跳过指定的位数
+    pub fn skip(&mut self, count: usize) -> Option<()> {
+        if self.remaining() < count {
+            return None;
+        }
+        self.position += count;
+        Some(())
+    }
+
+    /// 读取以 `\0` 结尾的字符串，无效的 UTF-8 按替换字符处理
+    pub fn read_string(&mut self) -> Option<String> {
+        let mut bytes = Vec::new();
+        loop {
+            match self.read_byte()? {
+                0 => break,
+                byte => bytes.push(byte),
+            }
+        }
+        Some(String::from_utf8_lossy(&bytes).into_owned())
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// 按与 [`BitReader`] 相同的顺序写入，用于构造测试数据
+    #[derive(Default)]
+    pub(crate) struct BitWriter {
+        data: Vec<u8>,
+        bits: usize,
+    }
+
+    impl BitWriter {
+        pub(crate) fn bit(&mut self, bit: bool) -> &mut Self {
+            if self.bits.is_multiple_of(8) {
+                self.data.push(0);
+            }
+            if bit {
+                *self.data.last_mut().unwrap() |= 1 << (self.bits % 8);
+            }
+            self.bits += 1;
+            self
+        }
+
+        pub(crate) fn bits(&mut self, value: u32, count: u32) -> &mut Self {
+            for index in 0..count {
+                self.bit(value >> index & 1 == 1);
+            }
+            self
+        }
+
+        pub(crate) fn bytes(&mut self, bytes: &[u8]) -> &mut Self {
+            for &byte in bytes {
+                self.bits(u32::from(byte), 8);
+            }
+            self
+        }
+
+        pub(crate) fn string(&mut self, value: &str) -> &mut Self {
+            self.bytes(value.as_bytes()).bytes(&[0])
+        }
+
+        pub(crate) fn finish(&mut self) -> Vec<u8> {
+            self.bits = 0;
+            std::mem::take(&mut self.data)
+        }
+    }
+
+    #[test]
+    fn reads_bits_from_lowest_bit_first() {
+        let mut reader = BitReader::new(&[0b1010_1101, 0xff]);
+        assert_eq!(reader.remaining(), 16);
+        assert_eq!(reader.read_bit(), Some(true));
+        assert_eq!(reader.read_bit(), Some(false));
+        assert_eq!(reader.read_bit(), Some(true));
+        assert_eq!(reader.read_bit(), Some(true));
+        assert_eq!(reader.read_bits(4), Some(0b1010));
+        assert_eq!(reader.read_bits(8), Some(0xff));
+        assert_eq!(reader.remaining(), 0);
+        assert_eq!(reader.read_bit(), None);
+    }
+
+    #[test]
+    fn reads_values_across_byte_boundaries() {
+        let mut reader = BitReader::new(&[0x34, 0x12, 0xab, 0x78, 0x56, 0x34, 0x12]);
+        assert_eq!(reader.skip(4), Some(()));
+        assert_eq!(reader.read_bits(12), Some(0x123));
+        assert_eq!(reader.read_byte(), Some(0xab));
+        assert_eq!(reader.read_bits(32), Some(0x1234_5678));
+
+        let mut reader = BitReader::new(&[0x34, 0x12]);
+        assert_eq!(reader.read_word(), Some(0x1234));
+    }
+
+    #[test]
+    fn does_not_consume_past_the_end() {
+        let mut reader = BitReader::new(&[0xff]);
+        assert_eq!(reader.read_bits(9), None);
+        assert_eq!(reader.skip(9), None);
+        assert_eq!(reader.read_bytes(2), None);
+        assert_eq!(reader.remaining(), 8);
+        assert_eq!(reader.read_byte(), Some(0xff));
+    }
+
+    #[test]
+    fn reads_aligned_and_unaligned_bytes() {
+        let data = BitWriter::default()
+            .bytes(b"ab")
+            .bit(true)
+            .bytes(b"cde")
+            .finish();
+        let mut reader = BitReader::new(&data);
+        assert_eq!(reader.read_bytes(2).as_deref(), Some(&b"ab"[..]));
+        assert_eq!(reader.read_bit(), Some(true));
+        assert_eq!(reader.read_bytes(3).as_deref(), Some(&b"cde"[..]));
+        // 最后一个字节只剩 7 位填充
+        assert_eq!(reader.remaining(), 7);
+        assert_eq!(reader.read_bytes(1), None);
+    }
+
+    #[test]
+    fn reads_strings() {
+        let data = BitWriter::default()
+            .bit(false)
+            .string("héllo")
+            .string("")
+            .bytes(b"cut")
+            .finish();
+        let mut reader = BitReader::new(&data);
+        reader.skip(1);
+        assert_eq!(reader.read_string().as_deref(), Some("héllo"));
+        assert_eq!(reader.read_string().as_deref(), Some(""));
+        // 没有结尾的 \0
+        assert_eq!(reader.read_string(), None);
+
+        let mut reader = BitReader::new(&[b'a', 0xff, 0]);
+        assert_eq!(reader.read_string().as_deref(), Some("a\u{fffd}"));
+    }
+}
@@ -0,0 +1,299 @@
+//! CS:GO 的游戏事件
+//!
+//! 录像开头的 `svc_GameEventList` 给出每个事件的编号、名称与参数名，
+//! 之后的 `svc_GameEvent` 只包含事件编号与按顺序排列的参数值。
+
+use super::proto::{Fields, Value};
+use std::collections::HashMap;
+
+/// 事件的名称与参数名
+#[derive(Debug, Clone, PartialEq)]
+pub struct EventDescriptor {
+    pub name: String,
+    pub keys: Vec<String>,
+}
+
+/// 事件编号到描述的映射
+pub type Descriptors = HashMap<i32, EventDescriptor>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum EventValue {
+    String(String),
+    Float(f32),
+    /// long、short 与 byte
+    Int(i32),
+    Bool(bool),
+    Uint64(u64),
+}
+
+impl Default for EventValue {
+    fn default() -> Self {
+        EventValue::Int(0)
+    }
+}
+
+/// 一个游戏事件
+#[derive(Debug, Clone, PartialEq)]
+pub struct GameEvent {
+    pub name: String,
+    pub values: Vec<(String, EventValue)>,
+}
+
+impl GameEvent {
+    pub fn get(&self, key: &str) -> Option<&EventValue> {
+        self.values
+            .iter()
+            .find(|(name, _)| name == key)
+            .map(|(_, value)| value)
+    }
+
+    pub fn int(&self, key: &str) -> Option<i32> {
+        match self.get(key)? {
+            EventValue::Int(value) => Some(*value),
+            EventValue::Bool(value) => Some(i32::from(*value)),
+            _ => None,
+        }
+    }
+
+    pub fn bool(&self, key: &str) -> Option<bool> {
+        match self.get(key)? {
+            EventValue::Bool(value) => Some(*value),
+            EventValue::Int(value) => Some(*value != 0),
+            _ => None,
+        }
+    }
+
+    pub fn string(&self, key: &str) -> Option<&str> {
+        match self.get(key)? {
+            EventValue::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn uint64(&self, key: &str) -> Option<u64> {
+        match self.get(key)? {
+            EventValue::Uint64(value) => Some(*value),
+            _ => None,
+        }
+    }
+}
+
+/// 解析 `CSVCMsg_GameEventList`
+pub fn parse_event_list(data: &[u8]) -> Descriptors {
+    let mut descriptors = Descriptors::new();
+    for (field, value) in Fields::new(data) {
+        let (1, Some(descriptor)) = (field, value.as_bytes()) else {
+            continue;
+        };
+        let (mut id, mut name, mut keys) = (None, String::new(), Vec::new());
+        for (field, value) in Fields::new(descriptor) {
+            match field {
+                1 => id = value.as_i32(),
+                2 => name = value.as_string().unwrap_or_default(),
+                3 => {
+                    let key = Fields::new(value.as_bytes().unwrap_or_default())
+                        .find(|(field, _)| *field == 2)
+                        .and_then(|(_, value)| value.as_string());
+                    keys.push(key.unwrap_or_default());
+                }
+                _ => {}
+            }
+        }
+        if let Some(id) = id {
+            descriptors.insert(id, EventDescriptor { name, keys });
+        }
+    }
+    descriptors
+}
+
+/// 解析 `CSVCMsg_GameEvent`
+///
+/// @returns 事件编号不在事件列表中时为空
+pub fn parse_event(data: &[u8], descriptors: &Descriptors) -> Option<GameEvent> {
+    let mut id = None;
+    let mut values = Vec::new();
+    for (field, value) in Fields::new(data) {
+        match field {
+            2 => id = value.as_i32(),
+            3 => values.push(parse_key(value.as_bytes().unwrap_or_default())),
+            _ => {}
+        }
+    }
+    let descriptor = descriptors.get(&id?)?;
+    Some(GameEvent {
+        name: descriptor.name.clone(),
+        values: descriptor
+            .keys
+            .iter()
+            .cloned()
+            .zip(values.into_iter().map(Option::unwrap_or_default))
+            .collect(),
+    })
+}
+
+/// 解析一个参数值，字段编号由参数类型决定
+fn parse_key(data: &[u8]) -> Option<EventValue> {
+    let fields: Vec<(u32, Value)> = Fields::new(data).collect();
+    let kind = fields
+        .iter()
+        .find(|(field, _)| *field == 1)
+        .and_then(|(_, value)| value.as_i32())?;
+    // 类型 1 到 8 依次对应字段 2 到 9
+    let field = u32::try_from(kind).ok()? + 1;
+    let value = fields
+        .iter()
+        .find(|(number, _)| *number == field)
+        .map(|(_, value)| *value);
+    Some(match kind {
+        1 | 8 => EventValue::String(
+            value
+                .and_then(|value| value.as_string())
+                .unwrap_or_default(),
+        ),
+        2 => EventValue::Float(value.and_then(|value| value.as_f32()).unwrap_or_default()),
+        3..=5 => EventValue::Int(value.and_then(|value| value.as_i32()).unwrap_or_default()),
+        6 => EventValue::Bool(value.and_then(|value| value.as_bool()).unwrap_or_default()),
+        7 => EventValue::Uint64(value.and_then(|value| value.as_u64()).unwrap_or_default()),
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::demo::packet::tests::{bytes_field, float_field, varint_field};
+
+    /// CSVCMsg_GameEventList，参数类型不影响解析，都写为 string
+    pub(crate) fn event_list(events: &[(i32, &str, &[&str])]) -> Vec<u8> {
+        let mut body = Vec::new();
+        for (id, name, keys) in events {
+            let mut descriptor = Vec::new();
+            varint_field(&mut descriptor, 1, *id as u64);
+            bytes_field(&mut descriptor, 2, name.as_bytes());
+            for key in *keys {
+                let mut key_data = Vec::new();
+                varint_field(&mut key_data, 1, 1);
+                bytes_field(&mut key_data, 2, key.as_bytes());
+                bytes_field(&mut descriptor, 3, &key_data);
+            }
+            bytes_field(&mut body, 1, &descriptor);
+        }
+        body
+    }
+
+    /// CSVCMsg_GameEvent，参数值按事件列表中的顺序排列
+    pub(crate) fn game_event(id: i32, values: &[EventValue]) -> Vec<u8> {
+        let mut body = Vec::new();
+        varint_field(&mut body, 2, id as u64);
+        for value in values {
+            let mut key = Vec::new();
+            match value {
+                EventValue::String(value) => {
+                    varint_field(&mut key, 1, 1);
+                    bytes_field(&mut key, 2, value.as_bytes());
+                }
+                EventValue::Float(value) => {
+                    varint_field(&mut key, 1, 2);
+                    float_field(&mut key, 3, *value);
+                }
+                EventValue::Int(value) => {
+                    varint_field(&mut key, 1, 3);
+                    varint_field(&mut key, 4, *value as i64 as u64);
+                }
+                EventValue::Bool(value) => {
+                    varint_field(&mut key, 1, 6);
+                    varint_field(&mut key, 7, u64::from(*value));
+                }
+                EventValue::Uint64(value) => {
+                    varint_field(&mut key, 1, 7);
+                    varint_field(&mut key, 8, *value);
+                }
+            }
+            bytes_field(&mut body, 3, &key);
+        }
+        body
+    }
+
+    fn string(value: &str) -> EventValue {
+        EventValue::String(value.to_string())
+    }
+
+    #[test]
+    fn parses_event_list() {
+        let descriptors = parse_event_list(&event_list(&[
+            (24, "player_death", &["userid", "attacker", "headshot"]),
+            (42, "round_end", &[]),
+        ]));
+        assert_eq!(descriptors.len(), 2);
+        assert_eq!(
+            descriptors[&24],
+            EventDescriptor {
+                name: "player_death".to_string(),
+                keys: vec![
+                    "userid".to_string(),
+                    "attacker".to_string(),
+                    "headshot".to_string()
+                ],
+            }
+        );
+        assert_eq!(descriptors[&42].keys, Vec::<String>::new());
+    }
+
+    #[test]
+    fn parses_every_value_type() {
+        let descriptors = parse_event_list(&event_list(&[(
+            7,
+            "test",
+            &["string", "float", "int", "negative", "bool", "uint64"],
+        )]));
+        let event = parse_event(
+            &game_event(
+                7,
+                &[
+                    string("weapon_ak47"),
+                    EventValue::Float(1.5),
+                    EventValue::Int(12),
+                    EventValue::Int(-1),
+                    EventValue::Bool(true),
+                    EventValue::Uint64(76561197960265813),
+                ],
+            ),
+            &descriptors,
+        )
+        .unwrap();
+        assert_eq!(event.name, "test");
+        assert_eq!(event.string("string"), Some("weapon_ak47"));
+        assert_eq!(event.get("float"), Some(&EventValue::Float(1.5)));
+        assert_eq!(event.int("int"), Some(12));
+        assert_eq!(event.int("negative"), Some(-1));
+        assert_eq!(event.bool("bool"), Some(true));
+        assert_eq!(event.uint64("uint64"), Some(76561197960265813));
+
+        // bool 与整数可以互相读取，其他类型不能
+        assert_eq!(event.int("bool"), Some(1));
+        assert_eq!(event.bool("int"), Some(true));
+        assert_eq!(event.string("int"), None);
+        assert_eq!(event.uint64("int"), None);
+        assert_eq!(event.int("missing"), None);
+    }
+
+    #[test]
+    fn handles_unknown_events_and_values() {
+        let descriptors = parse_event_list(&event_list(&[(1, "test", &["a", "b", "c"])]));
+        assert_eq!(parse_event(&game_event(2, &[]), &descriptors), None);
+
+        // 未知的参数类型使用默认值，缺少的参数被省略
+        let mut body = game_event(1, &[string("x")]);
+        let mut key = Vec::new();
+        varint_field(&mut key, 1, 9);
+        bytes_field(&mut body, 3, &key);
+        let event = parse_event(&body, &descriptors).unwrap();
+        assert_eq!(
+            event.values,
+            [
+                ("a".to_string(), string("x")),
+                ("b".to_string(), EventValue::Int(0)),
+            ]
+        );
+    }
+}
@@ -0,0 +1,565 @@
+//! Source 1 录像（.dem）
+//!
+//! 文件以 1072 字节的 `HL2DEMO` 文件头开始，之后是按 tick 排列的帧。文件头中的时长、tick 数与帧数
+//! 在录制结束时才写入，服务器崩溃或录制中断的录像这些值为 0，需要读完所有帧才能得到。
+//!
+//! 文件头与帧适用于所有 Source 1 游戏；游戏事件只能从 CS:GO 的录像中解析，
+//! 其他游戏的网络消息是按位打包的，格式随引擎版本变化。
+
+pub mod bitbuf;
+pub mod events;
+pub mod packet;
+mod proto;
+pub mod stringtable;
+
+use packet::{DemoEvent, PacketParser, Player};
+use std::fmt;
+use std::io::{self, Read};
+
+/// 文件头大小
+pub const HEADER_SIZE: usize = 1072;
+const MAGIC: &[u8; 8] = b"HL2DEMO\0";
+/// 文件头中字符串字段的长度
+const PATH_SIZE: usize = 260;
+/// 单帧数据的最大长度，超过时视为文件损坏
+const MAX_FRAME: usize = 64 * 1024 * 1024;
+/// 每个分屏玩家的 `democmdinfo_t` 大小
+const CMD_INFO_SIZE: usize = 76;
+
+#[derive(Debug)]
+pub enum DemoError {
+    Io(io::Error),
+    /// 不是 Source 1 录像或数据损坏
+    Invalid(String),
+}
+
+impl fmt::Display for DemoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DemoError::Io(error) => write!(f, "{}", error),
+            DemoError::Invalid(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for DemoError {}
+
+impl From<io::Error> for DemoError {
+    fn from(error: io::Error) -> Self {
+        DemoError::Io(error)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DemoHeader {
+    /// 录像格式版本，CS:GO、L4D 为 4，其他游戏为 3
+    pub demo_protocol: i32,
+    /// 录制时的网络协议版本
+    pub network_protocol: i32,
+    /// 服务器名称，SourceTV 录像为服务器的地址
+    pub server_name: String,
+    /// 录制者的名称，SourceTV 录像为 `tv_name`
+    pub client_name: String,
+    pub map_name: String,
+    /// 游戏目录，如 `csgo`、`cstrike`、`tf`
+    pub game_directory: String,
+    /// 时长（秒），录制中断时为 0
+    pub playback_time: f32,
+    pub ticks: i32,
+    pub frames: i32,
+    /// 登录数据的长度
+    pub signon_length: i32,
+}
+
+impl DemoHeader {
+    pub fn parse(data: &[u8]) -> Result<Self, DemoError> {
+        if data.len() < HEADER_SIZE || !data.starts_with(MAGIC) {
+            return Err(DemoError::Invalid("not a Source demo file".to_string()));
+        }
+        let int = |offset: usize| {
+            i32::from_le_bytes(data[offset..offset + 4].try_into().unwrap_or_default())
+        };
+        let string = |offset: usize| {
+            let field = &data[offset..offset + PATH_SIZE];
+            let end = field
+                .iter()
+                .position(|&byte| byte == 0)
+                .unwrap_or(PATH_SIZE);
+            String::from_utf8_lossy(&field[..end]).into_owned()
+        };
+        let strings = 16;
+        let numbers = strings + PATH_SIZE * 4;
+        Ok(Self {
+            demo_protocol: int(8),
+            network_protocol: int(12),
+            server_name: string(strings),
+            client_name: string(strings + PATH_SIZE),
+            map_name: string(strings + PATH_SIZE * 2),
+            game_directory: string(strings + PATH_SIZE * 3),
+            playback_time: f32::from_bits(int(numbers) as u32),
+            ticks: int(numbers + 4),
+            frames: int(numbers + 8),
+            signon_length: int(numbers + 12),
+        })
+    }
+
+    /// 是否为 CS:GO 录像，只有 CS:GO 的网络消息使用 protobuf
+    pub fn is_csgo(&self) -> bool {
+        self.game_directory.eq_ignore_ascii_case("csgo")
+    }
+
+    /// 数据包帧开头的 `democmdinfo_t` 大小，每个分屏玩家一份
+    fn cmd_info_size(&self) -> Option<usize> {
+        match self.demo_protocol {
+            2 | 3 => Some(CMD_INFO_SIZE),
+            4 if self.is_csgo() => Some(CMD_INFO_SIZE * 2),
+            // L4D 系列的分屏玩家数量与 CS:GO 不同，无法确定帧的大小
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Command {
+    /// 登录阶段的网络数据包
+    SignOn,
+    /// 网络数据包
+    Packet,
+    SyncTick,
+    /// 录制者执行的控制台命令
+    ConsoleCmd,
+    UserCmd,
+    DataTables,
+    Stop,
+    CustomData,
+    StringTables,
+}
+
+/// 一帧
+#[derive(Debug, Clone)]
+pub struct Frame {
+    pub command: Command,
+    pub tick: i32,
+    /// 录制者的分屏玩家编号，只有 4 版本的录像有
+    pub player_slot: u8,
+    /// 数据包帧为网络消息，其他帧为帧的内容
+    pub data: Vec<u8>,
+}
+
+/// 读取并解析文件头
+fn read_header(reader: &mut impl Read) -> Result<DemoHeader, DemoError> {
+    let mut data = vec![0u8; HEADER_SIZE];
+    reader
+        .read_exact(&mut data)
+        .map_err(|error| match error.kind() {
+            io::ErrorKind::UnexpectedEof => {
+                DemoError::Invalid("not a Source demo file".to_string())
+            }
+            _ => DemoError::Io(error),
+        })?;
+    DemoHeader::parse(&data)
+}
+
+/// 逐帧读取录像
+pub struct DemoReader<R> {
+    reader: R,
+    header: DemoHeader,
+    cmd_info_size: usize,
+    finished: bool,
+    truncated: bool,
+}
+
+impl<R: Read> DemoReader<R> {
+    /// 读取文件头
+    ///
+    /// @returns 不是 Source 1 录像或游戏的帧格式未知时返回 Invalid
+    pub fn new(mut reader: R) -> Result<Self, DemoError> {
+        let header = read_header(&mut reader)?;
+        let cmd_info_size = header.cmd_info_size().ok_or_else(|| {
+            DemoError::Invalid(format!(
+                "unsupported demo protocol {} for {}",
+                header.demo_protocol, header.game_directory
+            ))
+        })?;
+        Ok(Self::with_header(reader, header, cmd_info_size))
+    }
+
+    /// 从已经读取文件头的位置开始读取帧
+    fn with_header(reader: R, header: DemoHeader, cmd_info_size: usize) -> Self {
+        Self {
+            reader,
+            header,
+            cmd_info_size,
+            finished: false,
+            truncated: false,
+        }
+    }
+
+    pub fn header(&self) -> &DemoHeader {
+        &self.header
+    }
+
+    /// 文件是否在 Stop 帧之前结束，录制中断的录像没有 Stop 帧
+    pub fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+
+    /// 读取一个字段，文件在这里结束时返回 false
+    fn read(&mut self, buffer: &mut [u8]) -> Result<bool, DemoError> {
+        match self.reader.read_exact(buffer) {
+            Ok(()) => Ok(true),
+            Err(error) if error.kind() == io::ErrorKind::UnexpectedEof => {
+                self.truncated = true;
+                self.finished = true;
+                Ok(false)
+            }
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    fn read_int(&mut self) -> Result<Option<i32>, DemoError> {
+        let mut buffer = [0u8; 4];
+        Ok(self.read(&mut buffer)?.then(|| i32::from_le_bytes(buffer)))
+    }
+
+    fn skip(&mut self, size: usize) -> Result<bool, DemoError> {
+        let copied = io::copy(&mut (&mut self.reader).take(size as u64), &mut io::sink())?;
+        if copied < size as u64 {
+            self.truncated = true;
+            self.finished = true;
+            return Ok(false);
+        }
+        Ok(true)
+    }
+
+    /// 读取 `int32 长度 + 数据`
+    fn read_data(&mut self) -> Result<Option<Vec<u8>>, DemoError> {
+        let Some(size) = self.read_int()? else {
+            return Ok(None);
+        };
+        let size = usize::try_from(size)
+            .ok()
+            .filter(|&size| size <= MAX_FRAME)
+            .ok_or_else(|| DemoError::Invalid(format!("invalid frame size {}", size)))?;
+        let mut data = vec![0u8; size];
+        Ok(self.read(&mut data)?.then_some(data))
+    }
+
+    /// 读取下一帧
+    ///
+    /// @returns 读到 Stop 帧或文件结束时为空
+    pub fn next_frame(&mut self) -> Result<Option<Frame>, DemoError> {
+        if self.finished {
+            return Ok(None);
+        }
+        let mut command = [0u8; 1];
+        if !self.read(&mut command)? {
+            return Ok(None);
+        }
+        let Some(tick) = self.read_int()? else {
+            return Ok(None);
+        };
+        let mut player_slot = [0u8; 1];
+        if self.header.demo_protocol >= 4 && !self.read(&mut player_slot)? {
+            return Ok(None);
+        }
+
+        let command = match (command[0], self.header.demo_protocol >= 4) {
+            (1, _) => Command::SignOn,
+            (2, _) => Command::Packet,
+            (3, _) => Command::SyncTick,
+            (4, _) => Command::ConsoleCmd,
+            (5, _) => Command::UserCmd,
+            (6, _) => Command::DataTables,
+            (7, _) => Command::Stop,
+            (8, true) => Command::CustomData,
+            (8, false) | (9, true) => Command::StringTables,
+            (other, _) => {
+                return Err(DemoError::Invalid(format!(
+                    "unknown frame command {} at tick {}",
+                    other, tick
+                )))
+            }
+        };
+
+        let data = match command {
+            Command::SignOn | Command::Packet => {
+                // democmdinfo_t、入站与出站序号
+                if !self.skip(self.cmd_info_size + 8)? {
+                    return Ok(None);
+                }
+                self.read_data()?
+            }
+            Command::SyncTick => Some(Vec::new()),
+            Command::Stop => {
+                self.finished = true;
+                Some(Vec::new())
+            }
+            Command::UserCmd | Command::CustomData => {
+                // 出站序号或自定义数据的回调编号
+                if self.read_int()?.is_none() {
+                    return Ok(None);
+                }
+                self.read_data()?
+            }
+            Command::ConsoleCmd | Command::DataTables | Command::StringTables => {
+                self.read_data()?
+            }
+        };
+        Ok(data.map(|data| Frame {
+            command,
+            tick,
+            player_slot: player_slot[0],
+            data,
+        }))
+    }
+}
+
+/// 一回合的结果
+#[derive(Debug, Clone, PartialEq)]
+pub struct RoundResult {
+    pub tick: i32,
+    /// 获胜的队伍，2 为 T，3 为 CT
+    pub winner: i32,
+    /// 结束原因，见游戏的 `CSRoundEndReason`
+    pub reason: i32,
+    /// 结束消息，如 `#SFUI_Notice_CTs_Win`
+    pub message: String,
+}
+
+/// 读完整个录像后的汇总
+#[derive(Debug, Clone)]
+pub struct Summary {
+    pub header: DemoHeader,
+    /// 最后一帧的 tick，文件头中的 tick 数为 0 时可以用它估算时长
+    pub last_tick: i32,
+    /// 每个 tick 的时长（秒），来自服务器信息，只有 CS:GO 录像有
+    pub tick_interval: Option<f32>,
+    /// 文件是否在 Stop 帧之前结束
+    pub truncated: bool,
+    /// 是否解析了游戏事件，只有 CS:GO 录像会解析
+    pub events_parsed: bool,
+    /// 录像中出现过的玩家，不含 SourceTV
+    pub players: Vec<Player>,
+    pub kills: u32,
+    pub rounds: Vec<RoundResult>,
+}
+
+impl Summary {
+    /// 时长（秒），文件头中没有时按 tick 估算
+    pub fn duration(&self) -> Option<f32> {
+        if self.header.playback_time > 0.0 {
+            return Some(self.header.playback_time);
+        }
+        self.tick_interval
+            .map(|interval| self.last_tick.max(0) as f32 * interval)
+    }
+}
+
+/// 读取整个录像
+///
+/// @param on_event 每个游戏事件按 tick 顺序调用，只有 CS:GO 录像有事件
+/// @returns 汇总，帧格式未知的游戏只有文件头
+pub fn parse<R: Read>(
+    mut reader: R,
+    mut on_event: impl FnMut(DemoEvent),
+) -> Result<Summary, DemoError> {
+    let header = read_header(&mut reader)?;
+    let cmd_info_size = header.cmd_info_size();
+    let mut summary = Summary {
+        header: header.clone(),
+        last_tick: 0,
+        tick_interval: None,
+        truncated: false,
+        events_parsed: false,
+        players: Vec::new(),
+        kills: 0,
+        rounds: Vec::new(),
+    };
+    let Some(cmd_info_size) = cmd_info_size else {
+        return Ok(summary);
+    };
+
+    let mut reader = DemoReader::with_header(reader, header, cmd_info_size);
+    let events_parsed = reader.header().is_csgo();
+    summary.events_parsed = events_parsed;
+    let mut parser = PacketParser::default();
+
+    while let Some(frame) = reader.next_frame()? {
+        summary.last_tick = summary.last_tick.max(frame.tick);
+        if !events_parsed {
+            continue;
+        }
+        let mut handle = |event: DemoEvent| {
+            match &event.kind {
+                packet::DemoEventKind::Kill { .. } => summary.kills += 1,
+                packet::DemoEventKind::RoundEnded {
+                    winner,
+                    reason,
+                    message,
+                } => summary.rounds.push(RoundResult {
+                    tick: event.tick,
+                    winner: *winner,
+                    reason: *reason,
+                    message: message.clone(),
+                }),
+                _ => {}
+            }
+            on_event(event);
+        };
+        match frame.command {
+            Command::SignOn | Command::Packet => {
+                parser.packet(frame.tick, &frame.data, &mut handle)
+            }
+            Command::StringTables => parser.string_tables(&frame.data),
+            _ => {}
+        }
+    }
+
+    summary.truncated = reader.is_truncated();
+    summary.tick_interval = parser.tick_interval();
+    summary.players = parser.into_players();
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 录制中断的 CS:GO 录像文件头，时长、tick 数与帧数为 0
+    fn header(playback_time: f32) -> Vec<u8> {
+        let mut data = vec![0u8; HEADER_SIZE];
+        data[..8].copy_from_slice(MAGIC);
+        data[8..12].copy_from_slice(&4i32.to_le_bytes());
+        data[12..16].copy_from_slice(&13881i32.to_le_bytes());
+        let strings = 16;
+        for (index, value) in ["127.0.0.1:27015", "GOTV", "de_dust2", "csgo"]
+            .into_iter()
+            .enumerate()
+        {
+            let offset = strings + PATH_SIZE * index;
+            data[offset..offset + value.len()].copy_from_slice(value.as_bytes());
+        }
+        let numbers = strings + PATH_SIZE * 4;
+        data[numbers..numbers + 4].copy_from_slice(&playback_time.to_le_bytes());
+        data
+    }
+
+    /// 4 版本录像的数据包帧
+    fn packet_frame(command: u8, tick: i32, messages: &[u8]) -> Vec<u8> {
+        let mut data = vec![command];
+        data.extend_from_slice(&tick.to_le_bytes());
+        data.push(0);
+        data.extend(vec![0u8; CMD_INFO_SIZE * 2 + 8]);
+        data.extend_from_slice(&(messages.len() as i32).to_le_bytes());
+        data.extend_from_slice(messages);
+        data
+    }
+
+    #[test]
+    fn estimates_duration_of_truncated_demo() {
+        let mut demo = header(0.0);
+        demo.extend(packet_frame(1, 0, &packet::tests::server_info(1.0 / 64.0)));
+        demo.extend(packet_frame(2, 3200, &[]));
+        demo.extend(packet_frame(2, 6400, &[]));
+        // 最后一帧写到一半
+        demo.extend_from_slice(&[2, 0x40]);
+
+        let summary = parse(demo.as_slice(), |_| {}).unwrap();
+        assert_eq!(summary.header.map_name, "de_dust2");
+        assert!(summary.header.is_csgo());
+        assert!(summary.truncated);
+        assert_eq!(summary.last_tick, 6400);
+        assert_eq!(summary.tick_interval, Some(0.015625));
+        assert_eq!(summary.duration(), Some(100.0));
+    }
+
+    #[test]
+    fn prefers_header_duration() {
+        let mut demo = header(42.5);
+        demo.extend(packet_frame(1, 0, &packet::tests::server_info(1.0 / 128.0)));
+        demo.extend_from_slice(&[7, 0, 0, 0, 0, 0]);
+
+        let summary = parse(demo.as_slice(), |_| {}).unwrap();
+        assert!(!summary.truncated);
+        assert_eq!(summary.tick_interval, Some(0.0078125));
+        assert_eq!(summary.duration(), Some(42.5));
+    }
+
+    #[test]
+    fn summarizes_game_events() {
+        let mut messages = packet::tests::event_list();
+        messages.extend(packet::tests::create_string_table(
+            stringtable::USER_INFO,
+            &packet::tests::players(),
+        ));
+        let mut demo = header(0.0);
+        demo.extend(packet_frame(1, 0, &messages));
+        let mut kills = packet::tests::death(3, 2, 0, true);
+        kills.extend(packet::tests::death(2, 3, 0, false));
+        demo.extend(packet_frame(2, 640, &kills));
+        demo.extend(packet_frame(
+            2,
+            700,
+            &packet::tests::round_end(2, 9, "#SFUI_Notice_Terrorists_Win"),
+        ));
+        demo.extend_from_slice(&[7, 0xbc, 2, 0, 0, 0]);
+
+        let mut ticks = Vec::new();
+        let summary = parse(demo.as_slice(), |event| ticks.push(event.tick)).unwrap();
+        assert_eq!(ticks, [640, 640, 700]);
+        assert!(summary.events_parsed);
+        assert!(!summary.truncated);
+        assert_eq!(summary.last_tick, 700);
+        assert_eq!(summary.kills, 2);
+        assert_eq!(
+            summary.rounds,
+            [RoundResult {
+                tick: 700,
+                winner: 2,
+                reason: 9,
+                message: "#SFUI_Notice_Terrorists_Win".to_string(),
+            }]
+        );
+        let names: Vec<&str> = summary
+            .players
+            .iter()
+            .map(|player| player.name.as_str())
+            .collect();
+        assert_eq!(names, ["alice", "bob"]);
+    }
+
+    #[test]
+    fn reads_only_header_of_unknown_frame_layout() {
+        let mut demo = header(61.5);
+        let game = 16 + PATH_SIZE * 3;
+        demo[game..game + PATH_SIZE].fill(0);
+        demo[game..game + 10].copy_from_slice(b"left4dead2");
+        // 帧格式未知，不会被读取
+        demo.extend_from_slice(&[0xff; 32]);
+
+        let summary = parse(demo.as_slice(), |_| panic!("unexpected event")).unwrap();
+        assert_eq!(summary.header.game_directory, "left4dead2");
+        assert!(!summary.events_parsed);
+        assert_eq!(summary.last_tick, 0);
+        assert_eq!(summary.duration(), Some(61.5));
+        assert!(matches!(
+            DemoReader::new(demo.as_slice()),
+            Err(DemoError::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn rejects_other_files() {
+        assert!(matches!(
+            parse(&b"HL2DEMO\0"[..], |_| {}),
+            Err(DemoError::Invalid(_))
+        ));
+        let mut demo = header(0.0);
+        demo[..8].copy_from_slice(b"PBDEMS2\0");
+        assert!(matches!(
+            parse(demo.as_slice(), |_| {}),
+            Err(DemoError::Invalid(_))
+        ));
+    }
+}
@@ -0,0 +1,577 @@
+//! 解析 CS:GO 录像数据包帧中的网络消息，生成游戏事件
+
+use super::events::{self, Descriptors, GameEvent};
+use super::proto::{self, Fields};
+use super::stringtable::{self, Entry, PlayerInfo, TableInfo};
+use std::collections::HashMap;
+
+const SVC_SERVER_INFO: u64 = 8;
+const SVC_CREATE_STRING_TABLE: u64 = 12;
+const SVC_UPDATE_STRING_TABLE: u64 = 13;
+const SVC_GAME_EVENT: u64 = 25;
+const SVC_GAME_EVENT_LIST: u64 = 30;
+
+/// 录像中的玩家
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Player {
+    /// 服务器分配的用户 ID，事件中用它指代玩家
+    pub user_id: i32,
+    pub name: String,
+    /// `STEAM_1:0:123` 或 `BOT`
+    pub steam_id: String,
+    /// SteamID64，机器人为 0
+    pub steam_id64: u64,
+    pub bot: bool,
+}
+
+impl From<&PlayerInfo> for Player {
+    fn from(info: &PlayerInfo) -> Self {
+        Self {
+            user_id: info.user_id,
+            name: info.name.clone(),
+            steam_id: info.guid.clone(),
+            steam_id64: info.xuid,
+            bot: info.fake_player,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DemoEventKind {
+    PlayerConnected {
+        player: Player,
+    },
+    PlayerDisconnected {
+        player: Player,
+        reason: String,
+    },
+    /// 击杀，被世界（摔死等）击杀时没有 attacker
+    Kill {
+        attacker: Option<Player>,
+        victim: Player,
+        assister: Option<Player>,
+        weapon: String,
+        headshot: bool,
+        /// 是否穿墙击杀
+        penetrated: bool,
+    },
+    RoundEnded {
+        /// 获胜的队伍，2 为 T，3 为 CT
+        winner: i32,
+        reason: i32,
+        message: String,
+    },
+}
+
+/// 录像中的一个事件
+#[derive(Debug, Clone, PartialEq)]
+pub struct DemoEvent {
+    pub tick: i32,
+    pub kind: DemoEventKind,
+}
+
+/// 在帧之间保存事件列表、字符串表与玩家
+#[derive(Default)]
+pub struct PacketParser {
+    descriptors: Descriptors,
+    /// 按创建顺序排列的字符串表，更新消息用序号指定表
+    tables: Vec<TableInfo>,
+    /// 以用户 ID 为键
+    players: HashMap<i32, Player>,
+    /// 按第一次出现的顺序排列的用户 ID
+    order: Vec<i32>,
+    tick_interval: Option<f32>,
+}
+
+impl PacketParser {
+    /// 服务器信息中的 tick 时长（秒）
+    pub fn tick_interval(&self) -> Option<f32> {
+        self.tick_interval
+    }
+
+    /// 出现过的玩家，按第一次出现的顺序排列
+    pub fn into_players(mut self) -> Vec<Player> {
+        self.order
+            .iter()
+            .filter_map(|user_id| self.players.remove(user_id))
+            .collect()
+    }
+
+    /// 处理数据包帧中的网络消息，消息为 `varint 类型 + varint 长度 + protobuf`
+    pub fn packet(&mut self, tick: i32, data: &[u8], on_event: &mut impl FnMut(DemoEvent)) {
+        let mut position = 0;
+        while position < data.len() {
+            let Some((command, next)) = proto::read_varint(data, position) else {
+                return;
+            };
+            let Some((size, next)) = proto::read_varint(data, next) else {
+                return;
+            };
+            let Some(message) = usize::try_from(size)
+                .ok()
+                .and_then(|size| data.get(next..next.checked_add(size)?))
+            else {
+                return;
+            };
+            position = next + message.len();
+
+            match command {
+                SVC_SERVER_INFO => self.server_info(message),
+                SVC_CREATE_STRING_TABLE => self.create_string_table(message),
+                SVC_UPDATE_STRING_TABLE => self.update_string_table(message),
+                SVC_GAME_EVENT_LIST => self.descriptors = events::parse_event_list(message),
+                SVC_GAME_EVENT => {
+                    if let Some(event) = events::parse_event(message, &self.descriptors) {
+                        if let Some(kind) = self.game_event(&event) {
+                            on_event(DemoEvent { tick, kind });
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// 处理 `dem_stringtables` 帧
+    pub fn string_tables(&mut self, data: &[u8]) {
+        for (name, entries) in stringtable::parse_snapshot(data) {
+            if name == stringtable::USER_INFO {
+                self.user_info(&entries);
+            }
+        }
+    }
+
+    fn server_info(&mut self, data: &[u8]) {
+        // float tick_interval = 14
+        if let Some((_, value)) = Fields::new(data).find(|(field, _)| *field == 14) {
+            self.tick_interval = value.as_f32().filter(|interval| *interval > 0.0);
+        }
+    }
+
+    fn create_string_table(&mut self, data: &[u8]) {
+        let mut table = TableInfo::default();
+        let (mut count, mut string_data) = (0, None);
+        for (field, value) in Fields::new(data) {
+            match field {
+                1 => table.name = value.as_string().unwrap_or_default(),
+                2 => table.max_entries = value.as_u64().unwrap_or_default() as u32,
+                3 => count = value.as_u64().unwrap_or_default() as u32,
+                4 => table.user_data_fixed_size = value.as_bool().unwrap_or_default(),
+                5 => table.user_data_size = value.as_u64().unwrap_or_default() as u32,
+                6 => table.user_data_size_bits = value.as_u64().unwrap_or_default() as u32,
+                8 => string_data = value.as_bytes(),
+                _ => {}
+            }
+        }
+        if table.name == stringtable::USER_INFO {
+            let entries = stringtable::parse_update(string_data.unwrap_or_default(), count, &table);
+            self.user_info(&entries);
+        }
+        self.tables.push(table);
+    }
+
+    fn update_string_table(&mut self, data: &[u8]) {
+        let (mut table_id, mut count, mut string_data) = (0, 0, None);
+        for (field, value) in Fields::new(data) {
+            match field {
+                1 => table_id = value.as_u64().unwrap_or_default() as usize,
+                2 => count = value.as_u64().unwrap_or_default() as u32,
+                3 => string_data = value.as_bytes(),
+                _ => {}
+            }
+        }
+        let Some(table) = self.tables.get(table_id) else {
+            return;
+        };
+        if table.name == stringtable::USER_INFO {
+            let entries = stringtable::parse_update(string_data.unwrap_or_default(), count, table);
+            self.user_info(&entries);
+        }
+    }
+
+    /// 记录 `userinfo` 表中的玩家，玩家离开后表项被清空，但仍保留玩家以便解析之后的事件
+    fn user_info(&mut self, entries: &[Entry]) {
+        for entry in entries {
+            let Some(info) = entry.user_data.as_deref().and_then(PlayerInfo::parse) else {
+                continue;
+            };
+            if info.hltv || info.user_id <= 0 {
+                continue;
+            }
+            self.remember(Player::from(&info));
+        }
+    }
+
+    fn remember(&mut self, player: Player) {
+        if !self.players.contains_key(&player.user_id) {
+            self.order.push(player.user_id);
+        }
+        self.players.insert(player.user_id, player);
+    }
+
+    /// 按用户 ID 找到玩家，找不到时只有用户 ID
+    fn player(&self, user_id: i32) -> Player {
+        self.players.get(&user_id).cloned().unwrap_or(Player {
+            user_id,
+            ..Player::default()
+        })
+    }
+
+    fn game_event(&mut self, event: &GameEvent) -> Option<DemoEventKind> {
+        match event.name.as_str() {
+            "player_connect" => {
+                let steam_id = event.string("networkid").unwrap_or_default().to_string();
+                let player = Player {
+                    user_id: event.int("userid")?,
+                    name: event.string("name").unwrap_or_default().to_string(),
+                    bot: steam_id == "BOT",
+                    steam_id,
+                    steam_id64: event.uint64("xuid").unwrap_or_default(),
+                };
+                self.remember(player.clone());
+                Some(DemoEventKind::PlayerConnected { player })
+            }
+            "player_disconnect" => {
+                let mut player = self.player(event.int("userid")?);
+                if let Some(name) = event.string("name").filter(|name| !name.is_empty()) {
+                    player.name = name.to_string();
+                }
+                Some(DemoEventKind::PlayerDisconnected {
+                    player,
+                    reason: event.string("reason").unwrap_or_default().to_string(),
+                })
+            }
+            "player_death" => {
+                let other = |key: &str| {
+                    event
+                        .int(key)
+                        .filter(|user_id| *user_id > 0)
+                        .map(|user_id| self.player(user_id))
+                };
+                let victim = self.player(event.int("userid")?);
+                let attacker = other("attacker").filter(|attacker| *attacker != victim);
+                Some(DemoEventKind::Kill {
+                    attacker,
+                    assister: other("assister"),
+                    victim,
+                    weapon: event.string("weapon").unwrap_or_default().to_string(),
+                    headshot: event.bool("headshot").unwrap_or_default(),
+                    penetrated: event.int("penetrated").unwrap_or_default() > 0,
+                })
+            }
+            "round_end" => Some(DemoEventKind::RoundEnded {
+                winner: event.int("winner").unwrap_or_default(),
+                reason: event.int("reason").unwrap_or_default(),
+                message: event.string("message").unwrap_or_default().to_string(),
+            }),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::demo::bitbuf::tests::BitWriter;
+    use crate::demo::events::EventValue;
+    use crate::demo::stringtable::tests::player_info;
+
+    fn varint(out: &mut Vec<u8>, mut value: u64) {
+        while value >= 0x80 {
+            out.push(value as u8 | 0x80);
+            value >>= 7;
+        }
+        out.push(value as u8);
+    }
+
+    /// varint 字段，负数按 64 位补码传入
+    pub(crate) fn varint_field(out: &mut Vec<u8>, field: u32, value: u64) {
+        varint(out, u64::from(field) << 3);
+        varint(out, value);
+    }
+
+    /// string、bytes 与嵌套消息字段
+    pub(crate) fn bytes_field(out: &mut Vec<u8>, field: u32, value: &[u8]) {
+        varint(out, u64::from(field) << 3 | 2);
+        varint(out, value.len() as u64);
+        out.extend_from_slice(value);
+    }
+
+    /// float 字段
+    pub(crate) fn float_field(out: &mut Vec<u8>, field: u32, value: f32) {
+        varint(out, u64::from(field) << 3 | 5);
+        out.extend_from_slice(&value.to_le_bytes());
+    }
+
+    /// 带类型与长度的网络消息
+    pub(crate) fn message(command: u64, body: &[u8]) -> Vec<u8> {
+        let mut out = Vec::new();
+        varint(&mut out, command);
+        varint(&mut out, body.len() as u64);
+        out.extend_from_slice(body);
+        out
+    }
+
+    /// CSVCMsg_ServerInfo，只包含协议版本、tick 时长、游戏目录与地图
+    pub(crate) fn server_info(tick_interval: f32) -> Vec<u8> {
+        let mut body = Vec::new();
+        // protocol = 1
+        body.extend_from_slice(&[0x08, 13]);
+        // max_clients = 11
+        body.extend_from_slice(&[0x58, 10]);
+        // tick_interval = 14
+        body.push(14 << 3 | 5);
+        body.extend_from_slice(&tick_interval.to_le_bytes());
+        // game_dir = 15、map_name = 16
+        for (field, value) in [(15u8, "csgo"), (16, "de_dust2")] {
+            body.push(field << 3 | 2);
+            body.push(value.len() as u8);
+            body.extend_from_slice(value.as_bytes());
+        }
+        message(SVC_SERVER_INFO, &body)
+    }
+
+    /// 测试用的事件列表，编号依次为 1 到 5
+    pub(crate) fn event_list() -> Vec<u8> {
+        message(
+            SVC_GAME_EVENT_LIST,
+            &events::tests::event_list(&[
+                (
+                    1,
+                    "player_connect",
+                    &["name", "userid", "networkid", "xuid"],
+                ),
+                (2, "player_disconnect", &["userid", "reason", "name"]),
+                (
+                    3,
+                    "player_death",
+                    &[
+                        "userid",
+                        "attacker",
+                        "assister",
+                        "weapon",
+                        "headshot",
+                        "penetrated",
+                    ],
+                ),
+                (4, "round_end", &["winner", "reason", "message"]),
+                (5, "player_footstep", &["userid"]),
+            ]),
+        )
+    }
+
+    pub(crate) fn game_event(id: i32, values: &[EventValue]) -> Vec<u8> {
+        message(SVC_GAME_EVENT, &events::tests::game_event(id, values))
+    }
+
+    /// player_death
+    pub(crate) fn death(victim: i32, attacker: i32, assister: i32, headshot: bool) -> Vec<u8> {
+        game_event(
+            3,
+            &[
+                EventValue::Int(victim),
+                EventValue::Int(attacker),
+                EventValue::Int(assister),
+                EventValue::String("ak47".to_string()),
+                EventValue::Bool(headshot),
+                EventValue::Int(1),
+            ],
+        )
+    }
+
+    /// round_end
+    pub(crate) fn round_end(winner: i32, reason: i32, message: &str) -> Vec<u8> {
+        game_event(
+            4,
+            &[
+                EventValue::Int(winner),
+                EventValue::Int(reason),
+                EventValue::String(message.to_string()),
+            ],
+        )
+    }
+
+    /// CSVCMsg_CreateStringTable，表项从 0 开始
+    pub(crate) fn create_string_table(name: &str, user_data: &[Vec<u8>]) -> Vec<u8> {
+        let mut body = Vec::new();
+        bytes_field(&mut body, 1, name.as_bytes());
+        varint_field(&mut body, 2, 256);
+        varint_field(&mut body, 3, user_data.len() as u64);
+        varint_field(&mut body, 4, 0);
+        bytes_field(&mut body, 8, &stringtable::tests::update(0, 8, user_data));
+        message(SVC_CREATE_STRING_TABLE, &body)
+    }
+
+    /// 测试用的玩家，alice 与 bob 在 userinfo 表中，GOTV 是 SourceTV
+    pub(crate) fn players() -> Vec<Vec<u8>> {
+        vec![
+            player_info(2, "alice", "STEAM_1:0:1", 76561197960265730, false, false),
+            player_info(3, "bob", "BOT", 0, true, false),
+            player_info(4, "GOTV", "BOT", 0, true, true),
+        ]
+    }
+
+    fn player(user_id: i32, name: &str, steam_id: &str, steam_id64: u64, bot: bool) -> Player {
+        Player {
+            user_id,
+            name: name.to_string(),
+            steam_id: steam_id.to_string(),
+            steam_id64,
+            bot,
+        }
+    }
+
+    fn parse_events(parser: &mut PacketParser, tick: i32, data: &[u8]) -> Vec<DemoEventKind> {
+        let mut kinds = Vec::new();
+        parser.packet(tick, data, &mut |event| {
+            assert_eq!(event.tick, tick);
+            kinds.push(event.kind);
+        });
+        kinds
+    }
+
+    #[test]
+    fn reports_game_events() {
+        let alice = player(2, "alice", "STEAM_1:0:1", 76561197960265730, false);
+        let bob = player(3, "bob", "BOT", 0, true);
+        let carol = player(5, "carol", "STEAM_1:1:2", 76561197960265733, false);
+
+        let mut parser = PacketParser::default();
+        let mut data = event_list();
+        data.extend(create_string_table("downloadables", &[]));
+        data.extend(create_string_table(stringtable::USER_INFO, &players()));
+        assert_eq!(parse_events(&mut parser, 0, &data), []);
+
+        let mut data = game_event(
+            1,
+            &[
+                EventValue::String("carol".to_string()),
+                EventValue::Int(5),
+                EventValue::String("STEAM_1:1:2".to_string()),
+                EventValue::Uint64(76561197960265733),
+            ],
+        );
+        data.extend(death(3, 2, 5, true));
+        data.extend(game_event(5, &[EventValue::Int(2)]));
+        assert_eq!(
+            parse_events(&mut parser, 100, &data),
+            [
+                DemoEventKind::PlayerConnected {
+                    player: carol.clone(),
+                },
+                DemoEventKind::Kill {
+                    attacker: Some(alice.clone()),
+                    victim: bob.clone(),
+                    assister: Some(carol.clone()),
+                    weapon: "ak47".to_string(),
+                    headshot: true,
+                    penetrated: true,
+                },
+            ]
+        );
+
+        // 自杀没有 attacker，不认识的用户 ID 只有编号
+        let mut data = death(2, 2, 0, false);
+        data.extend(death(3, 9, 0, false));
+        let kills = parse_events(&mut parser, 200, &data);
+        assert!(matches!(
+            &kills[0],
+            DemoEventKind::Kill {
+                attacker: None,
+                assister: None,
+                victim,
+                headshot: false,
+                ..
+            } if *victim == alice
+        ));
+        assert!(matches!(
+            &kills[1],
+            DemoEventKind::Kill {
+                attacker: Some(Player { user_id: 9, name, .. }),
+                ..
+            } if name.is_empty()
+        ));
+
+        let mut data = round_end(3, 8, "#SFUI_Notice_CTs_Win");
+        data.extend(game_event(
+            2,
+            &[
+                EventValue::Int(2),
+                EventValue::String("Disconnect".to_string()),
+                EventValue::String(String::new()),
+            ],
+        ));
+        assert_eq!(
+            parse_events(&mut parser, 300, &data),
+            [
+                DemoEventKind::RoundEnded {
+                    winner: 3,
+                    reason: 8,
+                    message: "#SFUI_Notice_CTs_Win".to_string(),
+                },
+                DemoEventKind::PlayerDisconnected {
+                    player: alice.clone(),
+                    reason: "Disconnect".to_string(),
+                },
+            ]
+        );
+        assert_eq!(parser.into_players(), [alice, bob, carol]);
+    }
+
+    #[test]
+    fn reads_players_from_string_table_updates() {
+        let dave = player_info(6, "dave", "STEAM_1:0:3", 76561197960265734, false, false);
+        let mut parser = PacketParser::default();
+        let mut data = create_string_table("downloadables", &[]);
+        data.extend(create_string_table(stringtable::USER_INFO, &players()[..1]));
+        // 更新 userinfo 表，它是第二个创建的表
+        let mut body = Vec::new();
+        varint_field(&mut body, 1, 1);
+        varint_field(&mut body, 2, 1);
+        bytes_field(&mut body, 3, &stringtable::tests::update(3, 8, &[dave]));
+        data.extend(message(SVC_UPDATE_STRING_TABLE, &body));
+        // 不存在的表被忽略
+        data.extend(message(SVC_UPDATE_STRING_TABLE, &[0x08, 5]));
+        parser.packet(0, &data, &mut |_| {});
+
+        let names: Vec<String> = parser.into_players().into_iter().map(|p| p.name).collect();
+        assert_eq!(names, ["alice", "dave"]);
+    }
+
+    #[test]
+    fn reads_players_from_string_table_snapshot() {
+        let info = player_info(7, "erin", "STEAM_1:1:4", 76561197960265737, false, false);
+        let data = BitWriter::default()
+            .bytes(&[1])
+            .string(stringtable::USER_INFO)
+            .bits(1, 16)
+            .string("0")
+            .bit(true)
+            .bits(info.len() as u32, 16)
+            .bytes(&info)
+            .bit(false)
+            .finish();
+        let mut parser = PacketParser::default();
+        parser.string_tables(&data);
+        assert_eq!(
+            parser.into_players(),
+            [player(7, "erin", "STEAM_1:1:4", 76561197960265737, false)]
+        );
+    }
+
+    #[test]
+    fn reads_tick_interval_from_server_info() {
+        let mut parser = PacketParser::default();
+        let mut data = message(99, b"skipped");
+        data.extend(server_info(1.0 / 64.0));
+        parser.packet(0, &data, &mut |_| panic!("unexpected event"));
+        assert_eq!(parser.tick_interval(), Some(0.015625));
+
+        // 没有 tick 时长或时长无效时为空
+        let mut parser = PacketParser::default();
+        parser.packet(0, &server_info(0.0), &mut |_| {});
+        assert_eq!(parser.tick_interval(), None);
+        parser.packet(0, &message(SVC_SERVER_INFO, &[0x08, 13]), &mut |_| {});
+        assert_eq!(parser.tick_interval(), None);
+    }
+}
@@ -0,0 +1,118 @@
+//! 最小的 protobuf 解码，只用于读取 CS:GO 网络消息中需要的字段
+//!
+//! CS:GO 的网络消息是 `varint 类型 + varint 长度 + protobuf` 的序列，
+//! 这里不生成消息类型，按字段编号读取。
+
+/// 一个字段的值
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Value<'a> {
+    Varint(u64),
+    Fixed64(u64),
+    Bytes(&'a [u8]),
+    Fixed32(u32),
+}
+
+impl<'a> Value<'a> {
+    pub fn as_u64(&self) -> Option<u64> {
+        match *self {
+            Value::Varint(value) | Value::Fixed64(value) => Some(value),
+            Value::Fixed32(value) => Some(value as u64),
+            Value::Bytes(_) => None,
+        }
+    }
+
+    /// int32 字段，负数按 64 位补码编码
+    pub fn as_i32(&self) -> Option<i32> {
+        self.as_u64().map(|value| value as i32)
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        self.as_u64().map(|value| value != 0)
+    }
+
+    /// float 字段
+    pub fn as_f32(&self) -> Option<f32> {
+        match *self {
+            Value::Fixed32(value) => Some(f32::from_bits(value)),
+            _ => None,
+        }
+    }
+
+    pub fn as_bytes(&self) -> Option<&'a [u8]> {
+        match *self {
+            Value::Bytes(bytes) => Some(bytes),
+            _ => None,
+        }
+    }
+
+    /// string 字段，无效的 UTF-8 按替换字符处理
+    pub fn as_string(&self) -> Option<String> {
+        self.as_bytes()
+            .map(|bytes| String::from_utf8_lossy(bytes).into_owned())
+    }
+}
+
+/// 读取 varint
+///
+/// @returns 值与读取后的位置
+pub fn read_varint(data: &[u8], mut position: usize) -> Option<(u64, usize)> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let byte = *data.get(position)?;
+        position += 1;
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Some((value, position));
+        }
+    }
+    None
+}
+
+/// 按顺序遍历消息中的字段，遇到无效数据时结束
+pub struct Fields<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Fields<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data, position: 0 }
+    }
+
+    fn take(&mut self, size: usize) -> Option<&'a [u8]> {
+        let end = self.position.checked_add(size)?;
+        let bytes = self.data.get(self.position..end)?;
+        self.position = end;
+        Some(bytes)
+    }
+}
+
+impl<'a> Iterator for Fields<'a> {
+    type Item = (u32, Value<'a>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (key, position) = read_varint(self.data, self.position)?;
+        self.position = position;
+        let field = (key >> 3) as u32;
+        let value = match key & 7 {
+            0 => {
+                let (value, position) = read_varint(self.data, self.position)?;
+                self.position = position;
+                Value::Varint(value)
+            }
+            1 => Value::Fixed64(u64::from_le_bytes(self.take(8)?.try_into().ok()?)),
+            2 => {
+                let (size, position) = read_varint(self.data, self.position)?;
+                self.position = position;
+                Value::Bytes(self.take(usize::try_from(size).ok()?)?)
+            }
+            5 => Value::Fixed32(u32::from_le_bytes(self.take(4)?.try_into().ok()?)),
+            // 分组（3、4）在 proto2 中已经废弃，网络消息中不会出现
+            _ => {
+                self.position = self.data.len();
+                return None;
+            }
+        };
+        Some((field, value))
+    }
+}
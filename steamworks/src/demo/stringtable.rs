@@ -0,0 +1,422 @@
+//! 字符串表
+//!
+//! 服务器通过字符串表同步模型、声音与玩家信息，这里只关心 `userinfo` 表，
+//! 它的每一项对应一个玩家槽位，附带的数据是 `player_info_t`。
+
+use super::bitbuf::BitReader;
+
+/// `userinfo` 表的名称
+pub const USER_INFO: &str = "userinfo";
+/// 前缀复用时记住的最近字符串数量
+const HISTORY_SIZE: usize = 32;
+/// 非固定长度的附带数据的长度位数
+const USER_DATA_BITS: u32 = 14;
+
+/// 创建表时给出的参数，之后的更新需要用到
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TableInfo {
+    pub name: String,
+    pub max_entries: u32,
+    pub user_data_fixed_size: bool,
+    pub user_data_size: u32,
+    pub user_data_size_bits: u32,
+}
+
+/// 表中的一项
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    pub index: u32,
+    pub name: String,
+    pub user_data: Option<Vec<u8>>,
+}
+
+/// 解析 `svc_CreateStringTable` 与 `svc_UpdateStringTable` 中的表项
+///
+/// @param count 表项数量
+/// @returns 解析到的表项，数据无效时只返回之前的部分
+pub fn parse_update(data: &[u8], count: u32, table: &TableInfo) -> Vec<Entry> {
+    let mut reader = BitReader::new(data);
+    let mut entries = Vec::new();
+    let index_bits = table.max_entries.max(1).ilog2();
+    let mut history: Vec<String> = Vec::new();
+    let mut last_index: i64 = -1;
+
+    // 使用字典压缩的表无法解码，CS:GO 服务器不会这样发送
+    if reader.read_bit() != Some(false) {
+        return entries;
+    }
+    for _ in 0..count {
+        let Some(entry) = parse_entry(&mut reader, table, index_bits, last_index, &history) else {
+            break;
+        };
+        last_index = i64::from(entry.index);
+        if history.len() == HISTORY_SIZE {
+            history.remove(0);
+        }
+        history.push(entry.name.clone());
+        entries.push(entry);
+    }
+    entries
+}
+
+fn parse_entry(
+    reader: &mut BitReader,
+    table: &TableInfo,
+    index_bits: u32,
+    last_index: i64,
+    history: &[String],
+) -> Option<Entry> {
+    let index = if reader.read_bit()? {
+        u32::try_from(last_index + 1).ok()?
+    } else {
+        reader.read_bits(index_bits)?
+    };
+    if index >= table.max_entries {
+        return None;
+    }
+
+    let mut name = String::new();
+    if reader.read_bit()? {
+        if reader.read_bit()? {
+            // 复用最近某个字符串的前缀
+            let previous = history.get(reader.read_bits(5)? as usize)?;
+            let length = (reader.read_bits(5)? as usize).min(previous.len());
+            name = String::from_utf8_lossy(&previous.as_bytes()[..length]).into_owned();
+        }
+        name.push_str(&reader.read_string()?);
+    }
+
+    let user_data = if reader.read_bit()? {
+        if table.user_data_fixed_size {
+            let bits = table.user_data_size_bits;
+            let mut data: Vec<u8> = (0..bits / 8)
+                .map(|_| reader.read_byte())
+                .collect::<Option<_>>()?;
+            if !bits.is_multiple_of(8) {
+                data.push(reader.read_bits(bits % 8)? as u8);
+            }
+            Some(data)
+        } else {
+            let size = reader.read_bits(USER_DATA_BITS)? as usize;
+            Some(reader.read_bytes(size)?)
+        }
+    } else {
+        None
+    };
+    Some(Entry {
+        index,
+        name,
+        user_data,
+    })
+}
+
+/// 解析 `dem_stringtables` 帧，即录像开始时所有表的完整内容
+///
+/// @returns 表名与服务器端的表项
+pub fn parse_snapshot(data: &[u8]) -> Vec<(String, Vec<Entry>)> {
+    let mut reader = BitReader::new(data);
+    let mut tables = Vec::new();
+    let Some(count) = reader.read_byte() else {
+        return tables;
+    };
+    for _ in 0..count {
+        let Some(table) = parse_snapshot_table(&mut reader) else {
+            break;
+        };
+        tables.push(table);
+    }
+    tables
+}
+
+fn parse_snapshot_table(reader: &mut BitReader) -> Option<(String, Vec<Entry>)> {
+    let name = reader.read_string()?;
+    let mut entries = Vec::new();
+    for index in 0..u32::from(reader.read_word()?) {
+        let entry = reader.read_string()?;
+        let user_data = if reader.read_bit()? {
+            let size = reader.read_word()? as usize;
+            Some(reader.read_bytes(size)?)
+        } else {
+            None
+        };
+        entries.push(Entry {
+            index,
+            name: entry,
+            user_data,
+        });
+    }
+    // 客户端的表项，不需要
+    if reader.read_bit()? {
+        for _ in 0..reader.read_word()? {
+            reader.read_string()?;
+            if reader.read_bit()? {
+                let size = reader.read_word()? as usize;
+                reader.skip(size * 8)?;
+            }
+        }
+    }
+    Some((name, entries))
+}
+
+/// `player_info_t`，整数按大端序存储
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlayerInfo {
+    /// SteamID64，机器人为 0
+    pub xuid: u64,
+    pub name: String,
+    pub user_id: i32,
+    /// `STEAM_1:0:123` 或 `BOT`
+    pub guid: String,
+    pub fake_player: bool,
+    /// 是否为 SourceTV
+    pub hltv: bool,
+}
+
+impl PlayerInfo {
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        let string = |range: std::ops::Range<usize>| {
+            let field = data.get(range)?;
+            let end = field
+                .iter()
+                .position(|&byte| byte == 0)
+                .unwrap_or(field.len());
+            Some(String::from_utf8_lossy(&field[..end]).into_owned())
+        };
+        Some(Self {
+            xuid: u64::from_be_bytes(data.get(8..16)?.try_into().ok()?),
+            name: string(16..144)?,
+            user_id: i32::from_be_bytes(data.get(144..148)?.try_into().ok()?),
+            guid: string(148..181)?,
+            fake_player: *data.get(316)? != 0,
+            hltv: *data.get(317)? != 0,
+        })
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::demo::bitbuf::tests::BitWriter;
+
+    /// `player_info_t`
+    pub(crate) fn player_info(
+        user_id: i32,
+        name: &str,
+        guid: &str,
+        xuid: u64,
+        fake_player: bool,
+        hltv: bool,
+    ) -> Vec<u8> {
+        let mut data = vec![0u8; 340];
+        data[8..16].copy_from_slice(&xuid.to_be_bytes());
+        data[16..16 + name.len()].copy_from_slice(name.as_bytes());
+        data[144..148].copy_from_slice(&user_id.to_be_bytes());
+        data[148..148 + guid.len()].copy_from_slice(guid.as_bytes());
+        data[316] = u8::from(fake_player);
+        data[317] = u8::from(hltv);
+        data
+    }
+
+    /// 从 `first` 开始连续编号的表项，名称为编号，附带数据不固定长度
+    pub(crate) fn update(first: u32, index_bits: u32, user_data: &[Vec<u8>]) -> Vec<u8> {
+        let mut writer = BitWriter::default();
+        writer.bit(false);
+        for (offset, data) in user_data.iter().enumerate() {
+            if offset == 0 {
+                writer.bit(false).bits(first, index_bits);
+            } else {
+                writer.bit(true);
+            }
+            let index = first + offset as u32;
+            writer.bit(true).bit(false).string(&index.to_string());
+            writer
+                .bit(true)
+                .bits(data.len() as u32, USER_DATA_BITS)
+                .bytes(data);
+        }
+        writer.finish()
+    }
+
+    fn table(max_entries: u32) -> TableInfo {
+        TableInfo {
+            name: USER_INFO.to_string(),
+            max_entries,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn parses_update_entries() {
+        let data = BitWriter::default()
+            .bit(false)
+            // 连续编号 0，名称与 3 字节的数据
+            .bit(true)
+            .bit(true)
+            .bit(false)
+            .string("alpha")
+            .bit(true)
+            .bits(3, USER_DATA_BITS)
+            .bytes(&[1, 2, 3])
+            // 编号 5，复用 alpha 的前 3 个字符
+            .bit(false)
+            .bits(5, 8)
+            .bit(true)
+            .bit(true)
+            .bits(0, 5)
+            .bits(3, 5)
+            .string("ine")
+            .bit(false)
+            // 连续编号 6，没有名称与数据
+            .bit(true)
+            .bit(false)
+            .bit(false)
+            .finish();
+
+        let entries = parse_update(&data, 3, &table(256));
+        assert_eq!(
+            entries,
+            [
+                Entry {
+                    index: 0,
+                    name: "alpha".to_string(),
+                    user_data: Some(vec![1, 2, 3]),
+                },
+                Entry {
+                    index: 5,
+                    name: "alpine".to_string(),
+                    user_data: None,
+                },
+                Entry {
+                    index: 6,
+                    name: String::new(),
+                    user_data: None,
+                },
+            ]
+        );
+        // 数据不完整时只返回之前的表项
+        assert_eq!(parse_update(&data, 4, &table(256)), entries);
+        assert_eq!(parse_update(&data[..12], 3, &table(256)).len(), 1);
+    }
+
+    #[test]
+    fn parses_fixed_size_user_data() {
+        let table = TableInfo {
+            user_data_fixed_size: true,
+            user_data_size: 2,
+            user_data_size_bits: 12,
+            ..table(4)
+        };
+        let data = BitWriter::default()
+            .bit(false)
+            .bit(true)
+            .bit(false)
+            .bit(true)
+            .bytes(&[0xab])
+            .bits(0xc, 4)
+            .finish();
+        assert_eq!(
+            parse_update(&data, 1, &table),
+            [Entry {
+                index: 0,
+                name: String::new(),
+                user_data: Some(vec![0xab, 0xc]),
+            }]
+        );
+    }
+
+    #[test]
+    fn stops_at_invalid_entries() {
+        // 使用字典压缩
+        assert_eq!(parse_update(&[1], 1, &table(4)), []);
+
+        // 编号 3 之后的连续编号超出表的大小
+        let data = BitWriter::default()
+            .bit(false)
+            .bit(false)
+            .bits(3, 2)
+            .bit(false)
+            .bit(false)
+            .bit(true)
+            .bit(false)
+            .bit(false)
+            .finish();
+        let entries = parse_update(&data, 2, &table(4));
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].index, 3);
+    }
+
+    #[test]
+    fn parses_update_helper() {
+        let players = [player_info(2, "alice", "STEAM_1:0:1", 1, false, false)];
+        let entries = parse_update(&update(7, 8, &players), 1, &table(256));
+        assert_eq!(entries[0].index, 7);
+        assert_eq!(entries[0].name, "7");
+        assert_eq!(entries[0].user_data.as_deref(), Some(&players[0][..]));
+    }
+
+    #[test]
+    fn parses_snapshot() {
+        let info = player_info(3, "bob", "BOT", 0, true, false);
+        let data = BitWriter::default()
+            .bytes(&[2])
+            .string("downloadables")
+            .bits(0, 16)
+            .bit(false)
+            .string(USER_INFO)
+            .bits(2, 16)
+            .string("0")
+            .bit(false)
+            .string("1")
+            .bit(true)
+            .bits(info.len() as u32, 16)
+            .bytes(&info)
+            // 客户端表项被跳过
+            .bit(true)
+            .bits(1, 16)
+            .string("client")
+            .bit(true)
+            .bits(2, 16)
+            .bytes(&[9, 9])
+            .finish();
+
+        let tables = parse_snapshot(&data);
+        assert_eq!(tables.len(), 2);
+        assert_eq!(tables[0], ("downloadables".to_string(), Vec::new()));
+        assert_eq!(tables[1].0, USER_INFO);
+        assert_eq!(
+            tables[1].1,
+            [
+                Entry {
+                    index: 0,
+                    name: "0".to_string(),
+                    user_data: None,
+                },
+                Entry {
+                    index: 1,
+                    name: "1".to_string(),
+                    user_data: Some(info),
+                },
+            ]
+        );
+        // 不完整的表被丢弃
+        assert_eq!(parse_snapshot(&data[..data.len() - 2]).len(), 1);
+        assert_eq!(parse_snapshot(&[]), []);
+    }
+
+    #[test]
+    fn parses_player_info() {
+        let data = player_info(7, "Ünicode", "STEAM_1:1:42", 76561197960265813, false, true);
+        assert_eq!(
+            PlayerInfo::parse(&data),
+            Some(PlayerInfo {
+                xuid: 76561197960265813,
+                name: "Ünicode".to_string(),
+                user_id: 7,
+                guid: "STEAM_1:1:42".to_string(),
+                fake_player: false,
+                hltv: true,
+            })
+        );
+        assert_eq!(PlayerInfo::parse(&data[..317]), None);
+    }
+}
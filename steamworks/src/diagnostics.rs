@@ -0,0 +1,120 @@
+use napi_derive::napi;
+use std::fs::OpenOptions;
+use std::path::{Path, PathBuf};
+
+/// Steam 动态库文件名
+#[cfg(all(windows, target_pointer_width = "64"))]
+const STEAM_API_LIBRARY: &str = "steam_api64.dll";
+#[cfg(all(windows, target_pointer_width = "32"))]
+const STEAM_API_LIBRARY: &str = "steam_api.dll";
+#[cfg(not(any(windows, target_os = "macos")))]
+const STEAM_API_LIBRARY: &str = "libsteam_api.so";
+#[cfg(target_os = "macos")]
+const STEAM_API_LIBRARY: &str = "libsteam_api.dylib";
+
+#[napi(object)]
+/// Steam 初始化诊断报告
+pub struct InitDiagnostics {
+    /// 是否初始化成功
+    pub initialized: bool,
+    /// 初始化失败时 Steam 返回的错误信息
+    pub error: Option<String>,
+    /// Steam 客户端进程是否正在运行
+    pub steam_running: bool,
+    /// Steam 动态库是否已加载，插件与动态库一同加载，能生成报告时总是为 true
+    pub library_loaded: bool,
+    /// 在程序目录或工作目录中找到的 Steam 动态库路径
+    pub library_path: Option<String>,
+    /// 实际使用的 App ID，初始化失败且未传入时为空
+    pub app_id: Option<u32>,
+    /// steam_appid.txt 的路径，位于当前工作目录
+    pub app_id_file_path: String,
+    /// steam_appid.txt 是否存在
+    pub app_id_file_exists: bool,
+    /// steam_appid.txt 中记录的 App ID
+    pub app_id_file_app_id: Option<u32>,
+    /// steam_appid.txt 是否可以写入（不存在时能否创建）
+    pub app_id_file_writable: bool,
+    /// 当前用户是否拥有该 App，仅在初始化成功后可知
+    pub owns_app: Option<bool>,
+    /// 调用 restartAppIfNecessary 是否会通过 Steam 重新启动程序
+    pub restart_would_relaunch: bool,
+}
+
+/// 在程序目录与当前工作目录中查找 Steam 动态库
+fn find_library() -> Option<PathBuf> {
+    let exe_dir = std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(Path::to_path_buf));
+    let cwd = std::env::current_dir().ok();
+
+    exe_dir
+        .into_iter()
+        .chain(cwd)
+        .map(|dir| dir.join(STEAM_API_LIBRARY))
+        .find(|path| path.is_file())
+}
+
+/// 检查文件是否可写，不存在时尝试创建后删除
+fn is_writable(path: &Path) -> bool {
+    let existed = path.exists();
+    let writable = OpenOptions::new()
+        .append(true)
+        .create(true)
+        .open(path)
+        .is_ok();
+
+    if writable && !existed {
+        let _ = std::fs::remove_file(path);
+    }
+    writable
+}
+
+#[napi(catch_unwind)]
+/// 初始化 Steam 客户端，并返回诊断报告说明初始化失败的原因
+///
+/// 与 init 不同，初始化失败时不会抛出错误，错误信息记录在报告的 error 中
+///
+/// @param app_id 要加载的游戏的 App ID，为空时读取 steam_appid.txt
+/// @returns 诊断报告
+pub fn init_with_diagnostics(app_id: Option<u32>) -> InitDiagnostics {
+    let steam_running = unsafe { steamworks::sys::SteamAPI_IsSteamRunning() };
+
+    let app_id_file = std::env::current_dir()
+        .unwrap_or_default()
+        .join("steam_appid.txt");
+    let app_id_file_exists = app_id_file.is_file();
+    let app_id_file_app_id = std::fs::read_to_string(&app_id_file)
+        .ok()
+        .and_then(|content| content.trim().parse::<u32>().ok());
+
+    // 由 Steam 启动的进程会带有 SteamAppId 环境变量，
+    // 存在 steam_appid.txt 时 Steam 也不会重新启动程序
+    let launched_by_steam =
+        std::env::var_os("SteamAppId").is_some() || std::env::var_os("SteamGameId").is_some();
+    let restart_would_relaunch = app_id.is_some() && !app_id_file_exists && !launched_by_steam;
+
+    let result = crate::init(app_id);
+    let client = crate::client::get_client().ok().filter(|_| result.is_ok());
+
+    InitDiagnostics {
+        initialized: result.is_ok(),
+        error: result.err().map(|e| e.reason),
+        steam_running,
+        library_loaded: true,
+        library_path: find_library().map(|path| path.display().to_string()),
+        app_id: client
+            .as_ref()
+            .map(|client| client.app_id().0)
+            .or(app_id)
+            .or(app_id_file_app_id),
+        app_id_file_path: app_id_file.display().to_string(),
+        app_id_file_exists,
+        app_id_file_app_id,
+        app_id_file_writable: is_writable(&app_id_file),
+        owns_app: client
+            .as_ref()
+            .map(|client| client.is_subscribed_app(client.app_id())),
+        restart_would_relaunch,
+    }
+}
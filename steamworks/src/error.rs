@@ -0,0 +1,463 @@
+//! 插件统一错误类型
+//!
+//! 传递给 JS 的错误信息以 `[错误码] 描述` 开头，错误码保持稳定，可以直接用于判断错误类型。
+//! Steam 接口返回的错误格式为 `[STEAM_ERROR:EResult 数值:EResult 名称] 调用名: 描述`，
+//! 如 `[STEAM_ERROR:15:AccessDenied] join_lobby: access denied`。
+
+use crate::api::logging::steam_log;
+use napi::bindgen_prelude::Error;
+use std::any::Any;
+use std::fmt;
+use std::future::Future;
+use std::panic::{self, AssertUnwindSafe};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use steamworks::SteamError;
+
+/// Steam 客户端尚未初始化
+pub const STEAM_NOT_INITIALIZED: &str = "STEAM_NOT_INITIALIZED";
+/// Steam 客户端初始化失败，如 Steam 没有运行或没有拥有该游戏
+pub const STEAM_INIT_FAILED: &str = "STEAM_INIT_FAILED";
+/// Steam 接口调用失败
+pub const STEAM_ERROR: &str = "STEAM_ERROR";
+/// 等待结果期间 Steam 会话已切换（重新初始化或切换了 AppId）
+pub const STEAM_SESSION_CHANGED: &str = "STEAM_SESSION_CHANGED";
+/// 插件内部发生了 panic，调用没有完成
+pub const PANIC: &str = "PANIC";
+/// ID 参数无效，如负数、超出 64 位或不是十进制数字
+pub const INVALID_ID: &str = "INVALID_ID";
+/// 其他参数无效，如无法解析的 IP 地址
+pub const INVALID_ARGUMENT: &str = "INVALID_ARGUMENT";
+/// 网络请求在超时前没有收到回复
+pub const TIMEOUT: &str = "TIMEOUT";
+/// 网络错误，如地址无法解析、连接被拒绝
+pub const NETWORK_ERROR: &str = "NETWORK_ERROR";
+/// 收到的数据不符合协议
+pub const PROTOCOL_ERROR: &str = "PROTOCOL_ERROR";
+/// 服务器拒绝了密码，如 RCON 密码错误
+pub const AUTH_FAILED: &str = "AUTH_FAILED";
+/// 读写本地文件失败，如文件不存在或没有权限
+pub const IO_ERROR: &str = "IO_ERROR";
+/// 本地文件的内容无法解析
+pub const PARSE_ERROR: &str = "PARSE_ERROR";
+
+/// EResult 数值与名称对照表，名称与 steamworks-rs `SteamError` 的变体名一致
+const ERESULTS: &[(u32, &str)] = &[
+    (1, "OK"),
+    (2, "Generic"),
+    (3, "NoConnection"),
+    (5, "InvalidPassword"),
+    (6, "LoggedInElsewhere"),
+    (7, "InvalidProtocolVersion"),
+    (8, "InvalidParameter"),
+    (9, "FileNotFound"),
+    (10, "Busy"),
+    (11, "InvalidState"),
+    (12, "InvalidName"),
+    (13, "InvalidEmail"),
+    (14, "DuplicateName"),
+    (15, "AccessDenied"),
+    (16, "Timeout"),
+    (17, "Banned"),
+    (18, "AccountNotFound"),
+    (19, "InvalidSteamID"),
+    (20, "ServiceUnavailable"),
+    (21, "NotLoggedOn"),
+    (22, "Pending"),
+    (23, "EncryptionFailure"),
+    (24, "InsufficientPrivilege"),
+    (25, "LimitExceeded"),
+    (26, "Revoked"),
+    (27, "Expired"),
+    (28, "AlreadyRedeemed"),
+    (29, "DuplicateRequest"),
+    (30, "AlreadyOwned"),
+    (31, "IPNotFound"),
+    (32, "PersistFailed"),
+    (33, "LockingFailed"),
+    (34, "LogonSessionReplaced"),
+    (35, "ConnectFailed"),
+    (36, "HandshakeFailed"),
+    (37, "IOFailure"),
+    (38, "RemoteDisconnect"),
+    (39, "ShoppingCartNotFound"),
+    (40, "Blocked"),
+    (41, "Ignored"),
+    (42, "NoMatch"),
+    (43, "AccountDisabled"),
+    (44, "ServiceReadOnly"),
+    (45, "AccountNotFeatured"),
+    (46, "AdministratorOK"),
+    (47, "ContentVersion"),
+    (48, "TryAnotherCM"),
+    (49, "PasswordRequiredToKickSession"),
+    (50, "AlreadyLoggedInElsewhere"),
+    (51, "Suspended"),
+    (52, "Cancelled"),
+    (53, "DataCorruption"),
+    (54, "DiskFull"),
+    (55, "RemoteCallFailed"),
+    (56, "PasswordUnset"),
+    (57, "ExternalAccountUnlinked"),
+    (58, "PSNTicketInvalid"),
+    (59, "ExternalAccountAlreadyLinked"),
+    (60, "RemoteFileConflict"),
+    (61, "IllegalPassword"),
+    (62, "SameAsPreviousValue"),
+    (63, "AccountLogonDenied"),
+    (64, "CannotUseOldPassword"),
+    (65, "InvalidLoginAuthCode"),
+    (66, "AccountLogonDeniedNoMail"),
+    (67, "HardwareNotCapableOfIPT"),
+    (68, "IPTInitError"),
+    (69, "ParentalControlRestricted"),
+    (70, "FacebookQueryError"),
+    (71, "ExpiredLoginAuthCode"),
+    (72, "IPLoginRestrictionFailed"),
+    (73, "AccountLockedDown"),
+    (74, "AccountLogonDeniedVerifiedEmailRequired"),
+    (75, "NoMatchingURL"),
+    (76, "BadResponse"),
+    (77, "RequirePasswordReEntry"),
+    (78, "ValueOutOfRange"),
+    (79, "UnexpectedError"),
+    (80, "Disabled"),
+    (81, "InvalidCEGSubmission"),
+    (82, "RestrictedDevice"),
+    (83, "RegionLocked"),
+    (84, "RateLimitExceeded"),
+    (85, "AccountLoginDeniedNeedTwoFactor"),
+    (86, "ItemDeleted"),
+    (87, "AccountLoginDeniedThrottle"),
+    (88, "TwoFactorCodeMismatch"),
+    (89, "TwoFactorActivationCodeMismatch"),
+    (90, "AccountAssociatedToMultiplePartners"),
+    (91, "NotModified"),
+    (92, "NoMobileDevice"),
+    (93, "TimeNotSynced"),
+    (94, "SmsCodeFailed"),
+    (95, "AccountLimitExceeded"),
+    (96, "AccountActivityLimitExceeded"),
+    (97, "PhoneActivityLimitExceeded"),
+    (98, "RefundToWallet"),
+    (99, "EmailSendFailure"),
+    (100, "NotSettled"),
+    (101, "NeedCaptcha"),
+    (102, "GSLTDenied"),
+    (103, "GSOwnerDenied"),
+    (104, "InvalidItemType"),
+    (105, "IPBanned"),
+    (106, "GSLTExpired"),
+    (107, "InsufficientFunds"),
+    (108, "TooManyPending"),
+    (109, "NoSiteLicensesFound"),
+    (110, "WGNetworkSendExceeded"),
+    (111, "AccountNotFriends"),
+    (112, "LimitedUserAccount"),
+    (113, "CantRemoveItem"),
+    (114, "AccountDeleted"),
+    (115, "ExistingUserCancelledLicense"),
+    (116, "CommunityCooldown"),
+    (117, "NoLauncherSpecified"),
+    (118, "MustAgreeToSSA"),
+    (119, "LauncherMigrated"),
+    (120, "SteamRealmMismatch"),
+    (121, "InvalidSignature"),
+    (122, "ParseFailure"),
+    (123, "NoVerifiedPhone"),
+];
+
+/// 获取 Steam 错误对应的 EResult 数值与名称
+///
+/// steamworks-rs 没有提供反向转换，这里按变体名查表，未知的错误按 `k_EResultFail` 处理。
+pub fn eresult(error: &SteamError) -> (u32, String) {
+    let name = format!("{:?}", error);
+    let code = ERESULTS
+        .iter()
+        .find(|(_, eresult_name)| *eresult_name == name)
+        .map_or(2, |(code, _)| *code);
+    (code, name)
+}
+
+/// 将 EResult 数值转换为 steamworks-rs 的错误类型
+pub fn steam_error_from_code(code: u32) -> SteamError {
+    match code {
+        3 => SteamError::NoConnection,
+        5 => SteamError::InvalidPassword,
+        8 => SteamError::InvalidParameter,
+        9 => SteamError::FileNotFound,
+        10 => SteamError::Busy,
+        11 => SteamError::InvalidState,
+        15 => SteamError::AccessDenied,
+        16 => SteamError::Timeout,
+        17 => SteamError::Banned,
+        20 => SteamError::ServiceUnavailable,
+        21 => SteamError::NotLoggedOn,
+        25 => SteamError::LimitExceeded,
+        29 => SteamError::DuplicateRequest,
+        37 => SteamError::IOFailure,
+        40 => SteamError::Blocked,
+        42 => SteamError::NoMatch,
+        52 => SteamError::Cancelled,
+        84 => SteamError::RateLimitExceeded,
+        _ => SteamError::Generic,
+    }
+}
+
+#[derive(Debug)]
+pub enum SteamworksError {
+    /// 在 `init` 成功之前调用了 Steam 接口
+    NotInitialized,
+    /// `init` 无法初始化 Steam 客户端
+    InitFailed {
+        /// steamworks-rs 返回的原因
+        message: String,
+    },
+    /// 等待结果期间 Steam 会话已切换，结果不会再返回
+    SessionChanged,
+    /// Steam 接口调用失败
+    Steam {
+        /// 失败的接口调用
+        call: &'static str,
+        error: SteamError,
+    },
+    /// 插件内部发生了 panic
+    Panic {
+        /// 发生 panic 的调用
+        call: &'static str,
+        message: String,
+    },
+    /// ID 参数无效
+    InvalidId {
+        /// 参数名
+        name: &'static str,
+        /// 无效的原因
+        reason: String,
+    },
+    /// ID 以外的参数无效
+    InvalidArgument {
+        /// 参数名
+        name: &'static str,
+        /// 无效的原因
+        reason: String,
+    },
+    /// 网络请求超时
+    Timeout {
+        /// 超时的请求
+        call: &'static str,
+        /// 请求的地址
+        target: String,
+    },
+    /// 网络错误
+    Network {
+        /// 失败的请求
+        call: &'static str,
+        /// 请求的地址
+        target: String,
+        error: std::io::Error,
+    },
+    /// 收到的数据不符合协议
+    Protocol {
+        /// 失败的请求
+        call: &'static str,
+        /// 请求的地址
+        target: String,
+        message: String,
+    },
+    /// 服务器拒绝了密码
+    AuthFailed {
+        /// 失败的请求
+        call: &'static str,
+        /// 请求的地址
+        target: String,
+    },
+    /// 读写本地文件失败
+    Io {
+        /// 失败的调用
+        call: &'static str,
+        /// 文件路径
+        path: String,
+        error: std::io::Error,
+    },
+    /// 本地文件的内容无法解析
+    Parse {
+        /// 失败的调用
+        call: &'static str,
+        /// 文件路径，解析的不是文件时为出错的内容
+        path: String,
+        message: String,
+    },
+}
+
+impl SteamworksError {
+    /// 稳定的错误码
+    pub fn code(&self) -> &'static str {
+        match self {
+            SteamworksError::NotInitialized => STEAM_NOT_INITIALIZED,
+            SteamworksError::InitFailed { .. } => STEAM_INIT_FAILED,
+            SteamworksError::SessionChanged => STEAM_SESSION_CHANGED,
+            SteamworksError::Steam { .. } => STEAM_ERROR,
+            SteamworksError::Panic { .. } => PANIC,
+            SteamworksError::InvalidId { .. } => INVALID_ID,
+            SteamworksError::InvalidArgument { .. } => INVALID_ARGUMENT,
+            SteamworksError::Timeout { .. } => TIMEOUT,
+            SteamworksError::Network { .. } => NETWORK_ERROR,
+            SteamworksError::Protocol { .. } => PROTOCOL_ERROR,
+            SteamworksError::AuthFailed { .. } => AUTH_FAILED,
+            SteamworksError::Io { .. } => IO_ERROR,
+            SteamworksError::Parse { .. } => PARSE_ERROR,
+        }
+    }
+}
+
+impl fmt::Display for SteamworksError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SteamworksError::NotInitialized => write!(
+                f,
+                "[{}] Steam client is not initialized, call init() first",
+                self.code()
+            ),
+            SteamworksError::InitFailed { message } => {
+                write!(f, "[{}] init: {}", self.code(), message)
+            }
+            SteamworksError::SessionChanged => write!(
+                f,
+                "[{}] Steam session changed before the call completed",
+                self.code()
+            ),
+            SteamworksError::Steam { call, error } => {
+                let (code, name) = eresult(error);
+                write!(f, "[{}:{}:{}] {}: {}", self.code(), code, name, call, error)
+            }
+            SteamworksError::Panic { call, message } => {
+                write!(f, "[{}] {}: {}", self.code(), call, message)
+            }
+            SteamworksError::InvalidId { name, reason }
+            | SteamworksError::InvalidArgument { name, reason } => {
+                write!(f, "[{}] {}: {}", self.code(), name, reason)
+            }
+            SteamworksError::Timeout { call, target } => {
+                write!(f, "[{}] {} {}: no response", self.code(), call, target)
+            }
+            SteamworksError::Network {
+                call,
+                target,
+                error,
+            } => write!(f, "[{}] {} {}: {}", self.code(), call, target, error),
+            SteamworksError::Protocol {
+                call,
+                target,
+                message,
+            } => write!(f, "[{}] {} {}: {}", self.code(), call, target, message),
+            SteamworksError::AuthFailed { call, target } => {
+                write!(
+                    f,
+                    "[{}] {} {}: password rejected",
+                    self.code(),
+                    call,
+                    target
+                )
+            }
+            SteamworksError::Io { call, path, error } => {
+                write!(f, "[{}] {} {}: {}", self.code(), call, path, error)
+            }
+            SteamworksError::Parse {
+                call,
+                path,
+                message,
+            } => write!(f, "[{}] {} {}: {}", self.code(), call, path, message),
+        }
+    }
+}
+
+impl std::error::Error for SteamworksError {}
+
+impl From<SteamworksError> for Error {
+    fn from(error: SteamworksError) -> Self {
+        Error::from_reason(error.to_string())
+    }
+}
+
+/// 生成把 Steam 错误转换为 JS 错误的函数，用于 `map_err`
+///
+/// @param call 失败的接口调用名
+pub fn steam_error(call: &'static str) -> impl Fn(SteamError) -> Error {
+    move |error| SteamworksError::Steam { call, error }.into()
+}
+
+/// 取出 panic 携带的信息
+pub fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
+
+/// 执行 `f`，发生 panic 时返回 `SteamworksError::Panic`
+///
+/// 用于回调分发线程与异步任务等 napi 无法捕获 panic 的位置，保证 panic 不会终止 Electron 进程。
+/// panic 的位置由 [`install_panic_hook`] 记录到日志。
+///
+/// @param call 调用名，记录在错误信息中
+pub fn catch_panic<T>(call: &'static str, f: impl FnOnce() -> T) -> Result<T, SteamworksError> {
+    panic::catch_unwind(AssertUnwindSafe(f)).map_err(|payload| SteamworksError::Panic {
+        call,
+        message: panic_message(payload.as_ref()),
+    })
+}
+
+/// 捕获 panic 的 future，见 [`catch_panic_async`]
+pub struct CatchPanic<F> {
+    call: &'static str,
+    future: Pin<Box<F>>,
+}
+
+impl<F: Future> Future for CatchPanic<F> {
+    type Output = Result<F::Output, SteamworksError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let call = self.call;
+        let future = self.future.as_mut();
+        match catch_panic(call, move || future.poll(cx)) {
+            Ok(Poll::Ready(output)) => Poll::Ready(Ok(output)),
+            Ok(Poll::Pending) => Poll::Pending,
+            Err(error) => Poll::Ready(Err(error)),
+        }
+    }
+}
+
+/// 异步版本的 [`catch_panic`]，panic 时 future 以 `SteamworksError::Panic` 结束
+///
+/// @param call 调用名，记录在错误信息中
+pub fn catch_panic_async<F: Future>(call: &'static str, future: F) -> CatchPanic<F> {
+    CatchPanic {
+        call,
+        future: Box::pin(future),
+    }
+}
+
+/// 把所有 panic 的信息与位置记录到日志，之后仍交给原来的处理函数
+pub fn install_panic_hook() {
+    static INSTALL: std::sync::Once = std::sync::Once::new();
+    INSTALL.call_once(|| {
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            let location = info
+                .location()
+                .map(|location| format!("{}:{}", location.file(), location.line()))
+                .unwrap_or_default();
+            steam_log!(
+                Error,
+                "panic",
+                "{} at {}",
+                panic_message(info.payload()),
+                location
+            );
+            previous(info);
+        }));
+    });
+}
@@ -0,0 +1,181 @@
+//! 生成 `gamestate_integration_*.cfg`
+//!
+//! 游戏启动时读取 cfg 目录中所有 `gamestate_integration_*.cfg`，按其中的设置向 `uri` 发送状态。
+//! CS:GO 的 cfg 目录为 `csgo/cfg`，CS2 为 `game/csgo/cfg`。
+
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// cfg 的 `data` 节中启用的状态，包含观战时才有的所有玩家与炸弹信息
+const DATA: &[&str] = &[
+    "provider",
+    "map",
+    "round",
+    "player_id",
+    "player_state",
+    "player_match_stats",
+    "player_position",
+    "allplayers_id",
+    "allplayers_state",
+    "allplayers_match_stats",
+    "allplayers_position",
+    "bomb",
+    "phase_countdowns",
+];
+
+#[derive(Debug, Clone)]
+pub struct ConfigOptions {
+    /// 文件名中的名称，`gamestate_integration_<name>.cfg`
+    pub name: String,
+    /// 接收状态的地址，如 `http://127.0.0.1:3000`
+    pub uri: String,
+    /// 随状态发送的 `auth.token`
+    pub token: Option<String>,
+    /// 请求超时（秒）
+    pub timeout: f64,
+    /// 收集变化的时间（秒），同一时间段内的变化合并为一次发送
+    pub buffer: f64,
+    /// 两次发送之间的最短间隔（秒）
+    pub throttle: f64,
+    /// 状态没有变化时的发送间隔（秒）
+    pub heartbeat: f64,
+}
+
+impl ConfigOptions {
+    pub fn new(uri: impl Into<String>) -> Self {
+        Self {
+            name: "sourcebox".to_string(),
+            uri: uri.into(),
+            token: None,
+            timeout: 5.0,
+            buffer: 0.1,
+            throttle: 0.1,
+            heartbeat: 10.0,
+        }
+    }
+
+    /// cfg 文件名，名称中除字母、数字与 `_` 以外的字符替换为 `_`
+    pub fn file_name(&self) -> String {
+        let name: String = self
+            .name
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect();
+        format!("gamestate_integration_{}.cfg", name)
+    }
+
+    /// 生成 cfg 内容
+    pub fn render(&self) -> String {
+        let mut text = format!("\"{}\"\n{{\n", escape(&self.name));
+        text.push_str(&format!("    \"uri\" \"{}\"\n", escape(&self.uri)));
+        for (key, value) in [
+            ("timeout", self.timeout),
+            ("buffer", self.buffer),
+            ("throttle", self.throttle),
+            ("heartbeat", self.heartbeat),
+        ] {
+            // 不限制小数位，0.05 之类的值需要原样写入
+            text.push_str(&format!("    \"{}\" \"{}\"\n", key, value));
+        }
+        if let Some(token) = &self.token {
+            text.push_str("    \"auth\"\n    {\n");
+            text.push_str(&format!("        \"token\" \"{}\"\n", escape(token)));
+            text.push_str("    }\n");
+        }
+        text.push_str("    \"data\"\n    {\n");
+        for key in DATA {
+            text.push_str(&format!("        \"{}\" \"1\"\n", key));
+        }
+        text.push_str("    }\n}\n");
+        text
+    }
+}
+
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// 根据游戏安装目录找到 cfg 目录，传入的已经是 cfg 目录时原样返回
+pub fn cfg_dir(dir: &Path) -> PathBuf {
+    [
+        dir.join("game").join("csgo").join("cfg"),
+        dir.join("csgo").join("cfg"),
+    ]
+    .into_iter()
+    .find(|candidate| candidate.is_dir())
+    .unwrap_or_else(|| dir.to_path_buf())
+}
+
+/// 把 cfg 写入游戏目录，已存在时覆盖
+///
+/// @param dir 游戏安装目录或 cfg 目录
+/// @returns 写入的文件路径
+pub fn write(dir: &Path, options: &ConfigOptions) -> io::Result<PathBuf> {
+    let dir = cfg_dir(dir);
+    if !dir.is_dir() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("cfg directory {} does not exist", dir.display()),
+        ));
+    }
+    let path = dir.join(options.file_name());
+    std::fs::write(&path, options.render())?;
+    Ok(path)
+}
+
+/// 生成随机的 auth token
+pub fn generate_token() -> String {
+    // 标准库的 RandomState 每次使用随机密钥，足够用于本机通信的 token
+    let mut token = String::with_capacity(32);
+    for _ in 0..2 {
+        let mut hasher = RandomState::new().build_hasher();
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_nanos());
+        hasher.write_u128(nanos);
+        token.push_str(&format!("{:016x}", hasher.finish()));
+    }
+    token
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_timings_unrounded() {
+        let options = ConfigOptions {
+            timeout: 0.25,
+            buffer: 0.05,
+            throttle: 0.0,
+            heartbeat: 30.0,
+            ..ConfigOptions::new("http://127.0.0.1:3000")
+        };
+        let text = options.render();
+        assert!(text.contains("    \"timeout\" \"0.25\"\n"));
+        assert!(text.contains("    \"buffer\" \"0.05\"\n"));
+        assert!(text.contains("    \"throttle\" \"0\"\n"));
+        assert!(text.contains("    \"heartbeat\" \"30\"\n"));
+    }
+
+    #[test]
+    fn renders_config() {
+        let options = ConfigOptions {
+            name: "my \"box\"".to_string(),
+            token: Some("a\\b".to_string()),
+            ..ConfigOptions::new("http://127.0.0.1:3000/gsi")
+        };
+        assert_eq!(options.file_name(), "gamestate_integration_my__box_.cfg");
+
+        let text = options.render();
+        assert!(text.starts_with("\"my \\\"box\\\"\"\n{\n"));
+        assert!(text.contains("    \"uri\" \"http://127.0.0.1:3000/gsi\"\n"));
+        assert!(text.contains("    \"auth\"\n    {\n        \"token\" \"a\\\\b\"\n    }\n"));
+        assert!(text.contains("        \"allplayers_position\" \"1\"\n"));
+        assert!(text.ends_with("    }\n}\n"));
+        assert!(!ConfigOptions::new("x").render().contains("auth"));
+    }
+}
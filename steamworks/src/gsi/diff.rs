@@ -0,0 +1,309 @@
+//! 比较前后两次上报的状态，生成变化事件
+
+use super::state::{GameState, MatchStats};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GsiEventKind {
+    /// 地图改变，包括第一次收到地图
+    MapChanged { previous: Option<String> },
+    /// 比赛阶段改变，如热身结束进入 `live`
+    MapPhaseChanged { previous: Option<String> },
+    /// 回合阶段改变，如冻结时间结束进入 `live`
+    RoundPhaseChanged { previous: Option<String> },
+    /// 回合结束并决出胜方
+    RoundWon { team: String },
+    /// 任意一方比分改变
+    ScoreChanged,
+    /// 炸弹状态改变，如安放、拆除、爆炸
+    BombChanged { previous: Option<String> },
+    /// 本地玩家或正在观察的玩家改变
+    PlayerSwitched { previous: Option<String> },
+    /// 玩家的击杀、助攻、死亡、MVP 或得分改变
+    PlayerStatsChanged {
+        steam_id: String,
+        previous: MatchStats,
+    },
+    /// 玩家的生命值降为 0
+    PlayerDied { steam_id: String },
+}
+
+/// 比较两次状态
+///
+/// @param previous 同一客户端上一次的状态，第一次上报时为空
+/// @returns 按地图、回合、比分、炸弹、玩家的顺序排列的事件
+pub fn diff(previous: Option<&GameState>, current: &GameState) -> Vec<GsiEventKind> {
+    let empty = GameState::default();
+    let previous = previous.unwrap_or(&empty);
+    let mut events = Vec::new();
+
+    let map = |state: &GameState| state.map.as_ref().map(|map| map.name.clone());
+    let map_phase = |state: &GameState| state.map.as_ref().map(|map| map.phase.clone());
+    if current.map.is_some() && map(previous) != map(current) {
+        events.push(GsiEventKind::MapChanged {
+            previous: map(previous),
+        });
+    }
+    if current.map.is_some() && map_phase(previous) != map_phase(current) {
+        events.push(GsiEventKind::MapPhaseChanged {
+            previous: map_phase(previous),
+        });
+    }
+
+    let round_phase = |state: &GameState| state.round.as_ref().map(|round| round.phase.clone());
+    let win_team = |state: &GameState| {
+        state
+            .round
+            .as_ref()
+            .and_then(|round| round.win_team.clone())
+    };
+    if current.round.is_some() && round_phase(previous) != round_phase(current) {
+        events.push(GsiEventKind::RoundPhaseChanged {
+            previous: round_phase(previous),
+        });
+    }
+    if let Some(team) = win_team(current) {
+        if win_team(previous).is_none() {
+            events.push(GsiEventKind::RoundWon { team });
+        }
+    }
+
+    if let (Some(before), Some(after)) = (&previous.map, &current.map) {
+        if before.team_ct.score != after.team_ct.score || before.team_t.score != after.team_t.score
+        {
+            events.push(GsiEventKind::ScoreChanged);
+        }
+    }
+
+    // 观战时 bomb 节更详细，游戏中只有 round.bomb
+    let bomb = |state: &GameState| match &state.bomb {
+        Some(bomb) => Some(bomb.state.clone()),
+        None => state.round.as_ref().and_then(|round| round.bomb.clone()),
+    };
+    if bomb(previous) != bomb(current) && (current.bomb.is_some() || current.round.is_some()) {
+        events.push(GsiEventKind::BombChanged {
+            previous: bomb(previous),
+        });
+    }
+
+    let player = |state: &GameState| state.player.as_ref().map(|player| player.steamid.clone());
+    if previous.player.is_some() && current.player.is_some() && player(previous) != player(current)
+    {
+        events.push(GsiEventKind::PlayerSwitched {
+            previous: player(previous),
+        });
+    }
+
+    // 只比较前后都存在的玩家，玩家刚加入或刚开始观战时不报告
+    let before = previous.players();
+    for (steam_id, after) in current.players() {
+        let Some(before) = before.get(steam_id) else {
+            continue;
+        };
+        if let (Some(old), Some(new)) = (before.match_stats, after.match_stats) {
+            if old != new {
+                events.push(GsiEventKind::PlayerStatsChanged {
+                    steam_id: steam_id.to_string(),
+                    previous: old,
+                });
+            }
+        }
+        if let (Some(old), Some(new)) = (&before.state, &after.state) {
+            if old.health > 0 && new.health == 0 {
+                events.push(GsiEventKind::PlayerDied {
+                    steam_id: steam_id.to_string(),
+                });
+            }
+        }
+    }
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn state(value: serde_json::Value) -> GameState {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn map(name: &str, phase: &str, ct: u32, t: u32) -> serde_json::Value {
+        json!({
+            "name": name,
+            "phase": phase,
+            "team_ct": {"score": ct},
+            "team_t": {"score": t},
+        })
+    }
+
+    fn stats(kills: i32, deaths: i32) -> MatchStats {
+        MatchStats {
+            kills,
+            deaths,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn reports_changes() {
+        let cases = [
+            (
+                "first map",
+                None,
+                json!({"map": map("de_dust2", "warmup", 0, 0)}),
+                vec![
+                    GsiEventKind::MapChanged { previous: None },
+                    GsiEventKind::MapPhaseChanged { previous: None },
+                ],
+            ),
+            (
+                "map change",
+                Some(json!({"map": map("de_dust2", "live", 0, 0)})),
+                json!({"map": map("de_inferno", "live", 0, 0)}),
+                vec![GsiEventKind::MapChanged {
+                    previous: Some("de_dust2".to_string()),
+                }],
+            ),
+            (
+                "warmup ends",
+                Some(json!({"map": map("de_dust2", "warmup", 0, 0)})),
+                json!({"map": map("de_dust2", "live", 0, 0)}),
+                vec![GsiEventKind::MapPhaseChanged {
+                    previous: Some("warmup".to_string()),
+                }],
+            ),
+            (
+                "map section disabled",
+                Some(json!({"map": map("de_dust2", "live", 0, 0)})),
+                json!({}),
+                vec![],
+            ),
+            (
+                "freeze time ends",
+                Some(json!({"round": {"phase": "freezetime"}})),
+                json!({"round": {"phase": "live"}}),
+                vec![GsiEventKind::RoundPhaseChanged {
+                    previous: Some("freezetime".to_string()),
+                }],
+            ),
+            (
+                "round won",
+                Some(json!({
+                    "map": map("de_dust2", "live", 3, 2),
+                    "round": {"phase": "live"},
+                })),
+                json!({
+                    "map": map("de_dust2", "live", 4, 2),
+                    "round": {"phase": "over", "win_team": "CT"},
+                }),
+                vec![
+                    GsiEventKind::RoundPhaseChanged {
+                        previous: Some("live".to_string()),
+                    },
+                    GsiEventKind::RoundWon {
+                        team: "CT".to_string(),
+                    },
+                    GsiEventKind::ScoreChanged,
+                ],
+            ),
+            (
+                "round over heartbeat",
+                Some(json!({"round": {"phase": "over", "win_team": "T"}})),
+                json!({"round": {"phase": "over", "win_team": "T"}}),
+                vec![],
+            ),
+            (
+                "score without previous map",
+                None,
+                json!({"map": map("de_dust2", "live", 1, 0)}),
+                vec![
+                    GsiEventKind::MapChanged { previous: None },
+                    GsiEventKind::MapPhaseChanged { previous: None },
+                ],
+            ),
+            (
+                "bomb planted in round",
+                Some(json!({"round": {"phase": "live"}})),
+                json!({"round": {"phase": "live", "bomb": "planted"}}),
+                vec![GsiEventKind::BombChanged { previous: None }],
+            ),
+            (
+                "bomb section defused",
+                Some(json!({"bomb": {"state": "defusing"}})),
+                json!({"bomb": {"state": "defused"}}),
+                vec![GsiEventKind::BombChanged {
+                    previous: Some("defusing".to_string()),
+                }],
+            ),
+            (
+                "bomb section disabled",
+                Some(json!({"bomb": {"state": "planted"}})),
+                json!({}),
+                vec![],
+            ),
+            (
+                "spectated player switched",
+                Some(json!({"player": {"steamid": "1"}})),
+                json!({"player": {"steamid": "2"}}),
+                vec![GsiEventKind::PlayerSwitched {
+                    previous: Some("1".to_string()),
+                }],
+            ),
+            (
+                "first player",
+                None,
+                json!({"player": {"steamid": "1"}}),
+                vec![],
+            ),
+            (
+                "player stats",
+                Some(json!({"allplayers": {
+                    "1": {"match_stats": {"kills": 1, "deaths": 0}, "state": {"health": 100}},
+                    "2": {"match_stats": {"kills": 0, "deaths": 0}, "state": {"health": 100}},
+                }})),
+                json!({"allplayers": {
+                    "1": {"match_stats": {"kills": 2, "deaths": 0}, "state": {"health": 100}},
+                    "2": {"match_stats": {"kills": 0, "deaths": 1}, "state": {"health": 0}},
+                    "3": {"match_stats": {"kills": 5, "deaths": 5}, "state": {"health": 0}},
+                }}),
+                vec![
+                    GsiEventKind::PlayerStatsChanged {
+                        steam_id: "1".to_string(),
+                        previous: stats(1, 0),
+                    },
+                    GsiEventKind::PlayerStatsChanged {
+                        steam_id: "2".to_string(),
+                        previous: stats(0, 0),
+                    },
+                    GsiEventKind::PlayerDied {
+                        steam_id: "2".to_string(),
+                    },
+                ],
+            ),
+            (
+                "local player died",
+                Some(json!({"player": {"steamid": "1", "state": {"health": 12}}})),
+                json!({"player": {"steamid": "1", "state": {"health": 0}}}),
+                vec![GsiEventKind::PlayerDied {
+                    steam_id: "1".to_string(),
+                }],
+            ),
+            (
+                "dead player stays dead",
+                Some(json!({"player": {"steamid": "1", "state": {"health": 0}}})),
+                json!({"player": {"steamid": "1", "state": {"health": 0}}}),
+                vec![],
+            ),
+        ];
+
+        for (name, previous, current, expected) in cases {
+            let previous = previous.map(state);
+            assert_eq!(
+                diff(previous.as_ref(), &state(current)),
+                expected,
+                "{}",
+                name
+            );
+        }
+    }
+}
@@ -0,0 +1,526 @@
+//! CS:GO / CS2 Game State Integration
+//!
+//! 游戏按 `gamestate_integration_*.cfg` 的设置把状态以 JSON 通过 HTTP POST 发送到本机端口。
+//! 这里实现一个只接受 POST 的最小 HTTP/1.1 服务端，校验 `auth.token`，解析状态并与同一客户端
+//! 上一次的状态比较生成事件。
+
+pub mod config;
+pub mod diff;
+pub mod state;
+
+use crate::api::logging::steam_log;
+use diff::GsiEventKind;
+use state::GameState;
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+
+/// 请求头的最大大小
+const MAX_HEADER: usize = 16 * 1024;
+/// 请求体的最大大小，10 人观战的完整状态约 20KB
+const MAX_BODY: usize = 4 * 1024 * 1024;
+/// 连接空闲超过这个时间后关闭，游戏默认每 10 秒发送一次心跳
+const IDLE_TIMEOUT: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Default)]
+pub struct ListenOptions {
+    /// cfg 中 `auth` 节的 `token`，设置后拒绝 token 不匹配的状态
+    pub token: Option<String>,
+}
+
+/// 一次上报
+pub struct GsiUpdate {
+    /// 发送状态的地址
+    pub source: SocketAddr,
+    pub state: GameState,
+    /// 与同一客户端上一次状态相比的变化
+    pub events: Vec<GsiEventKind>,
+}
+
+pub type UpdateHandler = Arc<dyn Fn(GsiUpdate) + Send + Sync>;
+
+struct Shared {
+    options: ListenOptions,
+    handler: UpdateHandler,
+    /// 每个客户端（provider.steamid）上一次的状态
+    states: Mutex<HashMap<String, GameState>>,
+    /// 最后收到的状态
+    latest: Mutex<Option<GameState>>,
+    /// 每个地址只记录一次 token 错误，游戏会按心跳间隔不断重试
+    rejected: Mutex<Vec<IpAddr>>,
+}
+
+/// GSI 服务端，被丢弃时停止监听
+pub struct Server {
+    local_addr: SocketAddr,
+    shared: Arc<Shared>,
+    task: JoinHandle<()>,
+}
+
+impl Server {
+    /// 绑定端口并开始接收状态，需要在 tokio 运行时中调用
+    ///
+    /// @param address 监听地址，如 `127.0.0.1:3000`，端口为 0 时由系统分配
+    pub fn bind(
+        address: &str,
+        options: ListenOptions,
+        handler: UpdateHandler,
+    ) -> std::io::Result<Self> {
+        let listener = std::net::TcpListener::bind(address)?;
+        listener.set_nonblocking(true)?;
+        let listener = TcpListener::from_std(listener)?;
+        let local_addr = listener.local_addr()?;
+        let shared = Arc::new(Shared {
+            options,
+            handler,
+            states: Mutex::new(HashMap::new()),
+            latest: Mutex::new(None),
+            rejected: Mutex::new(Vec::new()),
+        });
+        let task = tokio::spawn(accept(listener, shared.clone()));
+        Ok(Self {
+            local_addr,
+            shared,
+            task,
+        })
+    }
+
+    /// 实际监听的地址
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// 最后收到的状态
+    pub fn latest(&self) -> Option<GameState> {
+        self.shared
+            .latest
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// 停止监听，已建立的连接处理完当前请求后关闭
+    pub fn stop(&self) {
+        self.task.abort();
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+async fn accept(listener: TcpListener, shared: Arc<Shared>) {
+    loop {
+        match listener.accept().await {
+            Ok((stream, source)) => {
+                tokio::spawn(serve(stream, source, shared.clone()));
+            }
+            Err(error) => {
+                steam_log!(Debug, "gsi", "accept failed: {}", error);
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+        }
+    }
+}
+
+/// 一个 HTTP 请求
+struct Request {
+    method: String,
+    body: Vec<u8>,
+    /// 客户端是否要求发送回复后关闭连接
+    close: bool,
+}
+
+/// 处理一个连接上的所有请求，游戏会复用连接
+async fn serve(mut stream: TcpStream, source: SocketAddr, shared: Arc<Shared>) {
+    let mut buffer = Vec::new();
+    loop {
+        let request = match tokio::time::timeout(
+            IDLE_TIMEOUT,
+            read_request(&mut stream, &mut buffer),
+        )
+        .await
+        {
+            Ok(Ok(Some(request))) => request,
+            Ok(Ok(None)) | Err(_) => return,
+            Ok(Err(status)) => {
+                let _ = respond(&mut stream, status, true).await;
+                return;
+            }
+        };
+
+        let status = if request.method != "POST" {
+            405
+        } else {
+            handle(&request.body, source, &shared)
+        };
+        if respond(&mut stream, status, request.close).await.is_err() || request.close {
+            return;
+        }
+    }
+}
+
+/// 读取一个请求，缓冲区中可能已有下一个请求的开头
+///
+/// @returns 连接在请求开始前关闭时为空，请求无效时返回要回复的状态码
+async fn read_request(
+    stream: &mut TcpStream,
+    buffer: &mut Vec<u8>,
+) -> Result<Option<Request>, u16> {
+    let header_end = loop {
+        if let Some(end) = buffer.windows(4).position(|window| window == b"\r\n\r\n") {
+            break end;
+        }
+        if buffer.len() > MAX_HEADER {
+            return Err(431);
+        }
+        if read_more(stream, buffer).await? == 0 {
+            return if buffer.is_empty() {
+                Ok(None)
+            } else {
+                Err(400)
+            };
+        }
+    };
+
+    let header = String::from_utf8_lossy(&buffer[..header_end]).into_owned();
+    let mut lines = header.split("\r\n");
+    let method = lines
+        .next()
+        .and_then(|line| line.split_whitespace().next())
+        .ok_or(400u16)?
+        .to_string();
+
+    let mut length = 0;
+    let mut close = false;
+    for line in lines {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        match name.trim().to_ascii_lowercase().as_str() {
+            "content-length" => length = value.parse::<usize>().map_err(|_| 400u16)?,
+            "transfer-encoding" => return Err(411),
+            "connection" => close = value.eq_ignore_ascii_case("close"),
+            _ => {}
+        }
+    }
+    if length > MAX_BODY {
+        return Err(413);
+    }
+
+    let body_start = header_end + 4;
+    while buffer.len() < body_start + length {
+        if read_more(stream, buffer).await? == 0 {
+            return Err(400);
+        }
+    }
+    let body = buffer[body_start..body_start + length].to_vec();
+    buffer.drain(..body_start + length);
+    Ok(Some(Request {
+        method,
+        body,
+        close,
+    }))
+}
+
+async fn read_more(stream: &mut TcpStream, buffer: &mut Vec<u8>) -> Result<usize, u16> {
+    let mut chunk = [0u8; 8192];
+    let size = stream.read(&mut chunk).await.map_err(|_| 400u16)?;
+    buffer.extend_from_slice(&chunk[..size]);
+    Ok(size)
+}
+
+async fn respond(stream: &mut TcpStream, status: u16, close: bool) -> std::io::Result<()> {
+    let reason = match status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        405 => "Method Not Allowed",
+        411 => "Length Required",
+        413 => "Payload Too Large",
+        431 => "Request Header Fields Too Large",
+        _ => "Error",
+    };
+    let connection = if close { "close" } else { "keep-alive" };
+    let response = format!(
+        "HTTP/1.1 {} {}\r\nContent-Length: 0\r\nConnection: {}\r\n\r\n",
+        status, reason, connection
+    );
+    stream.write_all(response.as_bytes()).await
+}
+
+/// 解析并校验状态，与上一次的状态比较后交给回调
+///
+/// @returns 回复的状态码
+fn handle(body: &[u8], source: SocketAddr, shared: &Shared) -> u16 {
+    let state: GameState = match serde_json::from_slice(body) {
+        Ok(state) => state,
+        Err(error) => {
+            steam_log!(Debug, "gsi", "invalid state from {}: {}", source, error);
+            return 400;
+        }
+    };
+
+    if let Some(token) = &shared.options.token {
+        let received = state.auth.as_ref().and_then(|auth| auth.get("token"));
+        if received != Some(token) {
+            let mut rejected = shared
+                .rejected
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner);
+            if !rejected.contains(&source.ip()) {
+                rejected.push(source.ip());
+                steam_log!(
+                    Warn,
+                    "gsi",
+                    "rejecting state from {}: auth token does not match",
+                    source
+                );
+            }
+            return 401;
+        }
+    }
+
+    let client = state
+        .provider
+        .as_ref()
+        .map(|provider| provider.steamid.clone())
+        .unwrap_or_default();
+    let events = {
+        let mut states = shared.states.lock().unwrap_or_else(PoisonError::into_inner);
+        let events = diff::diff(states.get(&client), &state);
+        states.insert(client, state.clone());
+        events
+    };
+    *shared.latest.lock().unwrap_or_else(PoisonError::into_inner) = Some(state.clone());
+
+    (shared.handler)(GsiUpdate {
+        source,
+        state,
+        events,
+    });
+    200
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn start(token: Option<&str>) -> (Server, Arc<Mutex<Vec<GsiUpdate>>>) {
+        let updates = Arc::new(Mutex::new(Vec::new()));
+        let handler: UpdateHandler = Arc::new({
+            let updates = updates.clone();
+            move |update| updates.lock().unwrap().push(update)
+        });
+        let options = ListenOptions {
+            token: token.map(str::to_string),
+        };
+        let server = Server::bind("127.0.0.1:0", options, handler).unwrap();
+        (server, updates)
+    }
+
+    fn post(body: &str, headers: &str) -> String {
+        format!(
+            "POST / HTTP/1.1\r\nHost: 127.0.0.1\r\nContent-Type: application/json\r\n\
+             Content-Length: {}\r\n{}\r\n{}",
+            body.len(),
+            headers,
+            body
+        )
+    }
+
+    fn state(token: &str, map: &str) -> String {
+        format!(
+            r#"{{"provider": {{"steamid": "76561197960265728"}},
+                "map": {{"name": "{}", "phase": "live"}},
+                "auth": {{"token": "{}"}}}}"#,
+            map, token
+        )
+    }
+
+    /// 读取指定数量的回复，返回每个回复的状态码与 Connection 头
+    async fn responses(stream: &mut TcpStream, count: usize) -> Vec<(u16, String)> {
+        let mut data = Vec::new();
+        while data
+            .windows(4)
+            .filter(|window| window == b"\r\n\r\n")
+            .count()
+            < count
+        {
+            let mut chunk = [0u8; 1024];
+            let size = stream.read(&mut chunk).await.unwrap();
+            assert_ne!(size, 0, "connection closed early");
+            data.extend_from_slice(&chunk[..size]);
+        }
+        String::from_utf8(data)
+            .unwrap()
+            .split_terminator("\r\n\r\n")
+            .map(|response| {
+                let status = response[9..12].parse().unwrap();
+                let connection = response
+                    .lines()
+                    .find_map(|line| line.strip_prefix("Connection: "))
+                    .unwrap()
+                    .to_string();
+                (status, connection)
+            })
+            .collect()
+    }
+
+    async fn assert_closed(stream: &mut TcpStream) {
+        let mut chunk = [0u8; 16];
+        assert_eq!(stream.read(&mut chunk).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn rejects_wrong_token() {
+        let (server, updates) = start(Some("secret"));
+        let mut stream = TcpStream::connect(server.local_addr()).await.unwrap();
+
+        for _ in 0..2 {
+            let request = post(&state("wrong", "de_dust2"), "");
+            stream.write_all(request.as_bytes()).await.unwrap();
+            assert_eq!(
+                responses(&mut stream, 1).await,
+                [(401, "keep-alive".into())]
+            );
+        }
+        // 每个地址只记录一次
+        assert_eq!(
+            *server.shared.rejected.lock().unwrap(),
+            [IpAddr::from([127, 0, 0, 1])]
+        );
+        assert!(updates.lock().unwrap().is_empty());
+        assert_eq!(server.latest(), None);
+
+        let request = post(&state("secret", "de_dust2"), "");
+        stream.write_all(request.as_bytes()).await.unwrap();
+        assert_eq!(
+            responses(&mut stream, 1).await,
+            [(200, "keep-alive".into())]
+        );
+
+        let updates = updates.lock().unwrap();
+        assert_eq!(updates.len(), 1);
+        assert_eq!(updates[0].source, stream.local_addr().unwrap());
+        assert_eq!(
+            updates[0].events,
+            [
+                GsiEventKind::MapChanged { previous: None },
+                GsiEventKind::MapPhaseChanged { previous: None },
+            ]
+        );
+        assert_eq!(server.latest().as_ref(), Some(&updates[0].state));
+    }
+
+    #[tokio::test]
+    async fn frames_keep_alive_requests() {
+        let (server, updates) = start(None);
+        let mut stream = TcpStream::connect(server.local_addr()).await.unwrap();
+
+        // 两个请求在同一次写入中，第三个请求分多次写入
+        let mut data = post(&state("", "de_dust2"), "");
+        data.push_str(&post(&state("", "de_inferno"), ""));
+        stream.write_all(data.as_bytes()).await.unwrap();
+        let third = post(&state("", "de_nuke"), "Connection: close\r\n");
+        for part in third.as_bytes().chunks(40) {
+            stream.write_all(part).await.unwrap();
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+
+        assert_eq!(
+            responses(&mut stream, 3).await,
+            [
+                (200, "keep-alive".into()),
+                (200, "keep-alive".into()),
+                (200, "close".into()),
+            ]
+        );
+        assert_closed(&mut stream).await;
+
+        let updates = updates.lock().unwrap();
+        let maps: Vec<_> = updates
+            .iter()
+            .map(|update| update.state.map.as_ref().unwrap().name.as_str())
+            .collect();
+        assert_eq!(maps, ["de_dust2", "de_inferno", "de_nuke"]);
+        // 同一客户端的状态与上一次比较
+        assert_eq!(
+            updates[2].events,
+            [GsiEventKind::MapChanged {
+                previous: Some("de_inferno".to_string()),
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn answers_invalid_requests() {
+        let (server, updates) = start(None);
+
+        // 非 POST 与无效 JSON 的请求之后连接仍可使用
+        let mut stream = TcpStream::connect(server.local_addr()).await.unwrap();
+        let mut data = "GET / HTTP/1.1\r\nHost: 127.0.0.1\r\n\r\n".to_string();
+        data.push_str(&post("{not json", ""));
+        data.push_str(&post(&state("", "de_dust2"), ""));
+        stream.write_all(data.as_bytes()).await.unwrap();
+        assert_eq!(
+            responses(&mut stream, 3).await,
+            [
+                (405, "keep-alive".into()),
+                (400, "keep-alive".into()),
+                (200, "keep-alive".into()),
+            ]
+        );
+
+        // 无法确定请求体长度或长度过大时回复后关闭连接
+        let cases = [
+            (
+                "POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n".to_string(),
+                411,
+            ),
+            (
+                format!(
+                    "POST / HTTP/1.1\r\nContent-Length: {}\r\n\r\n",
+                    MAX_BODY + 1
+                ),
+                413,
+            ),
+            (
+                "POST / HTTP/1.1\r\nContent-Length: ten\r\n\r\n".to_string(),
+                400,
+            ),
+            (
+                format!(
+                    "POST / HTTP/1.1\r\nX-Padding: {}\r\n",
+                    "a".repeat(MAX_HEADER)
+                ),
+                431,
+            ),
+        ];
+        for (request, status) in cases {
+            let mut stream = TcpStream::connect(server.local_addr()).await.unwrap();
+            stream.write_all(request.as_bytes()).await.unwrap();
+            assert_eq!(responses(&mut stream, 1).await, [(status, "close".into())]);
+            assert_closed(&mut stream).await;
+        }
+
+        // 请求体未发送完就关闭连接
+        let mut stream = TcpStream::connect(server.local_addr()).await.unwrap();
+        let request = post(&state("", "de_dust2"), "");
+        stream
+            .write_all(&request.as_bytes()[..request.len() - 10])
+            .await
+            .unwrap();
+        stream.shutdown().await.unwrap();
+        assert_eq!(responses(&mut stream, 1).await, [(400, "close".into())]);
+
+        assert_eq!(updates.lock().unwrap().len(), 1);
+    }
+}
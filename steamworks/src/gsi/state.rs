@@ -0,0 +1,286 @@
+//! Game State Integration 上报的游戏状态
+//!
+//! 参考 https://developer.valvesoftware.com/wiki/Counter-Strike:_Global_Offensive_Game_State_Integration
+//! 游戏每次上报完整的状态，只包含 cfg 中 `data` 节启用的部分，因此所有字段都可能缺失。
+
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct GameState {
+    pub provider: Option<Provider>,
+    pub map: Option<MapState>,
+    pub round: Option<RoundState>,
+    /// 本地玩家，观战时为正在观察的玩家
+    pub player: Option<PlayerState>,
+    /// 所有玩家，以 Steam ID 为键，只在观战或 GOTV 中上报
+    pub allplayers: Option<BTreeMap<String, PlayerState>>,
+    pub bomb: Option<BombState>,
+    pub phase_countdowns: Option<PhaseCountdowns>,
+    pub auth: Option<BTreeMap<String, String>>,
+}
+
+impl GameState {
+    /// 本地玩家与所有玩家，以 Steam ID 为键
+    pub fn players(&self) -> BTreeMap<&str, &PlayerState> {
+        let mut players: BTreeMap<&str, &PlayerState> = self
+            .allplayers
+            .iter()
+            .flatten()
+            .map(|(steam_id, player)| (steam_id.as_str(), player))
+            .collect();
+        if let Some(player) = &self.player {
+            if !player.steamid.is_empty() {
+                players.entry(&player.steamid).or_insert(player);
+            }
+        }
+        players
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct Provider {
+    pub name: String,
+    pub appid: u32,
+    pub version: u32,
+    /// 上报状态的客户端的 Steam ID
+    pub steamid: String,
+    /// Unix 时间（秒）
+    pub timestamp: u64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct MapState {
+    /// 游戏模式，如 `competitive`
+    pub mode: String,
+    pub name: String,
+    /// `warmup`、`live`、`intermission` 或 `gameover`
+    pub phase: String,
+    /// 已经结束的回合数
+    pub round: u32,
+    pub team_ct: TeamState,
+    pub team_t: TeamState,
+    pub num_matches_to_win_series: u32,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct TeamState {
+    pub score: u32,
+    pub consecutive_round_losses: u32,
+    pub timeouts_remaining: u32,
+    pub matches_won_this_series: u32,
+    /// 比赛中设置的队伍名称
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct RoundState {
+    /// `freezetime`、`live` 或 `over`
+    pub phase: String,
+    /// `planted`、`exploded` 或 `defused`，未安放时缺失
+    pub bomb: Option<String>,
+    /// 回合结束后获胜的队伍，`CT` 或 `T`
+    pub win_team: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct PlayerState {
+    /// allplayers 中的玩家没有这个字段，Steam ID 是键
+    pub steamid: String,
+    pub name: String,
+    pub clan: Option<String>,
+    /// 观战时的按键编号
+    pub observer_slot: Option<u32>,
+    /// `CT` 或 `T`
+    pub team: Option<String>,
+    /// `playing`、`menu` 或 `textinput`
+    pub activity: Option<String>,
+    pub state: Option<PlayerVitals>,
+    pub match_stats: Option<MatchStats>,
+    /// `x, y, z`
+    pub position: Option<String>,
+    pub forward: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct PlayerVitals {
+    pub health: u32,
+    pub armor: u32,
+    pub helmet: bool,
+    pub defusekit: Option<bool>,
+    /// 闪光程度，0 到 255
+    pub flashed: u32,
+    pub smoked: u32,
+    pub burning: u32,
+    pub money: u32,
+    pub round_kills: u32,
+    /// 本回合爆头击杀数
+    pub round_killhs: u32,
+    pub round_totaldmg: Option<u32>,
+    pub equip_value: u32,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct MatchStats {
+    pub kills: i32,
+    pub assists: i32,
+    pub deaths: i32,
+    pub mvps: i32,
+    pub score: i32,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct BombState {
+    /// `carried`、`dropped`、`planting`、`planted`、`defusing`、`defused` 或 `exploded`
+    pub state: String,
+    /// `x, y, z`
+    pub position: Option<String>,
+    /// 携带、安放或拆除炸弹的玩家 Steam ID
+    pub player: Option<String>,
+    /// 爆炸或拆除完成前的秒数
+    pub countdown: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct PhaseCountdowns {
+    /// `freezetime`、`live`、`bomb`、`defuse`、`over`、`warmup` 等
+    pub phase: String,
+    /// 阶段结束前的秒数
+    pub phase_ends_in: Option<String>,
+}
+
+/// 解析 `x, y, z` 格式的坐标
+pub fn parse_vector(text: &str) -> Option<[f64; 3]> {
+    let mut parts = text.split(',').map(|part| part.trim().parse::<f64>());
+    let vector = [
+        parts.next()?.ok()?,
+        parts.next()?.ok()?,
+        parts.next()?.ok()?,
+    ];
+    parts.next().is_none().then_some(vector)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_spectator_state() {
+        let state: GameState = serde_json::from_str(
+            r#"{
+                "provider": {"name": "Counter-Strike: Global Offensive", "appid": 730,
+                    "version": 13881, "steamid": "76561197960265728", "timestamp": 1700000000},
+                "map": {"mode": "competitive", "name": "de_mirage", "phase": "live", "round": 3,
+                    "team_ct": {"score": 2, "consecutive_round_losses": 0,
+                        "timeouts_remaining": 1, "matches_won_this_series": 0, "name": "Blue"},
+                    "team_t": {"score": 1, "consecutive_round_losses": 1},
+                    "num_matches_to_win_series": 0, "current_spectators": 0},
+                "round": {"phase": "live", "bomb": "planted"},
+                "allplayers": {
+                    "76561197960265729": {"name": "alice", "observer_slot": 1, "team": "CT",
+                        "state": {"health": 100, "armor": 100, "helmet": true, "defusekit": true,
+                            "flashed": 0, "smoked": 0, "burning": 0, "money": 4200,
+                            "round_kills": 1, "round_killhs": 1, "equip_value": 5700},
+                        "match_stats": {"kills": 5, "assists": 1, "deaths": 2, "mvps": 1,
+                            "score": 14},
+                        "position": "-1043.51, -2198.07, -167.97",
+                        "forward": "0.98, -0.17, -0.02"},
+                    "76561197960265730": {"name": "bob", "observer_slot": 6, "team": "T",
+                        "state": {"health": 0}}
+                },
+                "bomb": {"state": "planted", "position": "-1510.81, -2070.32, -163.97",
+                    "countdown": "38.4"},
+                "phase_countdowns": {"phase": "bomb", "phase_ends_in": "38.4"},
+                "auth": {"token": "secret"}
+            }"#,
+        )
+        .unwrap();
+
+        let provider = state.provider.as_ref().unwrap();
+        assert_eq!((provider.appid, provider.timestamp), (730, 1700000000));
+        let map = state.map.as_ref().unwrap();
+        assert_eq!((map.name.as_str(), map.round), ("de_mirage", 3));
+        assert_eq!(map.team_ct.name.as_deref(), Some("Blue"));
+        assert_eq!((map.team_ct.score, map.team_t.score), (2, 1));
+        assert_eq!(map.team_t.timeouts_remaining, 0);
+        assert_eq!(
+            state.round.as_ref().unwrap().bomb.as_deref(),
+            Some("planted")
+        );
+
+        let players = state.allplayers.as_ref().unwrap();
+        let alice = &players["76561197960265729"];
+        assert_eq!(alice.steamid, "");
+        assert_eq!(alice.observer_slot, Some(1));
+        let vitals = alice.state.as_ref().unwrap();
+        assert!(vitals.helmet);
+        assert_eq!(vitals.defusekit, Some(true));
+        assert_eq!((vitals.money, vitals.round_killhs), (4200, 1));
+        assert_eq!(vitals.round_totaldmg, None);
+        assert_eq!(
+            alice.match_stats,
+            Some(MatchStats {
+                kills: 5,
+                assists: 1,
+                deaths: 2,
+                mvps: 1,
+                score: 14,
+            })
+        );
+        assert_eq!(
+            parse_vector(alice.position.as_deref().unwrap()),
+            Some([-1043.51, -2198.07, -167.97])
+        );
+        let bob = &players["76561197960265730"];
+        assert_eq!(bob.state.as_ref().unwrap().health, 0);
+        assert_eq!(bob.match_stats, None);
+
+        let bomb = state.bomb.as_ref().unwrap();
+        assert_eq!(bomb.state, "planted");
+        assert_eq!(bomb.player, None);
+        assert_eq!(bomb.countdown.as_deref(), Some("38.4"));
+        let countdowns = state.phase_countdowns.as_ref().unwrap();
+        assert_eq!(countdowns.phase_ends_in.as_deref(), Some("38.4"));
+        assert_eq!(state.auth.as_ref().unwrap()["token"], "secret");
+        assert_eq!(state.player, None);
+    }
+
+    #[test]
+    fn merges_local_player_into_players() {
+        let state: GameState = serde_json::from_str(
+            r#"{
+                "player": {"steamid": "1", "name": "me", "activity": "playing"},
+                "allplayers": {"1": {"name": "me (allplayers)"}, "2": {"name": "other"}}
+            }"#,
+        )
+        .unwrap();
+        let players = state.players();
+        assert_eq!(players.len(), 2);
+        // allplayers 中的信息优先
+        assert_eq!(players["1"].name, "me (allplayers)");
+
+        let state: GameState =
+            serde_json::from_str(r#"{"player": {"steamid": "1", "name": "me"}}"#).unwrap();
+        assert_eq!(state.players()["1"].name, "me");
+        let state: GameState = serde_json::from_str(r#"{"player": {"name": "menu"}}"#).unwrap();
+        assert!(state.players().is_empty());
+    }
+
+    #[test]
+    fn parses_vectors() {
+        assert_eq!(parse_vector("1, -2.5,3"), Some([1.0, -2.5, 3.0]));
+        assert_eq!(parse_vector("1, 2"), None);
+        assert_eq!(parse_vector("1, 2, 3, 4"), None);
+        assert_eq!(parse_vector("1, x, 3"), None);
+    }
+}
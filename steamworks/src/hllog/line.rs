@@ -0,0 +1,644 @@
+//! HL 日志行解析
+//!
+//! 参考 https://developer.valvesoftware.com/wiki/HL_Log_Standard
+//! 玩家写作 `"名字<用户 ID><Steam ID><队伍>"`，行尾可以有若干 `(键 "值")` 或 `(标记)` 属性。
+//! CS:GO 等游戏会在玩家后附加 `[x y z]` 坐标，解析时忽略。
+
+/// 日志行的时间，服务器的本地时间
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LogTime {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+}
+
+impl LogTime {
+    /// 解析 `10/18/2026 - 12:34:56`
+    pub(crate) fn parse(text: &str) -> Option<Self> {
+        let (date, time) = text.split_once(" - ")?;
+        let mut date = date.split('/');
+        let mut time = time.split(':');
+        let next = |fields: &mut std::str::Split<'_, char>| -> Option<u32> {
+            fields.next()?.trim().parse().ok()
+        };
+        let month = next(&mut date)?;
+        let day = next(&mut date)?;
+        let year = next(&mut date)?;
+        let hour = next(&mut time)?;
+        let minute = next(&mut time)?;
+        let second = next(&mut time)?;
+        if month > 12 || day > 31 || hour > 23 || minute > 59 || second > 60 {
+            return None;
+        }
+        Some(Self {
+            year: year as u16,
+            month: month as u8,
+            day: day as u8,
+            hour: hour as u8,
+            minute: minute as u8,
+            second: second as u8,
+        })
+    }
+
+    /// 格式化为 `2026-10-18T12:34:56`，不含时区
+    pub fn to_iso_string(&self) -> String {
+        format!(
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+            self.year, self.month, self.day, self.hour, self.minute, self.second
+        )
+    }
+}
+
+/// 日志中的玩家
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Player {
+    pub name: String,
+    /// 服务器分配的用户 ID，控制台为 0
+    pub user_id: i32,
+    /// `STEAM_1:0:123`、`[U:1:123]`、`BOT` 或 `Console`
+    pub steam_id: String,
+    /// 队伍，未加入队伍时可能为空
+    pub team: String,
+}
+
+impl Player {
+    /// 解析 `名字<用户 ID><Steam ID><队伍>`，名字中可能有 `<`，因此从后向前解析
+    ///
+    /// CS:GO 的 `switched from team` 中玩家没有队伍，写作 `名字<用户 ID><Steam ID>`。
+    pub fn parse(text: &str) -> Option<Self> {
+        let (rest, last) = take_tag(text)?;
+        let (rest, second) = take_tag(rest)?;
+        let with_team =
+            take_tag(rest).and_then(|(name, user_id)| Some((name, user_id.parse().ok()?)));
+        let (name, user_id, steam_id, team) = match with_team {
+            Some((name, user_id)) => (name, user_id, second, last),
+            None => (rest, second.parse().ok()?, last, ""),
+        };
+        Some(Self {
+            name: name.to_string(),
+            user_id,
+            steam_id: steam_id.to_string(),
+            team: team.to_string(),
+        })
+    }
+}
+
+/// 拆出末尾的 `<...>`
+fn take_tag(text: &str) -> Option<(&str, &str)> {
+    let text = text.strip_suffix('>')?;
+    let start = text.rfind('<')?;
+    Some((&text[..start], &text[start + 1..]))
+}
+
+/// 行尾的属性，`(headshot)` 之类的标记没有值
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Property {
+    pub key: String,
+    pub value: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LogEventKind {
+    /// `Log file started`
+    LogStarted,
+    /// `Log file closed`
+    LogClosed,
+    /// `Loading map "..."`
+    MapLoading { map: String },
+    /// `Started map "..."`
+    MapStarted { map: String },
+    /// `"..." connected, address "..."`
+    Connected { player: Player, address: String },
+    /// `"..." STEAM USERID validated`
+    Validated { player: Player },
+    /// `"..." entered the game`
+    Entered { player: Player },
+    /// `"..." disconnected`，原因在 `reason` 属性中
+    Disconnected { player: Player },
+    /// `"..." joined team "..."` 或 `"..." switched from team <...> to <...>`
+    JoinedTeam { player: Player, team: String },
+    /// `"..." changed name to "..."`
+    ChangedName { player: Player, name: String },
+    /// `"..." killed "..." with "..."`，爆头等信息在属性中
+    Kill {
+        killer: Player,
+        victim: Player,
+        weapon: String,
+    },
+    /// `"..." attacked "..." with "..."`，伤害等信息在属性中
+    Attack {
+        attacker: Player,
+        victim: Player,
+        weapon: String,
+    },
+    /// `"..." committed suicide with "..."`
+    Suicide { player: Player, weapon: String },
+    /// `"..." say "..."` 或 `"..." say_team "..."`
+    Say {
+        player: Player,
+        message: String,
+        team_only: bool,
+    },
+    /// `"..." triggered "..."`，可以带 `against "..."` 目标
+    PlayerTriggered {
+        player: Player,
+        action: String,
+        target: Option<Player>,
+    },
+    /// `Team "..." triggered "..."`
+    TeamTriggered { team: String, action: String },
+    /// `Team "..." scored "..." with "..." players`
+    TeamScored {
+        team: String,
+        score: i32,
+        players: u32,
+    },
+    /// `World triggered "..."`
+    WorldTriggered { action: String },
+    /// `server_cvar: "..." "..."` 或 `Server cvar "..." = "..."`
+    Cvar { name: String, value: String },
+    /// `rcon from "...": command "..."`
+    Rcon { address: String, command: String },
+    /// 无法识别的日志行
+    Other,
+}
+
+/// 一行日志
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogLine {
+    /// 日志行的时间，没有时间前缀时为空
+    pub time: Option<LogTime>,
+    /// 去掉时间前缀后的内容
+    pub message: String,
+    pub kind: LogEventKind,
+    pub properties: Vec<Property>,
+}
+
+impl LogLine {
+    pub fn parse(line: &str) -> Self {
+        let line = line.trim_end_matches(['\0', '\n', '\r']);
+        let (time, message) = split_time(line);
+        let (core, properties) = split_properties(message);
+        Self {
+            time,
+            message: message.to_string(),
+            kind: parse_kind(core),
+            properties,
+        }
+    }
+
+    /// 属性的值，标记属性的值为空字符串
+    pub fn property(&self, key: &str) -> Option<&str> {
+        self.properties
+            .iter()
+            .rev()
+            .find(|property| property.key == key)
+            .map(|property| property.value.as_deref().unwrap_or(""))
+    }
+}
+
+/// 拆出 `L 10/18/2026 - 12:34:56: ` 时间前缀
+fn split_time(line: &str) -> (Option<LogTime>, &str) {
+    let parsed = line.strip_prefix("L ").and_then(|rest| {
+        let (time, message) = rest.split_once(": ")?;
+        Some((LogTime::parse(time)?, message))
+    });
+    match parsed {
+        Some((time, message)) => (Some(time), message),
+        None => (None, line),
+    }
+}
+
+/// 从行尾拆出属性
+///
+/// @returns 去掉属性后的内容与按出现顺序排列的属性
+fn split_properties(message: &str) -> (&str, Vec<Property>) {
+    let mut core = message.trim_end();
+    let mut properties = Vec::new();
+    while core.ends_with(')') {
+        let Some((start, property)) = last_property(core) else {
+            break;
+        };
+        properties.push(property);
+        core = core[..start].trim_end();
+    }
+    properties.reverse();
+    (core, properties)
+}
+
+/// 查找以 `)` 结尾的内容中最后一个属性，值中可能有括号，因此从右向左尝试每个 `(`
+fn last_property(text: &str) -> Option<(usize, Property)> {
+    let end = text.len() - 1;
+    text.match_indices('(').rev().find_map(|(start, _)| {
+        if start > 0 && !text[..start].ends_with(' ') {
+            return None;
+        }
+        parse_property(&text[start + 1..end]).map(|property| (start, property))
+    })
+}
+
+fn parse_property(text: &str) -> Option<Property> {
+    let (key, value) = match text.split_once(' ') {
+        Some((key, value)) => {
+            let value = value.strip_prefix('"')?.strip_suffix('"')?;
+            (key, Some(value.to_string()))
+        }
+        None => (text, None),
+    };
+    let valid = !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    valid.then(|| Property {
+        key: key.to_string(),
+        value,
+    })
+}
+
+/// 按顺序读取日志内容
+struct Cursor<'a> {
+    rest: &'a str,
+}
+
+impl<'a> Cursor<'a> {
+    /// 读取指定的单词，单词后必须是空白、引号或结尾
+    fn eat(&mut self, word: &str) -> bool {
+        let rest = self.rest.trim_start();
+        let Some(after) = rest.strip_prefix(word) else {
+            return false;
+        };
+        if after
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_alphanumeric() || c == '_')
+        {
+            return false;
+        }
+        self.rest = after;
+        true
+    }
+
+    fn expect(&mut self, word: &str) -> Option<()> {
+        self.eat(word).then_some(())
+    }
+
+    /// 读取带引号的值
+    fn quoted(&mut self) -> Option<&'a str> {
+        let rest = self.rest.trim_start().strip_prefix('"')?;
+        let end = rest.find('"')?;
+        self.rest = &rest[end + 1..];
+        Some(&rest[..end])
+    }
+
+    /// 读取带引号的玩家，名字中可能有引号，因此以 `>"` 作为结尾，之后的坐标会被跳过
+    fn player(&mut self) -> Option<Player> {
+        let rest = self.rest.trim_start().strip_prefix('"')?;
+        let end = rest.find(">\"")? + 1;
+        let player = Player::parse(&rest[..end])?;
+        self.rest = &rest[end + 1..];
+
+        let rest = self.rest.trim_start();
+        if let Some(position) = rest.strip_prefix('[') {
+            if let Some(end) = position.find(']') {
+                self.rest = &position[end + 1..];
+            }
+        }
+        Some(player)
+    }
+
+    /// 剩余内容去掉首尾的引号，用于可能含有引号的聊天内容与命令
+    fn text(&mut self) -> Option<&'a str> {
+        let rest = self.rest.trim();
+        self.rest = "";
+        rest.strip_prefix('"')?.strip_suffix('"')
+    }
+
+    fn number<T: std::str::FromStr>(&mut self) -> Option<T> {
+        self.quoted()?.trim().parse().ok()
+    }
+}
+
+fn parse_kind(core: &str) -> LogEventKind {
+    let mut cursor = Cursor { rest: core };
+    let kind = if core.starts_with('"') {
+        cursor
+            .player()
+            .and_then(|player| parse_player_action(player, cursor))
+    } else {
+        parse_server_action(cursor)
+    };
+    kind.unwrap_or(LogEventKind::Other)
+}
+
+fn parse_player_action(player: Player, mut cursor: Cursor<'_>) -> Option<LogEventKind> {
+    let kind = if cursor.eat("connected,") {
+        cursor.expect("address")?;
+        LogEventKind::Connected {
+            player,
+            address: cursor.quoted()?.to_string(),
+        }
+    } else if cursor.eat("STEAM USERID validated") {
+        LogEventKind::Validated { player }
+    } else if cursor.eat("entered the game") {
+        LogEventKind::Entered { player }
+    } else if cursor.eat("disconnected") {
+        LogEventKind::Disconnected { player }
+    } else if cursor.eat("joined team") {
+        LogEventKind::JoinedTeam {
+            player,
+            team: cursor.quoted()?.to_string(),
+        }
+    } else if cursor.eat("switched from team") {
+        let (_, team) = cursor.rest.rsplit_once(" to ")?;
+        let team = team.trim().strip_prefix('<')?.strip_suffix('>')?;
+        LogEventKind::JoinedTeam {
+            player,
+            team: team.to_string(),
+        }
+    } else if cursor.eat("changed name to") {
+        LogEventKind::ChangedName {
+            player,
+            name: cursor.text()?.to_string(),
+        }
+    } else if cursor.eat("killed") {
+        let victim = cursor.player()?;
+        cursor.expect("with")?;
+        LogEventKind::Kill {
+            killer: player,
+            victim,
+            weapon: cursor.quoted()?.to_string(),
+        }
+    } else if cursor.eat("attacked") {
+        let victim = cursor.player()?;
+        cursor.expect("with")?;
+        LogEventKind::Attack {
+            attacker: player,
+            victim,
+            weapon: cursor.quoted()?.to_string(),
+        }
+    } else if cursor.eat("committed suicide with") {
+        LogEventKind::Suicide {
+            player,
+            weapon: cursor.quoted()?.to_string(),
+        }
+    } else if cursor.eat("say_team") {
+        LogEventKind::Say {
+            player,
+            message: cursor.text()?.to_string(),
+            team_only: true,
+        }
+    } else if cursor.eat("say") {
+        LogEventKind::Say {
+            player,
+            message: cursor.text()?.to_string(),
+            team_only: false,
+        }
+    } else if cursor.eat("triggered") {
+        let action = cursor.quoted()?.to_string();
+        let target = if cursor.eat("against") {
+            Some(cursor.player()?)
+        } else {
+            None
+        };
+        LogEventKind::PlayerTriggered {
+            player,
+            action,
+            target,
+        }
+    } else {
+        return None;
+    };
+    Some(kind)
+}
+
+fn parse_server_action(mut cursor: Cursor<'_>) -> Option<LogEventKind> {
+    let kind = if cursor.eat("Log file started") {
+        LogEventKind::LogStarted
+    } else if cursor.eat("Log file closed") {
+        LogEventKind::LogClosed
+    } else if cursor.eat("Loading map") {
+        LogEventKind::MapLoading {
+            map: cursor.quoted()?.to_string(),
+        }
+    } else if cursor.eat("Started map") {
+        LogEventKind::MapStarted {
+            map: cursor.quoted()?.to_string(),
+        }
+    } else if cursor.eat("Team") {
+        let team = cursor.quoted()?.to_string();
+        if cursor.eat("triggered") {
+            LogEventKind::TeamTriggered {
+                team,
+                action: cursor.quoted()?.to_string(),
+            }
+        } else if cursor.eat("scored") {
+            let score = cursor.number()?;
+            cursor.expect("with")?;
+            LogEventKind::TeamScored {
+                team,
+                score,
+                players: cursor.number()?,
+            }
+        } else {
+            return None;
+        }
+    } else if cursor.eat("World triggered") {
+        LogEventKind::WorldTriggered {
+            action: cursor.quoted()?.to_string(),
+        }
+    } else if cursor.eat("server_cvar:") {
+        LogEventKind::Cvar {
+            name: cursor.quoted()?.to_string(),
+            value: cursor.quoted()?.to_string(),
+        }
+    } else if cursor.eat("Server cvar") {
+        let name = cursor.quoted()?.to_string();
+        cursor.expect("=")?;
+        LogEventKind::Cvar {
+            name,
+            value: cursor.quoted()?.to_string(),
+        }
+    } else if cursor.eat("rcon from") {
+        let address = cursor.quoted()?.to_string();
+        cursor.expect(":")?;
+        cursor.expect("command")?;
+        LogEventKind::Rcon {
+            address,
+            command: cursor.text()?.to_string(),
+        }
+    } else {
+        return None;
+    };
+    Some(kind)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn player(name: &str, user_id: i32, steam_id: &str, team: &str) -> Player {
+        Player {
+            name: name.to_string(),
+            user_id,
+            steam_id: steam_id.to_string(),
+            team: team.to_string(),
+        }
+    }
+
+    fn kind(line: &str) -> LogEventKind {
+        LogLine::parse(line).kind
+    }
+
+    #[test]
+    fn parses_time_prefix() {
+        let line = LogLine::parse("L 10/18/2026 - 20:15:01: Log file closed\n\0");
+        assert_eq!(
+            line.time,
+            Some(LogTime {
+                year: 2026,
+                month: 10,
+                day: 18,
+                hour: 20,
+                minute: 15,
+                second: 1,
+            })
+        );
+        assert_eq!(line.message, "Log file closed");
+        assert_eq!(line.kind, LogEventKind::LogClosed);
+
+        let line = LogLine::parse("L 13/18/2026 - 20:15:01: Log file closed");
+        assert_eq!(line.time, None);
+        assert_eq!(line.kind, LogEventKind::Other);
+    }
+
+    #[test]
+    fn parses_kill() {
+        let line = LogLine::parse(
+            r#"L 10/18/2026 - 20:15:40: "Alice<2><STEAM_1:0:12345><CT>" [-1044 1566 -37] killed "Bob<3><BOT><TERRORIST>" [-1012 1780 -39] with "ak47" (headshot) (penetrated)"#,
+        );
+        assert_eq!(
+            line.kind,
+            LogEventKind::Kill {
+                killer: player("Alice", 2, "STEAM_1:0:12345", "CT"),
+                victim: player("Bob", 3, "BOT", "TERRORIST"),
+                weapon: "ak47".to_string(),
+            }
+        );
+        assert_eq!(line.property("headshot"), Some(""));
+        assert_eq!(line.property("penetrated"), Some(""));
+        assert_eq!(line.property("noscope"), None);
+
+        // GoldSrc 没有坐标，名字中可以有尖括号与引号
+        assert_eq!(
+            kind(
+                r#""<<"Neo">><5><STEAM_0:1:42><TERRORIST>" killed "Carl<6><STEAM_0:0:7><CT>" with "deagle""#
+            ),
+            LogEventKind::Kill {
+                killer: player("<<\"Neo\">>", 5, "STEAM_0:1:42", "TERRORIST"),
+                victim: player("Carl", 6, "STEAM_0:0:7", "CT"),
+                weapon: "deagle".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn parses_chat() {
+        assert_eq!(
+            kind(r#""Alice<2><[U:1:24690]><CT>" say "gg "ez" (not really)""#),
+            LogEventKind::Say {
+                player: player("Alice", 2, "[U:1:24690]", "CT"),
+                message: "gg \"ez\" (not really)".to_string(),
+                team_only: false,
+            }
+        );
+        assert_eq!(
+            kind(r#""Bob<3><BOT><TERRORIST>" say_team "rush b""#),
+            LogEventKind::Say {
+                player: player("Bob", 3, "BOT", "TERRORIST"),
+                message: "rush b".to_string(),
+                team_only: true,
+            }
+        );
+        // 死亡时的聊天带有 (dead) 标记
+        let line = LogLine::parse(r#""Bob<3><BOT><TERRORIST>" say "nice" (dead)"#);
+        assert!(matches!(&line.kind, LogEventKind::Say { message, .. } if message == "nice"));
+        assert_eq!(line.property("dead"), Some(""));
+    }
+
+    #[test]
+    fn parses_connections() {
+        let alice = player("Alice", 2, "STEAM_1:0:12345", "");
+        assert_eq!(
+            kind(r#""Alice<2><STEAM_1:0:12345><>" connected, address "192.168.1.20:27005""#),
+            LogEventKind::Connected {
+                player: alice.clone(),
+                address: "192.168.1.20:27005".to_string(),
+            }
+        );
+        assert_eq!(
+            kind(r#""Alice<2><STEAM_1:0:12345><>" STEAM USERID validated"#),
+            LogEventKind::Validated {
+                player: alice.clone()
+            }
+        );
+        assert_eq!(
+            kind(r#""Alice<2><STEAM_1:0:12345><>" entered the game"#),
+            LogEventKind::Entered {
+                player: alice.clone()
+            }
+        );
+        let line = LogLine::parse(
+            r#""Alice<2><STEAM_1:0:12345><>" disconnected (reason "Kicked by Console : (afk)")"#,
+        );
+        assert_eq!(line.kind, LogEventKind::Disconnected { player: alice });
+        assert_eq!(line.property("reason"), Some("Kicked by Console : (afk)"));
+    }
+
+    #[test]
+    fn parses_team_changes() {
+        assert_eq!(
+            kind(r#""Bob<3><BOT><>" joined team "TERRORIST""#),
+            LogEventKind::JoinedTeam {
+                player: player("Bob", 3, "BOT", ""),
+                team: "TERRORIST".to_string(),
+            }
+        );
+        assert_eq!(
+            kind(r#""Alice<2><STEAM_1:0:12345>" switched from team <Unassigned> to <CT>"#),
+            LogEventKind::JoinedTeam {
+                player: player("Alice", 2, "STEAM_1:0:12345", ""),
+                team: "CT".to_string(),
+            }
+        );
+        assert_eq!(
+            kind(r#"Team "CT" scored "7" with "5" players"#),
+            LogEventKind::TeamScored {
+                team: "CT".to_string(),
+                score: 7,
+                players: 5,
+            }
+        );
+    }
+
+    #[test]
+    fn parses_server_events() {
+        assert_eq!(
+            kind(r#"server_cvar: "mp_friendlyfire" "0""#),
+            LogEventKind::Cvar {
+                name: "mp_friendlyfire".to_string(),
+                value: "0".to_string(),
+            }
+        );
+        assert_eq!(
+            kind(r#"rcon from "10.0.0.2:51234": command "say "hi"""#),
+            LogEventKind::Rcon {
+                address: "10.0.0.2:51234".to_string(),
+                command: "say \"hi\"".to_string(),
+            }
+        );
+        assert_eq!(kind("Loading mapx \"de_dust2\""), LogEventKind::Other);
+    }
+}
@@ -0,0 +1,260 @@
+//! 接收服务器通过 `logaddress_add` 发送的 HL 日志
+//!
+//! 服务器把每行日志作为一个 UDP 包发送到指定地址，这里监听端口、校验 `sv_logsecret`
+//! 并把日志行解析为击杀、聊天、进出等事件。`send` 可以把保存的日志回放到监听端口，
+//! 用于在没有服务器的情况下调试。
+
+pub mod line;
+pub mod packet;
+
+use crate::api::logging::steam_log;
+use line::LogLine;
+use packet::LogPacket;
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::UdpSocket;
+use tokio::task::JoinHandle;
+
+/// 单个 UDP 包的最大大小
+const MAX_DATAGRAM: usize = 65535;
+
+#[derive(Debug, Clone, Default)]
+pub struct ListenOptions {
+    /// 服务器的 `sv_logsecret`，设置后只接受密码匹配的 `S` 包，为空时接受所有日志包
+    pub secret: Option<String>,
+}
+
+/// 收到的一行日志
+pub struct LogEvent {
+    /// 发送日志的服务器地址
+    pub source: SocketAddr,
+    pub line: LogLine,
+}
+
+pub type EventHandler = Arc<dyn Fn(LogEvent) + Send + Sync>;
+
+/// 日志监听器，被丢弃时停止监听
+pub struct Listener {
+    local_addr: SocketAddr,
+    task: JoinHandle<()>,
+}
+
+impl Listener {
+    /// 绑定端口并开始接收日志，需要在 tokio 运行时中调用
+    ///
+    /// @param address 监听地址，如 `0.0.0.0:27500`，端口为 0 时由系统分配
+    pub fn bind(
+        address: &str,
+        options: ListenOptions,
+        handler: EventHandler,
+    ) -> std::io::Result<Self> {
+        let socket = std::net::UdpSocket::bind(address)?;
+        socket.set_nonblocking(true)?;
+        let socket = UdpSocket::from_std(socket)?;
+        let local_addr = socket.local_addr()?;
+        let task = tokio::spawn(receive(socket, options, handler));
+        Ok(Self { local_addr, task })
+    }
+
+    /// 实际监听的地址
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// 停止监听
+    pub fn stop(&self) {
+        self.task.abort();
+    }
+
+    pub fn is_running(&self) -> bool {
+        !self.task.is_finished()
+    }
+}
+
+impl Drop for Listener {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+async fn receive(socket: UdpSocket, options: ListenOptions, handler: EventHandler) {
+    let mut buffer = vec![0u8; MAX_DATAGRAM];
+    // 每个来源只记录一次密码错误，避免日志被刷屏
+    let mut rejected: Vec<SocketAddr> = Vec::new();
+    loop {
+        let (size, source) = match socket.recv_from(&mut buffer).await {
+            Ok(received) => received,
+            Err(error) => {
+                // Windows 上对端不可达的 ICMP 回复也会让 recv_from 失败，忽略后继续接收
+                steam_log!(Debug, "hllog", "receive failed: {}", error);
+                continue;
+            }
+        };
+        let Some(packet) = LogPacket::decode(&buffer[..size]) else {
+            continue;
+        };
+
+        if let Some(secret) = &options.secret {
+            if packet.secret.as_ref() != Some(secret) {
+                if !rejected.contains(&source) {
+                    rejected.push(source);
+                    steam_log!(
+                        Warn,
+                        "hllog",
+                        "ignoring logs from {}: sv_logsecret does not match",
+                        source
+                    );
+                }
+                continue;
+            }
+        }
+
+        handler(LogEvent {
+            source,
+            line: LogLine::parse(&packet.line),
+        });
+    }
+}
+
+/// 把日志行逐行发送到监听地址，用于回放保存的日志
+///
+/// @param secret 设置后按 `sv_logsecret` 格式发送
+/// @param interval 每行之间的间隔，本机回放大量日志时间隔为 0 可能因接收缓冲区满而丢包
+/// @returns 发送的行数，空行会被跳过
+pub async fn send(
+    target: SocketAddr,
+    lines: &[String],
+    secret: Option<&str>,
+    interval: Duration,
+) -> std::io::Result<u32> {
+    let bind = if target.is_ipv4() {
+        SocketAddr::from((Ipv4Addr::UNSPECIFIED, 0))
+    } else {
+        SocketAddr::from((Ipv6Addr::UNSPECIFIED, 0))
+    };
+    let socket = UdpSocket::bind(bind).await?;
+    let mut sent = 0;
+    for line in lines {
+        let line = line.trim_end_matches(['\r', '\n']);
+        if line.trim().is_empty() {
+            continue;
+        }
+        let packet = LogPacket {
+            secret: secret.map(String::from),
+            line: line.to_string(),
+        };
+        if sent > 0 && !interval.is_zero() {
+            tokio::time::sleep(interval).await;
+        }
+        socket.send_to(&packet.encode(), target).await?;
+        sent += 1;
+    }
+    Ok(sent)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use line::LogEventKind;
+    use tokio::sync::mpsc;
+
+    const LOG: &str = include_str!("fixtures/csgo.log");
+
+    /// 在本地回环上监听，收到的日志行通过通道传出
+    fn listen(secret: Option<&str>) -> (Listener, mpsc::UnboundedReceiver<LogEvent>) {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let listener = Listener::bind(
+            "127.0.0.1:0",
+            ListenOptions {
+                secret: secret.map(String::from),
+            },
+            Arc::new(move |event| {
+                let _ = sender.send(event);
+            }),
+        )
+        .unwrap();
+        (listener, receiver)
+    }
+
+    fn lines(text: &str) -> Vec<String> {
+        text.lines().map(String::from).collect()
+    }
+
+    /// 接收指定数量的日志行，超时未收到时返回已收到的部分
+    async fn receive(
+        receiver: &mut mpsc::UnboundedReceiver<LogEvent>,
+        count: usize,
+    ) -> Vec<LogEvent> {
+        let mut events = Vec::new();
+        while events.len() < count {
+            match tokio::time::timeout(Duration::from_millis(300), receiver.recv()).await {
+                Ok(Some(event)) => events.push(event),
+                _ => break,
+            }
+        }
+        events
+    }
+
+    #[tokio::test]
+    async fn replays_captured_log() {
+        let (listener, mut receiver) = listen(None);
+        let sent = send(listener.local_addr(), &lines(LOG), None, Duration::ZERO)
+            .await
+            .unwrap();
+        assert_eq!(sent, 15);
+
+        let events = receive(&mut receiver, 15).await;
+        assert_eq!(events.len(), 15);
+        assert!(events
+            .iter()
+            .all(|event| event.source.ip() == listener.local_addr().ip()));
+        let kinds: Vec<_> = events.iter().map(|event| &event.line.kind).collect();
+        assert_eq!(*kinds[0], LogEventKind::LogStarted);
+        assert!(
+            matches!(kinds[4], LogEventKind::Connected { address, .. } if address == "192.168.1.20:27005")
+        );
+        assert!(matches!(kinds[7], LogEventKind::JoinedTeam { team, .. } if team == "CT"));
+        assert!(matches!(kinds[9], LogEventKind::Kill { weapon, .. } if weapon == "ak47"));
+        assert!(matches!(
+            kinds[11],
+            LogEventKind::Say {
+                team_only: true,
+                ..
+            }
+        ));
+        assert_eq!(*kinds[14], LogEventKind::LogClosed);
+        assert_eq!(events[9].line.property("headshot"), Some(""));
+        let closed = events[14].line.time.unwrap();
+        assert_eq!((closed.hour, closed.minute, closed.second), (20, 16, 1));
+        assert!(listener.is_running());
+    }
+
+    #[tokio::test]
+    async fn checks_log_secret() {
+        let (listener, mut receiver) = listen(Some("8675309"));
+        let address = listener.local_addr();
+        let line = lines("L 10/18/2026 - 20:15:01: Log file started");
+
+        // 没有密码与密码错误的包都被忽略
+        send(address, &line, None, Duration::ZERO).await.unwrap();
+        send(address, &line, Some("1234"), Duration::ZERO)
+            .await
+            .unwrap();
+        assert!(receive(&mut receiver, 1).await.is_empty());
+
+        send(address, &line, Some("8675309"), Duration::ZERO)
+            .await
+            .unwrap();
+        let events = receive(&mut receiver, 1).await;
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].line.kind, LogEventKind::LogStarted);
+
+        listener.stop();
+        tokio::task::yield_now().await;
+        send(address, &line, Some("8675309"), Duration::ZERO)
+            .await
+            .unwrap();
+        assert!(receive(&mut receiver, 1).await.is_empty());
+    }
+}
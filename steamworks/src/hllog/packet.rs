@@ -0,0 +1,126 @@
+//! HL 日志数据包
+//!
+//! 服务器通过 `logaddress_add` 把每行日志作为一个 UDP 包发送，包以四个 0xFF 开头：
+//! - `R` 之后是日志行
+//! - `S` 之后是 `sv_logsecret` 的值，紧接着是日志行
+//! - GoldSrc 使用 `log ` 前缀，之后是日志行
+//!
+//! 日志行以 `L 月/日/年 - 时:分:秒: ` 开头，以换行和 0 结尾。
+
+/// 包头
+const HEADER: [u8; 4] = [0xFF; 4];
+/// 不带密码的日志包
+const PLAIN: u8 = b'R';
+/// 带 `sv_logsecret` 的日志包
+const SECRET: u8 = b'S';
+/// GoldSrc 日志包前缀
+const GOLDSRC_PREFIX: &[u8] = b"log ";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogPacket {
+    /// `S` 包中的 `sv_logsecret`，其他包为空
+    pub secret: Option<String>,
+    /// 不含结尾换行的日志行
+    pub line: String,
+}
+
+impl LogPacket {
+    /// 解析数据包，不是日志包时为空
+    pub fn decode(datagram: &[u8]) -> Option<Self> {
+        let data = datagram.strip_prefix(&HEADER)?;
+        let (secret, line) = match *data.first()? {
+            PLAIN => (None, &data[1..]),
+            SECRET => {
+                // sv_logsecret 是整数，日志行从 `L ` 开始
+                let data = &data[1..];
+                let start = data.windows(2).position(|window| window == b"L ")?;
+                let secret = String::from_utf8_lossy(&data[..start]).into_owned();
+                (Some(secret), &data[start..])
+            }
+            _ => (None, data.strip_prefix(GOLDSRC_PREFIX)?),
+        };
+
+        let line = String::from_utf8_lossy(line);
+        let line = line.trim_end_matches(['\0', '\n', '\r']);
+        Some(Self {
+            secret,
+            line: line.to_string(),
+        })
+    }
+
+    /// 编码为服务器发送的格式，用于回放日志
+    pub fn encode(&self) -> Vec<u8> {
+        let mut datagram = HEADER.to_vec();
+        match &self.secret {
+            Some(secret) => {
+                datagram.push(SECRET);
+                datagram.extend_from_slice(secret.as_bytes());
+            }
+            None => datagram.push(PLAIN),
+        }
+        datagram.extend_from_slice(self.line.as_bytes());
+        datagram.extend_from_slice(b"\n\0");
+        datagram
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LINE: &str = "L 10/18/2026 - 20:15:01: Log file started";
+
+    #[test]
+    fn decodes_plain_packet() {
+        let packet =
+            LogPacket::decode(b"\xFF\xFF\xFF\xFFRL 10/18/2026 - 20:15:01: Log file started\n\0")
+                .unwrap();
+        assert_eq!(packet.secret, None);
+        assert_eq!(packet.line, LINE);
+    }
+
+    #[test]
+    fn decodes_secret_packet() {
+        let packet = LogPacket::decode(
+            b"\xFF\xFF\xFF\xFFS8675309L 10/18/2026 - 20:15:01: Log file started\n\0",
+        )
+        .unwrap();
+        assert_eq!(packet.secret.as_deref(), Some("8675309"));
+        assert_eq!(packet.line, LINE);
+        // 找不到日志行开头
+        assert_eq!(LogPacket::decode(b"\xFF\xFF\xFF\xFFS8675309\n\0"), None);
+    }
+
+    #[test]
+    fn decodes_goldsrc_packet() {
+        let packet =
+            LogPacket::decode(b"\xFF\xFF\xFF\xFFlog L 10/18/2026 - 20:15:01: Log file started\n\0")
+                .unwrap();
+        assert_eq!(packet.secret, None);
+        assert_eq!(packet.line, LINE);
+    }
+
+    #[test]
+    fn rejects_other_packets() {
+        assert_eq!(LogPacket::decode(b""), None);
+        assert_eq!(LogPacket::decode(b"\xFF\xFF\xFF\xFF"), None);
+        assert_eq!(LogPacket::decode(b"RL 10/18/2026 - 20:15:01: x"), None);
+        assert_eq!(
+            LogPacket::decode(b"\xFF\xFF\xFF\xFFTSource Engine Query\0"),
+            None
+        );
+    }
+
+    #[test]
+    fn encodes_replayable_packets() {
+        for secret in [None, Some("8675309".to_string())] {
+            let packet = LogPacket {
+                secret,
+                line: LINE.to_string(),
+            };
+            let datagram = packet.encode();
+            assert!(datagram.ends_with(b"started\n\0"));
+            assert_eq!(LogPacket::decode(&datagram), Some(packet));
+        }
+    }
+}
@@ -0,0 +1,88 @@
+//! ID 参数解析
+//!
+//! Steam ID、大厅 ID、创意工坊物品 ID 与输入句柄都是 64 位无符号整数，
+//! JS 可以传入 `bigint` 或十进制字符串（经 JSON 存储后的 ID 通常是字符串）。
+//! 负数、超出 64 位或不是十进制数字的值会被拒绝，错误格式为 `[INVALID_ID] 参数名: 原因`。
+
+use crate::error::SteamworksError;
+use napi::bindgen_prelude::{BigInt, Either};
+use steamworks::{LobbyId, PublishedFileId, SteamId};
+
+/// JS 传入的 ID 参数，`bigint | string`
+pub type IdArg = Either<BigInt, String>;
+
+fn invalid(name: &'static str, reason: impl Into<String>) -> SteamworksError {
+    SteamworksError::InvalidId {
+        name,
+        reason: reason.into(),
+    }
+}
+
+/// 无损读取 `bigint`，拒绝负数与超出 64 位的值
+///
+/// @param name 参数名，用于错误信息
+/// @param value JS 传入的值
+pub fn bigint_u64(name: &'static str, value: &BigInt) -> Result<u64, SteamworksError> {
+    let (signed, raw, lossless) = value.get_u64();
+    if signed && raw != 0 {
+        return Err(invalid(name, "must not be negative"));
+    }
+    if !lossless {
+        return Err(invalid(name, "does not fit in 64 bits"));
+    }
+    Ok(raw)
+}
+
+/// 读取十进制字符串，只接受数字，允许首尾空白
+///
+/// @param name 参数名，用于错误信息
+/// @param value JS 传入的值
+pub fn string_u64(name: &'static str, value: &str) -> Result<u64, SteamworksError> {
+    let digits = value.trim();
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(invalid(
+            name,
+            format!("{:?} is not a decimal integer", value),
+        ));
+    }
+    digits
+        .parse()
+        .map_err(|_| invalid(name, format!("{} does not fit in 64 bits", digits)))
+}
+
+/// 读取 64 位 ID 或句柄
+///
+/// @param name 参数名，用于错误信息
+/// @param value JS 传入的值
+pub fn parse_u64(name: &'static str, value: &IdArg) -> Result<u64, SteamworksError> {
+    match value {
+        Either::A(value) => bigint_u64(name, value),
+        Either::B(value) => string_u64(name, value),
+    }
+}
+
+/// 读取非零的 64 位 ID，0 在 Steam 中表示无效 ID
+fn parse_nonzero(name: &'static str, value: &IdArg) -> Result<u64, SteamworksError> {
+    match parse_u64(name, value)? {
+        0 => Err(invalid(name, "must not be 0")),
+        raw => Ok(raw),
+    }
+}
+
+/// 读取 Steam ID
+pub fn steam_id(name: &'static str, value: &IdArg) -> Result<SteamId, SteamworksError> {
+    parse_nonzero(name, value).map(SteamId::from_raw)
+}
+
+/// 读取大厅 ID
+pub fn lobby_id(name: &'static str, value: &IdArg) -> Result<LobbyId, SteamworksError> {
+    parse_nonzero(name, value).map(LobbyId::from_raw)
+}
+
+/// 读取创意工坊物品 ID
+pub fn published_file_id(
+    name: &'static str,
+    value: &IdArg,
+) -> Result<PublishedFileId, SteamworksError> {
+    parse_nonzero(name, value).map(PublishedFileId)
+}
@@ -1,40 +1,70 @@
+use api::logging::steam_log;
+use backend::SteamBackend;
+use error::SteamworksError;
 use napi::bindgen_prelude::Error;
 use napi_derive::napi;
+use std::sync::Arc;
 use steamworks::AppId;
 use steamworks::Client;
 
+pub mod backend;
 pub mod client;
+pub mod condebug;
+pub mod demo;
+pub mod diagnostics;
+pub mod error;
+pub mod gsi;
+pub mod hllog;
+pub mod id;
+pub mod query;
+pub mod rcon;
+pub mod serverlist;
+pub mod watch;
 
 #[macro_use]
 extern crate lazy_static;
 
-#[napi]
+#[napi(catch_unwind)]
 pub fn init(app_id: Option<u32>) -> Result<(), Error> {
     if client::has_client() {
-        client::drop_single();
         client::drop_client();
     }
 
     let (steam_client, steam_single) = app_id
         .map(|app_id| Client::init_app(AppId(app_id)))
         .unwrap_or_else(Client::init)
-        .map_err(|e| Error::from_reason(e.to_string()))?;
+        .map_err(|e| {
+            steam_log!(Warn, "init", "failed to initialize Steam: {}", e);
+            SteamworksError::InitFailed {
+                message: e.to_string(),
+            }
+        })?;
 
-    steam_client.user_stats().request_current_stats();
+    let backend = backend::steam::SteamworksBackend::new(steam_client, steam_single);
+    backend.request_current_stats();
 
-    client::set_client(steam_client);
-    client::set_single(steam_single);
-    Ok(())
+    client::set_client(Arc::new(backend));
+    api::pump::start_pump(api::pump::DEFAULT_INTERVAL_MS)
+}
+
+#[napi(catch_unwind)]
+/// 停止后台回调分发并释放 Steam 客户端
+pub fn shutdown() {
+    steam_log!(Info, "init", "shutting down Steam");
+    api::pump::stop_pump();
+    client::drop_client();
 }
 
-#[napi]
+#[napi(catch_unwind)]
 pub fn restart_app_if_necessary(app_id: u32) -> bool {
     steamworks::restart_app_if_necessary(AppId(app_id))
 }
 
-#[napi]
-pub fn run_callbacks() {
-    client::get_single().run_callbacks();
+#[napi(catch_unwind)]
+/// 手动分发一次回调，后台回调分发运行时可以不调用
+pub fn run_callbacks() -> Result<(), Error> {
+    client::get_client()?.run_callbacks();
+    Ok(())
 }
 
 pub mod api;
@@ -0,0 +1,279 @@
+//! 批量查询
+//!
+//! 并发查询多个服务器的 A2S_INFO，限制同时进行的查询数量，
+//! 每个服务器回复（或最终失败）时立即通过回调返回结果，不等待其他服务器。
+
+use super::message::ServerInfo;
+use super::{resolve, Error, Query};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Semaphore;
+
+/// 默认同时进行的查询数量
+pub const DEFAULT_CONCURRENCY: usize = 32;
+/// 默认超时或网络错误后的重试次数
+pub const DEFAULT_RETRIES: u32 = 1;
+
+#[derive(Debug, Clone, Copy)]
+pub struct BatchOptions {
+    /// 同时进行的查询数量
+    pub concurrency: usize,
+    /// 单次查询的超时
+    pub timeout: Duration,
+    /// 超时或网络错误后的重试次数，回复不符合协议时不会重试
+    pub retries: u32,
+}
+
+impl Default for BatchOptions {
+    fn default() -> Self {
+        Self {
+            concurrency: DEFAULT_CONCURRENCY,
+            timeout: super::DEFAULT_TIMEOUT,
+            retries: DEFAULT_RETRIES,
+        }
+    }
+}
+
+/// 一个服务器的查询结果
+pub struct BatchResult {
+    /// 传入的地址
+    pub address: String,
+    pub result: Result<ServerInfo, Error>,
+    /// 实际发出的查询次数
+    pub attempts: u32,
+}
+
+/// 查询一个服务器，超时或网络错误时重试
+///
+/// @returns 查询结果与查询次数
+pub async fn query_info_with_retry(
+    address: &str,
+    timeout: Duration,
+    retries: u32,
+) -> (Result<ServerInfo, Error>, u32) {
+    let target = match resolve(address).await {
+        Ok(target) => target,
+        Err(error) => return (Err(error), 0),
+    };
+
+    let mut attempts = 0;
+    loop {
+        attempts += 1;
+        let result = match Query::connect(target, timeout).await {
+            Ok(query) => query.info().await,
+            Err(error) => Err(error),
+        };
+        match result {
+            Err(Error::Timeout | Error::Io(_)) if attempts <= retries => continue,
+            result => return (result, attempts),
+        }
+    }
+}
+
+/// 并发查询所有服务器，全部完成后返回
+///
+/// @param addresses 服务器地址列表
+/// @param options 批量查询选项
+/// @param on_result 每个服务器查询完成时调用，调用顺序为完成顺序
+pub async fn query_all(
+    addresses: Vec<String>,
+    options: BatchOptions,
+    on_result: impl Fn(BatchResult) + Send + Sync + 'static,
+) {
+    let permits = Arc::new(Semaphore::new(options.concurrency.max(1)));
+    let on_result = Arc::new(on_result);
+
+    let tasks: Vec<_> = addresses
+        .into_iter()
+        .map(|address| {
+            let permits = permits.clone();
+            let on_result = on_result.clone();
+            tokio::spawn(async move {
+                // 信号量不会被关闭
+                let _permit = permits.acquire().await;
+                let (result, attempts) =
+                    query_info_with_retry(&address, options.timeout, options.retries).await;
+                on_result(BatchResult {
+                    address,
+                    result,
+                    attempts,
+                });
+            })
+        })
+        .collect();
+
+    for task in tasks {
+        let _ = task.await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::query::message::INFO_RESPONSE;
+    use crate::query::packet::SINGLE_PACKET;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Mutex;
+    use std::time::Instant;
+    use tokio::net::UdpSocket;
+
+    /// 模拟服务器收到的请求数与同时处理的最大请求数
+    #[derive(Default)]
+    struct Stats {
+        requests: AtomicUsize,
+        active: AtomicUsize,
+        max_active: AtomicUsize,
+    }
+
+    /// 本地回环上的模拟服务器，不要求 challenge，延迟一段时间后回复 A2S_INFO
+    ///
+    /// @param delay 回复前的延迟，为空时不回复
+    async fn stand_in_server(map: &'static str, delay: Option<Duration>) -> (String, Arc<Stats>) {
+        let socket = Arc::new(UdpSocket::bind("127.0.0.1:0").await.unwrap());
+        let address = socket.local_addr().unwrap().to_string();
+        let stats = Arc::new(Stats::default());
+        let server = stats.clone();
+        tokio::spawn(async move {
+            let mut buffer = vec![0; 1400];
+            loop {
+                let (_, peer) = socket.recv_from(&mut buffer).await.unwrap();
+                server.requests.fetch_add(1, Ordering::SeqCst);
+                let Some(delay) = delay else {
+                    continue;
+                };
+                let socket = socket.clone();
+                let server = server.clone();
+                tokio::spawn(async move {
+                    let active = server.active.fetch_add(1, Ordering::SeqCst) + 1;
+                    server.max_active.fetch_max(active, Ordering::SeqCst);
+                    tokio::time::sleep(delay).await;
+                    server.active.fetch_sub(1, Ordering::SeqCst);
+
+                    let mut datagram = SINGLE_PACKET.to_le_bytes().to_vec();
+                    datagram.push(INFO_RESPONSE);
+                    datagram.push(17);
+                    for field in ["Stand-in", map, "tf", "Team Fortress"] {
+                        datagram.extend_from_slice(field.as_bytes());
+                        datagram.push(0);
+                    }
+                    datagram.extend_from_slice(b"\xb8\x01\x03\x18\x00dl\x00\x018622567\0");
+                    socket.send_to(&datagram, peer).await.unwrap();
+                });
+            }
+        });
+        (address, stats)
+    }
+
+    async fn run(addresses: Vec<String>, options: BatchOptions) -> Vec<BatchResult> {
+        let results = Arc::new(Mutex::new(Vec::new()));
+        let collected = results.clone();
+        query_all(addresses, options, move |result| {
+            collected.lock().unwrap().push(result)
+        })
+        .await;
+        let results = std::mem::take(&mut *results.lock().unwrap());
+        results
+    }
+
+    #[tokio::test]
+    async fn limits_concurrent_queries() {
+        let (address, stats) =
+            stand_in_server("cp_badlands", Some(Duration::from_millis(50))).await;
+        let options = BatchOptions {
+            concurrency: 2,
+            timeout: Duration::from_secs(2),
+            retries: 0,
+        };
+        let results = run(vec![address; 6], options).await;
+
+        assert_eq!(results.len(), 6);
+        assert!(results.iter().all(|result| result.attempts == 1));
+        assert!(results
+            .iter()
+            .all(|result| result.result.as_ref().unwrap().map == "cp_badlands"));
+        assert_eq!(stats.requests.load(Ordering::SeqCst), 6);
+        assert_eq!(stats.max_active.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn retries_silent_servers() {
+        let (responsive, responsive_stats) =
+            stand_in_server("cp_badlands", Some(Duration::ZERO)).await;
+        let (silent, silent_stats) = stand_in_server("", None).await;
+        let options = BatchOptions {
+            concurrency: 4,
+            timeout: Duration::from_millis(100),
+            retries: 2,
+        };
+        let results = run(vec![silent.clone(), responsive.clone()], options).await;
+
+        let find = |address: &str| {
+            results
+                .iter()
+                .find(|result| result.address == address)
+                .unwrap()
+        };
+        assert!(matches!(find(&silent).result, Err(Error::Timeout)));
+        assert_eq!(find(&silent).attempts, 3);
+        assert_eq!(silent_stats.requests.load(Ordering::SeqCst), 3);
+        assert!(find(&responsive).result.is_ok());
+        assert_eq!(find(&responsive).attempts, 1);
+        assert_eq!(responsive_stats.requests.load(Ordering::SeqCst), 1);
+
+        let results = run(
+            vec![silent],
+            BatchOptions {
+                retries: 0,
+                ..options
+            },
+        )
+        .await;
+        assert_eq!(results[0].attempts, 1);
+        assert_eq!(silent_stats.requests.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test]
+    async fn times_out_each_query() {
+        let (address, _) = stand_in_server("cp_badlands", Some(Duration::from_millis(300))).await;
+        let options = BatchOptions {
+            concurrency: 4,
+            timeout: Duration::from_millis(50),
+            retries: 0,
+        };
+
+        let started = Instant::now();
+        let results = run(vec![address.clone(), address.clone()], options).await;
+        assert!(started.elapsed() < Duration::from_millis(250));
+        assert!(results
+            .iter()
+            .all(|result| matches!(result.result, Err(Error::Timeout))));
+
+        let options = BatchOptions {
+            timeout: Duration::from_secs(2),
+            ..options
+        };
+        let results = run(vec![address], options).await;
+        assert!(results[0].result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn reports_results_in_completion_order() {
+        let (slow, _) = stand_in_server("slow", Some(Duration::from_millis(100))).await;
+        let (silent, _) = stand_in_server("", None).await;
+        let (fast, _) = stand_in_server("fast", Some(Duration::ZERO)).await;
+        let options = BatchOptions {
+            concurrency: 3,
+            timeout: Duration::from_millis(250),
+            retries: 1,
+        };
+        let results = run(vec![slow.clone(), silent.clone(), fast.clone()], options).await;
+
+        let order: Vec<&str> = results
+            .iter()
+            .map(|result| result.address.as_str())
+            .collect();
+        assert_eq!(order, [fast.as_str(), slow.as_str(), silent.as_str()]);
+        assert_eq!(results[1].result.as_ref().unwrap().map, "slow");
+        assert_eq!(results[2].attempts, 2);
+    }
+}
@@ -0,0 +1,276 @@
+//! 局域网服务器发现
+//!
+//! 向子网广播 A2S_INFO，在等待时间内收集所有回复，按回复地址与服务器 Steam ID 去重。
+//! 服务器要求 challenge 时单独向该服务器重新请求，延迟从最后一次请求开始计算。
+
+use super::message::{ServerInfo, CHALLENGE_RESPONSE, GOLDSRC_INFO_RESPONSE, INFO_RESPONSE};
+use super::packet::{Assembler, SINGLE_PACKET};
+use super::{info_request, Error, MAX_CHALLENGES, MAX_DATAGRAM};
+use crate::api::logging::steam_log;
+use std::collections::{HashMap, HashSet};
+use std::io::ErrorKind;
+use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4};
+use std::ops::RangeInclusive;
+use std::time::{Duration, Instant};
+use tokio::net::UdpSocket;
+
+/// 默认广播的端口范围
+pub const DEFAULT_PORTS: RangeInclusive<u16> = 27015..=27020;
+/// 默认等待回复的时间
+pub const DEFAULT_DURATION: Duration = Duration::from_millis(1500);
+
+#[derive(Debug, Clone)]
+pub struct DiscoveryOptions {
+    /// 广播地址，也可以是单个主机
+    pub target: Ipv4Addr,
+    /// 广播的端口范围
+    pub ports: RangeInclusive<u16>,
+    /// 等待回复的时间，结束后返回已收到的服务器
+    pub duration: Duration,
+}
+
+impl Default for DiscoveryOptions {
+    fn default() -> Self {
+        Self {
+            target: Ipv4Addr::BROADCAST,
+            ports: DEFAULT_PORTS,
+            duration: DEFAULT_DURATION,
+        }
+    }
+}
+
+/// 发现的服务器
+pub struct LanServer {
+    /// 回复的地址
+    pub address: SocketAddr,
+    pub info: ServerInfo,
+}
+
+/// 一个回复地址的查询状态
+struct Peer {
+    assembler: Assembler,
+    /// 最后一次向该地址发出请求的时间
+    sent: Instant,
+    challenges: usize,
+    done: bool,
+}
+
+/// 带包头的 A2S_INFO 请求
+fn info_packet(challenge: Option<&[u8]>) -> Vec<u8> {
+    let mut packet = SINGLE_PACKET.to_le_bytes().to_vec();
+    packet.extend(info_request(challenge));
+    packet
+}
+
+/// 广播 A2S_INFO 并收集回复
+///
+/// @returns 按延迟排序的服务器，不符合协议的回复会被忽略，接收失败时返回已经收到的服务器；
+/// 端口范围为空时返回错误
+pub async fn discover(options: &DiscoveryOptions) -> Result<Vec<LanServer>, Error> {
+    if options.ports.is_empty() {
+        return Err(Error::Io(std::io::Error::new(
+            ErrorKind::InvalidInput,
+            format!(
+                "port range {}-{} is empty",
+                options.ports.start(),
+                options.ports.end()
+            ),
+        )));
+    }
+
+    let socket = UdpSocket::bind(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, 0)).await?;
+    socket.set_broadcast(true)?;
+
+    let request = info_packet(None);
+    let start = Instant::now();
+    for port in options.ports.clone() {
+        socket
+            .send_to(&request, SocketAddrV4::new(options.target, port))
+            .await?;
+    }
+
+    let deadline = tokio::time::Instant::now() + options.duration;
+    let mut peers: HashMap<SocketAddr, Peer> = HashMap::new();
+    let mut steam_ids = HashSet::new();
+    let mut servers = Vec::new();
+    let mut buffer = vec![0; MAX_DATAGRAM];
+    loop {
+        let (len, from) =
+            match tokio::time::timeout_at(deadline, socket.recv_from(&mut buffer)).await {
+                Ok(Ok(received)) => received,
+                // Windows 上发往未监听端口的包会让之后的 recv 返回 ConnectionReset
+                Ok(Err(error)) if error.kind() == ErrorKind::ConnectionReset => continue,
+                // 已经收到的回复仍然有效
+                Ok(Err(error)) => {
+                    steam_log!(Warn, "a2s", "LAN discovery stopped early: {}", error);
+                    break;
+                }
+                Err(_) => break,
+            };
+
+        let peer = peers.entry(from).or_insert_with(|| Peer {
+            assembler: Assembler::default(),
+            sent: start,
+            challenges: 0,
+            done: false,
+        });
+        if peer.done {
+            continue;
+        }
+
+        let response = match peer.assembler.push(&buffer[..len]) {
+            Ok(Some(response)) => response,
+            Ok(None) => continue,
+            Err(_) => {
+                peer.done = true;
+                continue;
+            }
+        };
+
+        match response.split_first() {
+            Some((&kind @ (INFO_RESPONSE | GOLDSRC_INFO_RESPONSE), data)) => {
+                peer.done = true;
+                let Ok(info) = ServerInfo::parse_response(kind, data, peer.sent.elapsed()) else {
+                    continue;
+                };
+                // 同一台服务器可能从多个地址回复
+                if let Some(steam_id) = info.steam_id.filter(|steam_id| *steam_id != 0) {
+                    if !steam_ids.insert(steam_id) {
+                        continue;
+                    }
+                }
+                servers.push(LanServer {
+                    address: from,
+                    info,
+                });
+            }
+            Some((&CHALLENGE_RESPONSE, data)) if data.len() >= 4 => {
+                if peer.challenges >= MAX_CHALLENGES {
+                    peer.done = true;
+                    continue;
+                }
+                peer.challenges += 1;
+                peer.sent = Instant::now();
+                socket.send_to(&info_packet(Some(&data[..4])), from).await?;
+            }
+            _ => peer.done = true,
+        }
+    }
+
+    servers.sort_by_key(|server| server.info.latency);
+    Ok(servers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHALLENGE: [u8; 4] = [9, 8, 7, 6];
+
+    /// 绑定本地回环上连续的端口，广播的端口范围只能是连续的
+    async fn bind_consecutive(count: u16) -> Vec<UdpSocket> {
+        'retry: loop {
+            let first = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+            let base = first.local_addr().unwrap().port();
+            let mut sockets = vec![first];
+            for offset in 1..count {
+                let Some(port) = base.checked_add(offset) else {
+                    continue 'retry;
+                };
+                match UdpSocket::bind(("127.0.0.1", port)).await {
+                    Ok(socket) => sockets.push(socket),
+                    Err(_) => continue 'retry,
+                }
+            }
+            return sockets;
+        }
+    }
+
+    /// A2S_INFO 回复，携带服务器 Steam ID
+    fn info_response(name: &str, steam_id: u64) -> Vec<u8> {
+        let mut data = SINGLE_PACKET.to_le_bytes().to_vec();
+        data.extend_from_slice(&[INFO_RESPONSE, 17]);
+        for field in [name, "de_nuke", "csgo", "Counter-Strike: Global Offensive"] {
+            data.extend_from_slice(field.as_bytes());
+            data.push(0);
+        }
+        data.extend_from_slice(&730u16.to_le_bytes());
+        data.extend_from_slice(&[0, 10, 0, b'd', b'l', 0, 1]);
+        data.extend_from_slice(b"1.38.7.9\0");
+        data.push(0x10);
+        data.extend_from_slice(&steam_id.to_le_bytes());
+        data
+    }
+
+    /// 模拟局域网中的服务器，可以要求 challenge 并延迟回复
+    fn respond(socket: UdpSocket, reply: Vec<u8>, challenge: bool, delay: Duration) {
+        tokio::spawn(async move {
+            let mut buffer = vec![0; MAX_DATAGRAM];
+            loop {
+                let (len, peer) = socket.recv_from(&mut buffer).await.unwrap();
+                if challenge && !buffer[..len].ends_with(&CHALLENGE) {
+                    let mut packet = SINGLE_PACKET.to_le_bytes().to_vec();
+                    packet.push(CHALLENGE_RESPONSE);
+                    packet.extend_from_slice(&CHALLENGE);
+                    socket.send_to(&packet, peer).await.unwrap();
+                    continue;
+                }
+                tokio::time::sleep(delay).await;
+                socket.send_to(&reply, peer).await.unwrap();
+            }
+        });
+    }
+
+    #[tokio::test]
+    async fn discovers_loopback_servers() {
+        let mut sockets = bind_consecutive(4).await;
+        let base = sockets[0].local_addr().unwrap().port();
+        // 最后一个端口没有服务器回复
+        drop(sockets.pop());
+        let mut sockets = sockets.into_iter();
+        let fast = info_response("fast", 76561197960265729);
+        respond(sockets.next().unwrap(), fast.clone(), false, Duration::ZERO);
+        // 同一台服务器从另一个端口回复
+        respond(sockets.next().unwrap(), fast, false, Duration::ZERO);
+        respond(
+            sockets.next().unwrap(),
+            info_response("slow", 76561197960265730),
+            true,
+            Duration::from_millis(80),
+        );
+
+        let servers = discover(&DiscoveryOptions {
+            target: Ipv4Addr::LOCALHOST,
+            ports: base..=base + 3,
+            duration: Duration::from_millis(400),
+        })
+        .await
+        .unwrap();
+
+        let names: Vec<_> = servers
+            .iter()
+            .map(|server| server.info.name.as_str())
+            .collect();
+        assert_eq!(names, ["fast", "slow"]);
+        assert!([base, base + 1].contains(&servers[0].address.port()));
+        assert_eq!(servers[1].address.port(), base + 2);
+        assert!(servers[0].info.latency < Duration::from_millis(80));
+        assert!(servers[1].info.latency >= Duration::from_millis(80));
+    }
+
+    #[tokio::test]
+    async fn rejects_empty_port_range() {
+        #[allow(clippy::reversed_empty_ranges)]
+        let ports = 27020..=27015;
+        let options = DiscoveryOptions {
+            target: Ipv4Addr::LOCALHOST,
+            ports,
+            duration: Duration::from_millis(10),
+        };
+        match discover(&options).await {
+            Err(Error::Io(error)) => assert_eq!(error.kind(), ErrorKind::InvalidInput),
+            Err(error) => panic!("unexpected error {}", error),
+            Ok(_) => panic!("empty port range was accepted"),
+        }
+    }
+}
@@ -0,0 +1,563 @@
+//! A2S 回复解析
+//!
+//! 参考 https://developer.valvesoftware.com/wiki/Server_queries
+//!
+//! GoldSrc 服务器的旧格式回复与 The Ship 的额外字段解析后统一为相同的结构。
+
+use super::reader::Reader;
+use super::Error;
+use std::time::Duration;
+
+/// A2S_INFO 回复类型
+pub const INFO_RESPONSE: u8 = b'I';
+/// GoldSrc 旧格式的 A2S_INFO 回复类型
+pub const GOLDSRC_INFO_RESPONSE: u8 = b'm';
+/// A2S_PLAYER 回复类型
+pub const PLAYER_RESPONSE: u8 = b'D';
+/// A2S_RULES 回复类型
+pub const RULES_RESPONSE: u8 = b'E';
+/// 要求携带 challenge 重新请求
+pub const CHALLENGE_RESPONSE: u8 = b'A';
+
+/// 额外数据标记（EDF）
+const EDF_PORT: u8 = 0x80;
+const EDF_STEAM_ID: u8 = 0x10;
+const EDF_SOURCE_TV: u8 = 0x40;
+const EDF_KEYWORDS: u8 = 0x20;
+const EDF_GAME_ID: u8 = 0x01;
+
+/// The Ship 的 AppId，信息与玩家回复带有额外字段
+pub const THE_SHIP_APP_ID: u16 = 2400;
+
+/// GoldSrc 旧格式回复没有 AppId，根据游戏目录推断的官方游戏
+const GOLDSRC_APP_IDS: &[(&str, u16)] = &[
+    ("cstrike", 10),
+    ("tfc", 20),
+    ("dod", 30),
+    ("dmc", 40),
+    ("gearbox", 50),
+    ("ricochet", 60),
+    ("valve", 70),
+    ("czero", 80),
+    ("bshift", 130),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ServerType {
+    Dedicated,
+    NonDedicated,
+    SourceTv,
+    Unknown,
+}
+
+impl From<u8> for ServerType {
+    fn from(value: u8) -> Self {
+        match value.to_ascii_lowercase() {
+            b'd' => ServerType::Dedicated,
+            b'l' => ServerType::NonDedicated,
+            b'p' => ServerType::SourceTv,
+            _ => ServerType::Unknown,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Environment {
+    Linux,
+    Windows,
+    Mac,
+    Unknown,
+}
+
+impl From<u8> for Environment {
+    fn from(value: u8) -> Self {
+        match value {
+            b'l' | b'L' => Environment::Linux,
+            b'w' | b'W' => Environment::Windows,
+            b'm' | b'o' => Environment::Mac,
+            _ => Environment::Unknown,
+        }
+    }
+}
+
+/// SourceTV 信息
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourceTv {
+    pub port: u16,
+    pub name: String,
+}
+
+/// The Ship 服务器信息
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ShipInfo {
+    /// 游戏模式：0 Hunt、1 Elimination、2 Duel、3 Deathmatch、4 VIP Team、5 Team Elimination
+    pub mode: u8,
+    /// 需要的目击者数量
+    pub witnesses: u8,
+    /// 被目击后的时长（秒）
+    pub duration: u8,
+}
+
+/// GoldSrc 模组信息，只在旧格式回复中提供
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GoldSrcMod {
+    /// 模组网站
+    pub link: String,
+    /// 模组下载地址
+    pub download_link: String,
+    pub version: u32,
+    /// 模组大小（字节）
+    pub size: u32,
+    /// 是否只支持多人游戏
+    pub multiplayer_only: bool,
+    /// 是否使用自己的 DLL 而不是 Half-Life 的 DLL
+    pub custom_dll: bool,
+}
+
+/// A2S_INFO 回复
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServerInfo {
+    pub protocol: u8,
+    pub name: String,
+    pub map: String,
+    pub folder: String,
+    pub game: String,
+    pub app_id: u16,
+    pub players: u8,
+    pub max_players: u8,
+    pub bots: u8,
+    pub server_type: ServerType,
+    pub environment: Environment,
+    /// 是否需要密码
+    pub password: bool,
+    pub vac: bool,
+    pub version: String,
+    /// 游戏端口，EDF 0x80
+    pub port: Option<u16>,
+    /// 服务器 Steam ID，EDF 0x10
+    pub steam_id: Option<u64>,
+    /// EDF 0x40
+    pub source_tv: Option<SourceTv>,
+    /// 服务器标签，EDF 0x20
+    pub keywords: Option<String>,
+    /// 64 位游戏 ID，低 24 位为完整的 AppId，EDF 0x01
+    pub game_id: Option<u64>,
+    /// The Ship 的额外信息
+    pub ship: Option<ShipInfo>,
+    /// 是否为 GoldSrc 旧格式回复，旧格式没有版本号、Steam ID 等信息
+    pub goldsrc: bool,
+    /// GoldSrc 模组信息，仅旧格式回复
+    pub goldsrc_mod: Option<GoldSrcMod>,
+    /// 从发出最后一次请求到收到完整回复的时间
+    pub latency: Duration,
+}
+
+impl ServerInfo {
+    /// 根据回复类型解析 A2S_INFO 回复
+    pub fn parse_response(kind: u8, data: &[u8], latency: Duration) -> Result<Self, Error> {
+        match kind {
+            INFO_RESPONSE => Self::parse(data, latency),
+            GOLDSRC_INFO_RESPONSE => Self::parse_goldsrc(data, latency),
+            kind => Err(Error::Protocol(format!(
+                "unexpected info response type {:?}",
+                kind as char
+            ))),
+        }
+    }
+
+    /// 解析 A2S_INFO 回复，不含回复类型
+    pub fn parse(data: &[u8], latency: Duration) -> Result<Self, Error> {
+        let mut reader = Reader::new(data);
+        let mut info = ServerInfo {
+            protocol: reader.u8()?,
+            name: reader.string()?,
+            map: reader.string()?,
+            folder: reader.string()?,
+            game: reader.string()?,
+            app_id: reader.u16()?,
+            players: reader.u8()?,
+            max_players: reader.u8()?,
+            bots: reader.u8()?,
+            server_type: reader.u8()?.into(),
+            environment: reader.u8()?.into(),
+            password: reader.u8()? != 0,
+            vac: reader.u8()? != 0,
+            version: String::new(),
+            port: None,
+            steam_id: None,
+            source_tv: None,
+            keywords: None,
+            game_id: None,
+            ship: None,
+            goldsrc: false,
+            goldsrc_mod: None,
+            latency,
+        };
+        if info.app_id == THE_SHIP_APP_ID {
+            info.ship = Some(ShipInfo {
+                mode: reader.u8()?,
+                witnesses: reader.u8()?,
+                duration: reader.u8()?,
+            });
+        }
+        info.version = reader.string()?;
+
+        // 较老的服务器没有额外数据
+        if reader.is_empty() {
+            return Ok(info);
+        }
+        let edf = reader.u8()?;
+        if edf & EDF_PORT != 0 {
+            info.port = Some(reader.u16()?);
+        }
+        if edf & EDF_STEAM_ID != 0 {
+            info.steam_id = Some(reader.u64()?);
+        }
+        if edf & EDF_SOURCE_TV != 0 {
+            info.source_tv = Some(SourceTv {
+                port: reader.u16()?,
+                name: reader.string()?,
+            });
+        }
+        if edf & EDF_KEYWORDS != 0 {
+            info.keywords = Some(reader.string()?);
+        }
+        if edf & EDF_GAME_ID != 0 {
+            info.game_id = Some(reader.u64()?);
+        }
+        Ok(info)
+    }
+
+    /// 解析 GoldSrc 旧格式的 A2S_INFO 回复，不含回复类型
+    ///
+    /// 字段顺序与新格式不同，AppId 根据游戏目录推断，未知时为 0；游戏端口取自服务器上报的地址。
+    pub fn parse_goldsrc(data: &[u8], latency: Duration) -> Result<Self, Error> {
+        let mut reader = Reader::new(data);
+        let address = reader.string()?;
+        let name = reader.string()?;
+        let map = reader.string()?;
+        let folder = reader.string()?;
+        let game = reader.string()?;
+        let players = reader.u8()?;
+        let max_players = reader.u8()?;
+        let protocol = reader.u8()?;
+        let server_type = reader.u8()?.into();
+        let environment = reader.u8()?.into();
+        let password = reader.u8()? != 0;
+        let goldsrc_mod = if reader.u8()? != 0 {
+            let link = reader.string()?;
+            let download_link = reader.string()?;
+            reader.u8()?;
+            Some(GoldSrcMod {
+                link,
+                download_link,
+                version: reader.u32()?,
+                size: reader.u32()?,
+                multiplayer_only: reader.u8()? != 0,
+                custom_dll: reader.u8()? != 0,
+            })
+        } else {
+            None
+        };
+        let vac = reader.u8()? != 0;
+        let bots = reader.u8()?;
+
+        let app_id = GOLDSRC_APP_IDS
+            .iter()
+            .find(|(dir, _)| folder.eq_ignore_ascii_case(dir))
+            .map_or(0, |(_, app_id)| *app_id);
+        let port = address
+            .rsplit_once(':')
+            .and_then(|(_, port)| port.parse().ok());
+
+        Ok(ServerInfo {
+            protocol,
+            name,
+            map,
+            folder,
+            game,
+            app_id,
+            players,
+            max_players,
+            bots,
+            server_type,
+            environment,
+            password,
+            vac,
+            version: String::new(),
+            port,
+            steam_id: None,
+            source_tv: None,
+            keywords: None,
+            game_id: None,
+            ship: None,
+            goldsrc: true,
+            goldsrc_mod,
+            latency,
+        })
+    }
+}
+
+/// The Ship 玩家的额外信息
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ShipPlayer {
+    pub deaths: i32,
+    pub money: i32,
+}
+
+/// A2S_PLAYER 回复中的一位玩家
+#[derive(Debug, Clone, PartialEq)]
+pub struct Player {
+    pub index: u8,
+    pub name: String,
+    pub score: i32,
+    /// 在服务器中的时长（秒）
+    pub duration: f32,
+    /// The Ship 的额外信息
+    pub ship: Option<ShipPlayer>,
+}
+
+impl Player {
+    /// 解析 A2S_PLAYER 回复，不含回复类型
+    ///
+    /// 玩家数量只占一个字节，超过 255 位玩家时不准确，这里读取到数据末尾为止。
+    /// The Ship 在所有玩家之后附加每位玩家的死亡数与金钱，读完声明数量的玩家后
+    /// 剩余数据恰好是每位玩家 8 字节时按额外信息解析。
+    pub fn parse_list(data: &[u8]) -> Result<Vec<Self>, Error> {
+        let mut reader = Reader::new(data);
+        let count = reader.u8()? as usize;
+        let mut players: Vec<Player> = Vec::with_capacity(count);
+        while !reader.is_empty() {
+            if players.len() == count && reader.remaining() == count * 8 {
+                for player in &mut players {
+                    player.ship = Some(ShipPlayer {
+                        deaths: reader.i32()?,
+                        money: reader.i32()?,
+                    });
+                }
+                break;
+            }
+            players.push(Player {
+                index: reader.u8()?,
+                name: reader.string()?,
+                score: reader.i32()?,
+                duration: reader.f32()?,
+                ship: None,
+            });
+        }
+        Ok(players)
+    }
+}
+
+/// A2S_RULES 回复中的一条规则
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rule {
+    pub name: String,
+    pub value: String,
+}
+
+impl Rule {
+    /// 解析 A2S_RULES 回复，不含回复类型
+    ///
+    /// 部分游戏的规则过多时会被截断，缺少 `\0` 结尾的最后一条规则会被丢弃。
+    pub fn parse_list(data: &[u8]) -> Result<Vec<Self>, Error> {
+        let mut reader = Reader::new(data);
+        let count = reader.u16()?;
+        let mut rules = Vec::with_capacity(count as usize);
+        while reader.has_string() {
+            let name = reader.string()?;
+            if !reader.has_string() {
+                break;
+            }
+            let value = reader.string()?;
+            rules.push(Rule { name, value });
+        }
+        Ok(rules)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A2S_INFO 回复的固定部分，不含回复类型与 EDF
+    fn fixed_info(app_id: u16) -> Vec<u8> {
+        let mut data = vec![17];
+        for field in [
+            "My Server",
+            "de_dust2",
+            "csgo",
+            "Counter-Strike: Global Offensive",
+        ] {
+            data.extend_from_slice(field.as_bytes());
+            data.push(0);
+        }
+        data.extend_from_slice(&app_id.to_le_bytes());
+        data.extend_from_slice(&[12, 24, 2, b'd', b'l', 0, 1]);
+        data
+    }
+
+    #[test]
+    fn parses_info_without_edf() {
+        let mut data = fixed_info(730);
+        data.extend_from_slice(b"1.38.7.9\0");
+        let info = ServerInfo::parse(&data, Duration::from_millis(20)).unwrap();
+        assert_eq!(info.name, "My Server");
+        assert_eq!(info.map, "de_dust2");
+        assert_eq!(info.app_id, 730);
+        assert_eq!((info.players, info.max_players, info.bots), (12, 24, 2));
+        assert_eq!(info.server_type, ServerType::Dedicated);
+        assert_eq!(info.environment, Environment::Linux);
+        assert!(!info.password && info.vac);
+        assert_eq!(info.version, "1.38.7.9");
+        assert_eq!(info.port, None);
+        assert_eq!(info.keywords, None);
+        assert_eq!(info.latency, Duration::from_millis(20));
+    }
+
+    #[test]
+    fn parses_info_edf() {
+        let mut data = fixed_info(730);
+        data.extend_from_slice(b"1.38.7.9\0");
+        data.push(EDF_PORT | EDF_STEAM_ID | EDF_SOURCE_TV | EDF_KEYWORDS | EDF_GAME_ID);
+        data.extend_from_slice(&27016u16.to_le_bytes());
+        data.extend_from_slice(&90_123_456_789_012_345u64.to_le_bytes());
+        data.extend_from_slice(&27020u16.to_le_bytes());
+        data.extend_from_slice(b"GOTV\0");
+        data.extend_from_slice(b"secure,competitive\0");
+        data.extend_from_slice(&730u64.to_le_bytes());
+
+        let info = ServerInfo::parse(&data, Duration::ZERO).unwrap();
+        assert_eq!(info.port, Some(27016));
+        assert_eq!(info.steam_id, Some(90_123_456_789_012_345));
+        assert_eq!(
+            info.source_tv,
+            Some(SourceTv {
+                port: 27020,
+                name: "GOTV".to_string()
+            })
+        );
+        assert_eq!(info.keywords.as_deref(), Some("secure,competitive"));
+        assert_eq!(info.game_id, Some(730));
+    }
+
+    #[test]
+    fn parses_partial_edf() {
+        let mut data = fixed_info(440);
+        data.extend_from_slice(b"8622567\0");
+        data.push(EDF_PORT | EDF_KEYWORDS);
+        data.extend_from_slice(&27015u16.to_le_bytes());
+        data.extend_from_slice(b"payload\0");
+
+        let info = ServerInfo::parse(&data, Duration::ZERO).unwrap();
+        assert_eq!(info.port, Some(27015));
+        assert_eq!(info.steam_id, None);
+        assert_eq!(info.source_tv, None);
+        assert_eq!(info.keywords.as_deref(), Some("payload"));
+        assert_eq!(info.game_id, None);
+
+        // EDF 声明的字段缺失时报错
+        let mut data = fixed_info(440);
+        data.extend_from_slice(b"8622567\0");
+        data.push(EDF_STEAM_ID);
+        data.extend_from_slice(&[1, 2, 3, 4]);
+        assert!(ServerInfo::parse(&data, Duration::ZERO).is_err());
+    }
+
+    #[test]
+    fn parses_the_ship_info() {
+        let mut data = fixed_info(THE_SHIP_APP_ID);
+        data.extend_from_slice(&[1, 3, 10]);
+        data.extend_from_slice(b"1.0.0.4\0");
+        let info = ServerInfo::parse(&data, Duration::ZERO).unwrap();
+        assert_eq!(
+            info.ship,
+            Some(ShipInfo {
+                mode: 1,
+                witnesses: 3,
+                duration: 10
+            })
+        );
+        assert_eq!(info.version, "1.0.0.4");
+    }
+
+    #[test]
+    fn parses_goldsrc_info() {
+        let mut data = Vec::new();
+        for field in [
+            "192.168.1.10:27016",
+            "Old Server",
+            "de_aztec",
+            "cstrike",
+            "Counter-Strike",
+        ] {
+            data.extend_from_slice(field.as_bytes());
+            data.push(0);
+        }
+        data.extend_from_slice(&[5, 32, 47, b'D', b'W', 1, 0, 1, 3]);
+
+        let info =
+            ServerInfo::parse_response(GOLDSRC_INFO_RESPONSE, &data, Duration::ZERO).unwrap();
+        assert!(info.goldsrc);
+        assert_eq!(info.app_id, 10);
+        assert_eq!(info.port, Some(27016));
+        assert_eq!(info.environment, Environment::Windows);
+        assert!(info.password && info.vac);
+        assert_eq!(info.bots, 3);
+        assert_eq!(info.goldsrc_mod, None);
+    }
+
+    #[test]
+    fn parses_players_and_rules() {
+        let mut data = vec![2];
+        for (index, name, score) in [(0u8, "alice", 10i32), (1, "bob", -1)] {
+            data.push(index);
+            data.extend_from_slice(name.as_bytes());
+            data.push(0);
+            data.extend_from_slice(&score.to_le_bytes());
+            data.extend_from_slice(&61.5f32.to_le_bytes());
+        }
+        let players = Player::parse_list(&data).unwrap();
+        assert_eq!(players.len(), 2);
+        assert_eq!(players[1].name, "bob");
+        assert_eq!(players[1].score, -1);
+        assert_eq!(players[0].duration, 61.5);
+        assert_eq!(players[0].ship, None);
+
+        let mut data = 3u16.to_le_bytes().to_vec();
+        data.extend_from_slice(b"mp_friendlyfire\x000\0sv_cheats\x000\0sv_tags\0");
+        let rules = Rule::parse_list(&data).unwrap();
+        // 被截断的最后一条规则被丢弃
+        assert_eq!(
+            rules,
+            vec![
+                Rule {
+                    name: "mp_friendlyfire".to_string(),
+                    value: "0".to_string()
+                },
+                Rule {
+                    name: "sv_cheats".to_string(),
+                    value: "0".to_string()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn drops_rule_cut_mid_string() {
+        let mut data = 2u16.to_le_bytes().to_vec();
+        data.extend_from_slice(b"sv_cheats\x000\0sv_tags\0increased_maxplay");
+        let rules = Rule::parse_list(&data).unwrap();
+        assert_eq!(
+            rules,
+            vec![Rule {
+                name: "sv_cheats".to_string(),
+                value: "0".to_string()
+            }]
+        );
+
+        let mut data = 2u16.to_le_bytes().to_vec();
+        data.extend_from_slice(b"sv_cheats\x000\0sv_ta");
+        assert_eq!(Rule::parse_list(&data).unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,420 @@
+//! Source 服务器查询协议（A2S）
+//!
+//! 直接通过 UDP 查询服务器信息、玩家与规则，不依赖 Steam 客户端。
+//! 处理 challenge 握手、拆分回复重组与 bzip2 压缩回复，兼容 GoldSrc 服务器的旧格式回复。
+
+pub mod batch;
+pub mod lan;
+pub mod message;
+pub mod packet;
+pub mod reader;
+
+use crate::error::SteamworksError;
+use message::{
+    Player, Rule, ServerInfo, CHALLENGE_RESPONSE, GOLDSRC_INFO_RESPONSE, INFO_RESPONSE,
+    PLAYER_RESPONSE, RULES_RESPONSE,
+};
+use packet::{Assembler, SINGLE_PACKET};
+use std::fmt;
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::time::{Duration, Instant};
+use tokio::net::UdpSocket;
+
+/// 未指定端口时使用的默认查询端口
+pub const DEFAULT_PORT: u16 = 27015;
+/// 默认查询超时
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// A2S_INFO 请求
+const INFO_REQUEST: &[u8] = b"TSource Engine Query\0";
+/// A2S_PLAYER 请求类型
+const PLAYER_REQUEST: u8 = b'U';
+/// A2S_RULES 请求类型
+const RULES_REQUEST: u8 = b'V';
+/// 请求 challenge 时使用的占位值
+const NO_CHALLENGE: [u8; 4] = [0xFF; 4];
+/// 服务器连续要求 challenge 的最大次数
+const MAX_CHALLENGES: usize = 3;
+/// 单个 UDP 包的最大大小
+const MAX_DATAGRAM: usize = 65535;
+
+#[derive(Debug)]
+pub enum Error {
+    /// 超时前没有收到完整回复
+    Timeout,
+    Io(std::io::Error),
+    /// 回复不符合协议
+    Protocol(String),
+}
+
+impl Error {
+    /// 附加请求名与地址，转换为插件统一错误类型
+    pub fn context(self, call: &'static str, target: impl fmt::Display) -> SteamworksError {
+        let target = target.to_string();
+        match self {
+            Error::Timeout => SteamworksError::Timeout { call, target },
+            Error::Io(error) => SteamworksError::Network {
+                call,
+                target,
+                error,
+            },
+            Error::Protocol(message) => SteamworksError::Protocol {
+                call,
+                target,
+                message,
+            },
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Timeout => write!(f, "no response"),
+            Error::Io(error) => write!(f, "{}", error),
+            Error::Protocol(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<std::io::Error> for Error {
+    fn from(error: std::io::Error) -> Self {
+        Error::Io(error)
+    }
+}
+
+/// 解析服务器地址，支持 `ip:port`、`域名:port` 与全角冒号，未指定端口时使用 27015
+pub async fn resolve(address: &str) -> Result<SocketAddr, Error> {
+    let address = address.trim().replace('：', ":");
+    if let Ok(address) = address.parse::<SocketAddr>() {
+        return Ok(address);
+    }
+
+    let has_port = match address.rsplit_once(':') {
+        Some((host, port)) => !host.is_empty() && port.parse::<u16>().is_ok(),
+        None => false,
+    };
+    let address = if has_port {
+        address
+    } else {
+        format!("{}:{}", address, DEFAULT_PORT)
+    };
+
+    let mut addresses = tokio::net::lookup_host(address.as_str()).await?;
+    addresses.next().ok_or_else(|| {
+        Error::Io(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            format!("no address found for {}", address),
+        ))
+    })
+}
+
+/// A2S_INFO 请求内容，不含包头
+fn info_request(challenge: Option<&[u8]>) -> Vec<u8> {
+    let mut request = INFO_REQUEST.to_vec();
+    if let Some(challenge) = challenge {
+        request.extend_from_slice(challenge);
+    }
+    request
+}
+
+/// 与单个服务器通信的查询连接
+pub struct Query {
+    socket: UdpSocket,
+    timeout: Duration,
+}
+
+impl Query {
+    /// 创建连接到指定服务器的查询，只接收该服务器的回复
+    pub async fn connect(address: SocketAddr, timeout: Duration) -> Result<Self, Error> {
+        let bind = if address.is_ipv4() {
+            SocketAddr::from((Ipv4Addr::UNSPECIFIED, 0))
+        } else {
+            SocketAddr::from((Ipv6Addr::UNSPECIFIED, 0))
+        };
+        let socket = UdpSocket::bind(bind).await?;
+        socket.connect(address).await?;
+        Ok(Self { socket, timeout })
+    }
+
+    /// 查询服务器信息
+    pub async fn info(&self) -> Result<ServerInfo, Error> {
+        let (kind, data, latency) = self
+            .request(&[INFO_RESPONSE, GOLDSRC_INFO_RESPONSE], info_request)
+            .await?;
+        ServerInfo::parse_response(kind, &data, latency)
+    }
+
+    /// 查询服务器中的玩家
+    pub async fn players(&self) -> Result<Vec<Player>, Error> {
+        let (_, data, _) = self
+            .request(&[PLAYER_RESPONSE], |challenge| {
+                let mut request = vec![PLAYER_REQUEST];
+                request.extend_from_slice(challenge.unwrap_or(&NO_CHALLENGE));
+                request
+            })
+            .await?;
+        Player::parse_list(&data)
+    }
+
+    /// 查询服务器规则（控制台变量）
+    pub async fn rules(&self) -> Result<Vec<Rule>, Error> {
+        let (_, data, _) = self
+            .request(&[RULES_RESPONSE], |challenge| {
+                let mut request = vec![RULES_REQUEST];
+                request.extend_from_slice(challenge.unwrap_or(&NO_CHALLENGE));
+                request
+            })
+            .await?;
+        Rule::parse_list(&data)
+    }
+
+    /// 发送请求，服务器要求 challenge 时携带 challenge 重新请求，整个过程共用一个超时
+    ///
+    /// @param expected 期望的回复类型
+    /// @param build 根据 challenge 生成请求内容，不含包头
+    /// @returns 回复类型、去掉回复类型的回复数据与延迟
+    async fn request(
+        &self,
+        expected: &[u8],
+        build: impl Fn(Option<&[u8]>) -> Vec<u8>,
+    ) -> Result<(u8, Vec<u8>, Duration), Error> {
+        let deadline = tokio::time::Instant::now() + self.timeout;
+        let mut challenge: Option<Vec<u8>> = None;
+        for _ in 0..MAX_CHALLENGES {
+            let mut request = SINGLE_PACKET.to_le_bytes().to_vec();
+            request.extend(build(challenge.as_deref()));
+
+            let start = Instant::now();
+            let response = self.exchange(&request, expected, deadline).await?;
+            let latency = start.elapsed();
+
+            match response.split_first() {
+                Some((&kind, data)) if expected.contains(&kind) => {
+                    return Ok((kind, data.to_vec(), latency))
+                }
+                Some((&CHALLENGE_RESPONSE, data)) if data.len() >= 4 => {
+                    challenge = Some(data[..4].to_vec());
+                }
+                Some((&kind, _)) => {
+                    return Err(Error::Protocol(format!(
+                        "unexpected response type {:?}, expected {:?}",
+                        kind as char, expected[0] as char
+                    )))
+                }
+                None => return Err(Error::Protocol("empty response".to_string())),
+            }
+        }
+        Err(Error::Protocol(format!(
+            "server kept sending challenges after {} attempts",
+            MAX_CHALLENGES
+        )))
+    }
+
+    /// 发送一个请求并等待完整的回复
+    ///
+    /// GoldSrc 服务器可能对 A2S_INFO 同时回复新旧两种格式，之前的请求多出的信息回复会被跳过。
+    async fn exchange(
+        &self,
+        request: &[u8],
+        expected: &[u8],
+        deadline: tokio::time::Instant,
+    ) -> Result<Vec<u8>, Error> {
+        self.socket.send(request).await?;
+
+        let mut assembler = Assembler::default();
+        let mut buffer = vec![0; MAX_DATAGRAM];
+        tokio::time::timeout_at(deadline, async {
+            loop {
+                let len = self.socket.recv(&mut buffer).await?;
+                match assembler.push(&buffer[..len])? {
+                    Some(response) if is_stale_info(&response, expected) => {}
+                    Some(response) => return Ok(response),
+                    None => {}
+                }
+            }
+        })
+        .await
+        .map_err(|_| Error::Timeout)?
+    }
+}
+
+/// 是否为之前的 A2S_INFO 请求多出的回复
+fn is_stale_info(response: &[u8], expected: &[u8]) -> bool {
+    matches!(
+        response.first(),
+        Some(kind @ (&INFO_RESPONSE | &GOLDSRC_INFO_RESPONSE)) if !expected.contains(kind)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    const CHALLENGE: [u8; 4] = [0x12, 0x34, 0x56, 0x78];
+
+    /// 本地回环上的模拟服务器，没有携带正确 challenge 的请求都先回复 challenge
+    ///
+    /// @returns 服务器地址与收到的请求
+    async fn stand_in_server(
+        reply: impl Fn(u8) -> Vec<Vec<u8>> + Send + 'static,
+    ) -> (SocketAddr, Arc<Mutex<Vec<Vec<u8>>>>) {
+        let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let address = socket.local_addr().unwrap();
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let received = requests.clone();
+        tokio::spawn(async move {
+            let mut buffer = vec![0; MAX_DATAGRAM];
+            loop {
+                let (len, peer) = socket.recv_from(&mut buffer).await.unwrap();
+                let request = buffer[..len].to_vec();
+                received.lock().unwrap().push(request.clone());
+
+                let datagrams = if request.ends_with(&CHALLENGE) {
+                    reply(request[4])
+                } else {
+                    let mut challenge = SINGLE_PACKET.to_le_bytes().to_vec();
+                    challenge.push(CHALLENGE_RESPONSE);
+                    challenge.extend_from_slice(&CHALLENGE);
+                    vec![challenge]
+                };
+                for datagram in datagrams {
+                    socket.send_to(&datagram, peer).await.unwrap();
+                }
+            }
+        });
+        (address, requests)
+    }
+
+    fn single(kind: u8, data: &[u8]) -> Vec<u8> {
+        let mut datagram = SINGLE_PACKET.to_le_bytes().to_vec();
+        datagram.push(kind);
+        datagram.extend_from_slice(data);
+        datagram
+    }
+
+    fn reply(kind: u8) -> Vec<Vec<u8>> {
+        match kind {
+            b'T' => vec![single(
+                INFO_RESPONSE,
+                b"\x11Stand-in\0cp_badlands\0tf\0Team Fortress\0\xb8\x01\x03\x18\x00dl\x00\x018622567\0",
+            )],
+            PLAYER_REQUEST => {
+                let mut data = vec![1, 0];
+                data.extend_from_slice(b"alice\0");
+                data.extend_from_slice(&7i32.to_le_bytes());
+                data.extend_from_slice(&30f32.to_le_bytes());
+                vec![single(PLAYER_RESPONSE, &data)]
+            }
+            RULES_REQUEST => {
+                // 拆分为两个分包并乱序发送
+                let data = single(RULES_RESPONSE, b"\x02\x00sv_gravity\x00800\0mp_timelimit\x0030\0");
+                let (first, second) = data.split_at(20);
+                let part = |number: u8, data: &[u8]| {
+                    let mut datagram = packet::SPLIT_PACKET.to_le_bytes().to_vec();
+                    datagram.extend_from_slice(&42u32.to_le_bytes());
+                    datagram.extend_from_slice(&[2, number]);
+                    datagram.extend_from_slice(&1248u16.to_le_bytes());
+                    datagram.extend_from_slice(data);
+                    datagram
+                };
+                vec![part(1, second), part(0, first)]
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn challenge_handshake() {
+        let (address, requests) = stand_in_server(reply).await;
+        let query = Query::connect(address, Duration::from_secs(2))
+            .await
+            .unwrap();
+
+        let info = query.info().await.unwrap();
+        assert_eq!(info.name, "Stand-in");
+        assert_eq!(info.map, "cp_badlands");
+        assert_eq!(info.app_id, 440);
+        assert_eq!(info.version, "8622567");
+
+        let players = query.players().await.unwrap();
+        assert_eq!(players.len(), 1);
+        assert_eq!(players[0].name, "alice");
+        assert_eq!(players[0].score, 7);
+
+        let rules = query.rules().await.unwrap();
+        assert_eq!(rules.len(), 2);
+        assert_eq!(rules[0].name, "sv_gravity");
+        assert_eq!(rules[1].value, "30");
+
+        let requests = requests.lock().unwrap();
+        let mut info_request = SINGLE_PACKET.to_le_bytes().to_vec();
+        info_request.extend_from_slice(INFO_REQUEST);
+        let mut player_request = SINGLE_PACKET.to_le_bytes().to_vec();
+        player_request.push(PLAYER_REQUEST);
+        let mut rules_request = SINGLE_PACKET.to_le_bytes().to_vec();
+        rules_request.push(RULES_REQUEST);
+        let with = |request: &[u8], challenge: &[u8]| [request, challenge].concat();
+        assert_eq!(
+            *requests,
+            vec![
+                info_request.clone(),
+                with(&info_request, &CHALLENGE),
+                with(&player_request, &NO_CHALLENGE),
+                with(&player_request, &CHALLENGE),
+                with(&rules_request, &NO_CHALLENGE),
+                with(&rules_request, &CHALLENGE),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn gives_up_on_endless_challenges() {
+        let (address, requests) = stand_in_server(|_| Vec::new()).await;
+        let query = Query::connect(address, Duration::from_secs(2))
+            .await
+            .unwrap();
+        // 携带的 challenge 永远不被接受
+        let error = query
+            .request(&[PLAYER_RESPONSE], |_| vec![PLAYER_REQUEST, 0, 0, 0, 0])
+            .await
+            .unwrap_err();
+        assert!(matches!(error, Error::Protocol(_)));
+        assert_eq!(requests.lock().unwrap().len(), MAX_CHALLENGES);
+    }
+
+    #[tokio::test]
+    async fn times_out_without_response() {
+        let (address, _) = stand_in_server(|_| Vec::new()).await;
+        let query = Query::connect(address, Duration::from_millis(100))
+            .await
+            .unwrap();
+        // 携带正确 challenge 后服务器不再回复
+        let error = query
+            .request(&[PLAYER_RESPONSE], |_| {
+                [&[PLAYER_REQUEST][..], &CHALLENGE].concat()
+            })
+            .await
+            .unwrap_err();
+        assert!(matches!(error, Error::Timeout));
+        assert!(error
+            .context("query_players", address)
+            .to_string()
+            .starts_with("[TIMEOUT] query_players"));
+    }
+
+    #[tokio::test]
+    async fn resolves_addresses() {
+        assert_eq!(
+            resolve("127.0.0.1：27016").await.unwrap(),
+            "127.0.0.1:27016".parse().unwrap()
+        );
+        assert_eq!(
+            resolve(" 127.0.0.1 ").await.unwrap(),
+            "127.0.0.1:27015".parse().unwrap()
+        );
+    }
+}
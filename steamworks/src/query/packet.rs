@@ -0,0 +1,398 @@
+//! A2S 数据包拆分与重组
+//!
+//! 回复超过一个 UDP 包时服务器会拆分发送，每个分包带有相同的回复 ID、总数与序号，
+//! 分包可能乱序到达。回复 ID 最高位为 1 时，重组后的数据经过 bzip2 压缩，
+//! 第一个分包额外携带解压后的大小与 CRC32 校验值。
+//!
+//! GoldSrc 与 Orange Box 之前的 Source 服务器使用不同的分包头，包头中没有格式标记，
+//! 只能根据第一个分包的数据判断，格式确定之前收到的其他分包会先缓存。
+
+use super::reader::Reader;
+use super::Error;
+use bzip2::read::BzDecoder;
+use std::io::Read;
+use std::mem;
+
+/// 单包回复的包头
+pub const SINGLE_PACKET: i32 = -1;
+/// 拆分回复的包头
+pub const SPLIT_PACKET: i32 = -2;
+
+/// 压缩回复的回复 ID 标记
+const COMPRESSED_FLAG: u32 = 0x8000_0000;
+/// 解压后允许的最大大小，防止异常数据占用过多内存
+const MAX_DECOMPRESSED_SIZE: u32 = 1024 * 1024;
+/// 格式确定之前最多缓存的分包数量
+const MAX_PENDING: usize = 16;
+
+/// 分包头格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SplitFormat {
+    /// 总数、序号各一个字节，之后是 2 字节的分包大小
+    Source,
+    /// Orange Box 之前的 Source 服务器，没有分包大小
+    SourceWithoutSize,
+    /// 一个字节，高 4 位为序号，低 4 位为总数
+    GoldSrc,
+}
+
+impl SplitFormat {
+    /// 根据分包判断格式，只有第一个分包可以判断，其他分包返回 None
+    ///
+    /// 第一个分包的数据以单包包头开头，压缩回复以 bzip2 标记开头，
+    /// 按各格式的包头长度检查这些标记出现的位置。
+    fn detect(datagram: &[u8]) -> Option<Self> {
+        let single = SINGLE_PACKET.to_le_bytes();
+        let starts_with = |offset: usize, marker: &[u8]| {
+            datagram
+                .get(offset..offset + marker.len())
+                .is_some_and(|data| data == marker)
+        };
+
+        if starts_with(9, &single) {
+            return Some(SplitFormat::GoldSrc);
+        }
+        if datagram.get(9) != Some(&0) {
+            return None;
+        }
+        if starts_with(12, &single) || starts_with(20, b"BZh") {
+            Some(SplitFormat::Source)
+        } else if starts_with(10, &single) {
+            Some(SplitFormat::SourceWithoutSize)
+        } else {
+            None
+        }
+    }
+}
+
+/// 正在重组的拆分回复
+struct Split {
+    id: u32,
+    parts: Vec<Option<Vec<u8>>>,
+    /// 解压后的大小与 CRC32，仅压缩回复
+    compression: Option<(u32, u32)>,
+}
+
+/// 拆分回复重组器，依次传入收到的 UDP 包，回复完整后返回去掉包头的数据
+#[derive(Default)]
+pub struct Assembler {
+    split: Option<Split>,
+    /// 服务器使用的分包格式，由第一个分包确定后沿用
+    format: Option<SplitFormat>,
+    /// 格式确定之前收到的分包
+    pending: Vec<Vec<u8>>,
+}
+
+impl Assembler {
+    /// 传入收到的 UDP 包
+    ///
+    /// @returns 回复完整时返回以回复类型开头的数据，仍在等待其他分包时返回 None
+    pub fn push(&mut self, datagram: &[u8]) -> Result<Option<Vec<u8>>, Error> {
+        let mut reader = Reader::new(datagram);
+        match reader.i32()? {
+            SINGLE_PACKET => Ok(Some(reader.rest().to_vec())),
+            SPLIT_PACKET => self.push_split(datagram),
+            header => Err(Error::Protocol(format!(
+                "unknown packet header {:#010x}",
+                header as u32
+            ))),
+        }
+    }
+
+    fn push_split(&mut self, datagram: &[u8]) -> Result<Option<Vec<u8>>, Error> {
+        let Some(format) = self.format.or_else(|| SplitFormat::detect(datagram)) else {
+            if self.pending.len() < MAX_PENDING {
+                self.pending.push(datagram.to_vec());
+            }
+            return Ok(None);
+        };
+        self.format = Some(format);
+
+        let mut response = self.push_part(format, datagram)?;
+        for datagram in mem::take(&mut self.pending) {
+            if let Some(assembled) = self.push_part(format, &datagram)? {
+                response = Some(assembled);
+            }
+        }
+        Ok(response)
+    }
+
+    fn push_part(
+        &mut self,
+        format: SplitFormat,
+        datagram: &[u8],
+    ) -> Result<Option<Vec<u8>>, Error> {
+        let mut reader = Reader::new(datagram);
+        reader.i32()?;
+        let id = reader.u32()?;
+        let (total, number) = match format {
+            SplitFormat::GoldSrc => {
+                let packed = reader.u8()?;
+                (packed & 0x0F, packed >> 4)
+            }
+            SplitFormat::Source | SplitFormat::SourceWithoutSize => (reader.u8()?, reader.u8()?),
+        };
+        if format == SplitFormat::Source {
+            // 单个分包的最大大小，只用于发送端
+            reader.u16()?;
+        }
+        // GoldSrc 不支持压缩，回复 ID 最高位没有特殊含义
+        let compressed = format != SplitFormat::GoldSrc && id & COMPRESSED_FLAG != 0;
+
+        if total == 0 || number >= total {
+            return Err(Error::Protocol(format!(
+                "invalid split packet {} of {}",
+                number, total
+            )));
+        }
+
+        // 收到新的回复时丢弃之前未完成的回复
+        let split = match &mut self.split {
+            Some(split) if split.id == id && split.parts.len() == total as usize => split,
+            split => split.insert(Split {
+                id,
+                parts: vec![None; total as usize],
+                compression: None,
+            }),
+        };
+
+        if number == 0 && compressed {
+            split.compression = Some((reader.u32()?, reader.u32()?));
+        }
+        split.parts[number as usize] = Some(reader.rest().to_vec());
+
+        if split.parts.iter().any(Option::is_none) {
+            return Ok(None);
+        }
+
+        let split = self.split.take().expect("split checked above");
+        let mut data: Vec<u8> = split.parts.into_iter().flatten().flatten().collect();
+        if compressed {
+            let (size, checksum) = split.compression.ok_or_else(|| {
+                Error::Protocol("compressed response is missing its size".to_string())
+            })?;
+            data = decompress(&data, size, checksum)?;
+        }
+
+        let mut reader = Reader::new(&data);
+        match reader.i32()? {
+            SINGLE_PACKET => Ok(Some(reader.rest().to_vec())),
+            header => Err(Error::Protocol(format!(
+                "unknown reassembled packet header {:#010x}",
+                header as u32
+            ))),
+        }
+    }
+}
+
+/// 解压 bzip2 压缩的回复并校验大小与 CRC32
+fn decompress(data: &[u8], size: u32, checksum: u32) -> Result<Vec<u8>, Error> {
+    if size > MAX_DECOMPRESSED_SIZE {
+        return Err(Error::Protocol(format!(
+            "compressed response too large ({} bytes)",
+            size
+        )));
+    }
+
+    let mut output = Vec::with_capacity(size as usize);
+    BzDecoder::new(data)
+        .take(u64::from(size) + 1)
+        .read_to_end(&mut output)
+        .map_err(|e| Error::Protocol(format!("failed to decompress response: {}", e)))?;
+
+    if output.len() != size as usize {
+        return Err(Error::Protocol(format!(
+            "decompressed size mismatch, expected {} bytes but got {}",
+            size,
+            output.len()
+        )));
+    }
+    if crc32(&output) != checksum {
+        return Err(Error::Protocol(
+            "decompressed response failed CRC32 check".to_string(),
+        ));
+    }
+    Ok(output)
+}
+
+/// CRC-32（IEEE 802.3）
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bzip2::write::BzEncoder;
+    use bzip2::Compression;
+    use std::io::Write;
+
+    /// 以单包包头开头的完整回复
+    fn response(payload: &[u8]) -> Vec<u8> {
+        let mut data = SINGLE_PACKET.to_le_bytes().to_vec();
+        data.extend_from_slice(payload);
+        data
+    }
+
+    fn source_part(id: u32, total: u8, number: u8, extra: &[u8], data: &[u8]) -> Vec<u8> {
+        let mut datagram = SPLIT_PACKET.to_le_bytes().to_vec();
+        datagram.extend_from_slice(&id.to_le_bytes());
+        datagram.extend_from_slice(&[total, number]);
+        datagram.extend_from_slice(&1248u16.to_le_bytes());
+        datagram.extend_from_slice(extra);
+        datagram.extend_from_slice(data);
+        datagram
+    }
+
+    fn goldsrc_part(id: u32, total: u8, number: u8, data: &[u8]) -> Vec<u8> {
+        let mut datagram = SPLIT_PACKET.to_le_bytes().to_vec();
+        datagram.extend_from_slice(&id.to_le_bytes());
+        datagram.push(number << 4 | total);
+        datagram.extend_from_slice(data);
+        datagram
+    }
+
+    /// 依次传入所有分包，返回最后一个分包的结果，之前的分包都应返回 None
+    fn assemble(datagrams: &[Vec<u8>]) -> Result<Option<Vec<u8>>, Error> {
+        let mut assembler = Assembler::default();
+        let (last, rest) = datagrams.split_last().unwrap();
+        for datagram in rest {
+            assert_eq!(assembler.push(datagram).unwrap(), None);
+        }
+        assembler.push(last)
+    }
+
+    #[test]
+    fn single_packet() {
+        let mut assembler = Assembler::default();
+        assert_eq!(
+            assembler.push(&response(b"Ihello")).unwrap(),
+            Some(b"Ihello".to_vec())
+        );
+        assert!(matches!(
+            assembler.push(&[0xFE, 0xFF, 0xFF, 0x7F]),
+            Err(Error::Protocol(_))
+        ));
+    }
+
+    #[test]
+    fn reassembles_out_of_order_source_split() {
+        let data = response(b"Efirst part|second part|third part");
+        let parts: Vec<_> = data.chunks(14).collect();
+        assert_eq!(parts.len(), 3);
+
+        let split = |number: usize| source_part(7, 3, number as u8, &[], parts[number]);
+        // 第一个分包之前到达的分包先缓存，格式确定后再处理
+        assert_eq!(
+            assemble(&[split(2), split(0), split(1)]).unwrap(),
+            Some(data[4..].to_vec())
+        );
+        assert_eq!(
+            assemble(&[split(1), split(2), split(0)]).unwrap(),
+            Some(data[4..].to_vec())
+        );
+    }
+
+    #[test]
+    fn reassembles_source_split_without_size() {
+        let data = response(b"Dold source engine");
+        let (first, second) = data.split_at(10);
+        let part = |number: u8, data: &[u8]| {
+            let mut datagram = SPLIT_PACKET.to_le_bytes().to_vec();
+            datagram.extend_from_slice(&3u32.to_le_bytes());
+            datagram.extend_from_slice(&[2, number]);
+            datagram.extend_from_slice(data);
+            datagram
+        };
+        assert_eq!(
+            assemble(&[part(1, second), part(0, first)]).unwrap(),
+            Some(data[4..].to_vec())
+        );
+    }
+
+    #[test]
+    fn new_response_discards_incomplete_split() {
+        let old = response(b"Estale response");
+        let new = response(b"Efresh response");
+        let (old_first, _) = old.split_at(8);
+        let (first, second) = new.split_at(8);
+        assert_eq!(
+            assemble(&[
+                source_part(1, 2, 0, &[], old_first),
+                source_part(2, 2, 1, &[], second),
+                source_part(2, 2, 0, &[], first),
+            ])
+            .unwrap(),
+            Some(new[4..].to_vec())
+        );
+    }
+
+    #[test]
+    fn reassembles_goldsrc_split() {
+        let data = response(b"mgoldsrc info reply");
+        let (first, second) = data.split_at(12);
+        // GoldSrc 不支持压缩，回复 ID 最高位不表示压缩
+        let id = 0x8000_0005;
+        assert_eq!(
+            assemble(&[
+                goldsrc_part(id, 2, 1, second),
+                goldsrc_part(id, 2, 0, first)
+            ])
+            .unwrap(),
+            Some(data[4..].to_vec())
+        );
+    }
+
+    fn compress(data: &[u8]) -> Vec<u8> {
+        let mut encoder = BzEncoder::new(Vec::new(), Compression::best());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    /// 压缩回复的分包，第一个分包附带解压后的大小与校验值
+    fn compressed_parts(data: &[u8], checksum: u32) -> Vec<Vec<u8>> {
+        let compressed = compress(data);
+        let (first, second) = compressed.split_at(compressed.len() / 2);
+        let id = COMPRESSED_FLAG | 9;
+        let mut extra = (data.len() as u32).to_le_bytes().to_vec();
+        extra.extend_from_slice(&checksum.to_le_bytes());
+        vec![
+            source_part(id, 2, 1, &[], second),
+            source_part(id, 2, 0, &extra, first),
+        ]
+    }
+
+    #[test]
+    fn decompresses_bzip2_split() {
+        let data = response(&b"Erule\0value\0".repeat(64));
+        assert_eq!(
+            assemble(&compressed_parts(&data, crc32(&data))).unwrap(),
+            Some(data[4..].to_vec())
+        );
+    }
+
+    #[test]
+    fn rejects_bzip2_split_with_bad_checksum() {
+        let data = response(&b"Erule\0value\0".repeat(64));
+        match assemble(&compressed_parts(&data, crc32(&data) ^ 1)) {
+            Err(Error::Protocol(message)) => assert!(message.contains("CRC32"), "{}", message),
+            result => panic!("unexpected result {:?}", result),
+        }
+    }
+
+    #[test]
+    fn crc32_check_value() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+    }
+}
@@ -0,0 +1,98 @@
+//! A2S 数据读取
+//!
+//! 所有整数均为小端序，字符串以 `\0` 结尾。
+
+use super::Error;
+
+pub struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    /// 剩余未读取的字节数
+    pub fn remaining(&self) -> usize {
+        self.data.len() - self.pos
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.remaining() == 0
+    }
+
+    /// 读取指定长度的字节
+    pub fn bytes(&mut self, len: usize) -> Result<&'a [u8], Error> {
+        if self.remaining() < len {
+            return Err(Error::Protocol(format!(
+                "unexpected end of packet, need {} bytes at offset {} but only {} left",
+                len,
+                self.pos,
+                self.remaining()
+            )));
+        }
+        let bytes = &self.data[self.pos..self.pos + len];
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    /// 读取剩余的所有字节
+    pub fn rest(&mut self) -> &'a [u8] {
+        let rest = &self.data[self.pos..];
+        self.pos = self.data.len();
+        rest
+    }
+
+    pub fn u8(&mut self) -> Result<u8, Error> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    pub fn u16(&mut self) -> Result<u16, Error> {
+        Ok(u16::from_le_bytes(self.array()?))
+    }
+
+    pub fn i32(&mut self) -> Result<i32, Error> {
+        Ok(i32::from_le_bytes(self.array()?))
+    }
+
+    pub fn u32(&mut self) -> Result<u32, Error> {
+        Ok(u32::from_le_bytes(self.array()?))
+    }
+
+    pub fn u64(&mut self) -> Result<u64, Error> {
+        Ok(u64::from_le_bytes(self.array()?))
+    }
+
+    pub fn f32(&mut self) -> Result<f32, Error> {
+        Ok(f32::from_le_bytes(self.array()?))
+    }
+
+    /// 剩余数据中是否有以 `\0` 结尾的完整字符串
+    pub fn has_string(&self) -> bool {
+        self.data[self.pos..].contains(&0)
+    }
+
+    /// 读取以 `\0` 结尾的字符串，缺少结尾时读取到数据末尾
+    ///
+    /// 部分游戏会发送非 UTF-8 的名称，无效的字节会被替换为 U+FFFD。
+    pub fn string(&mut self) -> Result<String, Error> {
+        if self.is_empty() {
+            return Err(Error::Protocol(format!(
+                "unexpected end of packet, expected string at offset {}",
+                self.pos
+            )));
+        }
+        let rest = &self.data[self.pos..];
+        let len = rest.iter().position(|&b| b == 0).unwrap_or(rest.len());
+        self.pos += (len + 1).min(rest.len());
+        Ok(String::from_utf8_lossy(&rest[..len]).into_owned())
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], Error> {
+        let mut array = [0; N];
+        array.copy_from_slice(self.bytes(N)?);
+        Ok(array)
+    }
+}
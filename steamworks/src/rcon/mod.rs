@@ -0,0 +1,507 @@
+//! Source RCON 客户端
+//!
+//! 通过 TCP 执行服务器控制台命令。回复超过一个包时服务器会拆分发送且没有结束标记，
+//! 这里在每条命令后追加一个空的 SERVERDATA_RESPONSE_VALUE 包：服务器按顺序处理并原样回复，
+//! 收到它的回复即表示命令的回复已经完整。
+
+pub mod packet;
+
+use crate::api::logging::steam_log;
+use crate::error::SteamworksError;
+use crate::query;
+use packet::{
+    Packet, SERVERDATA_AUTH, SERVERDATA_AUTH_RESPONSE, SERVERDATA_EXECCOMMAND,
+    SERVERDATA_RESPONSE_VALUE,
+};
+use std::fmt;
+use std::io::ErrorKind;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::net::TcpStream;
+
+/// 默认的连接、验证与命令超时
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug)]
+pub enum Error {
+    /// 超时前没有收到完整回复
+    Timeout,
+    Io(std::io::Error),
+    /// 回复不符合协议
+    Protocol(String),
+    /// 密码错误
+    AuthFailed,
+}
+
+impl Error {
+    /// 附加请求名与地址，转换为插件统一错误类型
+    pub fn context(self, call: &'static str, target: impl fmt::Display) -> SteamworksError {
+        let target = target.to_string();
+        match self {
+            Error::Timeout => SteamworksError::Timeout { call, target },
+            Error::Io(error) => SteamworksError::Network {
+                call,
+                target,
+                error,
+            },
+            Error::Protocol(message) => SteamworksError::Protocol {
+                call,
+                target,
+                message,
+            },
+            Error::AuthFailed => SteamworksError::AuthFailed { call, target },
+        }
+    }
+
+    /// 是否为连接被对方关闭
+    fn is_disconnect(&self) -> bool {
+        match self {
+            Error::Io(error) => matches!(
+                error.kind(),
+                ErrorKind::UnexpectedEof
+                    | ErrorKind::ConnectionReset
+                    | ErrorKind::ConnectionAborted
+                    | ErrorKind::BrokenPipe
+            ),
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Timeout => write!(f, "no response"),
+            Error::Io(error) => write!(f, "{}", error),
+            Error::Protocol(message) => write!(f, "{}", message),
+            Error::AuthFailed => write!(f, "password rejected"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<std::io::Error> for Error {
+    fn from(error: std::io::Error) -> Self {
+        Error::Io(error)
+    }
+}
+
+impl From<query::Error> for Error {
+    fn from(error: query::Error) -> Self {
+        match error {
+            query::Error::Timeout => Error::Timeout,
+            query::Error::Io(error) => Error::Io(error),
+            query::Error::Protocol(message) => Error::Protocol(message),
+        }
+    }
+}
+
+/// RCON 连接，断开后执行命令时自动重新连接并验证
+pub struct Connection {
+    address: String,
+    password: String,
+    /// 连接、验证与每条命令各自的超时
+    timeout: Duration,
+    stream: Option<TcpStream>,
+    next_id: i32,
+    closed: bool,
+}
+
+impl Connection {
+    /// 连接服务器并验证密码
+    ///
+    /// @param address 服务器地址，未指定端口时使用 27015
+    pub async fn connect(address: &str, password: &str, timeout: Duration) -> Result<Self, Error> {
+        let mut connection = Self {
+            address: address.to_string(),
+            password: password.to_string(),
+            timeout,
+            stream: None,
+            next_id: 1,
+            closed: false,
+        };
+        connection.open().await?;
+        Ok(connection)
+    }
+
+    pub fn address(&self) -> &str {
+        &self.address
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.stream.is_some()
+    }
+
+    /// 关闭连接，之后不能再执行命令
+    pub fn close(&mut self) {
+        self.closed = true;
+        self.stream = None;
+    }
+
+    /// 执行命令并返回完整的回复
+    ///
+    /// 空闲连接可能已被服务器关闭，在收到任何回复之前发现连接断开时重新连接并重试一次，
+    /// 其他错误之后连接会被丢弃，下一条命令重新连接。
+    pub async fn exec(&mut self, command: &str) -> Result<String, Error> {
+        if self.closed {
+            return Err(Error::Io(std::io::Error::new(
+                ErrorKind::NotConnected,
+                "connection closed",
+            )));
+        }
+
+        let reconnected = self.stream.is_none();
+        if reconnected {
+            self.open().await?;
+        }
+
+        let result = match self.run(command).await {
+            Err((error, false)) if !reconnected && error.is_disconnect() => {
+                steam_log!(
+                    Debug,
+                    "rcon",
+                    "{} disconnected ({}), reconnecting",
+                    self.address,
+                    error
+                );
+                self.stream = None;
+                self.open().await?;
+                self.run(command).await
+            }
+            result => result,
+        };
+
+        result.map_err(|(error, _)| {
+            self.stream = None;
+            error
+        })
+    }
+
+    fn next_id(&mut self) -> i32 {
+        let id = self.next_id;
+        // ID 为 -1 表示验证失败，只使用正数
+        self.next_id = self.next_id.checked_add(1).unwrap_or(1);
+        id
+    }
+
+    /// 建立连接并验证密码
+    async fn open(&mut self) -> Result<(), Error> {
+        let id = self.next_id();
+        let stream = tokio::time::timeout(self.timeout, async {
+            let target = query::resolve(&self.address).await?;
+            let mut stream = TcpStream::connect(target).await?;
+            stream.set_nodelay(true)?;
+            authenticate(&mut stream, id, &self.password).await?;
+            Ok::<_, Error>(stream)
+        })
+        .await
+        .map_err(|_| Error::Timeout)??;
+
+        self.stream = Some(stream);
+        Ok(())
+    }
+
+    /// 发送命令并读取回复
+    ///
+    /// @returns 失败时同时返回是否已经收到过该命令的回复
+    async fn run(&mut self, command: &str) -> Result<String, (Error, bool)> {
+        let id = self.next_id();
+        let sentinel = self.next_id();
+        let timeout = self.timeout;
+        let Some(stream) = self.stream.as_mut() else {
+            return Err((
+                Error::Io(std::io::Error::from(ErrorKind::NotConnected)),
+                false,
+            ));
+        };
+
+        let mut received = false;
+        let result = tokio::time::timeout(timeout, async {
+            let mut request = Packet::new(id, SERVERDATA_EXECCOMMAND, command).encode();
+            request.extend(Packet::new(sentinel, SERVERDATA_RESPONSE_VALUE, "").encode());
+            stream.write_all(&request).await?;
+
+            let mut body = Vec::new();
+            loop {
+                let packet = Packet::read(stream).await?;
+                if packet.id == sentinel {
+                    break;
+                }
+                // 之前超时的命令残留的回复与空包回复之后的额外包都会被跳过
+                if packet.id == id && packet.kind == SERVERDATA_RESPONSE_VALUE {
+                    received = true;
+                    body.extend(packet.body);
+                }
+            }
+            // 回复按字节拆分，多字节字符可能跨越两个包，合并后再解码
+            Ok(String::from_utf8_lossy(&body).into_owned())
+        })
+        .await
+        .unwrap_or(Err(Error::Timeout));
+
+        result.map_err(|error| (error, received))
+    }
+}
+
+/// 发送密码并等待验证结果
+async fn authenticate(stream: &mut TcpStream, id: i32, password: &str) -> Result<(), Error> {
+    stream
+        .write_all(&Packet::new(id, SERVERDATA_AUTH, password).encode())
+        .await?;
+
+    loop {
+        let packet = Packet::read(stream).await?;
+        match packet.kind {
+            SERVERDATA_AUTH_RESPONSE if packet.id == -1 => return Err(Error::AuthFailed),
+            SERVERDATA_AUTH_RESPONSE if packet.id == id => return Ok(()),
+            // 服务器在验证结果之前会先发送一个空的回复
+            SERVERDATA_RESPONSE_VALUE => continue,
+            kind => {
+                return Err(Error::Protocol(format!(
+                    "unexpected packet type {} (id {}) during authentication",
+                    kind, packet.id
+                )))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use tokio::net::TcpListener;
+
+    /// 模拟服务器的行为
+    #[derive(Clone, Default)]
+    struct Script {
+        password: &'static str,
+        /// 每个回复包的最大长度
+        chunk: usize,
+        /// 验证后不再回复任何包
+        silent: bool,
+        /// 回复空包后关闭连接，模拟服务器关闭空闲连接
+        close_after_command: bool,
+        /// 发送部分回复后关闭连接
+        close_mid_response: bool,
+    }
+
+    /// 本地回环上的模拟服务器，命令的回复为 `reply:` 加上命令
+    ///
+    /// @returns 服务器地址与接受的连接数
+    async fn stand_in_server(script: Script) -> (String, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let connections = Arc::new(AtomicUsize::new(0));
+        let accepted = connections.clone();
+        tokio::spawn(async move {
+            loop {
+                let (stream, _) = listener.accept().await.unwrap();
+                accepted.fetch_add(1, Ordering::SeqCst);
+                tokio::spawn(serve(stream, script.clone()));
+            }
+        });
+        (address, connections)
+    }
+
+    async fn reply(stream: &mut TcpStream, id: i32, kind: i32, body: &[u8]) -> Result<(), Error> {
+        stream
+            .write_all(&Packet::new(id, kind, body).encode())
+            .await?;
+        Ok(())
+    }
+
+    async fn serve(mut stream: TcpStream, script: Script) -> Result<(), Error> {
+        loop {
+            let packet = Packet::read(&mut stream).await?;
+            match packet.kind {
+                SERVERDATA_AUTH => {
+                    reply(&mut stream, packet.id, SERVERDATA_RESPONSE_VALUE, b"").await?;
+                    let id = if packet.body == script.password.as_bytes() {
+                        packet.id
+                    } else {
+                        -1
+                    };
+                    reply(&mut stream, id, SERVERDATA_AUTH_RESPONSE, b"").await?;
+                }
+                _ if script.silent => {}
+                SERVERDATA_EXECCOMMAND => {
+                    let body = [b"reply:".as_slice(), &packet.body].concat();
+                    for chunk in body.chunks(script.chunk.max(1)) {
+                        reply(&mut stream, packet.id, SERVERDATA_RESPONSE_VALUE, chunk).await?;
+                        if script.close_mid_response {
+                            return Ok(());
+                        }
+                    }
+                }
+                _ => {
+                    // 与 srcds 相同，空包的回复之后还有一个额外的包
+                    reply(&mut stream, packet.id, SERVERDATA_RESPONSE_VALUE, b"").await?;
+                    reply(
+                        &mut stream,
+                        packet.id,
+                        SERVERDATA_RESPONSE_VALUE,
+                        &[0, 1, 0, 0],
+                    )
+                    .await?;
+                    if script.close_after_command {
+                        return Ok(());
+                    }
+                }
+            }
+        }
+    }
+
+    fn script() -> Script {
+        Script {
+            password: "hunter2",
+            chunk: 4096,
+            ..Script::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn rejects_wrong_password() {
+        let (address, _) = stand_in_server(script()).await;
+        let error = Connection::connect(&address, "hunter3", DEFAULT_TIMEOUT)
+            .await
+            .err()
+            .unwrap();
+        assert!(matches!(error, Error::AuthFailed));
+        assert!(error
+            .context("rcon_connect", &address)
+            .to_string()
+            .starts_with("[AUTH_FAILED] rcon_connect 127.0.0.1:"));
+    }
+
+    #[tokio::test]
+    async fn reassembles_split_response() {
+        let (address, connections) = stand_in_server(Script {
+            chunk: 7,
+            ..script()
+        })
+        .await;
+        let mut connection = Connection::connect(&address, "hunter2", DEFAULT_TIMEOUT)
+            .await
+            .unwrap();
+        // 多字节字符跨越两个包
+        let command = format!("status {}", "状态".repeat(20));
+        assert_eq!(
+            connection.exec(&command).await.unwrap(),
+            format!("reply:{}", command)
+        );
+        // 上一条命令残留的额外包被跳过
+        assert_eq!(connection.exec("echo").await.unwrap(), "reply:echo");
+        assert_eq!(connections.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn reconnects_after_idle_close() {
+        let (address, connections) = stand_in_server(Script {
+            close_after_command: true,
+            ..script()
+        })
+        .await;
+        let mut connection = Connection::connect(&address, "hunter2", DEFAULT_TIMEOUT)
+            .await
+            .unwrap();
+        assert_eq!(connection.exec("one").await.unwrap(), "reply:one");
+        // 服务器已关闭连接，没有收到任何回复，重新连接后重试
+        assert_eq!(connection.exec("two").await.unwrap(), "reply:two");
+        assert_eq!(connections.load(Ordering::SeqCst), 2);
+        assert!(connection.is_connected());
+
+        connection.close();
+        assert!(connection.exec("three").await.is_err());
+        assert_eq!(connections.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn does_not_retry_after_partial_response() {
+        let (address, connections) = stand_in_server(Script {
+            chunk: 4,
+            close_mid_response: true,
+            ..script()
+        })
+        .await;
+        let mut connection = Connection::connect(&address, "hunter2", DEFAULT_TIMEOUT)
+            .await
+            .unwrap();
+        // 已经收到部分回复，命令可能已经执行，不能重试
+        let error = connection.exec("kick bob").await.unwrap_err();
+        assert!(error.is_disconnect());
+        assert!(!connection.is_connected());
+        assert_eq!(connections.load(Ordering::SeqCst), 1);
+
+        // 下一条命令重新连接
+        assert!(connection.exec("status").await.is_err());
+        assert_eq!(connections.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn times_out_per_command() {
+        let (address, _) = stand_in_server(Script {
+            silent: true,
+            ..script()
+        })
+        .await;
+        let timeout = Duration::from_millis(100);
+        let mut connection = Connection::connect(&address, "hunter2", timeout)
+            .await
+            .unwrap();
+        let start = std::time::Instant::now();
+        assert!(matches!(
+            connection.exec("status").await,
+            Err(Error::Timeout)
+        ));
+        assert!(start.elapsed() < Duration::from_secs(2));
+        assert!(!connection.is_connected());
+    }
+
+    #[tokio::test]
+    async fn times_out_without_auth_response() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        tokio::spawn(async move {
+            // 接受连接但不回复
+            let (_stream, _) = listener.accept().await.unwrap();
+            std::future::pending::<()>().await;
+        });
+        let result = Connection::connect(&address, "hunter2", Duration::from_millis(100)).await;
+        assert!(matches!(result, Err(Error::Timeout)));
+    }
+
+    #[tokio::test]
+    async fn wraps_packet_ids() {
+        let (address, _) = stand_in_server(script()).await;
+        let mut connection = Connection::connect(&address, "hunter2", DEFAULT_TIMEOUT)
+            .await
+            .unwrap();
+        connection.next_id = i32::MAX - 1;
+        assert_eq!(connection.next_id(), i32::MAX - 1);
+        assert_eq!(connection.next_id(), i32::MAX);
+        // ID 不会变为负数，-1 表示验证失败
+        assert_eq!(connection.next_id(), 1);
+
+        connection.next_id = i32::MAX;
+        assert_eq!(connection.exec("wrap").await.unwrap(), "reply:wrap");
+        assert_eq!(connection.next_id, 2);
+    }
+
+    #[tokio::test]
+    async fn round_trips_packets() {
+        let packet = Packet::new(7, SERVERDATA_EXECCOMMAND, "say hi");
+        let data = packet.encode();
+        assert_eq!(&data[..4], &16i32.to_le_bytes());
+        assert_eq!(Packet::read(&mut data.as_slice()).await.unwrap(), packet);
+
+        let mut oversized = (1024 * 1024i32).to_le_bytes().to_vec();
+        oversized.extend_from_slice(&[0; 16]);
+        assert!(matches!(
+            Packet::read(&mut oversized.as_slice()).await,
+            Err(Error::Protocol(_))
+        ));
+    }
+}
@@ -0,0 +1,70 @@
+//! RCON 数据包
+//!
+//! 参考 https://developer.valvesoftware.com/wiki/Source_RCON_Protocol
+//! 每个包以小端 i32 大小开头，之后是 ID、类型与以两个 0 结尾的内容。
+
+use super::Error;
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+/// 验证请求
+pub const SERVERDATA_AUTH: i32 = 3;
+/// 验证结果，ID 为 -1 表示密码错误
+pub const SERVERDATA_AUTH_RESPONSE: i32 = 2;
+/// 执行命令，与验证结果的类型值相同，只会由客户端发送
+pub const SERVERDATA_EXECCOMMAND: i32 = 2;
+/// 命令回复
+pub const SERVERDATA_RESPONSE_VALUE: i32 = 0;
+
+/// ID、类型与两个结尾的 0
+const MIN_SIZE: i32 = 10;
+/// 允许的最大包大小，服务器发送的包通常不超过 4096 字节
+const MAX_SIZE: i32 = 64 * 1024;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Packet {
+    pub id: i32,
+    pub kind: i32,
+    pub body: Vec<u8>,
+}
+
+impl Packet {
+    pub fn new(id: i32, kind: i32, body: impl Into<Vec<u8>>) -> Self {
+        Self {
+            id,
+            kind,
+            body: body.into(),
+        }
+    }
+
+    /// 编码为带大小的完整数据包
+    pub fn encode(&self) -> Vec<u8> {
+        let size = MIN_SIZE + self.body.len() as i32;
+        let mut data = Vec::with_capacity(size as usize + 4);
+        data.extend_from_slice(&size.to_le_bytes());
+        data.extend_from_slice(&self.id.to_le_bytes());
+        data.extend_from_slice(&self.kind.to_le_bytes());
+        data.extend_from_slice(&self.body);
+        data.extend_from_slice(&[0, 0]);
+        data
+    }
+
+    /// 读取一个完整的数据包
+    pub async fn read(reader: &mut (impl AsyncRead + Unpin)) -> Result<Self, Error> {
+        let size = reader.read_i32_le().await?;
+        if !(MIN_SIZE..=MAX_SIZE).contains(&size) {
+            return Err(Error::Protocol(format!("invalid packet size {}", size)));
+        }
+
+        let mut data = vec![0; size as usize];
+        reader.read_exact(&mut data).await?;
+        let id = i32::from_le_bytes([data[0], data[1], data[2], data[3]]);
+        let kind = i32::from_le_bytes([data[4], data[5], data[6], data[7]]);
+        data.truncate(data.len() - 2);
+        data.drain(..8);
+        Ok(Self {
+            id,
+            kind,
+            body: data,
+        })
+    }
+}
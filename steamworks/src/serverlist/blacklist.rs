@@ -0,0 +1,292 @@
+//! 服务器黑名单
+//!
+//! 支持 Steam 服务器浏览器导出的黑名单（`serverblacklist` KeyValues，每个 `server` 节含
+//! `name`、`date` 与 `addr`），以及每行一个规则的纯文本。规则可以是单个地址、`1.2.3.*` 通配符、
+//! `1.2.3.0/24` 网段或 `1.2.3.4-1.2.3.20` 区间，端口为空或 0 时匹配所有端口。
+
+use super::vdf::{self, ParseError, Section};
+use super::{normalize_text, strip_comment};
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+
+/// 一条黑名单规则，包含首尾两个 IPv4 地址
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlacklistRange {
+    pub first: Ipv4Addr,
+    pub last: Ipv4Addr,
+    /// 端口，为空时匹配所有端口
+    pub port: Option<u16>,
+    /// Steam 黑名单中记录的服务器名称
+    pub name: Option<String>,
+}
+
+impl BlacklistRange {
+    /// 解析一条规则，无法解析时为空
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = normalize_text(text);
+        let (range, port) = split_port(&text)?;
+        let (first, last) = parse_ips(range)?;
+        Some(Self {
+            first,
+            last,
+            port: port.filter(|port| *port != 0),
+            name: None,
+        })
+    }
+
+    pub fn contains(&self, ip: Ipv4Addr, port: u16) -> bool {
+        (self.first..=self.last).contains(&ip) && self.port.is_none_or(|p| p == port)
+    }
+}
+
+impl fmt::Display for BlacklistRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.first == self.last {
+            write!(f, "{}", self.first)?;
+        } else {
+            write!(f, "{}-{}", self.first, self.last)?;
+        }
+        match self.port {
+            Some(port) => write!(f, ":{}", port),
+            None => Ok(()),
+        }
+    }
+}
+
+/// 拆分端口，`1.2.3.4-1.2.3.9:27015` 中的端口只能写在最后
+fn split_port(text: &str) -> Option<(&str, Option<u16>)> {
+    match text.rsplit_once(':') {
+        Some((range, port)) => Some((range, Some(port.parse().ok()?))),
+        None => Some((text, None)),
+    }
+}
+
+fn parse_ips(range: &str) -> Option<(Ipv4Addr, Ipv4Addr)> {
+    if let Some((first, last)) = range.split_once('-') {
+        let first: Ipv4Addr = first.trim().parse().ok()?;
+        let last: Ipv4Addr = last.trim().parse().ok()?;
+        return Some((first.min(last), first.max(last)));
+    }
+
+    if let Some((ip, bits)) = range.split_once('/') {
+        let ip = u32::from(ip.parse::<Ipv4Addr>().ok()?);
+        let bits: u32 = bits.parse().ok().filter(|bits| *bits <= 32)?;
+        let mask = u32::MAX.checked_shl(32 - bits).unwrap_or(0);
+        return Some((Ipv4Addr::from(ip & mask), Ipv4Addr::from(ip | !mask)));
+    }
+
+    if range.contains('*') {
+        // 通配符只能出现在末尾的若干段，如 `1.2.*.*`
+        let parts: Vec<&str> = range.split('.').collect();
+        if parts.len() != 4 {
+            return None;
+        }
+        let wildcard = parts.iter().position(|part| *part == "*")?;
+        if parts[wildcard..].iter().any(|part| *part != "*") {
+            return None;
+        }
+        let mut first = [0u8; 4];
+        let mut last = [255u8; 4];
+        for (index, part) in parts[..wildcard].iter().enumerate() {
+            first[index] = part.parse().ok()?;
+            last[index] = first[index];
+        }
+        return Some((Ipv4Addr::from(first), Ipv4Addr::from(last)));
+    }
+
+    let ip = range.parse().ok()?;
+    Some((ip, ip))
+}
+
+/// 服务器黑名单
+#[derive(Debug, Clone, Default)]
+pub struct Blacklist {
+    ranges: Vec<BlacklistRange>,
+}
+
+impl Blacklist {
+    pub fn new(ranges: Vec<BlacklistRange>) -> Self {
+        Self { ranges }
+    }
+
+    /// 解析黑名单文件，内容包含 `{` 时按 KeyValues 解析，否则按纯文本解析
+    ///
+    /// @returns 黑名单与无法解析的规则
+    pub fn parse(text: &str) -> Result<(Self, Vec<String>), ParseError> {
+        if text.contains('{') {
+            let mut ranges = Vec::new();
+            let mut invalid = Vec::new();
+            collect_ranges(&vdf::parse(text)?, &mut ranges, &mut invalid);
+            return Ok((Self { ranges }, invalid));
+        }
+
+        let mut ranges = Vec::new();
+        let mut invalid = Vec::new();
+        for line in text.lines() {
+            let Some(rule) = strip_comment(line).split_whitespace().next() else {
+                continue;
+            };
+            match BlacklistRange::parse(rule) {
+                Some(range) => ranges.push(range),
+                None => invalid.push(rule.to_string()),
+            }
+        }
+        Ok((Self { ranges }, invalid))
+    }
+
+    pub fn ranges(&self) -> &[BlacklistRange] {
+        &self.ranges
+    }
+
+    pub fn contains(&self, address: SocketAddr) -> bool {
+        match address.ip() {
+            IpAddr::V4(ip) => self
+                .ranges
+                .iter()
+                .any(|range| range.contains(ip, address.port())),
+            IpAddr::V6(_) => false,
+        }
+    }
+
+    /// 地址是否在黑名单中，未指定端口时使用 27015，域名无法判断，按不在黑名单中处理
+    pub fn contains_address(&self, address: &str) -> bool {
+        super::normalize_address(address)
+            .and_then(|address| address.parse().ok())
+            .is_some_and(|address| self.contains(address))
+    }
+
+    /// 移除黑名单中的服务器
+    ///
+    /// @param address 获取每一项的地址
+    pub fn filter<T>(&self, items: Vec<T>, address: impl Fn(&T) -> &str) -> Vec<T> {
+        if self.ranges.is_empty() {
+            return items;
+        }
+        items
+            .into_iter()
+            .filter(|item| !self.contains_address(address(item)))
+            .collect()
+    }
+}
+
+/// 收集所有含 `addr` 的节，黑名单文件的根节名称在不同版本中不同，因此不检查节名
+fn collect_ranges(section: &Section, ranges: &mut Vec<BlacklistRange>, invalid: &mut Vec<String>) {
+    for (_, child) in section.sections() {
+        match child.string("addr").or_else(|| child.string("address")) {
+            Some(addr) => match BlacklistRange::parse(addr) {
+                Some(mut range) => {
+                    range.name = child
+                        .string("name")
+                        .filter(|name| !name.is_empty())
+                        .map(String::from);
+                    ranges.push(range);
+                }
+                None => invalid.push(addr.to_string()),
+            },
+            None => collect_ranges(child, ranges, invalid),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::serverlist::{parse_history, parse_list};
+
+    const STEAM_BLACKLIST: &str = include_str!("fixtures/serverblacklist.vdf");
+    const TEXT_BLACKLIST: &str = include_str!("fixtures/blacklist.txt");
+
+    fn contains(blacklist: &Blacklist, address: &str) -> bool {
+        blacklist.contains(address.parse().unwrap())
+    }
+
+    #[test]
+    fn parses_rules() {
+        let rule = |text| BlacklistRange::parse(text).map(|range| range.to_string());
+        assert_eq!(rule("1.2.3.4").as_deref(), Some("1.2.3.4"));
+        assert_eq!(rule(" 1.2.3.4：27015 ").as_deref(), Some("1.2.3.4:27015"));
+        assert_eq!(rule("1.2.3.4:0").as_deref(), Some("1.2.3.4"));
+        assert_eq!(rule("1.2.*.*").as_deref(), Some("1.2.0.0-1.2.255.255"));
+        assert_eq!(rule("1.2.3.0/24").as_deref(), Some("1.2.3.0-1.2.3.255"));
+        assert_eq!(rule("1.2.3.4/32").as_deref(), Some("1.2.3.4"));
+        assert_eq!(
+            rule("0.0.0.0/0").as_deref(),
+            Some("0.0.0.0-255.255.255.255")
+        );
+        assert_eq!(
+            rule("1.2.3.9 - 1.2.3.4:27016").as_deref(),
+            Some("1.2.3.4-1.2.3.9:27016")
+        );
+        for invalid in ["1.2.3", "1.2.*.3", "1.2.3.0/33", "1.2.3.4:port", "*"] {
+            assert_eq!(rule(invalid), None, "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn parses_steam_blacklist() {
+        let (blacklist, invalid) = Blacklist::parse(STEAM_BLACKLIST).unwrap();
+        assert_eq!(invalid, ["300.1.1.1"]);
+        assert_eq!(blacklist.ranges().len(), 2);
+        assert_eq!(
+            blacklist.ranges()[0].name.as_deref(),
+            Some("Cheaters Paradise")
+        );
+        assert_eq!(blacklist.ranges()[1].name, None);
+
+        assert!(contains(&blacklist, "10.20.30.40:27017"));
+        assert!(!contains(&blacklist, "10.20.30.40:27015"));
+        assert!(contains(&blacklist, "172.16.0.1:27015"));
+        assert!(contains(&blacklist, "172.31.255.255:1"));
+        assert!(!contains(&blacklist, "172.32.0.0:27015"));
+        assert!(!contains(&blacklist, "[::1]:27017"));
+    }
+
+    #[test]
+    fn parses_text_blacklist() {
+        let (blacklist, invalid) = Blacklist::parse(TEXT_BLACKLIST).unwrap();
+        assert_eq!(invalid, ["1.2.*.3"]);
+        assert_eq!(blacklist.ranges().len(), 4);
+
+        // CIDR 网段与端口
+        assert!(contains(&blacklist, "192.168.1.200:27015"));
+        assert!(!contains(&blacklist, "192.168.1.200:27016"));
+        assert!(!contains(&blacklist, "192.168.2.1:27015"));
+        // 通配符匹配所有端口
+        assert!(contains(&blacklist, "10.0.0.7:1"));
+        // 区间首尾颠倒时自动调换
+        assert!(contains(&blacklist, "10.1.1.5:27016"));
+        assert!(contains(&blacklist, "10.1.1.20:27016"));
+        assert!(!contains(&blacklist, "10.1.1.21:27016"));
+        assert!(!contains(&blacklist, "10.1.1.12:27015"));
+        assert!(contains(&blacklist, "8.8.8.8:53"));
+
+        assert!(blacklist.contains_address("8.8.8.8"));
+        assert!(blacklist.contains_address("connect 192.168.1.1"));
+        assert!(!blacklist.contains_address("example.com:27015"));
+    }
+
+    #[test]
+    fn filters_imported_servers() {
+        let (blacklist, _) = Blacklist::parse(STEAM_BLACKLIST).unwrap();
+        let history = parse_history(include_str!("fixtures/serverbrowser_hist.vdf")).unwrap();
+        let kept = blacklist.filter(history.clone(), |entry| &entry.address);
+        assert_eq!(kept.len(), history.len() - 1);
+        assert!(kept
+            .iter()
+            .all(|entry| entry.address != "10.20.30.40:27017"));
+
+        let (blacklist, _) = Blacklist::parse(TEXT_BLACKLIST).unwrap();
+        let kept = blacklist.filter(history, |entry| &entry.address);
+        let addresses: Vec<_> = kept.iter().map(|entry| entry.address.as_str()).collect();
+        assert_eq!(addresses, ["play.example.com:27015", "10.20.30.40:27017"]);
+
+        let (list, _) = parse_list(include_str!("fixtures/servers.txt"));
+        let kept = blacklist.filter(list, |entry| &entry.address);
+        let addresses: Vec<_> = kept.iter().map(|entry| entry.address.as_str()).collect();
+        assert_eq!(addresses, ["play.example.com:27020", "10.20.30.40:27015"]);
+
+        // 空黑名单不过滤
+        let list = vec!["1.2.3.4:27015"];
+        assert_eq!(Blacklist::default().filter(list.clone(), |item| item), list);
+    }
+}
@@ -0,0 +1,287 @@
+//! 导入服务器列表
+//!
+//! 支持 Steam 的 `config/serverbrowser_hist.vdf`（服务器浏览器的收藏夹与历史记录）、
+//! 服务器浏览器黑名单，以及每行一个 `ip:port` 的纯文本列表。导入的地址统一为 `host:port` 格式。
+
+pub mod blacklist;
+pub mod vdf;
+
+use std::collections::HashSet;
+use std::net::SocketAddr;
+use vdf::{ParseError, Section};
+
+/// 未指定端口时使用的端口
+pub const DEFAULT_PORT: u16 = 27015;
+
+/// 服务器来自哪个列表
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntrySource {
+    Favorites,
+    History,
+    /// 纯文本列表
+    List,
+}
+
+/// 导入的服务器
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServerEntry {
+    /// `ip:port` 或 `域名:port`
+    pub address: String,
+    pub name: Option<String>,
+    pub app_id: Option<u32>,
+    /// 最后一次游戏的 Unix 时间（秒）
+    pub last_played: Option<u32>,
+    pub source: EntrySource,
+}
+
+impl ServerEntry {
+    fn new(address: String, source: EntrySource) -> Self {
+        Self {
+            address,
+            name: None,
+            app_id: None,
+            last_played: None,
+            source,
+        }
+    }
+}
+
+/// 去掉 `//`、`#` 或 `;` 注释，注释符号需要在行首或空白之后，`steam://` 中的 `//` 不是注释
+pub(crate) fn strip_comment(line: &str) -> &str {
+    let end = line
+        .char_indices()
+        .find(|&(index, c)| {
+            let starts = matches!(c, '#' | ';') || line[index..].starts_with("//");
+            starts
+                && line[..index]
+                    .chars()
+                    .next_back()
+                    .is_none_or(char::is_whitespace)
+        })
+        .map_or(line.len(), |(index, _)| index);
+    line[..end].trim()
+}
+
+/// 去掉 `steam://connect/` 与 `connect` 前缀，全角冒号替换为半角
+pub(crate) fn normalize_text(text: &str) -> String {
+    let text = text.trim().replace('：', ":");
+    let text = text
+        .strip_prefix("steam://connect/")
+        .or_else(|| text.strip_prefix("connect "))
+        .unwrap_or(&text);
+    text.trim().trim_end_matches('/').to_string()
+}
+
+/// 统一地址格式，未指定端口时使用 27015，域名转换为小写
+///
+/// @returns `ip:port` 或 `域名:port`，地址无效时为空
+pub fn normalize_address(text: &str) -> Option<String> {
+    let text = normalize_text(text);
+    if let Ok(address) = text.parse::<SocketAddr>() {
+        return (address.port() != 0).then(|| address.to_string());
+    }
+    if let Ok(ip) = text.parse::<std::net::IpAddr>() {
+        return Some(SocketAddr::new(ip, DEFAULT_PORT).to_string());
+    }
+
+    let (host, port) = match text.rsplit_once(':') {
+        Some((host, port)) => (host, port.parse::<u16>().ok().filter(|port| *port != 0)?),
+        None => (text.as_str(), DEFAULT_PORT),
+    };
+    let valid = !host.is_empty()
+        && host
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-')
+        // 全是数字与点但不是合法 IP，如 `1.2.3`
+        && !host.chars().all(|c| c.is_ascii_digit() || c == '.');
+    valid.then(|| format!("{}:{}", host.to_ascii_lowercase(), port))
+}
+
+/// 解析 `serverbrowser_hist.vdf`，同一列表中重复的地址只保留第一个
+///
+/// @returns 收藏夹中的服务器在前，历史记录在后，各自保持文件中的顺序
+pub fn parse_history(text: &str) -> Result<Vec<ServerEntry>, ParseError> {
+    let root = vdf::parse(text)?;
+    let mut entries = Vec::new();
+    for (name, source) in [
+        ("Favorites", EntrySource::Favorites),
+        ("History", EntrySource::History),
+    ] {
+        if let Some(section) = find_section(&root, name) {
+            collect_entries(section, source, &mut entries);
+        }
+    }
+    Ok(entries)
+}
+
+/// 深度优先查找名称匹配的节，文件的根节通常为 `Filters`
+fn find_section<'a>(section: &'a Section, name: &str) -> Option<&'a Section> {
+    section.sections().find_map(|(key, child)| {
+        if key.eq_ignore_ascii_case(name) {
+            Some(child)
+        } else {
+            find_section(child, name)
+        }
+    })
+}
+
+fn collect_entries(section: &Section, source: EntrySource, entries: &mut Vec<ServerEntry>) {
+    let mut seen = HashSet::new();
+    for (_, server) in section.sections() {
+        let Some(address) = server
+            .string("address")
+            .or_else(|| server.string("addr"))
+            .and_then(normalize_address)
+        else {
+            continue;
+        };
+        if !seen.insert(address.clone()) {
+            continue;
+        }
+
+        let number = |key: &str| {
+            server
+                .string(key)
+                .and_then(|value| value.trim().parse::<u32>().ok())
+                .filter(|value| *value != 0)
+        };
+        entries.push(ServerEntry {
+            name: server
+                .string("name")
+                .filter(|name| !name.is_empty())
+                .map(String::from),
+            app_id: number("appid"),
+            last_played: number("LastPlayed"),
+            ..ServerEntry::new(address, source)
+        });
+    }
+}
+
+/// 解析每行一个地址的纯文本列表，支持 `//`、`#`、`;` 注释与 `connect` 前缀，
+/// 地址后的其他内容会被忽略，重复的地址只保留第一个
+///
+/// @returns 服务器与无法解析的行
+pub fn parse_list(text: &str) -> (Vec<ServerEntry>, Vec<String>) {
+    let mut entries = Vec::new();
+    let mut invalid = Vec::new();
+    let mut seen = HashSet::new();
+    for line in text.lines() {
+        let line = strip_comment(line);
+        let line = line.strip_prefix("connect ").unwrap_or(line);
+        let Some(address) = line.split_whitespace().next() else {
+            continue;
+        };
+        match normalize_address(address) {
+            Some(address) => {
+                if seen.insert(address.clone()) {
+                    entries.push(ServerEntry::new(address, EntrySource::List));
+                }
+            }
+            None => invalid.push(line.to_string()),
+        }
+    }
+    (entries, invalid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HISTORY: &str = include_str!("fixtures/serverbrowser_hist.vdf");
+    const LIST: &str = include_str!("fixtures/servers.txt");
+
+    fn entry(
+        address: &str,
+        name: Option<&str>,
+        app_id: Option<u32>,
+        last_played: Option<u32>,
+        source: EntrySource,
+    ) -> ServerEntry {
+        ServerEntry {
+            address: address.to_string(),
+            name: name.map(String::from),
+            app_id,
+            last_played,
+            source,
+        }
+    }
+
+    #[test]
+    fn parses_favorites_and_history() {
+        assert_eq!(
+            parse_history(HISTORY).unwrap(),
+            vec![
+                entry(
+                    "192.168.1.10:27015",
+                    Some("Dust2 Only 24/7"),
+                    Some(730),
+                    Some(1_700_000_000),
+                    EntrySource::Favorites
+                ),
+                entry(
+                    "play.example.com:27015",
+                    Some("Surf \"Beginner\""),
+                    Some(730),
+                    None,
+                    EntrySource::Favorites
+                ),
+                entry(
+                    "192.168.1.10:27015",
+                    None,
+                    Some(730),
+                    Some(1_700_000_500),
+                    EntrySource::History
+                ),
+                entry(
+                    "10.20.30.40:27017",
+                    Some("Valve Official"),
+                    Some(240),
+                    Some(1_690_000_000),
+                    EntrySource::History
+                ),
+            ]
+        );
+        assert!(parse_history("\"Filters\" { \"Favorites\" {").is_err());
+        assert_eq!(parse_history("\"Filters\" {}").unwrap(), Vec::new());
+    }
+
+    #[test]
+    fn parses_plain_list() {
+        let (entries, invalid) = parse_list(LIST);
+        let addresses: Vec<_> = entries.iter().map(|entry| entry.address.as_str()).collect();
+        assert_eq!(
+            addresses,
+            [
+                "192.168.1.10:27015",
+                "10.0.0.7:27016",
+                "play.example.com:27020",
+                "10.20.30.40:27015",
+                "8.8.8.8:27015",
+            ]
+        );
+        assert!(entries
+            .iter()
+            .all(|entry| entry.source == EntrySource::List && entry.name.is_none()));
+        assert_eq!(invalid, ["not_a_server!", "10.0.0.7:0"]);
+    }
+
+    #[test]
+    fn normalizes_addresses() {
+        assert_eq!(
+            normalize_address("[::1]:27016").as_deref(),
+            Some("[::1]:27016")
+        );
+        assert_eq!(normalize_address("::1").as_deref(), Some("[::1]:27015"));
+        assert_eq!(
+            normalize_address("connect Example.COM").as_deref(),
+            Some("example.com:27015")
+        );
+        assert_eq!(normalize_address("1.2.3"), None);
+        assert_eq!(normalize_address("example.com:70000"), None);
+        assert_eq!(
+            strip_comment("steam://connect/1.2.3.4 // x"),
+            "steam://connect/1.2.3.4"
+        );
+        assert_eq!(strip_comment("1.2.3.4#5"), "1.2.3.4#5");
+    }
+}
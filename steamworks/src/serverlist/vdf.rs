@@ -0,0 +1,241 @@
+//! Valve KeyValues 文本格式（VDF）的最小解析器
+//!
+//! 只支持 Steam 配置文件用到的部分：带引号或不带引号的键值、嵌套的 `{}`、`//` 注释与
+//! `\"` 等转义。`[$WIN32]` 之类的平台条件会被忽略，键值一律保留。
+
+use std::fmt;
+
+/// 一个键对应的值
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Value {
+    String(String),
+    Section(Section),
+}
+
+/// 按文件中的顺序保存的键值，键可以重复
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Section {
+    pub entries: Vec<(String, Value)>,
+}
+
+impl Section {
+    /// 第一个名称匹配的值，键不区分大小写
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.entries
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(key))
+            .map(|(_, value)| value)
+    }
+
+    /// 第一个名称匹配的字符串值
+    pub fn string(&self, key: &str) -> Option<&str> {
+        match self.get(key)? {
+            Value::String(value) => Some(value),
+            Value::Section(_) => None,
+        }
+    }
+
+    /// 第一个名称匹配的子节
+    pub fn section(&self, key: &str) -> Option<&Section> {
+        match self.get(key)? {
+            Value::Section(section) => Some(section),
+            Value::String(_) => None,
+        }
+    }
+
+    /// 所有子节
+    pub fn sections(&self) -> impl Iterator<Item = (&str, &Section)> {
+        self.entries.iter().filter_map(|(name, value)| match value {
+            Value::Section(section) => Some((name.as_str(), section)),
+            Value::String(_) => None,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// 出错的行号，从 1 开始
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Token {
+    String(String),
+    Open,
+    Close,
+}
+
+struct Lexer<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+    line: usize,
+}
+
+impl Lexer<'_> {
+    fn error(&self, message: impl Into<String>) -> ParseError {
+        ParseError {
+            line: self.line,
+            message: message.into(),
+        }
+    }
+
+    /// 跳过空白与注释
+    fn skip_blank(&mut self) {
+        while let Some(&c) = self.chars.peek() {
+            if c == '\n' {
+                self.line += 1;
+                self.chars.next();
+            } else if c.is_whitespace() || c == '\u{feff}' {
+                self.chars.next();
+            } else if c == '/' {
+                let mut rest = self.chars.clone();
+                rest.next();
+                if rest.peek() != Some(&'/') {
+                    return;
+                }
+                while self.chars.next_if(|&c| c != '\n').is_some() {}
+            } else {
+                return;
+            }
+        }
+    }
+
+    fn next(&mut self) -> Result<Option<Token>, ParseError> {
+        loop {
+            self.skip_blank();
+            let Some(c) = self.chars.next() else {
+                return Ok(None);
+            };
+            match c {
+                '{' => return Ok(Some(Token::Open)),
+                '}' => return Ok(Some(Token::Close)),
+                '"' => return self.quoted().map(Some),
+                // 平台条件，如 `[$WIN32]`
+                '[' => {
+                    while self.chars.next_if(|&c| c != ']' && c != '\n').is_some() {}
+                    self.chars.next_if_eq(&']');
+                }
+                _ => {
+                    let mut value = String::from(c);
+                    while let Some(c) = self
+                        .chars
+                        .next_if(|&c| !c.is_whitespace() && !matches!(c, '{' | '}' | '"'))
+                    {
+                        value.push(c);
+                    }
+                    return Ok(Some(Token::String(value)));
+                }
+            }
+        }
+    }
+
+    fn quoted(&mut self) -> Result<Token, ParseError> {
+        let mut value = String::new();
+        loop {
+            match self.chars.next() {
+                Some('"') => return Ok(Token::String(value)),
+                Some('\\') => match self.chars.next() {
+                    Some('n') => value.push('\n'),
+                    Some('t') => value.push('\t'),
+                    Some(c @ ('\\' | '"')) => value.push(c),
+                    // 未知的转义按原样保留，Windows 路径中的单个反斜杠很常见
+                    Some(c) => {
+                        value.push('\\');
+                        value.push(c);
+                    }
+                    None => break,
+                },
+                Some(c) => {
+                    if c == '\n' {
+                        self.line += 1;
+                    }
+                    value.push(c);
+                }
+                None => break,
+            }
+        }
+        Err(self.error("unterminated string"))
+    }
+}
+
+/// 解析 KeyValues 文本
+///
+/// @returns 顶层的键值，通常只有一个根节
+pub fn parse(text: &str) -> Result<Section, ParseError> {
+    let mut lexer = Lexer {
+        chars: text.chars().peekable(),
+        line: 1,
+    };
+    // 已打开但未关闭的节及其名称
+    let mut stack: Vec<(String, Section)> = Vec::new();
+    let mut current = Section::default();
+
+    while let Some(token) = lexer.next()? {
+        match token {
+            Token::String(key) => match lexer.next()? {
+                Some(Token::String(value)) => current.entries.push((key, Value::String(value))),
+                Some(Token::Open) => stack.push((key, std::mem::take(&mut current))),
+                Some(Token::Close) | None => {
+                    return Err(lexer.error(format!("missing value for key {:?}", key)))
+                }
+            },
+            Token::Close => {
+                let Some((key, parent)) = stack.pop() else {
+                    return Err(lexer.error("unexpected '}'"));
+                };
+                let section = std::mem::replace(&mut current, parent);
+                current.entries.push((key, Value::Section(section)));
+            }
+            Token::Open => return Err(lexer.error("unexpected '{'")),
+        }
+    }
+
+    match stack.pop() {
+        Some((key, _)) => Err(lexer.error(format!("section {:?} is not closed", key))),
+        None => Ok(current),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_nested_sections() {
+        let root = parse(
+            "\u{feff}// comment\n\"Root\"\n{\n\tkey value [$WIN32]\n\t\"Path\" \"C:\\Games\\\"cs\\\"\"\n\tchild { \"a\" \"1\" }\n\t\"key\" \"again\"\n}\n",
+        )
+        .unwrap();
+        let section = root.section("root").unwrap();
+        assert_eq!(section.string("KEY"), Some("value"));
+        assert_eq!(section.string("path"), Some("C:\\Games\"cs\""));
+        assert_eq!(section.section("child").unwrap().string("a"), Some("1"));
+        assert_eq!(section.string("child"), None);
+        // 重复的键按顺序保留
+        assert_eq!(section.entries.len(), 4);
+        assert_eq!(section.sections().count(), 1);
+    }
+
+    #[test]
+    fn reports_errors_with_line() {
+        let error = |text| parse(text).unwrap_err().to_string();
+        assert_eq!(
+            error("\"a\"\n{\n\"b\" \"c\"\n"),
+            "line 4: section \"a\" is not closed"
+        );
+        assert_eq!(error("\"a\" \"b\"\n}"), "line 2: unexpected '}'");
+        assert_eq!(
+            error("\"a\" { \"b\" }"),
+            "line 1: missing value for key \"b\""
+        );
+        assert_eq!(error("\"a\" \"b\n"), "line 2: unterminated string");
+    }
+}
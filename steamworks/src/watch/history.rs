@@ -0,0 +1,299 @@
+//! 服务器人数历史
+//!
+//! 每个服务器一个文本文件，每行一个采样 `时间,玩家数,最大玩家数,机器人数`，离线时人数为空。
+//! 采样只追加写入，行数达到容量的两倍时重写文件只保留最近的采样，文件大小因此有上限。
+
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, PoisonError};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// 默认每个服务器保留的采样数量，按 30 秒的间隔约为一天
+pub const DEFAULT_CAPACITY: usize = 2880;
+
+/// 一次采样
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sample {
+    /// Unix 时间（秒）
+    pub time: u64,
+    /// 玩家数量（包括机器人），离线时为空
+    pub players: Option<u8>,
+    pub max_players: u8,
+    pub bots: u8,
+}
+
+impl Sample {
+    /// 当前时间的离线采样
+    pub fn offline() -> Self {
+        Self {
+            time: now(),
+            players: None,
+            max_players: 0,
+            bots: 0,
+        }
+    }
+
+    /// 当前时间的在线采样
+    pub fn online(players: u8, max_players: u8, bots: u8) -> Self {
+        Self {
+            time: now(),
+            players: Some(players),
+            max_players,
+            bots,
+        }
+    }
+
+    fn to_line(self) -> String {
+        match self.players {
+            Some(players) => format!(
+                "{},{},{},{}\n",
+                self.time, players, self.max_players, self.bots
+            ),
+            None => format!("{},,,\n", self.time),
+        }
+    }
+
+    fn parse_line(line: &str) -> Option<Self> {
+        let mut fields = line.trim().split(',');
+        let time = fields.next()?.parse().ok()?;
+        let players = fields.next()?;
+        let max_players = fields.next()?;
+        let bots = fields.next()?;
+        if players.is_empty() {
+            return Some(Self {
+                time,
+                players: None,
+                max_players: 0,
+                bots: 0,
+            });
+        }
+        Some(Self {
+            time,
+            players: Some(players.parse().ok()?),
+            max_players: max_players.parse().ok()?,
+            bots: bots.parse().ok()?,
+        })
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs())
+}
+
+/// 保存在目录中的人数历史
+pub struct History {
+    dir: PathBuf,
+    /// 每个服务器保留的采样数量
+    capacity: usize,
+    /// 每个文件当前的行数，首次写入时读取
+    lines: Mutex<HashMap<String, usize>>,
+}
+
+impl History {
+    pub fn new(dir: impl Into<PathBuf>, capacity: usize) -> Self {
+        Self {
+            dir: dir.into(),
+            capacity: capacity.max(1),
+            lines: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 服务器对应的文件，地址中除字母、数字、`.` 与 `-` 以外的字符替换为 `_`
+    pub fn path(&self, address: &str) -> PathBuf {
+        let name: String = address
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '.' || c == '-' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        self.dir.join(format!("{}.csv", name))
+    }
+
+    /// 追加一次采样
+    pub fn record(&self, address: &str, sample: Sample) -> io::Result<()> {
+        let path = self.path(address);
+        let mut lines = self.lines.lock().unwrap_or_else(PoisonError::into_inner);
+        let count = match lines.get(address) {
+            Some(count) => *count,
+            None => read_lines(&path)?.len(),
+        };
+
+        let count = if count + 1 >= self.capacity * 2 {
+            let kept = self.capacity - 1;
+            let existing = read_lines(&path)?;
+            let mut data = existing[existing.len().saturating_sub(kept)..].join("\n");
+            if !data.is_empty() {
+                data.push('\n');
+            }
+            data.push_str(&sample.to_line());
+
+            // 先写入临时文件再替换，写入中断时不会丢失原有的历史
+            let temp = path.with_extension("csv.tmp");
+            fs::write(&temp, data)?;
+            fs::rename(&temp, &path)?;
+            kept.min(existing.len()) + 1
+        } else {
+            fs::create_dir_all(&self.dir)?;
+            OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&path)?
+                .write_all(sample.to_line().as_bytes())?;
+            count + 1
+        };
+
+        lines.insert(address.to_string(), count);
+        Ok(())
+    }
+
+    /// 读取服务器的历史
+    ///
+    /// @returns 按时间排序的最近采样，最多为容量个，无法解析的行会被跳过
+    pub fn load(&self, address: &str) -> io::Result<Vec<Sample>> {
+        let samples: Vec<Sample> = read_lines(&self.path(address))?
+            .iter()
+            .filter_map(|line| Sample::parse_line(line))
+            .collect();
+        let skip = samples.len().saturating_sub(self.capacity);
+        Ok(samples[skip..].to_vec())
+    }
+}
+
+/// 读取文件中的非空行，文件不存在时为空
+fn read_lines(path: &Path) -> io::Result<Vec<String>> {
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(error) => return Err(error),
+    };
+    let mut lines = Vec::new();
+    for line in BufReader::new(file).lines() {
+        let line = line?;
+        if !line.trim().is_empty() {
+            lines.push(line);
+        }
+    }
+    Ok(lines)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 每个测试独立的临时目录
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "steamworks-history-{}-{}",
+            std::process::id(),
+            name
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn sample(time: u64, players: u8) -> Sample {
+        Sample {
+            time,
+            players: Some(players),
+            max_players: 32,
+            bots: 1,
+        }
+    }
+
+    fn file_lines(history: &History, address: &str) -> Vec<String> {
+        read_lines(&history.path(address)).unwrap()
+    }
+
+    #[test]
+    fn appends_and_loads_samples() {
+        let dir = temp_dir("append");
+        let history = History::new(&dir, 10);
+        assert_eq!(history.load("127.0.0.1:27015").unwrap(), []);
+
+        history.record("127.0.0.1:27015", sample(100, 5)).unwrap();
+        let offline = Sample {
+            time: 130,
+            players: None,
+            max_players: 0,
+            bots: 0,
+        };
+        history.record("127.0.0.1:27015", offline).unwrap();
+        history.record("127.0.0.1:27015", sample(160, 7)).unwrap();
+
+        assert_eq!(
+            history.path("127.0.0.1:27015"),
+            dir.join("127.0.0.1_27015.csv")
+        );
+        assert_eq!(
+            file_lines(&history, "127.0.0.1:27015"),
+            ["100,5,32,1", "130,,,", "160,7,32,1"]
+        );
+        assert_eq!(
+            history.load("127.0.0.1:27015").unwrap(),
+            [sample(100, 5), offline, sample(160, 7)]
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn rewrites_file_at_twice_the_capacity() {
+        let dir = temp_dir("rewrite");
+        let history = History::new(&dir, 3);
+        for time in 1..=5 {
+            history.record("server", sample(time, time as u8)).unwrap();
+        }
+        assert_eq!(file_lines(&history, "server").len(), 5);
+        // 加载时只返回最近的容量个采样
+        assert_eq!(
+            history.load("server").unwrap(),
+            [sample(3, 3), sample(4, 4), sample(5, 5)]
+        );
+
+        history.record("server", sample(6, 6)).unwrap();
+        assert_eq!(
+            file_lines(&history, "server"),
+            ["4,4,32,1", "5,5,32,1", "6,6,32,1"]
+        );
+        assert!(!dir.join("server.csv.tmp").exists());
+
+        // 重写后从保留的行数重新计数
+        history.record("server", sample(7, 7)).unwrap();
+        history.record("server", sample(8, 8)).unwrap();
+        assert_eq!(file_lines(&history, "server").len(), 5);
+        history.record("server", sample(9, 9)).unwrap();
+        assert_eq!(
+            file_lines(&history, "server"),
+            ["7,7,32,1", "8,8,32,1", "9,9,32,1"]
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn counts_existing_lines_on_first_write() {
+        let dir = temp_dir("existing");
+        fs::create_dir_all(&dir).unwrap();
+        let data: String = (1..=5).map(|time| sample(time, 1).to_line()).collect();
+        fs::write(dir.join("server.csv"), data + "not a sample\n").unwrap();
+
+        // 无法解析的行也计入行数，加载时跳过
+        let history = History::new(&dir, 3);
+        assert_eq!(
+            history.load("server").unwrap(),
+            [sample(3, 1), sample(4, 1), sample(5, 1)]
+        );
+        history.record("server", sample(6, 1)).unwrap();
+        assert_eq!(
+            file_lines(&history, "server"),
+            ["5,1,32,1", "not a sample", "6,1,32,1"]
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
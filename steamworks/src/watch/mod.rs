@@ -0,0 +1,260 @@
+//! 服务器监视
+//!
+//! 按间隔轮询一组服务器的 A2S_INFO 与 A2S_PLAYER，比较前后两次结果生成上线、离线、换图、
+//! 玩家进出、满员与延迟突增事件，并可以把每次采样的人数保存到磁盘用于绘制图表。
+//! 每个服务器在独立的任务中轮询，互不影响。
+
+pub mod history;
+mod state;
+
+use crate::api::logging::steam_log;
+use crate::query::message::ServerInfo;
+use crate::query::{self, Query};
+use history::{History, Sample};
+use state::{Poll, ServerState};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tokio::time::MissedTickBehavior;
+
+/// 默认轮询间隔
+pub const DEFAULT_INTERVAL: Duration = Duration::from_secs(30);
+/// 允许的最小轮询间隔
+pub const MIN_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone)]
+pub struct WatchOptions {
+    /// 默认轮询间隔，添加服务器时可以单独指定
+    pub interval: Duration,
+    /// 单次查询的超时
+    pub timeout: Duration,
+    /// 是否查询玩家列表以报告玩家进出
+    pub players: bool,
+    /// 连续失败多少次后认为离线
+    pub offline_after: u32,
+    /// 延迟超过基准的倍数
+    pub latency_spike_factor: f64,
+    /// 延迟至少增加多少才算突增，避免低延迟服务器的正常抖动被误报
+    pub latency_spike_min: Duration,
+    /// 保存人数历史的目录，为空时不保存
+    pub history_dir: Option<std::path::PathBuf>,
+    /// 每个服务器保留的采样数量
+    pub history_capacity: usize,
+}
+
+impl Default for WatchOptions {
+    fn default() -> Self {
+        Self {
+            interval: DEFAULT_INTERVAL,
+            timeout: query::DEFAULT_TIMEOUT,
+            players: true,
+            offline_after: 2,
+            latency_spike_factor: 2.0,
+            latency_spike_min: Duration::from_millis(100),
+            history_dir: None,
+            history_capacity: history::DEFAULT_CAPACITY,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum WatchEventKind {
+    /// 服务器开始回复，包括第一次查询成功
+    Online,
+    /// 服务器连续多次没有回复
+    Offline(query::Error),
+    /// 地图改变
+    MapChanged {
+        previous: String,
+    },
+    PlayerJoined(String),
+    PlayerLeft(String),
+    /// 服务器已满
+    Full,
+    /// 服务器从满员变为有空位
+    HasSlots,
+    /// 延迟突增，持续突增只报告一次
+    LatencySpike {
+        baseline: Duration,
+    },
+}
+
+pub struct WatchEvent {
+    /// 添加服务器时传入的地址
+    pub address: String,
+    pub kind: WatchEventKind,
+    /// 本次查询到的服务器信息，离线时为空
+    pub info: Option<ServerInfo>,
+}
+
+pub type EventHandler = Arc<dyn Fn(WatchEvent) + Send + Sync>;
+
+/// 服务器监视器，被丢弃时停止所有轮询
+pub struct Watcher {
+    options: Arc<WatchOptions>,
+    handler: EventHandler,
+    history: Option<Arc<History>>,
+    tasks: Mutex<HashMap<String, JoinHandle<()>>>,
+}
+
+impl Watcher {
+    pub fn new(options: WatchOptions, handler: EventHandler) -> Self {
+        let history = options
+            .history_dir
+            .as_ref()
+            .map(|dir| Arc::new(History::new(dir, options.history_capacity)));
+        Self {
+            options: Arc::new(options),
+            handler,
+            history,
+            tasks: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 开始轮询服务器，已经在轮询的服务器会以新的间隔重新开始，需要在 tokio 运行时中调用
+    ///
+    /// @param interval 轮询间隔，为空时使用默认间隔，最小 1 秒
+    /// @returns 是否为新添加的服务器
+    pub fn add(&self, address: &str, interval: Option<Duration>) -> bool {
+        let address = address.trim().to_string();
+        let interval = interval.unwrap_or(self.options.interval).max(MIN_INTERVAL);
+        let task = tokio::spawn(watch_server(
+            address.clone(),
+            interval,
+            self.options.clone(),
+            self.handler.clone(),
+            self.history.clone(),
+        ));
+
+        let previous = self
+            .tasks
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(address, task);
+        if let Some(previous) = &previous {
+            previous.abort();
+        }
+        previous.is_none()
+    }
+
+    /// 停止轮询服务器，已保存的历史不会被删除
+    ///
+    /// @returns 服务器是否在轮询中
+    pub fn remove(&self, address: &str) -> bool {
+        let task = self
+            .tasks
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(address.trim());
+        if let Some(task) = &task {
+            task.abort();
+        }
+        task.is_some()
+    }
+
+    /// 正在轮询的服务器地址
+    pub fn addresses(&self) -> Vec<String> {
+        let mut addresses: Vec<String> = self
+            .tasks
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .keys()
+            .cloned()
+            .collect();
+        addresses.sort();
+        addresses
+    }
+
+    /// 停止所有轮询
+    pub fn stop(&self) {
+        let tasks = std::mem::take(&mut *self.tasks.lock().unwrap_or_else(PoisonError::into_inner));
+        for task in tasks.into_values() {
+            task.abort();
+        }
+    }
+
+    /// 人数历史，未指定保存目录时为空
+    pub fn history(&self) -> Option<&History> {
+        self.history.as_deref()
+    }
+}
+
+impl Drop for Watcher {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// 查询一次服务器，服务器没有玩家时不查询玩家列表
+async fn poll(address: &str, options: &WatchOptions) -> Poll {
+    let query = match query::resolve(address).await {
+        Ok(target) => Query::connect(target, options.timeout).await,
+        Err(error) => Err(error),
+    };
+    let info = match query {
+        Ok(query) => query.info().await.map(|info| (query, info)),
+        Err(error) => Err(error),
+    };
+
+    match info {
+        Ok((query, info)) => {
+            let players = if !options.players {
+                None
+            } else if info.players == 0 {
+                Some(Vec::new())
+            } else {
+                query.players().await.ok()
+            };
+            Poll {
+                result: Ok(info),
+                players,
+            }
+        }
+        Err(error) => Poll {
+            result: Err(error),
+            players: None,
+        },
+    }
+}
+
+/// 按间隔轮询一个服务器直到任务被取消
+async fn watch_server(
+    address: String,
+    interval: Duration,
+    options: Arc<WatchOptions>,
+    handler: EventHandler,
+    history: Option<Arc<History>>,
+) {
+    let mut state = ServerState::default();
+    let mut ticker = tokio::time::interval(interval);
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    loop {
+        ticker.tick().await;
+        let poll = poll(&address, &options).await;
+        let info = poll.result.as_ref().ok().cloned();
+
+        if let Some(history) = &history {
+            let sample = info.as_ref().map_or_else(Sample::offline, |info| {
+                Sample::online(info.players, info.max_players, info.bots)
+            });
+            if let Err(error) = history.record(&address, sample) {
+                steam_log!(
+                    Warn,
+                    "watch",
+                    "failed to record history for {}: {}",
+                    address,
+                    error
+                );
+            }
+        }
+
+        for kind in state.update(poll, &options) {
+            handler(WatchEvent {
+                address: address.clone(),
+                kind,
+                info: info.clone(),
+            });
+        }
+    }
+}
@@ -0,0 +1,443 @@
+//! 比较前后两次查询结果，生成变化事件
+
+use super::{WatchEventKind, WatchOptions};
+use crate::query::message::{Player, ServerInfo};
+use crate::query::Error;
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// 延迟基准的平滑系数，新采样所占的比例
+const LATENCY_SMOOTHING: f64 = 0.2;
+/// 判断延迟突增前至少需要的成功查询次数
+const LATENCY_WARMUP: u32 = 3;
+
+/// 一次查询的结果
+pub struct Poll {
+    pub result: Result<ServerInfo, Error>,
+    /// 玩家列表，未查询或查询失败时为空
+    pub players: Option<Vec<Player>>,
+}
+
+/// 一个服务器上一次的状态，服务器上线时重新建立
+#[derive(Default)]
+pub struct ServerState {
+    /// 是否在线，第一次确定之前为空
+    online: Option<bool>,
+    /// 连续失败的次数
+    failures: u32,
+    map: String,
+    full: bool,
+    /// 每个名字的玩家数量，名字可能重复
+    players: Option<HashMap<String, usize>>,
+    /// 平滑后的延迟（秒）
+    latency: f64,
+    latency_samples: u32,
+    spiking: bool,
+}
+
+impl ServerState {
+    /// 根据新的查询结果更新状态
+    ///
+    /// @returns 按上线、地图、玩家离开、玩家加入、满员、延迟的顺序排列的事件
+    pub fn update(&mut self, poll: Poll, options: &WatchOptions) -> Vec<WatchEventKind> {
+        let info = match poll.result {
+            Ok(info) => info,
+            Err(error) => {
+                self.failures += 1;
+                // 单个 UDP 包丢失很常见，连续失败多次才认为离线
+                if self.failures >= options.offline_after && self.online != Some(false) {
+                    *self = ServerState {
+                        online: Some(false),
+                        failures: self.failures,
+                        ..Default::default()
+                    };
+                    return vec![WatchEventKind::Offline(error)];
+                }
+                return Vec::new();
+            }
+        };
+
+        let mut events = Vec::new();
+        self.failures = 0;
+        let full = info.max_players > 0 && info.players >= info.max_players;
+        let players = poll.players.map(count_players);
+
+        if self.online != Some(true) {
+            // 上线时只建立基准，不报告与离线前的差异
+            *self = ServerState {
+                online: Some(true),
+                map: info.map,
+                full,
+                players,
+                ..Default::default()
+            };
+            events.push(WatchEventKind::Online);
+        } else {
+            if info.map != self.map {
+                let previous = std::mem::replace(&mut self.map, info.map);
+                events.push(WatchEventKind::MapChanged { previous });
+            }
+
+            if let Some(players) = players {
+                if let Some(previous) = &self.players {
+                    diff_players(previous, &players, &mut events);
+                }
+                self.players = Some(players);
+            }
+
+            if full != self.full {
+                self.full = full;
+                events.push(if full {
+                    WatchEventKind::Full
+                } else {
+                    WatchEventKind::HasSlots
+                });
+            }
+        }
+
+        if let Some(event) = self.update_latency(info.latency, options) {
+            events.push(event);
+        }
+        events
+    }
+
+    /// 更新延迟基准，延迟超过基准一定倍数且增加量足够大时报告突增，持续突增只报告一次
+    fn update_latency(
+        &mut self,
+        latency: Duration,
+        options: &WatchOptions,
+    ) -> Option<WatchEventKind> {
+        let latency = latency.as_secs_f64();
+        let baseline = self.latency;
+        let spiking = self.latency_samples >= LATENCY_WARMUP
+            && latency > baseline * options.latency_spike_factor
+            && latency - baseline >= options.latency_spike_min.as_secs_f64();
+
+        self.latency = if self.latency_samples == 0 {
+            latency
+        } else {
+            baseline + (latency - baseline) * LATENCY_SMOOTHING
+        };
+        self.latency_samples = self.latency_samples.saturating_add(1);
+
+        let started = spiking && !self.spiking;
+        self.spiking = spiking;
+        started.then(|| WatchEventKind::LatencySpike {
+            baseline: Duration::from_secs_f64(baseline),
+        })
+    }
+}
+
+/// 按名字统计玩家，连接中的玩家没有名字，不参与比较
+fn count_players(players: Vec<Player>) -> HashMap<String, usize> {
+    let mut counts = HashMap::new();
+    for player in players {
+        if !player.name.is_empty() {
+            *counts.entry(player.name).or_insert(0) += 1;
+        }
+    }
+    counts
+}
+
+fn diff_players(
+    previous: &HashMap<String, usize>,
+    current: &HashMap<String, usize>,
+    events: &mut Vec<WatchEventKind>,
+) {
+    let mut left: Vec<_> = previous
+        .iter()
+        .flat_map(|(name, count)| {
+            let remaining = current.get(name).copied().unwrap_or(0);
+            std::iter::repeat_n(name, count.saturating_sub(remaining))
+        })
+        .collect();
+    let mut joined: Vec<_> = current
+        .iter()
+        .flat_map(|(name, count)| {
+            let before = previous.get(name).copied().unwrap_or(0);
+            std::iter::repeat_n(name, count.saturating_sub(before))
+        })
+        .collect();
+    left.sort();
+    joined.sort();
+
+    events.extend(
+        left.into_iter()
+            .map(|name| WatchEventKind::PlayerLeft(name.clone())),
+    );
+    events.extend(
+        joined
+            .into_iter()
+            .map(|name| WatchEventKind::PlayerJoined(name.clone())),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::query::message::{Environment, ServerType};
+
+    fn info(map: &str, players: u8, max_players: u8, latency: u64) -> ServerInfo {
+        ServerInfo {
+            protocol: 17,
+            name: "My Server".to_string(),
+            map: map.to_string(),
+            folder: "csgo".to_string(),
+            game: "Counter-Strike: Global Offensive".to_string(),
+            app_id: 730,
+            players,
+            max_players,
+            bots: 0,
+            server_type: ServerType::Dedicated,
+            environment: Environment::Linux,
+            password: false,
+            vac: true,
+            version: "1.38.7.9".to_string(),
+            port: None,
+            steam_id: None,
+            source_tv: None,
+            keywords: None,
+            game_id: None,
+            ship: None,
+            goldsrc: false,
+            goldsrc_mod: None,
+            latency: Duration::from_millis(latency),
+        }
+    }
+
+    fn online(info: ServerInfo, names: &[&str]) -> Poll {
+        let players = names
+            .iter()
+            .enumerate()
+            .map(|(index, name)| Player {
+                index: index as u8,
+                name: name.to_string(),
+                score: 0,
+                duration: 0.0,
+                ship: None,
+            })
+            .collect();
+        Poll {
+            result: Ok(info),
+            players: Some(players),
+        }
+    }
+
+    fn offline() -> Poll {
+        Poll {
+            result: Err(Error::Timeout),
+            players: None,
+        }
+    }
+
+    /// 事件的简短描述，便于比较
+    fn names(events: Vec<WatchEventKind>) -> Vec<String> {
+        events
+            .into_iter()
+            .map(|event| match event {
+                WatchEventKind::Online => "online".to_string(),
+                WatchEventKind::Offline(_) => "offline".to_string(),
+                WatchEventKind::MapChanged { previous } => format!("map {}", previous),
+                WatchEventKind::PlayerJoined(name) => format!("+{}", name),
+                WatchEventKind::PlayerLeft(name) => format!("-{}", name),
+                WatchEventKind::Full => "full".to_string(),
+                WatchEventKind::HasSlots => "has slots".to_string(),
+                WatchEventKind::LatencySpike { baseline } => {
+                    format!("spike {}", (baseline.as_secs_f64() * 1000.0).round())
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn reports_offline_after_threshold_and_online_again() {
+        let options = WatchOptions {
+            offline_after: 3,
+            ..Default::default()
+        };
+        let mut state = ServerState::default();
+        assert_eq!(
+            names(state.update(online(info("de_dust2", 1, 10, 20), &["a"]), &options)),
+            ["online"]
+        );
+
+        assert!(state.update(offline(), &options).is_empty());
+        assert!(state.update(offline(), &options).is_empty());
+        assert_eq!(names(state.update(offline(), &options)), ["offline"]);
+        // 已经离线时不再重复报告
+        assert!(state.update(offline(), &options).is_empty());
+
+        // 重新上线只建立基准，不报告离线前后的地图与玩家差异
+        assert_eq!(
+            names(state.update(online(info("de_inferno", 2, 10, 20), &["b", "c"]), &options)),
+            ["online"]
+        );
+        assert!(state
+            .update(online(info("de_inferno", 2, 10, 20), &["b", "c"]), &options)
+            .is_empty());
+    }
+
+    #[test]
+    fn single_failure_resets_after_success() {
+        let options = WatchOptions::default();
+        let mut state = ServerState::default();
+        state.update(online(info("de_dust2", 0, 10, 20), &[]), &options);
+        assert!(state.update(offline(), &options).is_empty());
+        assert!(state
+            .update(online(info("de_dust2", 0, 10, 20), &[]), &options)
+            .is_empty());
+        assert!(state.update(offline(), &options).is_empty());
+        assert_eq!(names(state.update(offline(), &options)), ["offline"]);
+    }
+
+    #[test]
+    fn first_poll_failures_report_offline() {
+        let options = WatchOptions::default();
+        let mut state = ServerState::default();
+        assert!(state.update(offline(), &options).is_empty());
+        assert_eq!(names(state.update(offline(), &options)), ["offline"]);
+    }
+
+    #[test]
+    fn reports_map_change() {
+        let options = WatchOptions::default();
+        let mut state = ServerState::default();
+        state.update(online(info("de_dust2", 0, 10, 20), &[]), &options);
+        assert_eq!(
+            names(state.update(online(info("de_mirage", 0, 10, 20), &[]), &options)),
+            ["map de_dust2"]
+        );
+        assert!(state
+            .update(online(info("de_mirage", 0, 10, 20), &[]), &options)
+            .is_empty());
+    }
+
+    #[test]
+    fn counts_players_with_duplicate_names() {
+        let options = WatchOptions::default();
+        let mut state = ServerState::default();
+        state.update(
+            online(info("de_dust2", 3, 10, 20), &["Player", "Player", "bob"]),
+            &options,
+        );
+
+        // 同名玩家中的一个离开，另一个名字加入两次
+        assert_eq!(
+            names(state.update(
+                online(
+                    info("de_dust2", 4, 10, 20),
+                    &["Player", "bob", "eve", "eve"]
+                ),
+                &options
+            )),
+            ["-Player", "+eve", "+eve"]
+        );
+        // 连接中的玩家没有名字，不报告
+        assert!(state
+            .update(
+                online(
+                    info("de_dust2", 5, 10, 20),
+                    &["eve", "Player", "", "eve", "bob"]
+                ),
+                &options
+            )
+            .is_empty());
+        assert_eq!(
+            names(state.update(
+                online(info("de_dust2", 3, 10, 20), &["Player"; 3]),
+                &options
+            )),
+            ["-bob", "-eve", "-eve", "+Player", "+Player"]
+        );
+    }
+
+    #[test]
+    fn missing_player_list_keeps_previous_players() {
+        let options = WatchOptions::default();
+        let mut state = ServerState::default();
+        state.update(online(info("de_dust2", 1, 10, 20), &["a"]), &options);
+        let poll = Poll {
+            result: Ok(info("de_dust2", 1, 10, 20)),
+            players: None,
+        };
+        assert!(state.update(poll, &options).is_empty());
+        assert_eq!(
+            names(state.update(online(info("de_dust2", 1, 10, 20), &["b"]), &options)),
+            ["-a", "+b"]
+        );
+    }
+
+    #[test]
+    fn reports_full_and_has_slots() {
+        let options = WatchOptions::default();
+        let mut state = ServerState::default();
+        state.update(online(info("de_dust2", 9, 10, 20), &[]), &options);
+        assert_eq!(
+            names(state.update(online(info("de_dust2", 10, 10, 20), &[]), &options)),
+            ["full"]
+        );
+        assert!(state
+            .update(online(info("de_dust2", 10, 10, 20), &[]), &options)
+            .is_empty());
+        assert_eq!(
+            names(state.update(online(info("de_dust2", 8, 10, 20), &[]), &options)),
+            ["has slots"]
+        );
+
+        // 最大人数为 0 的服务器不算满员
+        let mut state = ServerState::default();
+        state.update(online(info("de_dust2", 0, 0, 20), &[]), &options);
+        assert!(state
+            .update(online(info("de_dust2", 0, 0, 20), &[]), &options)
+            .is_empty());
+    }
+
+    #[test]
+    fn full_on_first_poll_is_not_reported() {
+        let options = WatchOptions::default();
+        let mut state = ServerState::default();
+        assert_eq!(
+            names(state.update(online(info("de_dust2", 10, 10, 20), &[]), &options)),
+            ["online"]
+        );
+        assert!(state
+            .update(online(info("de_dust2", 10, 10, 20), &[]), &options)
+            .is_empty());
+    }
+
+    #[test]
+    fn reports_latency_spike_once() {
+        let options = WatchOptions::default();
+        let mut state = ServerState::default();
+        let mut poll =
+            |latency| names(state.update(online(info("m", 0, 10, latency), &[]), &options));
+
+        assert_eq!(poll(50), ["online"]);
+        // 预热完成前不判断突增
+        assert!(poll(500).is_empty());
+        // 基准 50 + (500 - 50) * 0.2 = 140，再平滑为 140 + (40 - 140) * 0.2 = 120
+        assert!(poll(40).is_empty());
+        assert_eq!(poll(400), ["spike 120"]);
+        // 持续突增只报告一次
+        assert!(poll(500).is_empty());
+        assert!(poll(100).is_empty());
+        assert!(poll(100).is_empty());
+    }
+
+    #[test]
+    fn small_latency_increase_is_not_a_spike() {
+        let options = WatchOptions::default();
+        let mut state = ServerState::default();
+        for _ in 0..5 {
+            state.update(online(info("m", 0, 10, 10), &[]), &options);
+        }
+        // 超过基准的倍数，但增加量小于 latency_spike_min
+        assert!(state
+            .update(online(info("m", 0, 10, 90), &[]), &options)
+            .is_empty());
+        assert_eq!(
+            names(state.update(online(info("m", 0, 10, 200), &[]), &options)),
+            ["spike 26"]
+        );
+    }
+}